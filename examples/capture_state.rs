@@ -0,0 +1,112 @@
+//! Capture a Voltr vault's state to a fixture directory.
+//!
+//! ```text
+//! # Reads SOLANA_RPC_URL, defaults to mainnet:
+//! cargo run --example capture_state --features replay -- \
+//!     <VAULT_PUBKEY> <FIXTURE_DIR> <AMOUNT> [deposit|redeem]
+//! ```
+//!
+//! Fetches the vault, its LP mint, asset mint and idle token account and the
+//! Clock sysvar in one call, so all of them are read at the same slot, and
+//! writes them as a `FixtureCache` directory. Then quotes `AMOUNT` (a deposit
+//! unless told otherwise) from the live accounts and again from the fixture
+//! read back from disk; the two must agree.
+//!
+//! RPC only serves current account state, so this captures the vault as it
+//! is now; replay the directory later with the `replay_fixture` example.
+
+use std::collections::HashMap;
+use std::env;
+use std::str::FromStr;
+
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_program::clock::Clock;
+use solana_program::sysvar;
+use solana_pubkey::Pubkey;
+
+use titan_voltr_integration::fixture::FixtureCache;
+use titan_voltr_integration::prelude::*;
+use titan_voltr_integration::replay::ReplaySnapshot;
+
+const DEFAULT_RPC_URL: &str = "https://api.mainnet-beta.solana.com";
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (vault_key, dir, amount, redeem) = match args.as_slice() {
+        [key, dir, amount] => (key, dir, amount, false),
+        [key, dir, amount, side] if side == "deposit" || side == "redeem" => {
+            (key, dir, amount, side == "redeem")
+        }
+        _ => {
+            return Err(
+                "usage: capture_state <VAULT_PUBKEY> <FIXTURE_DIR> <AMOUNT> [deposit|redeem]"
+                    .into(),
+            );
+        }
+    };
+    let vault_key = Pubkey::from_str(vault_key)?;
+    let amount: u64 = amount.parse()?;
+
+    let rpc_url = env::var("SOLANA_RPC_URL").unwrap_or_else(|_| DEFAULT_RPC_URL.to_string());
+    let rpc = RpcClient::new(rpc_url);
+
+    // The vault names the token accounts to capture alongside it.
+    let mut venue = VoltrVaultVenue::new_uninitialized(vault_key);
+    venue.update_state_from_accounts(&[Some(rpc.get_account(&vault_key).await?)])?;
+    let mut pubkeys = venue.get_required_pubkeys_for_update()?;
+    pubkeys.push(sysvar::clock::ID);
+
+    let response = rpc
+        .get_multiple_accounts_with_commitment(&pubkeys, rpc.commitment())
+        .await?;
+    let mut accounts = HashMap::new();
+    for (pubkey, account) in pubkeys.iter().zip(response.value) {
+        let account = account.ok_or_else(|| format!("account {pubkey} does not exist"))?;
+        accounts.insert(*pubkey, account);
+    }
+    let clock: Clock = accounts[&sysvar::clock::ID].deserialize_data()?;
+    let fixture = FixtureCache::new(
+        vault_key,
+        ReplaySnapshot {
+            slot: response.context.slot,
+            timestamp: clock.unix_timestamp.max(0) as u64,
+            accounts,
+        },
+    );
+    fixture.save(dir)?;
+    println!(
+        "captured vault {vault_key} at slot {} (unix time {}) to {dir}",
+        fixture.snapshot().slot,
+        fixture.snapshot().timestamp
+    );
+
+    let live = fixture.venue().await?;
+    let replayed = FixtureCache::load(dir)?.venue().await?;
+    let (asset_mint, lp_mint) = (live.get_token(0)?.pubkey, live.get_token(1)?.pubkey);
+    let (input_mint, output_mint) = if redeem {
+        (lp_mint, asset_mint)
+    } else {
+        (asset_mint, lp_mint)
+    };
+    let request = QuoteRequest {
+        input_mint,
+        output_mint,
+        amount,
+        swap_type: SwapType::ExactIn,
+    };
+    let quote = live.quote(request.clone())?;
+    let replayed_quote = replayed.quote(request)?;
+    println!("\n== Quote ==\n{quote:#?}");
+    if (quote.expected_output, quote.not_enough_liquidity)
+        != (
+            replayed_quote.expected_output,
+            replayed_quote.not_enough_liquidity,
+        )
+    {
+        return Err(format!("the saved fixture quotes differently: {replayed_quote:#?}").into());
+    }
+    println!("the saved fixture reproduces this quote");
+
+    Ok(())
+}
@@ -0,0 +1,146 @@
+//! Decode a Voltr vault account.
+//!
+//! ```text
+//! # Fetch and decode a live vault (reads SOLANA_RPC_URL, defaults to mainnet):
+//! cargo run --example decode_vault -- <VAULT_PUBKEY>
+//!
+//! # Decode a dump pasted by a user, base64 or hex, optionally naming the vault:
+//! cargo run --example decode_vault < dump.txt
+//! cargo run --example decode_vault -- --stdin <VAULT_PUBKEY> < dump.txt
+//! ```
+//!
+//! Prints the parsed vault, the PDAs derived from the vault address, the fee
+//! configuration and the health checks that can be run on what was decoded.
+//! A live vault is also refreshed as a venue, adding its summary and health
+//! report.
+
+use std::env;
+use std::io::Read;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_pubkey::Pubkey;
+use titan_integration_template::account_caching::rpc_cache::RpcClientCache;
+
+use titan_voltr_integration::pda::VaultPdas;
+use titan_voltr_integration::prelude::*;
+
+const DEFAULT_RPC_URL: &str = "https://api.mainnet-beta.solana.com";
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (vault_key, from_stdin) = match args.as_slice() {
+        [] => (None, true),
+        [flag, key] if flag == "--stdin" => (Some(Pubkey::from_str(key)?), true),
+        [key] => (Some(Pubkey::from_str(key)?), false),
+        _ => return Err("usage: decode_vault [--stdin] [VAULT_PUBKEY]".into()),
+    };
+
+    let rpc_url = env::var("SOLANA_RPC_URL").unwrap_or_else(|_| DEFAULT_RPC_URL.to_string());
+    let vault = if from_stdin {
+        let mut input = String::new();
+        std::io::stdin().read_to_string(&mut input)?;
+        decode_dump(&input)?
+    } else {
+        let key = vault_key.expect("a pubkey is given when not reading stdin");
+        let account = RpcClient::new(rpc_url.clone()).get_account(&key).await?;
+        Vault::decode(&account.data)?
+    };
+
+    println!("== Vault ==\n{vault:#?}");
+
+    if let Some(key) = vault_key {
+        println!("\n== Derived PDAs ==");
+        let pdas = VaultPdas::derive(&key, &VOLTR_VAULT_PROGRAM);
+        println!("protocol         {}", pdas.protocol);
+        println!("lp_mint          {}", pdas.lp_mint);
+        println!("lp_mint_auth     {}", pdas.lp_mint_auth);
+        println!("asset_idle_auth  {}", pdas.asset_idle_auth);
+        println!("lp mint matches  {}", vault.lp.mint == pdas.lp_mint);
+    }
+
+    let fees = &vault.fee_configuration;
+    println!("\n== Fees (bps) ==");
+    println!("issuance         {}", fees.issuance_fee);
+    println!("redemption       {}", fees.redemption_fee);
+    println!(
+        "management       {} (manager {}, admin {}, protocol {})",
+        vault.get_total_fee_configuration_management_fee()?,
+        fees.manager_management_fee,
+        fees.admin_management_fee,
+        fees.protocol_management_fee
+    );
+    println!(
+        "performance      {} (manager {}, admin {}, protocol {})",
+        vault.get_total_fee_configuration_performance_fee()?,
+        fees.manager_performance_fee,
+        fees.admin_performance_fee,
+        fees.protocol_performance_fee
+    );
+    println!(
+        "accrued LP fees  {}",
+        vault.get_total_accumulated_lp_fees()?
+    );
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    println!("\n== Vault checks ==");
+    println!(
+        "accounting age   {}s",
+        now.saturating_sub(vault.last_updated_ts)
+    );
+    match vault.get_unlocked_asset_value(now) {
+        Ok(unlocked) => println!("unlocked value   {unlocked} of {}", vault.asset.total_value),
+        Err(_) => println!(
+            "unlocked value   locked profit exceeds total value {}; redeems are rejected",
+            vault.asset.total_value
+        ),
+    }
+    println!(
+        "instant redeems  {}",
+        if vault.vault_configuration.withdrawal_waiting_period == 0 {
+            "available".to_string()
+        } else {
+            format!(
+                "unavailable (waiting period {}s)",
+                vault.vault_configuration.withdrawal_waiting_period
+            )
+        }
+    );
+
+    if let (Some(key), false) = (vault_key, from_stdin) {
+        let cache = RpcClientCache::new(RpcClient::new(rpc_url));
+        let mut venue = VoltrVaultVenue::new_uninitialized(key);
+        match venue.update_state(&cache).await {
+            Ok(()) => {
+                println!("\n== Venue ==\n{}", venue.summary());
+                println!("{:#?}", venue.health_report());
+            }
+            Err(e) => println!("\n== Venue ==\nrefresh failed: {e}"),
+        }
+    }
+
+    Ok(())
+}
+
+/// Decode a base64 or hex account dump, ignoring surrounding whitespace and
+/// an optional `0x` prefix on hex.
+fn decode_dump(input: &str) -> Result<Vault, VaultDecodeError> {
+    let trimmed = input.trim();
+    let hex = trimmed.strip_prefix("0x").unwrap_or(trimmed);
+    match decode_hex(hex) {
+        Some(data) => Vault::decode(&data),
+        None => Vault::from_base64(trimmed),
+    }
+}
+
+fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    if s.is_empty() || !s.len().is_multiple_of(2) || !s.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).ok())
+        .collect()
+}
@@ -0,0 +1,70 @@
+//! Re-run quotes and diagnostics against a captured fixture directory.
+//!
+//! ```text
+//! cargo run --example replay_fixture --features replay -- <FIXTURE_DIR> [AMOUNT ...]
+//! ```
+//!
+//! Loads a directory written by the `capture_state` example, then prints the
+//! venue summary and health report as of the capture, and a detailed deposit
+//! and redeem quote for each `AMOUNT` (one whole token by default), all priced
+//! at the captured Unix time.
+
+use std::env;
+
+use solana_pubkey::Pubkey;
+
+use titan_voltr_integration::fixture::FixtureCache;
+use titan_voltr_integration::prelude::*;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let Some((dir, amounts)) = args.split_first() else {
+        return Err("usage: replay_fixture <FIXTURE_DIR> [AMOUNT ...]".into());
+    };
+    let fixture = FixtureCache::load(dir)?;
+    let venue = fixture.venue().await?;
+    let (asset, lp) = (venue.get_token(0)?, venue.get_token(1)?);
+    let amounts = if amounts.is_empty() {
+        vec![10u64.pow(asset.decimals as u32)]
+    } else {
+        amounts
+            .iter()
+            .map(|amount| amount.parse())
+            .collect::<Result<_, _>>()?
+    };
+
+    let snapshot = fixture.snapshot();
+    println!(
+        "== Fixture ==\nvault {} at slot {} (unix time {}), {} accounts",
+        fixture.vault_key(),
+        snapshot.slot,
+        snapshot.timestamp,
+        snapshot.accounts.len()
+    );
+    println!("\n== Venue ==\n{}", venue.summary());
+    println!("{:#?}", venue.health_report());
+
+    // A user without token accounts, so rent for the output ATA is included.
+    let user = Pubkey::default();
+    for amount in amounts {
+        for (side, input_mint, output_mint) in [
+            ("deposit", asset.pubkey, lp.pubkey),
+            ("redeem", lp.pubkey, asset.pubkey),
+        ] {
+            let request = QuoteRequest {
+                input_mint,
+                output_mint,
+                amount,
+                swap_type: SwapType::ExactIn,
+            };
+            println!("\n== {side} {amount} ==");
+            match venue.quote_detailed(&request, &user, Some(&fixture)).await {
+                Ok(quote) => println!("{quote:#?}"),
+                Err(e) => println!("quote failed: {e}"),
+            }
+        }
+    }
+
+    Ok(())
+}
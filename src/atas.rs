@@ -0,0 +1,125 @@
+//! Per-venue memo of the user ATAs swap instructions reference.
+//!
+//! Each deposit or redeem instruction names the user's asset and LP ATAs,
+//! two PDA searches per build. Settlement flows rebuild instructions for the
+//! same few users over and over, so the venue remembers the ATAs of its most
+//! recently served users.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use solana_pubkey::Pubkey;
+
+use crate::{constants::TOKEN_PROGRAM, pda::VaultPdas, voltr_venue::VoltrVaultVenue};
+
+/// Users whose ATAs a venue remembers; the least recently used is dropped
+/// first.
+pub const ATA_MEMO_CAPACITY: usize = 64;
+
+/// What the ATAs of a user derive from besides the user: asset mint, asset
+/// token program and LP mint.
+type AtaSeeds = [Pubkey; 3];
+
+#[derive(Default)]
+struct AtaMemoEntries {
+    /// Seeds every entry was derived from; entries are dropped when they
+    /// change.
+    seeds: AtaSeeds,
+    /// `(user, (asset ATA, LP ATA))`, most recently used first.
+    users: VecDeque<(Pubkey, (Pubkey, Pubkey))>,
+}
+
+/// Bounded LRU of user ATAs, shared through `&self`.
+#[derive(Default)]
+pub(crate) struct AtaMemo {
+    entries: Mutex<AtaMemoEntries>,
+}
+
+impl Clone for AtaMemo {
+    fn clone(&self) -> Self {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        Self {
+            entries: Mutex::new(AtaMemoEntries {
+                seeds: entries.seeds,
+                users: entries.users.clone(),
+            }),
+        }
+    }
+}
+
+impl AtaMemo {
+    fn get(&self, user: &Pubkey, seeds: &AtaSeeds) -> Option<(Pubkey, Pubkey)> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if entries.seeds != *seeds {
+            return None;
+        }
+        let position = entries.users.iter().position(|(key, _)| key == user)?;
+        let entry = entries.users.remove(position)?;
+        entries.users.push_front(entry);
+        Some(entry.1)
+    }
+
+    fn insert(&self, user: Pubkey, seeds: AtaSeeds, atas: (Pubkey, Pubkey)) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if entries.seeds != seeds {
+            entries.seeds = seeds;
+            entries.users.clear();
+        }
+        if entries.users.iter().any(|(key, _)| *key == user) {
+            return;
+        }
+        entries.users.truncate(ATA_MEMO_CAPACITY - 1);
+        entries.users.push_front((user, atas));
+    }
+
+    fn len(&self) -> usize {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .users
+            .len()
+    }
+}
+
+impl VoltrVaultVenue {
+    /// The user's asset and LP ATAs, remembered per user unless
+    /// `config.disable_ata_memo` is set.
+    pub(crate) fn user_atas(&self, user: &Pubkey, pdas: &VaultPdas) -> (Pubkey, Pubkey) {
+        if self.config().disable_ata_memo {
+            return self.derive_user_atas(user, pdas);
+        }
+        let seeds = [
+            self.vault_state.asset.mint,
+            self.asset_token_program,
+            pdas.lp_mint,
+        ];
+        if let Some(atas) = self.ata_memo.get(user, &seeds) {
+            return atas;
+        }
+        let atas = self.derive_user_atas(user, pdas);
+        self.ata_memo.insert(*user, seeds, atas);
+        atas
+    }
+
+    fn derive_user_atas(&self, user: &Pubkey, pdas: &VaultPdas) -> (Pubkey, Pubkey) {
+        let user_asset_ata =
+            spl_associated_token_account::get_associated_token_address_with_program_id(
+                user,
+                &self.vault_state.asset.mint,
+                &self.asset_token_program,
+            );
+        let user_lp_ata =
+            spl_associated_token_account::get_associated_token_address_with_program_id(
+                user,
+                &pdas.lp_mint,
+                &TOKEN_PROGRAM,
+            );
+        (user_asset_ata, user_lp_ata)
+    }
+
+    /// Users whose ATAs are currently remembered, at most
+    /// `ATA_MEMO_CAPACITY`.
+    pub fn memoized_ata_users(&self) -> usize {
+        self.ata_memo.len()
+    }
+}
@@ -0,0 +1,73 @@
+use solana_pubkey::Pubkey;
+
+use crate::{
+    clock::QuoteClock,
+    config::{MintMetadata, VoltrVenueConfig},
+    state::Vault,
+    voltr_venue::VoltrVaultVenue,
+};
+
+/// Fluent construction of a `VoltrVaultVenue`, from `VoltrVaultVenue::builder()`.
+///
+/// `build()` goes through `new()` or `new_uninitialized()` and `with_config()`,
+/// so a built venue is the same as one assembled from those calls.
+#[derive(Clone, Debug)]
+pub struct VoltrVaultVenueBuilder {
+    vault_key: Pubkey,
+    vault_state: Option<Vault>,
+    config: VoltrVenueConfig,
+}
+
+impl VoltrVaultVenueBuilder {
+    /// Start from an already parsed vault, as `new()` does. Without it the
+    /// first `update_state()` loads the vault, as after `new_uninitialized()`.
+    pub fn vault_state(mut self, vault_state: Vault) -> Self {
+        self.vault_state = Some(vault_state);
+        self
+    }
+
+    /// Replace the whole configuration, including any mint metadata or
+    /// program id set earlier in the chain.
+    pub fn config(mut self, config: VoltrVenueConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Set `config.known_mint_metadata`.
+    pub fn known_mint_metadata(mut self, metadata: MintMetadata) -> Self {
+        self.config.known_mint_metadata = Some(metadata);
+        self
+    }
+
+    /// Set `config.program_id`.
+    pub fn program_id(mut self, program_id: Pubkey) -> Self {
+        self.config.program_id = Some(program_id);
+        self
+    }
+
+    /// Set `config.clock`.
+    pub fn clock(mut self, clock: QuoteClock) -> Self {
+        self.config.clock = clock;
+        self
+    }
+
+    pub fn build(self) -> VoltrVaultVenue {
+        let venue = match self.vault_state {
+            Some(vault_state) => VoltrVaultVenue::new(self.vault_key, vault_state),
+            None => VoltrVaultVenue::new_uninitialized(self.vault_key),
+        };
+        venue.with_config(self.config)
+    }
+}
+
+impl VoltrVaultVenue {
+    /// Builder for a venue on `vault_key`, uninitialized with the default
+    /// configuration unless told otherwise.
+    pub fn builder(vault_key: Pubkey) -> VoltrVaultVenueBuilder {
+        VoltrVaultVenueBuilder {
+            vault_key,
+            vault_state: None,
+            config: VoltrVenueConfig::default(),
+        }
+    }
+}
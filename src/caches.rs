@@ -0,0 +1,120 @@
+//! Minimal `AccountsCache` implementations for feeding known accounts to a
+//! venue from scripts and tests, without an RPC connection.
+//!
+//! `StaticCache` serves a fixed set of accounts; `ClosureCache` asks a
+//! closure, for accounts built or read on demand. Neither awaits anything,
+//! so both work under any async runtime.
+//!
+//! ```
+//! use std::collections::HashMap;
+//!
+//! use solana_account::Account;
+//! use solana_pubkey::Pubkey;
+//! use titan_integration_template::account_caching::AccountsCache;
+//! use titan_voltr_integration::caches::{ClosureCache, StaticCache};
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let key = Pubkey::new_unique();
+//! let account = Account {
+//!     lamports: 1_000_000,
+//!     ..Account::default()
+//! };
+//!
+//! let cache = StaticCache::from_pairs(&[(key, account.clone())]);
+//! assert_eq!(cache.get_account(&key).await.unwrap(), Some(account.clone()));
+//!
+//! let accounts = HashMap::from([(key, account)]);
+//! let cache = ClosureCache::new(move |pubkey: &Pubkey| accounts.get(pubkey).cloned());
+//! let fetched = cache
+//!     .get_accounts(&[key, Pubkey::new_unique()])
+//!     .await
+//!     .unwrap();
+//! assert!(fetched[0].is_some() && fetched[1].is_none());
+//!
+//! // Either can then load a venue: `venue.update_state(&cache).await?`.
+//! # }
+//! ```
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use solana_account::Account;
+use solana_pubkey::Pubkey;
+
+use titan_integration_template::{
+    account_caching::AccountsCache, trading_venue::error::TradingVenueError,
+};
+
+/// `AccountsCache` answering every lookup with `lookup`; accounts it
+/// returns `None` for are missing.
+pub struct ClosureCache<F> {
+    lookup: F,
+}
+
+impl<F> ClosureCache<F>
+where
+    F: Fn(&Pubkey) -> Option<Account> + Send + Sync,
+{
+    pub fn new(lookup: F) -> Self {
+        Self { lookup }
+    }
+}
+
+#[async_trait]
+impl<F> AccountsCache for ClosureCache<F>
+where
+    F: Fn(&Pubkey) -> Option<Account> + Send + Sync,
+{
+    async fn get_account(&self, pubkey: &Pubkey) -> Result<Option<Account>, TradingVenueError> {
+        Ok((self.lookup)(pubkey))
+    }
+
+    async fn get_accounts(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> Result<Vec<Option<Account>>, TradingVenueError> {
+        Ok(pubkeys.iter().map(|pubkey| (self.lookup)(pubkey)).collect())
+    }
+}
+
+/// `AccountsCache` over a fixed set of accounts.
+#[derive(Clone, Debug, Default)]
+pub struct StaticCache {
+    accounts: HashMap<Pubkey, Account>,
+}
+
+impl StaticCache {
+    pub fn from_pairs(pairs: &[(Pubkey, Account)]) -> Self {
+        Self {
+            accounts: pairs.iter().cloned().collect(),
+        }
+    }
+
+    /// Add or replace the account at `pubkey`.
+    pub fn insert(&mut self, pubkey: Pubkey, account: Account) {
+        self.accounts.insert(pubkey, account);
+    }
+
+    /// Make the account at `pubkey` missing.
+    pub fn remove(&mut self, pubkey: &Pubkey) {
+        self.accounts.remove(pubkey);
+    }
+}
+
+#[async_trait]
+impl AccountsCache for StaticCache {
+    async fn get_account(&self, pubkey: &Pubkey) -> Result<Option<Account>, TradingVenueError> {
+        Ok(self.accounts.get(pubkey).cloned())
+    }
+
+    async fn get_accounts(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> Result<Vec<Option<Account>>, TradingVenueError> {
+        Ok(pubkeys
+            .iter()
+            .map(|pubkey| self.accounts.get(pubkey).cloned())
+            .collect())
+    }
+}
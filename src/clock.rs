@@ -0,0 +1,49 @@
+//! Where a venue takes "now" from for its time-dependent terms.
+
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use solana_program::clock::Clock;
+
+/// Source of the unix timestamp quotes, fee estimates and staleness checks
+/// are computed at.
+///
+/// Only management fee accrual, locked-profit degradation and the
+/// accounting age depend on it; everything else is as of the last refresh.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum QuoteClock {
+    /// The local wall clock, falling back to the vault's `last_updated_ts`
+    /// when it is unavailable.
+    #[default]
+    WallClock,
+    /// Always this unix timestamp, for deterministic simulations and tests.
+    Fixed(u64),
+    /// The cluster's `Clock::unix_timestamp` as read at `captured_at`,
+    /// advanced by the local time elapsed since, so quotes follow chain time
+    /// rather than a drifting local clock.
+    SysvarCaptured { ts: u64, captured_at: Instant },
+}
+
+impl QuoteClock {
+    /// Capture `clock`'s unix timestamp now; negative timestamps read as 0.
+    pub fn from_sysvar(clock: &Clock) -> Self {
+        Self::SysvarCaptured {
+            ts: clock.unix_timestamp.max(0) as u64,
+            captured_at: Instant::now(),
+        }
+    }
+
+    /// The clock's current unix timestamp, `None` when it is the wall clock
+    /// and the system time is before the epoch.
+    pub fn now(&self) -> Option<u64> {
+        match *self {
+            Self::WallClock => SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .ok()
+                .map(|d| d.as_secs()),
+            Self::Fixed(ts) => Some(ts),
+            Self::SysvarCaptured { ts, captured_at } => {
+                Some(ts.saturating_add(captured_at.elapsed().as_secs()))
+            }
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use solana_account::Account;
+use solana_program_pack::Pack;
+use solana_pubkey::Pubkey;
+
+use crate::clock::QuoteClock;
+
+/// Static properties of a mint that can be supplied ahead of time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MintMetadata {
+    pub decimals: u8,
+    pub token_program: Pubkey,
+}
+
+impl MintMetadata {
+    /// Minimal initialized mint account carrying these properties.
+    pub(crate) fn to_mint_account(self) -> Account {
+        let mut data = vec![0u8; spl_token::state::Mint::LEN];
+        let mint = spl_token::state::Mint {
+            decimals: self.decimals,
+            is_initialized: true,
+            ..Default::default()
+        };
+        mint.pack_into_slice(&mut data);
+
+        Account {
+            lamports: 0,
+            data,
+            owner: self.token_program,
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+}
+
+/// What quoting does while the vault's idle ATA has a delegate or close
+/// authority; see `HealthReport::idle_ata_anomaly`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IdleAtaAnomalyAction {
+    /// Only report it.
+    #[default]
+    Report,
+    /// Refuse redeems, which pay out of the idle ATA; deposits only pass
+    /// through it.
+    BlockRedeems,
+    /// Refuse swaps in both directions.
+    BlockAll,
+}
+
+/// Optional behaviour of a `VoltrVaultVenue`.
+#[derive(Clone, Debug, Default)]
+pub struct VoltrVenueConfig {
+    /// Asset mint metadata used when the cache has no asset mint account.
+    ///
+    /// There is no LP counterpart: the LP mint supply drives every quote, so
+    /// the LP mint is always fetched.
+    pub known_mint_metadata: Option<MintMetadata>,
+    /// Largest slot difference tolerated between the accounts of one
+    /// slot-aware refresh; 0 requires them all to come from one slot.
+    pub max_slot_span: u64,
+    /// Extra fetches a slot-aware refresh makes before accepting accounts
+    /// that span more than `max_slot_span` slots.
+    pub inconsistent_fetch_retries: u8,
+    /// Largest change in the implied asset-per-LP price, in bps, a refresh
+    /// may show without a locked-profit report before it is flagged as an
+    /// anomaly. `None` disables the check.
+    pub max_share_price_change_bps: Option<u64>,
+    /// Refuse quotes while a share price anomaly is flagged instead of only
+    /// reporting it.
+    pub fail_on_share_price_anomaly: bool,
+    /// Whether quotes and instructions are refused while the idle ATA has a
+    /// delegate or close authority.
+    pub idle_ata_anomaly_action: IdleAtaAnomalyAction,
+    /// Age, in seconds, of the vault's `last_updated_ts` above which its
+    /// `total_value` is treated as stale: refreshes log a warning and the
+    /// health report flags the venue. `None` disables the check.
+    pub max_accounting_age_secs: Option<u64>,
+    /// Safety margin, in bps, taken off redeem outputs while the accounting
+    /// is stale. 0 only warns.
+    pub stale_accounting_haircut_bps: u16,
+    /// Refreshes that must fail in a row before the venue reports
+    /// `VenueStatus::Stale` and flags its quotes `degraded`, still quoting
+    /// from the last good state. 0 never reports it.
+    pub stale_after_failed_refreshes: u32,
+    /// Age, in seconds, of the last good state past which a stale venue
+    /// refuses quotes and swaps. `None` quotes stale state however old.
+    pub max_stale_state_age_secs: Option<u64>,
+    /// Smallest amount of asset, in base units, a swap must move to be
+    /// quoted: a redeem's payout, or a deposit's input net of the issuance
+    /// fee, which is what the LP minted is worth. Smaller non-zero swaps are
+    /// rejected with `LiquidityRejection::BelowMinimumOutput`, since they
+    /// would not cover the transaction fee and any ATA rent. 0 disables the
+    /// check.
+    pub min_output_in_asset_units: u64,
+    /// Vault program deployment the venue targets, e.g. on devnet. Its
+    /// instructions, PDAs and lookup-table keys all derive from it. `None`
+    /// uses `VOLTR_VAULT_PROGRAM`.
+    pub program_id: Option<Pubkey>,
+    /// Derive PDAs on every use instead of through the process-wide cache
+    /// behind `VaultPdas::for_vault()`, for memory-sensitive deployments.
+    pub disable_pda_cache: bool,
+    /// Derive the user's ATAs on every instruction build instead of
+    /// remembering them for the last `ATA_MEMO_CAPACITY` users.
+    pub disable_ata_memo: bool,
+    /// Refuse Token program account data longer than the 165-byte layout,
+    /// as `Account::unpack` does, instead of reading the layout and ignoring
+    /// the rest. The lenient read is for data carrying a Token-2022
+    /// extension tail after the base layout, whose fields sit at the same
+    /// offsets.
+    pub strict_token_account_layout: bool,
+    /// Fail refreshes on a Token-2022 asset mint whose extensions this
+    /// build cannot read, instead of reading its base fields and reporting
+    /// `HealthReport::unknown_mint_extensions`.
+    pub strict_mint_extensions: bool,
+    /// Address lookup table meant to hold this venue's shared keys. When set,
+    /// `get_lookup_table_keys()` given a cache returns only the keys the
+    /// table is missing.
+    pub lookup_table: Option<Pubkey>,
+    /// Shortest interval, in seconds of the venue's clock, between two
+    /// warnings about `quote()` calls refused for the same liquidity reason.
+    /// Every refusal is still counted in `rejection_count()`. `None` never
+    /// warns.
+    pub rejection_warning_interval_secs: Option<u64>,
+    /// How far, in seconds, execution may land from the quote's timestamp;
+    /// `quote_error_bound()` covers the fee accrual and profit unlocking over
+    /// this window either side. 0 trusts the clock exactly.
+    pub quote_error_window_secs: u64,
+    /// Source of the timestamp every time-dependent computation uses; see
+    /// `VoltrVaultVenue::effective_ts()`.
+    pub clock: QuoteClock,
+}
@@ -7,6 +7,9 @@ pub const TOKEN_PROGRAM: Pubkey =
     Pubkey::from_str_const("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
 pub const TOKEN_22_PROGRAM: Pubkey =
     Pubkey::from_str_const("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+/// Mint of wrapped SOL under the Token program.
+pub const NATIVE_MINT: Pubkey =
+    Pubkey::from_str_const("So11111111111111111111111111111111111111112");
 
 pub const PROTOCOL_SEED: &[u8] = b"protocol";
 pub const VAULT_LP_MINT_SEED: &[u8] = b"vault_lp_mint";
@@ -16,6 +19,54 @@ pub const VAULT_ASSET_IDLE_AUTH_SEED: &[u8] = b"vault_asset_idle_auth";
 pub const ATA_PROGRAM: Pubkey =
     Pubkey::from_str_const("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
 
-pub const MAX_FEE_BPS: u16 = 10_000;
-pub const ONE_YEAR_U64: u64 = 365 * 24 * 60 * 60;
-pub const DEAD_WEIGHT: u64 = 1_000;
+pub const ADDRESS_LOOKUP_TABLE_PROGRAM: Pubkey =
+    Pubkey::from_str_const("AddressLookupTab1e1111111111111111111111111");
+
+/// Bytes of lookup table metadata before its first address.
+pub const LOOKUP_TABLE_META_SIZE: usize = 56;
+
+pub use crate::core::{DEAD_WEIGHT, MAX_FEE_BPS, ONE_YEAR_U64};
+
+/// Version of what `quote()` returns. Bumped whenever an output can change
+/// for the same vault state, config and timestamp, so routers know to
+/// re-validate their slippage models. Each version's quotes on a fixed grid
+/// of vaults, outputs, `not_enough_liquidity` and errors, are pinned in
+/// `test_semantics_version` in `tests/test_core.rs`.
+///
+/// - 2: a first deposit minting exactly `DEAD_WEIGHT` LP is rejected as
+///   `not_enough_liquidity`.
+/// - 3: a self-pair of a vault mint quotes an output of 0 instead of
+///   failing with `InvalidMint`.
+pub const QUOTE_SEMANTICS_VERSION: u32 = 3;
+
+/// Maximum age of a vault state passed at construction for the first
+/// `update_state()` to reuse it instead of fetching the vault again.
+pub const PREFETCHED_VAULT_MAX_AGE_SECS: u64 = 5;
+
+/// Compute unit limits of a swap transaction from
+/// `build_transaction_instructions()`, ATA setup and SOL wrapping included.
+///
+/// Measured in LiteSVM, the costliest are a wrapped SOL deposit creating the
+/// LP ATA, about 96k at the median, and a redeem creating the asset ATA,
+/// about 74k. The cost varies with the PDA bump searches of the user's
+/// token accounts; each limit is at least half again the median.
+pub const DEPOSIT_COMPUTE_UNIT_LIMIT: u32 = 160_000;
+pub const REDEEM_COMPUTE_UNIT_LIMIT: u32 = 130_000;
+
+/// Bounds of a `request_heap_frame` size: the default heap and the largest
+/// the runtime grants.
+pub const MIN_HEAP_FRAME_BYTES: u32 = 32 * 1024;
+pub const MAX_HEAP_FRAME_BYTES: u32 = 256 * 1024;
+
+/// Anchor discriminators as listed in the program IDL: the first 8 bytes of
+/// the SHA-256 of `global:<instruction>`, `account:<type>` or
+/// `event:<type>`.
+pub const DEPOSIT_VAULT_DISCRIMINATOR: [u8; 8] = [126, 224, 21, 255, 228, 53, 117, 33];
+pub const INSTANT_WITHDRAW_VAULT_DISCRIMINATOR: [u8; 8] = [221, 56, 115, 168, 128, 220, 235, 245];
+pub const VAULT_ACCOUNT_DISCRIMINATOR: [u8; 8] = [211, 8, 232, 43, 2, 152, 117, 119];
+pub const DEPOSIT_VAULT_EVENT_DISCRIMINATOR: [u8; 8] = [11, 15, 7, 92, 150, 100, 165, 232];
+pub const INSTANT_WITHDRAW_VAULT_EVENT_DISCRIMINATOR: [u8; 8] = [46, 57, 60, 20, 6, 160, 164, 247];
+
+/// Accounts of the `deposit_vault` and `instant_withdraw_vault` instructions.
+pub const DEPOSIT_ACCOUNTS_LEN: usize = 13;
+pub const INSTANT_WITHDRAW_ACCOUNTS_LEN: usize = 12;
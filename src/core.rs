@@ -0,0 +1,557 @@
+//! Pure Voltr vault pricing, usable without `std`.
+//!
+//! Everything here depends only on `core`, so it builds with
+//! `--no-default-features --features core` for targets such as SBF or wasm.
+//! The venue, instruction building and cache integration live in the
+//! `std`-only modules and quote through `quote_snapshot()`.
+
+use core::fmt;
+
+pub use crate::fixed::FRAC_BITS;
+use crate::fixed::U80F48;
+
+pub const MAX_FEE_BPS: u16 = 10_000;
+pub const ONE_YEAR_U64: u64 = 365 * 24 * 60 * 60;
+pub const DEAD_WEIGHT: u64 = 1_000;
+
+/// Arithmetic failure in the pricing math.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CoreError {
+    MathOverflow,
+    DivisionByZero,
+    /// An issuance or redemption fee above `MAX_FEE_BPS`, which the program
+    /// never stores: the vault account was most likely read at the wrong
+    /// offsets.
+    FeeExceedsMax {
+        fee_bps: u16,
+    },
+}
+
+impl fmt::Display for CoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CoreError::MathOverflow => f.write_str("Math Overflow"),
+            CoreError::DivisionByZero => f.write_str("Division By Zero"),
+            CoreError::FeeExceedsMax { fee_bps } => write!(
+                f,
+                "Fee Exceeds Max: {} bps, vault data likely misparsed",
+                fee_bps
+            ),
+        }
+    }
+}
+
+type Result<T> = core::result::Result<T, CoreError>;
+
+fn to_u64(value: u128) -> Result<u64> {
+    u64::try_from(value).map_err(|_| CoreError::MathOverflow)
+}
+
+/// `MAX_FEE_BPS - fee_bps`, the share of an amount a fee leaves.
+fn fee_complement(fee_bps: u16) -> Result<u16> {
+    MAX_FEE_BPS
+        .checked_sub(fee_bps)
+        .ok_or(CoreError::FeeExceedsMax { fee_bps })
+}
+
+/// Direction of a swap through a Voltr vault.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwapDirection {
+    /// Asset -> LP via `deposit_vault`.
+    Deposit,
+    /// LP -> asset via `instant_withdraw_vault`.
+    Redeem,
+}
+
+/// Why a quote was marked `not_enough_liquidity`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LiquidityRejection {
+    /// The deposit would push the vault's total value above `max_cap`.
+    CapExceeded,
+    /// The first deposit mints no more LP tokens than the dead weight, which
+    /// would leave the depositor none.
+    BelowDeadWeight,
+    /// The vault's idle balance cannot cover the redeem payout.
+    IdleBalanceInsufficient,
+    /// The redeem payout exceeds the vault's unlocked value.
+    UnlockedValueExceeded,
+    /// The swap moves less asset than `VoltrVenueConfig::min_output_in_asset_units`,
+    /// too little to be worth a transaction.
+    BelowMinimumOutput,
+}
+
+impl LiquidityRejection {
+    /// Whether a smaller input amount could be accepted.
+    pub fn is_capacity_limit(self) -> bool {
+        !matches!(
+            self,
+            LiquidityRejection::BelowDeadWeight | LiquidityRejection::BelowMinimumOutput
+        )
+    }
+}
+
+impl fmt::Display for LiquidityRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LiquidityRejection::CapExceeded => "deposit exceeds the vault max cap",
+            LiquidityRejection::BelowDeadWeight => "first deposit does not exceed the dead weight",
+            LiquidityRejection::IdleBalanceInsufficient => "redeem exceeds the vault idle balance",
+            LiquidityRejection::UnlockedValueExceeded => "redeem exceeds the vault unlocked value",
+            LiquidityRejection::BelowMinimumOutput => "swap is below the minimum output",
+        })
+    }
+}
+
+/// A swap output before flooring to base units, for consumers doing their
+/// own rounding.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExactOutput {
+    /// Redeem payout in the program's U80F48 fixed point, with `FRAC_BITS`
+    /// fractional bits.
+    Fixed(u128),
+    /// Deposit LP as `numerator / denominator`, dead weight taken.
+    Ratio { numerator: u128, denominator: u128 },
+}
+
+impl ExactOutput {
+    /// No output in `direction`.
+    pub fn zero(direction: SwapDirection) -> Self {
+        match direction {
+            SwapDirection::Deposit => ExactOutput::Ratio {
+                numerator: 0,
+                denominator: 1,
+            },
+            SwapDirection::Redeem => ExactOutput::Fixed(0),
+        }
+    }
+
+    /// The output floored to base units, as the program pays it.
+    pub fn floor(&self) -> Result<u64> {
+        match *self {
+            ExactOutput::Fixed(bits) => to_u64(U80F48(bits).to_floor()),
+            ExactOutput::Ratio {
+                numerator,
+                denominator,
+            } => to_u64(
+                numerator
+                    .checked_div(denominator)
+                    .ok_or(CoreError::DivisionByZero)?,
+            ),
+        }
+    }
+}
+
+/// Calculate LP tokens to mint on the **initial** deposit (when LP supply is 0).
+///
+/// Normalizes the asset `amount` from `from_decimals` to `to_decimals` (LP always 9).
+pub fn calc_init_lp_to_mint(amount: u64, from_decimals: u8, to_decimals: u8) -> Result<u64> {
+    let (numerator, denominator) = init_lp_to_mint_exact(amount, from_decimals, to_decimals)?;
+    to_u64(numerator / denominator)
+}
+
+/// `calc_init_lp_to_mint()` before flooring, as `(numerator, denominator)`.
+fn init_lp_to_mint_exact(amount: u64, from_decimals: u8, to_decimals: u8) -> Result<(u128, u128)> {
+    // Mint decimals are a u8, and 10^39 already overflows a u128.
+    10u128
+        .checked_pow(to_decimals as u32)
+        .and_then(|scale| (amount as u128).checked_mul(scale))
+        .zip(10u128.checked_pow(from_decimals as u32))
+        .ok_or(CoreError::MathOverflow)
+}
+
+/// Calculate LP tokens to mint on a subsequent deposit.
+///
+/// Maintains the ratio: `lp_to_mint / (total_lp + lp_to_mint) = amount_after_fee / (total_asset + amount)`
+///
+/// Formula: `x = (a * (10000 - i) * y) / (10000 * z - a * (10000 - i))`
+/// where a = amount, i = issuance_fee_bps, y = total_lp, z = total_asset + amount
+pub fn calc_deposit_lp_to_mint(
+    amount: u64,
+    total_lp_supply_pre_deposit: u64,
+    total_asset_pre_deposit: u64,
+    issuance_fee_bps: u16,
+) -> Result<u64> {
+    let (numerator, denominator) = calc_deposit_lp_to_mint_exact(
+        amount,
+        total_lp_supply_pre_deposit,
+        total_asset_pre_deposit,
+        issuance_fee_bps,
+    )?;
+    to_u64(numerator / denominator)
+}
+
+/// `calc_deposit_lp_to_mint()` before flooring, as `(numerator,
+/// denominator)` with a positive denominator.
+pub fn calc_deposit_lp_to_mint_exact(
+    amount: u64,
+    total_lp_supply_pre_deposit: u64,
+    total_asset_pre_deposit: u64,
+    issuance_fee_bps: u16,
+) -> Result<(u128, u128)> {
+    let total_asset_post_deposit = total_asset_pre_deposit
+        .checked_add(amount)
+        .ok_or(CoreError::MathOverflow)? as u128;
+
+    let fee_adjusted = fee_complement(issuance_fee_bps)? as u128;
+
+    let numerator = (amount as u128)
+        .checked_mul(total_lp_supply_pre_deposit as u128)
+        .and_then(|v| v.checked_mul(fee_adjusted))
+        .ok_or(CoreError::MathOverflow)?;
+
+    let denominator = total_asset_post_deposit
+        .checked_mul(MAX_FEE_BPS as u128)
+        .and_then(|v| v.checked_sub((amount as u128).checked_mul(fee_adjusted)?))
+        .ok_or(CoreError::MathOverflow)?;
+
+    if denominator == 0 {
+        return Err(CoreError::DivisionByZero);
+    }
+
+    Ok((numerator, denominator))
+}
+
+/// `amount` less a fee of `fee_bps`, floored: the asset a deposit credits
+/// the vault after its issuance fee.
+pub fn calc_amount_after_fee(amount: u64, fee_bps: u16) -> Result<u64> {
+    let after_fee = amount as u128 * fee_complement(fee_bps)? as u128 / MAX_FEE_BPS as u128;
+    to_u64(after_fee)
+}
+
+/// Calculate the management fee in asset terms for a given time period.
+pub fn calc_management_fee_amount_in_asset(
+    time_elapsed: u64,
+    total_asset_value: u64,
+    management_fee_bps: u16,
+) -> Result<u64> {
+    let divisor = (MAX_FEE_BPS as u64)
+        .checked_mul(ONE_YEAR_U64)
+        .ok_or(CoreError::MathOverflow)? as u128;
+
+    let fee_amount = (total_asset_value as u128)
+        .checked_mul(time_elapsed as u128)
+        .and_then(|v| v.checked_mul(management_fee_bps as u128))
+        .and_then(|v| {
+            v.checked_add(divisor.saturating_sub(1))
+                .and_then(|v| v.checked_div(divisor))
+        })
+        .ok_or(CoreError::MathOverflow)?;
+
+    to_u64(fee_amount)
+}
+
+/// Calculate asset tokens to redeem for a given LP burn amount.
+///
+/// Replicates the on-chain U80F48 fixed-point arithmetic:
+///   Decimal::from_num(lp).full_mul_int_ratio(asset, supply)
+///       .full_mul_int_ratio(MAX_FEE_BPS - fee, MAX_FEE_BPS).to_floor()
+pub fn calc_withdraw_asset_to_redeem(
+    amount_lp_to_burn: u64,
+    total_lp_supply_pre_withdraw: u64,
+    total_unlocked_asset: u64,
+    redemption_fee_bps: u16,
+) -> Result<u64> {
+    let bits = calc_withdraw_asset_to_redeem_fixed(
+        amount_lp_to_burn,
+        total_lp_supply_pre_withdraw,
+        total_unlocked_asset,
+        redemption_fee_bps,
+    )?;
+    to_u64(U80F48(bits).to_floor())
+}
+
+/// `calc_withdraw_asset_to_redeem()` before `to_floor()`: the U80F48 bits,
+/// with `FRAC_BITS` fractional bits.
+pub fn calc_withdraw_asset_to_redeem_fixed(
+    amount_lp_to_burn: u64,
+    total_lp_supply_pre_withdraw: u64,
+    total_unlocked_asset: u64,
+    redemption_fee_bps: u16,
+) -> Result<u128> {
+    if total_lp_supply_pre_withdraw == 0 {
+        return Err(CoreError::DivisionByZero);
+    }
+
+    let fee_adjusted = fee_complement(redemption_fee_bps)?;
+    let asset = U80F48::from_int(amount_lp_to_burn)
+        .mul_int_ratio(total_unlocked_asset, total_lp_supply_pre_withdraw)?
+        .mul_int_ratio(fee_adjusted as u64, MAX_FEE_BPS as u64)?;
+    Ok(asset.to_bits())
+}
+
+/// Calculate LP tokens to mint for accumulated fees.
+///
+/// `lp_to_mint = (fee_amount * total_lp_supply) / (total_assets - fee_amount)`
+pub fn calc_fee_lp_to_mint(
+    fee_amount: u64,
+    total_lp_supply_pre_fee: u64,
+    total_asset_post_fee: u64,
+) -> Result<u64> {
+    let denominator = (total_asset_post_fee as u128)
+        .checked_sub(fee_amount as u128)
+        .ok_or(CoreError::MathOverflow)?;
+
+    if denominator == 0 {
+        return Err(CoreError::DivisionByZero);
+    }
+
+    let numerator = (fee_amount as u128)
+        .checked_mul(total_lp_supply_pre_fee as u128)
+        .ok_or(CoreError::MathOverflow)?;
+
+    let lp_to_mint = numerator
+        .checked_add(denominator.saturating_sub(1))
+        .and_then(|v| v.checked_div(denominator))
+        .ok_or(CoreError::DivisionByZero)?;
+
+    to_u64(lp_to_mint)
+}
+
+/// Profit from the last report still locked at `current_ts`, degrading
+/// linearly over `degradation_duration` seconds.
+pub fn calc_locked_profit(
+    last_updated_locked_profit: u64,
+    last_report: u64,
+    degradation_duration: u64,
+    current_ts: u64,
+) -> Result<u64> {
+    // A clock behind the report counts as no time elapsed: fully locked.
+    let duration = current_ts.saturating_sub(last_report) as u128;
+    let degradation_duration = degradation_duration as u128;
+
+    // Zero duration: profit unlocks the moment it is reported.
+    if degradation_duration == 0 {
+        return Ok(0);
+    }
+    // Past the degradation window everything has unlocked. This includes
+    // `last_report == 0` (never reported), whose elapsed time since the
+    // epoch exceeds any real window; such a vault has no reported profit
+    // to lock.
+    if duration > degradation_duration {
+        return Ok(0);
+    }
+
+    let locked_profit = (last_updated_locked_profit as u128)
+        .checked_mul(degradation_duration.saturating_sub(duration))
+        .and_then(|v| v.checked_div(degradation_duration))
+        .ok_or(CoreError::MathOverflow)?;
+
+    to_u64(locked_profit)
+}
+
+/// The vault figures a quote depends on, free of account and token types.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct VaultSnapshot {
+    pub total_value: u64,
+    /// LP mint supply, excluding accumulated fee LP and dead weight.
+    pub lp_supply: u64,
+    /// Fee LP accrued by all parties but not yet minted.
+    pub accumulated_lp_fees: u64,
+    pub dead_weight: u64,
+    pub idle_balance: u64,
+    /// 0 when uncapped.
+    pub max_cap: u64,
+    pub issuance_fee_bps: u16,
+    pub redemption_fee_bps: u16,
+    /// Management fee of all parties combined.
+    pub management_fee_bps: u16,
+    pub last_management_fee_update_ts: u64,
+    pub locked_profit_degradation_duration: u64,
+    pub last_updated_locked_profit: u64,
+    pub last_report: u64,
+    pub asset_decimals: u8,
+    pub lp_decimals: u8,
+}
+
+impl VaultSnapshot {
+    /// LP supply including accumulated fee LP and the dead weight.
+    pub fn total_lp_supply_incl_fees(&self) -> Result<u64> {
+        self.accumulated_lp_fees
+            .checked_add(self.lp_supply)
+            .and_then(|s| s.checked_add(self.dead_weight))
+            .ok_or(CoreError::MathOverflow)
+    }
+
+    /// Total value minus the profit still locked at `current_ts`; fails with
+    /// `MathOverflow` when more profit is locked than the vault holds.
+    pub fn unlocked_value(&self, current_ts: u64) -> Result<u64> {
+        let locked_profit = calc_locked_profit(
+            self.last_updated_locked_profit,
+            self.last_report,
+            self.locked_profit_degradation_duration,
+            current_ts,
+        )?;
+        self.total_value
+            .checked_sub(locked_profit)
+            .ok_or(CoreError::MathOverflow)
+    }
+
+    /// Management fee LP of all parties accrued since the last fee update,
+    /// estimated to `current_ts`.
+    pub fn pending_management_fee_lp(&self, current_ts: u64) -> Result<u64> {
+        self.management_fee_lp(current_ts, self.total_lp_supply_incl_fees()?)
+    }
+
+    /// Estimate management-fee LP tokens that would be minted at `current_ts`.
+    ///
+    /// Mirrors the program's order: the fee accrues first, priced against
+    /// the whole `total_value`, locked profit included, and the swap is then
+    /// priced against the diluted supply, a redeem using the unlocked value.
+    /// Checked against the program with both pending in
+    /// `test_fee_locked_profit` in `tests/test_program.rs`.
+    fn management_fee_lp(&self, current_ts: u64, total_lp_supply_incl_fees: u64) -> Result<u64> {
+        // Zero timestamp: the vault predates fee updates or has never been
+        // cranked, so there is no accrual start; charging from the epoch
+        // would invent decades of fees. Nothing accrues until the first
+        // crank stamps it.
+        if self.last_management_fee_update_ts == 0 {
+            return Ok(0);
+        }
+        // No fee configured, or nothing to charge it on.
+        if self.management_fee_bps == 0 || self.total_value == 0 {
+            return Ok(0);
+        }
+
+        // Zero elapsed also covers a clock behind the last update.
+        let time_elapsed = current_ts.saturating_sub(self.last_management_fee_update_ts);
+        if time_elapsed == 0 {
+            return Ok(0);
+        }
+
+        let fee_amount_in_asset = calc_management_fee_amount_in_asset(
+            time_elapsed,
+            self.total_value,
+            self.management_fee_bps,
+        )?;
+
+        // A fee that rounds to nothing mints nothing; one that would consume
+        // the whole vault has no finite LP price, so none is projected.
+        if fee_amount_in_asset == 0 || fee_amount_in_asset >= self.total_value {
+            return Ok(0);
+        }
+
+        calc_fee_lp_to_mint(
+            fee_amount_in_asset,
+            total_lp_supply_incl_fees,
+            self.total_value,
+        )
+    }
+}
+
+/// Output of swapping `amount` in `direction` at unix time `current_ts`, with
+/// the reason when the vault cannot fill it (the output is then 0).
+///
+/// Pending management fees and locked-profit degradation are projected to
+/// `current_ts`. Instant redeems require a zero withdrawal waiting period;
+/// that is for the caller to check.
+pub fn quote_snapshot(
+    snapshot: &VaultSnapshot,
+    direction: SwapDirection,
+    amount: u64,
+    current_ts: u64,
+) -> Result<(u64, Option<LiquidityRejection>)> {
+    let (output, rejection) = quote_snapshot_exact(snapshot, direction, amount, current_ts)?;
+    Ok((output.floor()?, rejection))
+}
+
+/// `quote_snapshot()` before flooring the output.
+pub fn quote_snapshot_exact(
+    snapshot: &VaultSnapshot,
+    direction: SwapDirection,
+    amount: u64,
+    current_ts: u64,
+) -> Result<(ExactOutput, Option<LiquidityRejection>)> {
+    let rejected = |rejection| Ok((ExactOutput::zero(direction), Some(rejection)));
+    if amount == 0 {
+        return Ok((ExactOutput::zero(direction), None));
+    }
+
+    let total_asset_value = snapshot.total_value;
+    let total_lp_supply_incl_fees = snapshot.total_lp_supply_incl_fees()?;
+    let mgmt_fee_lp = snapshot.management_fee_lp(current_ts, total_lp_supply_incl_fees)?;
+    let total_lp_supply_after_mgmt_fee = total_lp_supply_incl_fees
+        .checked_add(mgmt_fee_lp)
+        .ok_or(CoreError::MathOverflow)?;
+
+    if direction == SwapDirection::Redeem {
+        // A loss cranked into `total_value` after a profit report can leave
+        // more profit locked than the vault holds. Nothing is redeemable
+        // until degradation catches up; deposits do not read unlocked value.
+        let locked_profit = calc_locked_profit(
+            snapshot.last_updated_locked_profit,
+            snapshot.last_report,
+            snapshot.locked_profit_degradation_duration,
+            current_ts,
+        )?;
+        if locked_profit > total_asset_value {
+            return rejected(LiquidityRejection::UnlockedValueExceeded);
+        }
+        let total_unlocked_asset = total_asset_value - locked_profit;
+        let bits = calc_withdraw_asset_to_redeem_fixed(
+            amount,
+            total_lp_supply_after_mgmt_fee,
+            total_unlocked_asset,
+            snapshot.redemption_fee_bps,
+        )?;
+        let asset_to_redeem = to_u64(U80F48(bits).to_floor())?;
+
+        // Never advertise more than the vault's unlocked value, whatever the
+        // idle balance says; exceeding it points at bad supply accounting or
+        // an LP amount larger than the supply. Checked first so that a payout
+        // the vault is worth but cannot pay instantly, with the rest deployed
+        // to strategies, is reported as limited by idle liquidity.
+        if total_unlocked_asset < asset_to_redeem {
+            return rejected(LiquidityRejection::UnlockedValueExceeded);
+        }
+        if snapshot.idle_balance < asset_to_redeem {
+            return rejected(LiquidityRejection::IdleBalanceInsufficient);
+        }
+        return Ok((ExactOutput::Fixed(bits), None));
+    }
+
+    // Enforce vault max cap: if max_cap > 0, the deposit must not push
+    // total asset value above the configured ceiling. Fee accrual mints
+    // LP and locked profit only hides value from redeems; neither moves
+    // `asset.total_value`, so the check is the same before and after
+    // the program accrues pending fees. Checked against the program in
+    // `test_differential` in `tests/test_program.rs`.
+    if snapshot.max_cap > 0 && total_asset_value.saturating_add(amount) > snapshot.max_cap {
+        return rejected(LiquidityRejection::CapExceeded);
+    }
+
+    let (numerator, denominator) = if total_lp_supply_incl_fees == 0 {
+        init_lp_to_mint_exact(amount, snapshot.asset_decimals, snapshot.lp_decimals)?
+    } else {
+        calc_deposit_lp_to_mint_exact(
+            amount,
+            total_lp_supply_after_mgmt_fee,
+            total_asset_value,
+            snapshot.issuance_fee_bps,
+        )?
+    };
+    let lp_before_deadweight = to_u64(numerator / denominator)?;
+
+    // Minting exactly the dead weight would leave the depositor nothing:
+    // rejected like any other deposit too small to mint.
+    if snapshot.dead_weight == 0 {
+        if lp_before_deadweight <= DEAD_WEIGHT {
+            return rejected(LiquidityRejection::BelowDeadWeight);
+        }
+        // Above the dead weight, so the subtraction cannot underflow.
+        let numerator = numerator - DEAD_WEIGHT as u128 * denominator;
+        return Ok((
+            ExactOutput::Ratio {
+                numerator,
+                denominator,
+            },
+            None,
+        ));
+    }
+    Ok((
+        ExactOutput::Ratio {
+            numerator,
+            denominator,
+        },
+        None,
+    ))
+}
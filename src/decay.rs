@@ -0,0 +1,52 @@
+use titan_integration_template::trading_venue::{error::TradingVenueError, QuoteRequest};
+
+use crate::voltr_venue::VoltrVaultVenue;
+
+/// How much a quote moves if it executes `horizon_secs` after now on the
+/// same loaded state.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QuoteDecay {
+    pub horizon_secs: u64,
+    pub output_now: u64,
+    pub output_at_horizon: u64,
+    /// `|output_at_horizon - output_now|`.
+    pub absolute_change: u64,
+    /// `absolute_change` relative to `output_now`, zero when `output_now` is.
+    pub change_bps: u64,
+}
+
+impl VoltrVaultVenue {
+    /// Estimate how fast a quote for `request` goes stale.
+    ///
+    /// Quotes `request` now and `horizon_secs` later with `quote_at()`, so
+    /// only the time-driven terms move: management fee accrual and
+    /// locked-profit degradation. A vault with neither quotes the same at
+    /// any horizon. Routers can use the result to order refreshes and size
+    /// slippage buffers per venue.
+    pub fn quote_decay_estimate(
+        &self,
+        request: &QuoteRequest,
+        horizon_secs: u64,
+    ) -> Result<QuoteDecay, TradingVenueError> {
+        let now = self.effective_ts();
+        let output_now = self.quote_at(request, now)?.expected_output;
+        let output_at_horizon = self
+            .quote_at(request, now.saturating_add(horizon_secs))?
+            .expected_output;
+
+        let absolute_change = output_now.abs_diff(output_at_horizon);
+        let change_bps = if output_now == 0 {
+            0
+        } else {
+            (absolute_change as u128 * 10_000 / output_now as u128) as u64
+        };
+
+        Ok(QuoteDecay {
+            horizon_secs,
+            output_now,
+            output_at_horizon,
+            absolute_change,
+            change_bps,
+        })
+    }
+}
@@ -0,0 +1,156 @@
+//! Everything about a swap that depends on its direction, resolved once per
+//! call: the user's mints and token programs, the fee charged, the room
+//! under the max cap and the size of the swap instruction, as well as the
+//! shared accounts it write-locks.
+
+use solana_instruction::AccountMeta;
+use solana_pubkey::Pubkey;
+use titan_integration_template::trading_venue::{error::TradingVenueError, QuoteRequest};
+
+use crate::{
+    constants::{DEPOSIT_ACCOUNTS_LEN, INSTANT_WITHDRAW_ACCOUNTS_LEN, TOKEN_PROGRAM},
+    pda::VaultPdas,
+    voltr_venue::{SwapDirection, VoltrVaultVenue},
+};
+
+/// A swap direction on one vault, with what the vault's state makes of it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DirectionInfo {
+    pub direction: SwapDirection,
+    /// Mint of the token the user pays in: the asset of a deposit, the LP
+    /// mint of a redeem.
+    pub input_mint: Pubkey,
+    pub input_token_program: Pubkey,
+    /// Mint of the token the user receives.
+    pub output_mint: Pubkey,
+    pub output_token_program: Pubkey,
+    /// Issuance fee of a deposit or redemption fee of a redeem, in bps.
+    pub fee_bps: u16,
+    /// Room left under the max cap for a deposit; `None` for redeems and
+    /// uncapped vaults.
+    pub capacity_limit: Option<u64>,
+    /// Largest input advertised in `get_token_info()`, as of the last
+    /// refresh.
+    pub max_input: u64,
+    pub compute_unit_limit: u32,
+    /// Accounts of the swap instruction.
+    pub accounts_len: usize,
+}
+
+impl DirectionInfo {
+    /// The user's ATA debited by the swap.
+    pub fn input_account(&self, user: &Pubkey) -> Pubkey {
+        spl_associated_token_account::get_associated_token_address_with_program_id(
+            user,
+            &self.input_mint,
+            &self.input_token_program,
+        )
+    }
+
+    /// The user's ATA credited by the swap.
+    pub fn output_account(&self, user: &Pubkey) -> Pubkey {
+        spl_associated_token_account::get_associated_token_address_with_program_id(
+            user,
+            &self.output_mint,
+            &self.output_token_program,
+        )
+    }
+}
+
+impl VoltrVaultVenue {
+    /// What a swap in `direction` involves on this vault.
+    ///
+    /// The LP mint is the vault's PDA, as in the swap instructions.
+    pub fn direction_info(&self, direction: SwapDirection) -> DirectionInfo {
+        self.direction_info_with_pdas(direction, &self.pdas())
+    }
+
+    /// `direction_info()` of the direction of `request`, rejecting unrelated
+    /// mint pairs.
+    pub fn direction_info_for(
+        &self,
+        request: &QuoteRequest,
+    ) -> Result<DirectionInfo, TradingVenueError> {
+        Ok(self.direction_info(self.direction_for(request)?))
+    }
+
+    /// `direction_info()` with the vault's PDAs already derived.
+    pub(crate) fn direction_info_with_pdas(
+        &self,
+        direction: SwapDirection,
+        pdas: &VaultPdas,
+    ) -> DirectionInfo {
+        let asset = (self.vault_state.asset.mint, self.asset_token_program);
+        let lp = (pdas.lp_mint, TOKEN_PROGRAM);
+        let fees = &self.vault_state.fee_configuration;
+        let max_cap = self.vault_state.vault_configuration.max_cap;
+
+        let ((input_mint, input_token_program), (output_mint, output_token_program)) =
+            match direction {
+                SwapDirection::Deposit => (asset, lp),
+                SwapDirection::Redeem => (lp, asset),
+            };
+        let (fee_bps, capacity_limit, accounts_len) = match direction {
+            SwapDirection::Deposit => (
+                fees.issuance_fee,
+                (max_cap > 0)
+                    .then(|| max_cap.saturating_sub(self.vault_state.get_total_asset_value())),
+                DEPOSIT_ACCOUNTS_LEN,
+            ),
+            SwapDirection::Redeem => (fees.redemption_fee, None, INSTANT_WITHDRAW_ACCOUNTS_LEN),
+        };
+
+        DirectionInfo {
+            direction,
+            input_mint,
+            input_token_program,
+            output_mint,
+            output_token_program,
+            fee_bps,
+            capacity_limit,
+            max_input: self.max_input_amount(direction),
+            compute_unit_limit: direction.default_compute_unit_limit(),
+            accounts_len,
+        }
+    }
+
+    /// Writable accounts other than the user's that a swap in `direction`
+    /// locks, in instruction order: where swaps on this vault contend for
+    /// write locks.
+    ///
+    /// Read off the account metas the swap instructions are built from.
+    pub fn write_locked_accounts(&self, direction: SwapDirection) -> Vec<Pubkey> {
+        // The user and their ATAs are passed as the zero key, which only the
+        // readonly system program shares.
+        let user = Pubkey::default();
+        let pdas = self.pdas();
+        let metas: Vec<AccountMeta> = match direction {
+            SwapDirection::Deposit => self
+                .deposit_account_metas(&user, &user, &user, &pdas)
+                .into(),
+            SwapDirection::Redeem => self
+                .instant_withdraw_account_metas(&user, &user, &user, &pdas)
+                .into(),
+        };
+        metas
+            .into_iter()
+            .filter(|meta| meta.is_writable && meta.pubkey != user)
+            .map(|meta| meta.pubkey)
+            .collect()
+    }
+
+    /// Whether a swap in `direction` on this vault and one in
+    /// `other_direction` on `other` write-lock a common account, so cannot
+    /// be scheduled in parallel. Always the case on the same vault.
+    pub fn conflicts_with(
+        &self,
+        direction: SwapDirection,
+        other: &VoltrVaultVenue,
+        other_direction: SwapDirection,
+    ) -> bool {
+        let other_locks = other.write_locked_accounts(other_direction);
+        self.write_locked_accounts(direction)
+            .iter()
+            .any(|key| other_locks.contains(key))
+    }
+}
@@ -0,0 +1,310 @@
+//! Finding every vault of a program deployment and building venues for them.
+//!
+//! Accounts that do not become venues are reported with a [`SkipReason`]
+//! rather than dropped, so gaps in onboarding stay visible.
+
+use std::collections::HashSet;
+
+use solana_account::Account;
+use solana_account_decoder_client_types::UiAccountEncoding;
+use solana_client::{
+    nonblocking::rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use solana_pubkey::Pubkey;
+
+use titan_integration_template::trading_venue::error::TradingVenueError;
+
+use crate::{
+    config::VoltrVenueConfig,
+    constants::VOLTR_VAULT_PROGRAM,
+    errors::VaultDecodeError,
+    state::{vault_discriminator, Vault},
+    voltr_venue::VoltrVaultVenue,
+};
+
+/// Why discovery did not build a venue for an account.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SkipReason {
+    /// The account is not a vault this crate can read.
+    ParseError(VaultDecodeError),
+    /// The account holds no lamports or no data.
+    Closed,
+    /// The vault's asset is not in `DiscoveryOptions::asset_mints`.
+    FilteredByAsset,
+    /// The vault is on the denylist, or an allowlist is set without it.
+    Excluded,
+}
+
+/// Which vaults discovery turns into venues, and how they are configured.
+#[derive(Clone, Debug, Default)]
+pub struct DiscoveryOptions {
+    /// Configuration given to every venue. Its `program_id` is also the
+    /// program searched.
+    pub config: VoltrVenueConfig,
+    /// Only build venues for vaults of these assets; `None` accepts all.
+    pub asset_mints: Option<HashSet<Pubkey>>,
+    /// Only build venues for these vaults; `None` accepts all.
+    pub allowlist: Option<HashSet<Pubkey>>,
+    /// Never build venues for these vaults, e.g. ones known to misbehave.
+    pub denylist: HashSet<Pubkey>,
+}
+
+/// Venues built by discovery and the accounts it skipped.
+///
+/// Venues are constructed from the vault account only; call
+/// `update_state()` on them before quoting.
+#[derive(Clone, Default)]
+pub struct DiscoveryReport {
+    pub venues: Vec<VoltrVaultVenue>,
+    pub skipped: Vec<(Pubkey, SkipReason)>,
+}
+
+/// Build venues from program accounts already at hand, classifying every
+/// account that does not become one.
+pub fn discover_from_accounts(
+    accounts: impl IntoIterator<Item = (Pubkey, Account)>,
+    options: &DiscoveryOptions,
+) -> DiscoveryReport {
+    collect_report(
+        accounts
+            .into_iter()
+            .map(|(key, account)| (key, venue_or_skip(key, &account, options))),
+    )
+}
+
+/// `discover_from_accounts()` with the accounts parsed across a pool of
+/// `threads` worker threads (0 for one per CPU), for dumps of thousands of
+/// vaults. Venues and skipped accounts keep the order of `accounts`, so the
+/// report is the same as the sequential one.
+#[cfg(feature = "rayon")]
+pub fn discover_from_accounts_parallel(
+    accounts: Vec<(Pubkey, Account)>,
+    options: &DiscoveryOptions,
+    threads: usize,
+) -> Result<DiscoveryReport, TradingVenueError> {
+    use rayon::prelude::*;
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .map_err(|e| TradingVenueError::AmmMethodError(e.to_string().into()))?;
+    let classified: Vec<_> = pool.install(|| {
+        accounts
+            .into_par_iter()
+            .map(|(key, account)| (key, venue_or_skip(key, &account, options)))
+            .collect()
+    });
+    Ok(collect_report(classified))
+}
+
+fn venue_or_skip(
+    key: Pubkey,
+    account: &Account,
+    options: &DiscoveryOptions,
+) -> Result<VoltrVaultVenue, SkipReason> {
+    classify(&key, account, options)
+        .map(|vault| VoltrVaultVenue::new(key, vault).with_config(options.config.clone()))
+}
+
+fn collect_report(
+    classified: impl IntoIterator<Item = (Pubkey, Result<VoltrVaultVenue, SkipReason>)>,
+) -> DiscoveryReport {
+    let mut report = DiscoveryReport::default();
+    for (key, venue) in classified {
+        match venue {
+            Ok(venue) => report.venues.push(venue),
+            Err(reason) => {
+                log::debug!("Skipping Voltr account {}: {:?}", key, reason);
+                report.skipped.push((key, reason));
+            }
+        }
+    }
+    if !report.skipped.is_empty() {
+        log::info!(
+            "Voltr discovery built {} venues, skipped {} accounts",
+            report.venues.len(),
+            report.skipped.len()
+        );
+    }
+    report
+}
+
+fn classify(
+    key: &Pubkey,
+    account: &Account,
+    options: &DiscoveryOptions,
+) -> Result<Vault, SkipReason> {
+    if options.denylist.contains(key)
+        || options
+            .allowlist
+            .as_ref()
+            .is_some_and(|allowed| !allowed.contains(key))
+    {
+        return Err(SkipReason::Excluded);
+    }
+    if account.lamports == 0 || account.data.is_empty() {
+        return Err(SkipReason::Closed);
+    }
+    let vault = Vault::decode(&account.data).map_err(SkipReason::ParseError)?;
+    if options
+        .asset_mints
+        .as_ref()
+        .is_some_and(|mints| !mints.contains(&vault.asset.mint))
+    {
+        return Err(SkipReason::FilteredByAsset);
+    }
+    Ok(vault)
+}
+
+/// Fetch every account of the configured program carrying the vault
+/// discriminator and build venues for them. See [`discover_from_accounts`].
+pub async fn find_all_vaults(
+    rpc: &RpcClient,
+    options: &DiscoveryOptions,
+) -> Result<DiscoveryReport, TradingVenueError> {
+    let accounts = fetch_vault_accounts(rpc, options).await?;
+    Ok(discover_from_accounts(accounts, options))
+}
+
+/// `discover_from_accounts()` for the accounts not in `last_seen` only.
+pub fn discover_created_since(
+    accounts: impl IntoIterator<Item = (Pubkey, Account)>,
+    options: &DiscoveryOptions,
+    last_seen: &HashSet<Pubkey>,
+) -> DiscoveryReport {
+    discover_from_accounts(
+        accounts
+            .into_iter()
+            .filter(|(key, _)| !last_seen.contains(key)),
+        options,
+    )
+}
+
+/// `find_all_vaults()` reporting only the accounts not in `last_seen`, for
+/// incremental discovery runs.
+pub async fn find_vaults_created_since(
+    rpc: &RpcClient,
+    options: &DiscoveryOptions,
+    last_seen: &HashSet<Pubkey>,
+) -> Result<DiscoveryReport, TradingVenueError> {
+    let accounts = fetch_vault_accounts(rpc, options).await?;
+    Ok(discover_created_since(accounts, options, last_seen))
+}
+
+async fn fetch_vault_accounts(
+    rpc: &RpcClient,
+    options: &DiscoveryOptions,
+) -> Result<Vec<(Pubkey, Account)>, TradingVenueError> {
+    let program_id = options.config.program_id.unwrap_or(VOLTR_VAULT_PROGRAM);
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+            0,
+            vault_discriminator().to_vec(),
+        ))]),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    rpc.get_program_accounts_with_config(&program_id, config)
+        .await
+        .map_err(|e| TradingVenueError::AmmMethodError(e.to_string().into()))
+}
+
+/// Changes between two `VaultRegistry` syncs.
+#[derive(Clone, Default)]
+pub struct RegistryDelta {
+    /// Venues for vaults not seen before.
+    pub added: Vec<VoltrVaultVenue>,
+    /// Seen vaults since closed, in key order.
+    pub removed: Vec<Pubkey>,
+    /// Unseen accounts that did not become venues; reported again on the
+    /// next sync.
+    pub skipped: Vec<(Pubkey, SkipReason)>,
+}
+
+/// The vaults discovery has built venues for, kept across scheduled runs
+/// so each only reports what changed.
+///
+/// Persisting the seen set is up to the caller: save `seen_vaults()` and
+/// restore it with `with_seen()`.
+#[derive(Clone, Debug, Default)]
+pub struct VaultRegistry {
+    options: DiscoveryOptions,
+    seen: HashSet<Pubkey>,
+}
+
+impl VaultRegistry {
+    /// A registry that has seen nothing, so its first sync adds every vault.
+    pub fn new(options: DiscoveryOptions) -> Self {
+        Self {
+            options,
+            seen: HashSet::new(),
+        }
+    }
+
+    /// A registry resuming from a persisted seen set.
+    pub fn with_seen(options: DiscoveryOptions, seen: Vec<Pubkey>) -> Self {
+        Self {
+            options,
+            seen: seen.into_iter().collect(),
+        }
+    }
+
+    pub fn seen(&self) -> &HashSet<Pubkey> {
+        &self.seen
+    }
+
+    /// The seen set in key order, for persisting.
+    pub fn seen_vaults(&self) -> Vec<Pubkey> {
+        let mut seen: Vec<Pubkey> = self.seen.iter().copied().collect();
+        seen.sort();
+        seen
+    }
+
+    /// Scan the program and record what changed since the last sync. See
+    /// [`VaultRegistry::sync_from_accounts`].
+    pub async fn sync(&mut self, rpc: &RpcClient) -> Result<RegistryDelta, TradingVenueError> {
+        let accounts = fetch_vault_accounts(rpc, &self.options).await?;
+        Ok(self.sync_from_accounts(accounts))
+    }
+
+    /// Record the changes `accounts`, a full scan of the program, shows
+    /// since the last sync. A seen vault is removed when it is missing from
+    /// the scan or closed; it is added again should it reappear.
+    pub fn sync_from_accounts(
+        &mut self,
+        accounts: impl IntoIterator<Item = (Pubkey, Account)>,
+    ) -> RegistryDelta {
+        let accounts: Vec<(Pubkey, Account)> = accounts.into_iter().collect();
+        let live: HashSet<Pubkey> = accounts
+            .iter()
+            .filter(|(_, account)| account.lamports > 0 && !account.data.is_empty())
+            .map(|(key, _)| *key)
+            .collect();
+        let mut removed: Vec<Pubkey> = self.seen.difference(&live).copied().collect();
+        removed.sort();
+        for key in &removed {
+            self.seen.remove(key);
+        }
+
+        let report = discover_created_since(accounts, &self.options, &self.seen);
+        self.seen
+            .extend(report.venues.iter().map(|venue| venue.vault_key));
+        if !report.venues.is_empty() || !removed.is_empty() {
+            log::info!(
+                "Voltr vault registry added {} vaults, removed {}",
+                report.venues.len(),
+                removed.len()
+            );
+        }
+        RegistryDelta {
+            added: report.venues,
+            removed,
+            skipped: report.skipped,
+        }
+    }
+}
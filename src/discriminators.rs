@@ -0,0 +1,77 @@
+//! Anchor discriminators of the instructions, accounts and events the venue
+//! reads and builds. The values are pinned in `constants`; the hashes they come
+//! from are recomputed on first use in debug builds.
+
+use std::sync::Once;
+
+use crate::constants::{
+    DEPOSIT_VAULT_DISCRIMINATOR, DEPOSIT_VAULT_EVENT_DISCRIMINATOR,
+    INSTANT_WITHDRAW_VAULT_DISCRIMINATOR, INSTANT_WITHDRAW_VAULT_EVENT_DISCRIMINATOR,
+    VAULT_ACCOUNT_DISCRIMINATOR,
+};
+
+/// First 8 bytes of the SHA-256 of `preimage`.
+fn sighash(preimage: &str) -> [u8; 8] {
+    let mut discriminator = [0u8; 8];
+    discriminator.copy_from_slice(&solana_sdk::hash::hash(preimage.as_bytes()).to_bytes()[..8]);
+    discriminator
+}
+
+/// Anchor's discriminator of the instruction `name`, hashed at runtime.
+pub fn instruction_discriminator(name: &str) -> [u8; 8] {
+    sighash(&format!("global:{}", name))
+}
+
+/// Anchor's discriminator of the account type `name`, hashed at runtime.
+pub fn account_discriminator(name: &str) -> [u8; 8] {
+    sighash(&format!("account:{}", name))
+}
+
+/// Anchor's discriminator of the event type `name`, hashed at runtime.
+pub fn event_discriminator(name: &str) -> [u8; 8] {
+    sighash(&format!("event:{}", name))
+}
+
+/// `pinned`, panicking on first use in debug builds unless `computed` gives
+/// the same bytes.
+fn checked(once: &Once, pinned: [u8; 8], computed: impl FnOnce() -> [u8; 8]) -> [u8; 8] {
+    if cfg!(debug_assertions) {
+        once.call_once(|| assert_eq!(computed(), pinned, "discriminator hashing changed"));
+    }
+    pinned
+}
+
+pub(crate) fn deposit_vault() -> [u8; 8] {
+    static CHECKED: Once = Once::new();
+    checked(&CHECKED, DEPOSIT_VAULT_DISCRIMINATOR, || {
+        instruction_discriminator("deposit_vault")
+    })
+}
+
+pub(crate) fn instant_withdraw_vault() -> [u8; 8] {
+    static CHECKED: Once = Once::new();
+    checked(&CHECKED, INSTANT_WITHDRAW_VAULT_DISCRIMINATOR, || {
+        instruction_discriminator("instant_withdraw_vault")
+    })
+}
+
+pub(crate) fn vault_account() -> [u8; 8] {
+    static CHECKED: Once = Once::new();
+    checked(&CHECKED, VAULT_ACCOUNT_DISCRIMINATOR, || {
+        account_discriminator("Vault")
+    })
+}
+
+pub(crate) fn deposit_vault_event() -> [u8; 8] {
+    static CHECKED: Once = Once::new();
+    checked(&CHECKED, DEPOSIT_VAULT_EVENT_DISCRIMINATOR, || {
+        event_discriminator("DepositVaultEvent")
+    })
+}
+
+pub(crate) fn instant_withdraw_vault_event() -> [u8; 8] {
+    static CHECKED: Once = Once::new();
+    checked(&CHECKED, INSTANT_WITHDRAW_VAULT_EVENT_DISCRIMINATOR, || {
+        event_discriminator("InstantWithdrawVaultEvent")
+    })
+}
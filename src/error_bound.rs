@@ -0,0 +1,145 @@
+//! Analytical worst-case error of a quote, for setting slippage without
+//! simulating.
+
+use titan_integration_template::trading_venue::{error::TradingVenueError, QuoteRequest};
+
+use crate::{
+    constants::MAX_FEE_BPS, core::quote_snapshot, errors::VoltrError, fixed::U80F48,
+    voltr_venue::VoltrVaultVenue,
+};
+
+/// How far the output of a quote may be from what executes, in output base
+/// units, by source. See `VoltrVaultVenue::quote_error_bound()`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct QuoteErrorBound {
+    /// Integer rounding: one unit for the final floor of the deposit or
+    /// redeem formula, plus, while a management fee accrues, the output moved
+    /// by the LP its two rounded-up steps can differ by (one fee LP, and one
+    /// asset unit of fee worth up to `ceil(supply / total_value)` LP).
+    pub rounding: u64,
+    /// Largest output change from executing `quote_error_window_secs`
+    /// earlier or later than quoted. Management fee accrual and locked-profit
+    /// degradation are monotone in time, so the window's ends bound it.
+    pub timestamp: u64,
+    /// Output the performance fee, which quotes do not model, would dilute
+    /// if charged on all of the LP price above the high water mark; on the
+    /// whole price when no high water mark is recorded.
+    pub performance_fee: u64,
+    /// Output the stale-accounting haircut withholds from a redeem quote,
+    /// which execution pays on top of it. 0 unless
+    /// `stale_accounting_haircut_bps` applies.
+    pub haircut: u64,
+}
+
+impl QuoteErrorBound {
+    /// The terms added up, saturating.
+    pub fn total(&self) -> u64 {
+        self.rounding
+            .saturating_add(self.timestamp)
+            .saturating_add(self.performance_fee)
+            .saturating_add(self.haircut)
+    }
+}
+
+impl VoltrVaultVenue {
+    /// Largest difference, in output base units, between `quote(request)`
+    /// and what executing it can return: `quote_error_terms()` added up.
+    ///
+    /// 0 for zero-amount and rejected quotes, whose output is fixed at 0.
+    pub fn quote_error_bound(&self, request: &QuoteRequest) -> Result<u64, TradingVenueError> {
+        self.quote_error_terms(request).map(|terms| terms.total())
+    }
+
+    /// The terms of `quote_error_bound()`, from the loaded state at
+    /// `effective_ts()`.
+    pub fn quote_error_terms(
+        &self,
+        request: &QuoteRequest,
+    ) -> Result<QuoteErrorBound, TradingVenueError> {
+        let current_ts = self.effective_ts();
+        let (quote, rejection) = self.quote_with_rejection_at(request, current_ts)?;
+        let output = quote.expected_output;
+        if rejection.is_some() || output == 0 {
+            return Ok(QuoteErrorBound::default());
+        }
+
+        let snapshot = self.vault_snapshot()?;
+        let direction = self.direction_for(request)?;
+        let (unadjusted, _) = quote_snapshot(&snapshot, direction, request.amount, current_ts)
+            .map_err(VoltrError::from)?;
+        let haircut = unadjusted.saturating_sub(output);
+
+        let supply = snapshot
+            .total_lp_supply_incl_fees()
+            .and_then(|supply| {
+                Ok(supply.saturating_add(snapshot.pending_management_fee_lp(current_ts)?))
+            })
+            .map_err(VoltrError::from)?;
+
+        let fee_accrues = snapshot.management_fee_bps > 0
+            && snapshot.last_management_fee_update_ts != 0
+            && snapshot.total_value > 0;
+        let fee_lp_error = if fee_accrues {
+            1 + supply.div_ceil(snapshot.total_value)
+        } else {
+            0
+        };
+        let per_lp = output.div_ceil(supply.max(1));
+        let rounding = per_lp.saturating_mul(fee_lp_error).saturating_add(1);
+
+        let window = self.config().quote_error_window_secs;
+        let timestamp = [
+            current_ts.saturating_sub(window),
+            current_ts.saturating_add(window),
+        ]
+        .into_iter()
+        .filter_map(|ts| match self.quote_with_rejection_at(request, ts) {
+            Ok((quote, None)) => Some(quote.expected_output.abs_diff(output)),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(0);
+
+        let performance_fee_bps = self
+            .vault_state
+            .get_total_fee_configuration_performance_fee()?;
+        let performance_fee = performance_fee_dilution(
+            output,
+            performance_fee_bps,
+            snapshot.total_value,
+            supply,
+            self.vault_state.high_water_mark.highest_asset_per_lp(),
+        );
+
+        Ok(QuoteErrorBound {
+            rounding,
+            timestamp,
+            performance_fee,
+            haircut,
+        })
+    }
+}
+
+/// `output` times the performance fee times the share of the LP price
+/// above `high_water_mark`, rounded up.
+fn performance_fee_dilution(
+    output: u64,
+    performance_fee_bps: u16,
+    total_value: u64,
+    supply: u64,
+    high_water_mark: U80F48,
+) -> u64 {
+    if performance_fee_bps == 0 || supply == 0 {
+        return 0;
+    }
+    let price = U80F48::from_int(total_value).saturating_mul_int_ratio(1, supply);
+    if price == U80F48::ZERO || price <= high_water_mark {
+        return 0;
+    }
+    let (price, high_water_mark) = (price.to_bits(), high_water_mark.to_bits());
+    let fee = (output as u128 * performance_fee_bps as u128).div_ceil(MAX_FEE_BPS as u128);
+    // Scale price and excess to 64 bits so `fee * excess` fits in u128.
+    let shift = (u128::BITS - price.leading_zeros()).saturating_sub(64);
+    let (price, excess) = (price >> shift, (price - high_water_mark) >> shift);
+    (fee * excess).div_ceil(price).min(u64::MAX as u128) as u64
+}
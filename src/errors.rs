@@ -1,6 +1,13 @@
+use std::fmt;
+
+use solana_pubkey::Pubkey;
 use thiserror::Error;
+use titan_integration_template::trading_venue::error::TradingVenueError;
+
+use crate::core::CoreError;
 
-#[derive(Error, Clone, Copy, Debug)]
+#[derive(Error, Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u32)]
 pub enum VoltrError {
     #[error("Invalid Source Mint")]
     InvalidSourceMint = 0,
@@ -19,4 +26,131 @@ pub enum VoltrError {
 
     #[error("Insufficient Idle Balance")]
     InsufficientIdleBalance = 6,
+
+    #[error("Venue Not Initialized")]
+    NotInitialized = 7,
+
+    #[error("Share Price Anomaly")]
+    SharePriceAnomaly = 8,
+
+    #[error("Quote Denied By Policy")]
+    PolicyDenied = 9,
+
+    #[error("Unsupported Swap Type")]
+    UnsupportedSwapType = 10,
+
+    #[error("Vault Migrated")]
+    VaultMigrated = 11,
+
+    #[error("Invalid Fee Configuration")]
+    InvalidFeeConfiguration = 12,
+
+    #[error("Invalid Vault Mints")]
+    InvalidVaultMints = 13,
+
+    #[error("Fee Exceeds Max: {fee_bps} bps, vault data likely misparsed")]
+    FeeExceedsMax { fee_bps: u16 } = 14,
+
+    #[error("Idle ATA Has Delegate Or Close Authority")]
+    IdleAtaAnomaly = 15,
+
+    #[error("Account Too Short: {len} bytes, expected at least {min}")]
+    AccountTooShort { len: usize, min: usize } = 16,
+
+    #[error("Invalid Account Discriminator: {found:?}")]
+    InvalidDiscriminator { found: [u8; 8] } = 17,
+
+    #[error("Field Out Of Range: {field}")]
+    FieldOutOfRange { field: &'static str } = 18,
+
+    #[error("Vault Key State Mismatch: vault {vault_key} state has mint {found_mint}, expected {expected_mint}")]
+    VaultKeyStateMismatch {
+        vault_key: Pubkey,
+        expected_mint: Pubkey,
+        found_mint: Pubkey,
+    } = 19,
+
+    #[error("Stale State Expired: last good state is {age_secs}s old")]
+    StaleStateExpired { age_secs: u64 } = 20,
+}
+
+/// Why a dump could not be decoded as a `Vault` account: the data failed
+/// to parse, or the dump was not base64 to begin with.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum VaultDecodeError {
+    /// `Vault::load()` failed, with `AccountTooShort` or
+    /// `InvalidDiscriminator`.
+    #[error(transparent)]
+    Parse(#[from] VoltrError),
+
+    #[error("Invalid Base64: {0}")]
+    InvalidBase64(#[from] base64::DecodeError),
+}
+
+/// Every account a refresh could not parse, with why.
+///
+/// A refresh that collects any applies none of its accounts. Converted into
+/// a `TradingVenueError`, a single failure is passed through as is.
+#[derive(Debug, Default)]
+pub struct StateUpdateError {
+    pub per_account: Vec<(Pubkey, TradingVenueError)>,
+}
+
+impl StateUpdateError {
+    /// The value of `result`, recording its error against `pubkey`.
+    pub(crate) fn check<T>(
+        &mut self,
+        pubkey: Pubkey,
+        result: Result<T, TradingVenueError>,
+    ) -> Option<T> {
+        result.map_err(|e| self.per_account.push((pubkey, e))).ok()
+    }
+}
+
+impl fmt::Display for StateUpdateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} accounts failed to load:", self.per_account.len())?;
+        for (pubkey, error) in &self.per_account {
+            write!(f, "\n  {pubkey}: {error}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for StateUpdateError {}
+
+impl From<StateUpdateError> for TradingVenueError {
+    fn from(mut e: StateUpdateError) -> Self {
+        match e.per_account.len() {
+            1 => e.per_account.pop().expect("one failure").1,
+            _ => TradingVenueError::AmmMethodError(e.to_string().into()),
+        }
+    }
+}
+
+impl From<CoreError> for VoltrError {
+    fn from(e: CoreError) -> Self {
+        match e {
+            CoreError::MathOverflow => VoltrError::MathOverflow,
+            CoreError::DivisionByZero => VoltrError::DivisionByZero,
+            CoreError::FeeExceedsMax { fee_bps } => VoltrError::FeeExceedsMax { fee_bps },
+        }
+    }
+}
+
+impl From<VoltrError> for TradingVenueError {
+    fn from(e: VoltrError) -> Self {
+        match e {
+            VoltrError::MathOverflow | VoltrError::DivisionByZero => {
+                TradingVenueError::CheckedMathError(e.to_string().into())
+            }
+            VoltrError::FeeExceedsMax { .. }
+            | VoltrError::AccountTooShort { .. }
+            | VoltrError::InvalidDiscriminator { .. }
+            | VoltrError::FieldOutOfRange { .. } => {
+                TradingVenueError::DeserializationFailed(e.to_string().into())
+            }
+            _ => TradingVenueError::AmmMethodError(e.to_string().into()),
+        }
+    }
 }
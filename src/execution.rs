@@ -0,0 +1,99 @@
+use titan_integration_template::trading_venue::{
+    error::TradingVenueError, QuoteRequest, QuoteResult,
+};
+
+use crate::{
+    core::{quote_snapshot, VaultSnapshot},
+    errors::VoltrError,
+    voltr_venue::{SwapDirection, VoltrVaultVenue},
+};
+
+/// The user's input and output token account balances at one point of an
+/// execution.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SwapBalances {
+    pub input: u64,
+    pub output: u64,
+}
+
+/// What an executed swap actually cost, measured from the user's balances.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ExecutionReport {
+    pub direction: SwapDirection,
+    /// Drop in the input balance.
+    pub input_spent: u64,
+    /// Rise in the output balance.
+    pub output_received: u64,
+    /// `output_received / input_spent`, in base units.
+    pub realized_price: f64,
+    /// Output `input_spent` would have bought at the venue's state and the
+    /// quote's timestamp with no issuance, redemption or management fee,
+    /// ignoring liquidity limits.
+    pub fee_free_output: u64,
+    /// `fee_free_output - output_received`: the fee charged, in output base
+    /// units. Negative only when the vault moved in the user's favour
+    /// between the snapshot and execution.
+    pub implied_fee: i128,
+    /// Output of the quote the swap was executed on.
+    pub quoted_output: u64,
+    /// `quoted_output - output_received`: positive when the swap paid less
+    /// than quoted.
+    pub slippage: i128,
+}
+
+impl VoltrVaultVenue {
+    /// Reconcile an executed swap for `request` against `quote`, the quote
+    /// the user acted on, taken at `quoted_at`.
+    ///
+    /// Slippage is measured against `quote`, and the fee-free output is
+    /// priced at `quoted_at`, so neither moves with the time of the call.
+    /// `pre` and `post` are the user's input and output balances around the
+    /// transaction. Both directions settle in one instruction, the redeem
+    /// burning LP and paying out the asset in `instant_withdraw_vault`, so
+    /// the balances around that transaction cover the whole swap. Call this
+    /// before refreshing the venue, so it still holds the state the swap was
+    /// quoted against.
+    pub fn reconcile_execution(
+        &self,
+        request: &QuoteRequest,
+        quote: &QuoteResult,
+        quoted_at: u64,
+        pre: SwapBalances,
+        post: SwapBalances,
+    ) -> Result<ExecutionReport, TradingVenueError> {
+        let direction = self.direction_for(request)?;
+        let input_spent = pre.input.checked_sub(post.input).ok_or_else(|| {
+            TradingVenueError::AmmMethodError("Input balance increased during the swap".into())
+        })?;
+        let output_received = post.output.checked_sub(pre.output).ok_or_else(|| {
+            TradingVenueError::AmmMethodError("Output balance decreased during the swap".into())
+        })?;
+
+        let quoted_output = quote.expected_output;
+        let fee_free = VaultSnapshot {
+            idle_balance: u64::MAX,
+            max_cap: 0,
+            issuance_fee_bps: 0,
+            redemption_fee_bps: 0,
+            management_fee_bps: 0,
+            ..self.vault_snapshot()?
+        };
+        let (fee_free_output, _) = quote_snapshot(&fee_free, direction, input_spent, quoted_at)
+            .map_err(VoltrError::from)?;
+
+        Ok(ExecutionReport {
+            direction,
+            input_spent,
+            output_received,
+            realized_price: if input_spent == 0 {
+                0.0
+            } else {
+                output_received as f64 / input_spent as f64
+            },
+            fee_free_output,
+            implied_fee: fee_free_output as i128 - output_received as i128,
+            quoted_output,
+            slippage: quoted_output as i128 - output_received as i128,
+        })
+    }
+}
@@ -0,0 +1,245 @@
+//! Fee LP owed to the vault's fee recipients, for operator dashboards, and
+//! where the fees a swap pays end up.
+
+use titan_integration_template::trading_venue::{error::TradingVenueError, QuoteRequest};
+
+use crate::{
+    core::{calc_amount_after_fee, quote_snapshot, CoreError, VaultSnapshot},
+    errors::VoltrError,
+    voltr_venue::{SwapDirection, VoltrVaultVenue},
+};
+
+/// An amount of fee LP and its worth in the vault asset.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FeeAmount {
+    pub lp: u64,
+    pub asset_value: u64,
+}
+
+/// Unharvested fees of one recipient.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RecipientFees {
+    /// Fee LP recorded in the vault's `FeeState`.
+    pub accumulated: FeeAmount,
+    /// Management fee LP accrued since the last fee update but not yet
+    /// recorded.
+    pub pending_management: FeeAmount,
+}
+
+impl RecipientFees {
+    pub fn total(&self) -> FeeAmount {
+        FeeAmount {
+            lp: self.accumulated.lp + self.pending_management.lp,
+            asset_value: self.accumulated.asset_value + self.pending_management.asset_value,
+        }
+    }
+}
+
+/// Fees owed to each recipient at `current_ts`.
+///
+/// Asset values are at the vault's total value per LP, counting the pending
+/// management fee LP in the supply. Performance fees are only charged when
+/// the manager reports profit and are not projected.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AccruedFees {
+    pub current_ts: u64,
+    pub manager: RecipientFees,
+    pub admin: RecipientFees,
+    pub protocol: RecipientFees,
+}
+
+impl AccruedFees {
+    pub fn total(&self) -> FeeAmount {
+        [self.manager, self.admin, self.protocol]
+            .iter()
+            .map(RecipientFees::total)
+            .fold(FeeAmount::default(), |sum, fees| FeeAmount {
+                lp: sum.lp + fees.lp,
+                asset_value: sum.asset_value + fees.asset_value,
+            })
+    }
+}
+
+/// Who ends up with a fee.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FeeDestination {
+    /// Left in the vault as asset, raising the value of every LP still
+    /// held. No fee LP is recorded for anyone.
+    RemainingLps,
+    /// Recorded as fee LP for the manager in the vault's `FeeState`.
+    Manager,
+    /// Recorded as fee LP for the admin in the vault's `FeeState`.
+    Admin,
+    /// Recorded as fee LP for the protocol in the vault's `FeeState`.
+    Protocol,
+    /// Destroyed. The Voltr program burns no fee today.
+    Burned,
+}
+
+/// What a fee is charged for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FeeKind {
+    Issuance,
+    Redemption,
+    /// Management fee accrued since the last fee update, which the swap's
+    /// instruction settles before pricing it.
+    Management,
+}
+
+/// One fee a swap pays or settles.
+///
+/// The program keeps issuance and redemption fees in the vault: a deposit
+/// adds its whole input to the vault's value while minting LP for the input
+/// net of the fee, and a redeem takes only its payout out of the value. Both
+/// therefore accrue to the remaining LPs. Management fees are recorded as
+/// fee LP for each recipient, split by their management fee.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeeComponent {
+    pub kind: FeeKind,
+    pub destination: FeeDestination,
+    /// Worth of the fee in the vault asset.
+    pub asset_value: u64,
+    /// Fee LP recorded for `destination`; 0 for fees left in the vault.
+    pub lp: u64,
+}
+
+/// `lp` of `total_lp` shares in `total_value`; 0 when there are no shares.
+fn lp_value(lp: u64, total_lp: u64, total_value: u64) -> Result<u64, VoltrError> {
+    if total_lp == 0 {
+        return Ok(0);
+    }
+    u64::try_from(lp as u128 * total_value as u128 / total_lp as u128)
+        .map_err(|_| VoltrError::MathOverflow)
+}
+
+/// `lp` split by `bps` of `total_bps`, rounded down.
+fn share_of(lp: u64, bps: u16, total_bps: u16) -> u64 {
+    if total_bps == 0 {
+        return 0;
+    }
+    (lp as u128 * bps as u128 / total_bps as u128) as u64
+}
+
+impl VoltrVaultVenue {
+    /// Unharvested fee LP of the manager, admin and protocol, with the
+    /// pending management fee estimated to `current_ts`.
+    ///
+    /// The pending fee is split in proportion to each recipient's management
+    /// fee, rounding each share down. Computed from the loaded state only.
+    pub fn accrued_fees(&self, current_ts: u64) -> Result<AccruedFees, TradingVenueError> {
+        if !self.initialized {
+            return Err(VoltrError::NotInitialized.into());
+        }
+        let snapshot = self.vault_snapshot()?;
+        let pending_lp = snapshot
+            .pending_management_fee_lp(current_ts)
+            .map_err(VoltrError::from)?;
+        let total_lp = snapshot
+            .total_lp_supply_incl_fees()
+            .and_then(|supply| {
+                supply
+                    .checked_add(pending_lp)
+                    .ok_or(CoreError::MathOverflow)
+            })
+            .map_err(VoltrError::from)?;
+
+        let fee_state = &self.vault_state.fee_state;
+        let fee_configuration = &self.vault_state.fee_configuration;
+        let recipient = |accumulated_lp: u64, management_fee_bps: u16| {
+            let pending_lp = share_of(pending_lp, management_fee_bps, snapshot.management_fee_bps);
+            Ok::<_, VoltrError>(RecipientFees {
+                accumulated: FeeAmount {
+                    lp: accumulated_lp,
+                    asset_value: lp_value(accumulated_lp, total_lp, snapshot.total_value)?,
+                },
+                pending_management: FeeAmount {
+                    lp: pending_lp,
+                    asset_value: lp_value(pending_lp, total_lp, snapshot.total_value)?,
+                },
+            })
+        };
+
+        Ok(AccruedFees {
+            current_ts,
+            manager: recipient(
+                fee_state.accumulated_lp_manager_fees,
+                fee_configuration.manager_management_fee,
+            )?,
+            admin: recipient(
+                fee_state.accumulated_lp_admin_fees,
+                fee_configuration.admin_management_fee,
+            )?,
+            protocol: recipient(
+                fee_state.accumulated_lp_protocol_fees,
+                fee_configuration.protocol_management_fee,
+            )?,
+        })
+    }
+}
+
+impl VoltrVaultVenue {
+    /// Fees `request` pays or settles at `current_ts`, by destination.
+    ///
+    /// Empty for zero amounts and rejected quotes. Components worth nothing
+    /// are left out: the swap fee when its rate is 0, management fee shares
+    /// of recipients without a management fee.
+    pub fn swap_fees(
+        &self,
+        request: &QuoteRequest,
+        current_ts: u64,
+    ) -> Result<Vec<FeeComponent>, TradingVenueError> {
+        let direction = self.direction_for(request)?;
+        let (quote, rejection) = self.quote_with_rejection_at(request, current_ts)?;
+        if rejection.is_some() || request.amount == 0 {
+            return Ok(Vec::new());
+        }
+
+        let snapshot = self.vault_snapshot()?;
+        let (kind, fee) = match direction {
+            SwapDirection::Deposit => {
+                let credited = calc_amount_after_fee(request.amount, snapshot.issuance_fee_bps)
+                    .map_err(VoltrError::from)?;
+                (FeeKind::Issuance, request.amount - credited)
+            }
+            SwapDirection::Redeem => {
+                let fee_free = VaultSnapshot {
+                    idle_balance: u64::MAX,
+                    redemption_fee_bps: 0,
+                    ..snapshot
+                };
+                let (gross, _) = quote_snapshot(&fee_free, direction, request.amount, current_ts)
+                    .map_err(VoltrError::from)?;
+                (
+                    FeeKind::Redemption,
+                    gross.saturating_sub(quote.expected_output),
+                )
+            }
+        };
+
+        let mut fees = Vec::new();
+        if fee > 0 {
+            fees.push(FeeComponent {
+                kind,
+                destination: FeeDestination::RemainingLps,
+                asset_value: fee,
+                lp: 0,
+            });
+        }
+        let accrued = self.accrued_fees(current_ts)?;
+        for (destination, recipient) in [
+            (FeeDestination::Manager, accrued.manager),
+            (FeeDestination::Admin, accrued.admin),
+            (FeeDestination::Protocol, accrued.protocol),
+        ] {
+            if recipient.pending_management.lp > 0 {
+                fees.push(FeeComponent {
+                    kind: FeeKind::Management,
+                    destination,
+                    asset_value: recipient.pending_management.asset_value,
+                    lp: recipient.pending_management.lp,
+                });
+            }
+        }
+        Ok(fees)
+    }
+}
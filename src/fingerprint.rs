@@ -0,0 +1,75 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use solana_account::Account;
+use titan_integration_template::trading_venue::error::TradingVenueError;
+
+use crate::voltr_venue::VoltrVaultVenue;
+
+/// Hash of an account's owner, lamports and data, for telling whether an
+/// account changed without keeping or comparing its bytes.
+///
+/// Stable within a process only; do not persist it.
+pub fn account_fingerprint(account: &Account) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    account.owner.hash(&mut hasher);
+    account.lamports.hash(&mut hasher);
+    account.data.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Fingerprints of the accounts listed by `token_account_keys()`.
+pub(crate) fn token_fingerprints(accounts: &[Option<Account>]) -> [Option<u64>; 3] {
+    core::array::from_fn(|i| accounts[i].as_ref().map(account_fingerprint))
+}
+
+impl VoltrVaultVenue {
+    /// Load the accounts listed by `get_required_pubkeys_for_update()`, in
+    /// that order, as delivered by an account subscription instead of a
+    /// cache.
+    ///
+    /// Accounts are fingerprinted against the last load and only those that
+    /// changed are parsed again. Returns whether anything was reloaded, and so
+    /// whether quotes may have changed. A venue built with
+    /// `new_uninitialized()` only lists its vault; once that is loaded the
+    /// call returns `true` and the caller fetches the newly listed accounts.
+    /// Failures count toward `VenueStatus::Stale`.
+    pub fn update_state_from_accounts(
+        &mut self,
+        accounts: &[Option<Account>],
+    ) -> Result<bool, TradingVenueError> {
+        let result = self.reload_changed(accounts);
+        self.record_refresh(result)
+    }
+
+    /// `update_state_from_accounts()` before its outcome is recorded.
+    fn reload_changed(&mut self, accounts: &[Option<Account>]) -> Result<bool, TradingVenueError> {
+        let expected = if self.vault_loaded { 4 } else { 1 };
+        if accounts.len() != expected {
+            return Err(TradingVenueError::AmmMethodError(
+                format!("Expected {expected} accounts, got {}", accounts.len()).into(),
+            ));
+        }
+
+        let vault_fingerprint = accounts[0].as_ref().map(account_fingerprint);
+        let vault_changed =
+            vault_fingerprint.is_none() || vault_fingerprint != self.vault_fingerprint;
+        if !self.vault_loaded {
+            self.load_vault_account(accounts[0].as_ref())?;
+            return Ok(true);
+        }
+
+        let tokens = token_fingerprints(&accounts[1..]);
+        if !vault_changed && self.initialized && self.token_fingerprints == Some(tokens) {
+            return Ok(false);
+        }
+
+        if vault_changed {
+            self.load_accounts(accounts)?;
+        } else {
+            self.load_token_accounts(&accounts[1..])?;
+        }
+        self.state_slot = None;
+        self.inconsistent_slot_span = None;
+        Ok(true)
+    }
+}
@@ -0,0 +1,91 @@
+//! The program's U80F48 fixed point: 80 integer and 48 fractional bits in
+//! a `u128`, as redeem payouts and the high water mark are computed in.
+//!
+//! Like [`crate::core`], this depends only on `core`.
+
+use core::cmp::Ordering;
+
+use crate::core::CoreError;
+
+/// Fractional bits in the on-chain U80F48 fixed-point type.
+pub const FRAC_BITS: u32 = 48;
+
+/// A U80F48 value, holding its raw bits.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct U80F48(pub u128);
+
+impl U80F48 {
+    pub const ZERO: Self = U80F48(0);
+    pub const MAX: Self = U80F48(u128::MAX);
+
+    pub const fn from_bits(bits: u128) -> Self {
+        U80F48(bits)
+    }
+
+    pub const fn to_bits(self) -> u128 {
+        self.0
+    }
+
+    /// `n` exactly; every `u64` fits in the 80 integer bits.
+    pub const fn from_int(n: u64) -> Self {
+        U80F48((n as u128) << FRAC_BITS)
+    }
+
+    /// The integer part, as the program's `to_floor()`.
+    pub const fn to_floor(self) -> u128 {
+        self.0 >> FRAC_BITS
+    }
+
+    /// The integer part, plus one when there is a fractional part.
+    pub const fn to_ceil(self) -> u128 {
+        let frac_mask = (1u128 << FRAC_BITS) - 1;
+        self.to_floor() + (self.0 & frac_mask != 0) as u128
+    }
+
+    /// `self * a / b`, floored, without overflowing in the intermediate
+    /// product: the program's `full_mul_int_ratio()`.
+    pub fn mul_int_ratio(self, a: u64, b: u64) -> Result<Self, CoreError> {
+        if b == 0 {
+            return Err(CoreError::DivisionByZero);
+        }
+        // Schoolbook division: with self = q * b + r, the product is
+        // q * a + r * a / b, and r * a < b * a fits in a u128.
+        let (a, b) = (a as u128, b as u128);
+        let (q, r) = (self.0 / b, self.0 % b);
+        q.checked_mul(a)
+            .and_then(|v| v.checked_add(r * a / b))
+            .map(U80F48)
+            .ok_or(CoreError::MathOverflow)
+    }
+
+    /// `mul_int_ratio()`, `None` on overflow or a zero `b`.
+    pub fn checked_mul_int_ratio(self, a: u64, b: u64) -> Option<Self> {
+        self.mul_int_ratio(a, b).ok()
+    }
+
+    /// `mul_int_ratio()`, `MAX` on overflow or a zero `b`.
+    pub fn saturating_mul_int_ratio(self, a: u64, b: u64) -> Self {
+        self.mul_int_ratio(a, b).unwrap_or(Self::MAX)
+    }
+
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        self.0.checked_add(other.0).map(U80F48)
+    }
+
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        self.0.checked_sub(other.0).map(U80F48)
+    }
+
+    pub fn saturating_add(self, other: Self) -> Self {
+        U80F48(self.0.saturating_add(other.0))
+    }
+
+    pub fn saturating_sub(self, other: Self) -> Self {
+        U80F48(self.0.saturating_sub(other.0))
+    }
+
+    /// How `self` compares to the integer `n`.
+    pub fn cmp_int(self, n: u64) -> Ordering {
+        self.cmp(&Self::from_int(n))
+    }
+}
@@ -0,0 +1,237 @@
+//! Vault state captured to a directory, for reproducing reported quotes
+//! offline.
+//!
+//! A fixture directory holds a `fixture` file naming the vault and the slot
+//! and Unix time of the capture, and one `<PUBKEY>.account` file per
+//! captured account, both as `key=value` lines:
+//!
+//! ```text
+//! fixture:
+//!     vault=<VAULT_PUBKEY>
+//!     slot=<SLOT>
+//!     timestamp=<UNIX_TIME>
+//! <PUBKEY>.account:
+//!     lamports=<LAMPORTS>
+//!     owner=<OWNER_PUBKEY>
+//!     executable=false
+//!     rent_epoch=<EPOCH>
+//!     data=<BASE64>
+//! ```
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use base64::Engine;
+use solana_account::Account;
+use solana_pubkey::Pubkey;
+
+use titan_integration_template::{
+    account_caching::AccountsCache, trading_venue::error::TradingVenueError,
+};
+
+use crate::{
+    clock::QuoteClock, replay::ReplaySnapshot, slots::SlotAwareAccountsCache,
+    voltr_venue::VoltrVaultVenue,
+};
+
+const FIXTURE_FILE: &str = "fixture";
+const ACCOUNT_EXTENSION: &str = "account";
+
+fn fixture_error(message: String) -> TradingVenueError {
+    TradingVenueError::AmmMethodError(message.into())
+}
+
+/// The `key=value` lines of `contents`, failing on any other line.
+fn parse_fields(contents: &str, path: &Path) -> Result<HashMap<String, String>, TradingVenueError> {
+    contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            line.split_once('=')
+                .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+                .ok_or_else(|| {
+                    fixture_error(format!("{}: malformed line {line:?}", path.display()))
+                })
+        })
+        .collect()
+}
+
+fn field<T: FromStr>(
+    fields: &HashMap<String, String>,
+    key: &str,
+    path: &Path,
+) -> Result<T, TradingVenueError> {
+    let value = fields
+        .get(key)
+        .ok_or_else(|| fixture_error(format!("{}: missing {key}", path.display())))?;
+    value
+        .parse()
+        .map_err(|_| fixture_error(format!("{}: invalid {key} {value:?}", path.display())))
+}
+
+fn read_fields(path: &Path) -> Result<HashMap<String, String>, TradingVenueError> {
+    let contents =
+        fs::read_to_string(path).map_err(|e| fixture_error(format!("{}: {e}", path.display())))?;
+    parse_fields(&contents, path)
+}
+
+fn write_file(path: &Path, contents: String) -> Result<(), TradingVenueError> {
+    fs::write(path, contents).map_err(|e| fixture_error(format!("{}: {e}", path.display())))
+}
+
+fn read_account(path: &Path) -> Result<Account, TradingVenueError> {
+    let fields = read_fields(path)?;
+    let data: String = field(&fields, "data", path)?;
+    Ok(Account {
+        lamports: field(&fields, "lamports", path)?,
+        data: base64::engine::general_purpose::STANDARD
+            .decode(data)
+            .map_err(|e| fixture_error(format!("{}: invalid data: {e}", path.display())))?,
+        owner: field(&fields, "owner", path)?,
+        executable: field(&fields, "executable", path)?,
+        rent_epoch: field(&fields, "rent_epoch", path)?,
+    })
+}
+
+fn format_account(account: &Account) -> String {
+    format!(
+        "lamports={}\nowner={}\nexecutable={}\nrent_epoch={}\ndata={}\n",
+        account.lamports,
+        account.owner,
+        account.executable,
+        account.rent_epoch,
+        base64::engine::general_purpose::STANDARD.encode(&account.data)
+    )
+}
+
+/// An `AccountsCache` serving the accounts of one vault as captured at one
+/// slot, saved to and loaded from a fixture directory.
+#[derive(Clone, Debug)]
+pub struct FixtureCache {
+    vault_key: Pubkey,
+    snapshot: ReplaySnapshot,
+}
+
+impl FixtureCache {
+    pub fn new(vault_key: Pubkey, snapshot: ReplaySnapshot) -> Self {
+        Self {
+            vault_key,
+            snapshot,
+        }
+    }
+
+    pub fn vault_key(&self) -> Pubkey {
+        self.vault_key
+    }
+
+    /// The captured accounts, with the slot and Unix time they were read at.
+    pub fn snapshot(&self) -> &ReplaySnapshot {
+        &self.snapshot
+    }
+
+    /// Read the fixture directory at `dir`.
+    pub fn load(dir: impl AsRef<Path>) -> Result<Self, TradingVenueError> {
+        let dir = dir.as_ref();
+        let fixture_path = dir.join(FIXTURE_FILE);
+        let fields = read_fields(&fixture_path)?;
+        let vault_key = field(&fields, "vault", &fixture_path)?;
+        let slot = field(&fields, "slot", &fixture_path)?;
+        let timestamp = field(&fields, "timestamp", &fixture_path)?;
+
+        let entries =
+            fs::read_dir(dir).map_err(|e| fixture_error(format!("{}: {e}", dir.display())))?;
+        let mut accounts = HashMap::new();
+        for entry in entries {
+            let path = entry
+                .map_err(|e| fixture_error(format!("{}: {e}", dir.display())))?
+                .path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some(ACCOUNT_EXTENSION) {
+                continue;
+            }
+            let pubkey = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| Pubkey::from_str(stem).ok())
+                .ok_or_else(|| {
+                    fixture_error(format!("{}: not named after a pubkey", path.display()))
+                })?;
+            accounts.insert(pubkey, read_account(&path)?);
+        }
+
+        Ok(Self::new(
+            vault_key,
+            ReplaySnapshot {
+                slot,
+                timestamp,
+                accounts,
+            },
+        ))
+    }
+
+    /// Write the fixture to `dir`, creating it if needed. Existing account
+    /// files of other pubkeys are left in place.
+    pub fn save(&self, dir: impl AsRef<Path>) -> Result<(), TradingVenueError> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir).map_err(|e| fixture_error(format!("{}: {e}", dir.display())))?;
+        write_file(
+            &dir.join(FIXTURE_FILE),
+            format!(
+                "vault={}\nslot={}\ntimestamp={}\n",
+                self.vault_key, self.snapshot.slot, self.snapshot.timestamp
+            ),
+        )?;
+        for (pubkey, account) in &self.snapshot.accounts {
+            write_file(
+                &dir.join(format!("{pubkey}.{ACCOUNT_EXTENSION}")),
+                format_account(account),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// A venue loaded from the captured accounts, quoting on a clock fixed
+    /// at the capture time so it prices exactly as the vault did then.
+    pub async fn venue(&self) -> Result<VoltrVaultVenue, TradingVenueError> {
+        let mut venue = VoltrVaultVenue::builder(self.vault_key)
+            .clock(QuoteClock::Fixed(self.snapshot.timestamp))
+            .build();
+        venue.update_state_with_slots(self).await?;
+        Ok(venue)
+    }
+}
+
+#[async_trait]
+impl AccountsCache for FixtureCache {
+    async fn get_account(&self, pubkey: &Pubkey) -> Result<Option<Account>, TradingVenueError> {
+        Ok(self.snapshot.accounts.get(pubkey).cloned())
+    }
+
+    async fn get_accounts(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> Result<Vec<Option<Account>>, TradingVenueError> {
+        Ok(pubkeys
+            .iter()
+            .map(|pubkey| self.snapshot.accounts.get(pubkey).cloned())
+            .collect())
+    }
+}
+
+#[async_trait]
+impl SlotAwareAccountsCache for FixtureCache {
+    /// Every account was captured at the fixture's slot.
+    async fn get_accounts_with_slots(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> Result<Vec<(Option<Account>, Option<u64>)>, TradingVenueError> {
+        Ok(self
+            .get_accounts(pubkeys)
+            .await?
+            .into_iter()
+            .map(|account| (account, Some(self.snapshot.slot)))
+            .collect())
+    }
+}
@@ -0,0 +1,240 @@
+use solana_program::program_option::COption;
+use solana_pubkey::Pubkey;
+use titan_integration_template::trading_venue::error::TradingVenueError;
+
+use crate::{
+    config::IdleAtaAnomalyAction,
+    errors::VoltrError,
+    state::Vault,
+    status::VenueStatus,
+    voltr_venue::{SwapDirection, VoltrVaultVenue},
+};
+
+/// Vault accounting captured at a refresh to compare share prices.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct SharePriceSnapshot {
+    total_value: u64,
+    total_lp: u64,
+    last_report: u64,
+}
+
+/// Share price of `vault_state` with `lp_mint_supply` LP minted; `None`
+/// without any LP to price.
+fn share_price_snapshot(vault_state: &Vault, lp_mint_supply: u64) -> Option<SharePriceSnapshot> {
+    let total_lp = vault_state
+        .get_total_lp_supply_incl_fees(lp_mint_supply)
+        .ok()
+        .filter(|lp| *lp > 0)?;
+    Some(SharePriceSnapshot {
+        total_value: vault_state.get_total_asset_value(),
+        total_lp,
+        last_report: vault_state.locked_profit_state.last_report,
+    })
+}
+
+/// Share-price baseline and anomaly a refresh stores once it succeeds.
+pub(crate) struct SharePriceCheck {
+    pub(crate) baseline: Option<SharePriceSnapshot>,
+    pub(crate) anomaly: Option<SharePriceAnomaly>,
+}
+
+impl SharePriceSnapshot {
+    /// Asset per LP in base units.
+    fn price(&self) -> f64 {
+        self.total_value as f64 / self.total_lp as f64
+    }
+}
+
+/// A refresh whose implied asset-per-LP price moved further than
+/// `max_share_price_change_bps` from the last accepted refresh without a
+/// locked-profit report in between.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SharePriceAnomaly {
+    /// Asset per LP, in base units, at the last accepted refresh.
+    pub baseline_price: f64,
+    /// Asset per LP, in base units, at the flagged refresh.
+    pub current_price: f64,
+    pub change_bps: u64,
+}
+
+/// A delegate or close authority on the vault's idle ATA. Either can move
+/// or close funds counted as instant liquidity outside the program.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IdleAtaAnomaly {
+    pub delegate: Option<Pubkey>,
+    pub delegated_amount: u64,
+    pub close_authority: Option<Pubkey>,
+}
+
+impl IdleAtaAnomaly {
+    /// The anomaly of a token account with these fields, `None` when they
+    /// are all unset.
+    pub(crate) fn from_token_account(
+        delegate: COption<Pubkey>,
+        delegated_amount: u64,
+        close_authority: COption<Pubkey>,
+    ) -> Option<Self> {
+        let anomaly = Self {
+            delegate: delegate.into(),
+            delegated_amount,
+            close_authority: close_authority.into(),
+        };
+        (anomaly.delegate.is_some()
+            || anomaly.delegated_amount > 0
+            || anomaly.close_authority.is_some())
+        .then_some(anomaly)
+    }
+}
+
+/// Point-in-time view of everything that makes a venue's quotes suspect.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HealthReport {
+    pub status: VenueStatus,
+    pub share_price_anomaly: Option<SharePriceAnomaly>,
+    /// Accounting age in seconds when it exceeds `max_accounting_age_secs`.
+    pub stale_accounting_age: Option<u64>,
+    /// Who can freeze the asset, including the vault's idle ATA. A settlement
+    /// risk to weigh, but common among stablecoins, so not unhealthy.
+    pub asset_freeze_authority: Option<Pubkey>,
+    /// Delegate or close authority found on the idle ATA at the last refresh.
+    pub idle_ata_anomaly: Option<IdleAtaAnomaly>,
+    /// The asset mint has Token-2022 extensions this build cannot read, so
+    /// only its base fields were. Quotes are still served, but an extension
+    /// could change how transfers of the asset behave.
+    pub unknown_mint_extensions: bool,
+}
+
+impl HealthReport {
+    /// Whether the venue's quotes can be trusted; ignores
+    /// `asset_freeze_authority` and `unknown_mint_extensions`.
+    pub fn is_healthy(&self) -> bool {
+        self.status == VenueStatus::Ready
+            && self.share_price_anomaly.is_none()
+            && self.stale_accounting_age.is_none()
+            && self.idle_ata_anomaly.is_none()
+    }
+}
+
+impl VoltrVaultVenue {
+    pub fn health_report(&self) -> HealthReport {
+        HealthReport {
+            status: self.status(),
+            share_price_anomaly: self.share_price_anomaly,
+            stale_accounting_age: self.stale_accounting_age(self.effective_ts()),
+            asset_freeze_authority: self.asset_mint_freeze_authority(),
+            idle_ata_anomaly: self.idle_ata_anomaly,
+            unknown_mint_extensions: self.asset_mint_unknown_extensions,
+        }
+    }
+
+    /// Fail with `IdleAtaAnomaly` when `idle_ata_anomaly_action` refuses
+    /// swaps in `direction` and the idle ATA has one.
+    pub(crate) fn ensure_idle_ata_trusted(
+        &self,
+        direction: SwapDirection,
+    ) -> Result<(), TradingVenueError> {
+        let blocked = match self.config().idle_ata_anomaly_action {
+            IdleAtaAnomalyAction::Report => false,
+            IdleAtaAnomalyAction::BlockRedeems => direction == SwapDirection::Redeem,
+            IdleAtaAnomalyAction::BlockAll => true,
+        };
+        if blocked && self.idle_ata_anomaly.is_some() {
+            return Err(VoltrError::IdleAtaAnomaly.into());
+        }
+        Ok(())
+    }
+
+    /// Warn about a delegate or close authority on the freshly loaded idle
+    /// ATA.
+    pub(crate) fn check_idle_ata(&self) {
+        if let Some(anomaly) = self.idle_ata_anomaly {
+            log::warn!(
+                "Voltr vault {} idle ATA {} has delegate {:?} for {} and close authority {:?}; idle funds can leave outside the program",
+                self.vault_key,
+                self.vault_state.asset.idle_ata,
+                anomaly.delegate,
+                anomaly.delegated_amount,
+                anomaly.close_authority
+            );
+        }
+    }
+
+    /// Seconds between the vault's last accounting update and `current_ts`.
+    pub fn accounting_age(&self, current_ts: u64) -> u64 {
+        current_ts.saturating_sub(self.vault_state.last_updated_ts)
+    }
+
+    /// `accounting_age()` when it exceeds `max_accounting_age_secs`.
+    pub(crate) fn stale_accounting_age(&self, current_ts: u64) -> Option<u64> {
+        let max_age = self.config().max_accounting_age_secs?;
+        let age = self.accounting_age(current_ts);
+        (age > max_age).then_some(age)
+    }
+
+    /// Warn when the freshly loaded accounting is older than allowed.
+    pub(crate) fn check_accounting_age(&self) {
+        if let Some(age) = self.stale_accounting_age(self.effective_ts()) {
+            log::warn!(
+                "Voltr vault {} accounting is {}s old; total value may not reflect strategy yields",
+                self.vault_key,
+                age
+            );
+        }
+    }
+
+    /// Compare the share price of `vault_state` and `lp_mint_supply`, about
+    /// to be loaded, with the last accepted one, returning the baseline and
+    /// anomaly to store alongside them.
+    ///
+    /// A locked-profit report legitimately moves the price, so a refresh
+    /// following one is accepted as the new baseline. When quoting is
+    /// configured to stop on anomalies, a flagged refresh fails with the
+    /// anomaly and does not replace the baseline, so the flag only clears
+    /// once the price returns within bounds of the last trusted state.
+    pub(crate) fn check_share_price(
+        &self,
+        vault_state: &Vault,
+        lp_mint_supply: u64,
+    ) -> Result<SharePriceCheck, SharePriceAnomaly> {
+        let unchanged = SharePriceCheck {
+            baseline: self.share_price_baseline,
+            anomaly: self.share_price_anomaly,
+        };
+        let Some(max_change_bps) = self.config().max_share_price_change_bps else {
+            return Ok(unchanged);
+        };
+        let Some(current) = share_price_snapshot(vault_state, lp_mint_supply) else {
+            return Ok(unchanged);
+        };
+
+        let anomaly = match self.share_price_baseline {
+            Some(baseline) if baseline.last_report == current.last_report => {
+                let change_bps = ((current.price() - baseline.price()).abs() / baseline.price()
+                    * 10_000.0) as u64;
+                (change_bps > max_change_bps).then_some(SharePriceAnomaly {
+                    baseline_price: baseline.price(),
+                    current_price: current.price(),
+                    change_bps,
+                })
+            }
+            _ => None,
+        };
+
+        if let Some(anomaly) = anomaly {
+            log::warn!(
+                "Voltr vault {} share price moved {} bps ({} -> {}) without a profit report",
+                self.vault_key,
+                anomaly.change_bps,
+                anomaly.baseline_price,
+                anomaly.current_price
+            );
+            if self.config().fail_on_share_price_anomaly {
+                return Err(anomaly);
+            }
+        }
+        Ok(SharePriceCheck {
+            baseline: Some(current),
+            anomaly,
+        })
+    }
+}
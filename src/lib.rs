@@ -1,5 +1,211 @@
+//! Titan trading venue for Voltr yield vaults.
+//!
+//! The crate root and [`prelude`] are the supported API surface; the
+//! modules below stay public so existing import paths keep working, but
+//! their layout may change between releases.
+//!
+//! With `default-features = false` and the `core` feature only the
+//! [`core`] pricing module is built, under `no_std`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "std")]
+pub mod atas;
+#[cfg(feature = "std")]
+pub mod builder;
+#[cfg(feature = "std")]
+pub mod caches;
+#[cfg(feature = "std")]
+pub mod clock;
+#[cfg(feature = "std")]
+pub mod config;
+#[cfg(feature = "std")]
 pub mod constants;
+pub mod core;
+#[cfg(feature = "std")]
+pub mod decay;
+#[cfg(feature = "std")]
+pub mod direction;
+#[cfg(feature = "std")]
+pub mod discovery;
+#[cfg(feature = "std")]
+pub mod discriminators;
+#[cfg(feature = "std")]
+pub mod error_bound;
+#[cfg(feature = "std")]
 pub mod errors;
+#[cfg(feature = "std")]
+pub mod execution;
+#[cfg(feature = "std")]
+pub mod fees;
+#[cfg(feature = "std")]
+pub mod fingerprint;
+pub mod fixed;
+#[cfg(feature = "replay")]
+pub mod fixture;
+#[cfg(feature = "std")]
+pub mod health;
+#[cfg(feature = "local-sim")]
+pub mod local_sim;
+#[cfg(feature = "std")]
+pub mod logs;
+#[cfg(feature = "anyhow-compat")]
+#[doc(hidden)]
 pub mod math;
+#[cfg(feature = "test-utils")]
+pub mod mock;
+#[cfg(feature = "std")]
+pub mod overrides;
+#[cfg(feature = "std")]
+pub mod pda;
+#[cfg(feature = "std")]
+pub mod policy;
+#[cfg(feature = "std")]
+pub mod preconditions;
+#[cfg(feature = "std")]
+pub mod preflight;
+#[cfg(feature = "std")]
+pub mod quote;
+#[cfg(feature = "std")]
+pub mod rejections;
+#[cfg(feature = "replay")]
+pub mod replay;
+#[cfg(feature = "std")]
+pub mod router;
+#[cfg(feature = "test-utils")]
+pub mod sampling;
+#[cfg(feature = "std")]
+pub mod slots;
+#[cfg(feature = "std")]
 pub mod state;
+#[cfg(feature = "anyhow-compat")]
+pub mod state_compat;
+#[cfg(feature = "std")]
+pub mod status;
+#[cfg(feature = "std")]
+pub mod subscriptions;
+#[cfg(feature = "std")]
+pub mod summary;
+#[cfg(feature = "std")]
+pub mod synthetic;
+#[cfg(feature = "std")]
+pub mod transaction;
+#[cfg(feature = "std")]
+pub mod ui;
+#[cfg(feature = "std")]
+pub mod verification;
+#[cfg(feature = "std")]
 pub mod voltr_venue;
+#[cfg(feature = "std")]
+pub mod warmup;
+
+#[cfg(feature = "std")]
+pub use builder::VoltrVaultVenueBuilder;
+#[cfg(feature = "std")]
+pub use caches::{ClosureCache, StaticCache};
+#[cfg(feature = "std")]
+pub use clock::QuoteClock;
+#[cfg(feature = "std")]
+pub use config::{IdleAtaAnomalyAction, MintMetadata, VoltrVenueConfig};
+#[cfg(feature = "std")]
+pub use constants::{QUOTE_SEMANTICS_VERSION, VOLTR_VAULT_PROGRAM};
+pub use core::ExactOutput;
+#[cfg(feature = "std")]
+pub use decay::QuoteDecay;
+#[cfg(feature = "std")]
+pub use direction::DirectionInfo;
+#[cfg(feature = "rayon")]
+pub use discovery::discover_from_accounts_parallel;
+#[cfg(feature = "std")]
+pub use discovery::{
+    find_all_vaults, find_vaults_created_since, DiscoveryOptions, DiscoveryReport, RegistryDelta,
+    SkipReason, VaultRegistry,
+};
+#[cfg(feature = "std")]
+pub use error_bound::QuoteErrorBound;
+#[cfg(feature = "std")]
+pub use errors::{StateUpdateError, VaultDecodeError, VoltrError};
+#[cfg(feature = "std")]
+pub use execution::{ExecutionReport, SwapBalances};
+#[cfg(feature = "std")]
+pub use fees::{AccruedFees, FeeAmount, FeeComponent, FeeDestination, FeeKind, RecipientFees};
+#[cfg(feature = "replay")]
+pub use fixture::FixtureCache;
+#[cfg(feature = "std")]
+pub use health::{HealthReport, IdleAtaAnomaly, SharePriceAnomaly};
+#[cfg(feature = "local-sim")]
+pub use local_sim::{SimValidation, SwapSimulation};
+#[cfg(feature = "std")]
+pub use logs::{
+    parse_program_logs, parse_transaction_logs, DepositVaultEvent, InstantWithdrawVaultEvent,
+    VoltrEvent,
+};
+#[cfg(feature = "test-utils")]
+pub use mock::MockableVoltrVenue;
+#[cfg(feature = "std")]
+pub use overrides::{PendingCrank, SnapshotOverrides};
+#[cfg(feature = "std")]
+pub use pda::{ProgramId, VaultKey};
+#[cfg(feature = "std")]
+pub use policy::{
+    MaxFeeBps, MintAllowlist, PolicyDecision, PolicyVenue, PolicyVenueError, QuotePolicy,
+};
+#[cfg(feature = "std")]
+pub use preconditions::{PreconditionFailure, RequiredAccount, UserAccountRole};
+#[cfg(feature = "std")]
+pub use preflight::{PreflightCheck, PreflightCheckReport, PreflightOutcome, PreflightReport};
+#[cfg(feature = "std")]
+pub use quote::{DetailedQuote, QuoteResultMeta};
+#[cfg(feature = "replay")]
+pub use replay::{ReplayCache, ReplaySession, ReplaySnapshot, ReplayStep};
+#[cfg(feature = "std")]
+pub use router::{best_deposit_venue, best_redeem_venue};
+#[cfg(feature = "std")]
+pub use slots::SlotAwareAccountsCache;
+#[cfg(feature = "std")]
+pub use state::{LpSupplyBreakdown, Vault};
+#[cfg(feature = "std")]
+pub use status::{LpMintAuthorityMismatch, RefreshStreak, VenueStatus};
+#[cfg(feature = "std")]
+pub use subscriptions::{
+    merge_subscription_plans, MergedPlan, SubscriptionPlan, SubscriptionPriority,
+};
+#[cfg(feature = "std")]
+pub use summary::VenueSummary;
+#[cfg(feature = "std")]
+pub use synthetic::SyntheticVaultParams;
+#[cfg(feature = "std")]
+pub use transaction::{HeapFrameRequest, SwapInstructionBatch, TxOptions};
+#[cfg(feature = "std")]
+pub use ui::{QuoteResultUi, Rounding, UiAmount};
+#[cfg(feature = "std")]
+pub use verification::{verify_quote_via_rpc, VerificationReport};
+#[cfg(feature = "std")]
+pub use voltr_venue::{LiquidityRejection, SwapDirection, VoltrVaultVenue};
+#[cfg(feature = "std")]
+pub use warmup::{WarmupOptions, WarmupOutcome, WarmupPhase, WarmupPhaseReport, WarmupReport};
+
+/// Everything needed to load, quote and trade through a Voltr venue,
+/// including the Titan template types the venue is driven through.
+#[cfg(feature = "std")]
+pub mod prelude {
+    pub use crate::{
+        best_deposit_venue, best_redeem_venue, DetailedQuote, DirectionInfo, ExecutionReport,
+        FeeComponent, FeeDestination, FeeKind, HealthReport, HeapFrameRequest, LiquidityRejection,
+        LpMintAuthorityMismatch, LpSupplyBreakdown, MintMetadata, PendingCrank,
+        PreconditionFailure, PreflightReport, QuoteClock, QuoteDecay, QuoteErrorBound,
+        QuoteResultMeta, QuoteResultUi, RequiredAccount, Rounding, SlotAwareAccountsCache,
+        SnapshotOverrides, SwapBalances, SwapDirection, SwapInstructionBatch, SyntheticVaultParams,
+        TxOptions, UiAmount, UserAccountRole, Vault, VaultDecodeError, VenueStatus, VenueSummary,
+        VoltrError, VoltrVaultVenue, VoltrVaultVenueBuilder, VoltrVenueConfig, WarmupOptions,
+        WarmupReport, QUOTE_SEMANTICS_VERSION, VOLTR_VAULT_PROGRAM,
+    };
+
+    pub use titan_integration_template::{
+        account_caching::AccountsCache,
+        trading_venue::{
+            error::TradingVenueError, AddressLookupTableTrait, FromAccount, QuoteRequest,
+            QuoteResult, SwapType, TradingVenue,
+        },
+    };
+}
@@ -0,0 +1,246 @@
+//! Executing swaps against the Voltr program in an embedded LiteSVM, to
+//! check a quote before signing a large order.
+//!
+//! The program binary from `programs/voltr_vault.so` is compiled in. The
+//! SVM only holds what is loaded into it: the accounts of the swap
+//! instruction come from an `AccountsCache`, and the user's wallet and token
+//! accounts are synthetic, so the simulation needs neither the user's funds
+//! nor an RPC connection beyond what the cache serves.
+
+use litesvm::{error::LiteSVMError, LiteSVM};
+use solana_account::{Account, ReadableAccount};
+use solana_compute_budget::compute_budget::ComputeBudget;
+use solana_program::native_token::LAMPORTS_PER_SOL;
+use solana_program_pack::Pack;
+use solana_pubkey::Pubkey;
+use solana_sdk::{clock::Clock, message::Message, transaction::Transaction};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_token::state::Account as TokenAccount;
+
+use titan_integration_template::{
+    account_caching::AccountsCache,
+    trading_venue::{error::TradingVenueError, QuoteRequest, TradingVenue},
+};
+
+use crate::{execution::SwapBalances, synthetic::token_account, voltr_venue::VoltrVaultVenue};
+
+/// The Voltr vault program the simulations run.
+pub const VOLTR_VAULT_PROGRAM_SO: &[u8] = include_bytes!("../programs/voltr_vault.so");
+
+/// Compute unit limit of simulated transactions: the most a transaction
+/// can request, so the limit never decides the outcome.
+const SIM_COMPUTE_UNIT_LIMIT: u64 = 1_400_000;
+
+/// Lamports given to a wallet the SVM does not hold yet.
+const SIM_WALLET_LAMPORTS: u64 = 10_000 * LAMPORTS_PER_SOL;
+
+/// One swap executed by `simulate_swap()`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SwapSimulation {
+    /// The user's balances the swap started from.
+    pub pre: SwapBalances,
+    /// The user's balances after it; `pre` when it failed.
+    pub post: SwapBalances,
+    pub compute_units: u64,
+    pub logs: Vec<String>,
+    /// The transaction error when the program rejected the swap.
+    pub error: Option<String>,
+}
+
+/// What executing a swap in the embedded SVM returned, from
+/// `VoltrVaultVenue::validate_by_simulation()`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SimValidation {
+    /// Output the venue quotes for the request.
+    pub quoted_output: u64,
+    /// Rise in the user's output balance; 0 when the swap failed.
+    pub simulated_output: u64,
+    pub compute_units: u64,
+    /// Program logs, with the failure reason when `error` is set.
+    pub logs: Vec<String>,
+    /// The transaction error when the program rejected the swap.
+    pub error: Option<String>,
+}
+
+impl SimValidation {
+    /// Whether the swap executed for at least the quote less `tolerance`.
+    pub fn within_tolerance(&self, tolerance: u64) -> bool {
+        self.error.is_none()
+            && self.simulated_output >= self.quoted_output.saturating_sub(tolerance)
+    }
+}
+
+fn svm_error(e: LiteSVMError) -> TradingVenueError {
+    TradingVenueError::AmmMethodError(e.to_string().into())
+}
+
+/// LiteSVM running the embedded program at `program_id`, with the largest
+/// compute budget and neither signatures nor blockhashes checked.
+pub fn program_svm(program_id: &Pubkey) -> LiteSVM {
+    let mut svm = LiteSVM::new()
+        .with_compute_budget(ComputeBudget {
+            compute_unit_limit: SIM_COMPUTE_UNIT_LIMIT,
+            ..Default::default()
+        })
+        .with_blockhash_check(false)
+        .with_sigverify(false)
+        .with_transaction_history(0);
+    svm.add_program(*program_id, VOLTR_VAULT_PROGRAM_SO);
+    svm
+}
+
+/// Set the SVM clock to `unix_timestamp`, as the program reads it for fee
+/// accrual and locked profit.
+pub fn set_unix_timestamp(svm: &mut LiteSVM, unix_timestamp: u64) {
+    let mut clock = svm.get_sysvar::<Clock>();
+    clock.unix_timestamp = unix_timestamp as i64;
+    svm.set_sysvar(&clock);
+}
+
+/// Copy `pubkeys` from `cache` into `svm`, skipping missing and executable
+/// accounts: programs are the SVM's own.
+pub async fn load_accounts(
+    svm: &mut LiteSVM,
+    cache: &dyn AccountsCache,
+    pubkeys: &[Pubkey],
+) -> Result<(), TradingVenueError> {
+    let accounts = cache.get_accounts(pubkeys).await?;
+    for (pubkey, account) in pubkeys.iter().zip(accounts) {
+        if let Some(account) = account.filter(|account| !account.executable) {
+            svm.set_account(*pubkey, account).map_err(svm_error)?;
+        }
+    }
+    Ok(())
+}
+
+/// Execute `request` through `venue` for `user` in `svm`.
+///
+/// The accounts of the swap instruction are loaded from `cache`. The user's
+/// input and output ATAs are then replaced by ones holding `input_balance`
+/// and nothing, and the wallet is funded unless `svm` already holds it.
+/// The transaction is left unsigned.
+///
+/// Fails when the instruction cannot be built or its accounts fetched; a
+/// swap the program rejects is a `SwapSimulation` with `error` set.
+pub async fn simulate_swap(
+    svm: &mut LiteSVM,
+    venue: &dyn TradingVenue,
+    cache: &dyn AccountsCache,
+    request: QuoteRequest,
+    user: &Pubkey,
+    input_balance: u64,
+) -> Result<SwapSimulation, TradingVenueError> {
+    let token_info = |mint: &Pubkey| {
+        venue
+            .get_token_info()
+            .iter()
+            .find(|info| info.pubkey == *mint)
+            .map(|info| info.get_token_program())
+            .ok_or_else(|| {
+                TradingVenueError::AmmMethodError(format!("Mint {mint} not traded").into())
+            })
+    };
+    let input_program = token_info(&request.input_mint)?;
+    let output_program = token_info(&request.output_mint)?;
+    let input_ata =
+        get_associated_token_address_with_program_id(user, &request.input_mint, &input_program);
+    let output_ata =
+        get_associated_token_address_with_program_id(user, &request.output_mint, &output_program);
+    let (input_mint, output_mint) = (request.input_mint, request.output_mint);
+
+    let instruction = venue.generate_swap_instruction(request, *user)?;
+    let pubkeys: Vec<Pubkey> = instruction
+        .accounts
+        .iter()
+        .map(|meta| meta.pubkey)
+        .collect();
+    load_accounts(svm, cache, &pubkeys).await?;
+
+    if svm.get_account(user).is_none() {
+        let wallet = Account {
+            lamports: SIM_WALLET_LAMPORTS,
+            owner: solana_sdk::system_program::id(),
+            ..Default::default()
+        };
+        svm.set_account(*user, wallet).map_err(svm_error)?;
+    }
+    let pre = SwapBalances {
+        input: input_balance,
+        output: 0,
+    };
+    svm.set_account(
+        input_ata,
+        token_account(&input_mint, user, &input_program, pre.input),
+    )
+    .map_err(svm_error)?;
+    svm.set_account(
+        output_ata,
+        token_account(&output_mint, user, &output_program, pre.output),
+    )
+    .map_err(svm_error)?;
+
+    let tx = Transaction::new_unsigned(Message::new(&[instruction], Some(user)));
+    let (meta, result) = match svm.simulate_transaction(tx) {
+        Ok(info) => (info.meta, Ok(info.post_accounts)),
+        Err(failed) => (failed.meta, Err(failed.err.to_string())),
+    };
+    let (post, error) = match result {
+        Ok(post_accounts) => {
+            let balance = |ata: &Pubkey| {
+                post_accounts
+                    .iter()
+                    .find(|(pubkey, _)| pubkey == ata)
+                    .and_then(|(_, account)| TokenAccount::unpack_from_slice(account.data()).ok())
+                    .map(|token| token.amount)
+                    .ok_or_else(|| {
+                        TradingVenueError::AmmMethodError(
+                            format!("Token account {ata} missing after the swap").into(),
+                        )
+                    })
+            };
+            let post = SwapBalances {
+                input: balance(&input_ata)?,
+                output: balance(&output_ata)?,
+            };
+            (post, None)
+        }
+        Err(error) => (pre, Some(error)),
+    };
+    Ok(SwapSimulation {
+        pre,
+        post,
+        compute_units: meta.compute_units_consumed,
+        logs: meta.logs,
+        error,
+    })
+}
+
+impl VoltrVaultVenue {
+    /// Execute `request` for `user` against the vault program in a fresh
+    /// embedded LiteSVM at `effective_ts()`, with the instruction accounts
+    /// from `cache`, next to the quote for it.
+    ///
+    /// The user's balances are synthetic: exactly the input amount, and no
+    /// output. Gate large orders on `within_tolerance()` of the result.
+    pub async fn validate_by_simulation(
+        &self,
+        request: &QuoteRequest,
+        user: &Pubkey,
+        cache: &dyn AccountsCache,
+    ) -> Result<SimValidation, TradingVenueError> {
+        let quote = self.quote(request.clone())?;
+
+        let mut svm = program_svm(&self.program_id());
+        set_unix_timestamp(&mut svm, self.effective_ts());
+        let sim =
+            simulate_swap(&mut svm, self, cache, request.clone(), user, request.amount).await?;
+
+        Ok(SimValidation {
+            quoted_output: quote.expected_output,
+            simulated_output: sim.post.output.saturating_sub(sim.pre.output),
+            compute_units: sim.compute_units,
+            logs: sim.logs,
+            error: sim.error,
+        })
+    }
+}
@@ -0,0 +1,254 @@
+//! Events the vault program emits on deposits and instant withdrawals,
+//! decoded from transaction logs for post-trade analytics.
+//!
+//! Each event carries the amounts the program actually moved and the vault
+//! totals around the swap, so executions can be reconciled from the chain
+//! instead of from balance diffs.
+
+use base64::Engine;
+use solana_pubkey::Pubkey;
+
+use titan_integration_template::trading_venue::TradingVenue;
+
+use crate::{
+    constants::VOLTR_VAULT_PROGRAM,
+    discriminators,
+    state::{bytes_at, pubkey_at, u128_at, u64_at, u8_at},
+    voltr_venue::VoltrVaultVenue,
+};
+
+const PROGRAM_DATA_PREFIX: &str = "Program data: ";
+
+/// Serialized `DepositVaultEvent` fields, after the discriminator. Fields a
+/// later program version appends are ignored.
+const DEPOSIT_EVENT_LEN: usize = 184;
+/// Serialized `InstantWithdrawVaultEvent` fields, after the discriminator.
+const INSTANT_WITHDRAW_EVENT_LEN: usize = 202;
+
+/// A `deposit_vault` as recorded by the program.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DepositVaultEvent {
+    pub user: Pubkey,
+    /// Asset taken from the user, issuance fee included.
+    pub asset_deposited: u64,
+    /// LP minted to the user.
+    pub lp_minted: u64,
+    pub vault: Pubkey,
+    pub asset_mint: Pubkey,
+    pub total_value_before: u64,
+    pub total_value_after: u64,
+    /// LP supply including fee LP not minted yet, as
+    /// `Vault::get_total_lp_supply_incl_fees` counts it.
+    pub lp_supply_before: u64,
+    pub lp_supply_after: u64,
+    pub highest_asset_per_lp_decimal_bits_before: u128,
+    pub highest_asset_per_lp_decimal_bits_after: u128,
+    pub timestamp: i64,
+}
+
+impl DepositVaultEvent {
+    /// Fee LP the deposit recorded on top of the user's: the management
+    /// fee it settled.
+    pub fn fee_lp_accrued(&self) -> u64 {
+        self.lp_supply_after
+            .saturating_sub(self.lp_supply_before)
+            .saturating_sub(self.lp_minted)
+    }
+
+    fn decode(data: &[u8]) -> Option<Self> {
+        if data.len() < DEPOSIT_EVENT_LEN {
+            return None;
+        }
+        Some(Self {
+            user: pubkey_at(data, 0).ok()?,
+            asset_deposited: u64_at(data, 32).ok()?,
+            lp_minted: u64_at(data, 40).ok()?,
+            vault: pubkey_at(data, 48).ok()?,
+            asset_mint: pubkey_at(data, 80).ok()?,
+            total_value_before: u64_at(data, 112).ok()?,
+            total_value_after: u64_at(data, 120).ok()?,
+            lp_supply_before: u64_at(data, 128).ok()?,
+            lp_supply_after: u64_at(data, 136).ok()?,
+            highest_asset_per_lp_decimal_bits_before: u128_at(data, 144).ok()?,
+            highest_asset_per_lp_decimal_bits_after: u128_at(data, 160).ok()?,
+            timestamp: bytes_at(data, 176).map(i64::from_le_bytes).ok()?,
+        })
+    }
+}
+
+/// An `instant_withdraw_vault` as recorded by the program.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InstantWithdrawVaultEvent {
+    pub user: Pubkey,
+    /// The instruction's amount argument, in LP when `is_amount_in_lp`.
+    pub amount: u64,
+    pub is_amount_in_lp: bool,
+    pub is_withdraw_all: bool,
+    /// Asset paid out to the user, after the redemption fee.
+    pub asset_withdrawn: u64,
+    /// LP burned from the user.
+    pub lp_burned: u64,
+    pub vault: Pubkey,
+    pub asset_mint: Pubkey,
+    /// Total value minus the profit still locked, which the payout was
+    /// priced on.
+    pub unlocked_value_before: u64,
+    pub total_value_before: u64,
+    pub total_value_after: u64,
+    /// LP supply including fee LP not minted yet, as
+    /// `Vault::get_total_lp_supply_incl_fees` counts it.
+    pub lp_supply_before: u64,
+    pub lp_supply_after: u64,
+    pub highest_asset_per_lp_decimal_bits_before: u128,
+    pub highest_asset_per_lp_decimal_bits_after: u128,
+    pub timestamp: i64,
+}
+
+impl InstantWithdrawVaultEvent {
+    /// Fee LP the withdrawal recorded: the management fee it settled.
+    pub fn fee_lp_accrued(&self) -> u64 {
+        (self.lp_supply_after as u128 + self.lp_burned as u128)
+            .saturating_sub(self.lp_supply_before as u128)
+            .try_into()
+            .unwrap_or(u64::MAX)
+    }
+
+    fn decode(data: &[u8]) -> Option<Self> {
+        if data.len() < INSTANT_WITHDRAW_EVENT_LEN {
+            return None;
+        }
+        let flag = |offset| match u8_at(data, offset).ok()? {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        };
+        Some(Self {
+            user: pubkey_at(data, 0).ok()?,
+            amount: u64_at(data, 32).ok()?,
+            is_amount_in_lp: flag(40)?,
+            is_withdraw_all: flag(41)?,
+            asset_withdrawn: u64_at(data, 42).ok()?,
+            lp_burned: u64_at(data, 50).ok()?,
+            vault: pubkey_at(data, 58).ok()?,
+            asset_mint: pubkey_at(data, 90).ok()?,
+            unlocked_value_before: u64_at(data, 122).ok()?,
+            total_value_before: u64_at(data, 130).ok()?,
+            total_value_after: u64_at(data, 138).ok()?,
+            lp_supply_before: u64_at(data, 146).ok()?,
+            lp_supply_after: u64_at(data, 154).ok()?,
+            highest_asset_per_lp_decimal_bits_before: u128_at(data, 162).ok()?,
+            highest_asset_per_lp_decimal_bits_after: u128_at(data, 178).ok()?,
+            timestamp: bytes_at(data, 194).map(i64::from_le_bytes).ok()?,
+        })
+    }
+}
+
+/// An event of the vault program found in transaction logs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VoltrEvent {
+    Deposit(DepositVaultEvent),
+    InstantWithdraw(InstantWithdrawVaultEvent),
+}
+
+impl VoltrEvent {
+    /// The vault the event happened on.
+    pub fn vault(&self) -> Pubkey {
+        match self {
+            VoltrEvent::Deposit(event) => event.vault,
+            VoltrEvent::InstantWithdraw(event) => event.vault,
+        }
+    }
+
+    pub fn user(&self) -> Pubkey {
+        match self {
+            VoltrEvent::Deposit(event) => event.user,
+            VoltrEvent::InstantWithdraw(event) => event.user,
+        }
+    }
+
+    /// Decode an event from its discriminator and Borsh-serialized fields,
+    /// `None` for other events and malformed payloads. Bytes past the known
+    /// fields are ignored.
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        let (discriminator, fields) = data.split_first_chunk::<8>()?;
+        if *discriminator == discriminators::deposit_vault_event() {
+            DepositVaultEvent::decode(fields).map(VoltrEvent::Deposit)
+        } else if *discriminator == discriminators::instant_withdraw_vault_event() {
+            InstantWithdrawVaultEvent::decode(fields).map(VoltrEvent::InstantWithdraw)
+        } else {
+            None
+        }
+    }
+}
+
+/// Events the vault program at `VOLTR_VAULT_PROGRAM` emitted in `logs`, in
+/// order. See [`parse_program_logs`].
+pub fn parse_transaction_logs(logs: &[String]) -> Vec<VoltrEvent> {
+    parse_program_logs(logs, &VOLTR_VAULT_PROGRAM)
+}
+
+/// Events `program_id` emitted in `logs`, the log messages of one
+/// transaction, in order.
+///
+/// `Program data:` lines are attributed to the program executing when they
+/// were logged, following the `invoke` and `success`/`failed` lines, so data
+/// logged by other programs is never mistaken for an event. Lines that do
+/// not decode are skipped. A failed transaction's logs still list the
+/// events emitted before the failure, although their effects were rolled
+/// back, so only pass the logs of successful transactions.
+pub fn parse_program_logs(logs: &[String], program_id: &Pubkey) -> Vec<VoltrEvent> {
+    let program_id = program_id.to_string();
+    let mut stack: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+    for line in logs {
+        if let Some(data) = line.strip_prefix(PROGRAM_DATA_PREFIX) {
+            if stack.last() == Some(&program_id.as_str()) {
+                events.extend(
+                    decode_program_data(data)
+                        .as_deref()
+                        .and_then(VoltrEvent::decode),
+                );
+            }
+            continue;
+        }
+        let Some(rest) = line.strip_prefix("Program ") else {
+            continue;
+        };
+        let mut words = rest.split(' ');
+        let (Some(program), Some(status)) = (words.next(), words.next()) else {
+            continue;
+        };
+        if status == "invoke" {
+            stack.push(program);
+        } else if (status == "success" || status.starts_with("failed"))
+            && stack.last() == Some(&program)
+        {
+            stack.pop();
+        }
+    }
+    events
+}
+
+/// The bytes of a `Program data:` line, whose fields are base64-encoded and
+/// separated by spaces.
+fn decode_program_data(data: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    for field in data.split_whitespace() {
+        bytes.extend(
+            base64::engine::general_purpose::STANDARD
+                .decode(field)
+                .ok()?,
+        );
+    }
+    Some(bytes)
+}
+
+impl VoltrVaultVenue {
+    /// Events of this vault in `logs`, emitted by the configured program.
+    /// See [`parse_program_logs`].
+    pub fn vault_events(&self, logs: &[String]) -> Vec<VoltrEvent> {
+        let mut events = parse_program_logs(logs, &self.program_id());
+        events.retain(|event| event.vault() == self.vault_key);
+        events
+    }
+}
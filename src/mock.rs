@@ -0,0 +1,150 @@
+//! A Voltr venue with scriptable behaviour, for testing routers built on
+//! the Titan template without chain state.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use solana_instruction::Instruction;
+use solana_pubkey::Pubkey;
+
+use titan_integration_template::{
+    account_caching::AccountsCache,
+    trading_venue::{
+        error::TradingVenueError, protocol::PoolProtocol, token_info::TokenInfo,
+        AddressLookupTableTrait, QuoteRequest, QuoteResult, TradingVenue,
+    },
+};
+
+use crate::voltr_venue::VoltrVaultVenue;
+
+type QuoteOverride = Arc<dyn Fn(&QuoteRequest) -> QuoteResult + Send + Sync>;
+
+/// A `VoltrVaultVenue` whose quotes, refresh failures and refresh latency
+/// can be scripted.
+///
+/// Everything not overridden, instruction generation in particular, goes to
+/// the wrapped venue, so router tests still exercise this crate's real
+/// instructions.
+pub struct MockableVoltrVenue {
+    inner: VoltrVaultVenue,
+    quote_override: Option<QuoteOverride>,
+    next_update_error: Option<TradingVenueError>,
+    latency: Duration,
+}
+
+impl MockableVoltrVenue {
+    pub fn new(inner: VoltrVaultVenue) -> Self {
+        Self {
+            inner,
+            quote_override: None,
+            next_update_error: None,
+            latency: Duration::ZERO,
+        }
+    }
+
+    pub fn inner(&self) -> &VoltrVaultVenue {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut VoltrVaultVenue {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> VoltrVaultVenue {
+        self.inner
+    }
+
+    /// Answer every `quote()` with `quote_fn` instead of the vault's state.
+    pub fn set_quote_override(
+        &mut self,
+        quote_fn: impl Fn(&QuoteRequest) -> QuoteResult + Send + Sync + 'static,
+    ) {
+        self.quote_override = Some(Arc::new(quote_fn));
+    }
+
+    pub fn clear_quote_override(&mut self) {
+        self.quote_override = None;
+    }
+
+    /// Fail the next `update_state()` with `err`, leaving the state as it
+    /// was. Later refreshes go through.
+    pub fn fail_next_update(&mut self, err: TradingVenueError) {
+        self.next_update_error = Some(err);
+    }
+
+    /// Delay every `update_state()` by `latency` before it fetches or fails.
+    pub fn set_latency(&mut self, latency: Duration) {
+        self.latency = latency;
+    }
+}
+
+#[async_trait]
+impl TradingVenue for MockableVoltrVenue {
+    fn initialized(&self) -> bool {
+        self.inner.initialized()
+    }
+
+    fn program_id(&self) -> Pubkey {
+        self.inner.program_id()
+    }
+
+    fn program_dependencies(&self) -> Vec<Pubkey> {
+        self.inner.program_dependencies()
+    }
+
+    fn market_id(&self) -> Pubkey {
+        self.inner.market_id()
+    }
+
+    fn protocol(&self) -> PoolProtocol {
+        self.inner.protocol()
+    }
+
+    fn get_token_info(&self) -> &[TokenInfo] {
+        self.inner.get_token_info()
+    }
+
+    fn bounds(&self, input_idx: u8, output_idx: u8) -> Result<(u64, u64), TradingVenueError> {
+        self.inner.bounds(input_idx, output_idx)
+    }
+
+    fn get_required_pubkeys_for_update(&self) -> Result<Vec<Pubkey>, TradingVenueError> {
+        self.inner.get_required_pubkeys_for_update()
+    }
+
+    async fn update_state(&mut self, cache: &dyn AccountsCache) -> Result<(), TradingVenueError> {
+        if !self.latency.is_zero() {
+            tokio::time::sleep(self.latency).await;
+        }
+        if let Some(err) = self.next_update_error.take() {
+            return Err(err);
+        }
+        self.inner.update_state(cache).await
+    }
+
+    fn quote(&self, request: QuoteRequest) -> Result<QuoteResult, TradingVenueError> {
+        match &self.quote_override {
+            Some(quote_fn) => Ok(quote_fn(&request)),
+            None => self.inner.quote(request),
+        }
+    }
+
+    fn generate_swap_instruction(
+        &self,
+        request: QuoteRequest,
+        user: Pubkey,
+    ) -> Result<Instruction, TradingVenueError> {
+        self.inner.generate_swap_instruction(request, user)
+    }
+}
+
+#[async_trait]
+impl AddressLookupTableTrait for MockableVoltrVenue {
+    async fn get_lookup_table_keys(
+        &self,
+        accounts_cache: Option<&dyn AccountsCache>,
+    ) -> Result<Vec<Pubkey>, TradingVenueError> {
+        self.inner.get_lookup_table_keys(accounts_cache).await
+    }
+}
@@ -0,0 +1,114 @@
+//! What-if quoting against a modified copy of the loaded vault figures.
+
+use titan_integration_template::trading_venue::{
+    error::TradingVenueError, QuoteRequest, QuoteResult,
+};
+
+use crate::{core::VaultSnapshot, errors::VoltrError, voltr_venue::VoltrVaultVenue};
+
+/// Replacements for the figures of a `VaultSnapshot`; `None` keeps the loaded
+/// value.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SnapshotOverrides {
+    pub idle_balance: Option<u64>,
+    pub total_value: Option<u64>,
+    /// LP mint supply, excluding accumulated fee LP and dead weight.
+    pub lp_supply: Option<u64>,
+    pub issuance_fee_bps: Option<u16>,
+    pub redemption_fee_bps: Option<u16>,
+    /// Management fee of all parties combined.
+    pub management_fee_bps: Option<u16>,
+    pub last_management_fee_update_ts: Option<u64>,
+    pub last_report: Option<u64>,
+    /// Unix time to quote at instead of `effective_ts()`.
+    pub current_ts: Option<u64>,
+}
+
+impl SnapshotOverrides {
+    /// `snapshot` with every set override applied.
+    pub fn apply(&self, snapshot: VaultSnapshot) -> VaultSnapshot {
+        VaultSnapshot {
+            idle_balance: self.idle_balance.unwrap_or(snapshot.idle_balance),
+            total_value: self.total_value.unwrap_or(snapshot.total_value),
+            lp_supply: self.lp_supply.unwrap_or(snapshot.lp_supply),
+            issuance_fee_bps: self.issuance_fee_bps.unwrap_or(snapshot.issuance_fee_bps),
+            redemption_fee_bps: self
+                .redemption_fee_bps
+                .unwrap_or(snapshot.redemption_fee_bps),
+            management_fee_bps: self
+                .management_fee_bps
+                .unwrap_or(snapshot.management_fee_bps),
+            last_management_fee_update_ts: self
+                .last_management_fee_update_ts
+                .unwrap_or(snapshot.last_management_fee_update_ts),
+            last_report: self.last_report.unwrap_or(snapshot.last_report),
+            ..snapshot
+        }
+    }
+}
+
+/// A fee crank seen pending for the vault, described by the caller, to quote
+/// the state it leaves with `apply_pending_crank()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PendingCrank {
+    /// Management fee harvest at `ts`: the fee LP estimated to `ts` is
+    /// realized and the fee timestamp reset to `ts`.
+    ManagementFeeHarvest { ts: u64 },
+    /// Profit report at `ts`, locking `locked_profit` from then on. The
+    /// reported profit is taken to be in the total value already; set
+    /// `SnapshotOverrides::total_value` on the result when it is not.
+    ProfitReport { locked_profit: u64, ts: u64 },
+}
+
+impl PendingCrank {
+    /// `snapshot` as `self` leaves it.
+    pub fn apply(&self, snapshot: VaultSnapshot) -> Result<VaultSnapshot, VoltrError> {
+        match *self {
+            PendingCrank::ManagementFeeHarvest { ts } => {
+                let fee_lp = snapshot.pending_management_fee_lp(ts)?;
+                Ok(VaultSnapshot {
+                    accumulated_lp_fees: snapshot
+                        .accumulated_lp_fees
+                        .checked_add(fee_lp)
+                        .ok_or(VoltrError::MathOverflow)?,
+                    last_management_fee_update_ts: ts,
+                    ..snapshot
+                })
+            }
+            PendingCrank::ProfitReport { locked_profit, ts } => Ok(VaultSnapshot {
+                last_updated_locked_profit: locked_profit,
+                last_report: ts,
+                ..snapshot
+            }),
+        }
+    }
+}
+
+impl VoltrVaultVenue {
+    /// `vault_snapshot()` as `crank` would leave it, for quoting the
+    /// post-crank state with `core::quote_snapshot()` before the crank
+    /// lands. The venue is not modified.
+    pub fn apply_pending_crank(
+        &self,
+        crank: PendingCrank,
+    ) -> Result<VaultSnapshot, TradingVenueError> {
+        Ok(crank.apply(self.vault_snapshot()?)?)
+    }
+
+    /// Quote `request` as if the vault's figures were replaced by
+    /// `overrides`, e.g. to ask what a redeem would pay with twice the idle
+    /// balance.
+    ///
+    /// The overrides apply to a copy of `vault_snapshot()`; the venue is not
+    /// modified and nothing is fetched. With no override set the result is
+    /// that of `quote()`.
+    pub fn quote_with_overrides(
+        &self,
+        request: &QuoteRequest,
+        overrides: &SnapshotOverrides,
+    ) -> Result<QuoteResult, TradingVenueError> {
+        let current_ts = overrides.current_ts.unwrap_or_else(|| self.effective_ts());
+        self.quote_overridden(request, overrides, current_ts)
+            .map(|(quote, _)| quote)
+    }
+}
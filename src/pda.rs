@@ -0,0 +1,217 @@
+//! Program-derived addresses of a Voltr vault program deployment.
+//!
+//! Every function takes the program id explicitly so devnet or forked
+//! deployments derive their own addresses; pass `VOLTR_VAULT_PROGRAM` for
+//! mainnet.
+//!
+//! Derivation is pure but costs a few hash rounds per bump tried, so
+//! `VaultPdas::for_vault()` memoizes it process-wide; venues built by the
+//! thousand for one program then derive each vault's PDAs once.
+//!
+//! The free functions take both keys as bare `Pubkey`s, so swapping them
+//! compiles and silently derives another address. The methods of
+//! [`VaultKey`] and [`ProgramId`] derive the same addresses with the roles
+//! in the types:
+//!
+//! ```
+//! use solana_pubkey::Pubkey;
+//! use titan_voltr_integration::pda::{self, ProgramId, VaultKey};
+//!
+//! let vault = VaultKey::from(Pubkey::new_unique());
+//! let lp_mint = vault.lp_mint(&ProgramId::MAINNET);
+//! assert_eq!(lp_mint, pda::vault_lp_mint_pda(&vault, &ProgramId::MAINNET));
+//! ```
+//!
+//! Passing the program where the vault goes no longer compiles:
+//!
+//! ```compile_fail
+//! # use titan_voltr_integration::pda::{ProgramId, VaultKey};
+//! # let vault = VaultKey::default();
+//! let pdas = vault.pdas(&vault);
+//! ```
+//!
+//! ```compile_fail
+//! # use solana_pubkey::Pubkey;
+//! # use titan_voltr_integration::pda::VaultKey;
+//! # let vault = VaultKey::default();
+//! let lp_mint = vault.lp_mint(&Pubkey::new_unique());
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::{OnceLock, RwLock};
+
+use solana_pubkey::Pubkey;
+
+use crate::constants::{
+    PROTOCOL_SEED, VAULT_ASSET_IDLE_AUTH_SEED, VAULT_LP_MINT_AUTH_SEED, VAULT_LP_MINT_SEED,
+    VOLTR_VAULT_PROGRAM,
+};
+
+/// Address of a Voltr `Vault` account.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct VaultKey(pub Pubkey);
+
+/// Id of a Voltr vault program deployment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ProgramId(pub Pubkey);
+
+impl VaultKey {
+    pub fn lp_mint(&self, program_id: &ProgramId) -> Pubkey {
+        Pubkey::find_program_address(&[VAULT_LP_MINT_SEED, self.as_ref()], program_id).0
+    }
+
+    pub fn lp_mint_auth(&self, program_id: &ProgramId) -> Pubkey {
+        Pubkey::find_program_address(&[VAULT_LP_MINT_AUTH_SEED, self.as_ref()], program_id).0
+    }
+
+    pub fn asset_idle_auth(&self, program_id: &ProgramId) -> Pubkey {
+        Pubkey::find_program_address(&[VAULT_ASSET_IDLE_AUTH_SEED, self.as_ref()], program_id).0
+    }
+
+    /// Every PDA of this vault, through the cache of `VaultPdas::for_vault()`.
+    pub fn pdas(&self, program_id: &ProgramId) -> VaultPdas {
+        VaultPdas::for_vault(self, program_id)
+    }
+}
+
+impl ProgramId {
+    /// The mainnet deployment, `VOLTR_VAULT_PROGRAM`.
+    pub const MAINNET: Self = ProgramId(VOLTR_VAULT_PROGRAM);
+
+    pub fn protocol(&self) -> Pubkey {
+        Pubkey::find_program_address(&[PROTOCOL_SEED], self).0
+    }
+}
+
+impl Default for ProgramId {
+    fn default() -> Self {
+        Self::MAINNET
+    }
+}
+
+macro_rules! pubkey_newtype {
+    ($name:ident) => {
+        impl From<Pubkey> for $name {
+            fn from(pubkey: Pubkey) -> Self {
+                $name(pubkey)
+            }
+        }
+
+        impl From<$name> for Pubkey {
+            fn from(key: $name) -> Self {
+                key.0
+            }
+        }
+
+        impl Deref for $name {
+            type Target = Pubkey;
+
+            fn deref(&self) -> &Pubkey {
+                &self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+    };
+}
+
+pubkey_newtype!(VaultKey);
+pubkey_newtype!(ProgramId);
+
+/// Untyped [`ProgramId::protocol`].
+pub fn protocol_pda(program_id: &Pubkey) -> Pubkey {
+    ProgramId(*program_id).protocol()
+}
+
+/// Untyped [`VaultKey::lp_mint`].
+pub fn vault_lp_mint_pda(vault_key: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    VaultKey(*vault_key).lp_mint(&ProgramId(*program_id))
+}
+
+/// Untyped [`VaultKey::lp_mint_auth`].
+pub fn vault_lp_mint_auth_pda(vault_key: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    VaultKey(*vault_key).lp_mint_auth(&ProgramId(*program_id))
+}
+
+/// Untyped [`VaultKey::asset_idle_auth`].
+pub fn vault_asset_idle_auth_pda(vault_key: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    VaultKey(*vault_key).asset_idle_auth(&ProgramId(*program_id))
+}
+
+/// Vault PDA bundles `VaultPdas::for_vault()` keeps before starting over;
+/// an entry is 160 bytes of keys plus map overhead.
+pub const PDA_CACHE_CAPACITY: usize = 65_536;
+
+/// `protocol_pda()` memoized per program id.
+fn cached_protocol_pda(program_id: &Pubkey) -> Pubkey {
+    static PROTOCOL_PDAS: OnceLock<RwLock<HashMap<Pubkey, Pubkey>>> = OnceLock::new();
+    let cache = PROTOCOL_PDAS.get_or_init(Default::default);
+    if let Some(protocol) = cache
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(program_id)
+    {
+        return *protocol;
+    }
+    let protocol = protocol_pda(program_id);
+    cache
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(*program_id, protocol);
+    protocol
+}
+
+/// Every PDA the swap instructions reference for one vault.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VaultPdas {
+    pub protocol: Pubkey,
+    pub lp_mint: Pubkey,
+    pub lp_mint_auth: Pubkey,
+    pub asset_idle_auth: Pubkey,
+}
+
+impl VaultPdas {
+    pub fn derive(vault_key: &Pubkey, program_id: &Pubkey) -> Self {
+        let (vault, program) = (VaultKey(*vault_key), ProgramId(*program_id));
+        Self {
+            protocol: program.protocol(),
+            lp_mint: vault.lp_mint(&program),
+            lp_mint_auth: vault.lp_mint_auth(&program),
+            asset_idle_auth: vault.asset_idle_auth(&program),
+        }
+    }
+
+    /// `derive()` through a process-wide cache keyed by vault and program.
+    ///
+    /// Safe to call from any thread. Once `PDA_CACHE_CAPACITY` vaults are
+    /// cached the cache is cleared, bounding its memory at the cost of
+    /// re-deriving the vaults still in use.
+    pub fn for_vault(vault_key: &Pubkey, program_id: &Pubkey) -> Self {
+        static VAULT_PDAS: OnceLock<RwLock<HashMap<(Pubkey, Pubkey), VaultPdas>>> = OnceLock::new();
+        let cache = VAULT_PDAS.get_or_init(Default::default);
+        let key = (*vault_key, *program_id);
+        if let Some(pdas) = cache.read().unwrap_or_else(|e| e.into_inner()).get(&key) {
+            return *pdas;
+        }
+
+        let (vault, program) = (VaultKey(*vault_key), ProgramId(*program_id));
+        let pdas = Self {
+            protocol: cached_protocol_pda(program_id),
+            lp_mint: vault.lp_mint(&program),
+            lp_mint_auth: vault.lp_mint_auth(&program),
+            asset_idle_auth: vault.asset_idle_auth(&program),
+        };
+        let mut cache = cache.write().unwrap_or_else(|e| e.into_inner());
+        if cache.len() >= PDA_CACHE_CAPACITY {
+            cache.clear();
+        }
+        cache.insert(key, pdas);
+        pdas
+    }
+}
@@ -0,0 +1,218 @@
+//! Risk policies deciding which requests a venue may quote and trade.
+
+use std::collections::HashSet;
+
+use async_trait::async_trait;
+use solana_instruction::Instruction;
+use solana_pubkey::Pubkey;
+use thiserror::Error;
+
+use titan_integration_template::{
+    account_caching::AccountsCache,
+    trading_venue::{
+        error::TradingVenueError, protocol::PoolProtocol, token_info::TokenInfo,
+        AddressLookupTableTrait, QuoteRequest, QuoteResult, TradingVenue,
+    },
+};
+
+use crate::{
+    errors::VoltrError,
+    voltr_venue::{SwapDirection, VoltrVaultVenue},
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PolicyDecision {
+    Allow,
+    /// Refuse the request, with a reason for the error message.
+    Deny(String),
+}
+
+/// Decides whether `venue` may quote and trade `request`.
+pub trait QuotePolicy: Send + Sync {
+    fn allow(&self, venue: &VoltrVaultVenue, request: &QuoteRequest) -> PolicyDecision;
+}
+
+/// Allow only vaults whose asset mint is in the set.
+#[derive(Clone, Debug, Default)]
+pub struct MintAllowlist(pub HashSet<Pubkey>);
+
+impl QuotePolicy for MintAllowlist {
+    fn allow(&self, venue: &VoltrVaultVenue, _request: &QuoteRequest) -> PolicyDecision {
+        let asset_mint = venue.vault_state.asset.mint;
+        if self.0.contains(&asset_mint) {
+            PolicyDecision::Allow
+        } else {
+            PolicyDecision::Deny(format!("asset mint {asset_mint} is not allowlisted"))
+        }
+    }
+}
+
+/// Cap the fee a swap may pay: the issuance fee for deposits and the
+/// redemption fee for redeems.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MaxFeeBps {
+    pub issuance: u16,
+    pub redemption: u16,
+}
+
+impl QuotePolicy for MaxFeeBps {
+    fn allow(&self, venue: &VoltrVaultVenue, request: &QuoteRequest) -> PolicyDecision {
+        let fees = &venue.vault_state.fee_configuration;
+        // Requests for other mints are left for the venue to reject.
+        let (name, fee, max) = match venue.direction_for(request) {
+            Ok(SwapDirection::Deposit) => ("issuance", fees.issuance_fee, self.issuance),
+            Ok(SwapDirection::Redeem) => ("redemption", fees.redemption_fee, self.redemption),
+            Err(_) => return PolicyDecision::Allow,
+        };
+        if fee > max {
+            PolicyDecision::Deny(format!("{name} fee {fee} bps exceeds the {max} bps cap"))
+        } else {
+            PolicyDecision::Allow
+        }
+    }
+}
+
+/// Why a `PolicyVenue` did not quote or build a request.
+///
+/// Through `TradingVenue` a denial becomes a `TradingVenueError` carrying
+/// `VoltrError::PolicyDenied` in its message; callers telling denials apart
+/// from liquidity or state problems use `try_quote()` and
+/// `try_generate_swap_instruction()` instead.
+#[derive(Error, Debug)]
+pub enum PolicyVenueError {
+    #[error("{}: {0}", VoltrError::PolicyDenied)]
+    Denied(String),
+
+    #[error(transparent)]
+    Venue(#[from] TradingVenueError),
+}
+
+impl PolicyVenueError {
+    pub fn is_denial(&self) -> bool {
+        matches!(self, PolicyVenueError::Denied(_))
+    }
+}
+
+impl From<PolicyVenueError> for TradingVenueError {
+    fn from(e: PolicyVenueError) -> Self {
+        match e {
+            PolicyVenueError::Denied(_) => TradingVenueError::AmmMethodError(e.to_string().into()),
+            PolicyVenueError::Venue(e) => e,
+        }
+    }
+}
+
+/// A venue that consults `policy` before quoting or building a swap, failing
+/// denied requests with `VoltrError::PolicyDenied` rather than returning an
+/// empty quote.
+pub struct PolicyVenue<P: QuotePolicy> {
+    inner: VoltrVaultVenue,
+    policy: P,
+}
+
+impl<P: QuotePolicy> PolicyVenue<P> {
+    pub fn new(inner: VoltrVaultVenue, policy: P) -> Self {
+        Self { inner, policy }
+    }
+
+    pub fn inner(&self) -> &VoltrVaultVenue {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut VoltrVaultVenue {
+        &mut self.inner
+    }
+
+    pub fn policy(&self) -> &P {
+        &self.policy
+    }
+
+    pub fn into_inner(self) -> VoltrVaultVenue {
+        self.inner
+    }
+
+    fn check(&self, request: &QuoteRequest) -> Result<(), PolicyVenueError> {
+        match self.policy.allow(&self.inner, request) {
+            PolicyDecision::Allow => Ok(()),
+            PolicyDecision::Deny(reason) => Err(PolicyVenueError::Denied(reason)),
+        }
+    }
+
+    /// `quote()`, with a denial kept apart from the venue's own errors.
+    pub fn try_quote(&self, request: QuoteRequest) -> Result<QuoteResult, PolicyVenueError> {
+        self.check(&request)?;
+        Ok(self.inner.quote(request)?)
+    }
+
+    /// `generate_swap_instruction()`, with a denial kept apart from the
+    /// venue's own errors.
+    pub fn try_generate_swap_instruction(
+        &self,
+        request: QuoteRequest,
+        user: Pubkey,
+    ) -> Result<Instruction, PolicyVenueError> {
+        self.check(&request)?;
+        Ok(self.inner.generate_swap_instruction(request, user)?)
+    }
+}
+
+#[async_trait]
+impl<P: QuotePolicy> TradingVenue for PolicyVenue<P> {
+    fn initialized(&self) -> bool {
+        self.inner.initialized()
+    }
+
+    fn program_id(&self) -> Pubkey {
+        self.inner.program_id()
+    }
+
+    fn program_dependencies(&self) -> Vec<Pubkey> {
+        self.inner.program_dependencies()
+    }
+
+    fn market_id(&self) -> Pubkey {
+        self.inner.market_id()
+    }
+
+    fn protocol(&self) -> PoolProtocol {
+        self.inner.protocol()
+    }
+
+    fn get_token_info(&self) -> &[TokenInfo] {
+        self.inner.get_token_info()
+    }
+
+    fn bounds(&self, input_idx: u8, output_idx: u8) -> Result<(u64, u64), TradingVenueError> {
+        self.inner.bounds(input_idx, output_idx)
+    }
+
+    fn get_required_pubkeys_for_update(&self) -> Result<Vec<Pubkey>, TradingVenueError> {
+        self.inner.get_required_pubkeys_for_update()
+    }
+
+    async fn update_state(&mut self, cache: &dyn AccountsCache) -> Result<(), TradingVenueError> {
+        self.inner.update_state(cache).await
+    }
+
+    fn quote(&self, request: QuoteRequest) -> Result<QuoteResult, TradingVenueError> {
+        Ok(self.try_quote(request)?)
+    }
+
+    fn generate_swap_instruction(
+        &self,
+        request: QuoteRequest,
+        user: Pubkey,
+    ) -> Result<Instruction, TradingVenueError> {
+        Ok(self.try_generate_swap_instruction(request, user)?)
+    }
+}
+
+#[async_trait]
+impl<P: QuotePolicy> AddressLookupTableTrait for PolicyVenue<P> {
+    async fn get_lookup_table_keys(
+        &self,
+        accounts_cache: Option<&dyn AccountsCache>,
+    ) -> Result<Vec<Pubkey>, TradingVenueError> {
+        self.inner.get_lookup_table_keys(accounts_cache).await
+    }
+}
@@ -0,0 +1,213 @@
+use solana_account::Account;
+use solana_program::program_error::ProgramError;
+use solana_program_pack::Pack;
+use solana_pubkey::Pubkey;
+use spl_token_2022::extension::StateWithExtensions;
+
+use titan_integration_template::{
+    account_caching::AccountsCache,
+    trading_venue::{error::TradingVenueError, QuoteRequest},
+};
+
+use crate::{
+    constants::{TOKEN_22_PROGRAM, TOKEN_PROGRAM},
+    voltr_venue::{SwapDirection, VoltrVaultVenue},
+};
+
+/// Role a user token account plays in a swap.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UserAccountRole {
+    /// Asset ATA debited by a deposit.
+    SourceAssetAta,
+    /// LP ATA credited by a deposit.
+    DestinationLpAta,
+    /// LP ATA debited by a redeem.
+    SourceLpAta,
+    /// Asset ATA credited by a redeem.
+    DestinationAssetAta,
+}
+
+/// A user token account the swap instruction reads from or writes to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RequiredAccount {
+    pub pubkey: Pubkey,
+    pub role: UserAccountRole,
+    pub mint: Pubkey,
+    pub token_program: Pubkey,
+    /// Whether the swap instruction creates the account when it is missing.
+    pub created_if_missing: bool,
+    /// Minimum token balance the account must hold before the swap.
+    pub min_balance: u64,
+}
+
+/// A precondition that would make the swap fail on-chain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PreconditionFailure {
+    /// The account does not exist and the instruction will not create it.
+    MissingAccount {
+        pubkey: Pubkey,
+        role: UserAccountRole,
+    },
+    /// The account exists but is not a token account for the expected mint
+    /// and owner.
+    InvalidAccount {
+        pubkey: Pubkey,
+        role: UserAccountRole,
+    },
+    /// The source account holds less than the swap input.
+    InsufficientBalance {
+        pubkey: Pubkey,
+        role: UserAccountRole,
+        required: u64,
+        available: u64,
+    },
+}
+
+impl VoltrVaultVenue {
+    /// List the user token accounts a swap in `direction` touches.
+    ///
+    /// Neither `deposit_vault` nor `instant_withdraw_vault` initializes user
+    /// ATAs, so every returned account must exist before submission. `amount`
+    /// is the swap input and becomes the minimum balance of the source account.
+    ///
+    /// The wSOL ATA of a vault with `is_native_asset()` is the exception: the
+    /// instructions of `build_transaction_instructions()` create it and, on a
+    /// deposit, fund it from the user's SOL, so it needs no prior balance.
+    pub fn required_user_accounts(
+        &self,
+        user: &Pubkey,
+        direction: SwapDirection,
+        amount: u64,
+    ) -> Vec<RequiredAccount> {
+        let native = self.is_native_asset();
+        let info = self.direction_info(direction);
+        let (source_role, dest_role) = match direction {
+            SwapDirection::Deposit => (
+                UserAccountRole::SourceAssetAta,
+                UserAccountRole::DestinationLpAta,
+            ),
+            SwapDirection::Redeem => (
+                UserAccountRole::SourceLpAta,
+                UserAccountRole::DestinationAssetAta,
+            ),
+        };
+
+        let source_min_balance = match direction {
+            SwapDirection::Deposit if native => 0,
+            _ => amount,
+        };
+        [
+            (
+                info.input_mint,
+                info.input_token_program,
+                source_role,
+                source_min_balance,
+            ),
+            (info.output_mint, info.output_token_program, dest_role, 0),
+        ]
+        .into_iter()
+        .map(|(mint, token_program, role, min_balance)| RequiredAccount {
+            pubkey: spl_associated_token_account::get_associated_token_address_with_program_id(
+                user,
+                &mint,
+                &token_program,
+            ),
+            role,
+            mint,
+            token_program,
+            created_if_missing: native && mint == self.vault_state.asset.mint,
+            min_balance,
+        })
+        .collect()
+    }
+
+    /// Fetch the user accounts required by `request` and report every
+    /// precondition that would make the swap fail. An empty list means the
+    /// user side of the swap is ready.
+    pub async fn check_preconditions(
+        &self,
+        user: &Pubkey,
+        request: &QuoteRequest,
+        cache: &dyn AccountsCache,
+    ) -> Result<Vec<PreconditionFailure>, TradingVenueError> {
+        let direction = self.direction_for(request)?;
+        let required = self.required_user_accounts(user, direction, request.amount);
+
+        let pubkeys: Vec<Pubkey> = required.iter().map(|account| account.pubkey).collect();
+        let accounts = cache.get_accounts(&pubkeys).await?;
+
+        let mut failures = Vec::new();
+        for (required, account) in required.iter().zip(accounts) {
+            let Some(account) = account else {
+                if !required.created_if_missing {
+                    failures.push(PreconditionFailure::MissingAccount {
+                        pubkey: required.pubkey,
+                        role: required.role,
+                    });
+                }
+                continue;
+            };
+
+            let strict = self.config().strict_token_account_layout;
+            match token_account_balance(&account, &required.mint, user, strict) {
+                Some(available) if available < required.min_balance => {
+                    failures.push(PreconditionFailure::InsufficientBalance {
+                        pubkey: required.pubkey,
+                        role: required.role,
+                        required: required.min_balance,
+                        available,
+                    });
+                }
+                Some(_) => {}
+                None => failures.push(PreconditionFailure::InvalidAccount {
+                    pubkey: required.pubkey,
+                    role: required.role,
+                }),
+            }
+        }
+
+        Ok(failures)
+    }
+}
+
+/// Unpack a Token program account. Unless `strict`, bytes past its 165-byte
+/// layout are ignored; see
+/// [`strict_token_account_layout`](crate::config::VoltrVenueConfig::strict_token_account_layout).
+pub(crate) fn unpack_token_account(
+    data: &[u8],
+    strict: bool,
+) -> Result<spl_token::state::Account, ProgramError> {
+    let len = spl_token::state::Account::LEN;
+    if strict || data.len() <= len {
+        return spl_token::state::Account::unpack(data);
+    }
+    log::debug!(
+        "Reading the first {} of {} bytes of a Token account",
+        len,
+        data.len()
+    );
+    spl_token::state::Account::unpack(&data[..len])
+}
+
+/// Read the balance of a token account, returning `None` unless it is an
+/// initialized account for `mint` owned by `owner`. `strict` is passed to
+/// `unpack_token_account()`.
+pub(crate) fn token_account_balance(
+    account: &Account,
+    mint: &Pubkey,
+    owner: &Pubkey,
+    strict: bool,
+) -> Option<u64> {
+    let (account_mint, account_owner, amount) = if account.owner == TOKEN_PROGRAM {
+        let token = unpack_token_account(&account.data, strict).ok()?;
+        (token.mint, token.owner, token.amount)
+    } else if account.owner == TOKEN_22_PROGRAM {
+        let token =
+            StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account.data).ok()?;
+        (token.base.mint, token.base.owner, token.base.amount)
+    } else {
+        return None;
+    };
+
+    (account_mint == *mint && account_owner == *owner).then_some(amount)
+}
@@ -0,0 +1,352 @@
+//! One-pass validation of a loaded venue before it is enabled in
+//! production: the checklist an operator would otherwise run by hand, each
+//! check reported with its outcome and timing.
+
+use std::time::{Duration, Instant};
+
+use solana_instruction::Instruction;
+use solana_program::system_program::ID as SYSTEM_PROGRAM_ID;
+use solana_pubkey::Pubkey;
+use titan_integration_template::{
+    account_caching::AccountsCache,
+    trading_venue::{error::TradingVenueError, AddressLookupTableTrait, TradingVenue},
+};
+
+use crate::{
+    errors::VoltrError,
+    voltr_venue::{SwapDirection, VoltrVaultVenue},
+};
+
+/// Signer of the swap instructions built by `preflight()`; they are never
+/// submitted.
+const PREFLIGHT_USER: Pubkey = Pubkey::new_from_array([0x11; 32]);
+
+/// Largest asset decimals for which one whole token fits in a `u64`.
+const MAX_ASSET_DECIMALS: u8 = 19;
+
+/// Decimals of every Voltr LP mint.
+const LP_DECIMALS: u8 = 9;
+
+/// Input bounds of a direction, as computed for the bounds check.
+type DirectionBounds = (SwapDirection, Result<(u64, u64), TradingVenueError>);
+
+/// A check of `preflight()`, in the order they run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum PreflightCheck {
+    /// `health_report()` finds nothing suspect.
+    Health,
+    /// The LP mint and idle ATA are those the program derives for the vault.
+    Pdas,
+    /// A whole asset token fits in a `u64`, and the LP has 9 decimals.
+    Decimals,
+    /// Fee rates are within the program's limits.
+    Fees,
+    /// Input bounds are computable in both directions.
+    Bounds,
+    /// Quotes at both ends of each direction's bounds are filled.
+    QuoteAtBounds,
+    /// A swap instruction builds in both directions.
+    Instructions,
+    /// Lookup table keys cover every account of the swap instructions but
+    /// the user's and the system program, and the configured table holds
+    /// them all.
+    LookupTable,
+}
+
+/// How a check of `preflight()` ended.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum PreflightOutcome {
+    Pass,
+    /// Not a reason to keep the venue disabled, but worth a look.
+    Warn(String),
+    Fail(String),
+}
+
+/// One check of a `PreflightReport`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PreflightCheckReport {
+    pub check: PreflightCheck,
+    pub outcome: PreflightOutcome,
+    pub elapsed: Duration,
+}
+
+/// Outcome of every check of `preflight()`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PreflightReport {
+    /// Every check, in the order they ran.
+    pub checks: Vec<PreflightCheckReport>,
+}
+
+impl PreflightReport {
+    /// The report of `check`, if it ran.
+    pub fn check(&self, check: PreflightCheck) -> Option<&PreflightCheckReport> {
+        self.checks.iter().find(|report| report.check == check)
+    }
+
+    /// Whether no check failed; warnings are allowed.
+    pub fn passed(&self) -> bool {
+        self.failures().next().is_none()
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &PreflightCheckReport> {
+        self.checks
+            .iter()
+            .filter(|report| matches!(report.outcome, PreflightOutcome::Fail(_)))
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &PreflightCheckReport> {
+        self.checks
+            .iter()
+            .filter(|report| matches!(report.outcome, PreflightOutcome::Warn(_)))
+    }
+
+    pub fn total_elapsed(&self) -> Duration {
+        self.checks.iter().map(|report| report.elapsed).sum()
+    }
+
+    fn record(&mut self, check: PreflightCheck, outcome: PreflightOutcome, started: Instant) {
+        self.checks.push(PreflightCheckReport {
+            check,
+            outcome,
+            elapsed: started.elapsed(),
+        });
+    }
+}
+
+impl VoltrVaultVenue {
+    /// Cross-check every invariant the venue relies on before enabling it.
+    ///
+    /// Runs on the loaded state; `cache` is only read for the configured
+    /// lookup table. Only a venue never loaded fails the call; every problem
+    /// found, a migrated vault included, is reported in its check.
+    pub async fn preflight(
+        &self,
+        cache: &dyn AccountsCache,
+    ) -> Result<PreflightReport, TradingVenueError> {
+        if !self.initialized {
+            return Err(VoltrError::NotInitialized.into());
+        }
+        let mut report = PreflightReport::default();
+
+        let started = Instant::now();
+        report.record(PreflightCheck::Health, self.preflight_health(), started);
+
+        let started = Instant::now();
+        report.record(PreflightCheck::Pdas, self.preflight_pdas(), started);
+
+        let started = Instant::now();
+        report.record(PreflightCheck::Decimals, self.preflight_decimals(), started);
+
+        let started = Instant::now();
+        let outcome = match self.vault_state.validate() {
+            Ok(()) => PreflightOutcome::Pass,
+            Err(e) => PreflightOutcome::Fail(e.to_string()),
+        };
+        report.record(PreflightCheck::Fees, outcome, started);
+
+        let started = Instant::now();
+        let bounds = [SwapDirection::Deposit, SwapDirection::Redeem]
+            .map(|direction| (direction, self.direction_bounds(direction)));
+        let outcome = match bounds.iter().find_map(|(_, bounds)| bounds.as_ref().err()) {
+            Some(e) => PreflightOutcome::Fail(e.to_string()),
+            None => match bounds
+                .iter()
+                .find(|(_, bounds)| matches!(bounds, Ok((_, 0))))
+            {
+                Some((direction, _)) => {
+                    PreflightOutcome::Warn(format!("no {direction:?} input can be filled"))
+                }
+                None => PreflightOutcome::Pass,
+            },
+        };
+        report.record(PreflightCheck::Bounds, outcome, started);
+
+        let started = Instant::now();
+        let outcome = self.preflight_quotes_at_bounds(&bounds);
+        report.record(PreflightCheck::QuoteAtBounds, outcome, started);
+
+        let started = Instant::now();
+        let instructions = [SwapDirection::Deposit, SwapDirection::Redeem].map(|direction| {
+            let amount = match bounds_of(&bounds, direction) {
+                Some((lower, _)) => lower.max(1),
+                None => 1,
+            };
+            (
+                direction,
+                self.generate_swap_instruction(
+                    self.direction_request(direction, amount),
+                    PREFLIGHT_USER,
+                ),
+            )
+        });
+        let outcome = match instructions.iter().find_map(|(direction, instruction)| {
+            let expected = self.direction_info(*direction).accounts_len;
+            match instruction {
+                Err(e) => Some(format!("{direction:?}: {e}")),
+                Ok(instruction) if instruction.accounts.len() != expected => Some(format!(
+                    "{direction:?} instruction has {} accounts instead of {expected}",
+                    instruction.accounts.len()
+                )),
+                Ok(_) => None,
+            }
+        }) {
+            Some(message) => PreflightOutcome::Fail(message),
+            None => PreflightOutcome::Pass,
+        };
+        report.record(PreflightCheck::Instructions, outcome, started);
+
+        let started = Instant::now();
+        let outcome = self.preflight_lookup_table(&instructions, cache).await;
+        report.record(PreflightCheck::LookupTable, outcome, started);
+
+        Ok(report)
+    }
+
+    fn preflight_health(&self) -> PreflightOutcome {
+        let health = self.health_report();
+        if !health.is_healthy() {
+            return PreflightOutcome::Fail(format!("{health:?}"));
+        }
+        if health.unknown_mint_extensions {
+            return PreflightOutcome::Warn(
+                "asset mint has extensions this build cannot read".to_string(),
+            );
+        }
+        match health.asset_freeze_authority {
+            Some(authority) => {
+                PreflightOutcome::Warn(format!("asset mint has freeze authority {authority}"))
+            }
+            None => PreflightOutcome::Pass,
+        }
+    }
+
+    fn preflight_pdas(&self) -> PreflightOutcome {
+        let pdas = self.pdas();
+        if self.vault_state.lp.mint != pdas.lp_mint {
+            return PreflightOutcome::Fail(format!(
+                "LP mint {} is not the vault's PDA {}",
+                self.vault_state.lp.mint, pdas.lp_mint
+            ));
+        }
+        let idle_ata = spl_associated_token_account::get_associated_token_address_with_program_id(
+            &pdas.asset_idle_auth,
+            &self.vault_state.asset.mint,
+            &self.asset_token_program,
+        );
+        if self.vault_state.asset.idle_ata != idle_ata {
+            return PreflightOutcome::Fail(format!(
+                "idle ATA {} is not the idle authority's ATA {}",
+                self.vault_state.asset.idle_ata, idle_ata
+            ));
+        }
+        PreflightOutcome::Pass
+    }
+
+    fn preflight_decimals(&self) -> PreflightOutcome {
+        if self.asset_mint_decimals > MAX_ASSET_DECIMALS {
+            PreflightOutcome::Fail(format!(
+                "asset has {} decimals, more than the {} a whole token fits in a u64 with",
+                self.asset_mint_decimals, MAX_ASSET_DECIMALS
+            ))
+        } else if self.lp_mint_decimals != LP_DECIMALS {
+            PreflightOutcome::Fail(format!(
+                "LP has {} decimals instead of {}",
+                self.lp_mint_decimals, LP_DECIMALS
+            ))
+        } else {
+            PreflightOutcome::Pass
+        }
+    }
+
+    fn preflight_quotes_at_bounds(&self, bounds: &[DirectionBounds]) -> PreflightOutcome {
+        for &direction in &[SwapDirection::Deposit, SwapDirection::Redeem] {
+            let Some((lower, upper)) = bounds_of(bounds, direction) else {
+                return PreflightOutcome::Fail(format!("{direction:?} bounds not computable"));
+            };
+            if upper == 0 {
+                continue;
+            }
+            for amount in [lower, upper] {
+                match self.quote_with_rejection(&self.direction_request(direction, amount)) {
+                    Ok((quote, None)) if quote.expected_output > 0 => {}
+                    Ok((_, Some(rejection))) => {
+                        return PreflightOutcome::Fail(format!(
+                            "{direction:?} of {amount} rejected: {rejection:?}"
+                        ));
+                    }
+                    Ok(_) => {
+                        return PreflightOutcome::Fail(format!(
+                            "{direction:?} of {amount} has no output"
+                        ));
+                    }
+                    Err(e) => {
+                        return PreflightOutcome::Fail(format!("{direction:?} of {amount}: {e}"));
+                    }
+                }
+            }
+        }
+        PreflightOutcome::Pass
+    }
+
+    async fn preflight_lookup_table(
+        &self,
+        instructions: &[(SwapDirection, Result<Instruction, TradingVenueError>)],
+        cache: &dyn AccountsCache,
+    ) -> PreflightOutcome {
+        let keys = match self.get_lookup_table_keys(None).await {
+            Ok(keys) => keys,
+            Err(e) => return PreflightOutcome::Fail(e.to_string()),
+        };
+        for (direction, instruction) in instructions {
+            let Ok(instruction) = instruction else {
+                return PreflightOutcome::Fail(format!("{direction:?} instruction not built"));
+            };
+            let info = self.direction_info(*direction);
+            // The system program is in every router's own table, so the
+            // venue's keys leave it out.
+            let unlisted = [
+                PREFLIGHT_USER,
+                info.input_account(&PREFLIGHT_USER),
+                info.output_account(&PREFLIGHT_USER),
+                SYSTEM_PROGRAM_ID,
+            ];
+            let missing: Vec<String> = instruction
+                .accounts
+                .iter()
+                .map(|meta| meta.pubkey)
+                .filter(|key| !unlisted.contains(key) && !keys.contains(key))
+                .map(|key| key.to_string())
+                .collect();
+            if !missing.is_empty() {
+                return PreflightOutcome::Fail(format!(
+                    "{direction:?} accounts missing from the lookup table keys: {}",
+                    missing.join(", ")
+                ));
+            }
+        }
+
+        if self.config().lookup_table.is_none() {
+            return PreflightOutcome::Pass;
+        }
+        match self.get_lookup_table_keys(Some(cache)).await {
+            Ok(missing) if missing.is_empty() => PreflightOutcome::Pass,
+            Ok(missing) => PreflightOutcome::Warn(format!(
+                "configured lookup table lacks {} of the venue's keys",
+                missing.len()
+            )),
+            Err(e) => PreflightOutcome::Fail(e.to_string()),
+        }
+    }
+}
+
+/// The bounds of `direction` in `bounds`, when they were computed.
+fn bounds_of(bounds: &[DirectionBounds], direction: SwapDirection) -> Option<(u64, u64)> {
+    bounds
+        .iter()
+        .find(|(d, _)| *d == direction)
+        .and_then(|(_, bounds)| bounds.as_ref().ok().copied())
+}
@@ -0,0 +1,293 @@
+use solana_program_pack::Pack;
+use solana_pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+use spl_token_2022::extension::ExtensionType;
+
+use titan_integration_template::{
+    account_caching::AccountsCache,
+    trading_venue::{error::TradingVenueError, QuoteRequest, QuoteResult},
+};
+
+use crate::{
+    constants::{NATIVE_MINT, TOKEN_22_PROGRAM},
+    core::{quote_snapshot_exact, ExactOutput},
+    errors::VoltrError,
+    fees::FeeComponent,
+    state::LpSupplyBreakdown,
+    voltr_venue::{LiquidityRejection, SwapDirection, VoltrVaultVenue},
+};
+
+/// Whether `pubkey` exists in `cache`. A cache that reports the account as
+/// missing, either as `None` or as `NoAccountFound`, yields `false` rather
+/// than an error.
+async fn account_exists(
+    pubkey: &Pubkey,
+    cache: &dyn AccountsCache,
+) -> Result<bool, TradingVenueError> {
+    match cache.get_account(pubkey).await {
+        Ok(account) => Ok(account.is_some()),
+        Err(TradingVenueError::NoAccountFound(_)) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// When the state behind a quote was loaded and when the quote was computed,
+/// for comparing the freshness of quotes from different venues.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct QuoteResultMeta {
+    /// `state_ts()` of the venue: when its state was loaded.
+    pub state_ts: u64,
+    /// Newest slot among the accounts of that state, when the cache reported
+    /// slots.
+    pub state_slot: Option<u64>,
+    /// `effective_ts()` the quote was computed at.
+    pub computed_at: u64,
+    /// `state_age_secs()`: seconds since a refresh last succeeded.
+    pub state_age_secs: u64,
+    /// Whether the quote was served from stale state after failed
+    /// refreshes; see `VenueStatus::Stale`.
+    pub degraded: bool,
+}
+
+/// A quote together with what the user pays beyond the swap input.
+#[derive(Clone, Debug)]
+pub struct DetailedQuote {
+    pub direction: SwapDirection,
+    pub quote: QuoteResult,
+    /// Why `quote.not_enough_liquidity` is set, if it is.
+    pub rejection: Option<LiquidityRejection>,
+    /// Largest input the vault accepts right now, set when `rejection` is a
+    /// capacity limit. A redeem rejected for idle balance can be retried at
+    /// this size or sent once strategies are unwound back into idle.
+    pub max_fillable_input: Option<u64>,
+    /// Room left under the vault's max cap before and after a deposit,
+    /// `None` for redeems and uncapped vaults. Zero once the vault is at or
+    /// over its cap, as appreciation can leave it.
+    pub remaining_capacity_before: Option<u64>,
+    pub remaining_capacity_after: Option<u64>,
+    /// User ATA credited by the swap.
+    pub output_account: Pubkey,
+    /// Whether `output_account` exists, `None` when no cache was supplied.
+    pub output_account_exists: Option<bool>,
+    /// Lamports the user must hold on top of the swap input and the
+    /// transaction fee; see `extra_lamports_required()`.
+    pub extra_lamports_required: u64,
+    /// LP supply the quote was priced against, before pending management
+    /// fees.
+    pub lp_supply: LpSupplyBreakdown,
+    /// Fees the swap pays or settles and who ends up with them.
+    pub fees: Vec<FeeComponent>,
+    pub meta: QuoteResultMeta,
+}
+
+impl VoltrVaultVenue {
+    /// Quote `request` as `quote()` does, along with the freshness of the
+    /// state it was computed from.
+    pub fn quote_with_meta(
+        &self,
+        request: &QuoteRequest,
+    ) -> Result<(QuoteResult, QuoteResultMeta), TradingVenueError> {
+        let meta = self.quote_meta();
+        let (quote, _) = self.quote_with_rejection_at(request, meta.computed_at)?;
+        Ok((quote, meta))
+    }
+
+    /// Quote `request` as `quote()` does, along with the output before
+    /// flooring to base units: U80F48 bits for a redeem, a ratio for a
+    /// deposit. `ExactOutput::floor()` is the amount the program pays.
+    ///
+    /// The exact output is zero when the quote is rejected. It carries no
+    /// stale-accounting haircut, so `expected_output` can then be below its
+    /// floor.
+    pub fn quote_fixed(
+        &self,
+        request: &QuoteRequest,
+    ) -> Result<(QuoteResult, ExactOutput), TradingVenueError> {
+        let current_ts = self.effective_ts();
+        let (quote, _) = self.quote_with_rejection_at(request, current_ts)?;
+        let direction = self.direction_for(request)?;
+        if quote.not_enough_liquidity {
+            return Ok((quote, ExactOutput::zero(direction)));
+        }
+        let (exact, _) = quote_snapshot_exact(
+            &self.vault_snapshot()?,
+            direction,
+            request.amount,
+            current_ts,
+        )
+        .map_err(VoltrError::from)?;
+        Ok((quote, exact))
+    }
+
+    /// Meta of a quote computed now.
+    fn quote_meta(&self) -> QuoteResultMeta {
+        QuoteResultMeta {
+            state_ts: self.state_ts(),
+            state_slot: self.state_slot(),
+            computed_at: self.effective_ts(),
+            state_age_secs: self.state_age_secs(),
+            degraded: self.is_degraded(),
+        }
+    }
+
+    /// Whether the user's output ATA for `direction` exists in `cache`.
+    ///
+    /// A cache that reports the account as missing, either as `None` or as
+    /// `NoAccountFound`, yields `false` rather than an error.
+    pub async fn output_account_exists(
+        &self,
+        user: &Pubkey,
+        direction: SwapDirection,
+        cache: &dyn AccountsCache,
+    ) -> Result<bool, TradingVenueError> {
+        account_exists(&self.output_account(user, direction), cache).await
+    }
+
+    /// Rent-exempt balance of a new output ATA for `direction`.
+    ///
+    /// Token-2022 ATAs are sized with the `ImmutableOwner` extension the ATA
+    /// program always adds; mints whose extensions require further account
+    /// extensions need slightly more.
+    pub fn output_account_rent(&self, direction: SwapDirection) -> u64 {
+        let len = if self.direction_info(direction).output_token_program == TOKEN_22_PROGRAM {
+            ExtensionType::try_calculate_account_len::<spl_token_2022::state::Account>(&[
+                ExtensionType::ImmutableOwner,
+            ])
+            .unwrap_or(spl_token_2022::state::Account::LEN)
+        } else {
+            spl_token::state::Account::LEN
+        };
+        Rent::default().minimum_balance(len)
+    }
+
+    /// Lamports a swap in `direction` needs the user to hold beyond the
+    /// swap input and the transaction fee: the rent of each ATA the
+    /// transaction creates.
+    ///
+    /// Redeems go through `instant_withdraw_vault`, which burns LP straight
+    /// from the user's ATA without a withdrawal receipt, so there is no
+    /// receipt rent paid upfront or refunded later to account for.
+    ///
+    /// On a vault with `is_native_asset()` the user's wSOL ATA is created in
+    /// both directions: the deposit wraps SOL into it and the redeem pays
+    /// into it. Its rent is counted even though it is refunded at the end of
+    /// the transaction when `TxOptions::close_wsol_ata` closes the ATA, since
+    /// the wallet still has to front it until then.
+    ///
+    /// Without a cache every ATA is assumed missing, so the estimate is an
+    /// upper bound.
+    pub async fn extra_lamports_required(
+        &self,
+        user: &Pubkey,
+        direction: SwapDirection,
+        cache: Option<&dyn AccountsCache>,
+    ) -> Result<u64, TradingVenueError> {
+        let exists = match cache {
+            Some(cache) => self.output_account_exists(user, direction, cache).await?,
+            None => false,
+        };
+        self.created_accounts_rent(user, direction, exists, cache)
+            .await
+    }
+
+    /// Rent of the ATAs a swap in `direction` creates, given whether the
+    /// output ATA exists: that one, and on a native deposit the wSOL ATA
+    /// when `cache` does not have it.
+    async fn created_accounts_rent(
+        &self,
+        user: &Pubkey,
+        direction: SwapDirection,
+        output_exists: bool,
+        cache: Option<&dyn AccountsCache>,
+    ) -> Result<u64, TradingVenueError> {
+        let mut rent = if output_exists {
+            0
+        } else {
+            self.output_account_rent(direction)
+        };
+        if self.is_native_asset() && direction == SwapDirection::Deposit {
+            let wsol_ata =
+                spl_associated_token_account::get_associated_token_address(user, &NATIVE_MINT);
+            let wsol_exists = match cache {
+                Some(cache) => account_exists(&wsol_ata, cache).await?,
+                None => false,
+            };
+            if !wsol_exists {
+                rent += self.output_account_rent(SwapDirection::Redeem);
+            }
+        }
+        Ok(rent)
+    }
+
+    /// Share of the vault's max cap its total value would take up after a
+    /// deposit of `deposit_amount`, `None` when the vault is uncapped.
+    ///
+    /// Above 1.0 exactly when the deposit is rejected for the cap, including
+    /// on a vault already over it; the max cap is inclusive, so a deposit
+    /// landing on it gives 1.0.
+    pub fn capacity_utilization_after(&self, deposit_amount: u64) -> Option<f64> {
+        let max_cap = self.vault_state.vault_configuration.max_cap;
+        if max_cap == 0 {
+            return None;
+        }
+        let used = self.vault_state.get_total_asset_value() as u128 + deposit_amount as u128;
+        let utilization = used as f64 / max_cap as f64;
+        // Rounding must not move a figure across the rejection threshold.
+        Some(if used > max_cap as u128 {
+            utilization.max(1.0 + f64::EPSILON)
+        } else {
+            utilization.min(1.0)
+        })
+    }
+
+    /// Quote `request` for `user`, reporting the rejection reason and the
+    /// lamports needed on top of the swap input.
+    ///
+    /// When `cache` is supplied it is used to check whether the output ATA
+    /// already exists.
+    pub async fn quote_detailed(
+        &self,
+        request: &QuoteRequest,
+        user: &Pubkey,
+        cache: Option<&dyn AccountsCache>,
+    ) -> Result<DetailedQuote, TradingVenueError> {
+        let info = self.direction_info_for(request)?;
+        let direction = info.direction;
+        let meta = self.quote_meta();
+        let (quote, rejection) = self.quote_with_rejection_at(request, meta.computed_at)?;
+        let max_fillable_input = match rejection {
+            Some(rejection) if rejection.is_capacity_limit() => {
+                Some(self.amount_bounds(request)?.1)
+            }
+            _ => None,
+        };
+        let remaining_capacity_after = info
+            .capacity_limit
+            .map(|limit| limit.saturating_sub(request.amount));
+        let lp_supply = self.vault_state.lp_supply_breakdown(self.lp_mint_supply)?;
+        let fees = self.swap_fees(request, meta.computed_at)?;
+
+        let output_account_exists = match cache {
+            Some(cache) => Some(self.output_account_exists(user, direction, cache).await?),
+            None => None,
+        };
+        let extra_lamports_required = self
+            .created_accounts_rent(user, direction, output_account_exists == Some(true), cache)
+            .await?;
+
+        Ok(DetailedQuote {
+            direction,
+            quote,
+            rejection,
+            max_fillable_input,
+            remaining_capacity_before: info.capacity_limit,
+            remaining_capacity_after,
+            output_account: self.output_account(user, direction),
+            output_account_exists,
+            extra_lamports_required,
+            lp_supply,
+            fees,
+            meta,
+        })
+    }
+}
@@ -0,0 +1,104 @@
+//! Counting quotes refused for liquidity, with rate-limited warnings.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::voltr_venue::{LiquidityRejection, VoltrVaultVenue};
+
+const REASONS: usize = 5;
+
+/// `last_warned_ts` before the first warning.
+const NEVER: u64 = u64::MAX;
+
+fn slot(reason: LiquidityRejection) -> usize {
+    match reason {
+        LiquidityRejection::CapExceeded => 0,
+        LiquidityRejection::BelowDeadWeight => 1,
+        LiquidityRejection::IdleBalanceInsufficient => 2,
+        LiquidityRejection::UnlockedValueExceeded => 3,
+        LiquidityRejection::BelowMinimumOutput => 4,
+    }
+}
+
+/// Per rejection reason, how many `quote()` calls it refused and when it was
+/// last warned about. Shared through `&self`, so quoting stays lock- and
+/// allocation-free.
+pub(crate) struct RejectionStats {
+    counts: [AtomicU64; REASONS],
+    last_warned_ts: [AtomicU64; REASONS],
+}
+
+impl Default for RejectionStats {
+    fn default() -> Self {
+        Self {
+            counts: Default::default(),
+            last_warned_ts: [const { AtomicU64::new(NEVER) }; REASONS],
+        }
+    }
+}
+
+impl Clone for RejectionStats {
+    fn clone(&self) -> Self {
+        let copy = |values: &[AtomicU64; REASONS]| {
+            values
+                .each_ref()
+                .map(|value| AtomicU64::new(value.load(Ordering::Relaxed)))
+        };
+        Self {
+            counts: copy(&self.counts),
+            last_warned_ts: copy(&self.last_warned_ts),
+        }
+    }
+}
+
+impl RejectionStats {
+    fn count(&self, reason: LiquidityRejection) {
+        self.counts[slot(reason)].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Whether a rejection for `reason` at `current_ts` is the first in
+    /// `interval_secs` and so should be warned about.
+    fn claim_warning(
+        &self,
+        reason: LiquidityRejection,
+        current_ts: u64,
+        interval_secs: u64,
+    ) -> bool {
+        let last_warned_ts = &self.last_warned_ts[slot(reason)];
+        let last = last_warned_ts.load(Ordering::Relaxed);
+        if last != NEVER && current_ts.saturating_sub(last) < interval_secs {
+            return false;
+        }
+        // Of concurrent callers, only the one that moves the timestamp warns.
+        last_warned_ts
+            .compare_exchange(last, current_ts, Ordering::Relaxed, Ordering::Relaxed)
+            .is_ok()
+    }
+}
+
+impl VoltrVaultVenue {
+    /// How many `quote()` calls `reason` has refused since the venue was
+    /// built.
+    pub fn rejection_count(&self, reason: LiquidityRejection) -> u64 {
+        self.rejection_stats.counts[slot(reason)].load(Ordering::Relaxed)
+    }
+
+    /// Count a `quote()` refused for `reason` at `current_ts`, warning at
+    /// most once per `rejection_warning_interval_secs` per reason.
+    pub(crate) fn record_rejection(&self, reason: LiquidityRejection, current_ts: u64) {
+        self.rejection_stats.count(reason);
+        let Some(interval_secs) = self.config().rejection_warning_interval_secs else {
+            return;
+        };
+        if self
+            .rejection_stats
+            .claim_warning(reason, current_ts, interval_secs)
+        {
+            log::warn!(
+                "Voltr vault {} is refusing quotes: {} ({} so far)",
+                self.vault_key,
+                reason,
+                self.rejection_count(reason)
+            );
+        }
+    }
+}
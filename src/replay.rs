@@ -0,0 +1,217 @@
+//! Replaying recorded vault accounts through a venue, for backtesting how
+//! quotes would have evolved.
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use solana_account::Account;
+use solana_pubkey::Pubkey;
+
+use titan_integration_template::{
+    account_caching::AccountsCache,
+    trading_venue::{error::TradingVenueError, TradingVenue},
+};
+
+use crate::{clock::QuoteClock, slots::SlotAwareAccountsCache, voltr_venue::VoltrVaultVenue};
+
+/// The tracked accounts of a vault as recorded at one slot.
+#[derive(Clone, Debug, Default)]
+pub struct ReplaySnapshot {
+    pub slot: u64,
+    /// Unix time of `slot`; quotes at this step are computed at it.
+    pub timestamp: u64,
+    pub accounts: HashMap<Pubkey, Account>,
+}
+
+/// An `AccountsCache` serving one snapshot of a series at a time.
+///
+/// Snapshots are ordered by slot; the cache starts at the first one.
+#[derive(Clone, Debug, Default)]
+pub struct ReplayCache {
+    snapshots: Vec<ReplaySnapshot>,
+    position: usize,
+}
+
+impl ReplayCache {
+    pub fn new(mut snapshots: Vec<ReplaySnapshot>) -> Self {
+        snapshots.sort_by_key(|snapshot| snapshot.slot);
+        Self {
+            snapshots,
+            position: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// Index of the snapshot being served.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// The snapshot being served, `None` for an empty series.
+    pub fn current(&self) -> Option<&ReplaySnapshot> {
+        self.snapshots.get(self.position)
+    }
+
+    /// Move to the next snapshot; `false`, staying put, at the last one.
+    pub fn advance(&mut self) -> bool {
+        if self.position + 1 >= self.snapshots.len() {
+            return false;
+        }
+        self.position += 1;
+        true
+    }
+
+    /// Serve the snapshot at `position` again, e.g. to replay from the start.
+    pub fn seek(&mut self, position: usize) -> Result<(), TradingVenueError> {
+        if position >= self.snapshots.len() {
+            return Err(TradingVenueError::AmmMethodError(
+                format!("No snapshot {position} in a series of {}", self.len()).into(),
+            ));
+        }
+        self.position = position;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AccountsCache for ReplayCache {
+    async fn get_account(&self, pubkey: &Pubkey) -> Result<Option<Account>, TradingVenueError> {
+        Ok(self
+            .current()
+            .and_then(|snapshot| snapshot.accounts.get(pubkey))
+            .cloned())
+    }
+
+    async fn get_accounts(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> Result<Vec<Option<Account>>, TradingVenueError> {
+        let snapshot = self.current();
+        Ok(pubkeys
+            .iter()
+            .map(|pubkey| snapshot.and_then(|s| s.accounts.get(pubkey)).cloned())
+            .collect())
+    }
+}
+
+#[async_trait]
+impl SlotAwareAccountsCache for ReplayCache {
+    /// Every account of a snapshot was recorded at its slot.
+    async fn get_accounts_with_slots(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> Result<Vec<(Option<Account>, Option<u64>)>, TradingVenueError> {
+        let slot = self.current().map(|snapshot| snapshot.slot);
+        Ok(self
+            .get_accounts(pubkeys)
+            .await?
+            .into_iter()
+            .map(|account| (account, slot))
+            .collect())
+    }
+}
+
+/// What an evaluation returned at one step of a replay.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReplayStep<T> {
+    pub slot: u64,
+    pub timestamp: u64,
+    /// Whether the step's accounts changed the venue's state.
+    pub changed: bool,
+    pub value: T,
+}
+
+/// Steps a venue through a `ReplayCache`, loading each snapshot with
+/// `update_state_from_accounts()` on a clock fixed at its timestamp.
+pub struct ReplaySession {
+    venue: VoltrVaultVenue,
+    cache: ReplayCache,
+    started: bool,
+}
+
+impl ReplaySession {
+    pub fn new(venue: VoltrVaultVenue, cache: ReplayCache) -> Self {
+        Self {
+            venue,
+            cache,
+            started: false,
+        }
+    }
+
+    pub fn venue(&self) -> &VoltrVaultVenue {
+        &self.venue
+    }
+
+    /// The cache, serving the snapshot last loaded.
+    pub fn cache(&self) -> &ReplayCache {
+        &self.cache
+    }
+
+    pub fn into_venue(self) -> VoltrVaultVenue {
+        self.venue
+    }
+
+    /// Load the next snapshot into the venue, returning whether its state
+    /// changed, or `None` once the series is exhausted.
+    ///
+    /// A venue built with `new_uninitialized()` loads its vault and then its
+    /// token accounts from the first snapshot.
+    pub fn step(&mut self) -> Option<Result<bool, TradingVenueError>> {
+        let has_next = if self.started {
+            self.cache.advance()
+        } else {
+            self.started = true;
+            !self.cache.is_empty()
+        };
+        has_next.then(|| self.load_current())
+    }
+
+    fn load_current(&mut self) -> Result<bool, TradingVenueError> {
+        let snapshot = self.cache.current().expect("position is in range");
+        // Quote, and stamp the state, as of the snapshot rather than now.
+        self.venue.set_clock(QuoteClock::Fixed(snapshot.timestamp));
+        let mut changed = false;
+        loop {
+            let pubkeys = self.venue.get_required_pubkeys_for_update()?;
+            let accounts: Vec<Option<Account>> = pubkeys
+                .iter()
+                .map(|pubkey| snapshot.accounts.get(pubkey).cloned())
+                .collect();
+            changed |= self.venue.update_state_from_accounts(&accounts)?;
+            // Loading the vault lists its token accounts as well.
+            if self.venue.get_required_pubkeys_for_update()?.len() == pubkeys.len() {
+                return Ok(changed);
+            }
+        }
+    }
+
+    /// Step through the rest of the series, calling `evaluate` with the
+    /// venue and the snapshot's timestamp after each step.
+    ///
+    /// Stops at the first snapshot the venue fails to load.
+    pub fn run<T>(
+        &mut self,
+        mut evaluate: impl FnMut(&VoltrVaultVenue, u64) -> T,
+    ) -> Result<Vec<ReplayStep<T>>, TradingVenueError> {
+        let mut steps = Vec::new();
+        while let Some(changed) = self.step() {
+            let changed = changed?;
+            let snapshot = self.cache.current().expect("a step loaded a snapshot");
+            let (slot, timestamp) = (snapshot.slot, snapshot.timestamp);
+            steps.push(ReplayStep {
+                slot,
+                timestamp,
+                changed,
+                value: evaluate(&self.venue, timestamp),
+            });
+        }
+        Ok(steps)
+    }
+}
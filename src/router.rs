@@ -0,0 +1,90 @@
+use solana_pubkey::Pubkey;
+
+use titan_integration_template::trading_venue::{QuoteRequest, QuoteResult, SwapType, TradingVenue};
+
+use crate::voltr_venue::VoltrVaultVenue;
+
+/// Pick the vault giving the most for a deposit of `amount` of `asset_mint`.
+///
+/// Deposits mint each vault's own LP, so outputs are compared by the asset
+/// value of the minted LP at the vault's current share price rather than by
+/// raw LP amounts. Venues for other assets, venues that fail to quote and
+/// quotes without liquidity are skipped; ties go to the lowest vault pubkey.
+pub fn best_deposit_venue(
+    venues: &[VoltrVaultVenue],
+    asset_mint: Pubkey,
+    amount: u64,
+) -> Option<(&VoltrVaultVenue, QuoteResult)> {
+    best_venue(
+        venues.iter().map(|venue| (venue, amount)),
+        asset_mint,
+        |venue| (asset_mint, venue.vault_state.lp.mint),
+        deposit_value_in_asset,
+    )
+}
+
+/// Pick the vault paying out the most `asset_mint` for a redeem.
+///
+/// `lp_amounts[i]` is the LP amount to redeem from `venues[i]`; venues
+/// without an amount are ignored. Skipping and tie-breaking follow
+/// `best_deposit_venue()`.
+pub fn best_redeem_venue<'a>(
+    venues: &'a [VoltrVaultVenue],
+    asset_mint: Pubkey,
+    lp_amounts: &[u64],
+) -> Option<(&'a VoltrVaultVenue, QuoteResult)> {
+    best_venue(
+        venues.iter().zip(lp_amounts.iter().copied()),
+        asset_mint,
+        |venue| (venue.vault_state.lp.mint, asset_mint),
+        |_, quote| Some(quote.expected_output),
+    )
+}
+
+fn best_venue<'a>(
+    candidates: impl Iterator<Item = (&'a VoltrVaultVenue, u64)>,
+    asset_mint: Pubkey,
+    mints: impl Fn(&VoltrVaultVenue) -> (Pubkey, Pubkey),
+    score: impl Fn(&VoltrVaultVenue, &QuoteResult) -> Option<u64>,
+) -> Option<(&'a VoltrVaultVenue, QuoteResult)> {
+    candidates
+        .filter(|(venue, _)| venue.vault_state.asset.mint == asset_mint)
+        .filter_map(|(venue, amount)| {
+            let (input_mint, output_mint) = mints(venue);
+            let quote = venue
+                .quote(QuoteRequest {
+                    input_mint,
+                    output_mint,
+                    amount,
+                    swap_type: SwapType::ExactIn,
+                })
+                .ok()?;
+            if quote.not_enough_liquidity || quote.expected_output == 0 {
+                return None;
+            }
+            Some((score(venue, &quote)?, venue, quote))
+        })
+        .max_by(|(a_score, a, _), (b_score, b, _)| {
+            // Reversed key order so equal scores prefer the lower pubkey.
+            a_score
+                .cmp(b_score)
+                .then_with(|| b.vault_key.cmp(&a.vault_key))
+        })
+        .map(|(_, venue, quote)| (venue, quote))
+}
+
+/// Asset value of the LP minted by a deposit quote at the vault's current
+/// share price. The first depositor owns the whole vault, so an empty vault
+/// values the LP at the deposited amount.
+fn deposit_value_in_asset(venue: &VoltrVaultVenue, quote: &QuoteResult) -> Option<u64> {
+    let total_lp = venue
+        .vault_state
+        .get_total_lp_supply_incl_fees(venue.lp_mint_supply)
+        .ok()?;
+    if total_lp == 0 {
+        return Some(quote.amount);
+    }
+    let value = quote.expected_output as u128 * venue.vault_state.get_total_asset_value() as u128
+        / total_lp as u128;
+    u64::try_from(value).ok()
+}
@@ -0,0 +1,79 @@
+//! Input amounts for testing quotes against execution, shared with
+//! downstream routers.
+//!
+//! Uniform sampling over a venue's bounds almost never lands near the lower
+//! bound, where the dead weight and fee rounding matter, so amounts are
+//! sampled log-uniformly. Routers validating their integration should run
+//! `boundary_probe_amounts()` as well: the lower bound, the max cap and the
+//! idle balance are where a venue and the program are most likely to
+//! disagree.
+
+use rand::Rng;
+use titan_integration_template::trading_venue::error::TradingVenueError;
+
+use crate::voltr_venue::{SwapDirection, VoltrVaultVenue};
+
+/// A log-uniformly sampled amount in `[lo, hi]`.
+///
+/// Panics unless `1 <= lo <= hi`.
+pub fn sample_log_uniform_u64(lo: u64, hi: u64) -> u64 {
+    assert!(lo >= 1, "log-uniform sampling requires lo >= 1");
+    assert!(lo <= hi);
+
+    let log_lo = (lo as f64).ln();
+    let log_hi = (hi as f64).ln();
+
+    let r: f64 = rand::rng().random();
+    let log_val = log_lo + r * (log_hi - log_lo);
+
+    (log_val.exp() as u64).clamp(lo, hi)
+}
+
+/// `n` amounts sampled log-uniformly within the `bounds()` of `direction`,
+/// none when nothing can be filled.
+pub fn sample_amounts_for_direction(
+    venue: &VoltrVaultVenue,
+    direction: SwapDirection,
+    n: usize,
+) -> Result<Vec<u64>, TradingVenueError> {
+    let (lower, upper) = venue.direction_bounds(direction)?;
+    if upper == 0 {
+        return Ok(Vec::new());
+    }
+    Ok((0..n)
+        .map(|_| sample_log_uniform_u64(lower, upper))
+        .collect())
+}
+
+/// Positive amounts on either side of every edge of `direction`, sorted and
+/// without duplicates.
+///
+/// The edges are the lower and upper `bounds()`, the room left under the
+/// max cap for a capped vault's deposits, and the largest redeem the idle
+/// balance covers. Amounts just past an edge are included on purpose: the
+/// venue must refuse them exactly when the program would.
+pub fn boundary_probe_amounts(
+    venue: &VoltrVaultVenue,
+    direction: SwapDirection,
+) -> Result<Vec<u64>, TradingVenueError> {
+    let (lower, upper) = venue.direction_bounds(direction)?;
+    let mut edges = Vec::new();
+    if upper > 0 {
+        edges.extend([lower, upper]);
+    }
+    if let Some(room) = venue.direction_info(direction).capacity_limit {
+        edges.push(room);
+    }
+    if direction == SwapDirection::Redeem {
+        edges.push(venue.max_amount_within_capacity(&venue.direction_request(direction, u64::MAX)));
+    }
+
+    let mut probes: Vec<u64> = edges
+        .into_iter()
+        .flat_map(|edge| [edge.saturating_sub(1), edge, edge.saturating_add(1)])
+        .filter(|&amount| amount > 0)
+        .collect();
+    probes.sort_unstable();
+    probes.dedup();
+    Ok(probes)
+}
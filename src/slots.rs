@@ -0,0 +1,104 @@
+use async_trait::async_trait;
+use solana_account::Account;
+use solana_pubkey::Pubkey;
+
+use titan_integration_template::{
+    account_caching::AccountsCache, trading_venue::error::TradingVenueError,
+};
+
+use crate::voltr_venue::VoltrVaultVenue;
+
+/// An `AccountsCache` that can tell which slot each account was read at.
+#[async_trait]
+pub trait SlotAwareAccountsCache: AccountsCache {
+    /// Fetch `pubkeys` together with the slot each account was observed at,
+    /// `None` when the slot is unknown.
+    async fn get_accounts_with_slots(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> Result<Vec<(Option<Account>, Option<u64>)>, TradingVenueError>;
+}
+
+/// Difference between the newest and oldest known slot, `None` when no
+/// slot is known.
+fn slot_span(fetched: &[(Option<Account>, Option<u64>)]) -> Option<(u64, u64)> {
+    let slots = fetched.iter().filter_map(|(_, slot)| *slot);
+    let min = slots.clone().min()?;
+    let max = slots.max()?;
+    Some((max - min, max))
+}
+
+impl VoltrVaultVenue {
+    /// `update_state()` through a cache that reports account slots.
+    ///
+    /// The vault and its token accounts are fetched in one call. When their
+    /// slots span more than `max_slot_span`, the fetch is retried up to
+    /// `inconsistent_fetch_retries` times; if every attempt is inconsistent
+    /// the last one is loaded anyway and the venue reports
+    /// `VenueStatus::Inconsistent` until a consistent refresh. Accounts
+    /// without a slot are not checked. Failures count toward
+    /// `VenueStatus::Stale`.
+    pub async fn update_state_with_slots(
+        &mut self,
+        cache: &dyn SlotAwareAccountsCache,
+    ) -> Result<(), TradingVenueError> {
+        let result = self.load_with_slots(cache).await;
+        self.record_refresh(result)
+    }
+
+    /// `update_state_with_slots()` before its outcome is recorded.
+    async fn load_with_slots(
+        &mut self,
+        cache: &dyn SlotAwareAccountsCache,
+    ) -> Result<(), TradingVenueError> {
+        if !self.vault_loaded {
+            let vault_account = cache.get_accounts(&[self.vault_key]).await?;
+            self.load_vault_account(vault_account[0].as_ref())?;
+        }
+
+        let [lp_mint, asset_mint, idle_ata] = self.token_account_keys();
+        let pubkeys = [self.vault_key, lp_mint, asset_mint, idle_ata];
+        let max_span = self.config().max_slot_span;
+
+        let mut attempts_left = self.config().inconsistent_fetch_retries;
+        let (fetched, span) = loop {
+            let fetched = cache.get_accounts_with_slots(&pubkeys).await?;
+            let span = slot_span(&fetched);
+            match span {
+                Some((span_slots, _)) if span_slots > max_span && attempts_left > 0 => {
+                    log::debug!(
+                        "Vault {} accounts span {} slots, refetching",
+                        self.vault_key,
+                        span_slots
+                    );
+                    attempts_left -= 1;
+                }
+                _ => break (fetched, span),
+            }
+        };
+
+        let accounts: Vec<Option<Account>> =
+            fetched.into_iter().map(|(account, _)| account).collect();
+        self.load_accounts(&accounts)?;
+
+        self.state_slot = span.map(|(_, newest)| newest);
+        self.inconsistent_slot_span = span
+            .map(|(span_slots, _)| span_slots)
+            .filter(|span_slots| *span_slots > max_span);
+        if let Some(span_slots) = self.inconsistent_slot_span {
+            log::warn!(
+                "Vault {} state is inconsistent: accounts span {} slots (max {})",
+                self.vault_key,
+                span_slots,
+                max_span
+            );
+        }
+        Ok(())
+    }
+
+    /// Newest slot among the accounts loaded by the last refresh, when the
+    /// cache reported slots.
+    pub fn state_slot(&self) -> Option<u64> {
+        self.state_slot
+    }
+}
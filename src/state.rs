@@ -1,11 +1,54 @@
-use anyhow::Result;
+use std::fmt;
+
 use solana_pubkey::Pubkey;
 
-use crate::errors::VoltrError;
+use crate::core::{calc_locked_profit, MAX_FEE_BPS};
+use crate::discriminators;
+use crate::errors::{VaultDecodeError, VoltrError};
+use crate::fixed::U80F48;
 
 const DISCRIMINATOR_SIZE: usize = 8;
 
-#[derive(Clone, Debug)]
+/// Discriminator plus the serialized fields `Vault::load` reads.
+pub const VAULT_MIN_DATA_LEN: usize = DISCRIMINATOR_SIZE + 680;
+
+/// Anchor account discriminator for the `Vault` account type.
+pub fn vault_discriminator() -> [u8; 8] {
+    discriminators::vault_account()
+}
+
+/// The `N` bytes of `data` at `offset`, failing with `AccountTooShort` when
+/// they run past its end.
+pub(crate) fn bytes_at<const N: usize>(data: &[u8], offset: usize) -> Result<[u8; N], VoltrError> {
+    data.get(offset..offset + N)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(VoltrError::AccountTooShort {
+            len: data.len(),
+            min: offset + N,
+        })
+}
+
+pub(crate) fn u8_at(data: &[u8], offset: usize) -> Result<u8, VoltrError> {
+    bytes_at::<1>(data, offset).map(|[byte]| byte)
+}
+
+fn u16_at(data: &[u8], offset: usize) -> Result<u16, VoltrError> {
+    bytes_at(data, offset).map(u16::from_le_bytes)
+}
+
+pub(crate) fn u64_at(data: &[u8], offset: usize) -> Result<u64, VoltrError> {
+    bytes_at(data, offset).map(u64::from_le_bytes)
+}
+
+pub(crate) fn u128_at(data: &[u8], offset: usize) -> Result<u128, VoltrError> {
+    bytes_at(data, offset).map(u128::from_le_bytes)
+}
+
+pub(crate) fn pubkey_at(data: &[u8], offset: usize) -> Result<Pubkey, VoltrError> {
+    bytes_at(data, offset).map(Pubkey::new_from_array)
+}
+
+#[derive(Clone, Debug, Default)]
 pub struct Vault {
     pub asset: VaultAsset,
     pub lp: VaultLp,
@@ -20,7 +63,20 @@ pub struct Vault {
 }
 
 impl Vault {
-    pub fn load(account_data: &[u8]) -> Result<Self> {
+    /// Parse vault account data, failing with `AccountTooShort` or
+    /// `InvalidDiscriminator` when it is not a `Vault` account. Whether the
+    /// vault can be quoted is left to [`Vault::validate`].
+    pub fn load(account_data: &[u8]) -> Result<Self, VoltrError> {
+        if account_data.len() < VAULT_MIN_DATA_LEN {
+            return Err(VoltrError::AccountTooShort {
+                len: account_data.len(),
+                min: VAULT_MIN_DATA_LEN,
+            });
+        }
+        let found = bytes_at(account_data, 0)?;
+        if found != vault_discriminator() {
+            return Err(VoltrError::InvalidDiscriminator { found });
+        }
         let d = DISCRIMINATOR_SIZE;
 
         let asset = VaultAsset::load(&account_data[d + 96..d + 264])?;
@@ -31,12 +87,10 @@ impl Vault {
             FeeConfiguration::load(&account_data[d + 504..d + 552])?;
         let fee_update = FeeUpdate::load(&account_data[d + 552..d + 568])?;
         let fee_state = FeeState::load(&account_data[d + 568..d + 608])?;
-        let dead_weight =
-            u64::from_le_bytes(account_data[d + 608..d + 616].try_into()?);
+        let dead_weight = u64_at(account_data, d + 608)?;
         let high_water_mark =
             HighWaterMark::load(&account_data[d + 616..d + 648])?;
-        let last_updated_ts =
-            u64::from_le_bytes(account_data[d + 648..d + 656].try_into()?);
+        let last_updated_ts = u64_at(account_data, d + 648)?;
         let locked_profit_state =
             LockedProfitState::load(&account_data[d + 664..d + 680])?;
 
@@ -54,34 +108,80 @@ impl Vault {
         })
     }
 
+    /// [`Vault::load`] account data of unknown origin, such as a dump pasted
+    /// by a user, with the failures of `from_base64()`.
+    pub fn decode(account_data: &[u8]) -> Result<Self, VaultDecodeError> {
+        Ok(Self::load(account_data)?)
+    }
+
+    /// [`Vault::decode`] the standard base64 encoding of the account data,
+    /// as returned by `getAccountInfo` with `encoding: "base64"`.
+    pub fn from_base64(s: &str) -> Result<Self, VaultDecodeError> {
+        use base64::Engine;
+
+        let data = base64::engine::general_purpose::STANDARD.decode(s.trim())?;
+        Self::decode(&data)
+    }
+
+    /// Fail unless the vault can be quoted: `InvalidVaultMints` when its
+    /// asset and LP mints are the same or either is unset, which would make
+    /// the swap direction ambiguous, and `InvalidFeeConfiguration` per
+    /// `FeeConfiguration::validate()`.
+    pub fn validate(&self) -> Result<(), VoltrError> {
+        let (asset_mint, lp_mint) = (self.asset.mint, self.lp.mint);
+        if asset_mint == lp_mint || asset_mint == Pubkey::default() || lp_mint == Pubkey::default()
+        {
+            return Err(VoltrError::InvalidVaultMints);
+        }
+        self.fee_configuration.validate()
+    }
+
     pub fn get_total_asset_value(&self) -> u64 {
         self.asset.total_value
     }
 
-    pub fn get_total_accumulated_lp_fees(&self) -> Result<u64> {
+    pub fn get_total_accumulated_lp_fees(&self) -> Result<u64, VoltrError> {
         self.fee_state
             .accumulated_lp_admin_fees
             .checked_add(self.fee_state.accumulated_lp_manager_fees)
             .and_then(|s| s.checked_add(self.fee_state.accumulated_lp_protocol_fees))
-            .ok_or_else(|| VoltrError::MathOverflow.into())
+            .ok_or(VoltrError::MathOverflow)
     }
 
-    pub fn get_total_lp_supply_incl_fees(&self, total_lp_supply_excl_fees: u64) -> Result<u64> {
-        self.get_total_accumulated_lp_fees()?
-            .checked_add(total_lp_supply_excl_fees)
+    /// Components of the LP supply the program prices against, given the LP
+    /// mint's `mint_supply`.
+    pub fn lp_supply_breakdown(&self, mint_supply: u64) -> Result<LpSupplyBreakdown, VoltrError> {
+        let total = self
+            .get_total_accumulated_lp_fees()?
+            .checked_add(mint_supply)
             .and_then(|s: u64| s.checked_add(self.dead_weight))
-            .ok_or_else(|| VoltrError::MathOverflow.into())
+            .ok_or(VoltrError::MathOverflow)?;
+        Ok(LpSupplyBreakdown {
+            mint_supply,
+            manager_fees: self.fee_state.accumulated_lp_manager_fees,
+            admin_fees: self.fee_state.accumulated_lp_admin_fees,
+            protocol_fees: self.fee_state.accumulated_lp_protocol_fees,
+            dead_weight: self.dead_weight,
+            total,
+        })
     }
 
-    pub fn get_total_fee_configuration_management_fee(&self) -> Result<u16> {
+    pub fn get_total_lp_supply_incl_fees(
+        &self,
+        total_lp_supply_excl_fees: u64,
+    ) -> Result<u64, VoltrError> {
+        Ok(self.lp_supply_breakdown(total_lp_supply_excl_fees)?.total)
+    }
+
+    pub fn get_total_fee_configuration_management_fee(&self) -> Result<u16, VoltrError> {
         self.fee_configuration
             .admin_management_fee
             .checked_add(self.fee_configuration.manager_management_fee)
             .and_then(|s| s.checked_add(self.fee_configuration.protocol_management_fee))
-            .ok_or_else(|| VoltrError::MathOverflow.into())
+            .ok_or(VoltrError::MathOverflow)
     }
 
-    pub fn get_unlocked_asset_value(&self, current_ts: u64) -> Result<u64> {
+    pub fn get_unlocked_asset_value(&self, current_ts: u64) -> Result<u64, VoltrError> {
         let locked_profit = self.locked_profit_state.calculate_locked_profit(
             self.vault_configuration.locked_profit_degradation_duration,
             current_ts,
@@ -89,19 +189,48 @@ impl Vault {
         self.asset
             .total_value
             .checked_sub(locked_profit)
-            .ok_or_else(|| VoltrError::MathOverflow.into())
+            .ok_or(VoltrError::MathOverflow)
     }
 
-    pub fn get_total_fee_configuration_performance_fee(&self) -> Result<u16> {
+    pub fn get_total_fee_configuration_performance_fee(&self) -> Result<u16, VoltrError> {
         self.fee_configuration
             .admin_performance_fee
             .checked_add(self.fee_configuration.manager_performance_fee)
             .and_then(|s| s.checked_add(self.fee_configuration.protocol_performance_fee))
-            .ok_or_else(|| VoltrError::MathOverflow.into())
+            .ok_or(VoltrError::MathOverflow)
+    }
+}
+
+impl TryFrom<&[u8]> for Vault {
+    type Error = VoltrError;
+
+    /// Same as [`Vault::load`].
+    fn try_from(account_data: &[u8]) -> Result<Self, VoltrError> {
+        Self::load(account_data)
     }
 }
 
-#[derive(Clone, Debug)]
+/// LP supply as the program counts it: minted LP, fee LP accrued but not
+/// yet minted, and the dead weight.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LpSupplyBreakdown {
+    pub mint_supply: u64,
+    pub manager_fees: u64,
+    pub admin_fees: u64,
+    pub protocol_fees: u64,
+    pub dead_weight: u64,
+    /// Sum of all components.
+    pub total: u64,
+}
+
+impl LpSupplyBreakdown {
+    /// Fee LP of all parties; cannot overflow since `total` did not.
+    pub fn accumulated_fees(&self) -> u64 {
+        self.manager_fees + self.admin_fees + self.protocol_fees
+    }
+}
+
+#[derive(Clone, Debug, Default)]
 pub struct VaultAsset {
     pub mint: Pubkey,
     pub idle_ata: Pubkey,
@@ -110,17 +239,17 @@ pub struct VaultAsset {
 }
 
 impl VaultAsset {
-    pub fn load(data: &[u8]) -> Result<Self> {
+    pub fn load(data: &[u8]) -> Result<Self, VoltrError> {
         Ok(VaultAsset {
-            mint: Pubkey::new_from_array(data[0..32].try_into()?),
-            idle_ata: Pubkey::new_from_array(data[32..64].try_into()?),
-            total_value: u64::from_le_bytes(data[64..72].try_into()?),
-            idle_ata_auth_bump: data[72],
+            mint: pubkey_at(data, 0)?,
+            idle_ata: pubkey_at(data, 32)?,
+            total_value: u64_at(data, 64)?,
+            idle_ata_auth_bump: u8_at(data, 72)?,
         })
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct VaultLp {
     pub mint: Pubkey,
     pub mint_bump: u8,
@@ -128,16 +257,16 @@ pub struct VaultLp {
 }
 
 impl VaultLp {
-    pub fn load(data: &[u8]) -> Result<Self> {
+    pub fn load(data: &[u8]) -> Result<Self, VoltrError> {
         Ok(VaultLp {
-            mint: Pubkey::new_from_array(data[0..32].try_into()?),
-            mint_bump: data[32],
-            mint_auth_bump: data[33],
+            mint: pubkey_at(data, 0)?,
+            mint_bump: u8_at(data, 32)?,
+            mint_auth_bump: u8_at(data, 33)?,
         })
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct VaultConfiguration {
     pub max_cap: u64,
     pub start_at_ts: u64,
@@ -147,18 +276,18 @@ pub struct VaultConfiguration {
 }
 
 impl VaultConfiguration {
-    pub fn load(data: &[u8]) -> Result<Self> {
+    pub fn load(data: &[u8]) -> Result<Self, VoltrError> {
         Ok(VaultConfiguration {
-            max_cap: u64::from_le_bytes(data[0..8].try_into()?),
-            start_at_ts: u64::from_le_bytes(data[8..16].try_into()?),
-            locked_profit_degradation_duration: u64::from_le_bytes(data[16..24].try_into()?),
-            withdrawal_waiting_period: u64::from_le_bytes(data[24..32].try_into()?),
-            disabled_operations: u16::from_le_bytes(data[32..34].try_into()?),
+            max_cap: u64_at(data, 0)?,
+            start_at_ts: u64_at(data, 8)?,
+            locked_profit_degradation_duration: u64_at(data, 16)?,
+            withdrawal_waiting_period: u64_at(data, 24)?,
+            disabled_operations: u16_at(data, 32)?,
         })
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct FeeConfiguration {
     pub manager_performance_fee: u16,
     pub admin_performance_fee: u16,
@@ -171,36 +300,66 @@ pub struct FeeConfiguration {
 }
 
 impl FeeConfiguration {
-    pub fn load(data: &[u8]) -> Result<Self> {
+    pub fn load(data: &[u8]) -> Result<Self, VoltrError> {
         Ok(FeeConfiguration {
-            manager_performance_fee: u16::from_le_bytes(data[0..2].try_into()?),
-            admin_performance_fee: u16::from_le_bytes(data[2..4].try_into()?),
-            manager_management_fee: u16::from_le_bytes(data[4..6].try_into()?),
-            admin_management_fee: u16::from_le_bytes(data[6..8].try_into()?),
-            redemption_fee: u16::from_le_bytes(data[8..10].try_into()?),
-            issuance_fee: u16::from_le_bytes(data[10..12].try_into()?),
-            protocol_performance_fee: u16::from_le_bytes(data[12..14].try_into()?),
-            protocol_management_fee: u16::from_le_bytes(data[14..16].try_into()?),
+            manager_performance_fee: u16_at(data, 0)?,
+            admin_performance_fee: u16_at(data, 2)?,
+            manager_management_fee: u16_at(data, 4)?,
+            admin_management_fee: u16_at(data, 6)?,
+            redemption_fee: u16_at(data, 8)?,
+            issuance_fee: u16_at(data, 10)?,
+            protocol_performance_fee: u16_at(data, 12)?,
+            protocol_management_fee: u16_at(data, 14)?,
         })
     }
+
+    /// Fail with `FieldOutOfRange` when the issuance or redemption fee is
+    /// above `MAX_FEE_BPS`, and with `InvalidFeeConfiguration` when the
+    /// management or the performance fees of all recipients add up to more
+    /// than it, neither of which the program ever charges.
+    pub fn validate(&self) -> Result<(), VoltrError> {
+        for (field, fee) in [
+            ("issuance_fee", self.issuance_fee),
+            ("redemption_fee", self.redemption_fee),
+        ] {
+            if fee > MAX_FEE_BPS {
+                return Err(VoltrError::FieldOutOfRange { field });
+            }
+        }
+        let sum = |fees: [u16; 3]| fees.iter().map(|&fee| u32::from(fee)).sum::<u32>();
+        let management = sum([
+            self.manager_management_fee,
+            self.admin_management_fee,
+            self.protocol_management_fee,
+        ]);
+        let performance = sum([
+            self.manager_performance_fee,
+            self.admin_performance_fee,
+            self.protocol_performance_fee,
+        ]);
+        if management > u32::from(MAX_FEE_BPS) || performance > u32::from(MAX_FEE_BPS) {
+            return Err(VoltrError::InvalidFeeConfiguration);
+        }
+        Ok(())
+    }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Default)]
 pub struct FeeUpdate {
     pub last_performance_fee_update_ts: u64,
     pub last_management_fee_update_ts: u64,
 }
 
 impl FeeUpdate {
-    pub fn load(data: &[u8]) -> Result<Self> {
+    pub fn load(data: &[u8]) -> Result<Self, VoltrError> {
         Ok(FeeUpdate {
-            last_performance_fee_update_ts: u64::from_le_bytes(data[0..8].try_into()?),
-            last_management_fee_update_ts: u64::from_le_bytes(data[8..16].try_into()?),
+            last_performance_fee_update_ts: u64_at(data, 0)?,
+            last_management_fee_update_ts: u64_at(data, 8)?,
         })
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct FeeState {
     pub accumulated_lp_manager_fees: u64,
     pub accumulated_lp_admin_fees: u64,
@@ -208,41 +367,49 @@ pub struct FeeState {
 }
 
 impl FeeState {
-    pub fn load(data: &[u8]) -> Result<Self> {
+    pub fn load(data: &[u8]) -> Result<Self, VoltrError> {
         Ok(FeeState {
-            accumulated_lp_manager_fees: u64::from_le_bytes(data[0..8].try_into()?),
-            accumulated_lp_admin_fees: u64::from_le_bytes(data[8..16].try_into()?),
-            accumulated_lp_protocol_fees: u64::from_le_bytes(data[16..24].try_into()?),
+            accumulated_lp_manager_fees: u64_at(data, 0)?,
+            accumulated_lp_admin_fees: u64_at(data, 8)?,
+            accumulated_lp_protocol_fees: u64_at(data, 16)?,
         })
     }
 }
 
-#[derive(Clone, Debug)]
+/// Performance fees are only charged when the manager reports profit, which
+/// quotes never project, so quoting does not read this. A zero
+/// `last_updated_ts` means the mark was never set.
+#[derive(Clone, Default)]
 pub struct HighWaterMark {
     pub highest_asset_per_lp_decimal_bits: u128,
     pub last_updated_ts: u64,
 }
 
 impl HighWaterMark {
-    pub fn load(data: &[u8]) -> Result<Self> {
+    pub fn load(data: &[u8]) -> Result<Self, VoltrError> {
         Ok(HighWaterMark {
-            highest_asset_per_lp_decimal_bits: u128::from_le_bytes(data[0..16].try_into()?),
-            last_updated_ts: u64::from_le_bytes(data[16..24].try_into()?),
+            highest_asset_per_lp_decimal_bits: u128_at(data, 0)?,
+            last_updated_ts: u64_at(data, 16)?,
         })
     }
+
+    /// The highest asset per LP recorded, zero if never set.
+    pub fn highest_asset_per_lp(&self) -> U80F48 {
+        U80F48::from_bits(self.highest_asset_per_lp_decimal_bits)
+    }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Default)]
 pub struct LockedProfitState {
     pub last_updated_locked_profit: u64,
     pub last_report: u64,
 }
 
 impl LockedProfitState {
-    pub fn load(data: &[u8]) -> Result<Self> {
+    pub fn load(data: &[u8]) -> Result<Self, VoltrError> {
         Ok(LockedProfitState {
-            last_updated_locked_profit: u64::from_le_bytes(data[0..8].try_into()?),
-            last_report: u64::from_le_bytes(data[8..16].try_into()?),
+            last_updated_locked_profit: u64_at(data, 0)?,
+            last_report: u64_at(data, 8)?,
         })
     }
 
@@ -250,19 +417,83 @@ impl LockedProfitState {
         &self,
         locked_profit_degradation_duration: u64,
         current_time: u64,
-    ) -> Result<u64> {
-        let duration = current_time.saturating_sub(self.last_report) as u128;
-        let degradation_duration = locked_profit_degradation_duration as u128;
+    ) -> Result<u64, VoltrError> {
+        calc_locked_profit(
+            self.last_updated_locked_profit,
+            self.last_report,
+            locked_profit_degradation_duration,
+            current_time,
+        )
+        .map_err(VoltrError::from)
+    }
+}
+
+/// Unix timestamp shown raw and as a UTC date-time.
+struct UnixTs(u64);
 
-        if duration > degradation_duration || degradation_duration == 0 {
-            return Ok(0);
+impl fmt::Debug for UnixTs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0 == 0 {
+            return write!(f, "0 (unset)");
         }
+        // Civil-from-days conversion (proleptic Gregorian calendar).
+        let days = (self.0 / 86_400) as i64;
+        let secs = self.0 % 86_400;
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z - era * 146_097;
+        let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + i64::from(month <= 2);
+        write!(
+            f,
+            "{} ({:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z)",
+            self.0,
+            year,
+            month,
+            day,
+            secs / 3_600,
+            secs / 60 % 60,
+            secs % 60
+        )
+    }
+}
 
-        let locked_profit = (self.last_updated_locked_profit as u128)
-            .checked_mul(degradation_duration.saturating_sub(duration))
-            .and_then(|v| v.checked_div(degradation_duration))
-            .ok_or(VoltrError::MathOverflow)?;
+impl fmt::Debug for FeeUpdate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FeeUpdate")
+            .field(
+                "last_performance_fee_update_ts",
+                &UnixTs(self.last_performance_fee_update_ts),
+            )
+            .field(
+                "last_management_fee_update_ts",
+                &UnixTs(self.last_management_fee_update_ts),
+            )
+            .finish()
+    }
+}
+
+impl fmt::Debug for HighWaterMark {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HighWaterMark")
+            .field(
+                "highest_asset_per_lp_decimal_bits",
+                &format_args!("{:#x}", self.highest_asset_per_lp_decimal_bits),
+            )
+            .field("last_updated_ts", &UnixTs(self.last_updated_ts))
+            .finish()
+    }
+}
 
-        Ok(u64::try_from(locked_profit)?)
+impl fmt::Debug for LockedProfitState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LockedProfitState")
+            .field("last_updated_locked_profit", &self.last_updated_locked_profit)
+            .field("last_report", &UnixTs(self.last_report))
+            .finish()
     }
 }
@@ -0,0 +1,45 @@
+//! `anyhow` signatures of the [`crate::state`] parsers and getters, kept for
+//! callers written before they returned `VoltrError`.
+//!
+//! These are free functions: `Vault::load(data)` now returns `VoltrError`,
+//! which `?` still converts into `anyhow::Error`, and a caller needing the
+//! old `anyhow::Result` itself calls `state_compat::load(data)` instead.
+
+use anyhow::Result;
+
+use crate::state::{LpSupplyBreakdown, Vault};
+
+/// See [`Vault::load`].
+pub fn load(account_data: &[u8]) -> Result<Vault> {
+    Ok(Vault::load(account_data)?)
+}
+
+/// See [`Vault::get_total_accumulated_lp_fees`].
+pub fn get_total_accumulated_lp_fees(vault: &Vault) -> Result<u64> {
+    Ok(vault.get_total_accumulated_lp_fees()?)
+}
+
+/// See [`Vault::lp_supply_breakdown`].
+pub fn lp_supply_breakdown(vault: &Vault, mint_supply: u64) -> Result<LpSupplyBreakdown> {
+    Ok(vault.lp_supply_breakdown(mint_supply)?)
+}
+
+/// See [`Vault::get_total_lp_supply_incl_fees`].
+pub fn get_total_lp_supply_incl_fees(vault: &Vault, total_lp_supply_excl_fees: u64) -> Result<u64> {
+    Ok(vault.get_total_lp_supply_incl_fees(total_lp_supply_excl_fees)?)
+}
+
+/// See [`Vault::get_total_fee_configuration_management_fee`].
+pub fn get_total_fee_configuration_management_fee(vault: &Vault) -> Result<u16> {
+    Ok(vault.get_total_fee_configuration_management_fee()?)
+}
+
+/// See [`Vault::get_unlocked_asset_value`].
+pub fn get_unlocked_asset_value(vault: &Vault, current_ts: u64) -> Result<u64> {
+    Ok(vault.get_unlocked_asset_value(current_ts)?)
+}
+
+/// See [`Vault::get_total_fee_configuration_performance_fee`].
+pub fn get_total_fee_configuration_performance_fee(vault: &Vault) -> Result<u16> {
+    Ok(vault.get_total_fee_configuration_performance_fee()?)
+}
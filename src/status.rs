@@ -0,0 +1,137 @@
+use solana_pubkey::Pubkey;
+use titan_integration_template::trading_venue::{error::TradingVenueError, TradingVenue};
+
+use crate::voltr_venue::VoltrVaultVenue;
+
+/// Operational state of a venue.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VenueStatus {
+    /// No successful `update_state()` yet; quoting fails.
+    Uninitialized,
+    /// State loaded from a consistent (or unchecked) fetch.
+    Ready,
+    /// The last `failed_refreshes` refreshes failed, at least
+    /// `config.stale_after_failed_refreshes`. Quotes are still served from
+    /// the last good state, `state_age_secs` old, and flagged `degraded`.
+    Stale {
+        failed_refreshes: u32,
+        state_age_secs: u64,
+    },
+    /// Stale, with the last good state older than
+    /// `config.max_stale_state_age_secs`; quoting fails until a refresh
+    /// succeeds.
+    Expired {
+        failed_refreshes: u32,
+        state_age_secs: u64,
+    },
+    /// The last slot-aware refresh loaded accounts spanning `slot_span`
+    /// slots, more than the configured maximum. Quotes are still served.
+    Inconsistent { slot_span: u64 },
+    /// The LP mint is no longer controlled by this venue's program, as when
+    /// the vault was migrated to another program or closed; quoting fails.
+    /// The observed authorities point operators to the successor.
+    Migrated(LpMintAuthorityMismatch),
+}
+
+/// LP mint authorities that do not belong to the vault under the venue's
+/// program.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LpMintAuthorityMismatch {
+    /// `vault_lp_mint_auth_pda` for the configured program.
+    pub expected: Pubkey,
+    /// Mint authority found; `None` when the mint has none.
+    pub mint_authority: Option<Pubkey>,
+    /// Freeze authority found. Only `None` or `expected` are accepted.
+    pub freeze_authority: Option<Pubkey>,
+}
+
+/// Refreshes failed in a row, for dashboards. Times are on the venue's
+/// clock.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RefreshStreak {
+    /// Refreshes failed since the last success.
+    pub failures: u32,
+    /// When the first and the latest of them failed.
+    pub first_failure_ts: Option<u64>,
+    pub last_failure_ts: Option<u64>,
+    /// When a refresh last succeeded, `None` before the first.
+    pub last_success_ts: Option<u64>,
+}
+
+impl VoltrVaultVenue {
+    pub fn status(&self) -> VenueStatus {
+        if !self.initialized() {
+            return VenueStatus::Uninitialized;
+        }
+        if let Some(mismatch) = self.lp_mint_authority_mismatch {
+            return VenueStatus::Migrated(mismatch);
+        }
+        if self.is_degraded() {
+            let (failed_refreshes, state_age_secs) =
+                (self.refresh_streak.failures, self.state_age_secs());
+            return match self.expired_state_age() {
+                Some(_) => VenueStatus::Expired {
+                    failed_refreshes,
+                    state_age_secs,
+                },
+                None => VenueStatus::Stale {
+                    failed_refreshes,
+                    state_age_secs,
+                },
+            };
+        }
+        match self.inconsistent_slot_span {
+            Some(slot_span) => VenueStatus::Inconsistent { slot_span },
+            None => VenueStatus::Ready,
+        }
+    }
+
+    pub fn refresh_streak(&self) -> RefreshStreak {
+        self.refresh_streak
+    }
+
+    /// Seconds, on the venue's clock, since the last successful refresh, or
+    /// since the state was loaded when no refresh succeeded yet.
+    pub fn state_age_secs(&self) -> u64 {
+        let loaded_at = self.refresh_streak.last_success_ts.unwrap_or(self.state_ts);
+        self.effective_ts().saturating_sub(loaded_at)
+    }
+
+    /// Whether enough refreshes failed in a row for quotes to be served
+    /// from stale state, per `config.stale_after_failed_refreshes`.
+    pub fn is_degraded(&self) -> bool {
+        let threshold = self.config().stale_after_failed_refreshes;
+        threshold > 0 && self.refresh_streak.failures >= threshold
+    }
+
+    /// Age of the state when the venue is degraded past
+    /// `config.max_stale_state_age_secs`.
+    pub(crate) fn expired_state_age(&self) -> Option<u64> {
+        let max_age = self.config().max_stale_state_age_secs?;
+        let age = self.state_age_secs();
+        (self.is_degraded() && age > max_age).then_some(age)
+    }
+
+    /// Count `result` into the streak of failed refreshes and pass it on.
+    pub(crate) fn record_refresh<T>(
+        &mut self,
+        result: Result<T, TradingVenueError>,
+    ) -> Result<T, TradingVenueError> {
+        let now = self.effective_ts();
+        let streak = &mut self.refresh_streak;
+        match &result {
+            Ok(_) => {
+                *streak = RefreshStreak {
+                    last_success_ts: Some(now),
+                    ..RefreshStreak::default()
+                }
+            }
+            Err(_) => {
+                streak.failures = streak.failures.saturating_add(1);
+                streak.first_failure_ts.get_or_insert(now);
+                streak.last_failure_ts = Some(now);
+            }
+        }
+        result
+    }
+}
@@ -0,0 +1,88 @@
+//! Account subscriptions of venues, classified by how often the accounts
+//! change so that infrastructure shared across venues can subscribe to each
+//! account once, at the right frequency.
+
+use std::collections::HashMap;
+
+use solana_pubkey::Pubkey;
+
+use crate::voltr_venue::VoltrVaultVenue;
+
+/// How closely an account has to be followed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SubscriptionPriority {
+    /// Rarely changes and is typically shared by several venues, as an
+    /// asset mint is by every vault of that asset.
+    Static,
+    /// Changes with every swap or crank of the vault and is read by its
+    /// venue alone.
+    HighFrequency,
+}
+
+/// The accounts a venue reads, each with its priority.
+pub type SubscriptionPlan = Vec<(Pubkey, SubscriptionPriority)>;
+
+/// Subscriptions of several venues, each account listed once.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MergedPlan {
+    /// Unique accounts in the order they were first planned, each at the
+    /// highest priority any venue gave it.
+    pub subscriptions: SubscriptionPlan,
+}
+
+impl MergedPlan {
+    /// Unique subscriptions at `priority`.
+    pub fn count(&self, priority: SubscriptionPriority) -> usize {
+        self.subscriptions
+            .iter()
+            .filter(|(_, p)| *p == priority)
+            .count()
+    }
+
+    pub fn len(&self) -> usize {
+        self.subscriptions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.subscriptions.is_empty()
+    }
+}
+
+impl VoltrVaultVenue {
+    /// Accounts `update_state()` reads, by priority: the vault, its idle ATA
+    /// and its LP mint, whose supply moves with every swap, are followed
+    /// closely; the asset mint is static and shared with every other vault
+    /// of the asset.
+    ///
+    /// A venue whose vault is not loaded yet only knows the vault account.
+    pub fn subscription_plan(&self) -> SubscriptionPlan {
+        if !self.vault_loaded {
+            return vec![(self.vault_key, SubscriptionPriority::HighFrequency)];
+        }
+        let [lp_mint, asset_mint, idle_ata] = self.token_account_keys();
+        vec![
+            (self.vault_key, SubscriptionPriority::HighFrequency),
+            (idle_ata, SubscriptionPriority::HighFrequency),
+            (lp_mint, SubscriptionPriority::HighFrequency),
+            (asset_mint, SubscriptionPriority::Static),
+        ]
+    }
+}
+
+/// Merge the `subscription_plan()`s of several venues, keeping each account
+/// once at the highest priority it was planned at: a vault's LP mint that is
+/// also another vault's asset stays high-frequency.
+pub fn merge_subscription_plans(plans: &[SubscriptionPlan]) -> MergedPlan {
+    let mut index: HashMap<Pubkey, usize> = HashMap::new();
+    let mut subscriptions = SubscriptionPlan::new();
+    for (pubkey, priority) in plans.iter().flatten() {
+        match index.get(pubkey) {
+            Some(&i) => subscriptions[i].1 = subscriptions[i].1.max(*priority),
+            None => {
+                index.insert(*pubkey, subscriptions.len());
+                subscriptions.push((*pubkey, *priority));
+            }
+        }
+    }
+    MergedPlan { subscriptions }
+}
@@ -0,0 +1,53 @@
+use std::fmt;
+
+use solana_pubkey::Pubkey;
+
+use crate::voltr_venue::VoltrVaultVenue;
+
+/// The figures that identify a venue's state in a log line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VenueSummary {
+    pub vault_key: Pubkey,
+    pub asset_mint: Pubkey,
+    pub total_value: u64,
+    pub idle_balance: u64,
+    pub lp_supply: u64,
+    pub issuance_fee_bps: u16,
+    pub redemption_fee_bps: u16,
+}
+
+impl fmt::Display for VenueSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Voltr vault {} asset={} tvl={} idle={} lp_supply={} fees i/r={}/{}",
+            self.vault_key,
+            self.asset_mint,
+            self.total_value,
+            self.idle_balance,
+            self.lp_supply,
+            self.issuance_fee_bps,
+            self.redemption_fee_bps
+        )
+    }
+}
+
+impl VoltrVaultVenue {
+    pub fn summary(&self) -> VenueSummary {
+        VenueSummary {
+            vault_key: self.vault_key,
+            asset_mint: self.vault_state.asset.mint,
+            total_value: self.vault_state.get_total_asset_value(),
+            idle_balance: self.asset_idle_balance,
+            lp_supply: self.lp_mint_supply,
+            issuance_fee_bps: self.vault_state.fee_configuration.issuance_fee,
+            redemption_fee_bps: self.vault_state.fee_configuration.redemption_fee,
+        }
+    }
+}
+
+impl fmt::Display for VoltrVaultVenue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.summary().fmt(f)
+    }
+}
@@ -0,0 +1,142 @@
+use solana_account::Account;
+use solana_program::native_token::LAMPORTS_PER_SOL;
+use solana_program_pack::Pack;
+use solana_pubkey::Pubkey;
+use spl_token::state::{Account as TokenAccount, AccountState};
+
+use titan_integration_template::trading_venue::error::TradingVenueError;
+
+use crate::{
+    config::MintMetadata,
+    constants::TOKEN_PROGRAM,
+    state::{
+        FeeConfiguration, FeeState, FeeUpdate, LockedProfitState, Vault, VaultAsset,
+        VaultConfiguration, VaultLp,
+    },
+    voltr_venue::VoltrVaultVenue,
+};
+
+/// Explicit vault state for `VoltrVaultVenue::new_synthetic()`.
+///
+/// The LP mint is always the vault's LP mint PDA with 9 decimals, as on
+/// chain. Defaults describe an empty, fee-free, uncapped Token program vault.
+#[derive(Clone, Debug)]
+pub struct SyntheticVaultParams {
+    pub asset_mint: Pubkey,
+    pub asset_token_program: Pubkey,
+    pub asset_decimals: u8,
+    pub idle_ata: Pubkey,
+    pub total_value: u64,
+    pub idle_balance: u64,
+    pub lp_supply: u64,
+    pub dead_weight: u64,
+    /// When the vault accounting was last updated.
+    pub last_updated_ts: u64,
+    pub vault_configuration: VaultConfiguration,
+    pub fee_configuration: FeeConfiguration,
+    pub fee_update: FeeUpdate,
+    pub fee_state: FeeState,
+    pub locked_profit_state: LockedProfitState,
+}
+
+/// An initialized token account of `mint` owned by `owner` holding `amount`,
+/// for synthetic caches and simulations.
+pub fn token_account(
+    mint: &Pubkey,
+    owner: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+) -> Account {
+    let mut account = Account::new(LAMPORTS_PER_SOL, TokenAccount::LEN, token_program);
+    let token = TokenAccount {
+        mint: *mint,
+        owner: *owner,
+        amount,
+        state: AccountState::Initialized,
+        ..Default::default()
+    };
+    token.pack_into_slice(&mut account.data);
+    account
+}
+
+impl Default for SyntheticVaultParams {
+    fn default() -> Self {
+        Self {
+            asset_mint: Pubkey::default(),
+            asset_token_program: TOKEN_PROGRAM,
+            asset_decimals: 0,
+            idle_ata: Pubkey::default(),
+            total_value: 0,
+            idle_balance: 0,
+            lp_supply: 0,
+            dead_weight: 0,
+            last_updated_ts: 0,
+            vault_configuration: VaultConfiguration::default(),
+            fee_configuration: FeeConfiguration::default(),
+            fee_update: FeeUpdate::default(),
+            fee_state: FeeState::default(),
+            locked_profit_state: LockedProfitState::default(),
+        }
+    }
+}
+
+impl VoltrVaultVenue {
+    /// Create an initialized venue from explicit parameters, without any
+    /// account fetch.
+    ///
+    /// Meant for tests and tooling, e.g. building instructions against a
+    /// localnet without the Voltr program. Quotes and instructions reflect
+    /// `params` only, not any chain state, and the venue never checks them
+    /// against the vault it names; a later `update_state()` replaces them
+    /// with what the cache holds.
+    pub fn new_synthetic(
+        vault_key: Pubkey,
+        params: SyntheticVaultParams,
+    ) -> Result<Self, TradingVenueError> {
+        params.fee_configuration.validate()?;
+        let lp_mint = Self::derive_vault_lp_mint_pda(&vault_key);
+        let vault_state = Vault {
+            asset: VaultAsset {
+                mint: params.asset_mint,
+                idle_ata: params.idle_ata,
+                total_value: params.total_value,
+                ..Default::default()
+            },
+            lp: VaultLp {
+                mint: lp_mint,
+                ..Default::default()
+            },
+            vault_configuration: params.vault_configuration,
+            fee_configuration: params.fee_configuration,
+            fee_update: params.fee_update,
+            fee_state: params.fee_state,
+            dead_weight: params.dead_weight,
+            last_updated_ts: params.last_updated_ts,
+            locked_profit_state: params.locked_profit_state,
+            ..Default::default()
+        };
+
+        let asset_mint_account = MintMetadata {
+            decimals: params.asset_decimals,
+            token_program: params.asset_token_program,
+        }
+        .to_mint_account();
+        let lp_mint_account = MintMetadata {
+            decimals: 9,
+            token_program: TOKEN_PROGRAM,
+        }
+        .to_mint_account();
+
+        let mut venue = Self::new(vault_key, vault_state);
+        venue.lp_mint_supply = params.lp_supply;
+        venue.asset_mint_decimals = params.asset_decimals;
+        venue.asset_token_program = params.asset_token_program;
+        venue.asset_idle_balance = params.idle_balance;
+        venue.constructed_at = None;
+        venue.initialized = true;
+        venue.state_ts = venue.effective_ts();
+        venue.refresh_token_info(Some(&asset_mint_account), &lp_mint_account)?;
+        venue.refresh_lookup_table_keys();
+        Ok(venue)
+    }
+}
@@ -0,0 +1,321 @@
+use std::collections::HashSet;
+
+use solana_instruction::{AccountMeta, Instruction};
+use solana_packet::PACKET_DATA_SIZE;
+use solana_program::system_program::ID as SYSTEM_PROGRAM_ID;
+use solana_pubkey::Pubkey;
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction,
+    hash::Hash,
+    message::{v0, AddressLookupTableAccount, VersionedMessage},
+    signature::{Keypair, Signature},
+    transaction::VersionedTransaction,
+};
+use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+
+use titan_integration_template::trading_venue::{
+    error::TradingVenueError, QuoteRequest, TradingVenue,
+};
+
+use crate::{
+    constants::{
+        DEPOSIT_COMPUTE_UNIT_LIMIT, MAX_HEAP_FRAME_BYTES, MIN_HEAP_FRAME_BYTES, NATIVE_MINT,
+        REDEEM_COMPUTE_UNIT_LIMIT, TOKEN_PROGRAM,
+    },
+    voltr_venue::{SwapDirection, VoltrVaultVenue},
+};
+
+/// Options for assembling a complete swap instruction list.
+#[derive(Clone, Debug)]
+pub struct TxOptions {
+    /// Compute unit limit; defaults to the estimate for the swap direction.
+    pub compute_unit_limit: Option<u32>,
+    /// Priority fee in micro-lamports per compute unit; omitted when `None`.
+    pub compute_unit_price_micro_lamports: Option<u64>,
+    /// Prepend an idempotent creation of the user's destination ATA.
+    pub include_ata_setup: bool,
+    /// Larger heap frame to request ahead of a redeem. The current program
+    /// redeems within the default 32 KiB heap, creating the output ATA in
+    /// the same transaction included; older versions and forks may not.
+    pub redeem_heap_frame: HeapFrameRequest,
+    /// On a vault with `is_native_asset()`, close the user's wSOL ATA after
+    /// the swap, turning its balance and rent back into SOL. Set by default,
+    /// so that a native redeem delivers SOL; unset it when the user held
+    /// wSOL in that ATA before, as closing it unwraps that as well.
+    pub close_wsol_ata: bool,
+}
+
+impl Default for TxOptions {
+    fn default() -> Self {
+        Self {
+            compute_unit_limit: None,
+            compute_unit_price_micro_lamports: None,
+            include_ata_setup: false,
+            redeem_heap_frame: HeapFrameRequest::default(),
+            close_wsol_ata: true,
+        }
+    }
+}
+
+/// When to request a heap frame of the given size, in bytes, for a redeem.
+/// Sizes must be multiples of 1 KiB between `MIN_HEAP_FRAME_BYTES` and
+/// `MAX_HEAP_FRAME_BYTES`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HeapFrameRequest {
+    /// Keep the default heap.
+    #[default]
+    Never,
+    /// Request the heap frame for every redeem.
+    Always(u32),
+    /// Request it when the transaction also creates the output ATA, i.e.
+    /// with `include_ata_setup`.
+    WithAtaSetup(u32),
+}
+
+impl HeapFrameRequest {
+    /// Heap frame to request for a swap in `direction`, if any.
+    fn bytes(self, direction: SwapDirection, include_ata_setup: bool) -> Option<u32> {
+        match (direction, self) {
+            (SwapDirection::Redeem, HeapFrameRequest::Always(bytes)) => Some(bytes),
+            (SwapDirection::Redeem, HeapFrameRequest::WithAtaSetup(bytes)) if include_ata_setup => {
+                Some(bytes)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Swap instructions for several users of one vault, from
+/// `generate_swap_instructions_batch()`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SwapInstructionBatch {
+    /// Per request, in order: an idempotent creation of the user's output
+    /// ATA, then the swap.
+    pub instructions: Vec<Vec<Instruction>>,
+    /// Distinct accounts, program ids included, referenced by the batch.
+    pub unique_accounts: usize,
+    /// Those of `unique_accounts` not in `get_lookup_table_keys()`, which a
+    /// transaction using the venue's lookup table still lists inline.
+    pub unique_accounts_outside_lut: usize,
+}
+
+impl SwapDirection {
+    /// Compute unit limit that covers the swap instruction in this direction.
+    pub fn default_compute_unit_limit(self) -> u32 {
+        match self {
+            SwapDirection::Deposit => DEPOSIT_COMPUTE_UNIT_LIMIT,
+            SwapDirection::Redeem => REDEEM_COMPUTE_UNIT_LIMIT,
+        }
+    }
+}
+
+/// The System program's `Transfer` of `lamports` from `from` to `to`.
+fn system_transfer(from: &Pubkey, to: &Pubkey, lamports: u64) -> Instruction {
+    // Bincode of `SystemInstruction::Transfer`: the variant index as a u32,
+    // then the lamports.
+    let mut data = Vec::with_capacity(12);
+    data.extend_from_slice(&2u32.to_le_bytes());
+    data.extend_from_slice(&lamports.to_le_bytes());
+    Instruction {
+        program_id: SYSTEM_PROGRAM_ID,
+        accounts: vec![AccountMeta::new(*from, true), AccountMeta::new(*to, false)],
+        data,
+    }
+}
+
+impl VoltrVaultVenue {
+    /// Build the ordered instruction list for a swap: compute unit limit,
+    /// optional priority fee, optional heap frame, optional destination ATA
+    /// setup, then the swap.
+    ///
+    /// On a vault of wrapped SOL the user swaps raw SOL: a deposit is
+    /// preceded by `wrap_sol_instructions()`, a redeem by the creation of
+    /// the wSOL ATA whatever `include_ata_setup`. Unless
+    /// `TxOptions::close_wsol_ata` is unset, both are followed by
+    /// `unwrap_sol_instruction()`, which refunds the ATA's rent.
+    ///
+    /// Fails when a heap frame is requested with an invalid size.
+    pub fn build_transaction_instructions(
+        &self,
+        request: QuoteRequest,
+        user: Pubkey,
+        opts: &TxOptions,
+    ) -> Result<Vec<Instruction>, TradingVenueError> {
+        let direction = self.direction_for(&request)?;
+        let native = self.is_native_asset();
+        let mut instructions = Vec::with_capacity(9);
+
+        instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(
+            opts.compute_unit_limit
+                .unwrap_or_else(|| direction.default_compute_unit_limit()),
+        ));
+
+        if let Some(micro_lamports) = opts.compute_unit_price_micro_lamports {
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_price(
+                micro_lamports,
+            ));
+        }
+
+        if let Some(bytes) = opts
+            .redeem_heap_frame
+            .bytes(direction, opts.include_ata_setup)
+        {
+            if !(MIN_HEAP_FRAME_BYTES..=MAX_HEAP_FRAME_BYTES).contains(&bytes) || bytes % 1024 != 0
+            {
+                return Err(TradingVenueError::AmmMethodError(
+                    format!(
+                        "Heap frame of {} bytes is not a multiple of 1024 between {} and {}",
+                        bytes, MIN_HEAP_FRAME_BYTES, MAX_HEAP_FRAME_BYTES
+                    )
+                    .into(),
+                ));
+            }
+            instructions.push(ComputeBudgetInstruction::request_heap_frame(bytes));
+        }
+
+        if native && direction == SwapDirection::Deposit {
+            instructions.extend(self.wrap_sol_instructions(&user, request.amount)?);
+        }
+
+        if opts.include_ata_setup || (native && direction == SwapDirection::Redeem) {
+            let info = self.direction_info(direction);
+            instructions.push(create_associated_token_account_idempotent(
+                &user,
+                &user,
+                &info.output_mint,
+                &info.output_token_program,
+            ));
+        }
+
+        instructions.push(self.generate_swap_instruction(request, user)?);
+        if opts.close_wsol_ata {
+            instructions.extend(self.unwrap_sol_instruction(&user)?);
+        }
+        Ok(instructions)
+    }
+
+    /// Instructions moving `lamports` of the user's SOL into their wSOL ATA,
+    /// created when missing, ahead of a deposit. Empty unless
+    /// `is_native_asset()`.
+    pub fn wrap_sol_instructions(
+        &self,
+        user: &Pubkey,
+        lamports: u64,
+    ) -> Result<Vec<Instruction>, TradingVenueError> {
+        if !self.is_native_asset() {
+            return Ok(Vec::new());
+        }
+        let ata = spl_associated_token_account::get_associated_token_address(user, &NATIVE_MINT);
+        let sync_native = spl_token::instruction::sync_native(&TOKEN_PROGRAM, &ata)
+            .map_err(|e| TradingVenueError::AmmMethodError(e.to_string().into()))?;
+        Ok(vec![
+            create_associated_token_account_idempotent(user, user, &NATIVE_MINT, &TOKEN_PROGRAM),
+            system_transfer(user, &ata, lamports),
+            sync_native,
+        ])
+    }
+
+    /// Instruction closing the user's wSOL ATA into their wallet after a
+    /// swap, turning its whole balance and rent back into SOL, wSOL held
+    /// there before the swap included. `None` unless `is_native_asset()`.
+    pub fn unwrap_sol_instruction(
+        &self,
+        user: &Pubkey,
+    ) -> Result<Option<Instruction>, TradingVenueError> {
+        if !self.is_native_asset() {
+            return Ok(None);
+        }
+        let ata = spl_associated_token_account::get_associated_token_address(user, &NATIVE_MINT);
+        spl_token::instruction::close_account(&TOKEN_PROGRAM, &ata, user, user, &[])
+            .map(Some)
+            .map_err(|e| TradingVenueError::AmmMethodError(e.to_string().into()))
+    }
+
+    /// Assemble the instructions from `build_transaction_instructions()` into
+    /// a v0 transaction paid by `user`, compressing account keys against
+    /// `luts`.
+    ///
+    /// The transaction is signed when `signer` is given (it must be `user`),
+    /// otherwise its signatures are left as placeholders. Fails when the
+    /// serialized transaction does not fit in a packet.
+    pub fn build_v0_transaction(
+        &self,
+        request: QuoteRequest,
+        user: Pubkey,
+        blockhash: Hash,
+        luts: &[AddressLookupTableAccount],
+        opts: &TxOptions,
+        signer: Option<&Keypair>,
+    ) -> Result<VersionedTransaction, TradingVenueError> {
+        let instructions = self.build_transaction_instructions(request, user, opts)?;
+
+        let message = v0::Message::try_compile(&user, &instructions, luts, blockhash)
+            .map_err(|e| TradingVenueError::AmmMethodError(e.to_string().into()))?;
+        let message = VersionedMessage::V0(message);
+
+        // Signature count is a compact-u16, a single byte below 128 signers.
+        let num_signatures = message.header().num_required_signatures as usize;
+        let size = 1 + num_signatures * 64 + message.serialize().len();
+        if size > PACKET_DATA_SIZE {
+            return Err(TradingVenueError::AmmMethodError(
+                format!(
+                    "Transaction size {} exceeds the {} byte packet limit",
+                    size, PACKET_DATA_SIZE
+                )
+                .into(),
+            ));
+        }
+
+        match signer {
+            Some(signer) => VersionedTransaction::try_new(message, &[signer])
+                .map_err(|e| TradingVenueError::AmmMethodError(e.to_string().into())),
+            None => Ok(VersionedTransaction {
+                signatures: vec![Signature::default(); num_signatures],
+                message,
+            }),
+        }
+    }
+
+    /// Build the instructions of several users' swaps on this vault, for
+    /// packing into shared transactions.
+    ///
+    /// The vault PDAs are derived once for the whole batch. Instructions are
+    /// not validated against quotes, and each user's output ATA creation is
+    /// paid by that user.
+    pub fn generate_swap_instructions_batch(
+        &self,
+        requests: &[(QuoteRequest, Pubkey)],
+    ) -> Result<SwapInstructionBatch, TradingVenueError> {
+        let pdas = self.pdas();
+        let instructions = requests
+            .iter()
+            .map(|(request, user)| {
+                let info = self.direction_info_with_pdas(self.direction_for(request)?, &pdas);
+                Ok(vec![
+                    create_associated_token_account_idempotent(
+                        user,
+                        user,
+                        &info.output_mint,
+                        &info.output_token_program,
+                    ),
+                    self.swap_instruction_with_pdas(request, user, &pdas)?,
+                ])
+            })
+            .collect::<Result<Vec<_>, TradingVenueError>>()?;
+
+        let accounts: HashSet<Pubkey> = instructions
+            .iter()
+            .flatten()
+            .flat_map(|ix| {
+                std::iter::once(ix.program_id).chain(ix.accounts.iter().map(|meta| meta.pubkey))
+            })
+            .collect();
+        let lookup_keys: HashSet<Pubkey> = self.lookup_table_keys(&pdas).into_iter().collect();
+
+        Ok(SwapInstructionBatch {
+            unique_accounts: accounts.len(),
+            unique_accounts_outside_lut: accounts.difference(&lookup_keys).count(),
+            instructions,
+        })
+    }
+}
@@ -0,0 +1,137 @@
+//! Conversions between raw base-unit amounts, which every quote and
+//! instruction uses, and decimal UI amounts for display layers.
+//!
+//! Quotes are never decimal-normalized: `QuoteRequest::amount` and
+//! `QuoteResult::expected_output` are raw amounts of their own mints. Only
+//! the values produced here are in whole tokens.
+
+use std::fmt;
+
+use titan_integration_template::trading_venue::{
+    error::TradingVenueError, QuoteRequest, QuoteResult,
+};
+
+use crate::{
+    errors::VoltrError,
+    voltr_venue::{SwapDirection, VoltrVaultVenue},
+};
+
+/// A raw amount together with its mint's decimals.
+///
+/// `Display` prints the exact decimal value with all `decimals` fractional
+/// digits, so it parses back to the same raw amount.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UiAmount {
+    pub raw: u64,
+    pub decimals: u8,
+}
+
+impl UiAmount {
+    /// Nearest `f64`; exact only up to 2^53 base units.
+    pub fn to_f64(self) -> f64 {
+        self.raw as f64 / 10f64.powi(self.decimals as i32)
+    }
+}
+
+impl fmt::Display for UiAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let decimals = self.decimals as usize;
+        if decimals == 0 {
+            return write!(f, "{}", self.raw);
+        }
+        let digits = format!("{:0>width$}", self.raw, width = decimals + 1);
+        let (whole, fraction) = digits.split_at(digits.len() - decimals);
+        write!(f, "{whole}.{fraction}")
+    }
+}
+
+/// How to treat fractional digits beyond a mint's decimals.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rounding {
+    /// Drop them, never exceeding the UI amount.
+    Down,
+    /// Round up to the next base unit when any is nonzero.
+    Up,
+}
+
+/// Parse a decimal UI amount such as `"12.5"` into base units of a mint
+/// with `decimals` decimals.
+///
+/// Fails with `InvalidAmount` on anything but digits with at most one `.`,
+/// and with `MathOverflow` when the amount does not fit in a `u64`.
+pub fn parse_ui_amount(s: &str, decimals: u8, rounding: Rounding) -> Result<u64, VoltrError> {
+    let (whole, fraction) = s.trim().split_once('.').unwrap_or((s.trim(), ""));
+    let is_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+    if (whole.is_empty() && fraction.is_empty()) || !is_digits(whole) || !is_digits(fraction) {
+        return Err(VoltrError::InvalidAmount);
+    }
+
+    let decimals = decimals as usize;
+    let (kept, dropped) = fraction.split_at(fraction.len().min(decimals));
+    let digits = format!("{whole}{kept:0<decimals$}");
+    let digits = digits.trim_start_matches('0');
+    let mut raw = if digits.is_empty() {
+        0
+    } else {
+        digits.parse::<u64>().map_err(|_| VoltrError::MathOverflow)?
+    };
+    if rounding == Rounding::Up && dropped.bytes().any(|b| b != b'0') {
+        raw = raw.checked_add(1).ok_or(VoltrError::MathOverflow)?;
+    }
+    Ok(raw)
+}
+
+/// A quote with its amounts also in UI units, for display only.
+#[derive(Clone, Debug)]
+pub struct QuoteResultUi {
+    pub quote: QuoteResult,
+    pub input: UiAmount,
+    pub output: UiAmount,
+}
+
+impl VoltrVaultVenue {
+    pub fn asset_to_ui(&self, raw: u64) -> UiAmount {
+        UiAmount {
+            raw,
+            decimals: self.asset_mint_decimals,
+        }
+    }
+
+    pub fn lp_to_ui(&self, raw: u64) -> UiAmount {
+        UiAmount {
+            raw,
+            decimals: self.lp_mint_decimals,
+        }
+    }
+
+    /// Base units of the asset for a UI amount. See [`parse_ui_amount`].
+    pub fn ui_to_asset(&self, ui: &str, rounding: Rounding) -> Result<u64, TradingVenueError> {
+        Ok(parse_ui_amount(ui, self.asset_mint_decimals, rounding)?)
+    }
+
+    /// Base units of LP for a UI amount. See [`parse_ui_amount`].
+    pub fn ui_to_lp(&self, ui: &str, rounding: Rounding) -> Result<u64, TradingVenueError> {
+        Ok(parse_ui_amount(ui, self.lp_mint_decimals, rounding)?)
+    }
+
+    /// Quote `request`, a raw amount as always, attaching UI forms of its
+    /// input and output.
+    pub fn quote_ui(&self, request: &QuoteRequest) -> Result<QuoteResultUi, TradingVenueError> {
+        let (quote, _) = self.quote_with_rejection(request)?;
+        let (input, output) = match self.direction_for(request)? {
+            SwapDirection::Deposit => (
+                self.asset_to_ui(quote.amount),
+                self.lp_to_ui(quote.expected_output),
+            ),
+            SwapDirection::Redeem => (
+                self.lp_to_ui(quote.amount),
+                self.asset_to_ui(quote.expected_output),
+            ),
+        };
+        Ok(QuoteResultUi {
+            quote,
+            input,
+            output,
+        })
+    }
+}
@@ -0,0 +1,105 @@
+use solana_account::Account;
+use solana_account_decoder_client_types::UiAccountEncoding;
+use solana_client::{
+    nonblocking::rpc_client::RpcClient,
+    rpc_config::{RpcSimulateTransactionAccountsConfig, RpcSimulateTransactionConfig},
+};
+use solana_pubkey::Pubkey;
+use solana_sdk::{message::Message, transaction::Transaction};
+
+use titan_integration_template::trading_venue::{
+    error::TradingVenueError, QuoteRequest, QuoteResult, TradingVenue,
+};
+
+use crate::{preconditions::token_account_balance, voltr_venue::VoltrVaultVenue};
+
+/// Outcome of comparing an off-chain quote with an RPC simulation of the swap.
+#[derive(Clone, Debug)]
+pub struct VerificationReport {
+    /// The venue's off-chain quote for the request.
+    pub quote: QuoteResult,
+    /// Output token account credited by the swap.
+    pub output_account: Pubkey,
+    /// Output received in simulation, `None` when the simulation failed.
+    pub simulated_output: Option<u64>,
+    /// Absolute difference between the quote and the simulated output.
+    pub delta: Option<u64>,
+    /// Compute units reported by the simulation.
+    pub units_consumed: Option<u64>,
+    /// Transaction error returned by the simulation, if any.
+    pub error: Option<String>,
+    pub logs: Vec<String>,
+}
+
+impl VerificationReport {
+    /// Whether the simulation succeeded and matched the quote exactly.
+    pub fn is_exact(&self) -> bool {
+        self.delta == Some(0)
+    }
+}
+
+/// Quote `request` off-chain, simulate the swap through `rpc` and report how
+/// far the simulated output is from the quote.
+///
+/// The transaction names `user` as fee payer without signing it, so the
+/// simulation runs with `sigVerify = false` and `replaceRecentBlockhash`.
+/// `user` must hold the source tokens and both ATAs for the swap to succeed;
+/// a failing simulation is reported in the returned report rather than as an
+/// error.
+pub async fn verify_quote_via_rpc(
+    venue: &VoltrVaultVenue,
+    request: QuoteRequest,
+    user: Pubkey,
+    rpc: &RpcClient,
+) -> Result<VerificationReport, TradingVenueError> {
+    let info = venue.direction_info_for(&request)?;
+    let output_account = info.output_account(&user);
+    let strict = venue.config().strict_token_account_layout;
+
+    let quote = venue.quote(request.clone())?;
+    let instruction = venue.generate_swap_instruction(request, user)?;
+    let transaction = Transaction::new_unsigned(Message::new(&[instruction], Some(&user)));
+
+    let pre_balance = rpc
+        .get_account_with_commitment(&output_account, rpc.commitment())
+        .await
+        .map_err(|e| TradingVenueError::AmmMethodError(e.to_string().into()))?
+        .value
+        .and_then(|account| token_account_balance(&account, &info.output_mint, &user, strict))
+        .unwrap_or(0);
+
+    let config = RpcSimulateTransactionConfig {
+        sig_verify: false,
+        replace_recent_blockhash: true,
+        accounts: Some(RpcSimulateTransactionAccountsConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            addresses: vec![output_account.to_string()],
+        }),
+        ..Default::default()
+    };
+    let simulation = rpc
+        .simulate_transaction_with_config(&transaction, config)
+        .await
+        .map_err(|e| TradingVenueError::AmmMethodError(e.to_string().into()))?
+        .value;
+
+    let post_balance = match simulation.err {
+        Some(_) => None,
+        None => simulation
+            .accounts
+            .as_ref()
+            .and_then(|accounts| accounts.first()?.as_ref()?.decode::<Account>())
+            .and_then(|account| token_account_balance(&account, &info.output_mint, &user, strict)),
+    };
+    let simulated_output = post_balance.map(|post| post.saturating_sub(pre_balance));
+
+    Ok(VerificationReport {
+        delta: simulated_output.map(|sim| quote.expected_output.abs_diff(sim)),
+        quote,
+        output_account,
+        simulated_output,
+        units_consumed: simulation.units_consumed,
+        error: simulation.err.map(|e| e.to_string()),
+        logs: simulation.logs.unwrap_or_default(),
+    })
+}
@@ -1,11 +1,14 @@
+use std::time::{Duration, Instant};
+
 use async_trait::async_trait;
 use solana_account::Account;
 use solana_instruction::{AccountMeta, Instruction};
+use solana_program::program_error::ProgramError;
 use solana_program::system_program::ID as SYSTEM_PROGRAM_ID;
 use solana_program_pack::Pack;
 use solana_pubkey::Pubkey;
 use spl_token_2022::{
-    extension::StateWithExtensions,
+    extension::{BaseStateWithExtensions, StateWithExtensions},
     state::Mint as Mint22,
 };
 
@@ -13,22 +16,237 @@ use titan_integration_template::{
     account_caching::AccountsCache,
     trading_venue::{
         error::TradingVenueError, protocol::PoolProtocol, token_info::TokenInfo,
-        AddressLookupTableTrait, FromAccount, QuoteRequest, QuoteResult, TradingVenue,
+        AddressLookupTableTrait, FromAccount, QuoteRequest, QuoteResult, SwapType,
+        TradingVenue,
     },
 };
 
 use crate::{
+    atas::AtaMemo,
+    clock::QuoteClock,
+    config::VoltrVenueConfig,
     constants::*,
-    math::*,
+    core::{calc_amount_after_fee, quote_snapshot, VaultSnapshot},
+    discriminators,
+    errors::{StateUpdateError, VoltrError},
+    fingerprint::{account_fingerprint, token_fingerprints},
+    health::{IdleAtaAnomaly, SharePriceAnomaly, SharePriceSnapshot},
+    overrides::SnapshotOverrides,
+    pda::{ProgramId, VaultKey, VaultPdas},
+    preconditions::unpack_token_account,
+    rejections::RejectionStats,
     state::Vault,
+    status::{LpMintAuthorityMismatch, RefreshStreak},
 };
 
-/// Compute Anchor's 8-byte instruction discriminator for a given method name.
-fn anchor_discriminator(name: &str) -> [u8; 8] {
-    let preimage = format!("global:{}", name);
-    let mut sighash = [0u8; 8];
-    sighash.copy_from_slice(&solana_sdk::hash::hash(preimage.as_bytes()).to_bytes()[..8]);
-    sighash
+pub use crate::core::{LiquidityRejection, SwapDirection};
+
+/// Zero-output quote for `request` flagged `not_enough_liquidity`.
+fn illiquid_quote(request: &QuoteRequest) -> QuoteResult {
+    QuoteResult {
+        input_mint: request.input_mint,
+        output_mint: request.output_mint,
+        amount: request.amount,
+        expected_output: 0,
+        not_enough_liquidity: true,
+    }
+}
+
+/// Closed-form estimate of the largest `direction` input `quote_snapshot()`
+/// prices at `current_ts` without a capacity rejection: the room under the
+/// max cap for deposits, bounded by where the minted LP overflows, and for
+/// redeems the smaller of the idle balance and the unlocked value inverted
+/// through the redemption fee. Rounding can put the real edge a few units
+/// either side.
+fn capacity_edge_estimate(
+    snapshot: &VaultSnapshot,
+    direction: SwapDirection,
+    current_ts: u64,
+) -> u64 {
+    let (Ok(lp_supply), Ok(fee_lp)) = (
+        snapshot.total_lp_supply_incl_fees(),
+        snapshot.pending_management_fee_lp(current_ts),
+    ) else {
+        return 0;
+    };
+    let lp_supply = lp_supply.saturating_add(fee_lp) as f64;
+    let max_fee = MAX_FEE_BPS as f64;
+    match direction {
+        SwapDirection::Redeem => {
+            let Ok(unlocked) = snapshot.unlocked_value(current_ts) else {
+                return 0;
+            };
+            let fee_complement = MAX_FEE_BPS.saturating_sub(snapshot.redemption_fee_bps) as f64;
+            if unlocked == 0 || fee_complement == 0.0 {
+                return u64::MAX;
+            }
+            // The payout floors, so the edge sits just below the LP paying
+            // one unit more than the vault can.
+            let payable = snapshot.idle_balance.min(unlocked) as f64 + 1.0;
+            (payable * lp_supply / unlocked as f64 * max_fee / fee_complement) as u64
+        }
+        SwapDirection::Deposit => {
+            let total_value = snapshot.total_value;
+            let room = if snapshot.max_cap > 0 {
+                snapshot.max_cap.saturating_sub(total_value)
+            } else {
+                u64::MAX - total_value
+            };
+            // Solve `calc_deposit_lp_to_mint()` for the deposit minting
+            // `u64::MAX` LP; it only gets there when the supply is priced
+            // above the fee-adjusted asset.
+            let max_lp = u64::MAX as f64;
+            let fee_complement = MAX_FEE_BPS.saturating_sub(snapshot.issuance_fee_bps) as f64;
+            let overflow = if lp_supply == 0.0 {
+                max_lp * 10f64.powi(snapshot.asset_decimals as i32)
+                    / 10f64.powi(snapshot.lp_decimals as i32)
+            } else {
+                let excess = fee_complement * lp_supply - max_lp * (max_fee - fee_complement);
+                if excess > 0.0 {
+                    max_fee * max_lp * total_value as f64 / excess
+                } else {
+                    f64::INFINITY
+                }
+            };
+            room.min(overflow as u64)
+        }
+    }
+}
+
+/// Largest amount below `limit` for which `within` holds, given that it
+/// holds for zero, not for `limit`, and not past its edge. Steps out from
+/// `estimate` in doubling strides until the edge is bracketed, then bisects,
+/// so a close estimate costs a handful of calls.
+fn edge_near(estimate: u64, limit: u64, within: impl Fn(u64) -> bool) -> u64 {
+    let estimate = estimate.min(limit - 1);
+    // Invariant: within(lo) && !within(hi).
+    let (mut lo, mut hi) = if within(estimate) {
+        let (mut lo, mut step) = (estimate, 1u64);
+        loop {
+            let next = lo.saturating_add(step).min(limit);
+            if next == limit || !within(next) {
+                break (lo, next);
+            }
+            lo = next;
+            step = step.saturating_mul(2);
+        }
+    } else if !within(1) {
+        return 0;
+    } else {
+        let (mut hi, mut step) = (estimate, 1u64);
+        loop {
+            let next = hi.saturating_sub(step).max(1);
+            if within(next) {
+                break (next, hi);
+            }
+            hi = next;
+            step = step.saturating_mul(2);
+        }
+    };
+    while hi - lo > 1 {
+        let mid = lo + (hi - lo) / 2;
+        if within(mid) {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    lo
+}
+
+/// Fail with `UnsupportedSwapType` unless `swap_type` is `ExactIn`, the only
+/// semantics the venue implements. Other variants, present or future, must
+/// never be priced or built as if they were `ExactIn`.
+fn ensure_supported_swap_type(swap_type: &SwapType) -> Result<(), TradingVenueError> {
+    match swap_type {
+        SwapType::ExactIn => Ok(()),
+        _ => Err(VoltrError::UnsupportedSwapType.into()),
+    }
+}
+
+/// Addresses stored in an address lookup table account.
+pub(crate) fn lookup_table_addresses(account: &Account) -> Result<Vec<Pubkey>, TradingVenueError> {
+    let addresses = account
+        .data
+        .get(LOOKUP_TABLE_META_SIZE..)
+        .filter(|data| account.owner == ADDRESS_LOOKUP_TABLE_PROGRAM && data.len() % 32 == 0)
+        .ok_or(TradingVenueError::DeserializationFailed(
+            "Invalid address lookup table account".into(),
+        ))?;
+    Ok(addresses
+        .chunks_exact(32)
+        .map(|bytes| Pubkey::try_from(bytes).expect("chunks are 32 bytes"))
+        .collect())
+}
+
+/// `keys` in order, keeping only the first occurrence of each.
+fn dedup_keys(keys: impl IntoIterator<Item = Pubkey>) -> Vec<Pubkey> {
+    let mut unique = Vec::new();
+    for key in keys {
+        if !unique.contains(&key) {
+            unique.push(key);
+        }
+    }
+    unique
+}
+
+/// Unpack a Token-2022 mint, and tell whether its extensions could not be
+/// read. Unless `strict`, extensions newer than this build's spl-token-2022
+/// understands only cost the extensions: the base fields are read from the
+/// fixed-size prefix the extensions follow.
+fn unpack_token_2022_mint(data: &[u8], strict: bool) -> Result<(Mint22, bool), ProgramError> {
+    let error = match StateWithExtensions::<Mint22>::unpack(data) {
+        Ok(mint) => match mint.get_extension_types() {
+            Ok(_) => return Ok((mint.base, false)),
+            Err(e) if strict => return Err(e),
+            Err(_) => return Ok((mint.base, true)),
+        },
+        Err(e) => e,
+    };
+    if strict || data.len() <= Mint22::LEN {
+        return Err(error);
+    }
+    Ok((Mint22::unpack(&data[..Mint22::LEN])?, true))
+}
+
+/// A vault account parsed but not yet applied to the venue.
+struct ParsedVault {
+    state: Vault,
+    fingerprint: u64,
+}
+
+/// An asset mint parsed but not yet applied to the venue.
+struct ParsedAssetMint {
+    /// An account standing in for the mint from metadata; `None` when the
+    /// mint account was read from the cache, or is kept from an earlier
+    /// refresh.
+    stand_in: Option<Account>,
+    token_program: Pubkey,
+    decimals: u8,
+    /// Mint authority, freeze authority and supply; `None` when the mint
+    /// account itself was not read.
+    authorities: Option<(Option<Pubkey>, Option<Pubkey>, u64)>,
+    /// Whether the mint's Token-2022 extensions could not be read.
+    unknown_extensions: bool,
+}
+
+/// Token accounts parsed but not yet applied to the venue.
+struct ParsedTokenAccounts {
+    lp_mint: spl_token::state::Mint,
+    asset_mint: ParsedAssetMint,
+    idle_balance: u64,
+    idle_ata_anomaly: Option<IdleAtaAnomaly>,
+}
+
+/// What a refresh from a plain cache fetched beyond the state it applied.
+pub(crate) struct CacheLoad {
+    /// Accounts of the extra keys asked for, in order.
+    pub(crate) extra: Vec<Option<Account>>,
+    /// Whether the asset mint was in the cache, rather than stood in for
+    /// from `known_mint_metadata`.
+    pub(crate) asset_mint_loaded: bool,
+    /// `get_accounts()` calls made.
+    pub(crate) cache_calls: usize,
 }
 
 /// Titan-compatible trading venue for Voltr yield vaults.
@@ -50,8 +268,54 @@ pub struct VoltrVaultVenue {
     pub asset_mint_decimals: u8,
     pub asset_token_program: Pubkey,
     pub asset_idle_balance: u64,
+    config: VoltrVenueConfig,
     token_info: Vec<TokenInfo>,
-    initialized: bool,
+    /// Asset mint account behind `token_info`, kept for refreshes where the
+    /// cache no longer has it.
+    asset_mint_account: Option<Account>,
+    /// LP mint account behind `token_info`, compared on refresh to skip
+    /// rebuilding it.
+    lp_mint_account: Option<Account>,
+    max_deposit_amount: u64,
+    max_redeem_amount: u64,
+    /// `lookup_table_keys()` as of the last refresh, so
+    /// `get_lookup_table_keys()` need not derive PDAs per call.
+    cached_lookup_table_keys: Vec<Pubkey>,
+    /// `account_fingerprint()` of the vault account last loaded.
+    pub(crate) vault_fingerprint: Option<u64>,
+    /// Fingerprints of the token accounts last loaded, absent ones as `None`.
+    pub(crate) token_fingerprints: Option<[Option<u64>; 3]>,
+    pub(crate) vault_loaded: bool,
+    /// When the vault state handed to `new()` was parsed; lets the first
+    /// refresh skip re-fetching a vault that was just loaded.
+    pub(crate) constructed_at: Option<Instant>,
+    pub(crate) initialized: bool,
+    /// Newest slot among the accounts of the last slot-aware refresh.
+    pub(crate) state_slot: Option<u64>,
+    /// `effective_ts()` when the current state was loaded.
+    pub(crate) state_ts: u64,
+    /// Slot span of the last slot-aware refresh when it exceeded
+    /// `max_slot_span` on every attempt.
+    pub(crate) inconsistent_slot_span: Option<u64>,
+    /// Share price of the last accepted refresh.
+    pub(crate) share_price_baseline: Option<SharePriceSnapshot>,
+    pub(crate) share_price_anomaly: Option<SharePriceAnomaly>,
+    /// Delegate or close authority on the idle ATA at the last refresh.
+    pub(crate) idle_ata_anomaly: Option<IdleAtaAnomaly>,
+    /// LP mint authorities of the last refresh, when not the vault's own.
+    pub(crate) lp_mint_authority_mismatch: Option<LpMintAuthorityMismatch>,
+    /// Asset mint authorities and supply as of the last refresh that read
+    /// the asset mint account.
+    asset_mint_authority: Option<Pubkey>,
+    asset_mint_freeze_authority: Option<Pubkey>,
+    asset_mint_supply: u64,
+    /// Whether the asset mint's extensions could not be read at the last
+    /// refresh that read it.
+    pub(crate) asset_mint_unknown_extensions: bool,
+    pub(crate) rejection_stats: RejectionStats,
+    pub(crate) ata_memo: AtaMemo,
+    /// Refreshes failed since the last success, behind `VenueStatus::Stale`.
+    pub(crate) refresh_streak: RefreshStreak,
 }
 
 impl VoltrVaultVenue {
@@ -64,528 +328,1354 @@ impl VoltrVaultVenue {
             asset_mint_decimals: 0,
             asset_token_program: TOKEN_PROGRAM,
             asset_idle_balance: 0,
+            config: VoltrVenueConfig::default(),
             token_info: Vec::new(),
+            asset_mint_account: None,
+            lp_mint_account: None,
+            max_deposit_amount: 0,
+            max_redeem_amount: 0,
+            cached_lookup_table_keys: Vec::new(),
+            vault_fingerprint: None,
+            token_fingerprints: None,
+            vault_loaded: true,
+            constructed_at: Some(Instant::now()),
             initialized: false,
+            state_slot: None,
+            state_ts: 0,
+            inconsistent_slot_span: None,
+            share_price_baseline: None,
+            share_price_anomaly: None,
+            idle_ata_anomaly: None,
+            lp_mint_authority_mismatch: None,
+            asset_mint_authority: None,
+            asset_mint_freeze_authority: None,
+            asset_mint_supply: 0,
+            asset_mint_unknown_extensions: false,
+            rejection_stats: RejectionStats::default(),
+            ata_memo: AtaMemo::default(),
+            refresh_streak: RefreshStreak::default(),
         }
     }
 
-    /// Estimate management-fee LP tokens that would be minted at `current_ts`.
-    fn estimate_management_fee_lp(
-        &self,
-        current_ts: u64,
-        total_asset_value: u64,
-        total_lp_supply_incl_fees: u64,
-    ) -> Result<u64, TradingVenueError> {
-        let management_fee_bps = self
-            .vault_state
-            .get_total_fee_configuration_management_fee()
-            .map_err(|e: anyhow::Error| TradingVenueError::CheckedMathError(e.to_string().into()))?;
-
-        if self.vault_state.fee_update.last_management_fee_update_ts == 0
-            || total_asset_value == 0
-            || management_fee_bps == 0
-        {
-            return Ok(0);
+    /// `new()`, failing with `VaultKeyStateMismatch` when `vault_state` is
+    /// not the state of `vault_key`: its LP mint is not the vault's LP mint
+    /// PDA under the mainnet program, or its asset mint is not
+    /// `expected_asset_mint`.
+    ///
+    /// For other deployments use `new()` and `with_config()`; the first
+    /// `update_state()` checks the LP mint under the configured program.
+    pub fn try_new(
+        vault_key: Pubkey,
+        vault_state: Vault,
+        expected_asset_mint: Option<Pubkey>,
+    ) -> Result<Self, TradingVenueError> {
+        let venue = Self::new(vault_key, vault_state);
+        venue.ensure_state_of_key()?;
+        if let Some(expected_mint) = expected_asset_mint {
+            venue.ensure_mint(expected_mint, venue.vault_state.asset.mint)?;
         }
+        Ok(venue)
+    }
 
-        let time_elapsed = current_ts
-            .saturating_sub(self.vault_state.fee_update.last_management_fee_update_ts);
-        if time_elapsed == 0 {
-            return Ok(0);
+    /// Create a venue knowing only the vault address.
+    ///
+    /// The vault state is loaded by the first `update_state()`; until then
+    /// `get_required_pubkeys_for_update()` lists only the vault and quoting or
+    /// instruction generation fails with `NotInitialized`.
+    pub fn new_uninitialized(vault_key: Pubkey) -> Self {
+        Self {
+            vault_loaded: false,
+            constructed_at: None,
+            ..Self::new(vault_key, Vault::default())
         }
+    }
 
-        let fee_amount_in_asset =
-            calc_management_fee_amount_in_asset(time_elapsed, total_asset_value, management_fee_bps)
-                .map_err(|e: anyhow::Error| TradingVenueError::CheckedMathError(e.to_string().into()))?;
-
-        if fee_amount_in_asset == 0 || fee_amount_in_asset >= total_asset_value {
-            return Ok(0);
+    /// Replace the venue configuration.
+    pub fn with_config(mut self, config: VoltrVenueConfig) -> Self {
+        self.config = config;
+        // The program id, and so every PDA, may have changed.
+        if self.initialized {
+            self.refresh_lookup_table_keys();
         }
+        self
+    }
 
-        calc_fee_lp_to_mint(fee_amount_in_asset, total_lp_supply_incl_fees, total_asset_value)
-            .map_err(|e: anyhow::Error| TradingVenueError::CheckedMathError(e.to_string().into()))
+    pub fn config(&self) -> &VoltrVenueConfig {
+        &self.config
     }
 
-    /// Compute a redeem quote (LP -> asset).
-    fn quote_redeem(
-        &self,
-        request: &QuoteRequest,
-        current_ts: u64,
-        total_lp_supply_after_mgmt_fee: u64,
-    ) -> Result<QuoteResult, TradingVenueError> {
-        if self
-            .vault_state
-            .vault_configuration
-            .withdrawal_waiting_period
-            != 0
-        {
-            return Err(TradingVenueError::AmmMethodError(
-                "Withdrawal waiting period must be zero for instant redeems".into(),
-            ));
-        }
+    /// `QUOTE_SEMANTICS_VERSION` of the quotes this venue returns.
+    pub fn semantics_version(&self) -> u32 {
+        QUOTE_SEMANTICS_VERSION
+    }
 
-        let amount = request.amount;
-        let redemption_fee_bps = self.vault_state.fee_configuration.redemption_fee;
+    /// Replace `config.clock` alone, e.g. with a freshly captured
+    /// `QuoteClock::from_sysvar()` each slot.
+    pub fn set_clock(&mut self, clock: QuoteClock) {
+        self.config.clock = clock;
+    }
 
-        let total_unlocked_asset = self
-            .vault_state
-            .get_unlocked_asset_value(current_ts)
-            .map_err(|e: anyhow::Error| TradingVenueError::CheckedMathError(e.to_string().into()))?;
+    /// Unix time, on the venue's clock, at which the current state was
+    /// loaded; 0 before the first refresh.
+    pub fn state_ts(&self) -> u64 {
+        self.state_ts
+    }
 
-        let asset_to_redeem = calc_withdraw_asset_to_redeem(
-            amount,
-            total_lp_supply_after_mgmt_fee,
-            total_unlocked_asset,
-            redemption_fee_bps,
-        )
-        .map_err(|e: anyhow::Error| TradingVenueError::CheckedMathError(e.to_string().into()))?;
+    /// Asset mint authority as of the last refresh that read the asset mint;
+    /// `None` once minting is disabled.
+    pub fn asset_mint_authority(&self) -> Option<Pubkey> {
+        self.asset_mint_authority
+    }
 
-        if self.asset_idle_balance < asset_to_redeem {
-            return Ok(QuoteResult {
-                input_mint: request.input_mint,
-                output_mint: request.output_mint,
-                amount,
-                expected_output: 0,
-                not_enough_liquidity: true,
-            });
-        }
+    /// Asset mint freeze authority as of the last refresh that read the asset
+    /// mint. When set, the vault's idle ATA can be frozen, blocking redeems.
+    pub fn asset_mint_freeze_authority(&self) -> Option<Pubkey> {
+        self.asset_mint_freeze_authority
+    }
 
-        Ok(QuoteResult {
-            input_mint: request.input_mint,
-            output_mint: request.output_mint,
-            amount,
-            expected_output: asset_to_redeem,
-            not_enough_liquidity: false,
-        })
+    /// Asset mint supply as of the last refresh that read the asset mint.
+    pub fn asset_mint_supply(&self) -> u64 {
+        self.asset_mint_supply
     }
 
-    /// Build the `deposit_vault` instruction for a deposit (asset -> LP).
-    fn build_deposit_instruction(
-        &self,
-        deposit_amount: u64,
-        user: &Pubkey,
-    ) -> Result<Instruction, TradingVenueError> {
-        let (protocol_pda, _) =
-            Pubkey::find_program_address(&[PROTOCOL_SEED], &VOLTR_VAULT_PROGRAM);
+    /// Whether `mint` is the vault's asset or LP mint.
+    pub fn serves_mint(&self, mint: &Pubkey) -> bool {
+        let (asset_mint, lp_mint) = (&self.vault_state.asset.mint, &self.vault_state.lp.mint);
+        // Equal mints, which loading rejects, are those of a vault not
+        // loaded yet.
+        asset_mint != lp_mint && (mint == asset_mint || mint == lp_mint)
+    }
 
-        let (vault_lp_mint_pda, _) = Pubkey::find_program_address(
-            &[VAULT_LP_MINT_SEED, self.vault_key.as_ref()],
-            &VOLTR_VAULT_PROGRAM,
-        );
+    /// Whether the venue swaps `input` for `output`, in either direction.
+    pub fn serves_pair(&self, input: &Pubkey, output: &Pubkey) -> bool {
+        self.pair_direction(input, output).is_some()
+    }
 
-        let (vault_asset_idle_auth_pda, _) = Pubkey::find_program_address(
-            &[VAULT_ASSET_IDLE_AUTH_SEED, self.vault_key.as_ref()],
-            &VOLTR_VAULT_PROGRAM,
-        );
+    /// Direction of a swap of `input` for `output`, `None` unless they are
+    /// the vault's asset and LP mints in either order. Plain pubkey
+    /// comparisons, for filtering venues without quoting.
+    pub fn pair_direction(&self, input: &Pubkey, output: &Pubkey) -> Option<SwapDirection> {
+        let (asset_mint, lp_mint) = (&self.vault_state.asset.mint, &self.vault_state.lp.mint);
+        // Equal mints would make the pair ambiguous.
+        if asset_mint == lp_mint {
+            return None;
+        }
+        if input == asset_mint && output == lp_mint {
+            Some(SwapDirection::Deposit)
+        } else if input == lp_mint && output == asset_mint {
+            Some(SwapDirection::Redeem)
+        } else {
+            None
+        }
+    }
 
-        let (vault_lp_mint_auth_pda, _) = Pubkey::find_program_address(
-            &[VAULT_LP_MINT_AUTH_SEED, self.vault_key.as_ref()],
-            &VOLTR_VAULT_PROGRAM,
-        );
+    /// Resolve the swap direction for a request, rejecting unrelated mint pairs.
+    pub(crate) fn direction_for(
+        &self,
+        request: &QuoteRequest,
+    ) -> Result<SwapDirection, TradingVenueError> {
+        match self.pair_direction(&request.input_mint, &request.output_mint) {
+            Some(direction) => Ok(direction),
+            // Only when both mints are the same, which loading rejects.
+            None if request.input_mint == self.vault_state.asset.mint
+                && request.output_mint == self.vault_state.lp.mint =>
+            {
+                Err(VoltrError::InvalidVaultMints.into())
+            }
+            None => Err(TradingVenueError::InvalidMint(request.input_mint.into())),
+        }
+    }
 
-        let user_source_ata = spl_associated_token_account::get_associated_token_address_with_program_id(
-            user,
-            &self.vault_state.asset.mint,
-            &self.asset_token_program,
-        );
+    /// Whether the vault holds wrapped SOL. The user side of its asset is
+    /// then raw SOL: wrapped into a wSOL ATA ahead of a deposit, and
+    /// unwrapped by closing that ATA after a redeem.
+    pub fn is_native_asset(&self) -> bool {
+        self.vault_state.asset.mint == NATIVE_MINT
+    }
 
-        let user_dest_ata = spl_associated_token_account::get_associated_token_address_with_program_id(
-            user,
-            &vault_lp_mint_pda,
-            &TOKEN_PROGRAM,
-        );
+    /// The user's ATA credited by a swap in `direction`.
+    pub fn output_account(&self, user: &Pubkey, direction: SwapDirection) -> Pubkey {
+        self.direction_info(direction).output_account(user)
+    }
 
-        let accounts = vec![
+    /// Accounts of a `deposit_vault` instruction for `user`, in program
+    /// order.
+    ///
+    /// Derives nothing and builds no `Vec`, for callers assembling messages
+    /// themselves with the user's ATAs already at hand.
+    pub fn deposit_account_metas(
+        &self,
+        user: &Pubkey,
+        user_asset_ata: &Pubkey,
+        user_lp_ata: &Pubkey,
+        pdas: &VaultPdas,
+    ) -> [AccountMeta; DEPOSIT_ACCOUNTS_LEN] {
+        [
             AccountMeta::new_readonly(*user, true),
-            AccountMeta::new_readonly(protocol_pda, false),
+            AccountMeta::new_readonly(pdas.protocol, false),
             AccountMeta::new(self.vault_key, false),
             AccountMeta::new_readonly(self.vault_state.asset.mint, false),
-            AccountMeta::new(vault_lp_mint_pda, false),
-            AccountMeta::new(user_source_ata, false),
+            AccountMeta::new(pdas.lp_mint, false),
+            AccountMeta::new(*user_asset_ata, false),
             AccountMeta::new(self.vault_state.asset.idle_ata, false),
-            AccountMeta::new_readonly(vault_asset_idle_auth_pda, false),
-            AccountMeta::new(user_dest_ata, false),
-            AccountMeta::new_readonly(vault_lp_mint_auth_pda, false),
+            AccountMeta::new_readonly(pdas.asset_idle_auth, false),
+            AccountMeta::new(*user_lp_ata, false),
+            AccountMeta::new_readonly(pdas.lp_mint_auth, false),
             AccountMeta::new_readonly(self.asset_token_program, false),
             AccountMeta::new_readonly(TOKEN_PROGRAM, false),
             AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
-        ];
+        ]
+    }
+
+    /// Build the `deposit_vault` instruction for a deposit (asset -> LP).
+    ///
+    /// Fails with `InvalidAmount` for a zero deposit, which the program
+    /// rejects.
+    fn build_deposit_instruction(
+        &self,
+        deposit_amount: u64,
+        user: &Pubkey,
+        pdas: &VaultPdas,
+    ) -> Result<Instruction, TradingVenueError> {
+        if deposit_amount == 0 {
+            return Err(VoltrError::InvalidAmount.into());
+        }
+        let (user_asset_ata, user_lp_ata) = self.user_atas(user, pdas);
 
         let mut data = Vec::with_capacity(16);
-        data.extend_from_slice(&anchor_discriminator("deposit_vault"));
+        data.extend_from_slice(&discriminators::deposit_vault());
         data.extend_from_slice(&deposit_amount.to_le_bytes());
 
         Ok(Instruction {
-            program_id: VOLTR_VAULT_PROGRAM,
-            accounts,
+            program_id: self.program_id(),
+            accounts: self
+                .deposit_account_metas(user, &user_asset_ata, &user_lp_ata, pdas)
+                .into(),
             data,
         })
     }
 
-    /// Build the `instant_withdraw_vault` instruction for a redeem (LP -> asset).
-    fn build_instant_withdraw_vault_instruction(
+    /// Accounts of an `instant_withdraw_vault` instruction for `user`, in
+    /// program order. Derives nothing, like `deposit_account_metas()`.
+    pub fn instant_withdraw_account_metas(
         &self,
-        redeem_amount: u64,
         user: &Pubkey,
-    ) -> Result<Instruction, TradingVenueError> {
-        let (protocol_pda, _) =
-            Pubkey::find_program_address(&[PROTOCOL_SEED], &VOLTR_VAULT_PROGRAM);
-
-        let (vault_lp_mint_pda, _) = Pubkey::find_program_address(
-            &[VAULT_LP_MINT_SEED, self.vault_key.as_ref()],
-            &VOLTR_VAULT_PROGRAM,
-        );
-
-        let (vault_asset_idle_auth_pda, _) = Pubkey::find_program_address(
-            &[VAULT_ASSET_IDLE_AUTH_SEED, self.vault_key.as_ref()],
-            &VOLTR_VAULT_PROGRAM,
-        );
-
-        let user_lp_ata =
-            spl_associated_token_account::get_associated_token_address_with_program_id(
-                user,
-                &vault_lp_mint_pda,
-                &TOKEN_PROGRAM,
-            );
-
-        let user_asset_ata =
-            spl_associated_token_account::get_associated_token_address_with_program_id(
-                user,
-                &self.vault_state.asset.mint,
-                &self.asset_token_program,
-            );
-
-        let accounts = vec![
+        user_asset_ata: &Pubkey,
+        user_lp_ata: &Pubkey,
+        pdas: &VaultPdas,
+    ) -> [AccountMeta; INSTANT_WITHDRAW_ACCOUNTS_LEN] {
+        [
             AccountMeta::new_readonly(*user, true),
-            AccountMeta::new_readonly(protocol_pda, false),
+            AccountMeta::new_readonly(pdas.protocol, false),
             AccountMeta::new(self.vault_key, false),
             AccountMeta::new_readonly(self.vault_state.asset.mint, false),
-            AccountMeta::new(vault_lp_mint_pda, false),
-            AccountMeta::new(user_lp_ata, false),
+            AccountMeta::new(pdas.lp_mint, false),
+            AccountMeta::new(*user_lp_ata, false),
             AccountMeta::new(self.vault_state.asset.idle_ata, false),
-            AccountMeta::new(vault_asset_idle_auth_pda, false),
-            AccountMeta::new(user_asset_ata, false),
+            AccountMeta::new(pdas.asset_idle_auth, false),
+            AccountMeta::new(*user_asset_ata, false),
             AccountMeta::new_readonly(self.asset_token_program, false),
             AccountMeta::new_readonly(TOKEN_PROGRAM, false),
             AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
-        ];
+        ]
+    }
+
+    /// Build the `instant_withdraw_vault` instruction for a redeem (LP -> asset).
+    ///
+    /// Fails with `InvalidAmount` for a zero redeem. Whether the vault can
+    /// redeem `redeem_amount` instantly is left to
+    /// `generate_swap_instruction_validated()`.
+    fn build_instant_withdraw_vault_instruction(
+        &self,
+        redeem_amount: u64,
+        user: &Pubkey,
+        pdas: &VaultPdas,
+    ) -> Result<Instruction, TradingVenueError> {
+        if redeem_amount == 0 {
+            return Err(VoltrError::InvalidAmount.into());
+        }
+        let (user_asset_ata, user_lp_ata) = self.user_atas(user, pdas);
 
         let mut data = Vec::with_capacity(18);
-        data.extend_from_slice(&anchor_discriminator("instant_withdraw_vault"));
+        data.extend_from_slice(&discriminators::instant_withdraw_vault());
         data.extend_from_slice(&redeem_amount.to_le_bytes());
         data.push(1u8);
         data.push(0u8);
 
         Ok(Instruction {
-            program_id: VOLTR_VAULT_PROGRAM,
-            accounts,
+            program_id: self.program_id(),
+            accounts: self
+                .instant_withdraw_account_metas(user, &user_asset_ata, &user_lp_ata, pdas)
+                .into(),
             data,
         })
     }
 
-    /// Derive the vault LP mint PDA.
-    pub fn derive_vault_lp_mint_pda(vault_key: &Pubkey) -> Pubkey {
-        Pubkey::find_program_address(
-            &[VAULT_LP_MINT_SEED, vault_key.as_ref()],
-            &VOLTR_VAULT_PROGRAM,
-        )
-        .0
-    }
-}
-
-impl FromAccount for VoltrVaultVenue {
-    fn from_account(pubkey: &Pubkey, account: &Account) -> Result<Self, TradingVenueError> {
-        let vault_state = Vault::load(&account.data)
-            .map_err(|e: anyhow::Error| TradingVenueError::DeserializationFailed(e.to_string().into()))?;
-        Ok(VoltrVaultVenue::new(*pubkey, vault_state))
+    /// Token accounts refreshed alongside the vault: LP mint, asset mint and
+    /// idle ATA, in the order `load_token_accounts()` expects.
+    pub(crate) fn token_account_keys(&self) -> [Pubkey; 3] {
+        [
+            self.vault_state.lp.mint,
+            self.vault_state.asset.mint,
+            self.vault_state.asset.idle_ata,
+        ]
     }
-}
 
-#[async_trait]
-impl TradingVenue for VoltrVaultVenue {
-    fn initialized(&self) -> bool {
-        self.initialized
+    /// Parse the vault account into `vault_state`, keeping the previous
+    /// state when it fails `Vault::validate()`.
+    pub(crate) fn load_vault_account(
+        &mut self,
+        vault_account: Option<&Account>,
+    ) -> Result<(), TradingVenueError> {
+        let vault = self.parse_vault_account(vault_account)?;
+        self.apply_vault_account(vault);
+        Ok(())
     }
 
-    fn program_id(&self) -> Pubkey {
-        VOLTR_VAULT_PROGRAM
+    /// Parse the vault and the accounts listed by `token_account_keys()`, in
+    /// the order `get_required_pubkeys_for_update()` lists them, applying
+    /// them only when all of them parse.
+    pub(crate) fn load_accounts(
+        &mut self,
+        accounts: &[Option<Account>],
+    ) -> Result<(), TradingVenueError> {
+        let mut errors = StateUpdateError::default();
+        let vault = errors.check(
+            self.vault_key,
+            self.parse_vault_account(accounts[0].as_ref()),
+        );
+        let tokens = self.parse_token_accounts(&accounts[1..], &mut errors);
+        let (Some(vault), Some(tokens)) = (vault, tokens) else {
+            return Err(errors.into());
+        };
+        self.apply_token_accounts(Some(vault), tokens, &accounts[1..])
     }
 
-    fn program_dependencies(&self) -> Vec<Pubkey> {
-        vec![
-            VOLTR_VAULT_PROGRAM,
-            TOKEN_PROGRAM,
-            TOKEN_22_PROGRAM,
-            ATA_PROGRAM,
-        ]
+    /// Parse the accounts listed by `token_account_keys()` and mark the venue
+    /// initialized, applying them only when all of them parse.
+    pub(crate) fn load_token_accounts(
+        &mut self,
+        accounts: &[Option<Account>],
+    ) -> Result<(), TradingVenueError> {
+        let mut errors = StateUpdateError::default();
+        let Some(tokens) = self.parse_token_accounts(accounts, &mut errors) else {
+            return Err(errors.into());
+        };
+        self.apply_token_accounts(None, tokens, accounts)
     }
 
-    fn market_id(&self) -> Pubkey {
-        self.vault_key
+    fn parse_vault_account(
+        &self,
+        vault_account: Option<&Account>,
+    ) -> Result<ParsedVault, TradingVenueError> {
+        let vault_account =
+            vault_account.ok_or(TradingVenueError::NoAccountFound(self.vault_key.into()))?;
+        let state = Vault::load(&vault_account.data)?;
+        state.validate()?;
+        // The account at `vault_key` must be the vault the stored state came
+        // from, or the caller paired a key with another vault's state.
+        if self.vault_loaded {
+            self.ensure_mint(self.vault_state.lp.mint, state.lp.mint)?;
+            self.ensure_mint(self.vault_state.asset.mint, state.asset.mint)?;
+        }
+        Ok(ParsedVault {
+            state,
+            fingerprint: account_fingerprint(vault_account),
+        })
     }
 
-    fn protocol(&self) -> PoolProtocol {
-        PoolProtocol::VoltrVault
+    fn apply_vault_account(&mut self, vault: ParsedVault) {
+        self.vault_state = vault.state;
+        self.vault_fingerprint = Some(vault.fingerprint);
+        self.vault_loaded = true;
     }
 
-    fn get_token_info(&self) -> &[TokenInfo] {
-        &self.token_info
+    /// Parse the LP mint, asset mint and idle ATA, recording every failure
+    /// in `errors`; `None` when there was any.
+    fn parse_token_accounts(
+        &self,
+        accounts: &[Option<Account>],
+        errors: &mut StateUpdateError,
+    ) -> Option<ParsedTokenAccounts> {
+        let [lp_mint, asset_mint, idle_ata] = self.token_account_keys();
+
+        let lp = errors.check(lp_mint, {
+            accounts[0]
+                .as_ref()
+                .ok_or(TradingVenueError::NoAccountFound(lp_mint.into()))
+                .and_then(|account| {
+                    spl_token::state::Mint::unpack(&account.data)
+                        .map_err(|e| TradingVenueError::DeserializationFailed(e.to_string().into()))
+                })
+        });
+        let asset = errors.check(asset_mint, self.parse_asset_mint(accounts[1].as_ref()));
+
+        // Without a parsed asset mint, keep checking the idle ATA against the
+        // token program last loaded.
+        let token_program = asset
+            .as_ref()
+            .map_or(self.asset_token_program, |asset| asset.token_program);
+        let idle = errors.check(idle_ata, {
+            accounts[2]
+                .as_ref()
+                .ok_or(TradingVenueError::NoAccountFound(idle_ata.into()))
+                .and_then(|account| {
+                    if token_program == TOKEN_PROGRAM {
+                        unpack_token_account(&account.data, self.config.strict_token_account_layout)
+                            .map(|idle| {
+                                let anomaly = IdleAtaAnomaly::from_token_account(
+                                    idle.delegate,
+                                    idle.delegated_amount,
+                                    idle.close_authority,
+                                );
+                                (idle.amount, anomaly)
+                            })
+                    } else {
+                        StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account.data)
+                            .map(|idle| {
+                                let anomaly = IdleAtaAnomaly::from_token_account(
+                                    idle.base.delegate,
+                                    idle.base.delegated_amount,
+                                    idle.base.close_authority,
+                                );
+                                (idle.base.amount, anomaly)
+                            })
+                    }
+                    .map_err(|e| TradingVenueError::DeserializationFailed(e.to_string().into()))
+                })
+        });
+
+        let (idle_balance, idle_ata_anomaly) = idle?;
+        Some(ParsedTokenAccounts {
+            lp_mint: lp?,
+            asset_mint: asset?,
+            idle_balance,
+            idle_ata_anomaly,
+        })
     }
 
-    fn get_required_pubkeys_for_update(&self) -> Result<Vec<Pubkey>, TradingVenueError> {
-        Ok(vec![
-            self.vault_key,
-            self.vault_state.lp.mint,
-            self.vault_state.asset.mint,
-            self.vault_state.asset.idle_ata,
-        ])
+    /// Parse the asset mint (Token or Token-2022). The asset mint is static,
+    /// so when the cache has pruned it fall back to the configured metadata
+    /// or to what an earlier refresh loaded.
+    fn parse_asset_mint(
+        &self,
+        asset_mint_account: Option<&Account>,
+    ) -> Result<ParsedAssetMint, TradingVenueError> {
+        let asset_mint = self.vault_state.asset.mint;
+        match (asset_mint_account, self.config.known_mint_metadata) {
+            (Some(account), _) => {
+                let (decimals, mint_authority, freeze_authority, supply, unknown_extensions) =
+                    if account.owner == TOKEN_PROGRAM {
+                        let mint = spl_token::state::Mint::unpack(&account.data)
+                            .map_err(|e| TradingVenueError::DeserializationFailed(e.to_string().into()))?;
+                        (mint.decimals, mint.mint_authority, mint.freeze_authority, mint.supply, false)
+                    } else {
+                        let (mint, unknown_extensions) =
+                            unpack_token_2022_mint(&account.data, self.config.strict_mint_extensions)
+                                .map_err(|e| TradingVenueError::DeserializationFailed(e.to_string().into()))?;
+                        if unknown_extensions {
+                            log::warn!(
+                                "Voltr vault {} asset mint {} has Token-2022 extensions this build cannot read; using its base fields",
+                                self.vault_key,
+                                asset_mint
+                            );
+                        }
+                        (
+                            mint.decimals,
+                            mint.mint_authority,
+                            mint.freeze_authority,
+                            mint.supply,
+                            unknown_extensions,
+                        )
+                    };
+                Ok(ParsedAssetMint {
+                    stand_in: None,
+                    token_program: account.owner,
+                    decimals,
+                    authorities: Some((mint_authority.into(), freeze_authority.into(), supply)),
+                    unknown_extensions,
+                })
+            }
+            (None, Some(metadata)) => Ok(ParsedAssetMint {
+                stand_in: Some(metadata.to_mint_account()),
+                token_program: metadata.token_program,
+                decimals: metadata.decimals,
+                authorities: None,
+                unknown_extensions: false,
+            }),
+            (None, None) => match (self.token_info.first(), &self.asset_mint_account) {
+                (Some(loaded), Some(_)) if loaded.pubkey == asset_mint => Ok(ParsedAssetMint {
+                    stand_in: None,
+                    token_program: self.asset_token_program,
+                    decimals: self.asset_mint_decimals,
+                    authorities: None,
+                    unknown_extensions: false,
+                }),
+                _ => Err(TradingVenueError::NoAccountFound(asset_mint.into())),
+            },
+        }
     }
 
-    async fn update_state(&mut self, cache: &dyn AccountsCache) -> Result<(), TradingVenueError> {
-        let pubkeys = vec![
-            self.vault_key,
-            self.vault_state.lp.mint,
-            self.vault_state.asset.mint,
-            self.vault_state.asset.idle_ata,
-        ];
-
-        let accounts = cache.get_accounts(&pubkeys).await?;
-
-        // Parse vault state
-        let vault_account = accounts[0]
+    /// Apply the parsed token accounts, and `vault` when the vault account
+    /// was reloaded with them, and mark the venue initialized.
+    ///
+    /// Everything that can fail runs before the venue changes, so a failed
+    /// refresh leaves it as it was, except that a share-price anomaly
+    /// refused under `fail_on_share_price_anomaly` is still flagged to stop
+    /// quoting on the state it was found against.
+    fn apply_token_accounts(
+        &mut self,
+        vault: Option<ParsedVault>,
+        tokens: ParsedTokenAccounts,
+        accounts: &[Option<Account>],
+    ) -> Result<(), TradingVenueError> {
+        let ParsedTokenAccounts {
+            lp_mint,
+            asset_mint,
+            idle_balance,
+            idle_ata_anomaly,
+        } = tokens;
+        let lp_mint_account = accounts[0].as_ref().expect("the LP mint parsed");
+        let asset_mint_account = asset_mint.stand_in.as_ref().or(accounts[1].as_ref());
+
+        let vault_state = vault
             .as_ref()
-            .ok_or(TradingVenueError::NoAccountFound(self.vault_key.into()))?;
-        self.vault_state = Vault::load(&vault_account.data)
-            .map_err(|e: anyhow::Error| TradingVenueError::DeserializationFailed(e.to_string().into()))?;
+            .map_or(&self.vault_state, |vault| &vault.state);
+        self.check_token_info(vault_state, asset_mint_account, lp_mint_account)?;
+        let share_price = match self.check_share_price(vault_state, lp_mint.supply) {
+            Ok(share_price) => share_price,
+            Err(anomaly) => {
+                self.share_price_anomaly = Some(anomaly);
+                return Err(VoltrError::SharePriceAnomaly.into());
+            }
+        };
 
-        // Parse LP mint
-        let lp_mint_account = accounts[1]
-            .as_ref()
-            .ok_or(TradingVenueError::NoAccountFound(
-                self.vault_state.lp.mint.into(),
-            ))?;
-        let lp_mint = spl_token::state::Mint::unpack(&lp_mint_account.data)
-            .map_err(|e| TradingVenueError::DeserializationFailed(e.to_string().into()))?;
+        if let Some(vault) = vault {
+            self.apply_vault_account(vault);
+        }
         self.lp_mint_supply = lp_mint.supply;
         self.lp_mint_decimals = lp_mint.decimals;
-
-        // Parse asset mint (supports both Token and Token-2022)
-        let asset_mint_account = accounts[2]
-            .as_ref()
-            .ok_or(TradingVenueError::NoAccountFound(
-                self.vault_state.asset.mint.into(),
-            ))?;
-        self.asset_token_program = asset_mint_account.owner;
-
-        if asset_mint_account.owner == TOKEN_PROGRAM {
-            let mint = spl_token::state::Mint::unpack(&asset_mint_account.data)
-                .map_err(|e| TradingVenueError::DeserializationFailed(e.to_string().into()))?;
-            self.asset_mint_decimals = mint.decimals;
-        } else {
-            let mint = StateWithExtensions::<Mint22>::unpack(&asset_mint_account.data)
-                .map_err(|e| TradingVenueError::DeserializationFailed(e.to_string().into()))?;
-            self.asset_mint_decimals = mint.base.decimals;
+        self.check_lp_mint_authorities(&lp_mint);
+
+        self.asset_token_program = asset_mint.token_program;
+        self.asset_mint_decimals = asset_mint.decimals;
+        if let Some((mint_authority, freeze_authority, supply)) = asset_mint.authorities {
+            self.asset_mint_authority = mint_authority;
+            self.asset_mint_freeze_authority = freeze_authority;
+            self.asset_mint_supply = supply;
+            self.asset_mint_unknown_extensions = asset_mint.unknown_extensions;
+        }
+        self.asset_idle_balance = idle_balance;
+        self.idle_ata_anomaly = idle_ata_anomaly;
+        self.share_price_baseline = share_price.baseline;
+        self.share_price_anomaly = share_price.anomaly;
+
+        // Idle funds are part of the vault's total value, so a larger idle
+        // balance means `total_value` has not been cranked since a transfer in.
+        if self.asset_idle_balance > self.vault_state.asset.total_value {
+            log::warn!(
+                "Voltr vault {} idle balance {} exceeds total value {}; vault accounting is stale",
+                self.vault_key,
+                self.asset_idle_balance,
+                self.vault_state.asset.total_value
+            );
         }
 
-        // Parse idle ATA balance
-        let idle_ata_account = accounts[3]
-            .as_ref()
-            .ok_or(TradingVenueError::NoAccountFound(
-                self.vault_state.asset.idle_ata.into(),
-            ))?;
-
-        if self.asset_token_program == TOKEN_PROGRAM {
-            let idle = spl_token::state::Account::unpack(&idle_ata_account.data)
-                .map_err(|e| TradingVenueError::DeserializationFailed(e.to_string().into()))?;
-            self.asset_idle_balance = idle.amount;
-        } else {
-            let idle = StateWithExtensions::<spl_token_2022::state::Account>::unpack(
-                &idle_ata_account.data,
+        if let Ok(locked_profit) = self
+            .vault_state
+            .locked_profit_state
+            .calculate_locked_profit(
+                self.vault_state.vault_configuration.locked_profit_degradation_duration,
+                self.effective_ts(),
             )
-            .map_err(|e| TradingVenueError::DeserializationFailed(e.to_string().into()))?;
-            self.asset_idle_balance = idle.base.amount;
-        }
-
-        // Build token info
-        self.token_info = vec![
-            TokenInfo::new(
-                &self.vault_state.asset.mint,
-                asset_mint_account,
-                u64::MAX,
-            )?,
-            TokenInfo::new(&self.vault_state.lp.mint, lp_mint_account, u64::MAX)?,
-        ];
+            && locked_profit > self.vault_state.asset.total_value
+        {
+            log::warn!(
+                "Voltr vault {} locked profit {} exceeds total value {}; redeems are unavailable until it degrades",
+                self.vault_key,
+                locked_profit,
+                self.vault_state.asset.total_value
+            );
+        }
 
         self.initialized = true;
+        self.state_ts = self.effective_ts();
+        self.check_accounting_age();
+        self.check_idle_ata();
+
+        self.refresh_token_info(asset_mint_account, lp_mint_account)
+            .expect("the mint accounts were checked");
+        self.refresh_lookup_table_keys();
+        self.token_fingerprints = Some(token_fingerprints(accounts));
         Ok(())
     }
 
-    fn quote(&self, request: QuoteRequest) -> Result<QuoteResult, TradingVenueError> {
+    /// Flag the venue as migrated unless the LP mint is minted, and at most
+    /// frozen, by the vault's LP mint authority under the configured program.
+    fn check_lp_mint_authorities(&mut self, lp_mint: &spl_token::state::Mint) {
+        let expected = self.pdas().lp_mint_auth;
+        let mint_authority = Option::from(lp_mint.mint_authority);
+        let freeze_authority = Option::from(lp_mint.freeze_authority);
+        let freeze_ok = freeze_authority.is_none_or(|authority| authority == expected);
+        self.lp_mint_authority_mismatch = (mint_authority != Some(expected) || !freeze_ok)
+            .then_some(LpMintAuthorityMismatch {
+                expected,
+                mint_authority,
+                freeze_authority,
+            });
+        if let Some(mismatch) = &self.lp_mint_authority_mismatch {
+            log::warn!(
+                "Voltr vault {} LP mint authorities {:?} / {:?} are not {}; the vault may have migrated",
+                self.vault_key,
+                mismatch.mint_authority,
+                mismatch.freeze_authority,
+                expected
+            );
+        }
+    }
+
+    /// Fail as `refresh_token_info()` would on these mint accounts once
+    /// `vault_state` is loaded, without changing anything.
+    fn check_token_info(
+        &self,
+        vault_state: &Vault,
+        asset_mint_account: Option<&Account>,
+        lp_mint_account: &Account,
+    ) -> Result<(), TradingVenueError> {
+        let asset_mint = vault_state.asset.mint;
+        let asset_account = asset_mint_account
+            .or(self.asset_mint_account.as_ref())
+            .ok_or(TradingVenueError::NoAccountFound(asset_mint.into()))?;
+        TokenInfo::new(&asset_mint, asset_account, 0)?;
+        TokenInfo::new(&vault_state.lp.mint, lp_mint_account, 0)?;
+        Ok(())
+    }
+
+    /// Rebuild `token_info` from the mint accounts, advertising as each
+    /// token's max amount the largest input the vault accepts right now:
+    /// the deposit capacity for the asset and the redeemable LP for the LP
+    /// token, matching the upper end of `bounds()`. A venue that cannot quote
+    /// advertises zero.
+    ///
+    /// `asset_mint_account` is `None` when the cache pruned the asset mint,
+    /// keeping the one loaded before. A refresh that changes neither mint
+    /// account keeps the existing `token_info` and the stored accounts, so
+    /// references returned by `get_token_info()` stay valid across it; max
+    /// amounts, which move with every fee accrual, are updated in place.
+    pub(crate) fn refresh_token_info(
+        &mut self,
+        asset_mint_account: Option<&Account>,
+        lp_mint_account: &Account,
+    ) -> Result<(), TradingVenueError> {
         let asset_mint = self.vault_state.asset.mint;
         let lp_mint = self.vault_state.lp.mint;
+        let max_input = |input_mint, output_mint| {
+            self.max_fillable_amount(&QuoteRequest {
+                input_mint,
+                output_mint,
+                amount: 0,
+                swap_type: SwapType::ExactIn,
+            })
+            .unwrap_or(0)
+        };
+        let (max_deposit_amount, max_redeem_amount) =
+            (max_input(asset_mint, lp_mint), max_input(lp_mint, asset_mint));
 
-        let is_deposit = request.input_mint == asset_mint && request.output_mint == lp_mint;
-        let is_redeem = request.input_mint == lp_mint && request.output_mint == asset_mint;
+        let same_account = |loaded: &Option<Account>, fresh: &Account| {
+            loaded
+                .as_ref()
+                .is_some_and(|a| a.owner == fresh.owner && a.data == fresh.data)
+        };
+        let asset_changed =
+            asset_mint_account.is_some_and(|fresh| !same_account(&self.asset_mint_account, fresh));
+        let lp_changed = !same_account(&self.lp_mint_account, lp_mint_account);
+        let same_mints = matches!(
+            self.token_info.as_slice(),
+            [asset, lp] if asset.pubkey == asset_mint && lp.pubkey == lp_mint
+        );
+        let mints_unchanged = same_mints && !asset_changed && !lp_changed;
+        let max_unchanged = (max_deposit_amount, max_redeem_amount)
+            == (self.max_deposit_amount, self.max_redeem_amount);
+        if mints_unchanged && max_unchanged {
+            return Ok(());
+        }
+        self.max_deposit_amount = max_deposit_amount;
+        self.max_redeem_amount = max_redeem_amount;
 
-        if !is_deposit && !is_redeem {
-            return Err(TradingVenueError::InvalidMint(request.input_mint.into()));
+        if asset_changed {
+            self.asset_mint_account = asset_mint_account.cloned();
         }
+        if lp_changed {
+            self.lp_mint_account = Some(lp_mint_account.clone());
+        }
+        let (Some(asset_account), Some(lp_account)) =
+            (&self.asset_mint_account, &self.lp_mint_account)
+        else {
+            return Err(TradingVenueError::NoAccountFound(asset_mint.into()));
+        };
+        let token_info = [
+            TokenInfo::new(&asset_mint, asset_account, max_deposit_amount)?,
+            TokenInfo::new(&lp_mint, lp_account, max_redeem_amount)?,
+        ];
 
-        // Handle zero input without error (required by Titan)
-        if request.amount == 0 {
-            return Ok(QuoteResult {
-                input_mint: request.input_mint,
-                output_mint: request.output_mint,
-                amount: 0,
-                expected_output: 0,
-                not_enough_liquidity: false,
-            });
+        if mints_unchanged {
+            for (loaded, fresh) in self.token_info.iter_mut().zip(token_info) {
+                *loaded = fresh;
+            }
+        } else {
+            self.token_info = Vec::from(token_info);
         }
+        Ok(())
+    }
 
-        let total_asset_value = self.vault_state.get_total_asset_value();
-        let total_lp_supply_incl_fees = self
-            .vault_state
-            .get_total_lp_supply_incl_fees(self.lp_mint_supply)
-            .map_err(|e: anyhow::Error| TradingVenueError::CheckedMathError(e.to_string().into()))?;
-
-        let current_ts = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map(|d| d.as_secs())
-            .unwrap_or(self.vault_state.last_updated_ts);
-
-        let mgmt_fee_lp = self.estimate_management_fee_lp(
-            current_ts,
-            total_asset_value,
-            total_lp_supply_incl_fees,
-        )?;
-
-        let total_lp_supply_after_mgmt_fee = total_lp_supply_incl_fees
-            .checked_add(mgmt_fee_lp)
-            .ok_or_else(|| TradingVenueError::CheckedMathError(
-                "LP supply overflow after management fee".into(),
-            ))?;
-
-        // --- Redeem path (LP -> asset) ---
-        if is_redeem {
-            return self.quote_redeem(&request, current_ts, total_lp_supply_after_mgmt_fee);
-        }
-
-        // --- Deposit path (asset -> LP) ---
-        let amount = request.amount;
-
-        // Enforce vault max cap: if max_cap > 0, the deposit must not push
-        // total asset value above the configured ceiling.
-        let max_cap = self.vault_state.vault_configuration.max_cap;
-        if max_cap > 0 {
-            let new_total = total_asset_value.saturating_add(amount);
-            if new_total > max_cap {
-                return Ok(QuoteResult {
+    /// Largest `direction` input advertised in `get_token_info()`, as of the
+    /// last refresh.
+    pub fn max_input_amount(&self, direction: SwapDirection) -> u64 {
+        match direction {
+            SwapDirection::Deposit => self.max_deposit_amount,
+            SwapDirection::Redeem => self.max_redeem_amount,
+        }
+    }
+
+    /// An exact-in request of `amount` in `direction`, on the vault's mints.
+    pub(crate) fn direction_request(&self, direction: SwapDirection, amount: u64) -> QuoteRequest {
+        let (asset, lp) = (self.vault_state.asset.mint, self.vault_state.lp.mint);
+        let (input_mint, output_mint) = match direction {
+            SwapDirection::Deposit => (asset, lp),
+            SwapDirection::Redeem => (lp, asset),
+        };
+        QuoteRequest {
+            input_mint,
+            output_mint,
+            amount,
+            swap_type: SwapType::ExactIn,
+        }
+    }
+
+    /// `bounds()` of `direction`.
+    pub(crate) fn direction_bounds(
+        &self,
+        direction: SwapDirection,
+    ) -> Result<(u64, u64), TradingVenueError> {
+        self.amount_bounds(&self.direction_request(direction, 0))
+    }
+
+    /// Refresh the LP mint, asset mint and idle ATA while keeping the current
+    /// vault state.
+    ///
+    /// `update_state()` takes this path on its first refresh when the vault
+    /// state given at construction is younger than
+    /// `PREFETCHED_VAULT_MAX_AGE_SECS`, halving the startup fetch and parse
+    /// work for venues built with `from_account()`. Later refreshes always
+    /// re-fetch the vault. Failures count toward `VenueStatus::Stale`, as in
+    /// `update_state()`.
+    pub async fn update_state_excluding_vault(
+        &mut self,
+        cache: &dyn AccountsCache,
+    ) -> Result<(), TradingVenueError> {
+        let result = self.load_excluding_vault(cache, &[]).await;
+        self.record_refresh(result).map(|_| ())
+    }
+
+    /// `update_state_excluding_vault()` before its outcome is recorded,
+    /// fetching `extra_keys` in the same call.
+    async fn load_excluding_vault(
+        &mut self,
+        cache: &dyn AccountsCache,
+        extra_keys: &[Pubkey],
+    ) -> Result<CacheLoad, TradingVenueError> {
+        if !self.vault_loaded {
+            return Err(VoltrError::NotInitialized.into());
+        }
+
+        let mut keys = self.token_account_keys().to_vec();
+        keys.extend_from_slice(extra_keys);
+        let mut accounts = cache.get_accounts(&keys).await?;
+        let extra = accounts.split_off(keys.len() - extra_keys.len());
+        self.load_token_accounts(&accounts)?;
+        Ok(CacheLoad {
+            extra,
+            // The asset mint is the second token account, after the LP mint.
+            asset_mint_loaded: accounts[1].is_some(),
+            cache_calls: 1,
+        })
+    }
+
+    /// Fail with `VaultKeyStateMismatch` unless the stored state's LP mint is
+    /// the LP mint PDA of `vault_key` under the venue's program.
+    fn ensure_state_of_key(&self) -> Result<(), TradingVenueError> {
+        let lp_mint = VaultKey(self.vault_key).lp_mint(&ProgramId(self.program_id()));
+        self.ensure_mint(lp_mint, self.vault_state.lp.mint)
+    }
+
+    /// Fail with `VaultKeyStateMismatch` unless `found_mint` is
+    /// `expected_mint`.
+    fn ensure_mint(
+        &self,
+        expected_mint: Pubkey,
+        found_mint: Pubkey,
+    ) -> Result<(), TradingVenueError> {
+        if expected_mint != found_mint {
+            return Err(VoltrError::VaultKeyStateMismatch {
+                vault_key: self.vault_key,
+                expected_mint,
+                found_mint,
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Whether the first refresh may trust the vault state given at
+    /// construction instead of fetching it again.
+    fn can_reuse_constructed_vault(&self) -> bool {
+        !self.initialized
+            && self.constructed_at.is_some_and(|at| {
+                at.elapsed() <= Duration::from_secs(PREFETCHED_VAULT_MAX_AGE_SECS)
+            })
+    }
+
+    /// Fail with `NotInitialized` until the first successful `update_state()`,
+    /// with `VaultMigrated` while the LP mint belongs to someone else, with
+    /// `StaleStateExpired` while `VenueStatus::Expired`, and with
+    /// `SharePriceAnomaly` while an anomaly blocks quoting.
+    fn ensure_initialized(&self) -> Result<(), TradingVenueError> {
+        if !self.initialized {
+            return Err(VoltrError::NotInitialized.into());
+        }
+        if self.lp_mint_authority_mismatch.is_some() {
+            return Err(VoltrError::VaultMigrated.into());
+        }
+        if let Some(age_secs) = self.expired_state_age() {
+            return Err(VoltrError::StaleStateExpired { age_secs }.into());
+        }
+        if self.config.fail_on_share_price_anomaly && self.share_price_anomaly.is_some() {
+            return Err(VoltrError::SharePriceAnomaly.into());
+        }
+        Ok(())
+    }
+
+    /// Unix timestamp quotes, fee estimates and staleness checks are computed
+    /// at, from `config.clock`. The wall clock falls back to the vault's last
+    /// update when the system time is unavailable.
+    pub fn effective_ts(&self) -> u64 {
+        self.config
+            .clock
+            .now()
+            .unwrap_or(self.vault_state.last_updated_ts)
+    }
+
+    /// Quote `request`, also reporting why the quote is marked
+    /// `not_enough_liquidity` when it is.
+    pub fn quote_with_rejection(
+        &self,
+        request: &QuoteRequest,
+    ) -> Result<(QuoteResult, Option<LiquidityRejection>), TradingVenueError> {
+        self.quote_with_rejection_at(request, self.effective_ts())
+    }
+
+    /// Quote `request` as if it executed at unix time `current_ts`.
+    ///
+    /// Management fee accrual and locked-profit degradation are projected to
+    /// `current_ts` from the loaded state; everything else is as of the last
+    /// refresh. Deterministic for a given state and timestamp.
+    pub fn quote_at(
+        &self,
+        request: &QuoteRequest,
+        current_ts: u64,
+    ) -> Result<QuoteResult, TradingVenueError> {
+        self.quote_with_rejection_at(request, current_ts)
+            .map(|(quote, _)| quote)
+    }
+
+    pub(crate) fn quote_with_rejection_at(
+        &self,
+        request: &QuoteRequest,
+        current_ts: u64,
+    ) -> Result<(QuoteResult, Option<LiquidityRejection>), TradingVenueError> {
+        self.quote_overridden(request, &SnapshotOverrides::default(), current_ts)
+    }
+
+    /// Quote `request` at `current_ts` against `vault_snapshot()` with
+    /// `overrides` applied.
+    pub(crate) fn quote_overridden(
+        &self,
+        request: &QuoteRequest,
+        overrides: &SnapshotOverrides,
+        current_ts: u64,
+    ) -> Result<(QuoteResult, Option<LiquidityRejection>), TradingVenueError> {
+        ensure_supported_swap_type(&request.swap_type)?;
+        self.ensure_initialized()?;
+        let direction = self.direction_for(request)?;
+
+        // Handle zero input without error (required by Titan)
+        if request.amount == 0 {
+            return Ok((
+                QuoteResult {
                     input_mint: request.input_mint,
                     output_mint: request.output_mint,
-                    amount,
+                    amount: 0,
                     expected_output: 0,
-                    not_enough_liquidity: true,
-                });
-            }
+                    not_enough_liquidity: false,
+                },
+                None,
+            ));
+        }
+
+        self.ensure_idle_ata_trusted(direction)?;
+
+        if direction == SwapDirection::Redeem
+            && self.vault_state.vault_configuration.withdrawal_waiting_period != 0
+        {
+            return Err(TradingVenueError::AmmMethodError(
+                "Withdrawal waiting period must be zero for instant redeems".into(),
+            ));
         }
 
-        let issuance_fee_bps = self.vault_state.fee_configuration.issuance_fee;
+        let snapshot = overrides.apply(self.vault_snapshot()?);
+        let (output, rejection) = quote_snapshot(&snapshot, direction, request.amount, current_ts)
+            .map_err(VoltrError::from)?;
+        if let Some(rejection) = rejection {
+            return Ok((illiquid_quote(request), Some(rejection)));
+        }
 
-        let lp_before_deadweight = if total_lp_supply_incl_fees == 0 {
-            calc_init_lp_to_mint(amount, self.asset_mint_decimals, self.lp_mint_decimals)
-                .map_err(|e: anyhow::Error| TradingVenueError::CheckedMathError(e.to_string().into()))?
+        // Stale accounting may misstate strategy value, so keep a margin.
+        let haircut_bps = self.config.stale_accounting_haircut_bps;
+        let expected_output = if direction == SwapDirection::Redeem
+            && haircut_bps > 0
+            && self.stale_accounting_age(current_ts).is_some()
+        {
+            (output as u128 * MAX_FEE_BPS.saturating_sub(haircut_bps) as u128
+                / MAX_FEE_BPS as u128) as u64
         } else {
-            calc_deposit_lp_to_mint(
+            output
+        };
+
+        let min_output = self.config.min_output_in_asset_units;
+        if min_output > 0 {
+            let asset_moved = match direction {
+                SwapDirection::Deposit => {
+                    calc_amount_after_fee(request.amount, snapshot.issuance_fee_bps)
+                        .map_err(VoltrError::from)?
+                }
+                SwapDirection::Redeem => expected_output,
+            };
+            if asset_moved < min_output {
+                return Ok((
+                    illiquid_quote(request),
+                    Some(LiquidityRejection::BelowMinimumOutput),
+                ));
+            }
+        }
+
+        Ok((
+            QuoteResult {
+                input_mint: request.input_mint,
+                output_mint: request.output_mint,
+                amount: request.amount,
+                expected_output,
+                not_enough_liquidity: false,
+            },
+            None,
+        ))
+    }
+
+    /// The loaded state reduced to the figures `core::quote_snapshot()`
+    /// prices from.
+    pub fn vault_snapshot(&self) -> Result<VaultSnapshot, TradingVenueError> {
+        let vault = &self.vault_state;
+        let lp_supply = vault
+            .lp_supply_breakdown(self.lp_mint_supply)?;
+        Ok(VaultSnapshot {
+            total_value: vault.get_total_asset_value(),
+            lp_supply: lp_supply.mint_supply,
+            accumulated_lp_fees: lp_supply.accumulated_fees(),
+            dead_weight: lp_supply.dead_weight,
+            idle_balance: self.asset_idle_balance,
+            max_cap: vault.vault_configuration.max_cap,
+            issuance_fee_bps: vault.fee_configuration.issuance_fee,
+            redemption_fee_bps: vault.fee_configuration.redemption_fee,
+            management_fee_bps: vault
+                .get_total_fee_configuration_management_fee()?,
+            last_management_fee_update_ts: vault.fee_update.last_management_fee_update_ts,
+            locked_profit_degradation_duration: vault
+                .vault_configuration
+                .locked_profit_degradation_duration,
+            last_updated_locked_profit: vault.locked_profit_state.last_updated_locked_profit,
+            last_report: vault.locked_profit_state.last_report,
+            asset_decimals: self.asset_mint_decimals,
+            lp_decimals: self.lp_mint_decimals,
+        })
+    }
+
+    /// Largest input amount up to `request.amount` that is not rejected for
+    /// exceeding the vault's capacity (max cap, idle balance or unlocked
+    /// value). Output grows with input, so capacity is a single upper edge,
+    /// searched for outward from `capacity_edge_estimate()`.
+    pub(crate) fn max_amount_within_capacity(&self, request: &QuoteRequest) -> u64 {
+        let within = |amount: u64| {
+            let probe = QuoteRequest {
                 amount,
-                total_lp_supply_after_mgmt_fee,
-                total_asset_value,
-                issuance_fee_bps,
+                ..request.clone()
+            };
+            matches!(
+                self.quote_with_rejection(&probe),
+                Ok((_, rejection)) if !rejection.is_some_and(LiquidityRejection::is_capacity_limit)
             )
-            .map_err(|e: anyhow::Error| TradingVenueError::CheckedMathError(e.to_string().into()))?
         };
 
-        let lp_to_mint = if self.vault_state.dead_weight == 0 {
-            if lp_before_deadweight < DEAD_WEIGHT {
-                return Ok(QuoteResult {
-                    input_mint: request.input_mint,
-                    output_mint: request.output_mint,
-                    amount,
-                    expected_output: 0,
-                    not_enough_liquidity: true,
-                });
-            }
-            lp_before_deadweight.saturating_sub(DEAD_WEIGHT)
+        if within(request.amount) {
+            return request.amount;
+        }
+        let estimate = self
+            .direction_for(request)
+            .ok()
+            .zip(self.vault_snapshot().ok())
+            .map_or(0, |(direction, snapshot)| {
+                capacity_edge_estimate(&snapshot, direction, self.effective_ts())
+            });
+        edge_near(estimate, request.amount, within)
+    }
+
+    /// Largest input in the direction of `request` the vault can currently
+    /// fill with a positive output, or zero when it can fill none: the upper
+    /// end of `amount_bounds()`.
+    pub(crate) fn max_fillable_amount(
+        &self,
+        request: &QuoteRequest,
+    ) -> Result<u64, TradingVenueError> {
+        let ceiling = self
+            .direction_info_for(request)?
+            .capacity_limit
+            .unwrap_or(u64::MAX);
+        let upper = self.max_amount_within_capacity(&QuoteRequest {
+            amount: ceiling,
+            ..request.clone()
+        });
+        Ok(if upper > 0 && self.fills(request, upper) {
+            upper
         } else {
-            lp_before_deadweight
-        };
+            0
+        })
+    }
 
-        Ok(QuoteResult {
-            input_mint: request.input_mint,
-            output_mint: request.output_mint,
+    /// Whether `amount` in the direction of `request` quotes a positive
+    /// output without rejection.
+    fn fills(&self, request: &QuoteRequest, amount: u64) -> bool {
+        let probe = QuoteRequest {
             amount,
-            expected_output: lp_to_mint,
-            not_enough_liquidity: false,
-        })
+            ..request.clone()
+        };
+        matches!(
+            self.quote_with_rejection(&probe),
+            Ok((quote, None)) if quote.expected_output > 0
+        )
     }
 
-    fn generate_swap_instruction(
+    /// Input range `(lower, upper)` the direction of `request` can currently
+    /// fill with a positive output, or `(0, 0)` when nothing can be filled.
+    ///
+    /// The max cap is inclusive: a deposit landing exactly on it is accepted,
+    /// so a capped deposit's upper bound is exactly `max_cap` minus the
+    /// vault's total value.
+    pub(crate) fn amount_bounds(&self, request: &QuoteRequest) -> Result<(u64, u64), TradingVenueError> {
+        let upper = self.max_fillable_amount(request)?;
+        if upper == 0 {
+            return Ok((0, 0));
+        }
+        let fills = |amount: u64| self.fills(request, amount);
+
+        // Output never decreases with input. Invariant: !fills(lo) && fills(hi).
+        let (mut lo, mut hi) = (0u64, upper);
+        while hi - lo > 1 {
+            let mid = lo + (hi - lo) / 2;
+            if fills(mid) {
+                hi = mid;
+            } else {
+                lo = mid;
+            }
+        }
+        Ok((hi, upper))
+    }
+
+    /// `generate_swap_instruction()` that first quotes `request` and refuses
+    /// to build an instruction the vault would reject.
+    ///
+    /// Fails when the quote reports insufficient liquidity or zero output; the
+    /// error names the reason and the largest amount the vault can currently
+    /// accept.
+    pub fn generate_swap_instruction_validated(
         &self,
         request: QuoteRequest,
         user: Pubkey,
     ) -> Result<Instruction, TradingVenueError> {
-        let asset_mint = self.vault_state.asset.mint;
-        let lp_mint = self.vault_state.lp.mint;
-
-        let is_deposit = request.input_mint == asset_mint && request.output_mint == lp_mint;
-        let is_redeem = request.input_mint == lp_mint && request.output_mint == asset_mint;
+        let (quote, rejection) = self.quote_with_rejection(&request)?;
 
-        if !is_deposit && !is_redeem {
-            return Err(TradingVenueError::InvalidMint(request.input_mint.into()));
+        if quote.not_enough_liquidity || quote.expected_output == 0 {
+            let reason = match rejection {
+                Some(rejection) => rejection.to_string(),
+                None => "quote has zero output".to_string(),
+            };
+            return Err(TradingVenueError::AmmMethodError(
+                format!(
+                    "Refusing to build swap of {}: {}; maximum viable amount is {}",
+                    request.amount,
+                    reason,
+                    self.max_amount_within_capacity(&request)
+                )
+                .into(),
+            ));
         }
 
-        if is_redeem {
-            return self.build_instant_withdraw_vault_instruction(request.amount, &user);
+        self.generate_swap_instruction(request, user)
+    }
+
+    /// Derive the vault LP mint PDA under the mainnet program. See
+    /// [`VaultKey::lp_mint`] for other deployments.
+    pub fn derive_vault_lp_mint_pda(vault_key: &Pubkey) -> Pubkey {
+        VaultKey(*vault_key).lp_mint(&ProgramId::MAINNET)
+    }
+
+    /// PDAs of this vault under the venue's program id.
+    pub fn pdas(&self) -> VaultPdas {
+        if self.config.disable_pda_cache {
+            return VaultPdas::derive(&self.vault_key, &self.program_id());
         }
+        VaultPdas::for_vault(&self.vault_key, &self.program_id())
+    }
+}
+
+impl FromAccount for VoltrVaultVenue {
+    fn from_account(pubkey: &Pubkey, account: &Account) -> Result<Self, TradingVenueError> {
+        let vault_state = Vault::load(&account.data)?;
+        vault_state.validate()?;
+        Ok(VoltrVaultVenue::new(*pubkey, vault_state))
+    }
+}
+
+impl TryFrom<(&Pubkey, &Account)> for VoltrVaultVenue {
+    type Error = TradingVenueError;
 
-        self.build_deposit_instruction(request.amount, &user)
+    /// Same as `FromAccount::from_account()`.
+    fn try_from((pubkey, account): (&Pubkey, &Account)) -> Result<Self, Self::Error> {
+        Self::from_account(pubkey, account)
     }
 }
 
 #[async_trait]
-impl AddressLookupTableTrait for VoltrVaultVenue {
-    async fn get_lookup_table_keys(
+impl TradingVenue for VoltrVaultVenue {
+    fn initialized(&self) -> bool {
+        self.initialized
+    }
+
+    fn program_id(&self) -> Pubkey {
+        self.config.program_id.unwrap_or(VOLTR_VAULT_PROGRAM)
+    }
+
+    /// The vault program first, then the token and ATA programs, without
+    /// duplicates.
+    fn program_dependencies(&self) -> Vec<Pubkey> {
+        dedup_keys([
+            self.program_id(),
+            TOKEN_PROGRAM,
+            TOKEN_22_PROGRAM,
+            ATA_PROGRAM,
+        ])
+    }
+
+    fn market_id(&self) -> Pubkey {
+        self.vault_key
+    }
+
+    fn protocol(&self) -> PoolProtocol {
+        PoolProtocol::VoltrVault
+    }
+
+    fn get_token_info(&self) -> &[TokenInfo] {
+        &self.token_info
+    }
+
+    fn bounds(&self, input_idx: u8, output_idx: u8) -> Result<(u64, u64), TradingVenueError> {
+        self.ensure_initialized()?;
+
+        let request = QuoteRequest {
+            input_mint: self.get_token(input_idx as usize)?.pubkey,
+            output_mint: self.get_token(output_idx as usize)?.pubkey,
+            amount: 0,
+            swap_type: SwapType::ExactIn,
+        };
+        self.amount_bounds(&request)
+    }
+
+    fn get_required_pubkeys_for_update(&self) -> Result<Vec<Pubkey>, TradingVenueError> {
+        if !self.vault_loaded {
+            return Ok(vec![self.vault_key]);
+        }
+
+        let [lp_mint, asset_mint, idle_ata] = self.token_account_keys();
+        Ok(vec![self.vault_key, lp_mint, asset_mint, idle_ata])
+    }
+
+    /// Failures count toward `VenueStatus::Stale`.
+    async fn update_state(&mut self, cache: &dyn AccountsCache) -> Result<(), TradingVenueError> {
+        let result = self.load_from_cache(cache, &[]).await;
+        self.record_refresh(result).map(|_| ())
+    }
+
+    /// Self-pairs of a vault mint, which the template probes while building
+    /// its token graph, get a zero-output quote rather than an error: they
+    /// are well formed, just not swappable, and the error path allocates its
+    /// message.
+    fn quote(&self, request: QuoteRequest) -> Result<QuoteResult, TradingVenueError> {
+        if request.input_mint == request.output_mint && self.serves_mint(&request.input_mint) {
+            ensure_supported_swap_type(&request.swap_type)?;
+            self.ensure_initialized()?;
+            return Ok(QuoteResult {
+                input_mint: request.input_mint,
+                output_mint: request.output_mint,
+                amount: request.amount,
+                expected_output: 0,
+                not_enough_liquidity: false,
+            });
+        }
+
+        let current_ts = self.effective_ts();
+        let (quote, rejection) = self.quote_with_rejection_at(&request, current_ts)?;
+        if let Some(rejection) = rejection {
+            self.record_rejection(rejection, current_ts);
+        }
+        Ok(quote)
+    }
+
+    fn generate_swap_instruction(
         &self,
-        _accounts_cache: Option<&dyn AccountsCache>,
-    ) -> Result<Vec<Pubkey>, TradingVenueError> {
-        let (protocol_pda, _) =
-            Pubkey::find_program_address(&[PROTOCOL_SEED], &VOLTR_VAULT_PROGRAM);
+        request: QuoteRequest,
+        user: Pubkey,
+    ) -> Result<Instruction, TradingVenueError> {
+        self.swap_instruction_with_pdas(&request, &user, &self.pdas())
+    }
+}
 
-        let (vault_lp_mint_pda, _) = Pubkey::find_program_address(
-            &[VAULT_LP_MINT_SEED, self.vault_key.as_ref()],
-            &VOLTR_VAULT_PROGRAM,
-        );
+impl VoltrVaultVenue {
+    /// `update_state()` before its outcome is recorded, fetching
+    /// `extra_keys` along with the first `get_accounts()` call.
+    pub(crate) async fn load_from_cache(
+        &mut self,
+        cache: &dyn AccountsCache,
+        extra_keys: &[Pubkey],
+    ) -> Result<CacheLoad, TradingVenueError> {
+        // A lazily constructed venue only learns its mints and idle ATA from
+        // the vault itself, so its first refresh fetches the vault on its own.
+        let load = if !self.vault_loaded {
+            let mut keys = vec![self.vault_key];
+            keys.extend_from_slice(extra_keys);
+            let mut accounts = cache.get_accounts(&keys).await?;
+            let extra = accounts.split_off(1);
+            self.load_vault_account(accounts[0].as_ref())?;
+            let tokens = self.load_excluding_vault(cache, &[]).await?;
+            CacheLoad {
+                extra,
+                cache_calls: 2,
+                ..tokens
+            }
+        } else if self.can_reuse_constructed_vault() {
+            // Without the vault account, the LP mint is all there is to tie
+            // the constructed state to `vault_key`.
+            self.ensure_state_of_key()?;
+            self.load_excluding_vault(cache, extra_keys).await?
+        } else {
+            let mut keys = self.get_required_pubkeys_for_update()?;
+            keys.extend_from_slice(extra_keys);
+            let mut accounts = cache.get_accounts(&keys).await?;
+            let extra = accounts.split_off(keys.len() - extra_keys.len());
+
+            self.load_accounts(&accounts)?;
+            CacheLoad {
+                extra,
+                asset_mint_loaded: accounts[2].is_some(),
+                cache_calls: 1,
+            }
+        };
 
-        let (vault_asset_idle_auth_pda, _) = Pubkey::find_program_address(
-            &[VAULT_ASSET_IDLE_AUTH_SEED, self.vault_key.as_ref()],
-            &VOLTR_VAULT_PROGRAM,
-        );
+        // A plain cache reports no slots, so consistency is unknown.
+        self.state_slot = None;
+        self.inconsistent_slot_span = None;
+        Ok(load)
+    }
 
-        let (vault_lp_mint_auth_pda, _) = Pubkey::find_program_address(
-            &[VAULT_LP_MINT_AUTH_SEED, self.vault_key.as_ref()],
-            &VOLTR_VAULT_PROGRAM,
-        );
+    /// `generate_swap_instruction()` with the vault's PDAs already derived.
+    pub(crate) fn swap_instruction_with_pdas(
+        &self,
+        request: &QuoteRequest,
+        user: &Pubkey,
+        pdas: &VaultPdas,
+    ) -> Result<Instruction, TradingVenueError> {
+        ensure_supported_swap_type(&request.swap_type)?;
+        self.ensure_initialized()?;
+
+        let info = self.direction_info_with_pdas(self.direction_for(request)?, pdas);
+        self.ensure_idle_ata_trusted(info.direction)?;
+        match info.direction {
+            SwapDirection::Deposit => self.build_deposit_instruction(request.amount, user, pdas),
+            SwapDirection::Redeem => {
+                self.build_instant_withdraw_vault_instruction(request.amount, user, pdas)
+            }
+        }
+    }
 
-        Ok(vec![
-            VOLTR_VAULT_PROGRAM,
+    /// Rebuild the list `get_lookup_table_keys()` returns from the loaded
+    /// state.
+    pub(crate) fn refresh_lookup_table_keys(&mut self) {
+        self.cached_lookup_table_keys = self.lookup_table_keys(&self.pdas());
+    }
+
+    /// Accounts shared by every swap on this vault, as listed by
+    /// `get_lookup_table_keys()`: the program id first, then the vault's
+    /// accounts, then the token programs, without duplicates. Signers and
+    /// user token accounts differ per swap and are never listed.
+    pub(crate) fn lookup_table_keys(&self, pdas: &VaultPdas) -> Vec<Pubkey> {
+        dedup_keys([
+            self.program_id(),
             self.vault_key,
             self.vault_state.asset.mint,
-            vault_lp_mint_pda,
+            pdas.lp_mint,
             self.vault_state.asset.idle_ata,
-            vault_asset_idle_auth_pda,
-            vault_lp_mint_auth_pda,
-            protocol_pda,
+            pdas.asset_idle_auth,
+            pdas.lp_mint_auth,
+            pdas.protocol,
             self.asset_token_program,
             TOKEN_PROGRAM,
         ])
     }
 }
+
+#[async_trait]
+impl AddressLookupTableTrait for VoltrVaultVenue {
+    /// Accounts shared by every swap on this vault, cached at each refresh.
+    ///
+    /// With a cache and `VoltrVenueConfig::lookup_table` set, only the keys
+    /// missing from that table are returned: what it must be extended with.
+    async fn get_lookup_table_keys(
+        &self,
+        accounts_cache: Option<&dyn AccountsCache>,
+    ) -> Result<Vec<Pubkey>, TradingVenueError> {
+        let keys = if self.cached_lookup_table_keys.is_empty() {
+            self.lookup_table_keys(&self.pdas())
+        } else {
+            self.cached_lookup_table_keys.clone()
+        };
+
+        let (Some(cache), Some(table)) = (accounts_cache, self.config.lookup_table) else {
+            return Ok(keys);
+        };
+        let account = cache
+            .get_account(&table)
+            .await?
+            .ok_or(TradingVenueError::NoAccountFound(table.into()))?;
+        let present = lookup_table_addresses(&account)?;
+        Ok(keys
+            .into_iter()
+            .filter(|key| !present.contains(key))
+            .collect())
+    }
+}
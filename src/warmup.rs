@@ -0,0 +1,194 @@
+//! One-call onboarding of a venue: state, metadata, bounds, lookup table
+//! keys and compute unit estimates, fetched in as few cache calls as the
+//! venue allows.
+
+use std::time::{Duration, Instant};
+
+use solana_pubkey::Pubkey;
+use titan_integration_template::{
+    account_caching::AccountsCache,
+    trading_venue::{error::TradingVenueError, AddressLookupTableTrait, TradingVenue},
+};
+
+use crate::{
+    config::MintMetadata,
+    voltr_venue::{lookup_table_addresses, SwapDirection, VoltrVaultVenue},
+};
+
+/// What `warm_up()` does beyond loading the state.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WarmupOptions {
+    /// Do not fetch `VoltrVenueConfig::lookup_table` to report the keys it
+    /// is missing.
+    pub skip_lookup_table: bool,
+    /// Fail the warmup when the asset mint is not in the cache, instead of
+    /// loading from `known_mint_metadata` and reporting the metadata phase
+    /// as failed.
+    pub require_asset_mint: bool,
+}
+
+/// A step of `warm_up()`, in the order they run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum WarmupPhase {
+    /// The vault, LP mint, asset mint and idle ATA. The only fatal phase.
+    State,
+    /// Asset mint decimals, token program, authorities and supply.
+    Metadata,
+    /// Input bounds in both directions.
+    Bounds,
+    /// Lookup table keys, and those the configured table is missing.
+    LookupTable,
+    /// Compute unit limits of the swap instructions.
+    ComputeUnits,
+}
+
+/// How a phase of `warm_up()` ended.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WarmupOutcome {
+    Loaded,
+    Skipped(String),
+    Failed(String),
+}
+
+/// One phase of a `WarmupReport`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WarmupPhaseReport {
+    pub phase: WarmupPhase,
+    pub outcome: WarmupOutcome,
+    pub elapsed: Duration,
+}
+
+/// What `warm_up()` loaded, skipped or failed to load.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WarmupReport {
+    /// Every phase, in the order they ran.
+    pub phases: Vec<WarmupPhaseReport>,
+    /// Batched `get_accounts()` calls made on the cache.
+    pub cache_calls: usize,
+    pub asset_metadata: Option<MintMetadata>,
+    pub deposit_bounds: Option<(u64, u64)>,
+    pub redeem_bounds: Option<(u64, u64)>,
+    pub lookup_table_keys: Vec<Pubkey>,
+    /// Of `lookup_table_keys`, those missing from the configured table;
+    /// `None` when it was not checked.
+    pub lookup_table_missing: Option<Vec<Pubkey>>,
+    pub deposit_compute_units: u32,
+    pub redeem_compute_units: u32,
+}
+
+impl WarmupReport {
+    /// The report of `phase`, if it ran.
+    pub fn phase(&self, phase: WarmupPhase) -> Option<&WarmupPhaseReport> {
+        self.phases.iter().find(|report| report.phase == phase)
+    }
+
+    /// Phases that failed without failing the warmup.
+    pub fn failed(&self) -> impl Iterator<Item = &WarmupPhaseReport> {
+        self.phases
+            .iter()
+            .filter(|report| matches!(report.outcome, WarmupOutcome::Failed(_)))
+    }
+
+    pub fn total_elapsed(&self) -> Duration {
+        self.phases.iter().map(|report| report.elapsed).sum()
+    }
+
+    fn record(&mut self, phase: WarmupPhase, outcome: WarmupOutcome, started: Instant) {
+        self.phases.push(WarmupPhaseReport {
+            phase,
+            outcome,
+            elapsed: started.elapsed(),
+        });
+    }
+}
+
+impl VoltrVaultVenue {
+    /// Load everything quoting and instruction generation need, leaving the
+    /// venue ready for both without further cache calls.
+    ///
+    /// Loads the state as `update_state()` does, fetching the configured
+    /// lookup table along with the first `get_accounts()` call. A venue
+    /// whose vault was not loaded yet needs a second call for the token
+    /// accounts, since their addresses come from the vault; otherwise one
+    /// call does. Only the state phase fails the warmup, and it counts
+    /// toward `VenueStatus::Stale` as `update_state()` does; the others are
+    /// reported.
+    pub async fn warm_up(
+        &mut self,
+        cache: &dyn AccountsCache,
+        opts: WarmupOptions,
+    ) -> Result<WarmupReport, TradingVenueError> {
+        let mut report = WarmupReport::default();
+
+        let started = Instant::now();
+        let lookup_table = self
+            .config()
+            .lookup_table
+            .filter(|_| !opts.skip_lookup_table);
+        let result = self.load_from_cache(cache, lookup_table.as_slice()).await;
+        let load = self.record_refresh(result)?;
+        report.cache_calls += load.cache_calls;
+        let table_account = load.extra.into_iter().next().flatten();
+        report.record(WarmupPhase::State, WarmupOutcome::Loaded, started);
+
+        let started = Instant::now();
+        report.asset_metadata = Some(MintMetadata {
+            decimals: self.asset_mint_decimals,
+            token_program: self.asset_token_program,
+        });
+        let outcome = if load.asset_mint_loaded {
+            WarmupOutcome::Loaded
+        } else if opts.require_asset_mint {
+            return Err(TradingVenueError::NoAccountFound(
+                self.vault_state.asset.mint.into(),
+            ));
+        } else {
+            WarmupOutcome::Failed(
+                "asset mint not in cache; authorities and supply not loaded".to_string(),
+            )
+        };
+        report.record(WarmupPhase::Metadata, outcome, started);
+
+        let started = Instant::now();
+        let outcome = match (self.bounds(0, 1), self.bounds(1, 0)) {
+            (Ok(deposit), Ok(redeem)) => {
+                report.deposit_bounds = Some(deposit);
+                report.redeem_bounds = Some(redeem);
+                WarmupOutcome::Loaded
+            }
+            (Err(e), _) | (_, Err(e)) => WarmupOutcome::Failed(e.to_string()),
+        };
+        report.record(WarmupPhase::Bounds, outcome, started);
+
+        let started = Instant::now();
+        report.lookup_table_keys = self.get_lookup_table_keys(None).await?;
+        let outcome = match (lookup_table, table_account) {
+            (None, _) => WarmupOutcome::Skipped("no lookup table checked".to_string()),
+            (Some(table), None) => {
+                WarmupOutcome::Failed(TradingVenueError::NoAccountFound(table.into()).to_string())
+            }
+            (Some(_), Some(account)) => match lookup_table_addresses(&account) {
+                Ok(present) => {
+                    report.lookup_table_missing = Some(
+                        report
+                            .lookup_table_keys
+                            .iter()
+                            .filter(|key| !present.contains(key))
+                            .copied()
+                            .collect(),
+                    );
+                    WarmupOutcome::Loaded
+                }
+                Err(e) => WarmupOutcome::Failed(e.to_string()),
+            },
+        };
+        report.record(WarmupPhase::LookupTable, outcome, started);
+
+        let started = Instant::now();
+        report.deposit_compute_units = SwapDirection::Deposit.default_compute_unit_limit();
+        report.redeem_compute_units = SwapDirection::Redeem.default_compute_unit_limit();
+        report.record(WarmupPhase::ComputeUnits, WarmupOutcome::Loaded, started);
+
+        Ok(report)
+    }
+}
@@ -0,0 +1,503 @@
+//! Shared fixtures for offline venue tests.
+//!
+//! Builds synthetic Voltr vault, mint and token accounts that can be fed to
+//! `VoltrVaultVenue` through an in-memory `AccountsCache`, so the venue logic
+//! can be exercised without an RPC endpoint.
+
+#![allow(dead_code)]
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+#[cfg(feature = "local-sim")]
+use litesvm::LiteSVM;
+use solana_account::Account;
+use solana_program::native_token::LAMPORTS_PER_SOL;
+use solana_program::program_option::COption;
+use solana_program_pack::Pack;
+use solana_pubkey::Pubkey;
+#[cfg(feature = "local-sim")]
+use solana_sdk::{signature::Keypair, signer::Signer};
+use spl_token::state::Mint;
+
+use titan_integration_template::account_caching::AccountsCache;
+use titan_integration_template::trading_venue::error::TradingVenueError;
+use titan_integration_template::trading_venue::{FromAccount, TradingVenue};
+
+use titan_voltr_integration::constants::{
+    PROTOCOL_SEED, TOKEN_PROGRAM, VAULT_ASSET_IDLE_AUTH_SEED, VAULT_LP_MINT_AUTH_SEED,
+    VAULT_LP_MINT_SEED, VOLTR_VAULT_PROGRAM,
+};
+#[cfg(feature = "local-sim")]
+use titan_voltr_integration::local_sim;
+use titan_voltr_integration::pda::vault_lp_mint_auth_pda;
+use titan_voltr_integration::slots::SlotAwareAccountsCache;
+use titan_voltr_integration::state::{
+    FeeConfiguration, FeeState, FeeUpdate, LockedProfitState, VaultConfiguration,
+};
+use titan_voltr_integration::synthetic::{self, SyntheticVaultParams};
+use titan_voltr_integration::voltr_venue::VoltrVaultVenue;
+
+/// Anchor discriminator (8) plus the serialized vault fields the venue reads.
+pub const VAULT_DATA_LEN: usize = 8 + 680;
+
+/// Size of the program's `Vault` account, past the fields the venue reads.
+pub const PROGRAM_VAULT_LEN: usize = 8 + 920;
+
+/// Parameters of a synthetic vault. Defaults describe a funded, fee-free,
+/// uncapped 6-decimal vault with its whole value sitting idle.
+#[derive(Clone, Debug)]
+pub struct VaultFixture {
+    pub vault_key: Pubkey,
+    pub asset_mint: Pubkey,
+    pub idle_ata: Pubkey,
+    pub asset_decimals: u8,
+    pub total_value: u64,
+    pub idle_balance: u64,
+    pub lp_supply: u64,
+    pub max_cap: u64,
+    pub start_at_ts: u64,
+    pub locked_profit_degradation_duration: u64,
+    pub withdrawal_waiting_period: u64,
+    pub manager_management_fee: u16,
+    pub manager_performance_fee: u16,
+    pub issuance_fee: u16,
+    pub redemption_fee: u16,
+    pub last_management_fee_update_ts: u64,
+    pub accumulated_lp_fees: u64,
+    pub dead_weight: u64,
+    pub last_updated_ts: u64,
+    pub last_updated_locked_profit: u64,
+    pub last_report: u64,
+}
+
+impl Default for VaultFixture {
+    fn default() -> Self {
+        Self {
+            vault_key: Pubkey::new_unique(),
+            asset_mint: Pubkey::new_unique(),
+            idle_ata: Pubkey::new_unique(),
+            asset_decimals: 6,
+            total_value: 1_000_000_000_000,
+            idle_balance: 1_000_000_000_000,
+            lp_supply: 1_000_000_000_000,
+            max_cap: 0,
+            start_at_ts: 0,
+            locked_profit_degradation_duration: 0,
+            withdrawal_waiting_period: 0,
+            manager_management_fee: 0,
+            manager_performance_fee: 0,
+            issuance_fee: 0,
+            redemption_fee: 0,
+            last_management_fee_update_ts: 0,
+            accumulated_lp_fees: 0,
+            dead_weight: 1_000,
+            last_updated_ts: 0,
+            last_updated_locked_profit: 0,
+            last_report: 0,
+        }
+    }
+}
+
+impl VaultFixture {
+    pub fn lp_mint(&self) -> Pubkey {
+        VoltrVaultVenue::derive_vault_lp_mint_pda(&self.vault_key)
+    }
+
+    /// The LP mint with `supply`, minted by the vault's LP mint authority.
+    pub fn lp_mint_account(&self, supply: u64) -> Account {
+        let mut account = mint_account(supply, 9);
+        let mut mint = Mint::unpack(&account.data).unwrap();
+        mint.mint_authority =
+            COption::Some(vault_lp_mint_auth_pda(&self.vault_key, &VOLTR_VAULT_PROGRAM));
+        mint.pack_into_slice(&mut account.data);
+        account
+    }
+
+    /// Serialize the vault account data at the offsets `Vault::load` reads.
+    pub fn vault_data(&self) -> Vec<u8> {
+        let d = 8;
+        let mut data = vec![0u8; VAULT_DATA_LEN];
+        data[..8].copy_from_slice(&vault_discriminator());
+
+        let mut put = |offset: usize, bytes: &[u8]| {
+            data[d + offset..d + offset + bytes.len()].copy_from_slice(bytes);
+        };
+
+        // asset
+        put(96, self.asset_mint.as_ref());
+        put(128, self.idle_ata.as_ref());
+        put(160, &self.total_value.to_le_bytes());
+        // lp
+        put(264, self.lp_mint().as_ref());
+        // vault configuration
+        put(424, &self.max_cap.to_le_bytes());
+        put(432, &self.start_at_ts.to_le_bytes());
+        put(440, &self.locked_profit_degradation_duration.to_le_bytes());
+        put(448, &self.withdrawal_waiting_period.to_le_bytes());
+        // fee configuration
+        put(504, &self.manager_performance_fee.to_le_bytes());
+        put(508, &self.manager_management_fee.to_le_bytes());
+        put(512, &self.redemption_fee.to_le_bytes());
+        put(514, &self.issuance_fee.to_le_bytes());
+        // fee update
+        put(560, &self.last_management_fee_update_ts.to_le_bytes());
+        // fee state (all attributed to the manager)
+        put(568, &self.accumulated_lp_fees.to_le_bytes());
+        put(608, &self.dead_weight.to_le_bytes());
+        put(648, &self.last_updated_ts.to_le_bytes());
+        // locked profit state
+        put(664, &self.last_updated_locked_profit.to_le_bytes());
+        put(672, &self.last_report.to_le_bytes());
+
+        data
+    }
+
+    pub fn vault_account(&self) -> Account {
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: self.vault_data(),
+            owner: VOLTR_VAULT_PROGRAM,
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    /// The four accounts `update_state()` fetches, keyed by address.
+    pub fn accounts(&self) -> Vec<(Pubkey, Account)> {
+        vec![
+            (self.vault_key, self.vault_account()),
+            (self.lp_mint(), self.lp_mint_account(self.lp_supply)),
+            (self.asset_mint, mint_account(0, self.asset_decimals)),
+            (
+                self.idle_ata,
+                synthetic::token_account(
+                    &self.asset_mint,
+                    &Pubkey::new_unique(),
+                    &TOKEN_PROGRAM,
+                    self.idle_balance,
+                ),
+            ),
+        ]
+    }
+
+    pub fn cache(&self) -> MockCache {
+        MockCache::from_pairs(self.accounts())
+    }
+
+    /// Construct the venue from the vault account and load its state.
+    pub async fn venue(&self) -> VoltrVaultVenue {
+        let mut venue = VoltrVaultVenue::from_account(&self.vault_key, &self.vault_account())
+            .expect("fixture vault should parse");
+        venue
+            .update_state(&self.cache())
+            .await
+            .expect("fixture state should load");
+        venue
+    }
+
+    /// The same vault as `SyntheticVaultParams`, skipping account bytes.
+    pub fn synthetic_params(&self) -> SyntheticVaultParams {
+        SyntheticVaultParams {
+            asset_mint: self.asset_mint,
+            asset_token_program: TOKEN_PROGRAM,
+            asset_decimals: self.asset_decimals,
+            idle_ata: self.idle_ata,
+            total_value: self.total_value,
+            idle_balance: self.idle_balance,
+            lp_supply: self.lp_supply,
+            dead_weight: self.dead_weight,
+            last_updated_ts: self.last_updated_ts,
+            vault_configuration: VaultConfiguration {
+                max_cap: self.max_cap,
+                start_at_ts: self.start_at_ts,
+                locked_profit_degradation_duration: self.locked_profit_degradation_duration,
+                withdrawal_waiting_period: self.withdrawal_waiting_period,
+                ..Default::default()
+            },
+            fee_configuration: FeeConfiguration {
+                manager_performance_fee: self.manager_performance_fee,
+                manager_management_fee: self.manager_management_fee,
+                redemption_fee: self.redemption_fee,
+                issuance_fee: self.issuance_fee,
+                ..Default::default()
+            },
+            fee_update: FeeUpdate {
+                last_management_fee_update_ts: self.last_management_fee_update_ts,
+                ..Default::default()
+            },
+            fee_state: FeeState {
+                accumulated_lp_manager_fees: self.accumulated_lp_fees,
+                ..Default::default()
+            },
+            locked_profit_state: LockedProfitState {
+                last_updated_locked_profit: self.last_updated_locked_profit,
+                last_report: self.last_report,
+            },
+        }
+    }
+
+    /// An initialized venue built directly from the fixture parameters.
+    pub fn synthetic_venue(&self) -> VoltrVaultVenue {
+        VoltrVaultVenue::new_synthetic(self.vault_key, self.synthetic_params())
+            .expect("fixture params should build a venue")
+    }
+
+    /// The fixture with its idle account at the idle authority's ATA and no
+    /// cap, as the program requires to swap: it reads a zero cap as no room
+    /// at all.
+    pub fn for_program(mut self) -> Self {
+        self.idle_ata = spl_associated_token_account::get_associated_token_address(
+            &self.idle_auth(),
+            &self.asset_mint,
+        );
+        self.max_cap = u64::MAX;
+        self
+    }
+
+    /// The vault's asset idle authority PDA.
+    pub fn idle_auth(&self) -> Pubkey {
+        Pubkey::find_program_address(
+            &[VAULT_ASSET_IDLE_AUTH_SEED, self.vault_key.as_ref()],
+            &VOLTR_VAULT_PROGRAM,
+        )
+        .0
+    }
+
+    /// `local_sim::program_svm()` over this vault at `unix_timestamp`,
+    /// holding `program_accounts()`.
+    #[cfg(feature = "local-sim")]
+    pub fn program_svm(&self, unix_timestamp: u64) -> LiteSVM {
+        let mut svm = local_sim::program_svm(&VOLTR_VAULT_PROGRAM);
+        for (pubkey, account) in self.program_accounts() {
+            svm.set_account(pubkey, account).unwrap();
+        }
+        local_sim::set_unix_timestamp(&mut svm, unix_timestamp);
+        svm
+    }
+
+    /// The accounts the program swaps over this vault with.
+    ///
+    /// The vault is widened to the program's layout with the PDA bumps
+    /// filled in, its idle ATA is owned by the idle authority, and a protocol
+    /// account allowing every operation is added. See `for_program()`.
+    pub fn program_accounts(&self) -> Vec<(Pubkey, Account)> {
+        let bump = |seed: &[u8]| {
+            Pubkey::find_program_address(&[seed, self.vault_key.as_ref()], &VOLTR_VAULT_PROGRAM).1
+        };
+        let mut accounts = self.accounts();
+        for (pubkey, account) in accounts.iter_mut() {
+            if *pubkey == self.vault_key {
+                account.data.resize(PROGRAM_VAULT_LEN, 0);
+                account.data[8 + 168] = bump(VAULT_ASSET_IDLE_AUTH_SEED);
+                account.data[8 + 296] = bump(VAULT_LP_MINT_SEED);
+                account.data[8 + 297] = bump(VAULT_LP_MINT_AUTH_SEED);
+            } else if *pubkey == self.idle_ata {
+                *account = synthetic::token_account(
+                    &self.asset_mint,
+                    &self.idle_auth(),
+                    &TOKEN_PROGRAM,
+                    self.idle_balance,
+                );
+            }
+        }
+
+        let (protocol, protocol_bump) =
+            Pubkey::find_program_address(&[PROTOCOL_SEED], &VOLTR_VAULT_PROGRAM);
+        let mut data = vec![0u8; 8 + 512];
+        data[..8].copy_from_slice(&solana_sdk::hash::hash(b"account:Protocol").to_bytes()[..8]);
+        // Operational state: every operation allowed.
+        data[40] = u8::MAX;
+        data[44] = protocol_bump;
+        accounts.push((
+            protocol,
+            Account {
+                lamports: LAMPORTS_PER_SOL,
+                data,
+                owner: VOLTR_VAULT_PROGRAM,
+                executable: false,
+                rent_epoch: 0,
+            },
+        ));
+        accounts
+    }
+}
+
+/// A funded wallet in `svm` holding `balances` in ATAs of the given mints.
+#[cfg(feature = "local-sim")]
+pub fn program_user(svm: &mut LiteSVM, balances: &[(Pubkey, u64)]) -> Keypair {
+    let user = Keypair::new();
+    svm.set_account(
+        user.pubkey(),
+        Account {
+            lamports: 10 * LAMPORTS_PER_SOL,
+            owner: solana_sdk::system_program::id(),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    for (mint, amount) in balances {
+        svm.set_account(
+            spl_associated_token_account::get_associated_token_address(&user.pubkey(), mint),
+            synthetic::token_account(mint, &user.pubkey(), &TOKEN_PROGRAM, *amount),
+        )
+        .unwrap();
+    }
+    user
+}
+
+/// Anchor account discriminator for the `Vault` account type.
+pub fn vault_discriminator() -> [u8; 8] {
+    let mut discriminator = [0u8; 8];
+    discriminator.copy_from_slice(&solana_sdk::hash::hash(b"account:Vault").to_bytes()[..8]);
+    discriminator
+}
+
+pub fn mint_account(supply: u64, decimals: u8) -> Account {
+    let mut account = Account::new(LAMPORTS_PER_SOL, Mint::LEN, &TOKEN_PROGRAM);
+    let mint = Mint {
+        supply,
+        decimals,
+        is_initialized: true,
+        ..Default::default()
+    };
+    mint.pack_into_slice(&mut account.data);
+    account
+}
+
+/// In-memory `AccountsCache` over a fixed set of accounts.
+#[derive(Clone, Default)]
+pub struct MockCache {
+    pub accounts: HashMap<Pubkey, Account>,
+}
+
+impl MockCache {
+    pub fn from_pairs(pairs: impl IntoIterator<Item = (Pubkey, Account)>) -> Self {
+        Self {
+            accounts: pairs.into_iter().collect(),
+        }
+    }
+
+    pub fn insert(&mut self, pubkey: Pubkey, account: Account) {
+        self.accounts.insert(pubkey, account);
+    }
+
+    pub fn remove(&mut self, pubkey: &Pubkey) {
+        self.accounts.remove(pubkey);
+    }
+}
+
+#[async_trait]
+impl AccountsCache for MockCache {
+    async fn get_account(&self, pubkey: &Pubkey) -> Result<Option<Account>, TradingVenueError> {
+        Ok(self.accounts.get(pubkey).cloned())
+    }
+
+    async fn get_accounts(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> Result<Vec<Option<Account>>, TradingVenueError> {
+        Ok(pubkeys
+            .iter()
+            .map(|pubkey| self.accounts.get(pubkey).cloned())
+            .collect())
+    }
+}
+
+/// `MockCache` counting the calls made on it, single and batched alike.
+#[derive(Default)]
+pub struct CountingCache {
+    pub inner: MockCache,
+    calls: AtomicUsize,
+}
+
+impl CountingCache {
+    pub fn new(inner: MockCache) -> Self {
+        Self {
+            inner,
+            calls: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn calls(&self) -> usize {
+        self.calls.load(Ordering::SeqCst)
+    }
+}
+
+#[async_trait]
+impl AccountsCache for CountingCache {
+    async fn get_account(&self, pubkey: &Pubkey) -> Result<Option<Account>, TradingVenueError> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        self.inner.get_account(pubkey).await
+    }
+
+    async fn get_accounts(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> Result<Vec<Option<Account>>, TradingVenueError> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        self.inner.get_accounts(pubkeys).await
+    }
+}
+
+/// `MockCache` that reports a scripted slot per account on each
+/// slot-aware fetch. The last slot list repeats once the script runs out.
+pub struct SlotMockCache {
+    pub inner: MockCache,
+    slots: Mutex<VecDeque<Vec<Option<u64>>>>,
+    pub slot_fetches: AtomicUsize,
+}
+
+impl SlotMockCache {
+    pub fn new(inner: MockCache, slots: impl IntoIterator<Item = Vec<Option<u64>>>) -> Self {
+        Self {
+            inner,
+            slots: Mutex::new(slots.into_iter().collect()),
+            slot_fetches: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn fetches(&self) -> usize {
+        self.slot_fetches.load(Ordering::SeqCst)
+    }
+}
+
+#[async_trait]
+impl AccountsCache for SlotMockCache {
+    async fn get_account(&self, pubkey: &Pubkey) -> Result<Option<Account>, TradingVenueError> {
+        self.inner.get_account(pubkey).await
+    }
+
+    async fn get_accounts(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> Result<Vec<Option<Account>>, TradingVenueError> {
+        self.inner.get_accounts(pubkeys).await
+    }
+}
+
+#[async_trait]
+impl SlotAwareAccountsCache for SlotMockCache {
+    async fn get_accounts_with_slots(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> Result<Vec<(Option<Account>, Option<u64>)>, TradingVenueError> {
+        self.slot_fetches.fetch_add(1, Ordering::SeqCst);
+        let slots = {
+            let mut script = self.slots.lock().unwrap();
+            if script.len() > 1 {
+                script.pop_front().unwrap()
+            } else {
+                script.front().cloned().unwrap_or_default()
+            }
+        };
+        let accounts = self.inner.get_accounts(pubkeys).await?;
+        Ok(accounts
+            .into_iter()
+            .enumerate()
+            .map(|(i, account)| (account, slots.get(i).copied().flatten()))
+            .collect())
+    }
+}
@@ -0,0 +1,148 @@
+//! Helpers for the integration tests that run against live mainnet vaults.
+//!
+//! Suites take a `LiveVault` per `#[case]`. Besides the vault the suites
+//! started from, each case picks the largest live vault with a
+//! configuration the others lack, so that it adds coverage: a max cap, a
+//! Token-2022 asset, profit still locking, or high fees. A case whose vault
+//! has been closed, or which no live vault fits, is skipped with a message
+//! instead of failing the suite.
+
+#![allow(dead_code)]
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use solana_account::Account;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_pubkey::Pubkey;
+
+use titan_voltr_integration::constants::TOKEN_22_PROGRAM;
+use titan_voltr_integration::core::calc_locked_profit;
+use titan_voltr_integration::discovery::{find_all_vaults, DiscoveryOptions};
+use titan_voltr_integration::state::Vault;
+
+/// The vault every suite was first written against.
+pub const REFERENCE_VAULT: Pubkey =
+    Pubkey::from_str_const("GqoypwVGG35JSR1AwCm2jeqJPUPvA4cWE45rSbfxHgdK");
+
+/// Issuance or redemption fee from which a vault counts as high-fee.
+pub const HIGH_FEE_BPS: u16 = 50;
+
+/// Accounts fetched per `getMultipleAccounts` call.
+const MULTIPLE_ACCOUNTS_LIMIT: usize = 100;
+
+/// The live vault a test case runs against.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LiveVault {
+    /// `REFERENCE_VAULT`: uncapped, SPL Token asset, low fees.
+    Reference,
+    /// A vault with a max cap, so deposits have a capacity edge.
+    Capped,
+    /// A vault whose asset is a Token-2022 mint.
+    Token2022Asset,
+    /// A vault with reported profit still locked, so redeems price against
+    /// less than its total value.
+    LockedProfit,
+    /// A vault charging at least `HIGH_FEE_BPS` on issuance or redemption.
+    HighFees,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// Whether `vault` is instantly redeemable and holds deposits, as every
+/// suite needs.
+fn is_tradable(vault: &Vault) -> bool {
+    vault.vault_configuration.withdrawal_waiting_period == 0 && vault.asset.total_value > 0
+}
+
+/// Whether `vault` has the configuration `case` asks for, Token-2022
+/// assets aside: telling those apart needs the asset mint.
+fn fits(vault: &Vault, case: LiveVault, now: u64) -> bool {
+    match case {
+        LiveVault::Reference | LiveVault::Token2022Asset => true,
+        LiveVault::Capped => vault.vault_configuration.max_cap > 0,
+        LiveVault::LockedProfit => calc_locked_profit(
+            vault.locked_profit_state.last_updated_locked_profit,
+            vault.locked_profit_state.last_report,
+            vault.vault_configuration.locked_profit_degradation_duration,
+            now,
+        )
+        .is_ok_and(|locked| locked > 0),
+        LiveVault::HighFees => {
+            vault.fee_configuration.issuance_fee >= HIGH_FEE_BPS
+                || vault.fee_configuration.redemption_fee >= HIGH_FEE_BPS
+        }
+    }
+}
+
+/// Fetch the vault account, or `None` after printing a skip message when
+/// the vault no longer exists.
+pub async fn fetch_vault(rpc: &RpcClient, vault_key: &Pubkey) -> Option<Account> {
+    let account = rpc
+        .get_account_with_commitment(vault_key, rpc.commitment())
+        .await
+        .expect("Failed to fetch vault account")
+        .value;
+    if account.is_none() {
+        eprintln!("vault {vault_key} no longer exists, skipping");
+    }
+    account
+}
+
+/// The vault `case` runs against and its account, or `None` after printing
+/// a skip message when it is closed or no live vault fits.
+pub async fn fetch_case(rpc: &RpcClient, case: LiveVault) -> Option<(Pubkey, Account)> {
+    if case == LiveVault::Reference {
+        let account = fetch_vault(rpc, &REFERENCE_VAULT).await?;
+        return Some((REFERENCE_VAULT, account));
+    }
+
+    let report = find_all_vaults(rpc, &DiscoveryOptions::default())
+        .await
+        .expect("Failed to fetch program accounts");
+    let now = now();
+    let mut candidates: Vec<_> = report
+        .venues
+        .iter()
+        .filter(|venue| is_tradable(&venue.vault_state) && fits(&venue.vault_state, case, now))
+        .map(|venue| (venue.vault_key, venue.vault_state.clone()))
+        .collect();
+    candidates.sort_by_key(|(key, vault)| (std::cmp::Reverse(vault.asset.total_value), *key));
+
+    let vault_key = if case == LiveVault::Token2022Asset {
+        let mut found = None;
+        for chunk in candidates.chunks(MULTIPLE_ACCOUNTS_LIMIT) {
+            let mints: Vec<Pubkey> = chunk.iter().map(|(_, vault)| vault.asset.mint).collect();
+            let accounts = rpc
+                .get_multiple_accounts(&mints)
+                .await
+                .expect("Failed to fetch asset mints");
+            found = chunk
+                .iter()
+                .zip(accounts)
+                .find(|(_, mint)| {
+                    mint.as_ref()
+                        .is_some_and(|mint| mint.owner == TOKEN_22_PROGRAM)
+                })
+                .map(|((key, _), _)| *key);
+            if found.is_some() {
+                break;
+            }
+        }
+        found
+    } else {
+        candidates.first().map(|(key, _)| *key)
+    };
+
+    let Some(vault_key) = vault_key else {
+        eprintln!("no live vault fits {case:?}, skipping");
+        return None;
+    };
+    let account = fetch_vault(rpc, &vault_key).await?;
+    eprintln!("{case:?}: vault {vault_key}");
+    Some((vault_key, account))
+}
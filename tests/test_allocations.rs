@@ -0,0 +1,171 @@
+mod common;
+
+#[cfg(test)]
+mod test_allocations {
+    //! Swap account metas for a 1000-user batch with known ATAs are built
+    //! without touching the allocator, and match the accounts of the
+    //! generated instructions. Quotes refused for liquidity, counted and
+    //! rate-limited, do not allocate either, nor do a fee-free vault's
+    //! quotes and management fee estimate, nor mint pair membership checks,
+    //! nor quotes of a vault mint against itself.
+    //!
+    //! A test binary of its own: the allocator it installs is global.
+
+    use assert_no_alloc::*;
+    use solana_instruction::AccountMeta;
+    use solana_pubkey::Pubkey;
+    use titan_integration_template::trading_venue::{QuoteRequest, SwapType, TradingVenue};
+
+    use titan_voltr_integration::config::VoltrVenueConfig;
+    use titan_voltr_integration::constants::{DEPOSIT_ACCOUNTS_LEN, INSTANT_WITHDRAW_ACCOUNTS_LEN};
+    use titan_voltr_integration::voltr_venue::{LiquidityRejection, SwapDirection};
+
+    use crate::common::VaultFixture;
+
+    #[cfg(debug_assertions)] // required when disable_release is set (default)
+    #[global_allocator]
+    static A: AllocDisabler = AllocDisabler;
+
+    const BATCH: usize = 1_000;
+
+    #[test]
+    fn test_batch_metas_do_not_allocate() {
+        let fixture = VaultFixture::default();
+        let venue = fixture.synthetic_venue();
+        let pdas = venue.pdas();
+        let users: Vec<(Pubkey, Pubkey, Pubkey)> = (0..BATCH)
+            .map(|_| {
+                let user = Pubkey::new_unique();
+                (
+                    user,
+                    venue.output_account(&user, SwapDirection::Redeem),
+                    venue.output_account(&user, SwapDirection::Deposit),
+                )
+            })
+            .collect();
+
+        let mut deposits: Vec<[AccountMeta; DEPOSIT_ACCOUNTS_LEN]> = Vec::with_capacity(BATCH);
+        let mut redeems: Vec<[AccountMeta; INSTANT_WITHDRAW_ACCOUNTS_LEN]> =
+            Vec::with_capacity(BATCH);
+        assert_no_alloc(|| {
+            for (user, asset_ata, lp_ata) in &users {
+                deposits.push(venue.deposit_account_metas(user, asset_ata, lp_ata, &pdas));
+                redeems.push(venue.instant_withdraw_account_metas(user, asset_ata, lp_ata, &pdas));
+            }
+        });
+
+        for (((user, _, _), deposit), redeem) in users.iter().zip(&deposits).zip(&redeems) {
+            for (input_mint, output_mint, metas) in [
+                (fixture.asset_mint, fixture.lp_mint(), &deposit[..]),
+                (fixture.lp_mint(), fixture.asset_mint, &redeem[..]),
+            ] {
+                let request = QuoteRequest {
+                    input_mint,
+                    output_mint,
+                    amount: 1_000_000,
+                    swap_type: SwapType::ExactIn,
+                };
+                let instruction = venue.generate_swap_instruction(request, *user).unwrap();
+                assert_eq!(instruction.accounts, metas);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rejected_quotes_do_not_allocate() {
+        let fixture = VaultFixture {
+            max_cap: 1_000_000_000_000,
+            ..Default::default()
+        };
+        let venue = fixture.synthetic_venue().with_config(VoltrVenueConfig {
+            rejection_warning_interval_secs: Some(60),
+            ..Default::default()
+        });
+        let request = QuoteRequest {
+            input_mint: fixture.asset_mint,
+            output_mint: fixture.lp_mint(),
+            amount: 1_000_000,
+            swap_type: SwapType::ExactIn,
+        };
+
+        assert_no_alloc(|| {
+            for _ in 0..BATCH {
+                let quote = venue.quote(request.clone()).unwrap();
+                assert!(quote.not_enough_liquidity);
+            }
+        });
+        assert_eq!(
+            venue.rejection_count(LiquidityRejection::CapExceeded),
+            BATCH as u64
+        );
+    }
+
+    #[test]
+    fn test_fee_free_quotes_do_not_allocate() {
+        let fixture = VaultFixture {
+            last_management_fee_update_ts: 1_600_000_000,
+            ..Default::default()
+        };
+        let venue = fixture.synthetic_venue();
+        let snapshot = venue.vault_snapshot().unwrap();
+        let requests = [
+            (fixture.asset_mint, fixture.lp_mint()),
+            (fixture.lp_mint(), fixture.asset_mint),
+        ]
+        .map(|(input_mint, output_mint)| QuoteRequest {
+            input_mint,
+            output_mint,
+            amount: 1_000_000,
+            swap_type: SwapType::ExactIn,
+        });
+
+        assert_no_alloc(|| {
+            assert_eq!(snapshot.pending_management_fee_lp(1_700_000_000), Ok(0));
+            for request in &requests {
+                let quote = venue.quote(request.clone()).unwrap();
+                assert!(!quote.not_enough_liquidity);
+            }
+        });
+    }
+
+    #[test]
+    fn test_pair_membership_does_not_allocate() {
+        let fixture = VaultFixture::default();
+        let venue = fixture.synthetic_venue();
+        let (asset, lp, other) = (fixture.asset_mint, fixture.lp_mint(), Pubkey::new_unique());
+
+        assert_no_alloc(|| {
+            for _ in 0..BATCH {
+                assert_eq!(
+                    venue.pair_direction(&asset, &lp),
+                    Some(SwapDirection::Deposit)
+                );
+                assert!(venue.serves_pair(&lp, &asset));
+                assert!(!venue.serves_pair(&asset, &other));
+                assert!(venue.serves_mint(&lp) && !venue.serves_mint(&other));
+                assert!(!venue.serves_pair(&lp, &lp) && !venue.serves_pair(&asset, &asset));
+            }
+        });
+    }
+
+    #[test]
+    fn test_self_pair_quotes_do_not_allocate() {
+        let fixture = VaultFixture::default();
+        let venue = fixture.synthetic_venue();
+        let requests = [fixture.asset_mint, fixture.lp_mint()].map(|mint| QuoteRequest {
+            input_mint: mint,
+            output_mint: mint,
+            amount: 1_000_000,
+            swap_type: SwapType::ExactIn,
+        });
+
+        assert_no_alloc(|| {
+            for _ in 0..BATCH {
+                for request in &requests {
+                    let quote = venue.quote(request.clone()).unwrap();
+                    assert_eq!(quote.expected_output, 0);
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,60 @@
+mod common;
+
+#[cfg(test)]
+mod test_anyhow_compat {
+    //! The `anyhow` signatures behind the `anyhow-compat` feature:
+    //! - `state_compat::load()` keeps the `VoltrError` variant of a failure,
+    //! - the `math` wrappers return what the core does,
+    //! - an out-of-range fee fails with the offending value.
+
+    use titan_voltr_integration::core::{
+        calc_deposit_lp_to_mint_exact, calc_withdraw_asset_to_redeem_fixed,
+    };
+    use titan_voltr_integration::errors::VoltrError;
+    use titan_voltr_integration::{math, state_compat};
+
+    use crate::common::VaultFixture;
+
+    #[test]
+    fn test_state_compat_keeps_variant() {
+        let data = VaultFixture::default().vault_data();
+        let error = state_compat::load(&data[..100]).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<VoltrError>(),
+            Some(VoltrError::AccountTooShort { len: 100, .. })
+        ));
+        assert!(state_compat::load(&data).is_ok());
+    }
+
+    #[test]
+    fn test_math_matches_core() {
+        const LP: u64 = 1_000_000_000_000;
+        const ASSET: u64 = 1_050_000_000_000;
+        for (amount, fee) in [(9_999, 1), (7_027, 37), (1_000_000_007, 25), (LP, 0)] {
+            assert_eq!(
+                math::calc_deposit_lp_to_mint_exact(amount, LP, ASSET, fee).unwrap(),
+                calc_deposit_lp_to_mint_exact(amount, LP, ASSET, fee).unwrap()
+            );
+            assert_eq!(
+                math::calc_withdraw_asset_to_redeem_fixed(amount, LP, ASSET, fee).unwrap(),
+                calc_withdraw_asset_to_redeem_fixed(amount, LP, ASSET, fee).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_fee_above_max_reports_value() {
+        const FEE: u16 = 43_210;
+        for error in [
+            math::calc_deposit_lp_to_mint(1_000_000, 1_000_000_000, 1_000_000_000, FEE),
+            math::calc_withdraw_asset_to_redeem(1_000_000, 1_000_000_000, 1_000_000_000, FEE),
+        ] {
+            let error = error.unwrap_err();
+            assert!(matches!(
+                error.downcast_ref::<VoltrError>(),
+                Some(VoltrError::FeeExceedsMax { fee_bps: FEE })
+            ));
+            assert!(error.to_string().contains("43210 bps"), "{error}");
+        }
+    }
+}
@@ -1,3 +1,5 @@
+mod mainnet;
+
 #[cfg(test)]
 mod test_construction {
     //! Integration test ensuring that a Voltr vault venue:
@@ -7,10 +9,9 @@ mod test_construction {
     //! - supports quoting for both swap directions,
     //! - and exposes sane quoting boundaries.
 
-    use std::{env, str::FromStr};
+    use std::env;
 
     use rstest::rstest;
-    use solana_pubkey::Pubkey;
     use titan_integration_template::account_caching::rpc_cache::RpcClientCache;
     use titan_integration_template::trading_venue::{QuoteRequest, SwapType};
     use titan_integration_template::trading_venue::{FromAccount, TradingVenue};
@@ -21,6 +22,8 @@ mod test_construction {
 
     use assert_no_alloc::*;
 
+    use crate::mainnet::{self, LiveVault};
+
     #[cfg(debug_assertions)] // required when disable_release is set (default)
     #[global_allocator]
     static A: AllocDisabler = AllocDisabler;
@@ -37,16 +40,18 @@ mod test_construction {
     /// - Calculate valid quoting boundaries for both directions,
     /// - Return nonzero, liquidity-supported quotes at both boundary edges.
     #[rstest]
+    #[case::reference(LiveVault::Reference)]
+    #[case::capped(LiveVault::Capped)]
+    #[case::token_2022_asset(LiveVault::Token2022Asset)]
+    #[case::locked_profit(LiveVault::LockedProfit)]
+    #[case::high_fees(LiveVault::HighFees)]
     #[tokio::test]
-    #[case("GqoypwVGG35JSR1AwCm2jeqJPUPvA4cWE45rSbfxHgdK")]
-    async fn test_construction(#[case] vault_key: String) {
+    async fn test_construction(#[case] live_vault: LiveVault) {
         init_test_logger();
 
         //
         // Prepare inputs
         //
-        let vault_key = Pubkey::from_str(&vault_key).expect("Invalid test pubkey");
-
         let rpc_url =
             env::var("SOLANA_RPC_URL").expect("SOLANA_RPC_URL must be set for integration tests");
         let rpc = RpcClient::new(rpc_url);
@@ -54,10 +59,9 @@ mod test_construction {
         //
         // Fetch the vault account and construct the venue
         //
-        let vault_account = rpc
-            .get_account(&vault_key)
-            .await
-            .expect("Failed to fetch vault account");
+        let Some((vault_key, vault_account)) = mainnet::fetch_case(&rpc, live_vault).await else {
+            return;
+        };
 
         let mut venue = VoltrVaultVenue::from_account(&vault_key, &vault_account)
             .expect("Failed to construct venue from account");
@@ -0,0 +1,1773 @@
+mod common;
+
+#[cfg(test)]
+mod test_core {
+    //! The no_std pricing core on its own:
+    //! - snapshot quotes match the venue for the same vault,
+    //! - hand-computed first-deposit, cap and idle-balance cases, and
+    //!   first deposits at decimals too wide to scale,
+    //! - the issuance fee folded into one rational and floored once, and
+    //!   the asset credited net of it floored on its own,
+    //! - exact outputs floor to the u64 amounts, through the core and
+    //!   `quote_fixed()`,
+    //! - out-of-range fees from a misread vault fail with the offending
+    //!   value, through the core and the venue.
+
+    use titan_integration_template::trading_venue::{
+        error::TradingVenueError, QuoteRequest, SwapType,
+    };
+
+    use titan_voltr_integration::core::{
+        calc_amount_after_fee, calc_deposit_lp_to_mint, calc_deposit_lp_to_mint_exact,
+        calc_withdraw_asset_to_redeem, calc_withdraw_asset_to_redeem_fixed, quote_snapshot,
+        quote_snapshot_exact, CoreError, ExactOutput, LiquidityRejection, SwapDirection,
+        VaultSnapshot, DEAD_WEIGHT, FRAC_BITS,
+    };
+    use titan_voltr_integration::errors::VoltrError;
+
+    use crate::common::VaultFixture;
+
+    const NOW: u64 = 1_700_000_000;
+
+    #[test]
+    fn test_snapshot_quote_matches_venue() {
+        let fixture = VaultFixture {
+            max_cap: 1_000_000_000_000 + 5_000_000,
+            idle_balance: 400_000_000_000,
+            accumulated_lp_fees: 1_000_000,
+            manager_management_fee: 200,
+            last_management_fee_update_ts: NOW - 86_400,
+            issuance_fee: 10,
+            redemption_fee: 25,
+            locked_profit_degradation_duration: 86_400,
+            last_updated_locked_profit: 10_000_000_000,
+            last_report: NOW - 3_600,
+            ..Default::default()
+        };
+        let venue = fixture.synthetic_venue();
+        let snapshot = venue.vault_snapshot().unwrap();
+
+        for (direction, input_mint, output_mint) in [
+            (
+                SwapDirection::Deposit,
+                fixture.asset_mint,
+                fixture.lp_mint(),
+            ),
+            (SwapDirection::Redeem, fixture.lp_mint(), fixture.asset_mint),
+        ] {
+            for amount in [1, 1_000_000, 5_000_001, 500_000_000_000] {
+                let (output, rejection) =
+                    quote_snapshot(&snapshot, direction, amount, NOW).unwrap();
+                let request = QuoteRequest {
+                    input_mint,
+                    output_mint,
+                    amount,
+                    swap_type: SwapType::ExactIn,
+                };
+                let quote = venue.quote_at(&request, NOW).unwrap();
+                assert_eq!(output, quote.expected_output, "{direction:?} {amount}");
+                assert_eq!(rejection.is_some(), quote.not_enough_liquidity);
+            }
+        }
+    }
+
+    #[test]
+    fn test_first_deposit_and_limits() {
+        let empty = VaultSnapshot {
+            asset_decimals: 6,
+            lp_decimals: 9,
+            ..Default::default()
+        };
+        // A 6-decimal asset unit mints 1_000 LP at 9 decimals; the first
+        // deposit gives up exactly the dead weight.
+        let empty_9 = VaultSnapshot {
+            asset_decimals: 9,
+            ..empty
+        };
+        assert_eq!(
+            quote_snapshot(&empty_9, SwapDirection::Deposit, DEAD_WEIGHT - 1, NOW).unwrap(),
+            (0, Some(LiquidityRejection::BelowDeadWeight))
+        );
+        assert_eq!(
+            quote_snapshot(&empty, SwapDirection::Deposit, 2, NOW).unwrap(),
+            (2_000 - DEAD_WEIGHT, None)
+        );
+
+        let funded = VaultSnapshot {
+            total_value: 1_000,
+            lp_supply: 1_000,
+            dead_weight: DEAD_WEIGHT,
+            idle_balance: 100,
+            max_cap: 1_500,
+            ..empty
+        };
+        assert_eq!(
+            quote_snapshot(&funded, SwapDirection::Deposit, 500, NOW).unwrap(),
+            (1_000, None)
+        );
+        assert_eq!(
+            quote_snapshot(&funded, SwapDirection::Deposit, 501, NOW).unwrap(),
+            (0, Some(LiquidityRejection::CapExceeded))
+        );
+        assert_eq!(
+            quote_snapshot(&funded, SwapDirection::Redeem, 200, NOW).unwrap(),
+            (100, None)
+        );
+        assert_eq!(
+            quote_snapshot(&funded, SwapDirection::Redeem, 202, NOW).unwrap(),
+            (0, Some(LiquidityRejection::IdleBalanceInsufficient))
+        );
+        assert_eq!(
+            quote_snapshot(&funded, SwapDirection::Redeem, 0, NOW).unwrap(),
+            (0, None)
+        );
+
+        // Decimals past 38 overflow the scaling instead of panicking.
+        for (asset_decimals, lp_decimals) in [(39, 9), (6, 39), (u8::MAX, u8::MAX)] {
+            let wide = VaultSnapshot {
+                asset_decimals,
+                lp_decimals,
+                ..empty
+            };
+            assert_eq!(
+                quote_snapshot(&wide, SwapDirection::Deposit, 1, NOW),
+                Err(CoreError::MathOverflow)
+            );
+        }
+    }
+
+    #[test]
+    fn test_deposit_issuance_fee_floors_once() {
+        const LP: u64 = 1_000_000_000_000;
+        const ASSET: u64 = 1_050_000_000_000;
+        // (amount, issuance fee bps, LP minted). The first 1, 37 and 9_999
+        // bps amounts sit on fee rounding boundaries, where rounding the fee
+        // `amount * fee / 10_000` down on its own before minting would give
+        // one more LP. The program mints the same in
+        // `test_program::test_differential`.
+        for (amount, fee, minted) in [
+            (9_999, 1, 9_521),
+            (7_027, 37, 6_667),
+            (10_000, 500, 9_047),
+            (10_001, 9_999, 0),
+            (1_000_000_007, 37, 948_853_805),
+        ] {
+            assert_eq!(
+                calc_deposit_lp_to_mint(amount, LP, ASSET, fee).unwrap(),
+                minted,
+                "amount {amount}, fee {fee} bps"
+            );
+        }
+        // The asset credited net of the fee floors on its own.
+        assert_eq!(calc_amount_after_fee(7_027, 37), Ok(7_001));
+        assert_eq!(calc_amount_after_fee(10_001, 9_999), Ok(1));
+    }
+
+    #[test]
+    fn test_exact_outputs_floor() {
+        const LP: u64 = 1_000_000_000_000;
+        const ASSET: u64 = 1_050_000_000_000;
+        for (amount, fee) in [(9_999, 1), (7_027, 37), (1_000_000_007, 25), (LP, 0)] {
+            let (numerator, denominator) =
+                calc_deposit_lp_to_mint_exact(amount, LP, ASSET, fee).unwrap();
+            assert!(denominator > 0);
+            assert_eq!(
+                (numerator / denominator) as u64,
+                calc_deposit_lp_to_mint(amount, LP, ASSET, fee).unwrap()
+            );
+            let fixed = calc_withdraw_asset_to_redeem_fixed(amount, LP, ASSET, fee).unwrap();
+            assert_eq!(
+                (fixed >> FRAC_BITS) as u64,
+                calc_withdraw_asset_to_redeem(amount, LP, ASSET, fee).unwrap()
+            );
+        }
+        // 7_027 at 37 bps mints 6_667 and a fraction.
+        let (numerator, denominator) = calc_deposit_lp_to_mint_exact(7_027, LP, ASSET, 37).unwrap();
+        assert_ne!(numerator % denominator, 0);
+
+        let fixture = VaultFixture {
+            idle_balance: 400_000_000_000,
+            issuance_fee: 10,
+            redemption_fee: 25,
+            ..Default::default()
+        };
+        let venue = fixture.synthetic_venue();
+        let snapshot = venue.vault_snapshot().unwrap();
+        for (direction, input_mint, output_mint) in [
+            (
+                SwapDirection::Deposit,
+                fixture.asset_mint,
+                fixture.lp_mint(),
+            ),
+            (SwapDirection::Redeem, fixture.lp_mint(), fixture.asset_mint),
+        ] {
+            for amount in [1, 1_000_003, 500_000_000_000] {
+                let (exact, rejection) =
+                    quote_snapshot_exact(&snapshot, direction, amount, NOW).unwrap();
+                assert_eq!(
+                    (exact.floor().unwrap(), rejection),
+                    quote_snapshot(&snapshot, direction, amount, NOW).unwrap()
+                );
+                assert_eq!(
+                    matches!(exact, ExactOutput::Fixed(_)),
+                    direction == SwapDirection::Redeem
+                );
+
+                let request = QuoteRequest {
+                    input_mint,
+                    output_mint,
+                    amount,
+                    swap_type: SwapType::ExactIn,
+                };
+                let (quote, exact) = venue.quote_fixed(&request).unwrap();
+                assert_eq!(exact.floor().unwrap(), quote.expected_output);
+                if quote.not_enough_liquidity {
+                    assert_eq!(exact, ExactOutput::zero(direction));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_fee_above_max_reports_value() {
+        const FEE: u16 = 43_210;
+        let expected = CoreError::FeeExceedsMax { fee_bps: FEE };
+        assert_eq!(
+            calc_deposit_lp_to_mint(1_000_000, 1_000_000_000, 1_000_000_000, FEE),
+            Err(expected)
+        );
+        assert_eq!(
+            calc_withdraw_asset_to_redeem(1_000_000, 1_000_000_000, 1_000_000_000, FEE),
+            Err(expected)
+        );
+        assert_eq!(calc_amount_after_fee(1_000_000, FEE), Err(expected));
+        // The largest fee the program stores still computes.
+        assert_eq!(calc_amount_after_fee(1_000_000, 10_000), Ok(0));
+        assert_eq!(
+            calc_deposit_lp_to_mint(1_000_000, 1_000_000_000, 1_000_000_000, 10_000),
+            Ok(0)
+        );
+        assert_eq!(
+            calc_withdraw_asset_to_redeem(1_000_000, 1_000_000_000, 1_000_000_000, 10_000),
+            Ok(0)
+        );
+
+        // The venue passes it on as a parsing failure.
+        let venue = VaultFixture::default().synthetic_venue();
+        let snapshot = VaultSnapshot {
+            redemption_fee_bps: FEE,
+            ..venue.vault_snapshot().unwrap()
+        };
+        assert_eq!(
+            quote_snapshot(&snapshot, SwapDirection::Redeem, 1_000_000, NOW),
+            Err(expected)
+        );
+        let error = TradingVenueError::from(VoltrError::from(expected));
+        assert!(matches!(error, TradingVenueError::DeserializationFailed(_)));
+        assert!(error.to_string().contains("43210 bps"), "{error}");
+    }
+}
+
+#[cfg(test)]
+mod test_decay {
+    //! Quote decay over a horizon:
+    //! - a vault accruing management fees and degrading locked profit drifts
+    //!   in both directions,
+    //! - a fee-free vault without locked profit quotes the same at any horizon.
+
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use titan_integration_template::trading_venue::{QuoteRequest, SwapType};
+
+    use titan_voltr_integration::VoltrVaultVenue;
+
+    use crate::common::VaultFixture;
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
+    fn requests(fixture: &VaultFixture) -> [QuoteRequest; 2] {
+        [
+            QuoteRequest {
+                input_mint: fixture.asset_mint,
+                output_mint: fixture.lp_mint(),
+                amount: 1_000_000_000,
+                swap_type: SwapType::ExactIn,
+            },
+            QuoteRequest {
+                input_mint: fixture.lp_mint(),
+                output_mint: fixture.asset_mint,
+                amount: 1_000_000_000,
+                swap_type: SwapType::ExactIn,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_high_fee_vault_decays() {
+        let now = now();
+        let fixture = VaultFixture {
+            manager_management_fee: 500,
+            last_management_fee_update_ts: now - 60,
+            locked_profit_degradation_duration: 86_400,
+            last_updated_locked_profit: 10_000_000_000,
+            last_report: now - 60,
+            ..Default::default()
+        };
+        let venue = fixture.synthetic_venue();
+
+        for request in requests(&fixture) {
+            let decay = venue.quote_decay_estimate(&request, 3_600).unwrap();
+            assert_eq!(decay.horizon_secs, 3_600);
+            assert!(decay.output_now > 0);
+            assert!(decay.absolute_change > 0, "{decay:?}");
+            assert_eq!(
+                decay.absolute_change,
+                decay.output_now.abs_diff(decay.output_at_horizon)
+            );
+            assert_eq!(
+                decay.change_bps,
+                (decay.absolute_change as u128 * 10_000 / decay.output_now as u128) as u64
+            );
+
+            // Deterministic for a fixed state and timestamp.
+            let at_horizon = |venue: &VoltrVaultVenue| {
+                venue.quote_at(&request, now + 3_600).unwrap().expected_output
+            };
+            assert_eq!(at_horizon(&venue), at_horizon(&venue.clone()));
+        }
+
+        // Fees make deposits mint more LP later, and redeems pay out less
+        // per LP as fee LP dilutes the supply.
+        let [deposit, redeem] = requests(&fixture);
+        let deposit = venue.quote_decay_estimate(&deposit, 3_600).unwrap();
+        assert!(deposit.output_at_horizon > deposit.output_now);
+        let redeem_at_now = venue.quote_at(&redeem, now).unwrap().expected_output;
+        let redeem_later = venue.quote_at(&redeem, now + 365 * 86_400).unwrap().expected_output;
+        assert!(redeem_later < redeem_at_now);
+    }
+
+    #[test]
+    fn test_zero_fee_vault_does_not_decay() {
+        let fixture = VaultFixture {
+            last_management_fee_update_ts: now() - 60,
+            ..Default::default()
+        };
+        let venue = fixture.synthetic_venue();
+
+        for request in requests(&fixture) {
+            let decay = venue.quote_decay_estimate(&request, 86_400).unwrap();
+            assert!(decay.output_now > 0);
+            assert_eq!(decay.output_at_horizon, decay.output_now);
+            assert_eq!(decay.absolute_change, 0);
+            assert_eq!(decay.change_bps, 0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_error_bound {
+    //! `quote_error_bound()` on synthetic vaults:
+    //! - a fee-free vault is only off by the final floor, and fixed outputs
+    //!   (zero amounts, rejections) by nothing,
+    //! - across randomized states, quoting anywhere in the configured window
+    //!   lands within `quote ± bound`,
+    //! - the unmodeled performance fee is bounded by its share of the output,
+    //! - a stale-accounting haircut on a redeem is covered in full.
+
+    use rand::Rng;
+    use titan_integration_template::trading_venue::{QuoteRequest, SwapType, TradingVenue};
+
+    use titan_voltr_integration::clock::QuoteClock;
+    use titan_voltr_integration::config::VoltrVenueConfig;
+    use titan_voltr_integration::voltr_venue::VoltrVaultVenue;
+
+    use crate::common::VaultFixture;
+
+    const T0: u64 = 1_700_000_000;
+    const WINDOW: u64 = 120;
+
+    fn venue(fixture: &VaultFixture, quote_error_window_secs: u64) -> VoltrVaultVenue {
+        fixture.synthetic_venue().with_config(VoltrVenueConfig {
+            quote_error_window_secs,
+            clock: QuoteClock::Fixed(T0),
+            ..Default::default()
+        })
+    }
+
+    fn requests(fixture: &VaultFixture, amount: u64) -> [QuoteRequest; 2] {
+        [
+            QuoteRequest {
+                input_mint: fixture.asset_mint,
+                output_mint: fixture.lp_mint(),
+                amount,
+                swap_type: SwapType::ExactIn,
+            },
+            QuoteRequest {
+                input_mint: fixture.lp_mint(),
+                output_mint: fixture.asset_mint,
+                amount,
+                swap_type: SwapType::ExactIn,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_fee_free_vault_off_by_rounding_only() {
+        let fixture = VaultFixture::default();
+        let venue = venue(&fixture, WINDOW);
+        for request in requests(&fixture, 1_000_000_000) {
+            let terms = venue.quote_error_terms(&request).unwrap();
+            assert_eq!(terms.rounding, 1);
+            assert_eq!(terms.timestamp, 0);
+            assert_eq!(terms.performance_fee, 0);
+            assert_eq!(terms.haircut, 0);
+            assert_eq!(venue.quote_error_bound(&request).unwrap(), 1);
+        }
+
+        // Outputs fixed at 0 cannot be off.
+        for request in requests(&fixture, 0) {
+            assert_eq!(venue.quote_error_bound(&request).unwrap(), 0);
+        }
+        let [_, too_large_redeem] = requests(&fixture, 2 * fixture.idle_balance);
+        assert!(
+            venue
+                .quote(too_large_redeem.clone())
+                .unwrap()
+                .not_enough_liquidity
+        );
+        assert_eq!(venue.quote_error_bound(&too_large_redeem).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_window_quotes_within_bound() {
+        let mut rng = rand::rng();
+        for _ in 0..200 {
+            let total_value = rng.random_range(1_000_000..1_000_000_000_000_000);
+            let fixture = VaultFixture {
+                total_value,
+                idle_balance: total_value,
+                lp_supply: rng.random_range(total_value / 2..total_value * 2),
+                manager_management_fee: rng.random_range(0..=500),
+                issuance_fee: rng.random_range(0..=100),
+                redemption_fee: rng.random_range(0..=100),
+                last_management_fee_update_ts: T0 - rng.random_range(0..30 * 86_400),
+                locked_profit_degradation_duration: rng.random_range(0..=86_400),
+                last_updated_locked_profit: rng.random_range(0..total_value / 10),
+                last_report: T0 - rng.random_range(0..86_400),
+                last_updated_ts: T0,
+                ..Default::default()
+            };
+            let venue = venue(&fixture, WINDOW);
+            let amount = rng.random_range(1_000..total_value / 10);
+            for request in requests(&fixture, amount) {
+                let quote = venue.quote(request.clone()).unwrap();
+                let bound = venue.quote_error_bound(&request).unwrap();
+                for ts in [T0 - WINDOW, T0 - WINDOW / 3, T0 + WINDOW / 2, T0 + WINDOW] {
+                    let executed = venue.quote_at(&request, ts).unwrap();
+                    if executed.not_enough_liquidity {
+                        continue;
+                    }
+                    assert!(
+                        executed.expected_output.abs_diff(quote.expected_output) <= bound,
+                        "{fixture:?} {request:?} at {ts}: {} vs {} ± {bound}",
+                        executed.expected_output,
+                        quote.expected_output
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_performance_fee_share_of_output() {
+        // No high water mark is recorded, so all of the price counts.
+        let fixture = VaultFixture {
+            manager_performance_fee: 1_000,
+            ..Default::default()
+        };
+        let venue = venue(&fixture, 0);
+        for request in requests(&fixture, 1_000_000_000) {
+            let output = venue.quote(request.clone()).unwrap().expected_output;
+            let terms = venue.quote_error_terms(&request).unwrap();
+            assert_eq!(terms.performance_fee, output.div_ceil(10));
+            assert_eq!(
+                venue.quote_error_bound(&request).unwrap(),
+                terms.rounding + terms.performance_fee
+            );
+        }
+    }
+
+    #[test]
+    fn test_stale_accounting_haircut_covered() {
+        let fixture = VaultFixture {
+            last_updated_ts: T0 - 7_200,
+            ..Default::default()
+        };
+        let [deposit, redeem] = requests(&fixture, 1_000_000_000);
+        let stale = fixture.synthetic_venue().with_config(VoltrVenueConfig {
+            clock: QuoteClock::Fixed(T0),
+            max_accounting_age_secs: Some(3_600),
+            stale_accounting_haircut_bps: 50,
+            ..Default::default()
+        });
+        // What executes: the same state without the haircut.
+        let executed = venue(&fixture, 0)
+            .quote(redeem.clone())
+            .unwrap()
+            .expected_output;
+        let quoted = stale.quote(redeem.clone()).unwrap().expected_output;
+        assert_eq!(quoted, executed * 9_950 / 10_000);
+
+        let terms = stale.quote_error_terms(&redeem).unwrap();
+        assert_eq!(terms.haircut, executed - quoted);
+        assert!(quoted + stale.quote_error_bound(&redeem).unwrap() >= executed);
+        // Deposits are not haircut.
+        assert_eq!(stale.quote_error_terms(&deposit).unwrap().haircut, 0);
+    }
+}
+
+#[cfg(test)]
+mod test_fees {
+    //! `accrued_fees()` per recipient:
+    //! - accumulated fee LP is valued at the vault's total value per LP,
+    //! - the pending management fee is split by each recipient's fee,
+    //! - an empty vault values everything at zero instead of dividing by it.
+
+    use solana_pubkey::Pubkey;
+
+    use titan_voltr_integration::constants::ONE_YEAR_U64;
+    use titan_voltr_integration::fees::{AccruedFees, FeeAmount, RecipientFees};
+    use titan_voltr_integration::state::{FeeConfiguration, FeeState, FeeUpdate};
+    use titan_voltr_integration::synthetic::SyntheticVaultParams;
+    use titan_voltr_integration::voltr_venue::VoltrVaultVenue;
+
+    const LAST_FEE_UPDATE_TS: u64 = 1_700_000_000;
+
+    fn venue(params: SyntheticVaultParams) -> VoltrVaultVenue {
+        VoltrVaultVenue::new_synthetic(Pubkey::new_unique(), params).unwrap()
+    }
+
+    fn fee_state(manager: u64, admin: u64, protocol: u64) -> FeeState {
+        FeeState {
+            accumulated_lp_manager_fees: manager,
+            accumulated_lp_admin_fees: admin,
+            accumulated_lp_protocol_fees: protocol,
+        }
+    }
+
+    fn accumulated(lp: u64, asset_value: u64) -> RecipientFees {
+        RecipientFees {
+            accumulated: FeeAmount { lp, asset_value },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_accumulated_fees_at_share_price() {
+        // 1_000 LP in total, worth 2 asset each.
+        let venue = venue(SyntheticVaultParams {
+            total_value: 2_000,
+            lp_supply: 900,
+            dead_weight: 40,
+            fee_state: fee_state(30, 20, 10),
+            ..Default::default()
+        });
+
+        let fees = venue.accrued_fees(LAST_FEE_UPDATE_TS).unwrap();
+        assert_eq!(
+            fees,
+            AccruedFees {
+                current_ts: LAST_FEE_UPDATE_TS,
+                manager: accumulated(30, 60),
+                admin: accumulated(20, 40),
+                protocol: accumulated(10, 20),
+            }
+        );
+        assert_eq!(
+            fees.total(),
+            FeeAmount {
+                lp: 60,
+                asset_value: 120
+            }
+        );
+    }
+
+    #[test]
+    fn test_pending_management_fee_split_by_recipient() {
+        let venue = venue(SyntheticVaultParams {
+            total_value: 1_000_000,
+            lp_supply: 1_000_000,
+            fee_configuration: FeeConfiguration {
+                manager_management_fee: 100,
+                admin_management_fee: 50,
+                protocol_management_fee: 50,
+                ..Default::default()
+            },
+            fee_update: FeeUpdate {
+                last_management_fee_update_ts: LAST_FEE_UPDATE_TS,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+
+        // Nothing is pending at the last update.
+        assert_eq!(
+            venue.accrued_fees(LAST_FEE_UPDATE_TS).unwrap().total(),
+            FeeAmount::default()
+        );
+
+        // A year at 2% charges 20_000 asset, minted as 20_409 LP.
+        let fees = venue
+            .accrued_fees(LAST_FEE_UPDATE_TS + ONE_YEAR_U64)
+            .unwrap();
+        let pending = |fees: RecipientFees| fees.pending_management;
+        assert_eq!(
+            pending(fees.manager),
+            FeeAmount {
+                lp: 10_204,
+                asset_value: 9_999
+            }
+        );
+        assert_eq!(
+            pending(fees.admin),
+            FeeAmount {
+                lp: 5_102,
+                asset_value: 4_999
+            }
+        );
+        assert_eq!(pending(fees.protocol), pending(fees.admin));
+        assert_eq!(fees.manager.accumulated, FeeAmount::default());
+    }
+
+    #[test]
+    fn test_zero_supply_values_fees_at_zero() {
+        let empty = venue(SyntheticVaultParams::default());
+        assert_eq!(
+            empty.accrued_fees(LAST_FEE_UPDATE_TS).unwrap().total(),
+            FeeAmount::default()
+        );
+
+        // Value donated to a vault without shares mints no fee LP.
+        let donated = venue(SyntheticVaultParams {
+            total_value: 5_000,
+            fee_configuration: FeeConfiguration {
+                manager_management_fee: 200,
+                ..Default::default()
+            },
+            fee_update: FeeUpdate {
+                last_management_fee_update_ts: LAST_FEE_UPDATE_TS,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let fees = donated
+            .accrued_fees(LAST_FEE_UPDATE_TS + ONE_YEAR_U64)
+            .unwrap();
+        assert_eq!(fees.total(), FeeAmount::default());
+    }
+
+    #[test]
+    fn test_uninitialized_venue_has_no_fees() {
+        let venue = VoltrVaultVenue::new_uninitialized(Pubkey::new_unique());
+        assert!(venue.accrued_fees(LAST_FEE_UPDATE_TS).is_err());
+    }
+}
+
+#[cfg(test)]
+mod test_fixed {
+    //! The U80F48 helpers:
+    //! - `mul_int_ratio()` against 192-bit reference arithmetic over random
+    //!   values, integers and edge cases, failing exactly when the reference
+    //!   does not fit in a u128,
+    //! - the checked and saturating variants agree with it,
+    //! - the redeem payout is `from_int(lp) * asset / supply * fee / 10000`,
+    //! - floor, ceil and integer comparison around the fractional bits,
+    //! - the high water mark reads its bits as U80F48.
+
+    use std::cmp::Ordering;
+
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use titan_voltr_integration::constants::MAX_FEE_BPS;
+    use titan_voltr_integration::core::{calc_withdraw_asset_to_redeem_fixed, CoreError};
+    use titan_voltr_integration::fixed::{FRAC_BITS, U80F48};
+    use titan_voltr_integration::state::HighWaterMark;
+
+    const CASES: usize = 100_000;
+
+    const ONE: u128 = 1 << FRAC_BITS;
+
+    /// `x * a / b` floored, as most significant first u64 limbs, by long
+    /// multiplication and division.
+    fn reference_mul_div(x: u128, a: u64, b: u64) -> [u64; 3] {
+        let lo = (x as u64 as u128) * a as u128;
+        let hi = (x >> 64) * a as u128 + (lo >> 64);
+        let product = [(hi >> 64) as u64, hi as u64, lo as u64];
+
+        let mut quotient = [0u64; 3];
+        let mut rem = 0u128;
+        for (q, limb) in quotient.iter_mut().zip(product) {
+            let cur = (rem << 64) | limb as u128;
+            *q = (cur / b as u128) as u64;
+            rem = cur % b as u128;
+        }
+        quotient
+    }
+
+    /// `reference_mul_div()`, `None` when it does not fit in a u128.
+    fn reference(x: u128, a: u64, b: u64) -> Option<u128> {
+        match reference_mul_div(x, a, b) {
+            [0, hi, lo] => Some(((hi as u128) << 64) | lo as u128),
+            _ => None,
+        }
+    }
+
+    /// A u64 drawn from edge values, small integers or the full range.
+    fn int(rng: &mut StdRng) -> u64 {
+        match rng.random_range(0..4) {
+            0 => [0, 1, 2, MAX_FEE_BPS as u64, u64::MAX - 1, u64::MAX][rng.random_range(0..6)],
+            1 => rng.random_range(0..1_000_000),
+            2 => rng.random::<u64>() >> rng.random_range(0..64),
+            _ => rng.random(),
+        }
+    }
+
+    /// A U80F48 drawn from integers, fractions below one or the full range.
+    fn value(rng: &mut StdRng) -> U80F48 {
+        match rng.random_range(0..4) {
+            0 => U80F48::from_int(int(rng)),
+            1 => U80F48::from_bits(rng.random_range(0..ONE)),
+            2 => U80F48::from_bits(rng.random::<u128>() >> rng.random_range(0..128)),
+            _ => U80F48::from_bits(rng.random()),
+        }
+    }
+
+    #[test]
+    fn test_mul_int_ratio_matches_reference() {
+        let seed = rand::rng().random();
+        let mut rng = StdRng::seed_from_u64(seed);
+        let (mut fits, mut overflows) = (0, 0);
+        for _ in 0..CASES {
+            let (x, a, b) = (value(&mut rng), int(&mut rng), int(&mut rng));
+            let got = x.mul_int_ratio(a, b);
+            let case = format!("{x:?} * {a} / {b}, seed {seed}");
+
+            if b == 0 {
+                assert_eq!(got, Err(CoreError::DivisionByZero), "{case}");
+                assert_eq!(x.saturating_mul_int_ratio(a, b), U80F48::MAX, "{case}");
+                continue;
+            }
+            match reference(x.to_bits(), a, b) {
+                Some(bits) => {
+                    fits += 1;
+                    assert_eq!(got, Ok(U80F48::from_bits(bits)), "{case}");
+                    assert_eq!(x.checked_mul_int_ratio(a, b), got.ok(), "{case}");
+                    assert_eq!(x.saturating_mul_int_ratio(a, b).to_bits(), bits, "{case}");
+                }
+                None => {
+                    overflows += 1;
+                    assert_eq!(got, Err(CoreError::MathOverflow), "{case}");
+                    assert_eq!(x.checked_mul_int_ratio(a, b), None, "{case}");
+                    assert_eq!(x.saturating_mul_int_ratio(a, b), U80F48::MAX, "{case}");
+                }
+            }
+        }
+        // Both outcomes must be exercised for the comparison to mean much.
+        assert!(
+            fits > CASES / 10 && overflows > CASES / 100,
+            "{fits} / {overflows}"
+        );
+    }
+
+    #[test]
+    fn test_redeem_payout_matches_reference() {
+        let seed = rand::rng().random();
+        let mut rng = StdRng::seed_from_u64(seed);
+        for _ in 0..CASES {
+            let (lp, supply, asset) = (int(&mut rng), int(&mut rng), int(&mut rng));
+            let fee_bps = rng.random_range(0..=MAX_FEE_BPS);
+            let got = calc_withdraw_asset_to_redeem_fixed(lp, supply, asset, fee_bps);
+            let case = format!("{lp} LP of {supply} over {asset}, fee {fee_bps}, seed {seed}");
+
+            if supply == 0 {
+                assert_eq!(got, Err(CoreError::DivisionByZero), "{case}");
+                continue;
+            }
+            let expected = reference((lp as u128) << FRAC_BITS, asset, supply).and_then(|bits| {
+                reference(bits, (MAX_FEE_BPS - fee_bps) as u64, MAX_FEE_BPS as u64)
+            });
+            assert_eq!(got, expected.ok_or(CoreError::MathOverflow), "{case}");
+        }
+    }
+
+    #[test]
+    fn test_floor_ceil_cmp_int() {
+        let half = U80F48::from_bits(ONE / 2);
+        let three = U80F48::from_int(3);
+        let three_and_a_bit = U80F48::from_bits(3 * ONE + 1);
+
+        assert_eq!((U80F48::ZERO.to_floor(), U80F48::ZERO.to_ceil()), (0, 0));
+        assert_eq!((half.to_floor(), half.to_ceil()), (0, 1));
+        assert_eq!((three.to_floor(), three.to_ceil()), (3, 3));
+        assert_eq!(
+            (three_and_a_bit.to_floor(), three_and_a_bit.to_ceil()),
+            (3, 4)
+        );
+        assert_eq!(U80F48::MAX.to_floor(), u128::MAX >> FRAC_BITS);
+        assert_eq!(U80F48::MAX.to_ceil(), (u128::MAX >> FRAC_BITS) + 1);
+        assert_eq!(U80F48::from_int(u64::MAX).to_floor(), u64::MAX as u128);
+
+        assert_eq!(half.cmp_int(0), Ordering::Greater);
+        assert_eq!(half.cmp_int(1), Ordering::Less);
+        assert_eq!(three.cmp_int(3), Ordering::Equal);
+        assert_eq!(three_and_a_bit.cmp_int(3), Ordering::Greater);
+        assert_eq!(U80F48::MAX.cmp_int(u64::MAX), Ordering::Greater);
+
+        // Integer arithmetic needs no rounding: 3 * 7 / 21 is exactly 1.
+        assert_eq!(three.mul_int_ratio(7, 21), Ok(U80F48::from_int(1)));
+        // A third of one floors to the last fractional bit below it.
+        assert_eq!(
+            U80F48::from_int(1).mul_int_ratio(1, 3).unwrap().to_bits(),
+            ONE / 3
+        );
+    }
+
+    #[test]
+    fn test_add_sub() {
+        let one = U80F48::from_int(1);
+        assert_eq!(one.checked_add(one), Some(U80F48::from_int(2)));
+        assert_eq!(U80F48::MAX.checked_add(U80F48::from_bits(1)), None);
+        assert_eq!(U80F48::MAX.saturating_add(one), U80F48::MAX);
+        assert_eq!(one.checked_sub(U80F48::from_int(2)), None);
+        assert_eq!(one.saturating_sub(U80F48::from_int(2)), U80F48::ZERO);
+        assert_eq!(
+            U80F48::from_int(2).checked_sub(U80F48::from_bits(ONE / 2)),
+            Some(U80F48::from_bits(3 * ONE / 2))
+        );
+    }
+
+    #[test]
+    fn test_high_water_mark() {
+        let mark = HighWaterMark {
+            highest_asset_per_lp_decimal_bits: 5 * ONE / 4,
+            ..Default::default()
+        };
+        let price = mark.highest_asset_per_lp();
+        assert_eq!((price.to_floor(), price.to_ceil()), (1, 2));
+        // 1.25 asset per LP values 8 LP at 10 asset.
+        assert_eq!(price.mul_int_ratio(8, 1).unwrap().to_floor(), 10);
+        assert_eq!(
+            HighWaterMark::default().highest_asset_per_lp(),
+            U80F48::ZERO
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_invariants {
+    //! Property tests over randomly generated vault snapshots asserting quote
+    //! invariants that must hold regardless of the vault configuration.
+
+    use rand::Rng;
+    use titan_integration_template::trading_venue::{QuoteRequest, SwapType, TradingVenue};
+
+    use crate::common::VaultFixture;
+
+    /// Generate a random but internally plausible vault snapshot.
+    fn random_fixture(rng: &mut impl Rng, now: u64) -> VaultFixture {
+        let total_value = rng.random_range(0..=1_000_000_000_000_000u64);
+        let degradation = rng.random_range(0..=86_400u64);
+        VaultFixture {
+            total_value,
+            // Occasionally exceed total value to mimic stale accounting.
+            idle_balance: rng.random_range(0..=total_value.saturating_add(total_value / 10)),
+            lp_supply: rng.random_range(0..=1_000_000_000_000_000u64),
+            accumulated_lp_fees: rng.random_range(0..=1_000_000u64),
+            dead_weight: rng.random_range(0..=1_000u64),
+            manager_management_fee: rng.random_range(0..=500u16),
+            last_management_fee_update_ts: now.saturating_sub(rng.random_range(0..=31_536_000)),
+            redemption_fee: rng.random_range(0..=10_000u16),
+            locked_profit_degradation_duration: degradation,
+            last_updated_locked_profit: rng.random_range(0..=total_value),
+            last_report: now.saturating_sub(rng.random_range(0..=2 * degradation)),
+            ..Default::default()
+        }
+    }
+
+    fn now() -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
+    #[tokio::test]
+    async fn test_redeem_never_exceeds_unlocked_or_idle() {
+        let mut rng = rand::rng();
+
+        for _ in 0..500 {
+            let fixture = random_fixture(&mut rng, now());
+            let venue = fixture.venue().await;
+
+            for _ in 0..10 {
+                let amount = rng.random_range(1..=fixture.lp_supply.saturating_mul(2).max(1));
+                let Ok(result) = venue.quote(QuoteRequest {
+                    input_mint: fixture.lp_mint(),
+                    output_mint: fixture.asset_mint,
+                    amount,
+                    swap_type: SwapType::ExactIn,
+                }) else {
+                    continue;
+                };
+
+                // Unlocked value only grows with time, so reading it after the
+                // quote bounds the value the quote used.
+                let unlocked = venue.vault_state.get_unlocked_asset_value(now()).unwrap();
+                assert!(
+                    result.expected_output <= unlocked,
+                    "Redeem of {} LP quoted {} above unlocked value {} for {:?}",
+                    amount,
+                    result.expected_output,
+                    unlocked,
+                    fixture
+                );
+                assert!(result.expected_output <= fixture.idle_balance);
+                if result.not_enough_liquidity {
+                    assert_eq!(result.expected_output, 0);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_lp_supply {
+    //! LP supply reconciliation:
+    //! - the breakdown lists each component and sums to the priced total,
+    //! - sums past u64::MAX fail instead of wrapping,
+    //! - detailed quotes carry the breakdown they were priced against.
+
+    use solana_pubkey::Pubkey;
+    use titan_integration_template::trading_venue::{QuoteRequest, SwapType};
+
+    use titan_voltr_integration::state::{FeeState, LpSupplyBreakdown, Vault};
+
+    use crate::common::VaultFixture;
+
+    fn vault(fee_state: FeeState, dead_weight: u64) -> Vault {
+        Vault {
+            fee_state,
+            dead_weight,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_breakdown_components() {
+        let vault = vault(
+            FeeState {
+                accumulated_lp_manager_fees: 30,
+                accumulated_lp_admin_fees: 20,
+                accumulated_lp_protocol_fees: 10,
+            },
+            1_000,
+        );
+
+        let breakdown = vault.lp_supply_breakdown(5_000).unwrap();
+        assert_eq!(
+            breakdown,
+            LpSupplyBreakdown {
+                mint_supply: 5_000,
+                manager_fees: 30,
+                admin_fees: 20,
+                protocol_fees: 10,
+                dead_weight: 1_000,
+                total: 6_060,
+            }
+        );
+        assert_eq!(breakdown.accumulated_fees(), 60);
+        assert_eq!(vault.get_total_lp_supply_incl_fees(5_000).unwrap(), breakdown.total);
+    }
+
+    #[test]
+    fn test_breakdown_overflow_guards() {
+        let near_max = u64::MAX - 10;
+
+        // Exactly u64::MAX still fits.
+        let fits = vault(
+            FeeState {
+                accumulated_lp_manager_fees: near_max,
+                accumulated_lp_admin_fees: 5,
+                accumulated_lp_protocol_fees: 0,
+            },
+            3,
+        );
+        assert_eq!(fits.lp_supply_breakdown(2).unwrap().total, u64::MAX);
+        assert!(fits.lp_supply_breakdown(3).is_err());
+
+        // Each stage of the sum is guarded: fees, mint supply, dead weight.
+        let fee_overflow = vault(
+            FeeState {
+                accumulated_lp_manager_fees: near_max,
+                accumulated_lp_admin_fees: 0,
+                accumulated_lp_protocol_fees: 11,
+            },
+            0,
+        );
+        assert!(fee_overflow.lp_supply_breakdown(0).is_err());
+        assert!(vault(FeeState::default(), 0).lp_supply_breakdown(u64::MAX).is_ok());
+        assert!(vault(FeeState::default(), 1).lp_supply_breakdown(u64::MAX).is_err());
+        assert!(fee_overflow.get_total_lp_supply_incl_fees(0).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_detailed_quote_carries_breakdown() {
+        let fixture = VaultFixture {
+            accumulated_lp_fees: 7_000,
+            ..Default::default()
+        };
+        let venue = fixture.synthetic_venue();
+        let request = QuoteRequest {
+            input_mint: fixture.asset_mint,
+            output_mint: fixture.lp_mint(),
+            amount: 1_000_000,
+            swap_type: SwapType::ExactIn,
+        };
+
+        let detailed = venue
+            .quote_detailed(&request, &Pubkey::new_unique(), None)
+            .await
+            .unwrap();
+        assert_eq!(detailed.lp_supply.mint_supply, fixture.lp_supply);
+        assert_eq!(detailed.lp_supply.manager_fees, 7_000);
+        assert_eq!(detailed.lp_supply.dead_weight, fixture.dead_weight);
+        assert_eq!(
+            detailed.lp_supply.total,
+            fixture.lp_supply + 7_000 + fixture.dead_weight
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_no_panic {
+    //! `quote()` never panics, whatever the state it is loaded from:
+    //! - 100k random cases of type-valid vault fields (extreme integers,
+    //!   any decimals, timestamps in any order), mint supplies, idle
+    //!   balances, clocks and requests, each loaded and quoted under
+    //!   `catch_unwind`,
+    //! - a panicking case is shrunk field by field to a minimal reproducer,
+    //!   reported with the seed that found it.
+
+    use std::panic::{self, AssertUnwindSafe};
+
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use solana_program_pack::Pack;
+    use solana_pubkey::Pubkey;
+    use spl_token::state::Mint;
+    use titan_integration_template::trading_venue::{
+        FromAccount, QuoteRequest, SwapType, TradingVenue,
+    };
+
+    use titan_voltr_integration::clock::QuoteClock;
+    use titan_voltr_integration::config::VoltrVenueConfig;
+    use titan_voltr_integration::constants::{MAX_FEE_BPS, TOKEN_PROGRAM};
+    use titan_voltr_integration::synthetic::token_account;
+    use titan_voltr_integration::voltr_venue::VoltrVaultVenue;
+
+    use crate::common::{mint_account, MockCache, VaultFixture};
+
+    const CASES: usize = 100_000;
+
+    /// Vault fields set by a case: name, offset past the discriminator and
+    /// width in bytes.
+    const VAULT_FIELDS: [(&str, usize, usize); 28] = [
+        ("asset.total_value", 160, 8),
+        ("max_cap", 424, 8),
+        ("start_at_ts", 432, 8),
+        ("locked_profit_degradation_duration", 440, 8),
+        ("withdrawal_waiting_period", 448, 8),
+        ("disabled_operations", 456, 2),
+        ("manager_performance_fee", 504, 2),
+        ("admin_performance_fee", 506, 2),
+        ("manager_management_fee", 508, 2),
+        ("admin_management_fee", 510, 2),
+        ("redemption_fee", 512, 2),
+        ("issuance_fee", 514, 2),
+        ("protocol_performance_fee", 516, 2),
+        ("protocol_management_fee", 518, 2),
+        ("last_performance_fee_update_ts", 552, 8),
+        ("last_management_fee_update_ts", 560, 8),
+        ("accumulated_lp_manager_fees", 568, 8),
+        ("accumulated_lp_admin_fees", 576, 8),
+        ("accumulated_lp_protocol_fees", 584, 8),
+        ("dead_weight", 608, 8),
+        ("highest_asset_per_lp_decimal_bits", 616, 16),
+        ("high_water_mark.last_updated_ts", 632, 8),
+        ("last_updated_ts", 648, 8),
+        ("last_updated_locked_profit", 664, 8),
+        ("last_report", 672, 8),
+        ("asset_idle_auth_bump", 168, 1),
+        ("lp_mint_bump", 296, 1),
+        ("lp_mint_auth_bump", 297, 1),
+    ];
+
+    /// Fields outside the vault account, with their largest value.
+    const OTHER_FIELDS: [(&str, u128); 7] = [
+        ("lp_supply", u64::MAX as u128),
+        ("idle_balance", u64::MAX as u128),
+        ("asset_decimals", u8::MAX as u128),
+        ("lp_decimals", u8::MAX as u128),
+        ("now", u64::MAX as u128),
+        ("amount", u64::MAX as u128),
+        // 0 deposits, 1 redeems, 2 swaps an unrelated mint.
+        ("pair", 2),
+    ];
+
+    const FIELDS: usize = VAULT_FIELDS.len() + OTHER_FIELDS.len();
+
+    /// Fields that refuse the swap outright unless 0, left at 0 in most
+    /// cases so that the others get quoted.
+    const GATES: [&str; 3] = [
+        "start_at_ts",
+        "withdrawal_waiting_period",
+        "disabled_operations",
+    ];
+
+    /// Largest value of field `i`. Fees are kept to what `Vault::validate()`
+    /// accepts, each of the three management and performance fees to a
+    /// third of the maximum, so that cases load; the rest span their type.
+    fn max(i: usize) -> u128 {
+        let Some((name, _, width)) = VAULT_FIELDS.get(i) else {
+            return OTHER_FIELDS[i - VAULT_FIELDS.len()].1;
+        };
+        match *name {
+            "redemption_fee" | "issuance_fee" => MAX_FEE_BPS as u128,
+            name if name.ends_with("_fee") => MAX_FEE_BPS as u128 / 3,
+            _ if *width == 16 => u128::MAX,
+            _ => (1u128 << (8 * width)) - 1,
+        }
+    }
+
+    fn name(i: usize) -> &'static str {
+        match VAULT_FIELDS.get(i) {
+            Some((name, _, _)) => name,
+            None => OTHER_FIELDS[i - VAULT_FIELDS.len()].0,
+        }
+    }
+
+    /// A value up to `max`: mostly at the scales vault figures live at,
+    /// often at the edges of the range or anywhere in it.
+    fn arbitrary(rng: &mut StdRng, max: u128) -> u128 {
+        match rng.random_range(0..16) {
+            0 => 0,
+            1 => rng.random_range(1..=3).min(max),
+            2 => max - rng.random_range(0..=2).min(max),
+            3 => 10u128.pow(rng.random_range(0..=38)).min(max),
+            4 => (1u128 << rng.random_range(0..128)).min(max),
+            5 => rng.random_range(0..=max),
+            _ => rng.random_range(0..=max.min(1_000_000_000_000)),
+        }
+    }
+
+    /// Field `i` of a new case.
+    fn draw(rng: &mut StdRng, i: usize) -> u128 {
+        match name(i) {
+            // The vault's own pair, now and then an unrelated mint.
+            "pair" if rng.random_bool(0.95) => rng.random_range(0..=1),
+            name if GATES.contains(&name) && rng.random_bool(0.75) => 0,
+            _ => arbitrary(rng, max(i)),
+        }
+    }
+
+    /// Everything a case loads and quotes, set on top of `base`.
+    struct Harness {
+        base: VaultFixture,
+        lp_mint: Pubkey,
+        vault_data: Vec<u8>,
+        runtime: tokio::runtime::Runtime,
+    }
+
+    impl Harness {
+        fn new() -> Self {
+            let base = VaultFixture::default();
+            Self {
+                lp_mint: base.lp_mint(),
+                vault_data: base.vault_data(),
+                base,
+                runtime: tokio::runtime::Builder::new_current_thread()
+                    .build()
+                    .unwrap(),
+            }
+        }
+
+        /// Load and quote `case`; errors are fine, only panics count.
+        fn run(&self, case: &[u128; FIELDS]) {
+            let mut vault = self.base.vault_account();
+            vault.data.clone_from(&self.vault_data);
+            for (i, (_, offset, width)) in VAULT_FIELDS.iter().enumerate() {
+                let at = 8 + offset;
+                vault.data[at..at + width].copy_from_slice(&case[i].to_le_bytes()[..*width]);
+            }
+            let other = |name: &str| {
+                let i = OTHER_FIELDS.iter().position(|(n, _)| *n == name).unwrap();
+                case[VAULT_FIELDS.len() + i]
+            };
+
+            let mut lp_mint = self.base.lp_mint_account(other("lp_supply") as u64);
+            let mut mint = Mint::unpack(&lp_mint.data).unwrap();
+            mint.decimals = other("lp_decimals") as u8;
+            mint.pack_into_slice(&mut lp_mint.data);
+            let cache = MockCache::from_pairs([
+                (self.base.vault_key, vault.clone()),
+                (self.lp_mint, lp_mint),
+                (
+                    self.base.asset_mint,
+                    mint_account(0, other("asset_decimals") as u8),
+                ),
+                (
+                    self.base.idle_ata,
+                    token_account(
+                        &self.base.asset_mint,
+                        &Pubkey::default(),
+                        &TOKEN_PROGRAM,
+                        other("idle_balance") as u64,
+                    ),
+                ),
+            ]);
+
+            let Ok(venue) = VoltrVaultVenue::from_account(&self.base.vault_key, &vault) else {
+                return;
+            };
+            let mut venue = venue.with_config(VoltrVenueConfig {
+                clock: QuoteClock::Fixed(other("now") as u64),
+                ..Default::default()
+            });
+            if self.runtime.block_on(venue.update_state(&cache)).is_err() {
+                return;
+            }
+            let (input_mint, output_mint) = match other("pair") {
+                0 => (self.base.asset_mint, self.lp_mint),
+                1 => (self.lp_mint, self.base.asset_mint),
+                _ => (Pubkey::default(), self.lp_mint),
+            };
+            let _ = venue.quote(QuoteRequest {
+                input_mint,
+                output_mint,
+                amount: other("amount") as u64,
+                swap_type: SwapType::ExactIn,
+            });
+        }
+
+        fn panics(&self, case: &[u128; FIELDS]) -> bool {
+            panic::catch_unwind(AssertUnwindSafe(|| self.run(case))).is_err()
+        }
+
+        /// Shrink a panicking `case` one field at a time, towards zero,
+        /// until no smaller value of any field still panics.
+        fn shrink(&self, mut case: [u128; FIELDS]) -> [u128; FIELDS] {
+            loop {
+                let mut shrunk = false;
+                for i in 0..FIELDS {
+                    let value = case[i];
+                    for candidate in [0, 1, value / 2, value.saturating_sub(1)] {
+                        if candidate >= case[i] {
+                            continue;
+                        }
+                        let mut smaller = case;
+                        smaller[i] = candidate;
+                        if self.panics(&smaller) {
+                            case = smaller;
+                            shrunk = true;
+                        }
+                    }
+                }
+                if !shrunk {
+                    return case;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_quote_never_panics() {
+        let seed: u64 = rand::rng().random();
+        let mut rng = StdRng::seed_from_u64(seed);
+        let harness = Harness::new();
+
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(|_| {}));
+        let mut failure = None;
+        for _ in 0..CASES {
+            let case: [u128; FIELDS] = std::array::from_fn(|i| draw(&mut rng, i));
+            if harness.panics(&case) {
+                failure = Some(harness.shrink(case));
+                break;
+            }
+        }
+        panic::set_hook(hook);
+
+        if let Some(case) = failure {
+            let fields: Vec<String> = (0..FIELDS)
+                .filter(|&i| case[i] != 0)
+                .map(|i| format!("{} = {}", name(i), case[i]))
+                .collect();
+            // Rerun with the hook back, to show the panic itself.
+            let _ = panic::catch_unwind(AssertUnwindSafe(|| harness.run(&case)));
+            panic!(
+                "quote() panicked (seed {seed}); minimal case, other fields 0:\n  {}",
+                fields.join("\n  ")
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_semantics_version {
+    //! Quote outputs pinned per `QUOTE_SEMANTICS_VERSION`:
+    //! - the current version has a golden set, and it is the newest one,
+    //! - quoting the canonical grid reproduces that set exactly, outputs,
+    //!   `not_enough_liquidity` and errors alike, so changing any of them
+    //!   fails here until the version is bumped and the new quotes are added
+    //!   as its golden set.
+
+    use titan_integration_template::trading_venue::{QuoteRequest, SwapType, TradingVenue};
+
+    use titan_voltr_integration::clock::QuoteClock;
+    use titan_voltr_integration::config::VoltrVenueConfig;
+    use titan_voltr_integration::constants::QUOTE_SEMANTICS_VERSION;
+
+    use crate::common::VaultFixture;
+
+    const NOW: u64 = 1_700_000_000;
+
+    /// Swap inputs quoted on every vault, in both directions. 1_000 mints
+    /// exactly the dead weight into the empty vault.
+    const AMOUNTS: [u64; 6] = [1, 999, 1_000, 1_000_000, 123_456_789, 50_000_000_000];
+
+    /// What `quote()` returned for one input of the grid.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    enum Quoted {
+        Out(u64),
+        /// Marked `not_enough_liquidity`, with its output.
+        Rejected(u64),
+        Failed,
+    }
+
+    use Quoted::{Failed, Out, Rejected};
+
+    /// Quotes of the grid, one set per version, oldest first.
+    const GOLDEN: &[(u32, &[Quoted])] = &[(1, V1), (2, V2), (3, V3)];
+
+    /// For each vault of `grid()` in turn: deposits of every amount, then
+    /// redeems, then the asset and LP self-pairs of 1_000.
+    #[rustfmt::skip]
+    const V1: &[Quoted] = &[
+        // fee-free
+        Out(1), Out(999), Out(1000), Out(1_000_000), Out(123_456_789), Out(50_000_000_050),
+        Out(0), Out(998), Out(999), Out(999_999), Out(123_456_788), Out(49_999_999_950),
+        Failed, Failed,
+        // swap fees
+        Out(0), Out(998), Out(999), Out(999_000), Out(123_333_317), Out(49_947_502_674),
+        Out(0), Out(996), Out(997), Out(997_499), Out(123_148_146), Out(49_874_999_950),
+        Failed, Failed,
+        // management fee
+        Out(1), Out(1000), Out(1001), Out(1_001_647), Out(123_660_189), Out(50_082_377_245),
+        Out(0), Out(997), Out(998), Out(998_355), Out(123_253_722), Out(49_917_758_251),
+        Failed, Failed,
+        // locked profit
+        Out(0), Out(951), Out(952), Out(952_380), Out(117_577_894), Out(47_619_047_666),
+        Out(1), Out(1001), Out(1002), Out(1_002_083), Out(123_713_990), Out(50_104_166_616),
+        Failed, Failed,
+        // idle and cap limited
+        Out(1), Out(999), Out(1000), Out(1_000_000), Out(123_456_789), Rejected(0),
+        Out(0), Out(998), Out(999), Out(999_999), Out(123_456_788), Rejected(0),
+        Failed, Failed,
+        // empty
+        Rejected(0), Rejected(0), Out(0), Out(999_000), Out(123_455_789), Out(49_999_999_000),
+        Failed, Failed, Failed, Failed, Failed, Failed,
+        Failed, Failed,
+    ];
+
+    /// An exact dead-weight first deposit is rejected.
+    #[rustfmt::skip]
+    const V2: &[Quoted] = &[
+        // fee-free
+        Out(1), Out(999), Out(1000), Out(1_000_000), Out(123_456_789), Out(50_000_000_050),
+        Out(0), Out(998), Out(999), Out(999_999), Out(123_456_788), Out(49_999_999_950),
+        Failed, Failed,
+        // swap fees
+        Out(0), Out(998), Out(999), Out(999_000), Out(123_333_317), Out(49_947_502_674),
+        Out(0), Out(996), Out(997), Out(997_499), Out(123_148_146), Out(49_874_999_950),
+        Failed, Failed,
+        // management fee
+        Out(1), Out(1000), Out(1001), Out(1_001_647), Out(123_660_189), Out(50_082_377_245),
+        Out(0), Out(997), Out(998), Out(998_355), Out(123_253_722), Out(49_917_758_251),
+        Failed, Failed,
+        // locked profit
+        Out(0), Out(951), Out(952), Out(952_380), Out(117_577_894), Out(47_619_047_666),
+        Out(1), Out(1001), Out(1002), Out(1_002_083), Out(123_713_990), Out(50_104_166_616),
+        Failed, Failed,
+        // idle and cap limited
+        Out(1), Out(999), Out(1000), Out(1_000_000), Out(123_456_789), Rejected(0),
+        Out(0), Out(998), Out(999), Out(999_999), Out(123_456_788), Rejected(0),
+        Failed, Failed,
+        // empty
+        Rejected(0), Rejected(0), Rejected(0), Out(999_000), Out(123_455_789), Out(49_999_999_000),
+        Failed, Failed, Failed, Failed, Failed, Failed,
+        Failed, Failed,
+    ];
+
+    /// Self-pairs quote an output of 0.
+    #[rustfmt::skip]
+    const V3: &[Quoted] = &[
+        // fee-free
+        Out(1), Out(999), Out(1000), Out(1_000_000), Out(123_456_789), Out(50_000_000_050),
+        Out(0), Out(998), Out(999), Out(999_999), Out(123_456_788), Out(49_999_999_950),
+        Out(0), Out(0),
+        // swap fees
+        Out(0), Out(998), Out(999), Out(999_000), Out(123_333_317), Out(49_947_502_674),
+        Out(0), Out(996), Out(997), Out(997_499), Out(123_148_146), Out(49_874_999_950),
+        Out(0), Out(0),
+        // management fee
+        Out(1), Out(1000), Out(1001), Out(1_001_647), Out(123_660_189), Out(50_082_377_245),
+        Out(0), Out(997), Out(998), Out(998_355), Out(123_253_722), Out(49_917_758_251),
+        Out(0), Out(0),
+        // locked profit
+        Out(0), Out(951), Out(952), Out(952_380), Out(117_577_894), Out(47_619_047_666),
+        Out(1), Out(1001), Out(1002), Out(1_002_083), Out(123_713_990), Out(50_104_166_616),
+        Out(0), Out(0),
+        // idle and cap limited
+        Out(1), Out(999), Out(1000), Out(1_000_000), Out(123_456_789), Rejected(0),
+        Out(0), Out(998), Out(999), Out(999_999), Out(123_456_788), Rejected(0),
+        Out(0), Out(0),
+        // empty
+        Rejected(0), Rejected(0), Rejected(0), Out(999_000), Out(123_455_789), Out(49_999_999_000),
+        Failed, Failed, Failed, Failed, Failed, Failed,
+        Out(0), Out(0),
+    ];
+
+    /// The canonical vaults: fee-free, with swap fees, with a management fee
+    /// accruing, with locked profit, idle- and cap-limited, and empty, where
+    /// one base unit mints one LP and the first deposit gives up the dead
+    /// weight.
+    fn grid() -> Vec<VaultFixture> {
+        vec![
+            VaultFixture::default(),
+            VaultFixture {
+                issuance_fee: 10,
+                redemption_fee: 25,
+                ..Default::default()
+            },
+            VaultFixture {
+                manager_management_fee: 200,
+                last_management_fee_update_ts: NOW - 30 * 86_400,
+                accumulated_lp_fees: 1_000_000,
+                ..Default::default()
+            },
+            VaultFixture {
+                total_value: 1_050_000_000_000,
+                idle_balance: 1_050_000_000_000,
+                locked_profit_degradation_duration: 86_400,
+                last_updated_locked_profit: 50_000_000_000,
+                last_report: NOW - 3_600,
+                ..Default::default()
+            },
+            VaultFixture {
+                idle_balance: 10_000_000_000,
+                max_cap: 1_000_000_000_000 + 20_000_000_000,
+                ..Default::default()
+            },
+            VaultFixture {
+                asset_decimals: 9,
+                total_value: 0,
+                idle_balance: 0,
+                lp_supply: 0,
+                dead_weight: 0,
+                ..Default::default()
+            },
+        ]
+    }
+
+    fn grid_quotes() -> Vec<Quoted> {
+        let mut quotes = Vec::new();
+        for fixture in grid() {
+            let venue = fixture.synthetic_venue().with_config(VoltrVenueConfig {
+                clock: QuoteClock::Fixed(NOW),
+                ..Default::default()
+            });
+            let (asset, lp) = (fixture.asset_mint, fixture.lp_mint());
+            let inputs = AMOUNTS
+                .map(|amount| (asset, lp, amount))
+                .into_iter()
+                .chain(AMOUNTS.map(|amount| (lp, asset, amount)))
+                .chain([(asset, asset, 1_000), (lp, lp, 1_000)]);
+            for (input_mint, output_mint, amount) in inputs {
+                let quote = venue.quote(QuoteRequest {
+                    input_mint,
+                    output_mint,
+                    amount,
+                    swap_type: SwapType::ExactIn,
+                });
+                quotes.push(match quote {
+                    Ok(quote) if quote.not_enough_liquidity => Rejected(quote.expected_output),
+                    Ok(quote) => Out(quote.expected_output),
+                    Err(_) => Failed,
+                });
+            }
+        }
+        quotes
+    }
+
+    #[test]
+    fn test_golden_outputs_match_version() {
+        let versions: Vec<u32> = GOLDEN.iter().map(|(version, _)| *version).collect();
+        assert!(versions.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(
+            versions.last(),
+            Some(&QUOTE_SEMANTICS_VERSION),
+            "QUOTE_SEMANTICS_VERSION was bumped without adding its golden outputs"
+        );
+        let venue = VaultFixture::default().synthetic_venue();
+        assert_eq!(venue.semantics_version(), QUOTE_SEMANTICS_VERSION);
+
+        let (_, golden) = GOLDEN.last().unwrap();
+        assert_eq!(
+            grid_quotes(),
+            *golden,
+            "quotes changed: bump QUOTE_SEMANTICS_VERSION and add the new quotes \
+             to GOLDEN"
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_ui {
+    //! UI amount conversions are exact at the mint's decimals:
+    //! - `Display` of a `UiAmount` parses back to the same raw amount,
+    //! - extra fractional digits follow the requested `Rounding`,
+    //! - `quote_ui` labels input and output with the right mint's decimals.
+
+    use rstest::rstest;
+    use titan_integration_template::trading_venue::{QuoteRequest, SwapType, TradingVenue};
+
+    use titan_voltr_integration::ui::{parse_ui_amount, Rounding, UiAmount};
+    use titan_voltr_integration::VoltrError;
+
+    use crate::common::VaultFixture;
+
+    #[rstest]
+    #[case(0, 1_234, "1234")]
+    #[case(6, 1_234_567, "1.234567")]
+    #[case(6, 5, "0.000005")]
+    #[case(9, 1_000_000_000, "1.000000000")]
+    #[case(9, u64::MAX, "18446744073.709551615")]
+    fn test_display_round_trips(#[case] decimals: u8, #[case] raw: u64, #[case] ui: &str) {
+        let amount = UiAmount { raw, decimals };
+        assert_eq!(amount.to_string(), ui);
+        for rounding in [Rounding::Down, Rounding::Up] {
+            assert_eq!(parse_ui_amount(ui, decimals, rounding).unwrap(), raw);
+        }
+    }
+
+    #[rstest]
+    #[case(0, "2.5", 2, 3)]
+    #[case(0, "7.000", 7, 7)]
+    #[case(6, "1.0000001", 1_000_000, 1_000_001)]
+    #[case(6, ".5", 500_000, 500_000)]
+    #[case(9, "3", 3_000_000_000, 3_000_000_000)]
+    #[case(9, "0.0000000009", 0, 1)]
+    fn test_parse_rounding(
+        #[case] decimals: u8,
+        #[case] ui: &str,
+        #[case] down: u64,
+        #[case] up: u64,
+    ) {
+        assert_eq!(parse_ui_amount(ui, decimals, Rounding::Down).unwrap(), down);
+        assert_eq!(parse_ui_amount(ui, decimals, Rounding::Up).unwrap(), up);
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_and_overflowing() {
+        for bad in ["", ".", "-1", "1.2.3", "1e6", "abc"] {
+            assert!(
+                matches!(
+                    parse_ui_amount(bad, 6, Rounding::Down),
+                    Err(VoltrError::InvalidAmount)
+                ),
+                "{bad:?}"
+            );
+        }
+        assert!(matches!(
+            parse_ui_amount("18446744073709.551616", 6, Rounding::Down),
+            Err(VoltrError::MathOverflow)
+        ));
+        assert!(matches!(
+            parse_ui_amount("18446744073709.5516151", 6, Rounding::Up),
+            Err(VoltrError::MathOverflow)
+        ));
+    }
+
+    #[rstest]
+    #[case(0)]
+    #[case(6)]
+    #[case(9)]
+    fn test_quote_ui_uses_mint_decimals(#[case] decimals: u8) {
+        let fixture = VaultFixture {
+            asset_decimals: decimals,
+            issuance_fee: 25,
+            ..Default::default()
+        };
+        let venue = fixture.synthetic_venue();
+        let lp_mint = fixture.lp_mint();
+        let amount = venue.ui_to_asset("12.5", Rounding::Down).unwrap();
+
+        let request = QuoteRequest {
+            input_mint: fixture.asset_mint,
+            output_mint: lp_mint,
+            amount,
+            swap_type: SwapType::ExactIn,
+        };
+        let ui = venue.quote_ui(&request).unwrap();
+        assert_eq!(
+            ui.quote.expected_output,
+            venue.quote(request).unwrap().expected_output
+        );
+        assert_eq!(ui.input, venue.asset_to_ui(amount));
+        assert_eq!(ui.output, venue.lp_to_ui(ui.quote.expected_output));
+        assert_eq!(ui.input.decimals, venue.asset_mint_decimals);
+        assert_eq!(ui.output.decimals, venue.lp_mint_decimals);
+        assert_eq!(
+            venue.ui_to_lp(&ui.output.to_string(), Rounding::Down).unwrap(),
+            ui.quote.expected_output
+        );
+
+        let redeem = QuoteRequest {
+            input_mint: lp_mint,
+            output_mint: fixture.asset_mint,
+            amount: ui.quote.expected_output,
+            swap_type: SwapType::ExactIn,
+        };
+        let ui = venue.quote_ui(&redeem).unwrap();
+        assert_eq!(ui.input.decimals, venue.lp_mint_decimals);
+        assert_eq!(ui.output.decimals, venue.asset_mint_decimals);
+    }
+}
+
+#[cfg(test)]
+mod test_zero_sentinels {
+    //! Pinned behavior of zero timestamps and durations:
+    //! - management fees accrue only with a stamped update, a configured fee,
+    //!   a funded vault and time elapsed since the update,
+    //! - locked profit is zero without a report or a degradation window and
+    //!   fully locked when the clock is behind the report,
+    //! - the high water mark, set or not, never moves a quote.
+
+    use rstest::rstest;
+    use titan_integration_template::trading_venue::{QuoteRequest, SwapType};
+
+    use titan_voltr_integration::core::{
+        calc_locked_profit, quote_snapshot, SwapDirection, VaultSnapshot,
+    };
+
+    use crate::common::VaultFixture;
+
+    const NOW: u64 = 1_700_000_000;
+    const TVL: u64 = 1_000_000_000_000;
+
+    #[rstest]
+    #[case::never_cranked_no_fee(0, 0, TVL, false)]
+    #[case::never_cranked_with_fee(0, 200, TVL, false)]
+    #[case::cranked_no_fee(NOW - 86_400, 0, TVL, false)]
+    #[case::cranked_with_fee(NOW - 86_400, 200, TVL, true)]
+    #[case::cranked_this_second(NOW, 200, TVL, false)]
+    #[case::clock_behind_update(NOW + 60, 200, TVL, false)]
+    #[case::never_cranked_empty(0, 200, 0, false)]
+    #[case::cranked_empty(NOW - 86_400, 200, 0, false)]
+    fn test_management_fee_sentinels(
+        #[case] last_management_fee_update_ts: u64,
+        #[case] management_fee_bps: u16,
+        #[case] total_value: u64,
+        #[case] accrues: bool,
+    ) {
+        let without_fee = VaultSnapshot {
+            total_value,
+            lp_supply: TVL,
+            dead_weight: 1_000,
+            idle_balance: total_value,
+            asset_decimals: 6,
+            lp_decimals: 9,
+            ..Default::default()
+        };
+        let with_fee = VaultSnapshot {
+            management_fee_bps,
+            last_management_fee_update_ts,
+            ..without_fee
+        };
+
+        for direction in [SwapDirection::Deposit, SwapDirection::Redeem] {
+            let baseline = quote_snapshot(&without_fee, direction, 1_000_000_000, NOW);
+            let quoted = quote_snapshot(&with_fee, direction, 1_000_000_000, NOW);
+            assert_eq!(quoted != baseline, accrues, "{direction:?}: {quoted:?} vs {baseline:?}");
+        }
+    }
+
+    #[rstest]
+    #[case::never_reported(1_000, 0, 100, 0)]
+    #[case::never_reported_no_window(1_000, 0, 0, 0)]
+    #[case::no_window(1_000, NOW, 0, 0)]
+    #[case::just_reported(1_000, NOW, 100, 1_000)]
+    #[case::half_degraded(1_000, NOW - 50, 100, 500)]
+    #[case::window_ends_now(1_000, NOW - 100, 100, 0)]
+    #[case::window_passed(1_000, NOW - 101, 100, 0)]
+    #[case::clock_behind_report(1_000, NOW + 60, 100, 1_000)]
+    #[case::nothing_reported(0, NOW, 100, 0)]
+    fn test_locked_profit_sentinels(
+        #[case] last_updated_locked_profit: u64,
+        #[case] last_report: u64,
+        #[case] degradation_duration: u64,
+        #[case] locked: u64,
+    ) {
+        assert_eq!(
+            calc_locked_profit(last_updated_locked_profit, last_report, degradation_duration, NOW)
+                .unwrap(),
+            locked
+        );
+    }
+
+    #[test]
+    fn test_high_water_mark_does_not_affect_quotes() {
+        let fixture = VaultFixture {
+            manager_performance_fee: 1_000,
+            manager_management_fee: 200,
+            last_management_fee_update_ts: NOW - 86_400,
+            ..Default::default()
+        };
+        let unset = fixture.synthetic_venue();
+        let mut set = fixture.synthetic_venue();
+        set.vault_state.high_water_mark.highest_asset_per_lp_decimal_bits = 1 << 47;
+        set.vault_state.high_water_mark.last_updated_ts = NOW - 3_600;
+
+        for (input_mint, output_mint) in [
+            (fixture.asset_mint, fixture.lp_mint()),
+            (fixture.lp_mint(), fixture.asset_mint),
+        ] {
+            let request = QuoteRequest {
+                input_mint,
+                output_mint,
+                amount: 1_000_000_000,
+                swap_type: SwapType::ExactIn,
+            };
+            assert_eq!(
+                set.quote_at(&request, NOW).unwrap().expected_output,
+                unset.quote_at(&request, NOW).unwrap().expected_output
+            );
+        }
+    }
+}
@@ -0,0 +1,2516 @@
+mod common;
+
+#[cfg(test)]
+mod test_dead_weight {
+    //! The first deposit into an empty vault, around the dead weight it
+    //! gives up, on a 9-decimal asset where one base unit mints one LP:
+    //! - minting `DEAD_WEIGHT - 1` or exactly `DEAD_WEIGHT` LP leaves the
+    //!   depositor nothing and is rejected, `DEAD_WEIGHT + 1` pays 1 LP,
+    //! - the deposit lower bound is that smallest paying amount,
+    //! - in LiteSVM on a fresh vault, the program fails the first, mints 0
+    //!   LP for the second and 1 LP for the third, so the rejections only
+    //!   turn away deposits that would pay nothing.
+
+    use solana_program_pack::Pack;
+    use solana_sdk::signer::Signer;
+    use solana_sdk::transaction::Transaction;
+    use spl_token::state::Account as TokenAccount;
+    use titan_integration_template::trading_venue::{QuoteRequest, SwapType, TradingVenue};
+
+    use titan_voltr_integration::constants::DEAD_WEIGHT;
+    use titan_voltr_integration::core::LiquidityRejection;
+
+    use crate::common::{program_user, VaultFixture};
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn fixture() -> VaultFixture {
+        VaultFixture {
+            asset_decimals: 9,
+            total_value: 0,
+            idle_balance: 0,
+            lp_supply: 0,
+            dead_weight: 0,
+            last_updated_ts: NOW,
+            ..Default::default()
+        }
+        .for_program()
+    }
+
+    fn deposit(fixture: &VaultFixture, amount: u64) -> QuoteRequest {
+        QuoteRequest {
+            input_mint: fixture.asset_mint,
+            output_mint: fixture.lp_mint(),
+            amount,
+            swap_type: SwapType::ExactIn,
+        }
+    }
+
+    /// LP the program mints for a first deposit of `amount`, or `None` when
+    /// it fails.
+    fn program_deposit(fixture: &VaultFixture, amount: u64) -> Option<u64> {
+        let venue = fixture.synthetic_venue();
+        let mut svm = fixture.program_svm(NOW);
+        let user = program_user(
+            &mut svm,
+            &[(fixture.asset_mint, amount), (fixture.lp_mint(), 0)],
+        );
+        let instruction = venue
+            .generate_swap_instruction(deposit(fixture, amount), user.pubkey())
+            .unwrap();
+        let tx = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&user.pubkey()),
+            &[&user],
+            svm.latest_blockhash(),
+        );
+        svm.send_transaction(tx).ok()?;
+        let ata = spl_associated_token_account::get_associated_token_address(
+            &user.pubkey(),
+            &fixture.lp_mint(),
+        );
+        Some(
+            TokenAccount::unpack(&svm.get_account(&ata).unwrap().data)
+                .unwrap()
+                .amount,
+        )
+    }
+
+    #[test]
+    fn test_boundary_quotes() {
+        let fixture = fixture();
+        let venue = fixture.synthetic_venue();
+        for (amount, output) in [
+            (DEAD_WEIGHT - 1, None),
+            (DEAD_WEIGHT, None),
+            (DEAD_WEIGHT + 1, Some(1)),
+        ] {
+            let (quote, rejection) = venue
+                .quote_with_rejection(&deposit(&fixture, amount))
+                .unwrap();
+            match output {
+                Some(output) => {
+                    assert_eq!(quote.expected_output, output, "{amount}");
+                    assert!(!quote.not_enough_liquidity, "{amount}");
+                    assert_eq!(rejection, None, "{amount}");
+                }
+                None => {
+                    assert_eq!(quote.expected_output, 0, "{amount}");
+                    assert!(quote.not_enough_liquidity, "{amount}");
+                    assert_eq!(rejection, Some(LiquidityRejection::BelowDeadWeight));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_deposit_lower_bound() {
+        let fixture = fixture();
+        let venue = fixture.synthetic_venue();
+        let (lower, _) = venue.bounds(0, 1).unwrap();
+        assert_eq!(lower, DEAD_WEIGHT + 1);
+    }
+
+    #[test]
+    fn test_program_at_boundary() {
+        let fixture = fixture();
+        assert_eq!(program_deposit(&fixture, DEAD_WEIGHT - 1), None);
+        assert_eq!(program_deposit(&fixture, DEAD_WEIGHT), Some(0));
+        assert_eq!(program_deposit(&fixture, DEAD_WEIGHT + 1), Some(1));
+    }
+}
+
+#[cfg(test)]
+mod test_differential {
+    //! The venue's quotes against the embedded program on synthetic vaults,
+    //! at the edges where the off-chain formulas could drift from it:
+    //! - the deposit cap edge is the same with a year of management fee
+    //!   unaccrued and a fresh locked profit report, and the program agrees
+    //!   on both sides of it,
+    //! - deposits mint exactly the quoted LP across issuance fees, at amounts
+    //!   where the fee lands on or next to a whole unit,
+    //! - the largest redeem the idle balance can pay executes for exactly
+    //!   its quote, and one LP more is refused by both.
+
+    use solana_pubkey::Pubkey;
+    use titan_integration_template::trading_venue::{QuoteRequest, SwapType, TradingVenue};
+
+    use titan_voltr_integration::caches::StaticCache;
+    use titan_voltr_integration::clock::QuoteClock;
+    use titan_voltr_integration::config::VoltrVenueConfig;
+    use titan_voltr_integration::constants::MAX_FEE_BPS;
+    use titan_voltr_integration::core::calc_withdraw_asset_to_redeem;
+    use titan_voltr_integration::local_sim::{self, SwapSimulation};
+    use titan_voltr_integration::voltr_venue::VoltrVaultVenue;
+
+    use crate::common::VaultFixture;
+
+    /// When the swaps execute.
+    const NOW: u64 = 1_700_000_000;
+
+    /// A venue over `fixture` quoting at `NOW`.
+    async fn venue(fixture: &VaultFixture, config: VoltrVenueConfig) -> VoltrVaultVenue {
+        fixture.venue().await.with_config(VoltrVenueConfig {
+            clock: QuoteClock::Fixed(NOW),
+            ..config
+        })
+    }
+
+    fn request(input_mint: Pubkey, output_mint: Pubkey, amount: u64) -> QuoteRequest {
+        QuoteRequest {
+            input_mint,
+            output_mint,
+            amount,
+            swap_type: SwapType::ExactIn,
+        }
+    }
+
+    /// `count` amounts from `base` up whose issuance fee at `fee_bps` is
+    /// within one of a whole unit, where rounding the fee on its own would
+    /// change the LP minted; each with its neighbours.
+    fn fee_boundary_amounts(base: u64, fee_bps: u16, count: usize) -> Vec<u64> {
+        (base..)
+            .filter(|amount| {
+                matches!(
+                    (*amount as u128 * fee_bps as u128) % MAX_FEE_BPS as u128,
+                    0 | 1 | 9_999
+                )
+            })
+            .take(count)
+            .flat_map(|amount| [amount - 1, amount, amount + 1])
+            .collect()
+    }
+
+    /// Execute each of `requests` on its own against the program at `NOW`,
+    /// for a user holding exactly the input amount.
+    async fn simulate(
+        fixture: &VaultFixture,
+        venue: &VoltrVaultVenue,
+        requests: &[QuoteRequest],
+    ) -> Vec<SwapSimulation> {
+        let cache = StaticCache::from_pairs(&fixture.program_accounts());
+        let mut svm = fixture.program_svm(NOW);
+        let user = Pubkey::new_unique();
+        let mut sims = Vec::with_capacity(requests.len());
+        for request in requests {
+            let sim = local_sim::simulate_swap(
+                &mut svm,
+                venue,
+                &cache,
+                request.clone(),
+                &user,
+                request.amount,
+            )
+            .await
+            .unwrap();
+            sims.push(sim);
+        }
+        sims
+    }
+
+    /// Assert the program rejected `sim` with the error named `error_code`.
+    fn assert_rejected(sim: &SwapSimulation, error_code: &str) {
+        assert!(sim.error.is_some(), "{:#?}", sim.logs);
+        assert!(
+            sim.logs.iter().any(|log| log.contains(error_code)),
+            "{:#?}",
+            sim.logs
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cap_edge_with_pending_fees() {
+        let capped = VaultFixture {
+            max_cap: 1_000_000_000_000 + 5_000_000,
+            ..VaultFixture::default().for_program()
+        };
+        // A year of 10% management fee unaccrued and a fresh locked profit
+        // report worth 10% of the vault.
+        let pending = VaultFixture {
+            manager_management_fee: 1_000,
+            last_management_fee_update_ts: NOW - 365 * 86_400,
+            last_updated_ts: NOW - 365 * 86_400,
+            locked_profit_degradation_duration: 86_400,
+            last_updated_locked_profit: 100_000_000_000,
+            last_report: NOW,
+            ..capped.clone()
+        };
+
+        let mut minted = Vec::new();
+        for fixture in [capped, pending] {
+            let venue = venue(&fixture, VoltrVenueConfig::default()).await;
+            let upper = venue.bounds(0, 1).unwrap().1;
+            assert_eq!(upper, 5_000_000);
+
+            let requests = [upper, upper + 1]
+                .map(|amount| request(fixture.asset_mint, fixture.lp_mint(), amount));
+            let sims = simulate(&fixture, &venue, &requests).await;
+
+            let quote = venue.quote(requests[0].clone()).unwrap();
+            assert!(!quote.not_enough_liquidity);
+            assert_eq!(sims[0].error, None, "{:#?}", sims[0].logs);
+            assert_eq!(sims[0].post.output, quote.expected_output);
+            minted.push(quote.expected_output);
+
+            let past = venue.quote(requests[1].clone()).unwrap();
+            assert!(past.not_enough_liquidity);
+            assert_rejected(&sims[1], "MaxCapExceeded");
+        }
+        // The pending fees do move the price, just not the cap.
+        assert_ne!(minted[0], minted[1]);
+    }
+
+    #[tokio::test]
+    async fn test_issuance_fee_rounding() {
+        // (amount, issuance fee bps, LP minted) pinned offline in
+        // `test_core::test_deposit_issuance_fee_floors_once`.
+        let pinned = [
+            (9_999, 1, 9_521),
+            (7_027, 37, 6_667),
+            (10_000, 500, 9_047),
+            (10_001, 9_999, 0),
+            (1_000_000_007, 37, 948_853_805),
+        ];
+
+        for fee in [1, 37, 500, 9_999] {
+            // 1_000_000_000_000 LP with the dead weight, over 1.05 asset each.
+            let fixture = VaultFixture {
+                lp_supply: 1_000_000_000_000 - 1_000,
+                dead_weight: 1_000,
+                total_value: 1_050_000_000_000,
+                idle_balance: 1_050_000_000_000,
+                issuance_fee: fee,
+                ..VaultFixture::default().for_program()
+            };
+            let venue = venue(&fixture, VoltrVenueConfig::default()).await;
+
+            let mut amounts: Vec<u64> = [1_000, 1_000_000, 1_000_000_000]
+                .into_iter()
+                .flat_map(|base| fee_boundary_amounts(base, fee, 8))
+                .collect();
+            amounts.extend(
+                pinned
+                    .iter()
+                    .filter(|(_, pinned_fee, _)| *pinned_fee == fee)
+                    .map(|(amount, _, _)| *amount),
+            );
+            let requests: Vec<_> = amounts
+                .iter()
+                .map(|amount| request(fixture.asset_mint, fixture.lp_mint(), *amount))
+                .collect();
+            let sims = simulate(&fixture, &venue, &requests).await;
+
+            for (request, sim) in requests.into_iter().zip(sims) {
+                let amount = request.amount;
+                let quote = venue.quote(request).unwrap();
+                // At 9_999 bps the small deposits mint nothing, and the
+                // program takes them all the same.
+                assert_eq!(sim.error, None, "{amount} at {fee} bps: {:#?}", sim.logs);
+                assert_eq!(
+                    sim.post.output, quote.expected_output,
+                    "{amount} at {fee} bps"
+                );
+                if let Some(&(_, _, minted)) =
+                    pinned.iter().find(|pin| (pin.0, pin.1) == (amount, fee))
+                {
+                    assert_eq!(sim.post.output, minted, "{amount} at {fee} bps");
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_redeem_idle_limit() {
+        // Less idle than the unlocked value, with management fee pending
+        // and profit still locking.
+        let fixture = VaultFixture {
+            idle_balance: 400_000_000_000,
+            redemption_fee: 100,
+            manager_management_fee: 200,
+            last_management_fee_update_ts: NOW - 30 * 86_400,
+            last_updated_ts: NOW - 30 * 86_400,
+            locked_profit_degradation_duration: 86_400,
+            last_updated_locked_profit: 50_000_000_000,
+            last_report: NOW - 3_600,
+            ..VaultFixture::default().for_program()
+        };
+        let venue = venue(&fixture, VoltrVenueConfig::default()).await;
+
+        // Invert the payout formula for the largest LP amount paying at most
+        // the idle balance, then settle its rounding against the payout.
+        let snapshot = venue.vault_snapshot().unwrap();
+        let idle = fixture.idle_balance;
+        let unlocked = snapshot.unlocked_value(NOW).unwrap();
+        assert!(unlocked > idle);
+        let supply = snapshot.total_lp_supply_incl_fees().unwrap()
+            + snapshot.pending_management_fee_lp(NOW).unwrap();
+        let fee_bps = snapshot.redemption_fee_bps;
+        let payout =
+            |lp: u64| calc_withdraw_asset_to_redeem(lp, supply, unlocked, fee_bps).unwrap();
+        let mut edge = ((idle as u128 + 1) * supply as u128 * MAX_FEE_BPS as u128
+            / (unlocked as u128 * (MAX_FEE_BPS - fee_bps) as u128)) as u64;
+        while payout(edge) > idle {
+            edge -= 1;
+        }
+        while payout(edge + 1) <= idle {
+            edge += 1;
+        }
+
+        let requests =
+            [edge, edge + 1].map(|amount| request(fixture.lp_mint(), fixture.asset_mint, amount));
+        let sims = simulate(&fixture, &venue, &requests).await;
+
+        let quote = venue.quote(requests[0].clone()).unwrap();
+        assert!(!quote.not_enough_liquidity);
+        assert_eq!(sims[0].error, None, "{:#?}", sims[0].logs);
+        assert_eq!(sims[0].post.output, quote.expected_output);
+        assert!(quote.expected_output <= idle);
+        assert_eq!(venue.bounds(1, 0).unwrap().1, edge);
+
+        let past = venue.quote(requests[1].clone()).unwrap();
+        assert!(past.not_enough_liquidity);
+        // The idle account cannot cover the transfer out.
+        assert_rejected(&sims[1], "insufficient funds");
+    }
+}
+
+#[cfg(test)]
+mod test_fee_destination {
+    //! Where the fees of a swap end up, checked against the program in
+    //! LiteSVM on a synthetic vault:
+    //! - a deposit adds its whole input, issuance fee included, to the
+    //!   vault's value and records no fee LP for it,
+    //! - a redeem takes only its payout out of the value, leaving the
+    //!   redemption fee to the remaining LPs,
+    //! - the management fee a swap settles is recorded as fee LP for the
+    //!   manager, exactly the LP the breakdown attributes to it,
+    //! - zero-amount, rejected and fee-free swaps carry no fees.
+
+    use litesvm::LiteSVM;
+    use solana_program_pack::Pack;
+    use solana_pubkey::Pubkey;
+    use solana_sdk::signer::Signer;
+    use solana_sdk::transaction::Transaction;
+    use spl_token::state::{Account as TokenAccount, Mint};
+    use titan_integration_template::trading_venue::{QuoteRequest, SwapType, TradingVenue};
+
+    use titan_voltr_integration::clock::QuoteClock;
+    use titan_voltr_integration::config::VoltrVenueConfig;
+    use titan_voltr_integration::fees::{FeeComponent, FeeDestination, FeeKind};
+    use titan_voltr_integration::state::Vault;
+    use titan_voltr_integration::voltr_venue::VoltrVaultVenue;
+
+    use crate::common::{program_user, VaultFixture};
+
+    const T0: u64 = 1_700_000_000;
+    /// When the swaps execute: 30 days of management fee after `T0`.
+    const T1: u64 = T0 + 30 * 86_400;
+
+    fn fixture() -> VaultFixture {
+        VaultFixture {
+            manager_management_fee: 200,
+            issuance_fee: 100,
+            redemption_fee: 100,
+            last_management_fee_update_ts: T0,
+            last_updated_ts: T0,
+            ..Default::default()
+        }
+        .for_program()
+    }
+
+    /// The vault account, LP supply and user balances at one point.
+    struct Balances {
+        vault: Vault,
+        lp_supply: u64,
+        user_asset: u64,
+        user_lp: u64,
+    }
+
+    fn balances(svm: &LiteSVM, fixture: &VaultFixture, user: &Pubkey) -> Balances {
+        let token_balance = |mint: &Pubkey| {
+            let ata = spl_associated_token_account::get_associated_token_address(user, mint);
+            TokenAccount::unpack(&svm.get_account(&ata).unwrap().data)
+                .unwrap()
+                .amount
+        };
+        Balances {
+            vault: Vault::load(&svm.get_account(&fixture.vault_key).unwrap().data).unwrap(),
+            lp_supply: Mint::unpack(&svm.get_account(&fixture.lp_mint()).unwrap().data)
+                .unwrap()
+                .supply,
+            user_asset: token_balance(&fixture.asset_mint),
+            user_lp: token_balance(&fixture.lp_mint()),
+        }
+    }
+
+    /// Execute `request` for a fresh user holding `lp` LP, returning the
+    /// balances before and after.
+    fn execute(
+        svm: &mut LiteSVM,
+        fixture: &VaultFixture,
+        venue: &VoltrVaultVenue,
+        request: &QuoteRequest,
+        lp: u64,
+    ) -> (Balances, Balances) {
+        let user = program_user(
+            svm,
+            &[
+                (fixture.asset_mint, fixture.total_value),
+                (fixture.lp_mint(), lp),
+            ],
+        );
+
+        let pre = balances(svm, fixture, &user.pubkey());
+        let instruction = venue
+            .generate_swap_instruction(request.clone(), user.pubkey())
+            .unwrap();
+        let tx = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&user.pubkey()),
+            &[&user],
+            svm.latest_blockhash(),
+        );
+        svm.send_transaction(tx).unwrap();
+        (pre, balances(svm, fixture, &user.pubkey()))
+    }
+
+    async fn venue(fixture: &VaultFixture) -> VoltrVaultVenue {
+        fixture.venue().await.with_config(VoltrVenueConfig {
+            clock: QuoteClock::Fixed(T1),
+            ..Default::default()
+        })
+    }
+
+    fn request(input_mint: Pubkey, output_mint: Pubkey, amount: u64) -> QuoteRequest {
+        QuoteRequest {
+            input_mint,
+            output_mint,
+            amount,
+            swap_type: SwapType::ExactIn,
+        }
+    }
+
+    /// The management fee component of `fees`, checking it is the manager's
+    /// only.
+    fn management_lp(fees: &[FeeComponent]) -> u64 {
+        let management: Vec<&FeeComponent> = fees
+            .iter()
+            .filter(|fee| fee.kind == FeeKind::Management)
+            .collect();
+        assert_eq!(management.len(), 1);
+        assert_eq!(management[0].destination, FeeDestination::Manager);
+        assert!(management[0].lp > 0);
+        management[0].lp
+    }
+
+    #[tokio::test]
+    async fn test_deposit_fee_stays_in_vault() {
+        let fixture = fixture();
+        let venue = venue(&fixture).await;
+        let amount = 1_000_000_000;
+        let deposit = request(fixture.asset_mint, fixture.lp_mint(), amount);
+
+        let fees = venue.swap_fees(&deposit, T1).unwrap();
+        assert_eq!(
+            fees[0],
+            FeeComponent {
+                kind: FeeKind::Issuance,
+                destination: FeeDestination::RemainingLps,
+                asset_value: amount / 100,
+                lp: 0,
+            }
+        );
+        let detailed = venue
+            .quote_detailed(&deposit, &Pubkey::new_unique(), None)
+            .await
+            .unwrap();
+        assert_eq!(detailed.fees, fees);
+
+        let mut svm = fixture.program_svm(T1);
+        let (pre, post) = execute(&mut svm, &fixture, &venue, &deposit, 0);
+        let minted = post.user_lp - pre.user_lp;
+        assert_eq!(minted, detailed.quote.expected_output);
+        assert_eq!(pre.user_asset - post.user_asset, amount);
+
+        // The whole input, fee included, became vault value, and only the
+        // depositor's LP was minted.
+        assert_eq!(
+            post.vault.asset.total_value - pre.vault.asset.total_value,
+            amount
+        );
+        assert_eq!(post.lp_supply - pre.lp_supply, minted);
+
+        // Fee LP was recorded for the settled management fee only.
+        let (pre_fees, post_fees) = (&pre.vault.fee_state, &post.vault.fee_state);
+        assert_eq!(
+            post_fees.accumulated_lp_manager_fees - pre_fees.accumulated_lp_manager_fees,
+            management_lp(&fees)
+        );
+        assert_eq!(
+            post_fees.accumulated_lp_admin_fees,
+            pre_fees.accumulated_lp_admin_fees
+        );
+        assert_eq!(
+            post_fees.accumulated_lp_protocol_fees,
+            pre_fees.accumulated_lp_protocol_fees
+        );
+    }
+
+    #[tokio::test]
+    async fn test_redemption_fee_stays_with_remaining_lps() {
+        let fixture = fixture();
+        let venue = venue(&fixture).await;
+        let amount = 1_000_000_000;
+        let redeem = request(fixture.lp_mint(), fixture.asset_mint, amount);
+
+        let fees = venue.swap_fees(&redeem, T1).unwrap();
+        assert_eq!(fees[0].kind, FeeKind::Redemption);
+        assert_eq!(fees[0].destination, FeeDestination::RemainingLps);
+        assert_eq!(fees[0].lp, 0);
+        let payout = venue.quote(redeem.clone()).unwrap().expected_output;
+        // 1% of the gross value, up to rounding.
+        let gross = fees[0].asset_value + payout;
+        assert!((gross / 100).abs_diff(fees[0].asset_value) <= 1);
+
+        let mut svm = fixture.program_svm(T1);
+        let (pre, post) = execute(&mut svm, &fixture, &venue, &redeem, amount);
+        assert_eq!(pre.user_lp - post.user_lp, amount);
+        assert_eq!(post.user_asset - pre.user_asset, payout);
+
+        // Only the payout left the vault; the fee is still in its value.
+        assert_eq!(
+            pre.vault.asset.total_value - post.vault.asset.total_value,
+            payout
+        );
+        assert_eq!(pre.lp_supply - post.lp_supply, amount);
+        assert_eq!(
+            post.vault.fee_state.accumulated_lp_manager_fees
+                - pre.vault.fee_state.accumulated_lp_manager_fees,
+            management_lp(&fees)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_no_fees_without_a_swap() {
+        let fixture = fixture();
+        let venue = venue(&fixture).await;
+        let (asset, lp) = (fixture.asset_mint, fixture.lp_mint());
+        assert!(venue
+            .swap_fees(&request(asset, lp, 0), T1)
+            .unwrap()
+            .is_empty());
+        let too_large = request(lp, asset, 2 * fixture.idle_balance);
+        assert!(venue.quote(too_large.clone()).unwrap().not_enough_liquidity);
+        assert!(venue.swap_fees(&too_large, T1).unwrap().is_empty());
+
+        let fee_free = VaultFixture::default();
+        let venue = fee_free.synthetic_venue();
+        let (asset, lp) = (fee_free.asset_mint, fee_free.lp_mint());
+        for request in [request(asset, lp, 1_000_000), request(lp, asset, 1_000_000)] {
+            assert!(venue.swap_fees(&request, T1).unwrap().is_empty());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_fee_locked_profit {
+    //! Redeems on a vault with both a large pending management fee and
+    //! profit halfway through degrading, against the program in LiteSVM:
+    //! - the program accrues the fee, diluting the LP supply against the
+    //!   total value, before pricing the redeem against the unlocked value,
+    //!   and quotes match it exactly at every amount,
+    //! - either effect alone also matches, and together they pay less than
+    //!   either alone, so neither hides the other.
+
+    use litesvm::LiteSVM;
+    use solana_program_pack::Pack;
+    use solana_pubkey::Pubkey;
+    use solana_sdk::signer::Signer;
+    use solana_sdk::transaction::Transaction;
+    use spl_token::state::Account as TokenAccount;
+    use titan_integration_template::trading_venue::{QuoteRequest, SwapType, TradingVenue};
+
+    use titan_voltr_integration::clock::QuoteClock;
+    use titan_voltr_integration::config::VoltrVenueConfig;
+
+    use crate::common::{program_user, VaultFixture};
+
+    const NOW: u64 = 1_700_000_000;
+
+    const AMOUNTS: [u64; 5] = [1, 999, 1_000_000, 50_000_000_000, 400_000_000_000];
+
+    /// 5% a year of management fee unpaid for 180 days, and 100k of profit
+    /// reported 12 hours into a 24 hour degradation.
+    fn fixture(management_fee: bool, locked_profit: bool) -> VaultFixture {
+        let mut fixture = VaultFixture {
+            total_value: 1_100_000_000_000,
+            idle_balance: 1_100_000_000_000,
+            last_updated_ts: NOW,
+            ..Default::default()
+        };
+        if management_fee {
+            fixture.manager_management_fee = 500;
+            fixture.last_management_fee_update_ts = NOW - 180 * 86_400;
+        }
+        if locked_profit {
+            fixture.locked_profit_degradation_duration = 86_400;
+            fixture.last_updated_locked_profit = 100_000_000_000;
+            fixture.last_report = NOW - 43_200;
+        }
+        fixture.for_program()
+    }
+
+    fn asset_balance(svm: &LiteSVM, fixture: &VaultFixture, user: &Pubkey) -> u64 {
+        let ata =
+            spl_associated_token_account::get_associated_token_address(user, &fixture.asset_mint);
+        TokenAccount::unpack(&svm.get_account(&ata).unwrap().data)
+            .unwrap()
+            .amount
+    }
+
+    /// Quote and execute a redeem of each of `AMOUNTS`, each on a fresh
+    /// copy of the vault, returning `(quoted, paid)` pairs.
+    async fn quoted_and_paid(fixture: &VaultFixture) -> Vec<(u64, u64)> {
+        let venue = fixture.venue().await.with_config(VoltrVenueConfig {
+            clock: QuoteClock::Fixed(NOW),
+            ..Default::default()
+        });
+        let mut results = Vec::new();
+        for amount in AMOUNTS {
+            let request = QuoteRequest {
+                input_mint: fixture.lp_mint(),
+                output_mint: fixture.asset_mint,
+                amount,
+                swap_type: SwapType::ExactIn,
+            };
+            let quoted = venue.quote(request.clone()).unwrap().expected_output;
+
+            let mut svm = fixture.program_svm(NOW);
+            let user = program_user(
+                &mut svm,
+                &[(fixture.asset_mint, 0), (fixture.lp_mint(), amount)],
+            );
+            let instruction = venue
+                .generate_swap_instruction(request, user.pubkey())
+                .unwrap();
+            let tx = Transaction::new_signed_with_payer(
+                &[instruction],
+                Some(&user.pubkey()),
+                &[&user],
+                svm.latest_blockhash(),
+            );
+            svm.send_transaction(tx).unwrap();
+            results.push((quoted, asset_balance(&svm, fixture, &user.pubkey())));
+        }
+        results
+    }
+
+    #[tokio::test]
+    async fn test_fee_and_locked_profit_redeems_match_program() {
+        let mut paid_by_case = Vec::new();
+        for (management_fee, locked_profit) in [(true, true), (true, false), (false, true)] {
+            let fixture = fixture(management_fee, locked_profit);
+            let results = quoted_and_paid(&fixture).await;
+            for (quoted, paid) in &results {
+                assert_eq!(
+                    quoted, paid,
+                    "management fee {management_fee}, locked profit {locked_profit}"
+                );
+            }
+            paid_by_case.push(results);
+        }
+
+        let [both, fee_only, locked_only] = &paid_by_case[..] else {
+            unreachable!()
+        };
+        // Past the dust amounts, both effects visibly reduce the payout.
+        for i in 1..AMOUNTS.len() {
+            assert!(both[i].1 < fee_only[i].1 && both[i].1 < locked_only[i].1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_instructions {
+    //! Offline instruction-assembly tests for synthetic vaults:
+    //! - full instruction lists and v0 transactions,
+    //! - heap frame requests ahead of redeems only, validated, and not
+    //!   needed by the program to redeem with the output ATA created,
+    //! - compute unit limits with half again the median measured swap to
+    //!   spare,
+    //! - validated instruction generation against the current quote,
+    //! - zero amounts and redeems beyond instant capacity refused unless the
+    //!   latter are explicitly allowed,
+    //! - golden account layouts of both swap instructions,
+    //! - multi-user batches sharing one PDA derivation,
+    //! - lookup-table keys cached per refresh and diffed against a table,
+    //! - program dependencies and lookup-table keys in a fixed order without
+    //!   duplicates, signers or user token accounts.
+
+    use solana_account::Account;
+    use solana_program::native_token::LAMPORTS_PER_SOL;
+    use solana_pubkey::Pubkey;
+    use solana_sdk::compute_budget::ComputeBudgetInstruction;
+    use solana_sdk::hash::Hash;
+    use solana_sdk::message::{AddressLookupTableAccount, VersionedMessage};
+    use solana_sdk::signature::Keypair;
+    use solana_sdk::signer::Signer;
+    use solana_sdk::transaction::Transaction;
+    use titan_integration_template::trading_venue::{
+        AddressLookupTableTrait, QuoteRequest, SwapType, TradingVenue,
+    };
+
+    use titan_voltr_integration::config::VoltrVenueConfig;
+    use titan_voltr_integration::constants::{
+        ADDRESS_LOOKUP_TABLE_PROGRAM, ATA_PROGRAM, LOOKUP_TABLE_META_SIZE, MAX_HEAP_FRAME_BYTES,
+        MIN_HEAP_FRAME_BYTES, NATIVE_MINT, PROTOCOL_SEED, REDEEM_COMPUTE_UNIT_LIMIT,
+        TOKEN_22_PROGRAM, TOKEN_PROGRAM, VAULT_ASSET_IDLE_AUTH_SEED, VAULT_LP_MINT_AUTH_SEED,
+        VOLTR_VAULT_PROGRAM,
+    };
+    use titan_voltr_integration::synthetic::SyntheticVaultParams;
+    use titan_voltr_integration::transaction::{HeapFrameRequest, TxOptions};
+    use titan_voltr_integration::voltr_venue::{SwapDirection, VoltrVaultVenue};
+
+    use crate::common::{program_user, MockCache, VaultFixture};
+
+    fn requests(fixture: &VaultFixture) -> [(SwapDirection, QuoteRequest); 2] {
+        [
+            (
+                SwapDirection::Deposit,
+                QuoteRequest {
+                    input_mint: fixture.asset_mint,
+                    output_mint: fixture.lp_mint(),
+                    amount: 1_000_000,
+                    swap_type: SwapType::ExactIn,
+                },
+            ),
+            (
+                SwapDirection::Redeem,
+                QuoteRequest {
+                    input_mint: fixture.lp_mint(),
+                    output_mint: fixture.asset_mint,
+                    amount: 1_000_000,
+                    swap_type: SwapType::ExactIn,
+                },
+            ),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_build_transaction_instructions() {
+        let fixture = VaultFixture::default();
+        let venue = fixture.venue().await;
+        let user = Pubkey::new_unique();
+
+        for (direction, request) in requests(&fixture) {
+            let swap = venue
+                .generate_swap_instruction(request.clone(), user)
+                .unwrap();
+
+            // Defaults: only the direction's compute unit limit precedes the swap.
+            let instructions = venue
+                .build_transaction_instructions(request.clone(), user, &TxOptions::default())
+                .unwrap();
+            assert_eq!(
+                instructions,
+                vec![
+                    ComputeBudgetInstruction::set_compute_unit_limit(
+                        direction.default_compute_unit_limit()
+                    ),
+                    swap.clone(),
+                ]
+            );
+
+            let opts = TxOptions {
+                compute_unit_limit: Some(321_000),
+                compute_unit_price_micro_lamports: Some(5_000),
+                include_ata_setup: true,
+                ..Default::default()
+            };
+            let instructions = venue
+                .build_transaction_instructions(request, user, &opts)
+                .unwrap();
+            assert_eq!(instructions.len(), 4);
+            assert_eq!(
+                instructions[0],
+                ComputeBudgetInstruction::set_compute_unit_limit(321_000)
+            );
+            assert_eq!(
+                instructions[1],
+                ComputeBudgetInstruction::set_compute_unit_price(5_000)
+            );
+            assert_eq!(instructions[2].program_id, ATA_PROGRAM);
+            assert_eq!(instructions[3], swap);
+            assert_eq!(instructions[3].program_id, VOLTR_VAULT_PROGRAM);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_redeem_heap_frame() {
+        let fixture = VaultFixture::default();
+        let venue = fixture.venue().await;
+        let user = Pubkey::new_unique();
+        let heap = ComputeBudgetInstruction::request_heap_frame(64 * 1024);
+        let build = |request: &QuoteRequest, redeem_heap_frame, include_ata_setup| {
+            venue.build_transaction_instructions(
+                request.clone(),
+                user,
+                &TxOptions {
+                    compute_unit_price_micro_lamports: Some(5_000),
+                    include_ata_setup,
+                    redeem_heap_frame,
+                    ..Default::default()
+                },
+            )
+        };
+
+        for (direction, request) in requests(&fixture) {
+            for (redeem_heap_frame, include_ata_setup, requested) in [
+                (HeapFrameRequest::Never, true, false),
+                (HeapFrameRequest::Always(64 * 1024), false, true),
+                (HeapFrameRequest::Always(64 * 1024), true, true),
+                (HeapFrameRequest::WithAtaSetup(64 * 1024), false, false),
+                (HeapFrameRequest::WithAtaSetup(64 * 1024), true, true),
+            ] {
+                let instructions = build(&request, redeem_heap_frame, include_ata_setup).unwrap();
+                let requested = requested && direction == SwapDirection::Redeem;
+                assert_eq!(instructions.contains(&heap), requested);
+                if requested {
+                    // After the compute budget settings, ahead of ATA setup.
+                    assert_eq!(instructions[2], heap);
+                }
+                assert_eq!(
+                    instructions.len(),
+                    3 + usize::from(requested) + usize::from(include_ata_setup)
+                );
+            }
+        }
+
+        let [(_, deposit), (_, redeem)] = requests(&fixture);
+        for bytes in [0, 16 * 1024, 64 * 1024 + 1, 512 * 1024] {
+            assert!(build(&redeem, HeapFrameRequest::Always(bytes), false).is_err());
+            // Not requested, so not validated.
+            assert!(build(&deposit, HeapFrameRequest::Always(bytes), false).is_ok());
+            assert!(build(&redeem, HeapFrameRequest::WithAtaSetup(bytes), false).is_ok());
+        }
+        for bytes in [MIN_HEAP_FRAME_BYTES, MAX_HEAP_FRAME_BYTES] {
+            assert!(build(&redeem, HeapFrameRequest::Always(bytes), false).is_ok());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_program_redeems_within_default_heap() {
+        let fixture = VaultFixture::default().for_program();
+        let venue = fixture.venue().await;
+        let mut svm = fixture.program_svm(1_700_000_000);
+        // No asset ATA yet, so the transaction creates it ahead of the redeem.
+        let user = program_user(&mut svm, &[(fixture.lp_mint(), 1_000_000)]);
+        let [_, (_, redeem)] = requests(&fixture);
+
+        let instructions = venue
+            .build_transaction_instructions(
+                redeem,
+                user.pubkey(),
+                &TxOptions {
+                    include_ata_setup: true,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        // Compute unit limit, ATA setup and the redeem: no heap frame.
+        assert_eq!(instructions.len(), 3);
+        let tx = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&user.pubkey()),
+            &[&user],
+            svm.latest_blockhash(),
+        );
+        let meta = svm.send_transaction(tx).unwrap();
+        assert!(meta.compute_units_consumed <= u64::from(REDEEM_COMPUTE_UNIT_LIMIT));
+    }
+
+    /// Vaults the compute unit limits are measured on: plain, accruing
+    /// management fee with locked profit still degrading, and wrapped SOL.
+    fn measured_fixtures() -> [VaultFixture; 3] {
+        [
+            VaultFixture::default(),
+            VaultFixture {
+                manager_management_fee: 200,
+                manager_performance_fee: 1_000,
+                issuance_fee: 100,
+                redemption_fee: 100,
+                last_management_fee_update_ts: MEASURED_AT - 365 * 86_400,
+                last_updated_ts: MEASURED_AT - 3_600,
+                locked_profit_degradation_duration: 86_400,
+                last_updated_locked_profit: 10_000_000,
+                last_report: MEASURED_AT - 3_600,
+                ..Default::default()
+            },
+            VaultFixture {
+                asset_mint: NATIVE_MINT,
+                asset_decimals: 9,
+                total_value: 100 * LAMPORTS_PER_SOL,
+                idle_balance: 100 * LAMPORTS_PER_SOL,
+                lp_supply: 100 * LAMPORTS_PER_SOL,
+                ..Default::default()
+            },
+        ]
+        .map(VaultFixture::for_program)
+    }
+
+    const MEASURED_AT: u64 = 1_700_000_000;
+
+    /// Users each swap is measured for. The ATA program and the vault
+    /// program search a PDA bump of the user's token accounts, at about
+    /// 1.5k CU per step, so the cost varies from user to user.
+    const MEASURED_USERS: usize = 15;
+
+    #[tokio::test]
+    async fn test_compute_unit_limits_cover_measured_swaps() {
+        // Per direction, the highest median over the fixtures.
+        let mut medians = [0u64; 2];
+        for fixture in measured_fixtures() {
+            let venue = fixture.venue().await;
+            let mut svm = fixture.program_svm(MEASURED_AT);
+            let mut measured: [Vec<u64>; 2] = Default::default();
+            for _ in 0..MEASURED_USERS {
+                for (i, (direction, mut request)) in requests(&fixture).into_iter().enumerate() {
+                    request.amount = LAMPORTS_PER_SOL;
+                    // The costliest path: the output ATA is created, and
+                    // native SOL wrapped ahead of a deposit or unwrapped
+                    // after a redeem.
+                    let balances = [(request.input_mint, request.amount)];
+                    let funded = if request.input_mint == NATIVE_MINT {
+                        &balances[..0]
+                    } else {
+                        &balances[..]
+                    };
+                    let user = program_user(&mut svm, funded);
+                    let instructions = venue
+                        .build_transaction_instructions(
+                            request,
+                            user.pubkey(),
+                            &TxOptions {
+                                include_ata_setup: true,
+                                compute_unit_price_micro_lamports: Some(1_000),
+                                close_wsol_ata: true,
+                                ..Default::default()
+                            },
+                        )
+                        .unwrap();
+                    let tx = Transaction::new_signed_with_payer(
+                        &instructions,
+                        Some(&user.pubkey()),
+                        &[&user],
+                        svm.latest_blockhash(),
+                    );
+                    let meta = svm.send_transaction(tx).unwrap_or_else(|failed| {
+                        panic!(
+                            "{direction:?} of {}: {:#?}",
+                            fixture.asset_mint, failed.meta.logs
+                        )
+                    });
+                    // The SVM runs with the largest budget, not the limit
+                    // the transaction sets.
+                    let limit = u64::from(direction.default_compute_unit_limit());
+                    assert!(meta.compute_units_consumed <= limit);
+                    measured[i].push(meta.compute_units_consumed);
+                }
+            }
+            for (median, mut consumed) in medians.iter_mut().zip(measured) {
+                consumed.sort_unstable();
+                *median = (*median).max(consumed[MEASURED_USERS / 2]);
+            }
+        }
+
+        // Each limit is at least half again the median cost, which leaves
+        // room for long bump searches and vault states the fixtures miss.
+        let directions = [SwapDirection::Deposit, SwapDirection::Redeem];
+        for (direction, median) in directions.into_iter().zip(medians) {
+            let limit = u64::from(direction.default_compute_unit_limit());
+            assert!(
+                median * 3 / 2 <= limit,
+                "{direction:?}: median {median} CU leaves too little of {limit}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_build_v0_transaction_with_lookup_table() {
+        let fixture = VaultFixture::default();
+        let venue = fixture.venue().await;
+        let keypair = Keypair::new();
+        let user = keypair.pubkey();
+
+        let lut = AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: venue.get_lookup_table_keys(None).await.unwrap(),
+        };
+        let opts = TxOptions {
+            compute_unit_price_micro_lamports: Some(1_000),
+            include_ata_setup: true,
+            ..Default::default()
+        };
+
+        for (_, request) in requests(&fixture) {
+            let plain = venue
+                .build_v0_transaction(request.clone(), user, Hash::default(), &[], &opts, None)
+                .unwrap();
+            let compressed = venue
+                .build_v0_transaction(
+                    request.clone(),
+                    user,
+                    Hash::default(),
+                    std::slice::from_ref(&lut),
+                    &opts,
+                    Some(&keypair),
+                )
+                .unwrap();
+
+            let (VersionedMessage::V0(plain_message), VersionedMessage::V0(message)) =
+                (&plain.message, &compressed.message)
+            else {
+                panic!("Expected v0 messages");
+            };
+
+            assert_eq!(message.address_table_lookups.len(), 1);
+            assert_eq!(message.address_table_lookups[0].account_key, lut.key);
+            assert!(message.account_keys.len() < plain_message.account_keys.len());
+            assert_eq!(message.account_keys[0], user);
+
+            assert_eq!(plain.signatures.len(), 1);
+            assert!(compressed.verify_with_results().iter().all(|ok| *ok));
+        }
+    }
+
+    /// Extract the "maximum viable amount" reported by a validation error.
+    fn reported_max(error: &str) -> u64 {
+        error
+            .rsplit("maximum viable amount is ")
+            .next()
+            .and_then(|tail| {
+                let digits: String = tail.chars().take_while(char::is_ascii_digit).collect();
+                digits.parse().ok()
+            })
+            .unwrap_or_else(|| panic!("No maximum in {error}"))
+    }
+
+    #[tokio::test]
+    async fn test_generate_swap_instruction_validated() {
+        let fixture = VaultFixture {
+            max_cap: 1_000_000_000_000 + 5_000_000,
+            idle_balance: 2_000_000,
+            ..Default::default()
+        };
+        let venue = fixture.venue().await;
+        let user = Pubkey::new_unique();
+
+        // Viable swaps build the same instruction as the permissive method.
+        for (_, request) in requests(&fixture) {
+            assert_eq!(
+                venue
+                    .generate_swap_instruction_validated(request.clone(), user)
+                    .unwrap(),
+                venue.generate_swap_instruction(request, user).unwrap()
+            );
+        }
+
+        let [(_, deposit), (_, redeem)] = requests(&fixture);
+        let cases = [
+            (QuoteRequest { amount: 10_000_000, ..deposit.clone() }, "max cap"),
+            (QuoteRequest { amount: 3_000_000, ..redeem.clone() }, "idle balance"),
+        ];
+        for (request, reason) in cases {
+            // The permissive method still builds the doomed swap.
+            assert!(venue
+                .generate_swap_instruction(request.clone(), user)
+                .is_ok());
+
+            let error = venue
+                .generate_swap_instruction_validated(request.clone(), user)
+                .unwrap_err()
+                .to_string();
+            assert!(error.contains(reason), "{error}");
+
+            // The reported maximum is the exact liquidity edge.
+            let max = reported_max(&error);
+            assert!(max > 0 && max < request.amount);
+            let at_max = venue
+                .quote(QuoteRequest { amount: max, ..request.clone() })
+                .unwrap();
+            assert!(!at_max.not_enough_liquidity && at_max.expected_output > 0);
+            let above_max = venue
+                .quote(QuoteRequest { amount: max + 1, ..request })
+                .unwrap();
+            assert!(above_max.not_enough_liquidity);
+        }
+
+        for request in [deposit, redeem] {
+            let error = venue
+                .generate_swap_instruction_validated(QuoteRequest { amount: 0, ..request }, user)
+                .unwrap_err()
+                .to_string();
+            assert!(error.contains("zero output"), "{error}");
+            assert_eq!(reported_max(&error), 0);
+        }
+    }
+
+    fn pda(seeds: &[&[u8]]) -> Pubkey {
+        Pubkey::find_program_address(seeds, &VOLTR_VAULT_PROGRAM).0
+    }
+
+    /// Anchor method discriminator.
+    fn discriminator(name: &str) -> [u8; 8] {
+        solana_sdk::hash::hash(format!("global:{name}").as_bytes()).to_bytes()[..8]
+            .try_into()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_swap_instruction_amount_checks() {
+        let fixture = VaultFixture {
+            idle_balance: 2_000_000,
+            ..Default::default()
+        };
+        let venue = fixture.venue().await;
+        let user = Pubkey::new_unique();
+        let [(_, deposit), (_, redeem)] = requests(&fixture);
+
+        // Zero amounts are refused in both directions.
+        for request in [&deposit, &redeem] {
+            let error = venue
+                .generate_swap_instruction(QuoteRequest { amount: 0, ..request.clone() }, user)
+                .unwrap_err();
+            assert!(error.to_string().contains("Invalid Amount"), "{error}");
+        }
+
+        // A redeem beyond idle is only refused by the validated builder,
+        // which names the largest instant redeem.
+        let too_large = QuoteRequest { amount: 3_000_000, ..redeem.clone() };
+        let error = venue
+            .generate_swap_instruction_validated(too_large.clone(), user)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("idle balance"), "{error}");
+        let max = reported_max(&error);
+        for (amount, builds) in [(max, true), (max + 1, false)] {
+            let request = QuoteRequest { amount, ..redeem.clone() };
+            assert_eq!(
+                venue.generate_swap_instruction_validated(request, user).is_ok(),
+                builds
+            );
+        }
+
+        // The plain builder and the batch assemble it with its amount
+        // serialized, leaving the capacity check to the program.
+        let instruction = venue
+            .generate_swap_instruction(too_large.clone(), user)
+            .unwrap();
+        assert_eq!(instruction.data[8..16], 3_000_000u64.to_le_bytes());
+        assert!(venue
+            .generate_swap_instructions_batch(&[(too_large, user)])
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_swap_instruction_account_layouts() {
+        let fixture = VaultFixture::default();
+        let venue = fixture.venue().await;
+        let user = Pubkey::new_unique();
+
+        let vault = fixture.vault_key.as_ref();
+        let protocol = pda(&[PROTOCOL_SEED]);
+        let idle_auth = pda(&[VAULT_ASSET_IDLE_AUTH_SEED, vault]);
+        let lp_mint_auth = pda(&[VAULT_LP_MINT_AUTH_SEED, vault]);
+        let asset_ata = venue.output_account(&user, SwapDirection::Redeem);
+        let lp_ata = venue.output_account(&user, SwapDirection::Deposit);
+        let system = solana_sdk::system_program::id();
+
+        // (pubkey, is_signer, is_writable) at each index, as the program's
+        // account structs declare them.
+        let deposit_layout = [
+            (user, true, false),
+            (protocol, false, false),
+            (fixture.vault_key, false, true),
+            (fixture.asset_mint, false, false),
+            (fixture.lp_mint(), false, true),
+            (asset_ata, false, true),
+            (fixture.idle_ata, false, true),
+            (idle_auth, false, false),
+            (lp_ata, false, true),
+            (lp_mint_auth, false, false),
+            (TOKEN_PROGRAM, false, false),
+            (TOKEN_PROGRAM, false, false),
+            (system, false, false),
+        ];
+        let redeem_layout = [
+            (user, true, false),
+            (protocol, false, false),
+            (fixture.vault_key, false, true),
+            (fixture.asset_mint, false, false),
+            (fixture.lp_mint(), false, true),
+            (lp_ata, false, true),
+            (fixture.idle_ata, false, true),
+            (idle_auth, false, true),
+            (asset_ata, false, true),
+            (TOKEN_PROGRAM, false, false),
+            (TOKEN_PROGRAM, false, false),
+            (system, false, false),
+        ];
+
+        let [(_, deposit), (_, redeem)] = requests(&fixture);
+        let cases = [
+            (deposit, &deposit_layout[..], "deposit_vault", 16),
+            (redeem, &redeem_layout[..], "instant_withdraw_vault", 18),
+        ];
+        for (request, layout, method, data_len) in cases {
+            let amount = request.amount;
+            let instruction = venue.generate_swap_instruction(request, user).unwrap();
+            assert_eq!(instruction.program_id, VOLTR_VAULT_PROGRAM);
+
+            let actual: Vec<_> = instruction
+                .accounts
+                .iter()
+                .map(|meta| (meta.pubkey, meta.is_signer, meta.is_writable))
+                .collect();
+            assert_eq!(actual, layout, "{method} accounts");
+
+            assert_eq!(instruction.data.len(), data_len, "{method} data");
+            assert_eq!(instruction.data[..8], discriminator(method));
+            assert_eq!(instruction.data[8..16], amount.to_le_bytes());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_generate_swap_instructions_batch() {
+        let fixture = VaultFixture::default();
+        let venue = fixture.venue().await;
+        let [(_, deposit), (_, redeem)] = requests(&fixture);
+        let users: Vec<Pubkey> = (0..5).map(|_| Pubkey::new_unique()).collect();
+
+        let batch = venue
+            .generate_swap_instructions_batch(
+                &users
+                    .iter()
+                    .map(|user| (deposit.clone(), *user))
+                    .collect::<Vec<_>>(),
+            )
+            .unwrap();
+        assert_eq!(batch.instructions.len(), users.len());
+        for (instructions, user) in batch.instructions.iter().zip(&users) {
+            let single = venue
+                .build_transaction_instructions(
+                    deposit.clone(),
+                    *user,
+                    &TxOptions {
+                        include_ata_setup: true,
+                        ..Default::default()
+                    },
+                )
+                .unwrap();
+            assert_eq!(instructions[..], single[1..]);
+        }
+        // 11 shared accounts (vault, mints, idle ATA, 3 PDAs, 4 programs)
+        // plus each user and their two ATAs; the LUT covers all shared ones
+        // but the system and ATA programs.
+        assert_eq!(batch.unique_accounts, 11 + 3 * users.len());
+        assert_eq!(batch.unique_accounts_outside_lut, 2 + 3 * users.len());
+
+        let mixed = venue
+            .generate_swap_instructions_batch(&[(deposit.clone(), users[0]), (redeem, users[1])])
+            .unwrap();
+        assert_eq!(mixed.instructions[1][0].program_id, ATA_PROGRAM);
+        assert_eq!(mixed.instructions[1][1].program_id, VOLTR_VAULT_PROGRAM);
+
+        let invalid = QuoteRequest {
+            input_mint: Pubkey::new_unique(),
+            ..deposit.clone()
+        };
+        assert!(
+            venue
+                .generate_swap_instructions_batch(&[(deposit, users[0]), (invalid, users[1])])
+                .is_err()
+        );
+    }
+
+    /// An address lookup table account holding `addresses`.
+    fn lookup_table_account(addresses: &[Pubkey]) -> Account {
+        let mut data = vec![0u8; LOOKUP_TABLE_META_SIZE];
+        // `ProgramState::LookupTable`
+        data[0] = 1;
+        for address in addresses {
+            data.extend_from_slice(address.as_ref());
+        }
+        Account {
+            lamports: 1,
+            data,
+            owner: ADDRESS_LOOKUP_TABLE_PROGRAM,
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lookup_table_keys_cached_and_diffed() {
+        let fixture = VaultFixture::default();
+        let venue = fixture.venue().await;
+        let keys = venue.get_lookup_table_keys(None).await.unwrap();
+        assert_eq!(keys.len(), 9);
+        assert_eq!(venue.get_lookup_table_keys(None).await.unwrap(), keys);
+        // Caches are ignored without a configured table.
+        assert_eq!(
+            venue
+                .get_lookup_table_keys(Some(&fixture.cache()))
+                .await
+                .unwrap(),
+            keys
+        );
+
+        // Switching program rebuilds the cached keys.
+        let devnet = Pubkey::new_unique();
+        let devnet_venue = fixture.venue().await.with_config(VoltrVenueConfig {
+            program_id: Some(devnet),
+            ..Default::default()
+        });
+        let devnet_keys = devnet_venue.get_lookup_table_keys(None).await.unwrap();
+        assert_eq!(devnet_keys[0], devnet);
+        assert_ne!(devnet_keys, keys);
+
+        let table = Pubkey::new_unique();
+        let venue = venue.with_config(VoltrVenueConfig {
+            lookup_table: Some(table),
+            ..Default::default()
+        });
+        let mut present = keys[..6].to_vec();
+        present.push(Pubkey::new_unique());
+        let mut cache = MockCache::from_pairs([(table, lookup_table_account(&present))]);
+        assert_eq!(
+            venue.get_lookup_table_keys(Some(&cache)).await.unwrap(),
+            keys[6..]
+        );
+
+        cache.insert(table, lookup_table_account(&keys));
+        assert!(
+            venue
+                .get_lookup_table_keys(Some(&cache))
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        let mut not_a_table = lookup_table_account(&keys);
+        not_a_table.owner = Pubkey::new_unique();
+        cache.insert(table, not_a_table);
+        assert!(venue.get_lookup_table_keys(Some(&cache)).await.is_err());
+        cache.remove(&table);
+        assert!(venue.get_lookup_table_keys(Some(&cache)).await.is_err());
+        // Without a cache the full list is still available.
+        assert_eq!(venue.get_lookup_table_keys(None).await.unwrap(), keys);
+    }
+
+    #[tokio::test]
+    async fn test_dependencies_and_lookup_keys_ordered_and_unique() {
+        let fixture = VaultFixture::default();
+        let token_22_venue = VoltrVaultVenue::new_synthetic(
+            fixture.vault_key,
+            SyntheticVaultParams {
+                asset_token_program: TOKEN_22_PROGRAM,
+                ..fixture.synthetic_params()
+            },
+        )
+        .unwrap();
+        let user = Pubkey::new_unique();
+
+        for (venue, token_programs) in [
+            (fixture.synthetic_venue(), vec![TOKEN_PROGRAM]),
+            (token_22_venue, vec![TOKEN_22_PROGRAM, TOKEN_PROGRAM]),
+        ] {
+            assert_eq!(
+                venue.program_dependencies(),
+                vec![
+                    VOLTR_VAULT_PROGRAM,
+                    TOKEN_PROGRAM,
+                    TOKEN_22_PROGRAM,
+                    ATA_PROGRAM
+                ]
+            );
+
+            let pdas = venue.pdas();
+            let mut expected = vec![
+                VOLTR_VAULT_PROGRAM,
+                fixture.vault_key,
+                fixture.asset_mint,
+                pdas.lp_mint,
+                fixture.idle_ata,
+                pdas.asset_idle_auth,
+                pdas.lp_mint_auth,
+                pdas.protocol,
+            ];
+            expected.extend(token_programs);
+            let keys = venue.get_lookup_table_keys(None).await.unwrap();
+            assert_eq!(keys, expected);
+
+            for (direction, request) in requests(&fixture) {
+                let instruction = venue.generate_swap_instruction(request, user).unwrap();
+                for account in instruction.accounts.iter().filter(|a| a.is_signer) {
+                    assert!(!keys.contains(&account.pubkey));
+                }
+                assert!(!keys.contains(&venue.output_account(&user, direction)));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_local_sim {
+    //! `validate_by_simulation()` on a synthetic vault accruing management
+    //! fee, run against the embedded program:
+    //! - a deposit and a redeem execute for exactly their quotes, reporting
+    //!   the compute units and logs of the swap,
+    //! - the user needs no wallet or token accounts in the cache,
+    //! - a redeem quoted before the idle balance was drained comes back
+    //!   with the program's error and fails any tolerance.
+
+    use solana_pubkey::Pubkey;
+    use titan_integration_template::trading_venue::{QuoteRequest, SwapType};
+
+    use titan_voltr_integration::caches::StaticCache;
+    use titan_voltr_integration::clock::QuoteClock;
+    use titan_voltr_integration::config::VoltrVenueConfig;
+    use titan_voltr_integration::voltr_venue::VoltrVaultVenue;
+
+    use crate::common::VaultFixture;
+
+    const T0: u64 = 1_700_000_000;
+    /// When the swaps execute: 30 days of management fee after `T0`.
+    const T1: u64 = T0 + 30 * 86_400;
+
+    fn fixture() -> VaultFixture {
+        VaultFixture {
+            manager_management_fee: 200,
+            issuance_fee: 100,
+            redemption_fee: 100,
+            last_management_fee_update_ts: T0,
+            last_updated_ts: T0,
+            ..Default::default()
+        }
+        .for_program()
+    }
+
+    async fn venue(fixture: &VaultFixture) -> VoltrVaultVenue {
+        fixture.venue().await.with_config(VoltrVenueConfig {
+            clock: QuoteClock::Fixed(T1),
+            ..Default::default()
+        })
+    }
+
+    fn request(input_mint: Pubkey, output_mint: Pubkey, amount: u64) -> QuoteRequest {
+        QuoteRequest {
+            input_mint,
+            output_mint,
+            amount,
+            swap_type: SwapType::ExactIn,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_deposit_and_redeem_match_quotes() {
+        let fixture = fixture();
+        let venue = venue(&fixture).await;
+        let cache = StaticCache::from_pairs(&fixture.program_accounts());
+        let user = Pubkey::new_unique();
+
+        for request in [
+            request(fixture.asset_mint, fixture.lp_mint(), 1_000_000_000),
+            request(fixture.lp_mint(), fixture.asset_mint, 1_000_000_000),
+        ] {
+            let validation = venue
+                .validate_by_simulation(&request, &user, &cache)
+                .await
+                .unwrap();
+            assert_eq!(validation.error, None, "{:#?}", validation.logs);
+            assert!(validation.quoted_output > 0);
+            assert_eq!(validation.simulated_output, validation.quoted_output);
+            assert!(validation.within_tolerance(0));
+            assert!(validation.compute_units > 0);
+            assert!(!validation.logs.is_empty());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rejected_redeem() {
+        let fixture = fixture();
+        let venue = venue(&fixture).await;
+        // The cache has moved on: the vault's idle asset is nearly all gone.
+        let drained = VaultFixture {
+            idle_balance: 1_000,
+            ..fixture.clone()
+        };
+        let cache = StaticCache::from_pairs(&drained.program_accounts());
+        let redeem = request(fixture.lp_mint(), fixture.asset_mint, 1_000_000_000);
+
+        let validation = venue
+            .validate_by_simulation(&redeem, &Pubkey::new_unique(), &cache)
+            .await
+            .unwrap();
+        assert!(validation.quoted_output > 0);
+        assert!(validation.error.is_some());
+        assert!(!validation.logs.is_empty());
+        assert_eq!(validation.simulated_output, 0);
+        assert!(!validation.within_tolerance(u64::MAX));
+    }
+}
+
+#[cfg(test)]
+mod test_logs {
+    //! Events decoded from the logs of the program executing our own
+    //! instructions in LiteSVM:
+    //! - a deposit's event records the input, the LP the quote promised and
+    //!   the vault totals of the accounts around it,
+    //! - a redeem's event records the LP burned and the payout the quote
+    //!   promised, on the unlocked value,
+    //! - the fee LP they settle is the management fee of `swap_fees()`,
+    //! - `vault_events()` keeps the venue's own vault only,
+    //! - data logged by other programs and malformed lines are skipped,
+    //! - fields appended by a later program version are ignored.
+
+    use base64::Engine;
+    use litesvm::LiteSVM;
+    use solana_program_pack::Pack;
+    use solana_pubkey::Pubkey;
+    use solana_sdk::signer::Signer;
+    use solana_sdk::transaction::Transaction;
+    use spl_token::state::Mint;
+    use titan_integration_template::trading_venue::{QuoteRequest, SwapType, TradingVenue};
+
+    use titan_voltr_integration::clock::QuoteClock;
+    use titan_voltr_integration::config::VoltrVenueConfig;
+    use titan_voltr_integration::constants::{
+        DEPOSIT_VAULT_EVENT_DISCRIMINATOR, VOLTR_VAULT_PROGRAM,
+    };
+    use titan_voltr_integration::fees::FeeKind;
+    use titan_voltr_integration::logs::{parse_program_logs, parse_transaction_logs, VoltrEvent};
+    use titan_voltr_integration::state::Vault;
+    use titan_voltr_integration::voltr_venue::VoltrVaultVenue;
+
+    use crate::common::{program_user, VaultFixture};
+
+    const T0: u64 = 1_700_000_000;
+    const T1: u64 = T0 + 30 * 86_400;
+
+    fn fixture() -> VaultFixture {
+        VaultFixture {
+            manager_management_fee: 200,
+            issuance_fee: 10,
+            redemption_fee: 25,
+            locked_profit_degradation_duration: 86_400,
+            last_updated_locked_profit: 10_000_000_000,
+            last_report: T1 - 3_600,
+            last_management_fee_update_ts: T0,
+            last_updated_ts: T0,
+            ..Default::default()
+        }
+        .for_program()
+    }
+
+    async fn venue(fixture: &VaultFixture) -> VoltrVaultVenue {
+        fixture.venue().await.with_config(VoltrVenueConfig {
+            clock: QuoteClock::Fixed(T1),
+            ..Default::default()
+        })
+    }
+
+    fn request(input_mint: Pubkey, output_mint: Pubkey, amount: u64) -> QuoteRequest {
+        QuoteRequest {
+            input_mint,
+            output_mint,
+            amount,
+            swap_type: SwapType::ExactIn,
+        }
+    }
+
+    /// Execute `request` for `user`, returning the logs and the vault
+    /// account before and after.
+    fn execute(
+        svm: &mut LiteSVM,
+        fixture: &VaultFixture,
+        venue: &VoltrVaultVenue,
+        request: &QuoteRequest,
+        user: &solana_sdk::signature::Keypair,
+    ) -> (Vec<String>, Vault, Vault) {
+        let vault = |svm: &LiteSVM| {
+            Vault::load(&svm.get_account(&fixture.vault_key).unwrap().data).unwrap()
+        };
+        let pre = vault(svm);
+        let instruction = venue
+            .generate_swap_instruction(request.clone(), user.pubkey())
+            .unwrap();
+        let tx = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&user.pubkey()),
+            &[user],
+            svm.latest_blockhash(),
+        );
+        let logs = svm.send_transaction(tx).unwrap().logs;
+        (logs, pre, vault(svm))
+    }
+
+    fn lp_mint_supply(svm: &LiteSVM, fixture: &VaultFixture) -> u64 {
+        Mint::unpack(&svm.get_account(&fixture.lp_mint()).unwrap().data)
+            .unwrap()
+            .supply
+    }
+
+    fn management_fee_lp(venue: &VoltrVaultVenue, request: &QuoteRequest) -> u64 {
+        venue
+            .swap_fees(request, T1)
+            .unwrap()
+            .iter()
+            .filter(|fee| fee.kind == FeeKind::Management)
+            .map(|fee| fee.lp)
+            .sum()
+    }
+
+    #[tokio::test]
+    async fn test_deposit_event() {
+        let fixture = fixture();
+        let venue = venue(&fixture).await;
+        let mut svm = fixture.program_svm(T1);
+        let amount = 1_000_000_000;
+        let user = program_user(
+            &mut svm,
+            &[(fixture.asset_mint, amount), (fixture.lp_mint(), 0)],
+        );
+        let deposit = request(fixture.asset_mint, fixture.lp_mint(), amount);
+        let quoted = venue.quote(deposit.clone()).unwrap().expected_output;
+        let supply_before = lp_mint_supply(&svm, &fixture);
+
+        let (logs, pre, post) = execute(&mut svm, &fixture, &venue, &deposit, &user);
+        let events = parse_transaction_logs(&logs);
+        let [VoltrEvent::Deposit(event)] = events[..] else {
+            panic!("expected one deposit event, got {events:?}");
+        };
+        assert_eq!(event.user, user.pubkey());
+        assert_eq!(event.vault, fixture.vault_key);
+        assert_eq!(event.asset_mint, fixture.asset_mint);
+        assert_eq!(event.asset_deposited, amount);
+        assert_eq!(event.lp_minted, quoted);
+        assert_eq!(event.timestamp, T1 as i64);
+
+        assert_eq!(event.total_value_before, pre.asset.total_value);
+        assert_eq!(event.total_value_after, post.asset.total_value);
+        assert_eq!(
+            event.lp_supply_before,
+            pre.get_total_lp_supply_incl_fees(supply_before).unwrap()
+        );
+        assert_eq!(
+            event.lp_supply_after,
+            post.get_total_lp_supply_incl_fees(lp_mint_supply(&svm, &fixture))
+                .unwrap()
+        );
+        assert_eq!(
+            event.highest_asset_per_lp_decimal_bits_after,
+            post.high_water_mark.highest_asset_per_lp_decimal_bits
+        );
+
+        let fee_lp = management_fee_lp(&venue, &deposit);
+        assert!(fee_lp > 0);
+        assert_eq!(event.fee_lp_accrued(), fee_lp);
+    }
+
+    #[tokio::test]
+    async fn test_redeem_event() {
+        let fixture = fixture();
+        let venue = venue(&fixture).await;
+        let mut svm = fixture.program_svm(T1);
+        let amount = 1_000_000_000;
+        let user = program_user(
+            &mut svm,
+            &[(fixture.asset_mint, 0), (fixture.lp_mint(), amount)],
+        );
+        let redeem = request(fixture.lp_mint(), fixture.asset_mint, amount);
+        let quoted = venue.quote(redeem.clone()).unwrap().expected_output;
+
+        let (logs, pre, post) = execute(&mut svm, &fixture, &venue, &redeem, &user);
+        let events = parse_transaction_logs(&logs);
+        let [VoltrEvent::InstantWithdraw(event)] = events[..] else {
+            panic!("expected one withdraw event, got {events:?}");
+        };
+        assert_eq!(event.user, user.pubkey());
+        assert_eq!(event.vault, fixture.vault_key);
+        assert_eq!(event.amount, amount);
+        assert!(event.is_amount_in_lp);
+        assert!(!event.is_withdraw_all);
+        assert_eq!(event.lp_burned, amount);
+        assert_eq!(event.asset_withdrawn, quoted);
+
+        assert_eq!(event.total_value_before, pre.asset.total_value);
+        assert_eq!(event.total_value_after, post.asset.total_value);
+        // The payout was priced on the value net of locked profit.
+        assert!(event.unlocked_value_before < event.total_value_before);
+        assert_eq!(
+            event.unlocked_value_before,
+            pre.get_unlocked_asset_value(T1).unwrap()
+        );
+        assert_eq!(event.fee_lp_accrued(), management_fee_lp(&venue, &redeem));
+    }
+
+    #[tokio::test]
+    async fn test_vault_events_keep_own_vault() {
+        let fixture = fixture();
+        let venue = venue(&fixture).await;
+        let mut svm = fixture.program_svm(T1);
+        let user = program_user(
+            &mut svm,
+            &[(fixture.asset_mint, 1_000_000), (fixture.lp_mint(), 0)],
+        );
+        let deposit = request(fixture.asset_mint, fixture.lp_mint(), 1_000_000);
+        let (logs, _, _) = execute(&mut svm, &fixture, &venue, &deposit, &user);
+
+        assert_eq!(venue.vault_events(&logs), parse_transaction_logs(&logs));
+        assert_eq!(venue.vault_events(&logs)[0].vault(), fixture.vault_key);
+        let other = VaultFixture::default().synthetic_venue();
+        assert!(other.vault_events(&logs).is_empty());
+        // Nor are events looked for under another program.
+        assert!(parse_program_logs(&logs, &Pubkey::new_unique()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_foreign_and_malformed_data_skipped() {
+        let fixture = fixture();
+        let venue = venue(&fixture).await;
+        let mut svm = fixture.program_svm(T1);
+        let user = program_user(
+            &mut svm,
+            &[(fixture.asset_mint, 1_000_000), (fixture.lp_mint(), 0)],
+        );
+        let deposit = request(fixture.asset_mint, fixture.lp_mint(), 1_000_000);
+        let (logs, _, _) = execute(&mut svm, &fixture, &venue, &deposit, &user);
+        let data = logs
+            .iter()
+            .find(|line| line.starts_with("Program data: "))
+            .unwrap()
+            .clone();
+
+        let program = VOLTR_VAULT_PROGRAM.to_string();
+        let other = Pubkey::new_unique().to_string();
+        let encode = |bytes: &[u8]| {
+            format!(
+                "Program data: {}",
+                base64::engine::general_purpose::STANDARD.encode(bytes)
+            )
+        };
+        let logs: Vec<String> = [
+            // Before any program runs.
+            data.clone(),
+            format!("Program {program} invoke [1]"),
+            // Logged by a program the vault invoked.
+            format!("Program {other} invoke [2]"),
+            data.clone(),
+            format!("Program {other} success"),
+            "Program data: not base64!".to_string(),
+            encode(&[0; 8]),
+            encode(&DEPOSIT_VAULT_EVENT_DISCRIMINATOR),
+            data.clone(),
+            format!("Program {program} success"),
+            data.clone(),
+        ]
+        .into_iter()
+        .collect();
+
+        let events = parse_transaction_logs(&logs);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].user(), user.pubkey());
+
+        // The known prefix of a longer event still decodes, a shorter one
+        // does not.
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(data.strip_prefix("Program data: ").unwrap())
+            .unwrap();
+        let extended = [bytes.as_slice(), &[7; 16]].concat();
+        assert_eq!(VoltrEvent::decode(&extended), Some(events[0]));
+        assert_eq!(VoltrEvent::decode(&bytes[..bytes.len() - 1]), None);
+    }
+}
+
+#[cfg(test)]
+mod test_native_asset {
+    //! Vaults of wrapped SOL, whose users swap raw SOL:
+    //! - only the native mint makes a vault native, and the wrapping
+    //!   helpers do nothing on any other vault,
+    //! - the user's wSOL ATA is required but created by the transaction,
+    //!   and its rent is counted up front, unless the ATA exists, since the
+    //!   wallet fronts it until closing the ATA refunds it,
+    //! - the ATA is only closed when asked to, so wSOL a user kept in it is
+    //!   not unwrapped,
+    //! - in LiteSVM, a deposit is paid from the wallet's SOL and a redeem
+    //!   pays SOL back into it, leaving no wSOL ATA behind.
+
+    use litesvm::LiteSVM;
+    use solana_account::Account;
+    use solana_program::native_token::LAMPORTS_PER_SOL;
+    use solana_program::program_option::COption;
+    use solana_program_pack::Pack;
+    use solana_pubkey::Pubkey;
+    use solana_sdk::rent::Rent;
+    use solana_sdk::signature::Keypair;
+    use solana_sdk::signer::Signer;
+    use solana_sdk::transaction::Transaction;
+    use spl_token::state::{Account as TokenAccount, AccountState};
+    use titan_integration_template::trading_venue::{QuoteRequest, SwapType, TradingVenue};
+
+    use titan_voltr_integration::constants::{NATIVE_MINT, TOKEN_PROGRAM};
+    use titan_voltr_integration::preconditions::UserAccountRole;
+    use titan_voltr_integration::synthetic::token_account;
+    use titan_voltr_integration::transaction::TxOptions;
+    use titan_voltr_integration::voltr_venue::{SwapDirection, VoltrVaultVenue};
+
+    use crate::common::{program_user, VaultFixture};
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn fixture() -> VaultFixture {
+        VaultFixture {
+            asset_mint: NATIVE_MINT,
+            asset_decimals: 9,
+            total_value: 100 * LAMPORTS_PER_SOL,
+            idle_balance: 100 * LAMPORTS_PER_SOL,
+            lp_supply: 100 * LAMPORTS_PER_SOL,
+            ..Default::default()
+        }
+        .for_program()
+    }
+
+    fn request(input_mint: Pubkey, output_mint: Pubkey, amount: u64) -> QuoteRequest {
+        QuoteRequest {
+            input_mint,
+            output_mint,
+            amount,
+            swap_type: SwapType::ExactIn,
+        }
+    }
+
+    fn wsol_ata(user: &Pubkey) -> Pubkey {
+        spl_associated_token_account::get_associated_token_address(user, &NATIVE_MINT)
+    }
+
+    #[tokio::test]
+    async fn test_only_native_mint_is_native() {
+        let native = fixture().venue().await;
+        assert!(native.is_native_asset());
+
+        let venue = VaultFixture::default().venue().await;
+        assert!(!venue.is_native_asset());
+        let user = Pubkey::new_unique();
+        assert!(venue
+            .wrap_sol_instructions(&user, 1_000)
+            .unwrap()
+            .is_empty());
+        assert!(venue.unwrap_sol_instruction(&user).unwrap().is_none());
+        assert_eq!(native.wrap_sol_instructions(&user, 1_000).unwrap().len(), 3);
+        assert!(native.unwrap_sol_instruction(&user).unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_wsol_ata_created_by_transaction() {
+        let fixture = fixture();
+        let venue = fixture.venue().await;
+        let user = Pubkey::new_unique();
+
+        let deposit = venue.required_user_accounts(&user, SwapDirection::Deposit, 500);
+        assert_eq!(deposit[0].role, UserAccountRole::SourceAssetAta);
+        assert_eq!(deposit[0].pubkey, wsol_ata(&user));
+        assert!(deposit[0].created_if_missing);
+        assert_eq!(deposit[0].min_balance, 0);
+        assert!(!deposit[1].created_if_missing);
+
+        let redeem = venue.required_user_accounts(&user, SwapDirection::Redeem, 500);
+        assert_eq!(redeem[0].min_balance, 500);
+        assert!(!redeem[0].created_if_missing);
+        assert_eq!(redeem[1].pubkey, wsol_ata(&user));
+        assert!(redeem[1].created_if_missing);
+
+        // Both directions front the wSOL ATA's rent, a deposit on top of
+        // the LP ATA's.
+        let lp_rent = venue.output_account_rent(SwapDirection::Deposit);
+        let wsol_rent = venue.output_account_rent(SwapDirection::Redeem);
+        for (direction, lamports) in [
+            (SwapDirection::Deposit, lp_rent + wsol_rent),
+            (SwapDirection::Redeem, wsol_rent),
+        ] {
+            assert_eq!(
+                venue
+                    .extra_lamports_required(&user, direction, None)
+                    .await
+                    .unwrap(),
+                lamports
+            );
+        }
+        let redeem = request(fixture.lp_mint(), NATIVE_MINT, 500);
+        let detailed = venue.quote_detailed(&redeem, &user, None).await.unwrap();
+        assert_eq!(detailed.extra_lamports_required, wsol_rent);
+
+        // An existing wSOL ATA costs nothing.
+        let mut cache = fixture.cache();
+        cache.insert(
+            wsol_ata(&user),
+            token_account(&NATIVE_MINT, &user, &TOKEN_PROGRAM, 0),
+        );
+        for (direction, lamports) in [
+            (SwapDirection::Deposit, lp_rent),
+            (SwapDirection::Redeem, 0),
+        ] {
+            assert_eq!(
+                venue
+                    .extra_lamports_required(&user, direction, Some(&cache))
+                    .await
+                    .unwrap(),
+                lamports
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wsol_ata_closed_on_request() {
+        let fixture = fixture();
+        let venue = fixture.venue().await;
+        let user = Pubkey::new_unique();
+        let close = venue.unwrap_sol_instruction(&user).unwrap().unwrap();
+        let redeem = request(fixture.lp_mint(), NATIVE_MINT, 500);
+
+        for close_wsol_ata in [false, true] {
+            let instructions = venue
+                .build_transaction_instructions(
+                    redeem.clone(),
+                    user,
+                    &TxOptions {
+                        close_wsol_ata,
+                        ..Default::default()
+                    },
+                )
+                .unwrap();
+            assert_eq!(instructions.contains(&close), close_wsol_ata);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_native_redeem_delivers_sol_by_default() {
+        let fixture = fixture();
+        let venue = fixture.venue().await;
+        let user = Pubkey::new_unique();
+        let close = venue.unwrap_sol_instruction(&user).unwrap().unwrap();
+        let redeem = request(fixture.lp_mint(), NATIVE_MINT, 500);
+
+        let instructions = venue
+            .build_transaction_instructions(redeem, user, &TxOptions::default())
+            .unwrap();
+        assert_eq!(instructions.last(), Some(&close));
+    }
+
+    /// Whether `pubkey` was closed, or never opened, in `svm`.
+    fn closed(svm: &LiteSVM, pubkey: &Pubkey) -> bool {
+        svm.get_account(pubkey)
+            .is_none_or(|account| account.lamports == 0)
+    }
+
+    /// `program_svm()` with the idle account turned into a native one, its
+    /// balance held as lamports on top of its rent.
+    fn program_svm(fixture: &VaultFixture) -> LiteSVM {
+        let mut svm = fixture.program_svm(NOW);
+        let rent = Rent::default().minimum_balance(TokenAccount::LEN);
+        let mut account = Account::new(
+            rent + fixture.idle_balance,
+            TokenAccount::LEN,
+            &TOKEN_PROGRAM,
+        );
+        TokenAccount {
+            mint: NATIVE_MINT,
+            owner: fixture.idle_auth(),
+            amount: fixture.idle_balance,
+            state: AccountState::Initialized,
+            is_native: COption::Some(rent),
+            ..Default::default()
+        }
+        .pack_into_slice(&mut account.data);
+        svm.set_account(fixture.idle_ata, account).unwrap();
+        svm
+    }
+
+    /// Send the instructions of `request` for `user`, with fees paid by
+    /// `payer` so that the user's lamports only move with the swap.
+    fn execute(
+        svm: &mut LiteSVM,
+        venue: &VoltrVaultVenue,
+        request: QuoteRequest,
+        user: &Keypair,
+        payer: &Keypair,
+    ) {
+        let instructions = venue
+            .build_transaction_instructions(
+                request,
+                user.pubkey(),
+                &TxOptions {
+                    include_ata_setup: true,
+                    close_wsol_ata: true,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        let tx = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&payer.pubkey()),
+            &[payer, user],
+            svm.latest_blockhash(),
+        );
+        svm.send_transaction(tx).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_program_round_trip_in_raw_sol() {
+        let fixture = fixture();
+        let venue = fixture.venue().await;
+        let mut svm = program_svm(&fixture);
+        let user = program_user(&mut svm, &[]);
+        let payer = program_user(&mut svm, &[]);
+        let lamports = |svm: &LiteSVM| svm.get_account(&user.pubkey()).unwrap().lamports;
+        let lp_ata = spl_associated_token_account::get_associated_token_address(
+            &user.pubkey(),
+            &fixture.lp_mint(),
+        );
+
+        let amount = 2 * LAMPORTS_PER_SOL;
+        let deposit = request(NATIVE_MINT, fixture.lp_mint(), amount);
+        let minted = venue.quote(deposit.clone()).unwrap().expected_output;
+        // The wSOL ATA's rent is fronted and refunded; the LP ATA's is spent.
+        let extra = venue
+            .extra_lamports_required(&user.pubkey(), SwapDirection::Deposit, None)
+            .await
+            .unwrap();
+        let wsol_rent = venue.output_account_rent(SwapDirection::Redeem);
+        let before = lamports(&svm);
+        execute(&mut svm, &venue, deposit, &user, &payer);
+        assert_eq!(before - lamports(&svm), amount + extra - wsol_rent);
+        assert!(closed(&svm, &wsol_ata(&user.pubkey())));
+        let lp_balance = |svm: &LiteSVM| {
+            TokenAccount::unpack(&svm.get_account(&lp_ata).unwrap().data)
+                .unwrap()
+                .amount
+        };
+        assert_eq!(lp_balance(&svm), minted);
+
+        let venue = VaultFixture {
+            idle_balance: fixture.idle_balance + amount,
+            total_value: fixture.total_value + amount,
+            lp_supply: fixture.lp_supply + minted,
+            ..fixture.clone()
+        }
+        .venue()
+        .await;
+        let redeem = request(fixture.lp_mint(), NATIVE_MINT, minted);
+        let paid = venue.quote(redeem.clone()).unwrap().expected_output;
+        let before = lamports(&svm);
+        execute(&mut svm, &venue, redeem, &user, &payer);
+        assert_eq!(lamports(&svm) - before, paid);
+        assert!(amount - paid <= 2);
+        assert!(closed(&svm, &wsol_ata(&user.pubkey())));
+        assert_eq!(lp_balance(&svm), 0);
+    }
+}
+
+#[cfg(all(test, feature = "test-utils"))]
+mod test_sampling {
+    //! The `sampling` helpers over synthetic and program-backed vaults:
+    //! - sampled amounts stay within the bounds, and none are drawn when
+    //!   nothing can be filled,
+    //! - probes straddle the lower and upper bounds, the cap room and the
+    //!   idle edge, sorted and positive,
+    //! - the program accepts a probe exactly when the venue does not reject
+    //!   it, paying the quoted amount.
+
+    use solana_program_pack::Pack;
+    use solana_sdk::signer::Signer;
+    use solana_sdk::transaction::Transaction;
+    use spl_token::state::Account as TokenAccount;
+    use titan_integration_template::trading_venue::{QuoteRequest, SwapType, TradingVenue};
+
+    use titan_voltr_integration::clock::QuoteClock;
+    use titan_voltr_integration::config::VoltrVenueConfig;
+    use titan_voltr_integration::sampling::{
+        boundary_probe_amounts, sample_amounts_for_direction, sample_log_uniform_u64,
+    };
+    use titan_voltr_integration::voltr_venue::{SwapDirection, VoltrVaultVenue};
+
+    use crate::common::{program_user, VaultFixture};
+
+    const T0: u64 = 1_700_000_000;
+    const DIRECTIONS: [SwapDirection; 2] = [SwapDirection::Deposit, SwapDirection::Redeem];
+
+    /// A capped vault holding a fifth of its value idle.
+    fn fixture() -> VaultFixture {
+        let fixture = VaultFixture {
+            issuance_fee: 10,
+            redemption_fee: 25,
+            last_updated_ts: T0,
+            ..Default::default()
+        }
+        .for_program();
+        VaultFixture {
+            max_cap: fixture.total_value + 5_000_000,
+            idle_balance: fixture.total_value / 5,
+            ..fixture
+        }
+    }
+
+    async fn venue(fixture: &VaultFixture) -> VoltrVaultVenue {
+        fixture.venue().await.with_config(VoltrVenueConfig {
+            clock: QuoteClock::Fixed(T0),
+            ..Default::default()
+        })
+    }
+
+    fn bounds(venue: &VoltrVaultVenue, direction: SwapDirection) -> (u64, u64) {
+        match direction {
+            SwapDirection::Deposit => venue.bounds(0, 1),
+            SwapDirection::Redeem => venue.bounds(1, 0),
+        }
+        .unwrap()
+    }
+
+    #[test]
+    fn test_log_uniform_within_range() {
+        for (lo, hi) in [(1, 1), (1, 10), (1_000, u64::MAX)] {
+            for _ in 0..100 {
+                let amount = sample_log_uniform_u64(lo, hi);
+                assert!((lo..=hi).contains(&amount));
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_samples_within_bounds() {
+        let fixture = fixture();
+        let capped = venue(&fixture).await;
+        for direction in DIRECTIONS {
+            let (lower, upper) = bounds(&capped, direction);
+            let samples = sample_amounts_for_direction(&capped, direction, 200).unwrap();
+            assert_eq!(samples.len(), 200);
+            assert!(samples.iter().all(|a| (lower..=upper).contains(a)));
+        }
+
+        let full = VaultFixture {
+            max_cap: fixture.total_value,
+            ..fixture
+        };
+        let full = venue(&full).await;
+        assert!(
+            sample_amounts_for_direction(&full, SwapDirection::Deposit, 10)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_probes_straddle_edges() {
+        let fixture = fixture();
+        let venue = venue(&fixture).await;
+        for direction in DIRECTIONS {
+            let (lower, upper) = bounds(&venue, direction);
+            let probes = boundary_probe_amounts(&venue, direction).unwrap();
+            assert!(probes.windows(2).all(|w| w[0] < w[1]), "{probes:?}");
+            assert!(probes[0] > 0);
+            for edge in [lower, upper] {
+                for amount in [edge - 1, edge, edge + 1] {
+                    assert!(amount == 0 || probes.contains(&amount), "{amount}");
+                }
+            }
+        }
+
+        let room = venue
+            .direction_info(SwapDirection::Deposit)
+            .capacity_limit
+            .unwrap();
+        let deposits = boundary_probe_amounts(&venue, SwapDirection::Deposit).unwrap();
+        assert!(deposits.contains(&room) && deposits.contains(&(room + 1)));
+    }
+
+    #[tokio::test]
+    async fn test_probes_against_program() {
+        let fixture = fixture();
+        let venue = venue(&fixture).await;
+        for direction in DIRECTIONS {
+            let info = venue.direction_info(direction);
+            for amount in boundary_probe_amounts(&venue, direction).unwrap() {
+                let request = QuoteRequest {
+                    input_mint: info.input_mint,
+                    output_mint: info.output_mint,
+                    amount,
+                    swap_type: SwapType::ExactIn,
+                };
+                let (quote, rejection) = venue.quote_with_rejection(&request).unwrap();
+                // Below the lower bound the program still executes, for no
+                // output; only capacity rejections must match.
+                let accepted = rejection.is_none();
+
+                let mut svm = fixture.program_svm(T0);
+                let user = program_user(
+                    &mut svm,
+                    &[(info.input_mint, amount), (info.output_mint, 0)],
+                );
+                // Redeems past the idle balance are refused before the program.
+                let Ok(instruction) = venue.generate_swap_instruction(request, user.pubkey())
+                else {
+                    assert!(!accepted, "{direction:?} of {amount}");
+                    continue;
+                };
+                let tx = Transaction::new_signed_with_payer(
+                    &[instruction],
+                    Some(&user.pubkey()),
+                    &[&user],
+                    svm.latest_blockhash(),
+                );
+                let executed = svm.send_transaction(tx).is_ok();
+                assert_eq!(executed, accepted, "{direction:?} of {amount}");
+                if executed {
+                    let output = svm
+                        .get_account(&info.output_account(&user.pubkey()))
+                        .unwrap();
+                    assert_eq!(
+                        TokenAccount::unpack(&output.data).unwrap().amount,
+                        quote.expected_output
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_zero_fee_vault {
+    //! A vault charging no fee of any kind, the base case every fee perturbs:
+    //! - quotes are the pro-rata share price, matching what the program
+    //!   executes in LiteSVM,
+    //! - a deposit redeemed straight back loses at most 2 base units,
+    //! - no fee component is reported, pending or settled,
+    //! - the management fee estimator returns before any fee math, even on
+    //!   figures that would overflow it.
+
+    use litesvm::LiteSVM;
+    use solana_program_pack::Pack;
+    use solana_pubkey::Pubkey;
+    use solana_sdk::signer::Signer;
+    use solana_sdk::transaction::Transaction;
+    use spl_token::state::Account as TokenAccount;
+    use titan_integration_template::trading_venue::{QuoteRequest, SwapType, TradingVenue};
+
+    use titan_voltr_integration::clock::QuoteClock;
+    use titan_voltr_integration::config::VoltrVenueConfig;
+    use titan_voltr_integration::core::{quote_snapshot, CoreError, SwapDirection, VaultSnapshot};
+    use titan_voltr_integration::fees::AccruedFees;
+    use titan_voltr_integration::overrides::SnapshotOverrides;
+    use titan_voltr_integration::voltr_venue::VoltrVaultVenue;
+
+    use crate::common::{program_user, VaultFixture};
+
+    const NOW: u64 = 1_700_000_000;
+
+    const AMOUNTS: [u64; 6] = [1, 7, 999, 1_000_000, 123_456_789, 50_000_000_000];
+
+    /// Fee-free, with a fee timestamp 30 days old so that any management
+    /// fee would have accrued.
+    fn fixture() -> VaultFixture {
+        VaultFixture {
+            total_value: 1_234_567_890_123,
+            idle_balance: 1_234_567_890_123,
+            lp_supply: 987_654_321_000,
+            last_management_fee_update_ts: NOW - 30 * 86_400,
+            last_updated_ts: NOW,
+            ..Default::default()
+        }
+    }
+
+    fn venue(fixture: &VaultFixture) -> VoltrVaultVenue {
+        fixture.synthetic_venue().with_config(VoltrVenueConfig {
+            clock: QuoteClock::Fixed(NOW),
+            ..Default::default()
+        })
+    }
+
+    fn request(input_mint: Pubkey, output_mint: Pubkey, amount: u64) -> QuoteRequest {
+        QuoteRequest {
+            input_mint,
+            output_mint,
+            amount,
+            swap_type: SwapType::ExactIn,
+        }
+    }
+
+    #[test]
+    fn test_quotes_are_pro_rata() {
+        let fixture = fixture();
+        let venue = venue(&fixture);
+        let supply = fixture.lp_supply + fixture.dead_weight;
+        for amount in AMOUNTS {
+            let minted = venue
+                .quote(request(fixture.asset_mint, fixture.lp_mint(), amount))
+                .unwrap()
+                .expected_output;
+            // amount / (value + amount) of the supply after minting, which
+            // is amount * supply / value.
+            let pro_rata = amount as u128 * supply as u128 / fixture.total_value as u128;
+            assert_eq!(minted as u128, pro_rata);
+
+            let paid = venue
+                .quote(request(fixture.lp_mint(), fixture.asset_mint, amount))
+                .unwrap()
+                .expected_output;
+            let pro_rata = amount as u128 * fixture.total_value as u128 / supply as u128;
+            assert!((paid as u128).abs_diff(pro_rata) <= 1);
+        }
+    }
+
+    #[test]
+    fn test_round_trip_loses_dust_only() {
+        let fixture = fixture();
+        let venue = venue(&fixture);
+        for amount in AMOUNTS {
+            let minted = venue
+                .quote(request(fixture.asset_mint, fixture.lp_mint(), amount))
+                .unwrap()
+                .expected_output;
+            let after_deposit = SnapshotOverrides {
+                idle_balance: Some(fixture.idle_balance + amount),
+                total_value: Some(fixture.total_value + amount),
+                lp_supply: Some(fixture.lp_supply + minted),
+                ..Default::default()
+            };
+            let redeemed = venue
+                .quote_with_overrides(
+                    &request(fixture.lp_mint(), fixture.asset_mint, minted),
+                    &after_deposit,
+                )
+                .unwrap()
+                .expected_output;
+            assert!(redeemed <= amount);
+            assert!(amount - redeemed <= 2, "{amount} came back as {redeemed}");
+        }
+    }
+
+    fn asset_balance(svm: &LiteSVM, fixture: &VaultFixture, user: &Pubkey) -> u64 {
+        let ata =
+            spl_associated_token_account::get_associated_token_address(user, &fixture.asset_mint);
+        TokenAccount::unpack(&svm.get_account(&ata).unwrap().data)
+            .unwrap()
+            .amount
+    }
+
+    #[tokio::test]
+    async fn test_program_round_trip_matches_quotes() {
+        let fixture = fixture().for_program();
+        let venue = fixture.venue().await.with_config(VoltrVenueConfig {
+            clock: QuoteClock::Fixed(NOW),
+            ..Default::default()
+        });
+        let mut svm = fixture.program_svm(NOW);
+        let amount = 123_456_789;
+        let user = program_user(
+            &mut svm,
+            &[(fixture.asset_mint, amount), (fixture.lp_mint(), 0)],
+        );
+
+        let deposit = request(fixture.asset_mint, fixture.lp_mint(), amount);
+        let minted = venue.quote(deposit.clone()).unwrap().expected_output;
+        let redeem = request(fixture.lp_mint(), fixture.asset_mint, minted);
+        let paid = venue
+            .quote_with_overrides(
+                &redeem,
+                &SnapshotOverrides {
+                    idle_balance: Some(fixture.idle_balance + amount),
+                    total_value: Some(fixture.total_value + amount),
+                    lp_supply: Some(fixture.lp_supply + minted),
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+            .expected_output;
+
+        for request in [deposit, redeem] {
+            let instruction = venue
+                .generate_swap_instruction(request, user.pubkey())
+                .unwrap();
+            let tx = Transaction::new_signed_with_payer(
+                &[instruction],
+                Some(&user.pubkey()),
+                &[&user],
+                svm.latest_blockhash(),
+            );
+            svm.send_transaction(tx).unwrap();
+        }
+        assert_eq!(asset_balance(&svm, &fixture, &user.pubkey()), paid);
+        assert!(amount - paid <= 2);
+    }
+
+    #[tokio::test]
+    async fn test_no_fee_components() {
+        let fixture = fixture();
+        let venue = venue(&fixture);
+        assert_eq!(
+            venue.accrued_fees(NOW).unwrap(),
+            AccruedFees {
+                current_ts: NOW,
+                ..Default::default()
+            }
+        );
+        for amount in AMOUNTS {
+            for request in [
+                request(fixture.asset_mint, fixture.lp_mint(), amount),
+                request(fixture.lp_mint(), fixture.asset_mint, amount),
+            ] {
+                let detailed = venue
+                    .quote_detailed(&request, &Pubkey::new_unique(), None)
+                    .await
+                    .unwrap();
+                assert_eq!(detailed.fees, vec![]);
+                assert_eq!(
+                    venue.quote_error_terms(&request).unwrap().performance_fee,
+                    0
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_management_fee_estimator_short_circuits() {
+        // Accruing on these figures overflows the fee math, so a zero result
+        // means it was never reached.
+        let snapshot = VaultSnapshot {
+            total_value: u64::MAX / 2,
+            lp_supply: u64::MAX / 4,
+            last_management_fee_update_ts: 1,
+            ..Default::default()
+        };
+        assert_eq!(snapshot.pending_management_fee_lp(u64::MAX), Ok(0));
+        let charged = VaultSnapshot {
+            management_fee_bps: 100,
+            ..snapshot
+        };
+        assert_eq!(
+            charged.pending_management_fee_lp(u64::MAX),
+            Err(CoreError::MathOverflow)
+        );
+
+        // Quotes on them still go through.
+        for direction in [SwapDirection::Deposit, SwapDirection::Redeem] {
+            let (output, _) = quote_snapshot(
+                &VaultSnapshot {
+                    idle_balance: u64::MAX,
+                    ..snapshot
+                },
+                direction,
+                1_000_000,
+                u64::MAX,
+            )
+            .unwrap();
+            assert!(output > 0);
+        }
+    }
+}
@@ -1,3 +1,6 @@
+mod common;
+mod mainnet;
+
 #[cfg(test)]
 mod simulations {
     //! Quoting tests for the Voltr vault venue.
@@ -6,10 +9,25 @@ mod simulations {
     //! - The venue loads on-chain state correctly
     //! - It exposes valid token info
     //! - It establishes valid quoting boundaries for both swap directions
-    //! - Its off-chain quote matches on-chain execution on and off the boundaries
-    //! - Its quoting function is monotone increasing for both directions
-    //! - Its quoting speed is sufficient for integration
-
+    //! - Its off-chain quote matches on-chain execution at every boundary
+    //!   probe within the bounds and at log-uniformly sampled amounts
+    //! - Its quoting function is monotone increasing for both directions,
+    //!   on fixture vaults loaded without RPC
+    //! - Its quoting speed is sufficient for integration, likewise
+    //! - The on-chain program treats the max cap as inclusive, like the venue,
+    //!   and checks it against the same total value with fees pending
+    //! - Executions reconcile to the exact amounts moved, with the fee implied
+    //!   by the vault's fee configuration
+    //! - A multi-user deposit batch executes as one transaction, paying each
+    //!   user their individual quote
+    //! - Deposits round the issuance fee exactly like the program, for fees
+    //!   across the bps range and amounts on fee rounding boundaries
+    //! - A redeem of exactly the idle balance limit executes for the quoted
+    //!   amount, and one LP more is rejected by both the venue and the program
+    //! - Executions anywhere in the error window land within the quote plus
+    //!   or minus `quote_error_bound()`
+
+    use async_trait::async_trait;
     use litesvm::LiteSVM;
     use rand::Rng;
     use rstest::rstest;
@@ -18,7 +36,6 @@ mod simulations {
     use solana_account::ReadableAccount;
     use solana_account::WritableAccount;
     use solana_client::nonblocking::rpc_client::RpcClient;
-    use solana_compute_budget::compute_budget::ComputeBudget;
     use solana_program::native_token::LAMPORTS_PER_SOL;
     use solana_program_pack::Pack;
     use solana_pubkey::Pubkey;
@@ -26,8 +43,8 @@ mod simulations {
     use solana_sdk::signer::Signer;
     use solana_sysvar::clock::{self, Clock};
     use solana_transaction::Transaction;
+    use std::collections::HashMap;
     use std::env;
-    use std::str::FromStr;
     use std::time::Instant;
 
     use spl_associated_token_account::get_associated_token_address_with_program_id;
@@ -40,8 +57,18 @@ mod simulations {
         FromAccount, QuoteRequest, SwapType, TradingVenue,
     };
 
-    use titan_voltr_integration::constants::VOLTR_VAULT_PROGRAM;
-    use titan_voltr_integration::voltr_venue::VoltrVaultVenue;
+    use titan_voltr_integration::caches::{ClosureCache, StaticCache};
+    use titan_voltr_integration::clock::QuoteClock;
+    use titan_voltr_integration::config::VoltrVenueConfig;
+    use titan_voltr_integration::constants::{MAX_FEE_BPS, ONE_YEAR_U64, VOLTR_VAULT_PROGRAM};
+    use titan_voltr_integration::core::calc_withdraw_asset_to_redeem;
+    use titan_voltr_integration::execution::SwapBalances;
+    use titan_voltr_integration::local_sim;
+    use titan_voltr_integration::sampling::{boundary_probe_amounts, sample_amounts_for_direction};
+    use titan_voltr_integration::voltr_venue::{SwapDirection, VoltrVaultVenue};
+
+    use crate::common::VaultFixture;
+    use crate::mainnet::{self, LiveVault};
 
     /// Initialize logging for test diagnostics.
     fn init_test_logger() {
@@ -49,22 +76,10 @@ mod simulations {
     }
 
     /// Creates a new LiteSVM instance configured with:
-    /// - The Voltr vault program loaded from `programs/voltr_vault.so`
+    /// - The Voltr vault program embedded by `local_sim`
     /// - A funded system account for signing transactions
     fn setup_litesvm() -> (LiteSVM, Keypair) {
-        let mut litesvm = LiteSVM::new()
-            .with_compute_budget(ComputeBudget {
-                compute_unit_limit: 1_400_000,
-                ..Default::default()
-            })
-            .with_blockhash_check(false)
-            .with_sigverify(false)
-            .with_transaction_history(0);
-
-        // Load the Voltr vault program binary
-        litesvm
-            .add_program_from_file(VOLTR_VAULT_PROGRAM, "programs/voltr_vault.so")
-            .unwrap();
+        let mut litesvm = local_sim::program_svm(&VOLTR_VAULT_PROGRAM);
 
         // Create a funded user wallet
         let keypair = Keypair::new();
@@ -75,9 +90,7 @@ mod simulations {
             executable: false,
             rent_epoch: 0,
         };
-        litesvm
-            .set_account(keypair.pubkey(), account.into())
-            .unwrap();
+        litesvm.set_account(keypair.pubkey(), account).unwrap();
 
         (litesvm, keypair)
     }
@@ -90,122 +103,72 @@ mod simulations {
         litesvm: &mut LiteSVM,
         keypair: &Keypair,
     ) -> u64 {
-        let tradable_mints = venue.get_token_info();
-
-        // Identify input/output tokens
-        let idx_0 = tradable_mints
-            .iter()
-            .position(|x| x.pubkey == request.input_mint)
-            .unwrap();
-        let idx_1 = (idx_0 + 1) % 2;
-
-        let (token_a, token_a_program) = (
-            tradable_mints[idx_0].pubkey,
-            tradable_mints[idx_0].get_token_program(),
-        );
-        let (token_b, token_b_program) = (
-            tradable_mints[idx_1].pubkey,
-            tradable_mints[idx_1].get_token_program(),
-        );
+        try_sim_quote_request(venue, cache, request, litesvm, keypair)
+            .await
+            .unwrap()
+    }
 
-        let token_account_a = get_associated_token_address_with_program_id(
-            &keypair.pubkey(),
-            &token_a,
-            &token_a_program,
-        );
-        let token_account_b = get_associated_token_address_with_program_id(
-            &keypair.pubkey(),
-            &token_b,
-            &token_b_program,
-        );
+    /// Simulate a swap using LiteSVM, returning the output token amount or
+    /// the transaction error when the program rejects the swap.
+    async fn try_sim_quote_request(
+        venue: &dyn TradingVenue,
+        cache: &dyn AccountsCache,
+        request: QuoteRequest,
+        litesvm: &mut LiteSVM,
+        keypair: &Keypair,
+    ) -> Result<u64, String> {
+        try_sim_balances(venue, cache, request, litesvm, keypair)
+            .await
+            .map(|(_, post)| post.output)
+    }
 
-        //
-        // Create synthetic token accounts inside the simulator
-        //
+    /// Simulate a swap using LiteSVM, returning the user's input and output
+    /// balances before and after it. The user starts with "infinite" input.
+    async fn try_sim_balances(
+        venue: &dyn TradingVenue,
+        cache: &dyn AccountsCache,
+        request: QuoteRequest,
+        litesvm: &mut LiteSVM,
+        keypair: &Keypair,
+    ) -> Result<(SwapBalances, SwapBalances), String> {
+        let sim =
+            local_sim::simulate_swap(litesvm, venue, cache, request, &keypair.pubkey(), u64::MAX)
+                .await
+                .unwrap();
+        match sim.error {
+            Some(err) => Err(err),
+            None => Ok((sim.pre, sim.post)),
+        }
+    }
 
-        // Token A account (source)
-        let mut account_a = Account::new(LAMPORTS_PER_SOL, TokenAccount::LEN, &spl_token::ID);
-        let mut account_a_data = TokenAccount::default();
-        account_a_data.mint = token_a;
-        account_a_data.owner = keypair.pubkey();
-        account_a_data.state = AccountState::Initialized;
-        account_a_data.amount = u64::MAX; // ensure "infinite" input
-        account_a_data.pack_into_slice(account_a.data_as_mut_slice());
-
-        // Token B account (destination)
-        let mut account_b = Account::new(LAMPORTS_PER_SOL, TokenAccount::LEN, &spl_token::ID);
-        let mut account_b_data = TokenAccount::default();
-        account_b_data.mint = token_b;
-        account_b_data.owner = keypair.pubkey();
-        account_b_data.state = AccountState::Initialized;
-        account_b_data.amount = 0;
-        account_b_data.pack_into_slice(account_b.data_as_mut_slice());
-
-        // Load token accounts into LiteSVM
-        litesvm.set_account(token_account_a, account_a).unwrap();
-        litesvm.set_account(token_account_b, account_b).unwrap();
+    /// Accounts cache serving patched copies of some accounts and fetching
+    /// the rest over RPC.
+    struct PatchedCache {
+        inner: RpcClientCache,
+        patched: HashMap<Pubkey, Account>,
+    }
 
-        //
-        // Build the swap instruction
-        //
-        let ix = venue
-            .generate_swap_instruction(request, keypair.pubkey())
-            .unwrap();
+    #[async_trait]
+    impl AccountsCache for PatchedCache {
+        async fn get_account(&self, pubkey: &Pubkey) -> Result<Option<Account>, TradingVenueError> {
+            match self.patched.get(pubkey) {
+                Some(account) => Ok(Some(account.clone())),
+                None => self.inner.get_account(pubkey).await,
+            }
+        }
 
-        // Load all instruction accounts into SVM (except executable ones already present)
-        let pks: Vec<Pubkey> = ix.accounts.iter().map(|acc| acc.pubkey).collect();
-        let accounts_to_load = cache.get_accounts(&pks).await.unwrap();
-        for (account, key) in accounts_to_load.iter().zip(pks) {
-            if let Some(acc) = account {
-                if acc.executable {
-                    continue;
+        async fn get_accounts(
+            &self,
+            pubkeys: &[Pubkey],
+        ) -> Result<Vec<Option<Account>>, TradingVenueError> {
+            let mut accounts = self.inner.get_accounts(pubkeys).await?;
+            for (pubkey, account) in pubkeys.iter().zip(accounts.iter_mut()) {
+                if let Some(patched) = self.patched.get(pubkey) {
+                    *account = Some(patched.clone());
                 }
-                litesvm.set_account(key, acc.clone()).unwrap();
             }
+            Ok(accounts)
         }
-
-        //
-        // Execute swap inside the SIM
-        //
-        let blockhash = litesvm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[ix],
-            Some(&keypair.pubkey()),
-            &[keypair],
-            blockhash,
-        );
-
-        let simulation_result = litesvm.simulate_transaction(tx).unwrap();
-
-        //
-        // Read output account and extract the final token amount
-        //
-        let account_b = simulation_result
-            .post_accounts
-            .into_iter()
-            .find(|(pk, _)| pk == &token_account_b)
-            .map(|(_, acc)| acc)
-            .unwrap();
-        let post_b = TokenAccount::unpack_from_slice(account_b.data())
-            .expect("Failed to unpack token B account");
-        post_b.amount
-    }
-
-    /// Returns a log-uniformly sampled u64 in `[lo, hi]`.
-    fn sample_log_uniform_u64(lo: u64, hi: u64) -> u64 {
-        assert!(lo >= 1, "log-uniform sampling requires lo >= 1");
-        assert!(lo <= hi);
-
-        let lo_f = lo as f64;
-        let hi_f = hi as f64;
-
-        let log_lo = lo_f.ln();
-        let log_hi = hi_f.ln();
-
-        let r: f64 = rand::rng().random();
-        let log_val = log_lo + r * (log_hi - log_lo);
-
-        (log_val.exp() as u64).clamp(lo, hi)
     }
 
     // -------------------------------------------------------------------------
@@ -213,15 +176,21 @@ mod simulations {
     // -------------------------------------------------------------------------
 
     #[rstest]
+    #[case::reference(LiveVault::Reference)]
+    #[case::capped(LiveVault::Capped)]
+    #[case::token_2022_asset(LiveVault::Token2022Asset)]
+    #[case::locked_profit(LiveVault::LockedProfit)]
+    #[case::high_fees(LiveVault::HighFees)]
     #[tokio::test]
-    #[case("GqoypwVGG35JSR1AwCm2jeqJPUPvA4cWE45rSbfxHgdK")]
-    async fn test_bound_simulation(#[case] vault_key: Pubkey) {
+    async fn test_bound_simulation(#[case] live_vault: LiveVault) {
         init_test_logger();
 
         // Fetch live vault data from RPC
         let rpc_url = env::var("SOLANA_RPC_URL").unwrap();
         let rpc = RpcClient::new(rpc_url);
-        let vault_account = rpc.get_account(&vault_key).await.unwrap();
+        let Some((vault_key, vault_account)) = mainnet::fetch_case(&rpc, live_vault).await else {
+            return;
+        };
 
         // Build venue + load state
         let cache = RpcClientCache::new(rpc);
@@ -245,10 +214,18 @@ mod simulations {
         let tradable_mints = venue.get_token_info();
         assert_eq!(tradable_mints.len(), 2);
 
-        for (in_idx, out_idx) in [(0, 1), (1, 0)] {
+        for (in_idx, out_idx, direction) in [
+            (0, 1, SwapDirection::Deposit),
+            (1, 0, SwapDirection::Redeem),
+        ] {
             let (lower, upper) = venue.bounds(in_idx as u8, out_idx as u8).unwrap();
+            let probes = boundary_probe_amounts(&venue, direction).unwrap();
 
-            for bound in [lower, upper] {
+            // Probes past the bounds are refused; the rest must execute as quoted.
+            for bound in probes
+                .into_iter()
+                .filter(|amount| (lower..=upper).contains(amount))
+            {
                 let request = QuoteRequest {
                     input_mint: venue.get_token(in_idx).unwrap().pubkey,
                     output_mint: venue.get_token(out_idx).unwrap().pubkey,
@@ -280,15 +257,21 @@ mod simulations {
     // -------------------------------------------------------------------------
 
     #[rstest]
+    #[case::reference(LiveVault::Reference)]
+    #[case::capped(LiveVault::Capped)]
+    #[case::token_2022_asset(LiveVault::Token2022Asset)]
+    #[case::locked_profit(LiveVault::LockedProfit)]
+    #[case::high_fees(LiveVault::HighFees)]
     #[tokio::test]
-    #[case("GqoypwVGG35JSR1AwCm2jeqJPUPvA4cWE45rSbfxHgdK")]
-    async fn test_random_samples(#[case] vault_key: Pubkey) {
+    async fn test_random_samples(#[case] live_vault: LiveVault) {
         init_test_logger();
 
         // Fetch live vault data from RPC
         let rpc_url = env::var("SOLANA_RPC_URL").unwrap();
         let rpc = RpcClient::new(rpc_url);
-        let vault_account = rpc.get_account(&vault_key).await.unwrap();
+        let Some((vault_key, vault_account)) = mainnet::fetch_case(&rpc, live_vault).await else {
+            return;
+        };
 
         let cache = RpcClientCache::new(rpc);
         let mut venue = VoltrVaultVenue::from_account(&vault_key, &vault_account).unwrap();
@@ -310,12 +293,11 @@ mod simulations {
         let tradable_mints = venue.get_token_info();
         assert_eq!(tradable_mints.len(), 2);
 
-        for (in_idx, out_idx) in [(0, 1), (1, 0)] {
-            let (lb, ub) = venue.bounds(in_idx as u8, out_idx as u8).unwrap();
-
-            for _ in 0..50 {
-                let amount = sample_log_uniform_u64(lb, ub);
-
+        for (in_idx, out_idx, direction) in [
+            (0, 1, SwapDirection::Deposit),
+            (1, 0, SwapDirection::Redeem),
+        ] {
+            for amount in sample_amounts_for_direction(&venue, direction, 50).unwrap() {
                 let request = QuoteRequest {
                     input_mint: venue.get_token(in_idx).unwrap().pubkey,
                     output_mint: venue.get_token(out_idx).unwrap().pubkey,
@@ -346,35 +328,29 @@ mod simulations {
     // -------------------------------------------------------------------------
 
     #[rstest]
+    // No fees.
+    #[case(0, 0)]
+    // Issuance and redemption fees, rounding on every quote.
+    #[case(37, 25)]
     #[tokio::test]
-    #[case("GqoypwVGG35JSR1AwCm2jeqJPUPvA4cWE45rSbfxHgdK")]
-    async fn test_monotone(#[case] vault_key: String) {
+    async fn test_monotone(#[case] issuance_fee: u16, #[case] redemption_fee: u16) {
         init_test_logger();
 
         //
-        // Prepare inputs
-        //
-        let vault_key = Pubkey::from_str(&vault_key).expect("Invalid test pubkey");
-
-        let rpc_url =
-            env::var("SOLANA_RPC_URL").expect("SOLANA_RPC_URL must be set for integration tests");
-        let rpc = RpcClient::new(rpc_url);
-
+        // Construct the venue from a fixture vault
         //
-        // Fetch the vault account and construct the venue
-        //
-        let vault_account = rpc
-            .get_account(&vault_key)
-            .await
-            .expect("Failed to fetch vault account");
-
-        let mut venue = VoltrVaultVenue::from_account(&vault_key, &vault_account)
+        let fixture = VaultFixture {
+            issuance_fee,
+            redemption_fee,
+            ..Default::default()
+        };
+        let mut venue = VoltrVaultVenue::from_account(&fixture.vault_key, &fixture.vault_account())
             .expect("Failed to construct venue from account");
 
         //
-        // Load on-chain state using the caching layer
+        // Load the fixture's state from a static cache
         //
-        let cache = RpcClientCache::new(rpc);
+        let cache = StaticCache::from_pairs(&fixture.accounts());
         venue
             .update_state(&cache)
             .await
@@ -393,13 +369,11 @@ mod simulations {
         // For each direction (deposit: asset→LP, redeem: LP→asset)
         // verify the swap function is monotone increasing.
         //
-        for (in_idx, out_idx) in [(0, 1), (1, 0)] {
-            let (lb, ub) = venue.bounds(in_idx, out_idx).unwrap();
-            let mut test_amounts = Vec::with_capacity(50);
-
-            for _ in 0..50 {
-                test_amounts.push(sample_log_uniform_u64(lb, ub));
-            }
+        for (in_idx, out_idx, direction) in [
+            (0, 1, SwapDirection::Deposit),
+            (1, 0, SwapDirection::Redeem),
+        ] {
+            let mut test_amounts = sample_amounts_for_direction(&venue, direction, 50).unwrap();
             test_amounts.sort();
 
             let mut prev = 0;
@@ -435,35 +409,31 @@ mod simulations {
     // -------------------------------------------------------------------------
 
     #[rstest]
+    // Management fees and locked profit projected on every quote.
+    #[case(10_000)]
     #[tokio::test]
-    #[case("GqoypwVGG35JSR1AwCm2jeqJPUPvA4cWE45rSbfxHgdK", 10_000)]
-    async fn test_quoting_speed(#[case] vault_key: String, #[case] iterations: usize) {
+    async fn test_quoting_speed(#[case] iterations: usize) {
         init_test_logger();
 
         //
-        // Prepare inputs
+        // Construct the venue from a fixture vault
         //
-        let vault_key = Pubkey::from_str(&vault_key).expect("Invalid test pubkey");
-
-        let rpc_url =
-            env::var("SOLANA_RPC_URL").expect("SOLANA_RPC_URL must be set for integration tests");
-        let rpc = RpcClient::new(rpc_url);
-
-        //
-        // Fetch the vault account and construct the venue
-        //
-        let vault_account = rpc
-            .get_account(&vault_key)
-            .await
-            .expect("Failed to fetch vault account");
-
-        let mut venue = VoltrVaultVenue::from_account(&vault_key, &vault_account)
+        let fixture = VaultFixture {
+            manager_management_fee: 200,
+            issuance_fee: 10,
+            redemption_fee: 25,
+            locked_profit_degradation_duration: 86_400,
+            last_updated_locked_profit: 10_000_000_000,
+            ..Default::default()
+        };
+        let mut venue = VoltrVaultVenue::from_account(&fixture.vault_key, &fixture.vault_account())
             .expect("Failed to construct venue from account");
 
         //
-        // Load on-chain state using the caching layer
+        // Load the fixture's state through a closure
         //
-        let cache = RpcClientCache::new(rpc);
+        let accounts: HashMap<Pubkey, Account> = fixture.accounts().into_iter().collect();
+        let cache = ClosureCache::new(move |pubkey: &Pubkey| accounts.get(pubkey).cloned());
         venue
             .update_state(&cache)
             .await
@@ -482,16 +452,14 @@ mod simulations {
         // For each direction (deposit: asset→LP, redeem: LP→asset)
         // verify quoting speed requirements are met.
         //
-        for (in_idx, out_idx) in [(0, 1), (1, 0)] {
+        for (in_idx, out_idx, direction) in [
+            (0, 1, SwapDirection::Deposit),
+            (1, 0, SwapDirection::Redeem),
+        ] {
             let input_mint = token_info[in_idx as usize].pubkey;
             let output_mint = token_info[out_idx as usize].pubkey;
 
-            let (lb, ub) = venue.bounds(in_idx, out_idx).unwrap();
-            let mut test_amounts = Vec::with_capacity(iterations);
-
-            for _ in 0..iterations {
-                test_amounts.push(sample_log_uniform_u64(lb, ub));
-            }
+            let test_amounts = sample_amounts_for_direction(&venue, direction, iterations).unwrap();
 
             let start = Instant::now();
             for amount in test_amounts {
@@ -519,4 +487,592 @@ mod simulations {
             );
         }
     }
+
+    // -------------------------------------------------------------------------
+    // Test 5: Max cap edge in simulation
+    // -------------------------------------------------------------------------
+
+    #[rstest]
+    #[case(LiveVault::Reference, 1_000_000, false)]
+    #[case(LiveVault::Reference, 1_000_000, true)]
+    #[tokio::test]
+    async fn test_max_cap_edge_simulation(
+        #[case] live_vault: LiveVault,
+        #[case] room: u64,
+        #[case] pending_fees: bool,
+    ) {
+        init_test_logger();
+
+        let rpc_url = env::var("SOLANA_RPC_URL").unwrap();
+        let rpc = RpcClient::new(rpc_url);
+        let Some((vault_key, mut vault_account)) = mainnet::fetch_case(&rpc, live_vault).await else {
+            return;
+        };
+
+        let latest_clock: Clock = rpc
+            .get_account(&clock::ID)
+            .await
+            .unwrap()
+            .deserialize_data()
+            .unwrap();
+        let now = latest_clock.unix_timestamp as u64;
+
+        // Cap the live vault so that exactly `room` more asset fits;
+        // `vault_configuration.max_cap` is the u64 at offset 8 + 424.
+        let live = VoltrVaultVenue::from_account(&vault_key, &vault_account).unwrap();
+        let total_value = live.vault_state.get_total_asset_value();
+        let max_cap = total_value + room;
+        let mut patch = |offset: usize, bytes: &[u8]| {
+            vault_account.data[8 + offset..8 + offset + bytes.len()].copy_from_slice(bytes);
+        };
+        patch(424, &max_cap.to_le_bytes());
+
+        if pending_fees {
+            // A year of unaccrued 10% management fee and a fresh locked
+            // profit report worth 10% of the vault: the deposit accrues both
+            // before its cap check, if the program checks after accrual.
+            patch(440, &86_400u64.to_le_bytes());
+            patch(508, &1_000u16.to_le_bytes());
+            patch(560, &(now - ONE_YEAR_U64).to_le_bytes());
+            patch(664, &(total_value / 10).to_le_bytes());
+            patch(672, &now.to_le_bytes());
+        }
+
+        let cache = PatchedCache {
+            inner: RpcClientCache::new(rpc),
+            patched: HashMap::from([(vault_key, vault_account.clone())]),
+        };
+        let mut venue = VoltrVaultVenue::from_account(&vault_key, &vault_account).unwrap();
+        venue.update_state(&cache).await.unwrap();
+        assert_eq!(venue.vault_state.vault_configuration.max_cap, max_cap);
+
+        let (mut litesvm, keypair) = setup_litesvm();
+        litesvm.set_sysvar::<Clock>(&latest_clock);
+
+        // The venue's upper deposit bound lands exactly on the cap.
+        assert_eq!(venue.bounds(0, 1).unwrap().1, room);
+
+        for (amount, fits) in [(room, true), (room + 1, false)] {
+            let request = QuoteRequest {
+                input_mint: venue.get_token(0).unwrap().pubkey,
+                output_mint: venue.get_token(1).unwrap().pubkey,
+                amount,
+                swap_type: SwapType::ExactIn,
+            };
+
+            let sim =
+                try_sim_quote_request(&venue, &cache, request.clone(), &mut litesvm, &keypair)
+                    .await;
+            let quote = venue.quote(request).unwrap();
+
+            log::debug!(
+                "Cap edge: amount = {}\nSimulated = {:?}\nOff-chain quote = {:?}",
+                amount,
+                sim,
+                quote
+            );
+
+            if fits {
+                assert!(!quote.not_enough_liquidity);
+                assert_eq!(sim.unwrap(), quote.expected_output);
+            } else {
+                assert!(quote.not_enough_liquidity);
+                assert!(sim.is_err(), "program accepted a deposit above max_cap");
+            }
+        }
+    }
+
+    // -------------------------------------------------------------------------
+    // Test 6: Execution reconciliation
+    // -------------------------------------------------------------------------
+
+    #[rstest]
+    #[case::reference(LiveVault::Reference)]
+    #[case::capped(LiveVault::Capped)]
+    #[case::token_2022_asset(LiveVault::Token2022Asset)]
+    #[case::locked_profit(LiveVault::LockedProfit)]
+    #[case::high_fees(LiveVault::HighFees)]
+    #[tokio::test]
+    async fn test_reconcile_execution(#[case] live_vault: LiveVault) {
+        init_test_logger();
+
+        let rpc_url = env::var("SOLANA_RPC_URL").unwrap();
+        let rpc = RpcClient::new(rpc_url);
+        let Some((vault_key, vault_account)) = mainnet::fetch_case(&rpc, live_vault).await else {
+            return;
+        };
+
+        let cache = RpcClientCache::new(rpc);
+        let mut venue = VoltrVaultVenue::from_account(&vault_key, &vault_account).unwrap();
+        venue.update_state(&cache).await.unwrap();
+
+        let (mut litesvm, keypair) = setup_litesvm();
+        let latest_clock = cache.get_account(&clock::ID).await.unwrap();
+        let latest_clock: Clock = latest_clock
+            .as_ref()
+            .ok_or(TradingVenueError::NoAccountFound(clock::ID.into()))
+            .unwrap()
+            .deserialize_data()
+            .unwrap();
+        litesvm.set_sysvar::<Clock>(&latest_clock);
+
+        for (in_idx, out_idx) in [(0, 1), (1, 0)] {
+            let (lower, upper) = venue.bounds(in_idx as u8, out_idx as u8).unwrap();
+            let request = QuoteRequest {
+                input_mint: venue.get_token(in_idx).unwrap().pubkey,
+                output_mint: venue.get_token(out_idx).unwrap().pubkey,
+                amount: lower.saturating_mul(1_000).min(upper),
+                swap_type: SwapType::ExactIn,
+            };
+
+            let quoted_at = venue.effective_ts();
+            let quote = venue.quote_at(&request, quoted_at).unwrap();
+            let (pre, post) =
+                try_sim_balances(&venue, &cache, request.clone(), &mut litesvm, &keypair)
+                    .await
+                    .unwrap();
+            let report = venue
+                .reconcile_execution(&request, &quote, quoted_at, pre, post)
+                .unwrap();
+            log::debug!("Direction = ({} -> {})\n{:#?}", in_idx, out_idx, report);
+
+            assert_eq!(report.input_spent, request.amount);
+            assert_eq!(report.output_received, post.output - pre.output);
+            assert_eq!(report.slippage, 0);
+            // Fees only ever reduce the output.
+            assert!(report.implied_fee >= 0);
+        }
+    }
+
+    // -------------------------------------------------------------------------
+    // Test 7: Multi-user deposit batch in one transaction
+    // -------------------------------------------------------------------------
+
+    #[rstest]
+    #[case::reference(LiveVault::Reference)]
+    #[case::capped(LiveVault::Capped)]
+    #[case::token_2022_asset(LiveVault::Token2022Asset)]
+    #[case::locked_profit(LiveVault::LockedProfit)]
+    #[case::high_fees(LiveVault::HighFees)]
+    #[tokio::test]
+    async fn test_deposit_batch_simulation(#[case] live_vault: LiveVault) {
+        init_test_logger();
+
+        let rpc_url = env::var("SOLANA_RPC_URL").unwrap();
+        let rpc = RpcClient::new(rpc_url);
+        let Some((vault_key, vault_account)) = mainnet::fetch_case(&rpc, live_vault).await else {
+            return;
+        };
+
+        let cache = RpcClientCache::new(rpc);
+        let mut venue = VoltrVaultVenue::from_account(&vault_key, &vault_account).unwrap();
+        venue.update_state(&cache).await.unwrap();
+
+        let (mut litesvm, payer) = setup_litesvm();
+        let latest_clock = cache.get_account(&clock::ID).await.unwrap();
+        let latest_clock: Clock = latest_clock
+            .as_ref()
+            .ok_or(TradingVenueError::NoAccountFound(clock::ID.into()))
+            .unwrap()
+            .deserialize_data()
+            .unwrap();
+        litesvm.set_sysvar::<Clock>(&latest_clock);
+
+        let asset_mint = venue.get_token(0).unwrap().pubkey;
+        let lp_mint = venue.get_token(1).unwrap().pubkey;
+        let (lower, upper) = venue.bounds(0, 1).unwrap();
+        let users: Vec<Keypair> = (0..5).map(|_| Keypair::new()).collect();
+
+        // Small deposits relative to the vault, so each one barely moves the
+        // share price seen by the next.
+        let requests: Vec<(QuoteRequest, Pubkey)> = users
+            .iter()
+            .enumerate()
+            .map(|(i, user)| {
+                let request = QuoteRequest {
+                    input_mint: asset_mint,
+                    output_mint: lp_mint,
+                    amount: lower.saturating_mul(100 * (i as u64 + 1)).min(upper / 10),
+                    swap_type: SwapType::ExactIn,
+                };
+                (request, user.pubkey())
+            })
+            .collect();
+        let quotes: Vec<u64> = requests
+            .iter()
+            .map(|(request, _)| venue.quote(request.clone()).unwrap().expected_output)
+            .collect();
+
+        for user in &users {
+            let wallet = Account::new(LAMPORTS_PER_SOL, 0, &solana_sdk::system_program::id());
+            litesvm.set_account(user.pubkey(), wallet).unwrap();
+
+            let mut source = Account::new(LAMPORTS_PER_SOL, TokenAccount::LEN, &spl_token::ID);
+            TokenAccount {
+                mint: asset_mint,
+                owner: user.pubkey(),
+                state: AccountState::Initialized,
+                amount: u64::MAX,
+                ..Default::default()
+            }
+            .pack_into_slice(source.data_as_mut_slice());
+            let source_key = get_associated_token_address_with_program_id(
+                &user.pubkey(),
+                &asset_mint,
+                &spl_token::ID,
+            );
+            litesvm.set_account(source_key, source).unwrap();
+        }
+
+        let batch = venue.generate_swap_instructions_batch(&requests).unwrap();
+        let mut instructions = vec![
+            solana_sdk::compute_budget::ComputeBudgetInstruction::set_compute_unit_limit(1_400_000),
+        ];
+        instructions.extend(batch.instructions.iter().flatten().cloned());
+
+        // Load the shared accounts; the users' accounts are already in place.
+        let pks: Vec<Pubkey> = instructions
+            .iter()
+            .flat_map(|ix| ix.accounts.iter().map(|meta| meta.pubkey))
+            .collect();
+        for (account, key) in cache.get_accounts(&pks).await.unwrap().into_iter().zip(pks) {
+            if let Some(account) = account
+                && !account.executable
+                && litesvm.get_account(&key).is_none()
+            {
+                litesvm.set_account(key, account).unwrap();
+            }
+        }
+
+        let mut signers: Vec<&Keypair> = vec![&payer];
+        signers.extend(&users);
+        let tx = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&payer.pubkey()),
+            &signers,
+            litesvm.latest_blockhash(),
+        );
+        let result = litesvm.simulate_transaction(tx).unwrap();
+
+        for ((user, (request, _)), quote) in users.iter().zip(&requests).zip(quotes) {
+            let lp_ata = get_associated_token_address_with_program_id(
+                &user.pubkey(),
+                &lp_mint,
+                &spl_token::ID,
+            );
+            let account = result
+                .post_accounts
+                .iter()
+                .find(|(pk, _)| pk == &lp_ata)
+                .map(|(_, acc)| acc)
+                .unwrap();
+            let received = TokenAccount::unpack_from_slice(account.data())
+                .unwrap()
+                .amount;
+
+            log::debug!(
+                "Deposit {} -> simulated {}, quoted {}",
+                request.amount,
+                received,
+                quote
+            );
+            // Earlier deposits in the batch leave their issuance fee in the
+            // vault, which can cost later users a rounding unit.
+            assert!(received.abs_diff(quote) <= 1);
+        }
+    }
+
+    // -------------------------------------------------------------------------
+    // Test 8: Issuance fee rounding in simulation
+    // -------------------------------------------------------------------------
+
+    /// `count` amounts from `base` up whose issuance fee
+    /// `amount * fee_bps / 10_000` is an integer or within one ten-thousandth
+    /// of one: where computing the fee separately and rounding it would
+    /// diverge from flooring the whole deposit formula once.
+    fn fee_boundary_amounts(base: u64, fee_bps: u16, count: usize) -> Vec<u64> {
+        (base..)
+            .filter(|amount| {
+                matches!(
+                    (*amount as u128 * fee_bps as u128) % 10_000,
+                    0 | 1 | 9_999
+                )
+            })
+            .take(count)
+            .collect()
+    }
+
+    #[rstest]
+    #[case(LiveVault::Reference, 1)]
+    #[case(LiveVault::Reference, 37)]
+    #[case(LiveVault::Reference, 500)]
+    #[case(LiveVault::Reference, 9_999)]
+    #[tokio::test]
+    async fn test_issuance_fee_rounding_simulation(
+        #[case] live_vault: LiveVault,
+        #[case] issuance_fee: u16,
+    ) {
+        init_test_logger();
+
+        let rpc_url = env::var("SOLANA_RPC_URL").unwrap();
+        let rpc = RpcClient::new(rpc_url);
+        let Some((vault_key, mut vault_account)) = mainnet::fetch_case(&rpc, live_vault).await else {
+            return;
+        };
+
+        let latest_clock: Clock = rpc
+            .get_account(&clock::ID)
+            .await
+            .unwrap()
+            .deserialize_data()
+            .unwrap();
+
+        // `fee_configuration.issuance_fee` is the u16 at offset 8 + 514.
+        vault_account.data[8 + 514..8 + 516].copy_from_slice(&issuance_fee.to_le_bytes());
+
+        let cache = PatchedCache {
+            inner: RpcClientCache::new(rpc),
+            patched: HashMap::from([(vault_key, vault_account.clone())]),
+        };
+        let mut venue = VoltrVaultVenue::from_account(&vault_key, &vault_account).unwrap();
+        venue.update_state(&cache).await.unwrap();
+        assert_eq!(
+            venue.vault_state.fee_configuration.issuance_fee,
+            issuance_fee
+        );
+
+        let (mut litesvm, keypair) = setup_litesvm();
+        litesvm.set_sysvar::<Clock>(&latest_clock);
+
+        let (lower, upper) = venue.bounds(0, 1).unwrap();
+        let mut amounts: Vec<u64> = [1_000, 1_000_000, 1_000_000_000]
+            .into_iter()
+            .flat_map(|base| fee_boundary_amounts(base, issuance_fee, 8))
+            .flat_map(|amount| [amount - 1, amount, amount + 1])
+            .filter(|amount| (lower..=upper).contains(amount))
+            .collect();
+        amounts.dedup();
+
+        let mut mismatches = Vec::new();
+        for amount in amounts {
+            let request = QuoteRequest {
+                input_mint: venue.get_token(0).unwrap().pubkey,
+                output_mint: venue.get_token(1).unwrap().pubkey,
+                amount,
+                swap_type: SwapType::ExactIn,
+            };
+            let sim =
+                sim_quote_request(&venue, &cache, request.clone(), &mut litesvm, &keypair).await;
+            let quote = venue.quote(request).unwrap().expected_output;
+            if sim != quote {
+                mismatches.push((amount, sim, quote));
+            }
+        }
+
+        assert!(
+            mismatches.is_empty(),
+            "issuance fee {issuance_fee} bps: (amount, simulated, quoted) {mismatches:?}"
+        );
+    }
+
+    // -------------------------------------------------------------------------
+    // Test 9: Redeem at the idle balance limit in simulation
+    // -------------------------------------------------------------------------
+
+    #[rstest]
+    #[case::reference(LiveVault::Reference)]
+    #[case::capped(LiveVault::Capped)]
+    #[case::token_2022_asset(LiveVault::Token2022Asset)]
+    #[case::locked_profit(LiveVault::LockedProfit)]
+    #[case::high_fees(LiveVault::HighFees)]
+    #[tokio::test]
+    async fn test_redeem_idle_limit_simulation(#[case] live_vault: LiveVault) {
+        init_test_logger();
+
+        let rpc_url = env::var("SOLANA_RPC_URL").unwrap();
+        let rpc = RpcClient::new(rpc_url);
+        let Some((vault_key, vault_account)) = mainnet::fetch_case(&rpc, live_vault).await else {
+            return;
+        };
+
+        let latest_clock: Clock = rpc
+            .get_account(&clock::ID)
+            .await
+            .unwrap()
+            .deserialize_data()
+            .unwrap();
+        let now = latest_clock.unix_timestamp as u64;
+
+        let mut cache = PatchedCache {
+            inner: RpcClientCache::new(rpc),
+            patched: HashMap::new(),
+        };
+        let mut venue = VoltrVaultVenue::from_account(&vault_key, &vault_account).unwrap();
+        venue.update_state(&cache).await.unwrap();
+
+        // Leave half the unlocked value idle, so that the idle balance and
+        // not the unlocked value limits redeems. An SPL token account's
+        // `amount` is the u64 at offset 64.
+        let unlocked = venue.vault_snapshot().unwrap().unlocked_value(now).unwrap();
+        let idle = unlocked / 2;
+        let idle_ata = venue.vault_state.asset.idle_ata;
+        let mut idle_account = cache.get_account(&idle_ata).await.unwrap().unwrap();
+        idle_account.data[64..72].copy_from_slice(&idle.to_le_bytes());
+        cache.patched.insert(idle_ata, idle_account);
+        venue.update_state(&cache).await.unwrap();
+        assert_eq!(venue.asset_idle_balance, idle);
+
+        // Invert the payout formula for the largest LP amount paying at most
+        // `idle`, then settle its rounding against the exact payout.
+        let snapshot = venue.vault_snapshot().unwrap();
+        let supply = snapshot.total_lp_supply_incl_fees().unwrap()
+            + snapshot.pending_management_fee_lp(now).unwrap();
+        let fee_bps = snapshot.redemption_fee_bps;
+        let payout =
+            |lp: u64| calc_withdraw_asset_to_redeem(lp, supply, unlocked, fee_bps).unwrap();
+        let mut edge = ((idle as u128 + 1) * supply as u128 * MAX_FEE_BPS as u128
+            / (unlocked as u128 * (MAX_FEE_BPS - fee_bps) as u128)) as u64;
+        while payout(edge) > idle {
+            edge -= 1;
+        }
+        while payout(edge + 1) <= idle {
+            edge += 1;
+        }
+        log::debug!(
+            "Idle limit: idle = {}\nAnalytic LP edge = {}\nVenue bounds = {:?}",
+            idle,
+            edge,
+            venue.bounds(1, 0).unwrap()
+        );
+
+        let (mut litesvm, keypair) = setup_litesvm();
+        litesvm.set_sysvar::<Clock>(&latest_clock);
+
+        for (amount, fits) in [(edge, true), (edge + 1, false)] {
+            let request = QuoteRequest {
+                input_mint: venue.get_token(1).unwrap().pubkey,
+                output_mint: venue.get_token(0).unwrap().pubkey,
+                amount,
+                swap_type: SwapType::ExactIn,
+            };
+
+            let sim =
+                try_sim_quote_request(&venue, &cache, request.clone(), &mut litesvm, &keypair)
+                    .await;
+            let quote = venue.quote_at(&request, now).unwrap();
+
+            log::debug!(
+                "Idle edge: amount = {}\nSimulated = {:?}\nOff-chain quote = {:?}",
+                amount,
+                sim,
+                quote
+            );
+
+            // The venue's classification matches the program's either way.
+            assert_eq!(quote.not_enough_liquidity, !fits);
+            assert_eq!(
+                sim.is_ok(),
+                fits,
+                "program disagrees at {amount} LP: {sim:?}"
+            );
+            if fits {
+                assert_eq!(sim.unwrap(), quote.expected_output);
+                assert!(quote.expected_output <= idle);
+            }
+        }
+    }
+
+    // -------------------------------------------------------------------------
+    // Test 10: Quote error bound against simulation
+    // -------------------------------------------------------------------------
+
+    #[rstest]
+    #[case::reference(LiveVault::Reference)]
+    #[case::capped(LiveVault::Capped)]
+    #[case::token_2022_asset(LiveVault::Token2022Asset)]
+    #[case::locked_profit(LiveVault::LockedProfit)]
+    #[case::high_fees(LiveVault::HighFees)]
+    #[tokio::test]
+    async fn test_quote_error_bound_simulation(#[case] live_vault: LiveVault) {
+        init_test_logger();
+        const WINDOW: u64 = 600;
+
+        let rpc_url = env::var("SOLANA_RPC_URL").unwrap();
+        let rpc = RpcClient::new(rpc_url);
+        let Some((vault_key, vault_account)) = mainnet::fetch_case(&rpc, live_vault).await else {
+            return;
+        };
+
+        let latest_clock: Clock = rpc
+            .get_account(&clock::ID)
+            .await
+            .unwrap()
+            .deserialize_data()
+            .unwrap();
+        let now = latest_clock.unix_timestamp as u64;
+
+        let cache = RpcClientCache::new(rpc);
+        let mut venue = VoltrVaultVenue::from_account(&vault_key, &vault_account)
+            .unwrap()
+            .with_config(VoltrVenueConfig {
+                quote_error_window_secs: WINDOW,
+                clock: QuoteClock::Fixed(now),
+                ..Default::default()
+            });
+        venue.update_state(&cache).await.unwrap();
+
+        let (mut litesvm, keypair) = setup_litesvm();
+        let mut rng = rand::rng();
+        let mut checked = 0;
+
+        for (in_idx, out_idx, direction) in [
+            (0, 1, SwapDirection::Deposit),
+            (1, 0, SwapDirection::Redeem),
+        ] {
+            for amount in sample_amounts_for_direction(&venue, direction, 20).unwrap() {
+                let request = QuoteRequest {
+                    input_mint: venue.get_token(in_idx).unwrap().pubkey,
+                    output_mint: venue.get_token(out_idx).unwrap().pubkey,
+                    amount,
+                    swap_type: SwapType::ExactIn,
+                };
+                let quote = venue.quote(request.clone()).unwrap();
+                let bound = venue.quote_error_bound(&request).unwrap();
+                // Rejected quotes have no execution to bound.
+                if quote.not_enough_liquidity || quote.expected_output == 0 {
+                    continue;
+                }
+
+                // Execute somewhere in the window around the quote's time.
+                let executed_at = now - WINDOW + rng.random_range(0..=2 * WINDOW);
+                litesvm.set_sysvar::<Clock>(&Clock {
+                    unix_timestamp: executed_at as i64,
+                    ..latest_clock.clone()
+                });
+                let sim =
+                    try_sim_quote_request(&venue, &cache, request.clone(), &mut litesvm, &keypair)
+                        .await
+                        .unwrap_or_else(|err| {
+                            panic!(
+                                "simulating {} at now {executed_at} failed: {err}",
+                                request.amount
+                            )
+                        });
+                checked += 1;
+
+                log::debug!(
+                    "Direction = ({} -> {})\nExecuted at = now {:+}s\nSimulated = {}\nQuote = {} ± {}",
+                    in_idx,
+                    out_idx,
+                    executed_at as i64 - now as i64,
+                    sim,
+                    quote.expected_output,
+                    bound
+                );
+
+                assert!(quote.expected_output.abs_diff(sim) <= bound);
+            }
+        }
+        assert!(checked > 0, "no quote was checked against execution");
+    }
 }
@@ -0,0 +1,3290 @@
+mod common;
+
+#[cfg(test)]
+mod test_builder {
+    //! Construction paths agree:
+    //! - `builder()` yields the same venue as `new()`/`new_uninitialized()`
+    //!   with `with_config()`,
+    //! - the `TryFrom` conversions match `FromAccount` and `Vault::load`.
+
+    use solana_account::Account;
+    use solana_pubkey::Pubkey;
+    use titan_integration_template::trading_venue::{
+        FromAccount, QuoteRequest, SwapType, TradingVenue,
+    };
+
+    use titan_voltr_integration::config::{MintMetadata, VoltrVenueConfig};
+    use titan_voltr_integration::constants::TOKEN_PROGRAM;
+    use titan_voltr_integration::state::Vault;
+    use titan_voltr_integration::voltr_venue::VoltrVaultVenue;
+
+    use crate::common::VaultFixture;
+
+    fn fixture() -> VaultFixture {
+        VaultFixture {
+            manager_management_fee: 150,
+            issuance_fee: 20,
+            redemption_fee: 30,
+            idle_balance: 400_000_000_000,
+            ..Default::default()
+        }
+    }
+
+    fn config() -> VoltrVenueConfig {
+        VoltrVenueConfig {
+            max_share_price_change_bps: Some(500),
+            stale_accounting_haircut_bps: 10,
+            ..Default::default()
+        }
+    }
+
+    fn requests(fixture: &VaultFixture) -> Vec<QuoteRequest> {
+        [1_000, 5_000_000, 300_000_000_000]
+            .into_iter()
+            .flat_map(|amount| {
+                [
+                    (fixture.asset_mint, fixture.lp_mint()),
+                    (fixture.lp_mint(), fixture.asset_mint),
+                ]
+                .map(|(input_mint, output_mint)| QuoteRequest {
+                    input_mint,
+                    output_mint,
+                    amount,
+                    swap_type: SwapType::ExactIn,
+                })
+            })
+            .collect()
+    }
+
+    async fn assert_same_quotes(
+        fixture: &VaultFixture,
+        mut a: VoltrVaultVenue,
+        mut b: VoltrVaultVenue,
+    ) {
+        a.update_state(&fixture.cache()).await.unwrap();
+        b.update_state(&fixture.cache()).await.unwrap();
+        assert_eq!(
+            a.config().max_share_price_change_bps,
+            b.config().max_share_price_change_bps
+        );
+        assert_eq!(
+            a.config().known_mint_metadata,
+            b.config().known_mint_metadata
+        );
+        for request in requests(fixture) {
+            let (qa, qb) = (a.quote(request.clone()).unwrap(), b.quote(request).unwrap());
+            assert_eq!(qa.expected_output, qb.expected_output);
+            assert_eq!(qa.not_enough_liquidity, qb.not_enough_liquidity);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_builder_matches_setters() {
+        let fixture = fixture();
+        let vault = Vault::load(&fixture.vault_data()).unwrap();
+        let metadata = MintMetadata {
+            decimals: fixture.asset_decimals,
+            token_program: TOKEN_PROGRAM,
+        };
+
+        let built = VoltrVaultVenue::builder(fixture.vault_key)
+            .vault_state(vault.clone())
+            .config(config())
+            .known_mint_metadata(metadata)
+            .build();
+        let assembled =
+            VoltrVaultVenue::new(fixture.vault_key, vault).with_config(VoltrVenueConfig {
+                known_mint_metadata: Some(metadata),
+                ..config()
+            });
+        assert_same_quotes(&fixture, built, assembled).await;
+
+        let built = VoltrVaultVenue::builder(fixture.vault_key)
+            .config(config())
+            .build();
+        assert!(!built.initialized());
+        let assembled = VoltrVaultVenue::new_uninitialized(fixture.vault_key).with_config(config());
+        assert_same_quotes(&fixture, built, assembled).await;
+    }
+
+    #[tokio::test]
+    async fn test_try_from_matches_loaders() {
+        let fixture = fixture();
+        let account = fixture.vault_account();
+
+        let converted = VoltrVaultVenue::try_from((&fixture.vault_key, &account)).unwrap();
+        let loaded = VoltrVaultVenue::from_account(&fixture.vault_key, &account).unwrap();
+        assert_same_quotes(&fixture, converted, loaded).await;
+
+        let vault = Vault::try_from(account.data.as_slice()).unwrap();
+        assert_eq!(
+            format!("{vault:?}"),
+            format!("{:?}", Vault::load(&account.data).unwrap())
+        );
+
+        let empty = Account::default();
+        assert!(VoltrVaultVenue::try_from((&Pubkey::new_unique(), &empty)).is_err());
+        assert!(Vault::try_from(&[0u8; 16][..]).is_err());
+    }
+}
+
+#[cfg(test)]
+mod test_clock {
+    //! `QuoteClock` selection through the venue config:
+    //! - a fixed clock moves management fee accrual and locked-profit
+    //!   degradation exactly as `quote_at()` would, and nothing else,
+    //! - a fee-free vault without locked profit quotes the same on any clock,
+    //! - a captured sysvar timestamp advances with local time.
+
+    use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+    use solana_program::clock::Clock;
+    use solana_pubkey::Pubkey;
+    use titan_integration_template::trading_venue::{QuoteRequest, SwapType, TradingVenue};
+
+    use titan_voltr_integration::clock::QuoteClock;
+    use titan_voltr_integration::config::VoltrVenueConfig;
+    use titan_voltr_integration::voltr_venue::VoltrVaultVenue;
+
+    use crate::common::VaultFixture;
+
+    const T0: u64 = 1_700_000_000;
+    const DAY: u64 = 86_400;
+
+    fn requests(fixture: &VaultFixture) -> [QuoteRequest; 2] {
+        [
+            QuoteRequest {
+                input_mint: fixture.asset_mint,
+                output_mint: fixture.lp_mint(),
+                amount: 1_000_000_000,
+                swap_type: SwapType::ExactIn,
+            },
+            QuoteRequest {
+                input_mint: fixture.lp_mint(),
+                output_mint: fixture.asset_mint,
+                amount: 1_000_000_000,
+                swap_type: SwapType::ExactIn,
+            },
+        ]
+    }
+
+    fn venue_at(fixture: &VaultFixture, clock: QuoteClock) -> VoltrVaultVenue {
+        fixture.synthetic_venue().with_config(VoltrVenueConfig {
+            max_accounting_age_secs: Some(DAY),
+            clock,
+            ..Default::default()
+        })
+    }
+
+    /// Outputs of `requests()` on `venue`'s own clock.
+    fn outputs(venue: &VoltrVaultVenue, fixture: &VaultFixture) -> [u64; 2] {
+        requests(fixture).map(|request| venue.quote(request).unwrap().expected_output)
+    }
+
+    #[test]
+    fn test_fixed_clock_moves_only_time_dependent_terms() {
+        let fees = VaultFixture {
+            manager_management_fee: 500,
+            last_management_fee_update_ts: T0,
+            last_updated_ts: T0,
+            ..Default::default()
+        };
+        let locked = VaultFixture {
+            locked_profit_degradation_duration: DAY,
+            last_updated_locked_profit: 100_000_000_000,
+            last_report: T0,
+            last_updated_ts: T0,
+            ..Default::default()
+        };
+
+        for fixture in [&fees, &locked] {
+            let at_t0 = venue_at(fixture, QuoteClock::Fixed(T0));
+            let user = Pubkey::new_unique();
+            for ts in [T0, T0 + 3_600, T0 + DAY, T0 + 2 * DAY] {
+                let mut venue = at_t0.clone();
+                venue.set_clock(QuoteClock::Fixed(ts));
+                assert_eq!(venue.effective_ts(), ts);
+
+                // Every time-dependent output follows the clock ...
+                for request in requests(fixture) {
+                    assert_eq!(
+                        venue.quote(request.clone()).unwrap().expected_output,
+                        venue.quote_at(&request, ts).unwrap().expected_output
+                    );
+                }
+                assert_eq!(
+                    venue.health_report().stale_accounting_age.is_some(),
+                    ts > T0 + DAY
+                );
+
+                // ... while the rest of the venue stays as loaded.
+                for request in requests(fixture) {
+                    assert_eq!(
+                        venue
+                            .generate_swap_instruction(request.clone(), user)
+                            .unwrap(),
+                        at_t0.generate_swap_instruction(request, user).unwrap()
+                    );
+                }
+                assert_eq!(
+                    venue.get_required_pubkeys_for_update().unwrap(),
+                    at_t0.get_required_pubkeys_for_update().unwrap()
+                );
+            }
+        }
+
+        // Management fees accrue: deposits mint more LP and redeems pay less
+        // per LP as time passes.
+        let [deposit_t0, redeem_t0] = outputs(&venue_at(&fees, QuoteClock::Fixed(T0)), &fees);
+        let [deposit_day, redeem_day] =
+            outputs(&venue_at(&fees, QuoteClock::Fixed(T0 + DAY)), &fees);
+        assert!(deposit_day > deposit_t0);
+        assert!(redeem_day < redeem_t0);
+        let pending = |ts| {
+            venue_at(&fees, QuoteClock::Fixed(ts))
+                .accrued_fees(ts)
+                .unwrap()
+                .total()
+                .lp
+        };
+        assert_eq!(pending(T0), 0);
+        assert!(pending(T0 + DAY) > 0);
+
+        // Locked profit unlocks over the degradation duration, then stays.
+        let redeem = |ts| outputs(&venue_at(&locked, QuoteClock::Fixed(ts)), &locked)[1];
+        assert!(redeem(T0) < redeem(T0 + 3_600));
+        assert!(redeem(T0 + 3_600) < redeem(T0 + DAY));
+        assert_eq!(redeem(T0 + DAY), redeem(T0 + 2 * DAY));
+    }
+
+    #[test]
+    fn test_fee_free_vault_ignores_clock() {
+        let fixture = VaultFixture {
+            last_updated_ts: T0,
+            ..Default::default()
+        };
+        let expected = outputs(&venue_at(&fixture, QuoteClock::Fixed(T0)), &fixture);
+        for clock in [
+            QuoteClock::Fixed(T0 + 365 * DAY),
+            QuoteClock::WallClock,
+            QuoteClock::SysvarCaptured {
+                ts: T0 + DAY,
+                captured_at: Instant::now(),
+            },
+        ] {
+            assert_eq!(outputs(&venue_at(&fixture, clock), &fixture), expected);
+        }
+    }
+
+    #[test]
+    fn test_clock_sources() {
+        let fixture = VaultFixture::default();
+        let venue = fixture.synthetic_venue();
+        assert_eq!(venue.config().clock, QuoteClock::WallClock);
+        let wall = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        assert!(venue.effective_ts().abs_diff(wall) <= 1);
+
+        // A timestamp captured ten seconds ago reads ten seconds later.
+        let captured = venue_at(
+            &fixture,
+            QuoteClock::SysvarCaptured {
+                ts: T0,
+                captured_at: Instant::now() - Duration::from_secs(10),
+            },
+        );
+        assert!((T0 + 10..=T0 + 11).contains(&captured.effective_ts()));
+
+        let sysvar = QuoteClock::from_sysvar(&Clock {
+            unix_timestamp: T0 as i64,
+            ..Default::default()
+        });
+        assert_eq!(sysvar.now(), Some(T0));
+        let before_epoch = QuoteClock::from_sysvar(&Clock {
+            unix_timestamp: -5,
+            ..Default::default()
+        });
+        assert_eq!(before_epoch.now(), Some(0));
+
+        let built = VoltrVaultVenue::builder(fixture.vault_key)
+            .clock(QuoteClock::Fixed(T0))
+            .build();
+        assert_eq!(built.config().clock, QuoteClock::Fixed(T0));
+    }
+}
+
+#[cfg(test)]
+mod test_decode {
+    //! Decoding raw vault dumps:
+    //! - a base64 dump decodes to the same vault as the account data,
+    //! - malformed dumps fail with a structured error instead of panicking,
+    //! - the parsers fail with a specific `VoltrError`, which the venue
+    //!   passes on as a deserialization failure.
+
+    use base64::Engine;
+    use base64::engine::general_purpose::STANDARD;
+
+    use titan_integration_template::trading_venue::FromAccount;
+    use titan_integration_template::trading_venue::error::TradingVenueError;
+
+    use titan_voltr_integration::state::{FeeConfiguration, VAULT_MIN_DATA_LEN, Vault, VaultAsset};
+    use titan_voltr_integration::voltr_venue::VoltrVaultVenue;
+    use titan_voltr_integration::{VaultDecodeError, VoltrError};
+
+    use crate::common::VaultFixture;
+
+    #[test]
+    fn test_from_base64_matches_account_data() {
+        let data = VaultFixture::default().vault_data();
+        let decoded = Vault::from_base64(&format!("  {}\n", STANDARD.encode(&data))).unwrap();
+        let loaded = Vault::load(&data).unwrap();
+
+        assert_eq!(format!("{decoded:#?}"), format!("{loaded:#?}"));
+    }
+
+    #[test]
+    fn test_too_short() {
+        let data = VaultFixture::default().vault_data();
+        let truncated = &data[..VAULT_MIN_DATA_LEN - 1];
+
+        assert_eq!(
+            Vault::from_base64(&STANDARD.encode(truncated)).unwrap_err(),
+            VaultDecodeError::Parse(VoltrError::AccountTooShort {
+                len: VAULT_MIN_DATA_LEN - 1,
+                min: VAULT_MIN_DATA_LEN,
+            })
+        );
+        assert!(Vault::load(truncated).is_err());
+    }
+
+    #[test]
+    fn test_bad_discriminator() {
+        let mut data = VaultFixture::default().vault_data();
+        data[..8].copy_from_slice(&[1; 8]);
+
+        assert_eq!(
+            Vault::decode(&data).unwrap_err(),
+            VaultDecodeError::Parse(VoltrError::InvalidDiscriminator { found: [1; 8] })
+        );
+    }
+
+    #[test]
+    fn test_invalid_base64() {
+        assert!(matches!(
+            Vault::from_base64("not base64!").unwrap_err(),
+            VaultDecodeError::InvalidBase64(_)
+        ));
+    }
+
+    /// `from_account()` on `data` fails as a deserialization failure naming
+    /// `error`.
+    fn assert_venue_rejects(fixture: &VaultFixture, data: Vec<u8>, error: VoltrError) {
+        let account = solana_account::Account {
+            data,
+            ..fixture.vault_account()
+        };
+        match VoltrVaultVenue::from_account(&fixture.vault_key, &account) {
+            Err(TradingVenueError::DeserializationFailed(message)) => {
+                assert!(
+                    message.to_string().contains(&error.to_string()),
+                    "{message}"
+                )
+            }
+            Err(e) => panic!("unexpected error {e}"),
+            Ok(_) => panic!("{error} not detected"),
+        }
+    }
+
+    #[test]
+    fn test_typed_parse_errors() {
+        let fixture = VaultFixture::default();
+        let data = fixture.vault_data();
+
+        let truncated = data[..VAULT_MIN_DATA_LEN - 1].to_vec();
+        let too_short = VoltrError::AccountTooShort {
+            len: VAULT_MIN_DATA_LEN - 1,
+            min: VAULT_MIN_DATA_LEN,
+        };
+        assert_eq!(Vault::load(&truncated).unwrap_err(), too_short);
+        assert_eq!(Vault::try_from(&truncated[..]).unwrap_err(), too_short);
+        assert_venue_rejects(&fixture, truncated, too_short);
+        // Field parsers report how much they needed.
+        assert_eq!(
+            VaultAsset::load(&[0; 64]).unwrap_err(),
+            VoltrError::AccountTooShort { len: 64, min: 72 }
+        );
+
+        let mut foreign = data.clone();
+        foreign[..8].copy_from_slice(&[1; 8]);
+        let bad_discriminator = VoltrError::InvalidDiscriminator { found: [1; 8] };
+        assert_eq!(Vault::load(&foreign).unwrap_err(), bad_discriminator);
+        assert_venue_rejects(&fixture, foreign, bad_discriminator);
+
+        let fees = FeeConfiguration {
+            redemption_fee: 10_001,
+            ..Default::default()
+        };
+        let out_of_range = VoltrError::FieldOutOfRange {
+            field: "redemption_fee",
+        };
+        assert_eq!(fees.validate().unwrap_err(), out_of_range);
+        let overcharging = VaultFixture {
+            redemption_fee: 10_001,
+            ..fixture.clone()
+        };
+        assert_eq!(
+            Vault::load(&overcharging.vault_data())
+                .unwrap()
+                .validate()
+                .unwrap_err(),
+            out_of_range
+        );
+        assert_venue_rejects(&overcharging, overcharging.vault_data(), out_of_range);
+    }
+}
+
+#[cfg(test)]
+mod test_discovery {
+    //! Discovery classifies every account it does not turn into a venue:
+    //! - a valid vault becomes a venue with the given configuration,
+    //! - other program accounts and truncated vaults are parse errors,
+    //! - closed accounts, other assets and excluded vaults are reported as
+    //!   such,
+    //! - incremental runs report only unseen vaults, and the registry tracks
+    //!   added and closed vaults across scans and a persisted seen set.
+
+    use std::collections::HashSet;
+
+    use solana_account::Account;
+    use solana_pubkey::Pubkey;
+
+    use titan_voltr_integration::config::VoltrVenueConfig;
+    use titan_voltr_integration::discovery::{
+        discover_created_since, discover_from_accounts, DiscoveryOptions, SkipReason, VaultRegistry,
+    };
+    use titan_voltr_integration::errors::{VaultDecodeError, VoltrError};
+    use titan_voltr_integration::voltr_venue::VoltrVaultVenue;
+
+    use crate::common::{VaultFixture, VAULT_DATA_LEN};
+
+    fn receipt_account() -> Account {
+        let mut account = VaultFixture::default().vault_account();
+        account.data[..8]
+            .copy_from_slice(&solana_sdk::hash::hash(b"account:Receipt").to_bytes()[..8]);
+        account
+    }
+
+    #[test]
+    fn test_mixed_accounts_are_classified() {
+        let valid = VaultFixture::default();
+        let receipt_key = Pubkey::new_unique();
+        let receipt = receipt_account();
+        let truncated = VaultFixture::default();
+        let mut truncated_account = truncated.vault_account();
+        truncated_account.data.truncate(100);
+        let closed = VaultFixture::default();
+        let closed_account = Account {
+            lamports: 0,
+            data: Vec::new(),
+            ..closed.vault_account()
+        };
+
+        let options = DiscoveryOptions {
+            config: VoltrVenueConfig {
+                max_slot_span: 3,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let report = discover_from_accounts(
+            [
+                (valid.vault_key, valid.vault_account()),
+                (receipt_key, receipt.clone()),
+                (truncated.vault_key, truncated_account),
+                (closed.vault_key, closed_account),
+            ],
+            &options,
+        );
+
+        assert_eq!(report.venues.len(), 1);
+        let venue = &report.venues[0];
+        assert_eq!(venue.vault_key, valid.vault_key);
+        assert_eq!(venue.vault_state.asset.mint, valid.asset_mint);
+        assert_eq!(venue.config().max_slot_span, 3);
+
+        let mut found = [0u8; 8];
+        found.copy_from_slice(&receipt.data[..8]);
+        assert_eq!(
+            report.skipped,
+            vec![
+                (
+                    receipt_key,
+                    SkipReason::ParseError(VaultDecodeError::Parse(
+                        VoltrError::InvalidDiscriminator { found }
+                    ))
+                ),
+                (
+                    truncated.vault_key,
+                    SkipReason::ParseError(VaultDecodeError::Parse(VoltrError::AccountTooShort {
+                        len: 100,
+                        min: VAULT_DATA_LEN
+                    }))
+                ),
+                (closed.vault_key, SkipReason::Closed),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_asset_filter_and_exclusion_lists() {
+        let fixtures: Vec<VaultFixture> = (0..4).map(|_| VaultFixture::default()).collect();
+        let accounts = || {
+            fixtures
+                .iter()
+                .map(|fixture| (fixture.vault_key, fixture.vault_account()))
+        };
+
+        let options = DiscoveryOptions {
+            asset_mints: Some(HashSet::from([
+                fixtures[0].asset_mint,
+                fixtures[1].asset_mint,
+                fixtures[2].asset_mint,
+            ])),
+            denylist: HashSet::from([fixtures[1].vault_key]),
+            ..Default::default()
+        };
+        let report = discover_from_accounts(accounts(), &options);
+        let built: Vec<Pubkey> = report.venues.iter().map(|venue| venue.vault_key).collect();
+        assert_eq!(built, vec![fixtures[0].vault_key, fixtures[2].vault_key]);
+        assert_eq!(
+            report.skipped,
+            vec![
+                (fixtures[1].vault_key, SkipReason::Excluded),
+                (fixtures[3].vault_key, SkipReason::FilteredByAsset),
+            ]
+        );
+
+        let options = DiscoveryOptions {
+            allowlist: Some(HashSet::from([fixtures[3].vault_key])),
+            ..Default::default()
+        };
+        let report = discover_from_accounts(accounts(), &options);
+        assert_eq!(report.venues.len(), 1);
+        assert_eq!(report.venues[0].vault_key, fixtures[3].vault_key);
+        assert!(
+            report
+                .skipped
+                .iter()
+                .all(|(_, reason)| *reason == SkipReason::Excluded)
+        );
+    }
+
+    fn keys(venues: &[VoltrVaultVenue]) -> Vec<Pubkey> {
+        venues.iter().map(|venue| venue.vault_key).collect()
+    }
+
+    #[test]
+    fn test_registry_tracks_added_and_removed_vaults() {
+        let fixtures: Vec<VaultFixture> = (0..4).map(|_| VaultFixture::default()).collect();
+        let [a, b, c, d] = [0, 1, 2, 3].map(|i| &fixtures[i]);
+        let other_asset = VaultFixture::default();
+        let options = DiscoveryOptions {
+            asset_mints: Some(fixtures.iter().map(|fixture| fixture.asset_mint).collect()),
+            ..Default::default()
+        };
+
+        let mut registry = VaultRegistry::new(options.clone());
+        let delta = registry.sync_from_accounts([
+            (a.vault_key, a.vault_account()),
+            (b.vault_key, b.vault_account()),
+            (c.vault_key, c.vault_account()),
+            (other_asset.vault_key, other_asset.vault_account()),
+        ]);
+        assert_eq!(
+            keys(&delta.added),
+            vec![a.vault_key, b.vault_key, c.vault_key]
+        );
+        assert!(delta.removed.is_empty());
+        assert_eq!(
+            delta.skipped,
+            vec![(other_asset.vault_key, SkipReason::FilteredByAsset)]
+        );
+
+        // Resumed from the persisted set: `b` has left the scan, `c` is
+        // closed and `d` is new.
+        let mut registry = VaultRegistry::with_seen(options.clone(), registry.seen_vaults());
+        let closed_c = Account {
+            lamports: 0,
+            data: Vec::new(),
+            ..c.vault_account()
+        };
+        let second_scan = [
+            (a.vault_key, a.vault_account()),
+            (c.vault_key, closed_c),
+            (d.vault_key, d.vault_account()),
+        ];
+        let delta = registry.sync_from_accounts(second_scan.clone());
+        assert_eq!(keys(&delta.added), vec![d.vault_key]);
+        let mut removed = vec![b.vault_key, c.vault_key];
+        removed.sort();
+        assert_eq!(delta.removed, removed);
+        assert_eq!(delta.skipped, vec![(c.vault_key, SkipReason::Closed)]);
+        assert_eq!(*registry.seen(), HashSet::from([a.vault_key, d.vault_key]));
+
+        // Nothing changes on a repeated scan.
+        let delta = registry.sync_from_accounts(second_scan.clone());
+        assert!(delta.added.is_empty() && delta.removed.is_empty());
+
+        // One-off incremental runs skip the given set the same way.
+        let report = discover_created_since(
+            second_scan,
+            &options,
+            &HashSet::from([a.vault_key, c.vault_key]),
+        );
+        assert_eq!(keys(&report.venues), vec![d.vault_key]);
+        assert!(report.skipped.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod test_discriminators {
+    //! The Anchor discriminators the venue writes into its instructions and
+    //! checks on the vault account, pinned to the bytes in the program IDL:
+    //! - the pinned constants are those bytes,
+    //! - hashing the names at runtime gives the same bytes,
+    //! - the vault account check and the fixtures agree on them,
+    //! - the events the program logs on swaps have theirs pinned too.
+
+    use titan_voltr_integration::constants::{
+        DEPOSIT_VAULT_DISCRIMINATOR, DEPOSIT_VAULT_EVENT_DISCRIMINATOR,
+        INSTANT_WITHDRAW_VAULT_DISCRIMINATOR, INSTANT_WITHDRAW_VAULT_EVENT_DISCRIMINATOR,
+        VAULT_ACCOUNT_DISCRIMINATOR,
+    };
+    use titan_voltr_integration::discriminators::{
+        account_discriminator, event_discriminator, instruction_discriminator,
+    };
+    use titan_voltr_integration::state::vault_discriminator;
+
+    /// From the program IDL.
+    const DEPOSIT_VAULT: [u8; 8] = [126, 224, 21, 255, 228, 53, 117, 33];
+    const INSTANT_WITHDRAW_VAULT: [u8; 8] = [221, 56, 115, 168, 128, 220, 235, 245];
+    const VAULT: [u8; 8] = [211, 8, 232, 43, 2, 152, 117, 119];
+    const DEPOSIT_VAULT_EVENT: [u8; 8] = [11, 15, 7, 92, 150, 100, 165, 232];
+    const INSTANT_WITHDRAW_VAULT_EVENT: [u8; 8] = [46, 57, 60, 20, 6, 160, 164, 247];
+
+    #[test]
+    fn test_deposit_vault() {
+        assert_eq!(DEPOSIT_VAULT_DISCRIMINATOR, DEPOSIT_VAULT, "deposit_vault");
+        assert_eq!(
+            instruction_discriminator("deposit_vault"),
+            DEPOSIT_VAULT,
+            "deposit_vault"
+        );
+    }
+
+    #[test]
+    fn test_instant_withdraw_vault() {
+        assert_eq!(
+            INSTANT_WITHDRAW_VAULT_DISCRIMINATOR, INSTANT_WITHDRAW_VAULT,
+            "instant_withdraw_vault"
+        );
+        assert_eq!(
+            instruction_discriminator("instant_withdraw_vault"),
+            INSTANT_WITHDRAW_VAULT,
+            "instant_withdraw_vault"
+        );
+    }
+
+    #[test]
+    fn test_vault_account() {
+        assert_eq!(VAULT_ACCOUNT_DISCRIMINATOR, VAULT, "account:Vault");
+        assert_eq!(account_discriminator("Vault"), VAULT, "account:Vault");
+        assert_eq!(vault_discriminator(), VAULT, "account:Vault");
+        assert_eq!(
+            crate::common::vault_discriminator(),
+            VAULT,
+            "fixture account:Vault"
+        );
+    }
+
+    #[test]
+    fn test_swap_events() {
+        for (pinned, name, bytes) in [
+            (
+                DEPOSIT_VAULT_EVENT_DISCRIMINATOR,
+                "DepositVaultEvent",
+                DEPOSIT_VAULT_EVENT,
+            ),
+            (
+                INSTANT_WITHDRAW_VAULT_EVENT_DISCRIMINATOR,
+                "InstantWithdrawVaultEvent",
+                INSTANT_WITHDRAW_VAULT_EVENT,
+            ),
+        ] {
+            assert_eq!(pinned, bytes, "{name}");
+            assert_eq!(event_discriminator(name), bytes, "{name}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_fingerprint {
+    //! `update_state_from_accounts()` reloads only on change:
+    //! - unchanged accounts short-circuit and report no re-quote,
+    //! - a single-byte change in the idle ATA balance is detected and loaded,
+    //! - a venue built with `new_uninitialized()` loads its vault first.
+
+    use solana_account::Account;
+    use titan_integration_template::trading_venue::TradingVenue;
+
+    use titan_voltr_integration::fingerprint::account_fingerprint;
+    use titan_voltr_integration::voltr_venue::VoltrVaultVenue;
+
+    use crate::common::VaultFixture;
+
+    /// SPL token account `amount` offset.
+    const AMOUNT_OFFSET: usize = 64;
+
+    fn accounts(fixture: &VaultFixture) -> Vec<Option<Account>> {
+        fixture
+            .accounts()
+            .into_iter()
+            .map(|(_, account)| Some(account))
+            .collect()
+    }
+
+    #[test]
+    fn test_fingerprint_covers_owner_lamports_and_data() {
+        let account = VaultFixture::default().vault_account();
+        let fingerprint = account_fingerprint(&account);
+        assert_eq!(fingerprint, account_fingerprint(&account.clone()));
+
+        let mut lamports = account.clone();
+        lamports.lamports += 1;
+        let mut owner = account.clone();
+        owner.owner = solana_pubkey::Pubkey::new_unique();
+        let mut data = account.clone();
+        data.data[100] ^= 1;
+        for changed in [lamports, owner, data] {
+            assert_ne!(account_fingerprint(&changed), fingerprint);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unchanged_accounts_short_circuit() {
+        let fixture = VaultFixture::default();
+        let mut venue = fixture.venue().await;
+        let accounts = accounts(&fixture);
+
+        // The first call has no vault fingerprint from `from_account()`.
+        assert!(venue.update_state_from_accounts(&accounts).unwrap());
+        let token_info = venue.get_token_info().as_ptr();
+        assert!(!venue.update_state_from_accounts(&accounts).unwrap());
+        assert_eq!(venue.get_token_info().as_ptr(), token_info);
+    }
+
+    #[tokio::test]
+    async fn test_single_byte_idle_balance_change_is_detected() {
+        let fixture = VaultFixture {
+            idle_balance: 500_000_000_000,
+            ..Default::default()
+        };
+        let mut venue = fixture.venue().await;
+        let mut accounts = accounts(&fixture);
+        venue.update_state_from_accounts(&accounts).unwrap();
+
+        let idle = accounts[3].as_mut().unwrap();
+        idle.data[AMOUNT_OFFSET] ^= 1;
+        let expected = u64::from_le_bytes(
+            idle.data[AMOUNT_OFFSET..AMOUNT_OFFSET + 8]
+                .try_into()
+                .unwrap(),
+        );
+        assert_ne!(expected, venue.asset_idle_balance);
+
+        assert!(venue.update_state_from_accounts(&accounts).unwrap());
+        assert_eq!(venue.asset_idle_balance, expected);
+        assert!(!venue.update_state_from_accounts(&accounts).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_vault_change_is_loaded() {
+        let fixture = VaultFixture::default();
+        let mut venue = fixture.venue().await;
+        venue
+            .update_state_from_accounts(&accounts(&fixture))
+            .unwrap();
+
+        let grown = VaultFixture {
+            total_value: fixture.total_value * 2,
+            ..fixture.clone()
+        };
+        assert!(venue.update_state_from_accounts(&accounts(&grown)).unwrap());
+        assert_eq!(venue.vault_state.asset.total_value, grown.total_value);
+    }
+
+    #[test]
+    fn test_uninitialized_venue_loads_vault_then_tokens() {
+        let fixture = VaultFixture::default();
+        let mut venue = VoltrVaultVenue::new_uninitialized(fixture.vault_key);
+        let accounts = accounts(&fixture);
+
+        assert!(venue.update_state_from_accounts(&accounts).is_err());
+        assert!(venue.update_state_from_accounts(&accounts[..1]).unwrap());
+        assert!(!venue.initialized());
+        assert_eq!(venue.get_required_pubkeys_for_update().unwrap().len(), 4);
+
+        assert!(venue.update_state_from_accounts(&accounts).unwrap());
+        assert!(venue.initialized());
+        assert_eq!(venue.vault_state.asset.total_value, fixture.total_value);
+        assert!(!venue.update_state_from_accounts(&accounts).unwrap());
+    }
+}
+
+#[cfg(all(test, feature = "replay"))]
+mod test_fixture {
+    //! Capturing vault state to a fixture directory and reading it back:
+    //! - a saved fixture reloads byte for byte, with its slot and timestamp,
+    //! - a venue loaded from it quotes exactly as the live venue did at
+    //!   capture time, and reports the same health,
+    //! - a damaged fixture is refused with the offending file named.
+
+    use std::fs;
+    use std::path::PathBuf;
+
+    use solana_pubkey::Pubkey;
+    use titan_integration_template::trading_venue::{QuoteRequest, SwapType, TradingVenue};
+
+    use titan_voltr_integration::clock::QuoteClock;
+    use titan_voltr_integration::fixture::FixtureCache;
+    use titan_voltr_integration::replay::ReplaySnapshot;
+    use titan_voltr_integration::voltr_venue::VoltrVaultVenue;
+
+    use crate::common::VaultFixture;
+
+    const CAPTURE_SLOT: u64 = 300_000_000;
+    const CAPTURE_TS: u64 = 1_700_003_600;
+    const T0: u64 = 1_700_000_000;
+
+    /// A fresh directory under the system temp dir, removed on drop.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new() -> Self {
+            Self(std::env::temp_dir().join(format!("voltr-fixture-{}", Pubkey::new_unique())))
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn fixture() -> VaultFixture {
+        VaultFixture {
+            idle_balance: 100_000_000_000,
+            manager_management_fee: 200,
+            issuance_fee: 10,
+            redemption_fee: 10,
+            last_management_fee_update_ts: T0,
+            locked_profit_degradation_duration: 86_400,
+            last_updated_locked_profit: 10_000_000_000,
+            last_report: T0,
+            last_updated_ts: T0,
+            ..Default::default()
+        }
+    }
+
+    fn capture(fixture: &VaultFixture) -> FixtureCache {
+        FixtureCache::new(
+            fixture.vault_key,
+            ReplaySnapshot {
+                slot: CAPTURE_SLOT,
+                timestamp: CAPTURE_TS,
+                accounts: fixture.accounts().into_iter().collect(),
+            },
+        )
+    }
+
+    fn requests(fixture: &VaultFixture) -> Vec<QuoteRequest> {
+        let (asset, lp) = (fixture.asset_mint, fixture.lp_mint());
+        [
+            (asset, lp, 1_000_000_000),
+            (lp, asset, 1_000_000_000),
+            // More than idle can pay.
+            (lp, asset, 500_000_000_000),
+            (asset, lp, 0),
+        ]
+        .into_iter()
+        .map(|(input_mint, output_mint, amount)| QuoteRequest {
+            input_mint,
+            output_mint,
+            amount,
+            swap_type: SwapType::ExactIn,
+        })
+        .collect()
+    }
+
+    #[tokio::test]
+    async fn test_fixture_round_trip() {
+        let fixture = fixture();
+        let captured = capture(&fixture);
+        let dir = TempDir::new();
+        captured.save(&dir.0).unwrap();
+
+        let loaded = FixtureCache::load(&dir.0).unwrap();
+        assert_eq!(loaded.vault_key(), fixture.vault_key);
+        assert_eq!(loaded.snapshot().slot, CAPTURE_SLOT);
+        assert_eq!(loaded.snapshot().timestamp, CAPTURE_TS);
+        assert_eq!(loaded.snapshot().accounts, captured.snapshot().accounts);
+
+        // The live venue, refreshed and quoting at capture time.
+        let mut live = VoltrVaultVenue::builder(fixture.vault_key)
+            .clock(QuoteClock::Fixed(CAPTURE_TS))
+            .build();
+        live.update_state(&fixture.cache()).await.unwrap();
+
+        let replayed = loaded.venue().await.unwrap();
+        assert_eq!(replayed.effective_ts(), CAPTURE_TS);
+        assert_eq!(replayed.state_slot(), Some(CAPTURE_SLOT));
+        assert_eq!(replayed.health_report(), live.health_report());
+        for request in requests(&fixture) {
+            let (expected, actual) = (
+                live.quote(request.clone()).unwrap(),
+                replayed.quote(request).unwrap(),
+            );
+            assert_eq!(
+                (actual.expected_output, actual.not_enough_liquidity),
+                (expected.expected_output, expected.not_enough_liquidity)
+            );
+        }
+    }
+
+    #[test]
+    fn test_damaged_fixture_rejected() {
+        let fixture = fixture();
+        let dir = TempDir::new();
+        capture(&fixture).save(&dir.0).unwrap();
+
+        let vault_file = dir.0.join(format!("{}.account", fixture.vault_key));
+        let contents = fs::read_to_string(&vault_file).unwrap();
+        fs::write(&vault_file, contents.replace("lamports=", "lamport=")).unwrap();
+        let error = FixtureCache::load(&dir.0).unwrap_err().to_string();
+        assert!(error.contains(&fixture.vault_key.to_string()));
+        assert!(error.contains("missing lamports"));
+
+        fs::remove_file(dir.0.join("fixture")).unwrap();
+        assert!(FixtureCache::load(&dir.0).is_err());
+    }
+}
+
+#[cfg(test)]
+mod test_health {
+    //! Health reporting across refreshes:
+    //! - benign share price drift is not flagged,
+    //! - a 50% jump without a profit report is flagged but still quoted,
+    //! - the hard-fail mode refuses quotes until the price returns,
+    //! - a locked-profit report resets the baseline,
+    //! - stale accounting degrades the venue and optionally haircuts redeems,
+    //! - asset mint authorities and supply are captured for both token
+    //!   programs, and a freeze authority is reported without degrading,
+    //! - a delegate or close authority on the idle ATA is reported for both
+    //!   token programs and blocks the directions configured, though
+    //!   zero-amount quotes still succeed,
+    //! - a Token-2022 asset mint with extensions this build cannot read
+    //!   loads from its base fields and is reported, unless strict.
+
+    use solana_account::Account;
+    use solana_program::program_option::COption;
+    use solana_program_pack::Pack;
+    use solana_pubkey::Pubkey;
+    use titan_integration_template::trading_venue::{QuoteRequest, SwapType, TradingVenue};
+
+    use titan_voltr_integration::config::{IdleAtaAnomalyAction, VoltrVenueConfig};
+    use titan_voltr_integration::constants::{TOKEN_22_PROGRAM, TOKEN_PROGRAM};
+    use titan_voltr_integration::health::IdleAtaAnomaly;
+    use titan_voltr_integration::voltr_venue::VoltrVaultVenue;
+
+    use crate::common::VaultFixture;
+
+    fn deposit_request(fixture: &VaultFixture) -> QuoteRequest {
+        QuoteRequest {
+            input_mint: fixture.asset_mint,
+            output_mint: fixture.lp_mint(),
+            amount: 1_000_000,
+            swap_type: SwapType::ExactIn,
+        }
+    }
+
+    async fn venue(fixture: &VaultFixture, fail_on_anomaly: bool) -> VoltrVaultVenue {
+        let mut venue =
+            VoltrVaultVenue::new_uninitialized(fixture.vault_key).with_config(VoltrVenueConfig {
+                max_share_price_change_bps: Some(500),
+                fail_on_share_price_anomaly: fail_on_anomaly,
+                ..Default::default()
+            });
+        venue.update_state(&fixture.cache()).await.unwrap();
+        venue
+    }
+
+    fn with_total_value(fixture: &VaultFixture, total_value: u64) -> VaultFixture {
+        VaultFixture {
+            total_value,
+            ..fixture.clone()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_share_price_anomaly_is_reported() {
+        let fixture = VaultFixture::default();
+        let mut venue = venue(&fixture, false).await;
+        assert!(venue.health_report().is_healthy());
+
+        // 1% drift stays under the 5% threshold.
+        let drifted = with_total_value(&fixture, 1_010_000_000_000);
+        venue.update_state(&drifted.cache()).await.unwrap();
+        assert!(venue.health_report().is_healthy());
+
+        // A 50% jump is flagged, but quotes continue.
+        let jumped = with_total_value(&drifted, 1_515_000_000_000);
+        venue.update_state(&jumped.cache()).await.unwrap();
+        let anomaly = venue.health_report().share_price_anomaly.unwrap();
+        assert_eq!(anomaly.change_bps, 5_000);
+        assert!(anomaly.current_price > anomaly.baseline_price);
+        assert!(venue.quote(deposit_request(&fixture)).is_ok());
+
+        // The jumped price became the baseline, so a stable refresh clears it.
+        venue.update_state(&jumped.cache()).await.unwrap();
+        assert!(venue.health_report().is_healthy());
+    }
+
+    #[tokio::test]
+    async fn test_share_price_anomaly_hard_fail() {
+        let fixture = VaultFixture::default();
+        let mut venue = venue(&fixture, true).await;
+
+        let jumped = with_total_value(&fixture, 1_500_000_000_000);
+        assert!(venue.update_state(&jumped.cache()).await.is_err());
+        assert!(venue.quote(deposit_request(&fixture)).is_err());
+        // The refused refresh loads nothing but the flag.
+        assert_eq!(venue.vault_state.asset.total_value, fixture.total_value);
+
+        // The anomalous state never becomes trusted.
+        assert!(venue.update_state(&jumped.cache()).await.is_err());
+        assert!(!venue.health_report().is_healthy());
+
+        venue.update_state(&fixture.cache()).await.unwrap();
+        assert!(venue.health_report().is_healthy());
+        assert!(venue.quote(deposit_request(&fixture)).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_profit_report_resets_baseline() {
+        let fixture = VaultFixture::default();
+        let mut venue = venue(&fixture, true).await;
+
+        let reported = VaultFixture {
+            last_report: 1_700_000_000,
+            ..with_total_value(&fixture, 1_500_000_000_000)
+        };
+        venue.update_state(&reported.cache()).await.unwrap();
+        assert!(venue.health_report().is_healthy());
+    }
+
+    fn now() -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
+    #[test]
+    fn test_accounting_age() {
+        let venue = VaultFixture {
+            last_updated_ts: 1_700_000_000,
+            ..Default::default()
+        }
+        .synthetic_venue();
+        assert_eq!(venue.accounting_age(1_700_000_600), 600);
+        // A clock behind the vault never reports a negative age.
+        assert_eq!(venue.accounting_age(1_600_000_000), 0);
+    }
+
+    #[tokio::test]
+    async fn test_stale_accounting_degrades_health() {
+        let fresh = VaultFixture {
+            last_updated_ts: now(),
+            ..Default::default()
+        };
+        let mut venue =
+            VoltrVaultVenue::new_uninitialized(fresh.vault_key).with_config(VoltrVenueConfig {
+                max_accounting_age_secs: Some(3_600),
+                ..Default::default()
+            });
+        venue.update_state(&fresh.cache()).await.unwrap();
+        assert!(venue.health_report().is_healthy());
+
+        let stale = VaultFixture {
+            last_updated_ts: now() - 7_200,
+            ..fresh.clone()
+        };
+        venue.update_state(&stale.cache()).await.unwrap();
+        let report = venue.health_report();
+        assert!(!report.is_healthy());
+        assert!(report.stale_accounting_age.unwrap() >= 7_200);
+
+        venue.update_state(&fresh.cache()).await.unwrap();
+        assert!(venue.health_report().is_healthy());
+    }
+
+    #[test]
+    fn test_stale_accounting_haircut() {
+        let now = now();
+        let fixture = VaultFixture {
+            last_updated_ts: now - 7_200,
+            ..Default::default()
+        };
+        let redeem = QuoteRequest {
+            input_mint: fixture.lp_mint(),
+            output_mint: fixture.asset_mint,
+            amount: 1_000_000,
+            swap_type: SwapType::ExactIn,
+        };
+        let venue = |haircut_bps| {
+            fixture.synthetic_venue().with_config(VoltrVenueConfig {
+                max_accounting_age_secs: Some(3_600),
+                stale_accounting_haircut_bps: haircut_bps,
+                ..Default::default()
+            })
+        };
+
+        let unadjusted = fixture
+            .synthetic_venue()
+            .quote_at(&redeem, now)
+            .unwrap()
+            .expected_output;
+        assert!(unadjusted > 0);
+
+        // Without a haircut the quote is unchanged, only the health flags it.
+        let warned = venue(0);
+        assert_eq!(
+            warned.quote_at(&redeem, now).unwrap().expected_output,
+            unadjusted
+        );
+        assert!(warned.health_report().stale_accounting_age.is_some());
+
+        let haircut = venue(50);
+        assert_eq!(
+            haircut.quote_at(&redeem, now).unwrap().expected_output,
+            unadjusted * 9_950 / 10_000
+        );
+        // Within the threshold the margin is not applied.
+        assert_eq!(
+            haircut
+                .quote_at(&redeem, now - 7_200)
+                .unwrap()
+                .expected_output,
+            unadjusted
+        );
+        // Deposits are never adjusted.
+        let deposit = deposit_request(&fixture);
+        assert_eq!(
+            haircut.quote_at(&deposit, now).unwrap().expected_output,
+            warned.quote_at(&deposit, now).unwrap().expected_output
+        );
+    }
+
+    /// An asset mint owned by `token_program` with the given authorities.
+    fn asset_mint_account(
+        token_program: Pubkey,
+        supply: u64,
+        mint_authority: Option<Pubkey>,
+        freeze_authority: Option<Pubkey>,
+    ) -> Account {
+        let mint = spl_token::state::Mint {
+            mint_authority: mint_authority.map_or(COption::None, COption::Some),
+            supply,
+            decimals: 6,
+            is_initialized: true,
+            freeze_authority: freeze_authority.map_or(COption::None, COption::Some),
+        };
+        // A Token-2022 mint without extensions has the same layout.
+        let mut data = vec![0; spl_token::state::Mint::LEN];
+        mint.pack_into_slice(&mut data);
+        Account {
+            lamports: 1_000_000_000,
+            data,
+            owner: token_program,
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_asset_mint_authorities_captured() {
+        let fixture = VaultFixture::default();
+        let authority = Pubkey::new_unique();
+
+        for token_program in [TOKEN_PROGRAM, TOKEN_22_PROGRAM] {
+            for mint_authority in [None, Some(authority)] {
+                for freeze_authority in [None, Some(authority)] {
+                    let mut cache = fixture.cache();
+                    cache.insert(
+                        fixture.asset_mint,
+                        asset_mint_account(
+                            token_program,
+                            123_456_789,
+                            mint_authority,
+                            freeze_authority,
+                        ),
+                    );
+                    let mut venue = VoltrVaultVenue::new_uninitialized(fixture.vault_key);
+                    venue.update_state(&cache).await.unwrap();
+
+                    assert_eq!(venue.asset_token_program, token_program);
+                    assert_eq!(venue.asset_mint_authority(), mint_authority);
+                    assert_eq!(venue.asset_mint_freeze_authority(), freeze_authority);
+                    assert_eq!(venue.asset_mint_supply(), 123_456_789);
+
+                    let report = venue.health_report();
+                    assert_eq!(report.asset_freeze_authority, freeze_authority);
+                    assert!(!report.unknown_mint_extensions);
+                    assert!(report.is_healthy());
+                }
+            }
+        }
+    }
+
+    /// The fixture's idle ATA, owned by `token_program`, with `anomaly`'s
+    /// authorities set.
+    fn idle_ata_account(
+        fixture: &VaultFixture,
+        token_program: Pubkey,
+        anomaly: Option<IdleAtaAnomaly>,
+    ) -> Account {
+        let mut account = fixture.cache().accounts[&fixture.idle_ata].clone();
+        let mut token = spl_token::state::Account::unpack(&account.data).unwrap();
+        if let Some(anomaly) = anomaly {
+            token.delegate = anomaly.delegate.map_or(COption::None, COption::Some);
+            token.delegated_amount = anomaly.delegated_amount;
+            token.close_authority = anomaly.close_authority.map_or(COption::None, COption::Some);
+        }
+        // A Token-2022 account without extensions has the same layout.
+        token.pack_into_slice(&mut account.data);
+        account.owner = token_program;
+        account
+    }
+
+    #[tokio::test]
+    async fn test_idle_ata_anomaly() {
+        let fixture = VaultFixture::default();
+        let deposit = deposit_request(&fixture);
+        let redeem = QuoteRequest {
+            input_mint: fixture.lp_mint(),
+            output_mint: fixture.asset_mint,
+            ..deposit.clone()
+        };
+        let delegated = IdleAtaAnomaly {
+            delegate: Some(Pubkey::new_unique()),
+            delegated_amount: 500_000,
+            close_authority: None,
+        };
+        let closable = IdleAtaAnomaly {
+            delegate: None,
+            delegated_amount: 0,
+            close_authority: Some(Pubkey::new_unique()),
+        };
+
+        for token_program in [TOKEN_PROGRAM, TOKEN_22_PROGRAM] {
+            let mut cache = fixture.cache();
+            cache.insert(
+                fixture.asset_mint,
+                asset_mint_account(token_program, 0, None, None),
+            );
+            for anomaly in [delegated, closable] {
+                for action in [
+                    IdleAtaAnomalyAction::Report,
+                    IdleAtaAnomalyAction::BlockRedeems,
+                    IdleAtaAnomalyAction::BlockAll,
+                ] {
+                    cache.insert(
+                        fixture.idle_ata,
+                        idle_ata_account(&fixture, token_program, Some(anomaly)),
+                    );
+                    let mut venue = VoltrVaultVenue::new_uninitialized(fixture.vault_key)
+                        .with_config(VoltrVenueConfig {
+                            idle_ata_anomaly_action: action,
+                            ..Default::default()
+                        });
+                    venue.update_state(&cache).await.unwrap();
+
+                    let report = venue.health_report();
+                    assert_eq!(report.idle_ata_anomaly, Some(anomaly));
+                    assert!(!report.is_healthy());
+
+                    let blocked = |request: &QuoteRequest| match venue.quote(request.clone()) {
+                        Ok(_) => false,
+                        Err(e) => {
+                            assert!(
+                                e.to_string()
+                                    .contains("Idle ATA Has Delegate Or Close Authority"),
+                                "{e}"
+                            );
+                            assert!(venue
+                                .generate_swap_instruction(request.clone(), Pubkey::new_unique())
+                                .is_err());
+                            true
+                        }
+                    };
+                    assert_eq!(blocked(&deposit), action == IdleAtaAnomalyAction::BlockAll);
+                    assert_eq!(blocked(&redeem), action != IdleAtaAnomalyAction::Report);
+                    for request in [&deposit, &redeem] {
+                        let zero = QuoteRequest {
+                            amount: 0,
+                            ..request.clone()
+                        };
+                        assert_eq!(venue.quote(zero).unwrap().expected_output, 0);
+                    }
+
+                    // Clearing the authorities clears the flag at the next refresh.
+                    cache.insert(
+                        fixture.idle_ata,
+                        idle_ata_account(&fixture, token_program, None),
+                    );
+                    venue.update_state(&cache).await.unwrap();
+                    assert_eq!(venue.health_report().idle_ata_anomaly, None);
+                    assert!(venue.health_report().is_healthy());
+                    assert!(venue.quote(redeem.clone()).is_ok());
+                }
+            }
+        }
+    }
+
+    /// A Token-2022 asset mint padded to the extension layout, followed by
+    /// `account_type` and a TLV entry of `extension_type`.
+    fn extended_mint_account(account_type: u8, extension_type: u16) -> Account {
+        let mut account = asset_mint_account(TOKEN_22_PROGRAM, 123_456_789, None, None);
+        account.data.resize(spl_token::state::Account::LEN, 0);
+        account.data.push(account_type);
+        account
+            .data
+            .extend_from_slice(&extension_type.to_le_bytes());
+        account.data.extend_from_slice(&4u16.to_le_bytes());
+        account.data.extend_from_slice(&[1, 2, 3, 4]);
+        account
+    }
+
+    #[tokio::test]
+    async fn test_unknown_mint_extensions() {
+        let fixture = VaultFixture::default();
+        // A mint account type with an extension type from the future, and an
+        // account type from the future.
+        for account in [
+            extended_mint_account(1, 0xfff0),
+            extended_mint_account(0xf0, 0xfff0),
+        ] {
+            let mut cache = fixture.cache();
+            cache.insert(fixture.asset_mint, account);
+
+            let mut venue = VoltrVaultVenue::new_uninitialized(fixture.vault_key);
+            venue.update_state(&cache).await.unwrap();
+            assert_eq!(venue.asset_token_program, TOKEN_22_PROGRAM);
+            assert_eq!(venue.asset_mint_decimals, 6);
+            assert_eq!(venue.asset_mint_supply(), 123_456_789);
+            let report = venue.health_report();
+            assert!(report.unknown_mint_extensions);
+            assert!(report.is_healthy());
+            assert!(
+                venue
+                    .quote(deposit_request(&fixture))
+                    .unwrap()
+                    .expected_output
+                    > 0
+            );
+
+            let mut strict = VoltrVaultVenue::new_uninitialized(fixture.vault_key).with_config(
+                VoltrVenueConfig {
+                    strict_mint_extensions: true,
+                    ..Default::default()
+                },
+            );
+            assert!(strict.update_state(&cache).await.is_err());
+        }
+
+        // A known extension, `MintCloseAuthority`, reads as before.
+        let mut cache = fixture.cache();
+        cache.insert(fixture.asset_mint, extended_mint_account(1, 3));
+        let mut venue = VoltrVaultVenue::new_uninitialized(fixture.vault_key);
+        venue.update_state(&cache).await.unwrap();
+        assert!(!venue.health_report().unknown_mint_extensions);
+    }
+}
+
+#[cfg(all(test, feature = "rayon"))]
+mod test_parallel_discovery {
+    //! `discover_from_accounts_parallel()` against the sequential discovery:
+    //! - a large mixed dump yields the same venues and skipped accounts, in
+    //!   input order, whatever the thread count,
+    //! - a 10k vault dump is timed both ways; the timings are logged at
+    //!   debug level rather than asserted, as they depend on the machine.
+
+    use std::time::Instant;
+
+    use solana_account::Account;
+    use solana_pubkey::Pubkey;
+
+    use titan_voltr_integration::discovery::{
+        discover_from_accounts, discover_from_accounts_parallel, DiscoveryOptions,
+        DiscoveryReport,
+    };
+
+    use crate::common::VaultFixture;
+
+    /// `len` accounts, every seventh truncated and every eleventh closed.
+    fn corpus(len: usize) -> Vec<(Pubkey, Account)> {
+        (0..len)
+            .map(|i| {
+                let fixture = VaultFixture::default();
+                let mut account = fixture.vault_account();
+                if i % 7 == 3 {
+                    account.data.truncate(100);
+                } else if i % 11 == 5 {
+                    account.lamports = 0;
+                    account.data.clear();
+                }
+                (fixture.vault_key, account)
+            })
+            .collect()
+    }
+
+    /// Initialize logging for test output.
+    fn init_test_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    fn assert_same_report(parallel: &DiscoveryReport, sequential: &DiscoveryReport) {
+        let keys = |report: &DiscoveryReport| -> Vec<Pubkey> {
+            report.venues.iter().map(|venue| venue.vault_key).collect()
+        };
+        assert_eq!(keys(parallel), keys(sequential));
+        assert_eq!(parallel.skipped, sequential.skipped);
+    }
+
+    #[test]
+    fn test_parallel_matches_sequential() {
+        let accounts = corpus(2_000);
+        let options = DiscoveryOptions::default();
+        let sequential = discover_from_accounts(accounts.clone(), &options);
+        assert!(!sequential.venues.is_empty() && !sequential.skipped.is_empty());
+
+        for threads in [0, 1, 4] {
+            let parallel =
+                discover_from_accounts_parallel(accounts.clone(), &options, threads).unwrap();
+            assert_same_report(&parallel, &sequential);
+        }
+    }
+
+    #[test]
+    fn test_large_dump_timing() {
+        init_test_logger();
+        let accounts = corpus(10_000);
+        let options = DiscoveryOptions::default();
+
+        let start = Instant::now();
+        let sequential = discover_from_accounts(accounts.clone(), &options);
+        let sequential_time = start.elapsed();
+
+        let start = Instant::now();
+        let parallel = discover_from_accounts_parallel(accounts, &options, 0).unwrap();
+        let parallel_time = start.elapsed();
+
+        assert_same_report(&parallel, &sequential);
+        log::debug!(
+            "discovered {} venues: sequential {:?}, parallel {:?}",
+            sequential.venues.len(),
+            sequential_time,
+            parallel_time
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_pda {
+    //! PDA derivation per program deployment:
+    //! - two program ids share no derived address,
+    //! - the mainnet wrapper matches the explicit derivation,
+    //! - the typed `VaultKey` and `ProgramId` methods match the untyped
+    //!   functions, which they convert to and from without ceremony,
+    //! - a venue configured for another deployment uses its PDAs in
+    //!   instructions, setup instructions and lookup-table keys,
+    //! - the process-wide cache agrees with uncached derivation, from any
+    //!   thread and with the cache disabled,
+    //! - memoized user ATAs agree with fresh derivation, across more users
+    //!   than the memo holds and after the program id changes.
+
+    use std::collections::HashSet;
+    use std::thread;
+
+    use solana_pubkey::Pubkey;
+    use titan_integration_template::trading_venue::{
+        AddressLookupTableTrait, QuoteRequest, SwapType, TradingVenue,
+    };
+
+    use titan_voltr_integration::atas::ATA_MEMO_CAPACITY;
+    use titan_voltr_integration::config::VoltrVenueConfig;
+    use titan_voltr_integration::constants::{TOKEN_PROGRAM, VOLTR_VAULT_PROGRAM};
+    use titan_voltr_integration::pda::{self, ProgramId, VaultKey, VaultPdas};
+    use titan_voltr_integration::transaction::TxOptions;
+    use titan_voltr_integration::voltr_venue::VoltrVaultVenue;
+
+    use crate::common::VaultFixture;
+
+    fn addresses(pdas: &VaultPdas) -> [Pubkey; 4] {
+        [
+            pdas.protocol,
+            pdas.lp_mint,
+            pdas.lp_mint_auth,
+            pdas.asset_idle_auth,
+        ]
+    }
+
+    #[test]
+    fn test_program_ids_share_no_pda() {
+        let vault_key = Pubkey::new_unique();
+        let devnet = Pubkey::new_unique();
+
+        let mainnet_pdas = VaultPdas::derive(&vault_key, &VOLTR_VAULT_PROGRAM);
+        let devnet_pdas = VaultPdas::derive(&vault_key, &devnet);
+        let all: HashSet<Pubkey> = addresses(&mainnet_pdas)
+            .into_iter()
+            .chain(addresses(&devnet_pdas))
+            .collect();
+        assert_eq!(all.len(), 8);
+
+        assert_eq!(
+            VoltrVaultVenue::derive_vault_lp_mint_pda(&vault_key),
+            pda::vault_lp_mint_pda(&vault_key, &VOLTR_VAULT_PROGRAM)
+        );
+        assert_eq!(
+            mainnet_pdas.protocol,
+            pda::protocol_pda(&VOLTR_VAULT_PROGRAM)
+        );
+    }
+
+    #[test]
+    fn test_typed_keys_match_untyped() {
+        let vault_key = Pubkey::new_unique();
+        let devnet = Pubkey::new_unique();
+        let vault = VaultKey::from(vault_key);
+
+        for program_id in [VOLTR_VAULT_PROGRAM, devnet] {
+            let program = ProgramId::from(program_id);
+            assert_eq!(
+                vault.pdas(&program),
+                VaultPdas::derive(&vault_key, &program_id)
+            );
+            assert_eq!(
+                vault.lp_mint(&program),
+                pda::vault_lp_mint_pda(&vault_key, &program_id)
+            );
+            assert_eq!(
+                vault.lp_mint_auth(&program),
+                pda::vault_lp_mint_auth_pda(&vault_key, &program_id)
+            );
+            assert_eq!(
+                vault.asset_idle_auth(&program),
+                pda::vault_asset_idle_auth_pda(&vault_key, &program_id)
+            );
+            assert_eq!(program.protocol(), pda::protocol_pda(&program_id));
+        }
+
+        // Both convert back, deref to the key and print as it.
+        assert_eq!(ProgramId::default(), ProgramId::MAINNET);
+        assert_eq!(*ProgramId::MAINNET, VOLTR_VAULT_PROGRAM);
+        assert_eq!(Pubkey::from(vault), vault_key);
+        assert_eq!(vault.to_string(), vault_key.to_string());
+        assert_eq!(
+            VoltrVaultVenue::derive_vault_lp_mint_pda(&vault),
+            vault.lp_mint(&ProgramId::MAINNET)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_venue_uses_configured_program() {
+        let fixture = VaultFixture::default();
+        let devnet = Pubkey::new_unique();
+        let mut venue = fixture.synthetic_venue();
+        assert_eq!(venue.program_id(), VOLTR_VAULT_PROGRAM);
+        venue = venue.with_config(VoltrVenueConfig {
+            program_id: Some(devnet),
+            ..Default::default()
+        });
+
+        let devnet_pdas = VaultPdas::derive(&fixture.vault_key, &devnet);
+        let mainnet_pdas: HashSet<Pubkey> =
+            addresses(&VaultPdas::derive(&fixture.vault_key, &VOLTR_VAULT_PROGRAM))
+                .into_iter()
+                .chain([VOLTR_VAULT_PROGRAM])
+                .collect();
+        assert_eq!(venue.program_id(), devnet);
+        assert_eq!(venue.pdas(), devnet_pdas);
+
+        let deposit = QuoteRequest {
+            input_mint: fixture.asset_mint,
+            output_mint: fixture.lp_mint(),
+            amount: 1_000_000,
+            swap_type: SwapType::ExactIn,
+        };
+        let opts = TxOptions {
+            include_ata_setup: true,
+            ..Default::default()
+        };
+        let instructions = venue
+            .build_transaction_instructions(deposit, Pubkey::new_unique(), &opts)
+            .unwrap();
+        let swap = instructions.last().unwrap();
+        assert_eq!(swap.program_id, devnet);
+        let referenced: HashSet<Pubkey> = instructions
+            .iter()
+            .flat_map(|ix| ix.accounts.iter().map(|meta| meta.pubkey))
+            .collect();
+        assert!(referenced.contains(&devnet_pdas.lp_mint));
+        assert!(referenced.contains(&devnet_pdas.protocol));
+        assert!(referenced.is_disjoint(&mainnet_pdas));
+
+        let lookup_keys: HashSet<Pubkey> = venue
+            .get_lookup_table_keys(None)
+            .await
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert!(lookup_keys.contains(&devnet));
+        assert!(lookup_keys.is_superset(&addresses(&devnet_pdas).into_iter().collect()));
+        assert!(lookup_keys.is_disjoint(&mainnet_pdas));
+    }
+
+    #[test]
+    fn test_cached_pdas_match_derivation() {
+        let devnet = Pubkey::new_unique();
+        let vaults: Vec<Pubkey> = (0..32).map(|_| Pubkey::new_unique()).collect();
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let vaults = vaults.clone();
+                thread::spawn(move || {
+                    for vault_key in &vaults {
+                        for program_id in [VOLTR_VAULT_PROGRAM, devnet] {
+                            assert_eq!(
+                                VaultPdas::for_vault(vault_key, &program_id),
+                                VaultPdas::derive(vault_key, &program_id)
+                            );
+                        }
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let fixture = VaultFixture::default();
+        let cached = fixture.synthetic_venue();
+        let uncached = fixture.synthetic_venue().with_config(VoltrVenueConfig {
+            disable_pda_cache: true,
+            ..Default::default()
+        });
+        assert_eq!(cached.pdas(), uncached.pdas());
+        assert_eq!(
+            cached.pdas(),
+            VaultPdas::derive(&fixture.vault_key, &VOLTR_VAULT_PROGRAM)
+        );
+    }
+
+    #[test]
+    fn test_memoized_atas_match_derivation() {
+        let fixture = VaultFixture::default();
+        let memoized = fixture.synthetic_venue();
+        let fresh = fixture.synthetic_venue().with_config(VoltrVenueConfig {
+            disable_ata_memo: true,
+            ..Default::default()
+        });
+        let deposit = QuoteRequest {
+            input_mint: fixture.asset_mint,
+            output_mint: fixture.lp_mint(),
+            amount: 1_000_000,
+            swap_type: SwapType::ExactIn,
+        };
+        let redeem = QuoteRequest {
+            input_mint: fixture.lp_mint(),
+            output_mint: fixture.asset_mint,
+            amount: 1_000_000,
+            swap_type: SwapType::ExactIn,
+        };
+        // User asset and LP ATAs of `venue`'s deposit instruction.
+        let deposit_atas = |venue: &VoltrVaultVenue, user| {
+            let accounts = venue
+                .generate_swap_instruction(deposit.clone(), user)
+                .unwrap()
+                .accounts;
+            (accounts[5].pubkey, accounts[8].pubkey)
+        };
+
+        let users: Vec<Pubkey> = (0..2 * ATA_MEMO_CAPACITY)
+            .map(|_| Pubkey::new_unique())
+            .collect();
+        // Forwards twice, each user evicted before it comes round again, then
+        // backwards, starting with the users still remembered.
+        for user in users.iter().chain(&users).chain(users.iter().rev()) {
+            for request in [&deposit, &redeem] {
+                assert_eq!(
+                    memoized
+                        .generate_swap_instruction(request.clone(), *user)
+                        .unwrap(),
+                    fresh
+                        .generate_swap_instruction(request.clone(), *user)
+                        .unwrap()
+                );
+            }
+            let expected = (
+                spl_associated_token_account::get_associated_token_address_with_program_id(
+                    user,
+                    &fixture.asset_mint,
+                    &TOKEN_PROGRAM,
+                ),
+                spl_associated_token_account::get_associated_token_address_with_program_id(
+                    user,
+                    &fixture.lp_mint(),
+                    &TOKEN_PROGRAM,
+                ),
+            );
+            assert_eq!(deposit_atas(&memoized, *user), expected);
+        }
+        assert_eq!(memoized.memoized_ata_users(), ATA_MEMO_CAPACITY);
+        assert_eq!(fresh.memoized_ata_users(), 0);
+
+        // Another deployment has another LP mint, so the remembered LP ATAs
+        // no longer apply.
+        let devnet = Pubkey::new_unique();
+        let moved = memoized.clone().with_config(VoltrVenueConfig {
+            program_id: Some(devnet),
+            ..Default::default()
+        });
+        let devnet_lp_mint = pda::vault_lp_mint_pda(&fixture.vault_key, &devnet);
+        assert_eq!(
+            deposit_atas(&moved, users[0]).1,
+            spl_associated_token_account::get_associated_token_address_with_program_id(
+                &users[0],
+                &devnet_lp_mint,
+                &TOKEN_PROGRAM,
+            )
+        );
+        assert_eq!(moved.memoized_ata_users(), 1);
+    }
+}
+
+#[cfg(all(test, feature = "replay"))]
+mod test_replay {
+    //! Replaying recorded snapshots through a venue:
+    //! - a ramping idle balance grows the fillable redeem size at every step,
+    //! - a repeated snapshot is reported unchanged,
+    //! - each snapshot is loaded and quoted at its own timestamp,
+    //! - the cache serves the snapshot being replayed, with its slot.
+
+    use titan_integration_template::account_caching::AccountsCache;
+    use titan_integration_template::trading_venue::{QuoteRequest, SwapType, TradingVenue};
+
+    use titan_voltr_integration::replay::{ReplayCache, ReplaySession, ReplaySnapshot};
+    use titan_voltr_integration::slots::SlotAwareAccountsCache;
+    use titan_voltr_integration::voltr_venue::VoltrVaultVenue;
+
+    use crate::common::VaultFixture;
+
+    const START_TS: u64 = 1_700_000_000;
+    const SLOT_TIME_SECS: u64 = 400;
+
+    fn snapshot(fixture: &VaultFixture, slot: u64) -> ReplaySnapshot {
+        ReplaySnapshot {
+            slot,
+            timestamp: START_TS + slot * SLOT_TIME_SECS,
+            accounts: fixture.accounts().into_iter().collect(),
+        }
+    }
+
+    /// Largest LP amount of a 10x grid the vault would redeem at `ts`.
+    fn fillable_redeem(venue: &VoltrVaultVenue, fixture: &VaultFixture, ts: u64) -> u64 {
+        (6..=12)
+            .map(|exp| 10u64.pow(exp))
+            .filter(|&amount| {
+                let request = QuoteRequest {
+                    input_mint: fixture.lp_mint(),
+                    output_mint: fixture.asset_mint,
+                    amount,
+                    swap_type: SwapType::ExactIn,
+                };
+                !venue.quote_at(&request, ts).unwrap().not_enough_liquidity
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    #[test]
+    fn test_idle_ramp_grows_fillable_redeem() {
+        let fixture = VaultFixture::default();
+        let idle_ramp = [
+            0,
+            1_000_000_000,
+            10_000_000_000,
+            100_000_000_000,
+            1_000_000_000_000,
+        ];
+        // Listed out of order; the cache replays by slot.
+        let snapshots = [3, 0, 1, 4, 2]
+            .into_iter()
+            .map(|slot| {
+                let ramped = VaultFixture {
+                    idle_balance: idle_ramp[slot as usize],
+                    ..fixture.clone()
+                };
+                snapshot(&ramped, slot)
+            })
+            .collect();
+        let mut session = ReplaySession::new(
+            VoltrVaultVenue::new_uninitialized(fixture.vault_key),
+            ReplayCache::new(snapshots),
+        );
+
+        let steps = session
+            .run(|venue, ts| fillable_redeem(venue, &fixture, ts))
+            .unwrap();
+        assert_eq!(
+            steps.iter().map(|step| step.slot).collect::<Vec<_>>(),
+            [0, 1, 2, 3, 4]
+        );
+        assert_eq!(steps[2].timestamp, START_TS + 2 * SLOT_TIME_SECS);
+        assert!(steps.iter().all(|step| step.changed));
+        assert_eq!(steps[0].value, 0);
+        for pair in steps.windows(2) {
+            assert!(pair[1].value > pair[0].value, "{pair:?}");
+        }
+
+        // The series is exhausted.
+        assert!(session.step().is_none());
+        assert!(session.run(|_, _| ()).unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_repeated_snapshot_is_unchanged() {
+        let fixture = VaultFixture::default();
+        let first = snapshot(&fixture, 10);
+        let repeated = ReplaySnapshot {
+            slot: 11,
+            timestamp: first.timestamp + SLOT_TIME_SECS,
+            ..first.clone()
+        };
+        let mut session = ReplaySession::new(
+            fixture.venue().await,
+            ReplayCache::new(vec![first, repeated]),
+        );
+
+        assert!(session.step().unwrap().unwrap());
+        let cache = session.cache();
+        assert_eq!(
+            cache.get_account(&fixture.vault_key).await.unwrap(),
+            Some(fixture.vault_account())
+        );
+        let with_slots = cache
+            .get_accounts_with_slots(&[fixture.vault_key])
+            .await
+            .unwrap();
+        assert_eq!(with_slots[0].1, Some(10));
+
+        assert!(!session.step().unwrap().unwrap());
+        assert_eq!(session.cache().position(), 1);
+        assert!(session.step().is_none());
+    }
+
+    #[test]
+    fn test_snapshots_quoted_at_their_timestamp() {
+        // Accruing management fee, so the price moves with the clock.
+        let fixture = VaultFixture {
+            manager_management_fee: 200,
+            last_management_fee_update_ts: START_TS,
+            last_updated_ts: START_TS,
+            ..Default::default()
+        };
+        let snapshots = [0, 30 * 86_400 / SLOT_TIME_SECS]
+            .into_iter()
+            .map(|slot| snapshot(&fixture, slot))
+            .collect();
+        let mut session = ReplaySession::new(
+            VoltrVaultVenue::new_uninitialized(fixture.vault_key),
+            ReplayCache::new(snapshots),
+        );
+        let redeem = QuoteRequest {
+            input_mint: fixture.lp_mint(),
+            output_mint: fixture.asset_mint,
+            amount: 1_000_000_000,
+            swap_type: SwapType::ExactIn,
+        };
+
+        let steps = session
+            .run(|venue, ts| {
+                assert_eq!(venue.effective_ts(), ts);
+                assert_eq!(venue.state_ts(), ts);
+                let output = venue.quote(redeem.clone()).unwrap().expected_output;
+                assert_eq!(output, venue.quote_at(&redeem, ts).unwrap().expected_output);
+                output
+            })
+            .unwrap();
+        assert_eq!(steps.len(), 2);
+        // 30 days of fee dilute the LP.
+        assert!(steps[1].value < steps[0].value, "{steps:?}");
+    }
+}
+
+#[cfg(test)]
+mod test_stale_state {
+    //! Degraded quoting through failing refreshes:
+    //! - failures below the threshold leave the venue ready,
+    //! - past it the venue is stale, still quoting the last good state with
+    //!   its age and a `degraded` flag in the quote meta,
+    //! - a stale state older than the hard limit is refused, for quotes and
+    //!   swaps alike, until a refresh succeeds,
+    //! - the failure streak and its timestamps reset on recovery, through
+    //!   `warm_up()` and `update_state_excluding_vault()` as well,
+    //! - without a threshold failures are counted but never degrade quotes.
+
+    use solana_pubkey::Pubkey;
+    use titan_integration_template::trading_venue::{
+        FromAccount, QuoteRequest, SwapType, TradingVenue,
+    };
+
+    use titan_voltr_integration::caches::ClosureCache;
+    use titan_voltr_integration::clock::QuoteClock;
+    use titan_voltr_integration::config::VoltrVenueConfig;
+    use titan_voltr_integration::status::{RefreshStreak, VenueStatus};
+    use titan_voltr_integration::voltr_venue::VoltrVaultVenue;
+    use titan_voltr_integration::warmup::WarmupOptions;
+
+    use crate::common::VaultFixture;
+
+    const T0: u64 = 1_700_000_000;
+
+    async fn venue(fixture: &VaultFixture, config: VoltrVenueConfig) -> VoltrVaultVenue {
+        let mut venue = VoltrVaultVenue::from_account(&fixture.vault_key, &fixture.vault_account())
+            .unwrap()
+            .with_config(VoltrVenueConfig {
+                clock: QuoteClock::Fixed(T0),
+                ..config
+            });
+        venue.update_state(&fixture.cache()).await.unwrap();
+        venue
+    }
+
+    fn deposit(fixture: &VaultFixture) -> QuoteRequest {
+        QuoteRequest {
+            input_mint: fixture.asset_mint,
+            output_mint: fixture.lp_mint(),
+            amount: 1_000_000,
+            swap_type: SwapType::ExactIn,
+        }
+    }
+
+    /// Fail a refresh at `ts`, as during an RPC outage.
+    async fn fail_refresh(venue: &mut VoltrVaultVenue, ts: u64) {
+        venue.set_clock(QuoteClock::Fixed(ts));
+        let outage = ClosureCache::new(|_: &Pubkey| None);
+        assert!(venue.update_state(&outage).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fail_and_recover() {
+        let fixture = VaultFixture::default();
+        let mut venue = venue(
+            &fixture,
+            VoltrVenueConfig {
+                stale_after_failed_refreshes: 2,
+                max_stale_state_age_secs: Some(60),
+                ..Default::default()
+            },
+        )
+        .await;
+        let request = deposit(&fixture);
+        let (good, meta) = venue.quote_with_meta(&request).unwrap();
+        assert_eq!((meta.state_age_secs, meta.degraded), (0, false));
+        assert_eq!(venue.refresh_streak().last_success_ts, Some(T0));
+
+        // One failure is within the threshold.
+        fail_refresh(&mut venue, T0 + 10).await;
+        assert_eq!(venue.status(), VenueStatus::Ready);
+        let (_, meta) = venue.quote_with_meta(&request).unwrap();
+        assert_eq!((meta.state_age_secs, meta.degraded), (10, false));
+
+        // The second goes stale, still quoting the last good state.
+        fail_refresh(&mut venue, T0 + 20).await;
+        assert_eq!(
+            venue.status(),
+            VenueStatus::Stale {
+                failed_refreshes: 2,
+                state_age_secs: 20,
+            }
+        );
+        let (quote, meta) = venue.quote_with_meta(&request).unwrap();
+        assert_eq!(quote.expected_output, good.expected_output);
+        assert_eq!((meta.state_age_secs, meta.degraded), (20, true));
+        let detailed = venue
+            .quote_detailed(&request, &Pubkey::new_unique(), None)
+            .await
+            .unwrap();
+        assert!(detailed.meta.degraded);
+
+        // Past the hard limit nothing is served.
+        venue.set_clock(QuoteClock::Fixed(T0 + 61));
+        assert_eq!(
+            venue.status(),
+            VenueStatus::Expired {
+                failed_refreshes: 2,
+                state_age_secs: 61,
+            }
+        );
+        let err = venue.quote(request.clone()).unwrap_err();
+        assert!(err.to_string().contains("Stale State Expired"), "{err}");
+        assert!(venue
+            .generate_swap_instruction(request.clone(), Pubkey::new_unique())
+            .is_err());
+
+        fail_refresh(&mut venue, T0 + 70).await;
+        assert_eq!(
+            venue.refresh_streak(),
+            RefreshStreak {
+                failures: 3,
+                first_failure_ts: Some(T0 + 10),
+                last_failure_ts: Some(T0 + 70),
+                last_success_ts: Some(T0),
+            }
+        );
+
+        // One successful refresh clears it all.
+        venue.set_clock(QuoteClock::Fixed(T0 + 80));
+        venue.update_state(&fixture.cache()).await.unwrap();
+        assert_eq!(venue.status(), VenueStatus::Ready);
+        assert_eq!(
+            venue.refresh_streak(),
+            RefreshStreak {
+                last_success_ts: Some(T0 + 80),
+                ..Default::default()
+            }
+        );
+        let (quote, meta) = venue.quote_with_meta(&request).unwrap();
+        assert_eq!(quote.expected_output, good.expected_output);
+        assert_eq!((meta.state_age_secs, meta.degraded), (0, false));
+    }
+
+    #[tokio::test]
+    async fn test_other_refresh_paths_recover() {
+        let fixture = VaultFixture::default();
+        let mut venue = venue(
+            &fixture,
+            VoltrVenueConfig {
+                stale_after_failed_refreshes: 1,
+                max_stale_state_age_secs: Some(60),
+                ..Default::default()
+            },
+        )
+        .await;
+        let recovered = |ts| RefreshStreak {
+            last_success_ts: Some(ts),
+            ..Default::default()
+        };
+
+        fail_refresh(&mut venue, T0 + 100).await;
+        assert!(matches!(venue.status(), VenueStatus::Expired { .. }));
+        venue
+            .warm_up(&fixture.cache(), WarmupOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(venue.status(), VenueStatus::Ready);
+        assert_eq!(venue.refresh_streak(), recovered(T0 + 100));
+        assert_eq!(venue.state_age_secs(), 0);
+
+        fail_refresh(&mut venue, T0 + 200).await;
+        assert!(matches!(venue.status(), VenueStatus::Expired { .. }));
+        venue
+            .update_state_excluding_vault(&fixture.cache())
+            .await
+            .unwrap();
+        assert_eq!(venue.status(), VenueStatus::Ready);
+        assert_eq!(venue.refresh_streak(), recovered(T0 + 200));
+        assert_eq!(venue.state_age_secs(), 0);
+
+        // Their failures count as well.
+        let outage = ClosureCache::new(|_: &Pubkey| None);
+        assert!(venue
+            .warm_up(&outage, WarmupOptions::default())
+            .await
+            .is_err());
+        assert!(venue.update_state_excluding_vault(&outage).await.is_err());
+        assert_eq!(venue.refresh_streak().failures, 2);
+    }
+
+    #[tokio::test]
+    async fn test_no_threshold_never_degrades() {
+        let fixture = VaultFixture::default();
+        let mut venue = venue(
+            &fixture,
+            VoltrVenueConfig {
+                max_stale_state_age_secs: Some(60),
+                ..Default::default()
+            },
+        )
+        .await;
+        for i in 1..=5 {
+            fail_refresh(&mut venue, T0 + i * 100).await;
+        }
+
+        assert_eq!(venue.refresh_streak().failures, 5);
+        assert!(!venue.is_degraded());
+        assert_eq!(venue.status(), VenueStatus::Ready);
+        let (_, meta) = venue.quote_with_meta(&deposit(&fixture)).unwrap();
+        assert_eq!((meta.state_age_secs, meta.degraded), (500, false));
+    }
+}
+
+#[cfg(test)]
+mod test_state_update {
+    //! Offline state-loading tests driven by an in-memory accounts cache:
+    //! - lazily constructed venues load the vault on their first refresh,
+    //! - quoting before that refresh fails cleanly,
+    //! - the first refresh after `from_account()` reuses the parsed vault,
+    //! - pruned asset mints fall back to known metadata,
+    //! - slot-aware refreshes retry or flag accounts from divergent slots,
+    //! - refreshes that change no mint keep the same `token_info`, updating
+    //!   max amounts moved by fee accrual in place,
+    //! - an LP mint controlled by another authority marks the vault migrated,
+    //! - fees summing past `MAX_FEE_BPS` are rejected when the vault loads,
+    //! - every broken account of a refresh is reported, and none applied,
+    //! - idle ATAs with trailing bytes past the Token layout load their
+    //!   balance, unless strict layout checks are configured,
+    //! - a vault whose asset and LP mints collide, or are unset, is rejected,
+    //! - a venue given another vault's state fails its first refresh, and a
+    //!   vault key that starts serving another vault fails later ones.
+
+    use solana_account::Account;
+    use solana_program::program_option::COption;
+    use solana_program_pack::Pack;
+    use solana_pubkey::Pubkey;
+    use spl_token::state::Mint;
+    use titan_integration_template::trading_venue::{
+        error::TradingVenueError, FromAccount, QuoteRequest, SwapType, TradingVenue,
+    };
+
+    use titan_voltr_integration::clock::QuoteClock;
+    use titan_voltr_integration::config::{MintMetadata, VoltrVenueConfig};
+    use titan_voltr_integration::constants::{TOKEN_PROGRAM, VOLTR_VAULT_PROGRAM};
+    use titan_voltr_integration::pda::vault_lp_mint_auth_pda;
+    use titan_voltr_integration::state::FeeConfiguration;
+    use titan_voltr_integration::status::{LpMintAuthorityMismatch, VenueStatus};
+    use titan_voltr_integration::synthetic::SyntheticVaultParams;
+    use titan_voltr_integration::voltr_venue::{SwapDirection, VoltrVaultVenue};
+    use titan_voltr_integration::{StateUpdateError, Vault, VoltrError};
+
+    use crate::common::{SlotMockCache, VaultFixture};
+
+    fn deposit_request(fixture: &VaultFixture, amount: u64) -> QuoteRequest {
+        QuoteRequest {
+            input_mint: fixture.asset_mint,
+            output_mint: fixture.lp_mint(),
+            amount,
+            swap_type: SwapType::ExactIn,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lazy_construction() {
+        let fixture = VaultFixture::default();
+        let cache = fixture.cache();
+
+        let mut venue = VoltrVaultVenue::new_uninitialized(fixture.vault_key);
+        assert!(!venue.initialized());
+        assert_eq!(
+            venue.get_required_pubkeys_for_update().unwrap(),
+            vec![fixture.vault_key]
+        );
+        assert!(venue.quote(deposit_request(&fixture, 1_000)).is_err());
+        assert!(venue
+            .generate_swap_instruction(deposit_request(&fixture, 1_000), Pubkey::new_unique())
+            .is_err());
+
+        venue.update_state(&cache).await.unwrap();
+
+        assert!(venue.initialized());
+        assert_eq!(
+            venue.get_required_pubkeys_for_update().unwrap(),
+            vec![
+                fixture.vault_key,
+                fixture.lp_mint(),
+                fixture.asset_mint,
+                fixture.idle_ata
+            ]
+        );
+        assert_eq!(venue.get_token_info().len(), 2);
+
+        // The lazy venue quotes exactly like one built from the vault account.
+        let eager = fixture.venue().await;
+        for amount in [1_000_000, 123_456_789] {
+            let lazy_quote = venue.quote(deposit_request(&fixture, amount)).unwrap();
+            let eager_quote = eager.quote(deposit_request(&fixture, amount)).unwrap();
+            assert_eq!(lazy_quote.expected_output, eager_quote.expected_output);
+            assert!(lazy_quote.expected_output > 0);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lazy_construction_missing_vault() {
+        let fixture = VaultFixture::default();
+        let mut cache = fixture.cache();
+        cache.remove(&fixture.vault_key);
+
+        let mut venue = VoltrVaultVenue::new_uninitialized(fixture.vault_key);
+        assert!(venue.update_state(&cache).await.is_err());
+        assert!(!venue.initialized());
+        assert_eq!(
+            venue.get_required_pubkeys_for_update().unwrap(),
+            vec![fixture.vault_key]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_first_refresh_reuses_constructed_vault() {
+        let fixture = VaultFixture {
+            manager_management_fee: 200,
+            issuance_fee: 25,
+            redemption_fee: 10,
+            ..Default::default()
+        };
+
+        // A cache without the vault account: only the optimized path succeeds.
+        let mut cache = fixture.cache();
+        cache.remove(&fixture.vault_key);
+
+        let mut venue =
+            VoltrVaultVenue::from_account(&fixture.vault_key, &fixture.vault_account()).unwrap();
+        venue.update_state(&cache).await.unwrap();
+        assert!(venue.initialized());
+
+        let mut full = VoltrVaultVenue::new_uninitialized(fixture.vault_key);
+        full.update_state(&fixture.cache()).await.unwrap();
+
+        for request in [
+            deposit_request(&fixture, 5_000_000),
+            QuoteRequest {
+                input_mint: fixture.lp_mint(),
+                output_mint: fixture.asset_mint,
+                amount: 5_000_000,
+                swap_type: SwapType::ExactIn,
+            },
+        ] {
+            assert_eq!(
+                venue.quote(request.clone()).unwrap().expected_output,
+                full.quote(request).unwrap().expected_output
+            );
+        }
+
+        // Subsequent refreshes always re-fetch the vault.
+        assert!(venue.update_state(&cache).await.is_err());
+        venue.update_state(&fixture.cache()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_asset_mint_metadata_fallback() {
+        let fixture = VaultFixture {
+            asset_decimals: 8,
+            ..Default::default()
+        };
+        let mut pruned = fixture.cache();
+        pruned.remove(&fixture.asset_mint);
+
+        // Neither the cache nor the config knows the asset mint.
+        let mut venue = VoltrVaultVenue::new_uninitialized(fixture.vault_key);
+        assert!(venue.update_state(&pruned).await.is_err());
+        assert!(!venue.initialized());
+
+        // Injected metadata stands in for the pruned mint.
+        let config = VoltrVenueConfig {
+            known_mint_metadata: Some(MintMetadata {
+                decimals: 8,
+                token_program: TOKEN_PROGRAM,
+            }),
+            ..Default::default()
+        };
+        let mut venue = VoltrVaultVenue::new_uninitialized(fixture.vault_key).with_config(config);
+        venue.update_state(&pruned).await.unwrap();
+        assert_eq!(venue.asset_mint_decimals, 8);
+        assert_eq!(venue.asset_token_program, TOKEN_PROGRAM);
+        assert_eq!(venue.get_token_info()[0].pubkey, fixture.asset_mint);
+
+        let full = fixture.venue().await;
+        let request = deposit_request(&fixture, 10_000_000);
+        assert_eq!(
+            venue.quote(request.clone()).unwrap().expected_output,
+            full.quote(request).unwrap().expected_output
+        );
+
+        // Once loaded, refreshes no longer need the asset mint at all.
+        let mut venue = fixture.venue().await;
+        venue.update_state(&pruned).await.unwrap();
+        assert_eq!(venue.asset_mint_decimals, 8);
+    }
+
+    #[tokio::test]
+    async fn test_slot_aware_refresh() {
+        let fixture = VaultFixture::default();
+        let config = VoltrVenueConfig {
+            max_slot_span: 2,
+            inconsistent_fetch_retries: 2,
+            ..Default::default()
+        };
+        let divergent = vec![Some(100), Some(100), Some(100), Some(110)];
+        let consistent = vec![Some(120), Some(121), Some(119), Some(120)];
+
+        // A divergent fetch is retried and the consistent retry is loaded.
+        let cache = SlotMockCache::new(fixture.cache(), [divergent.clone(), consistent]);
+        let mut venue = VoltrVaultVenue::new_uninitialized(fixture.vault_key)
+            .with_config(config.clone());
+        assert_eq!(venue.status(), VenueStatus::Uninitialized);
+        venue.update_state_with_slots(&cache).await.unwrap();
+        assert_eq!(cache.fetches(), 2);
+        assert_eq!(venue.status(), VenueStatus::Ready);
+        assert_eq!(venue.state_slot(), Some(121));
+
+        // Every attempt divergent: loaded but flagged, quotes still served.
+        let cache = SlotMockCache::new(fixture.cache(), [divergent]);
+        venue.update_state_with_slots(&cache).await.unwrap();
+        assert_eq!(cache.fetches(), 3);
+        assert_eq!(venue.status(), VenueStatus::Inconsistent { slot_span: 10 });
+        assert_eq!(venue.state_slot(), Some(110));
+        assert!(venue.quote(deposit_request(&fixture, 1_000_000)).is_ok());
+
+        // A plain refresh cannot check slots and clears the flag.
+        venue.update_state(&fixture.cache()).await.unwrap();
+        assert_eq!(venue.status(), VenueStatus::Ready);
+        assert_eq!(venue.state_slot(), None);
+
+        // Without slots the check is skipped.
+        let cache = SlotMockCache::new(fixture.cache(), [vec![None; 4]]);
+        venue.update_state_with_slots(&cache).await.unwrap();
+        assert_eq!(cache.fetches(), 1);
+        assert_eq!(venue.status(), VenueStatus::Ready);
+        assert_eq!(venue.state_slot(), None);
+    }
+
+    #[tokio::test]
+    async fn test_token_info_kept_across_identical_refreshes() {
+        let fixture = VaultFixture::default();
+        let mut venue = VoltrVaultVenue::new_uninitialized(fixture.vault_key);
+        venue.update_state(&fixture.cache()).await.unwrap();
+        let token_info = venue.get_token_info().as_ptr();
+
+        for _ in 0..3 {
+            venue.update_state(&fixture.cache()).await.unwrap();
+            assert_eq!(venue.get_token_info().as_ptr(), token_info);
+        }
+
+        // A supply change rewrites the LP mint and the redeemable maximum.
+        let mut cache = fixture.cache();
+        cache.insert(
+            fixture.lp_mint(),
+            fixture.lp_mint_account(fixture.lp_supply / 2),
+        );
+        venue.update_state(&cache).await.unwrap();
+        assert_ne!(venue.get_token_info().as_ptr(), token_info);
+        assert_eq!(venue.get_token_info()[1].pubkey, fixture.lp_mint());
+    }
+
+    #[tokio::test]
+    async fn test_token_info_kept_while_fees_accrue() {
+        const T0: u64 = 1_700_000_000;
+        // Redeems are capped by the idle balance, so the redeemable LP moves
+        // with every day of management fee diluting the share price.
+        let fixture = VaultFixture {
+            manager_management_fee: 200,
+            last_management_fee_update_ts: T0,
+            last_updated_ts: T0,
+            idle_balance: 10_000_000_000,
+            ..Default::default()
+        };
+        let mut venue =
+            VoltrVaultVenue::new_uninitialized(fixture.vault_key).with_config(VoltrVenueConfig {
+                clock: QuoteClock::Fixed(T0 + 86_400),
+                ..Default::default()
+            });
+        venue.update_state(&fixture.cache()).await.unwrap();
+        let token_info = venue.get_token_info().as_ptr();
+
+        for day in 2..4 {
+            let max_redeem = venue.max_input_amount(SwapDirection::Redeem);
+            venue.set_clock(QuoteClock::Fixed(T0 + day * 86_400));
+            venue.update_state(&fixture.cache()).await.unwrap();
+            assert_eq!(venue.get_token_info().as_ptr(), token_info);
+            assert_ne!(venue.max_input_amount(SwapDirection::Redeem), max_redeem);
+        }
+    }
+
+    /// The fixture's LP mint with its authorities replaced.
+    fn forged_lp_mint(
+        fixture: &VaultFixture,
+        mint_authority: Option<Pubkey>,
+        freeze_authority: Option<Pubkey>,
+    ) -> Account {
+        let mut account = fixture.lp_mint_account(fixture.lp_supply);
+        let mut mint = Mint::unpack(&account.data).unwrap();
+        mint.mint_authority = mint_authority.map_or(COption::None, COption::Some);
+        mint.freeze_authority = freeze_authority.map_or(COption::None, COption::Some);
+        mint.pack_into_slice(&mut account.data);
+        account
+    }
+
+    #[tokio::test]
+    async fn test_lp_mint_authority_mismatch_marks_migrated() {
+        let fixture = VaultFixture::default();
+        let expected = vault_lp_mint_auth_pda(&fixture.vault_key, &VOLTR_VAULT_PROGRAM);
+        let successor = Pubkey::new_unique();
+
+        for (mint_authority, freeze_authority) in [
+            // Minting moved to another program's PDA.
+            (Some(successor), None),
+            // Mint authority revoked, as on a closed vault.
+            (None, None),
+            // Still minted by the vault, but frozen by someone else.
+            (Some(expected), Some(successor)),
+        ] {
+            let mut venue = VoltrVaultVenue::new_uninitialized(fixture.vault_key);
+            let mut cache = fixture.cache();
+            cache.insert(
+                fixture.lp_mint(),
+                forged_lp_mint(&fixture, mint_authority, freeze_authority),
+            );
+            venue.update_state(&cache).await.unwrap();
+
+            assert_eq!(
+                venue.status(),
+                VenueStatus::Migrated(LpMintAuthorityMismatch {
+                    expected,
+                    mint_authority,
+                    freeze_authority,
+                })
+            );
+            let err = venue
+                .quote(deposit_request(&fixture, 1_000_000))
+                .unwrap_err();
+            assert!(err.to_string().contains("Vault Migrated"), "{err}");
+            assert!(
+                venue
+                    .generate_swap_instruction(deposit_request(&fixture, 1_000_000), successor)
+                    .is_err()
+            );
+
+            // The authority returning clears the flag.
+            venue.update_state(&fixture.cache()).await.unwrap();
+            assert_eq!(venue.status(), VenueStatus::Ready);
+        }
+
+        // Freezing by the vault's own authority is expected.
+        let mut venue = VoltrVaultVenue::new_uninitialized(fixture.vault_key);
+        let mut cache = fixture.cache();
+        cache.insert(
+            fixture.lp_mint(),
+            forged_lp_mint(&fixture, Some(expected), Some(expected)),
+        );
+        venue.update_state(&cache).await.unwrap();
+        assert_eq!(venue.status(), VenueStatus::Ready);
+    }
+
+    /// The fixture's vault account with `fees` written over its fee
+    /// configuration.
+    fn vault_account_with_fees(fixture: &VaultFixture, fees: &FeeConfiguration) -> Account {
+        let mut account = fixture.vault_account();
+        let fields = [
+            fees.manager_performance_fee,
+            fees.admin_performance_fee,
+            fees.manager_management_fee,
+            fees.admin_management_fee,
+            fees.redemption_fee,
+            fees.issuance_fee,
+            fees.protocol_performance_fee,
+            fees.protocol_management_fee,
+        ];
+        for (i, fee) in fields.iter().enumerate() {
+            let offset = 8 + 504 + 2 * i;
+            account.data[offset..offset + 2].copy_from_slice(&fee.to_le_bytes());
+        }
+        account
+    }
+
+    #[tokio::test]
+    async fn test_fee_configuration_over_max_rejected_at_load() {
+        let management = |manager, admin, protocol| FeeConfiguration {
+            manager_management_fee: manager,
+            admin_management_fee: admin,
+            protocol_management_fee: protocol,
+            ..Default::default()
+        };
+        let performance = |manager, admin, protocol| FeeConfiguration {
+            manager_performance_fee: manager,
+            admin_performance_fee: admin,
+            protocol_performance_fee: protocol,
+            ..Default::default()
+        };
+        assert!(management(5_000, 3_000, 2_000).validate().is_ok());
+        assert!(performance(10_000, 0, 0).validate().is_ok());
+
+        let fixture = VaultFixture::default();
+        let invalid = VoltrError::InvalidFeeConfiguration.to_string();
+        for fees in [
+            management(5_000, 5_000, 1),
+            management(10_000, 10_000, 10_000),
+            performance(3_334, 3_333, 3_334),
+            performance(10_000, 10_000, 10_000),
+        ] {
+            assert!(matches!(
+                fees.validate(),
+                Err(VoltrError::InvalidFeeConfiguration)
+            ));
+
+            let account = vault_account_with_fees(&fixture, &fees);
+            let Err(err) = VoltrVaultVenue::from_account(&fixture.vault_key, &account) else {
+                panic!("from_account() accepted an invalid fee configuration");
+            };
+            assert!(err.to_string().contains(&invalid), "{err}");
+            let params = SyntheticVaultParams {
+                fee_configuration: fees.clone(),
+                ..fixture.synthetic_params()
+            };
+            assert!(VoltrVaultVenue::new_synthetic(fixture.vault_key, params).is_err());
+
+            // A refresh fails, leaving the loaded vault quoting as before.
+            let mut venue = fixture.venue().await;
+            let before = venue.quote(deposit_request(&fixture, 1_000_000)).unwrap();
+            let mut cache = fixture.cache();
+            cache.insert(fixture.vault_key, account);
+            let err = venue.update_state(&cache).await.unwrap_err();
+            assert!(err.to_string().contains(&invalid), "{err}");
+            assert_eq!(
+                venue
+                    .quote(deposit_request(&fixture, 1_000_000))
+                    .unwrap()
+                    .expected_output,
+                before.expected_output
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_broken_accounts_reported_together() {
+        let fixture = VaultFixture::default();
+        let mut venue = fixture.venue().await;
+        let quote = |venue: &VoltrVaultVenue| {
+            venue
+                .quote(deposit_request(&fixture, 1_000_000))
+                .unwrap()
+                .expected_output
+        };
+        let before = quote(&venue);
+        let truncated = |pubkey: &Pubkey| {
+            let mut account = fixture.cache().accounts[pubkey].clone();
+            account.data.truncate(10);
+            account
+        };
+        let truncated_vault = truncated(&fixture.vault_key);
+        let truncated_idle = truncated(&fixture.idle_ata);
+
+        // A missing LP mint and a malformed idle ATA, with or without a
+        // malformed vault next to them.
+        for broken_vault in [false, true] {
+            let mut cache = fixture.cache();
+            cache.remove(&fixture.lp_mint());
+            cache.insert(fixture.idle_ata, truncated_idle.clone());
+            if broken_vault {
+                cache.insert(fixture.vault_key, truncated_vault.clone());
+            }
+
+            let err = venue.update_state(&cache).await.unwrap_err().to_string();
+            let failures = if broken_vault { 3 } else { 2 };
+            assert!(
+                err.contains(&format!("{failures} accounts failed to load")),
+                "{err}"
+            );
+            assert!(err.contains(&fixture.lp_mint().to_string()), "{err}");
+            assert!(err.contains(&fixture.idle_ata.to_string()), "{err}");
+            assert_eq!(err.contains(&fixture.vault_key.to_string()), broken_vault);
+
+            // Nothing was applied, not even the accounts that parsed.
+            assert!(venue.initialized());
+            assert_eq!(quote(&venue), before);
+        }
+
+        // A single failure is passed through as is.
+        let mut cache = fixture.cache();
+        cache.remove(&fixture.idle_ata);
+        assert!(matches!(
+            venue.update_state(&cache).await,
+            Err(TradingVenueError::NoAccountFound(_))
+        ));
+        venue.update_state(&fixture.cache()).await.unwrap();
+        assert_eq!(quote(&venue), before);
+
+        // Each failure is listed on its own line.
+        let composite = StateUpdateError {
+            per_account: vec![
+                (
+                    fixture.lp_mint(),
+                    TradingVenueError::NoAccountFound("lp".into()),
+                ),
+                (
+                    fixture.idle_ata,
+                    TradingVenueError::DeserializationFailed("idle".into()),
+                ),
+            ],
+        };
+        let lines: Vec<String> = composite.to_string().lines().map(str::to_owned).collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].contains(&fixture.lp_mint().to_string()));
+        assert!(lines[2].contains(&fixture.idle_ata.to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_padded_idle_ata_tolerated() {
+        let fixture = VaultFixture {
+            idle_balance: 123_456_789,
+            ..Default::default()
+        };
+        // The Token layout followed by an extension tail.
+        let mut cache = fixture.cache();
+        let mut idle = cache.accounts[&fixture.idle_ata].clone();
+        idle.data.extend_from_slice(&[0xab; 37]);
+        assert!(spl_token::state::Account::unpack(&idle.data).is_err());
+        cache.insert(fixture.idle_ata, idle);
+
+        let mut venue = fixture.venue().await;
+        venue.asset_idle_balance = 0;
+        venue.update_state(&cache).await.unwrap();
+        assert_eq!(venue.asset_idle_balance, fixture.idle_balance);
+
+        let mut strict = fixture.venue().await.with_config(VoltrVenueConfig {
+            strict_token_account_layout: true,
+            ..Default::default()
+        });
+        assert!(matches!(
+            strict.update_state(&cache).await,
+            Err(TradingVenueError::DeserializationFailed(_))
+        ));
+        strict.update_state(&fixture.cache()).await.unwrap();
+        assert_eq!(strict.asset_idle_balance, fixture.idle_balance);
+    }
+
+    #[tokio::test]
+    async fn test_colliding_vault_mints_rejected() {
+        let fixture = VaultFixture::default();
+        let invalid = VoltrError::InvalidVaultMints.to_string();
+        let forged = |asset_mint: Pubkey, lp_mint: Pubkey| {
+            let mut account = fixture.vault_account();
+            account.data[8 + 96..8 + 128].copy_from_slice(asset_mint.as_ref());
+            account.data[8 + 264..8 + 296].copy_from_slice(lp_mint.as_ref());
+            account
+        };
+
+        for account in [
+            // The LP mint posing as the asset, and the other way around.
+            forged(fixture.lp_mint(), fixture.lp_mint()),
+            forged(fixture.asset_mint, fixture.asset_mint),
+            forged(Pubkey::default(), fixture.lp_mint()),
+            forged(fixture.asset_mint, Pubkey::default()),
+        ] {
+            let Err(err) = VoltrVaultVenue::from_account(&fixture.vault_key, &account) else {
+                panic!("from_account() accepted colliding mints");
+            };
+            assert!(err.to_string().contains(&invalid), "{err}");
+
+            let mut cache = fixture.cache();
+            cache.insert(fixture.vault_key, account);
+
+            // A lazy venue never gets past its vault.
+            let mut lazy = VoltrVaultVenue::new_uninitialized(fixture.vault_key);
+            let err = lazy.update_state(&cache).await.unwrap_err();
+            assert!(err.to_string().contains(&invalid), "{err}");
+            assert!(!lazy.initialized());
+            assert_eq!(
+                lazy.get_required_pubkeys_for_update().unwrap(),
+                vec![fixture.vault_key]
+            );
+
+            // A loaded venue keeps quoting its previous state.
+            let mut venue = fixture.venue().await;
+            let before = venue.quote(deposit_request(&fixture, 1_000_000)).unwrap();
+            assert!(venue.update_state(&cache).await.is_err());
+            assert_eq!(venue.vault_state.asset.mint, fixture.asset_mint);
+            assert_eq!(venue.vault_state.lp.mint, fixture.lp_mint());
+            assert_eq!(
+                venue
+                    .quote(deposit_request(&fixture, 1_000_000))
+                    .unwrap()
+                    .expected_output,
+                before.expected_output
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_swapped_vault_key_state_caught() {
+        let a = VaultFixture::default();
+        let b = VaultFixture::default();
+        let mut cache = a.cache();
+        for (key, account) in b.accounts() {
+            cache.insert(key, account);
+        }
+        let mismatch = |err: TradingVenueError, expected_mint: Pubkey, found_mint: Pubkey| {
+            let expected = VoltrError::VaultKeyStateMismatch {
+                vault_key: a.vault_key,
+                expected_mint,
+                found_mint,
+            };
+            assert_eq!(
+                err.to_string(),
+                TradingVenueError::from(expected).to_string()
+            );
+        };
+
+        // The keys of one list zipped with the states of another.
+        let mut swapped = VoltrVaultVenue::from_account(&a.vault_key, &b.vault_account()).unwrap();
+        let err = swapped.update_state(&cache).await.unwrap_err();
+        mismatch(err, a.lp_mint(), b.lp_mint());
+        assert!(!swapped.initialized());
+
+        let try_new = |state, expected_asset_mint| {
+            VoltrVaultVenue::try_new(a.vault_key, state, expected_asset_mint)
+        };
+        let Err(err) = try_new(swapped.vault_state.clone(), None) else {
+            panic!("try_new() accepted another vault's state");
+        };
+        mismatch(err, a.lp_mint(), b.lp_mint());
+        let a_state = Vault::load(&a.vault_data()).unwrap();
+        let Err(err) = try_new(a_state.clone(), Some(b.asset_mint)) else {
+            panic!("try_new() accepted an unexpected asset mint");
+        };
+        mismatch(err, b.asset_mint, a.asset_mint);
+        let Ok(mut venue) = try_new(a_state, Some(a.asset_mint)) else {
+            panic!("try_new() refused the vault's own state");
+        };
+        venue.update_state(&cache).await.unwrap();
+        assert!(venue.initialized());
+
+        // Refreshes after the first re-read the vault itself.
+        cache.insert(a.vault_key, b.vault_account());
+        let err = venue.update_state(&cache).await.unwrap_err();
+        mismatch(err, a.lp_mint(), b.lp_mint());
+        assert_eq!(venue.vault_state.lp.mint, a.lp_mint());
+    }
+}
+
+#[cfg(test)]
+mod test_subscriptions {
+    //! Subscription plans of venues and their merge across venues:
+    //! - a venue plans the accounts it refreshes, the shared asset mint as
+    //!   static and the rest as high-frequency,
+    //! - three vaults of one asset subscribe to its mint once,
+    //! - an account planned at both priorities is kept at the higher one.
+
+    use std::collections::HashSet;
+
+    use solana_pubkey::Pubkey;
+    use titan_integration_template::trading_venue::TradingVenue;
+
+    use titan_voltr_integration::subscriptions::{merge_subscription_plans, SubscriptionPriority};
+    use titan_voltr_integration::voltr_venue::VoltrVaultVenue;
+
+    use crate::common::VaultFixture;
+
+    #[tokio::test]
+    async fn test_plan_covers_refreshed_accounts() {
+        let fixture = VaultFixture::default();
+        let venue = fixture.venue().await;
+        let plan = venue.subscription_plan();
+        let planned: HashSet<Pubkey> = plan.iter().map(|(pubkey, _)| *pubkey).collect();
+        let refreshed: HashSet<Pubkey> = venue
+            .get_required_pubkeys_for_update()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(planned, refreshed);
+        for (pubkey, priority) in plan {
+            let expected = if pubkey == fixture.asset_mint {
+                SubscriptionPriority::Static
+            } else {
+                SubscriptionPriority::HighFrequency
+            };
+            assert_eq!(priority, expected, "{pubkey}");
+        }
+
+        let vault_key = Pubkey::new_unique();
+        assert_eq!(
+            VoltrVaultVenue::new_uninitialized(vault_key).subscription_plan(),
+            vec![(vault_key, SubscriptionPriority::HighFrequency)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_shared_asset_mint_merged_once() {
+        let asset_mint = Pubkey::new_unique();
+        let mut plans = Vec::new();
+        for _ in 0..3 {
+            let fixture = VaultFixture {
+                asset_mint,
+                ..Default::default()
+            };
+            plans.push(fixture.venue().await.subscription_plan());
+        }
+
+        let merged = merge_subscription_plans(&plans);
+        let mints = merged
+            .subscriptions
+            .iter()
+            .filter(|(pubkey, _)| *pubkey == asset_mint)
+            .count();
+        assert_eq!(mints, 1);
+        assert_eq!(merged.len(), 3 * 3 + 1);
+        assert_eq!(merged.count(SubscriptionPriority::HighFrequency), 3 * 3);
+        assert_eq!(merged.count(SubscriptionPriority::Static), 1);
+        // First-seen order: the first venue's plan leads.
+        assert_eq!(merged.subscriptions[..4], plans[0][..]);
+    }
+
+    #[tokio::test]
+    async fn test_highest_priority_wins() {
+        // A vault whose asset is another vault's LP mint.
+        let base = VaultFixture::default();
+        let nested = VaultFixture {
+            asset_mint: base.lp_mint(),
+            ..Default::default()
+        };
+        let plans = [
+            nested.venue().await.subscription_plan(),
+            base.venue().await.subscription_plan(),
+        ];
+        assert!(plans[0].contains(&(base.lp_mint(), SubscriptionPriority::Static)));
+
+        let merged = merge_subscription_plans(&plans);
+        assert!(merged
+            .subscriptions
+            .contains(&(base.lp_mint(), SubscriptionPriority::HighFrequency)));
+        assert_eq!(merged.len(), 4 + 4 - 1);
+        assert_eq!(merged.count(SubscriptionPriority::Static), 1);
+        assert!(merge_subscription_plans(&[]).is_empty());
+    }
+}
+
+#[cfg(test)]
+mod test_summary {
+    //! One-line venue summaries and trimmed vault debug output.
+
+    use titan_voltr_integration::state::{HighWaterMark, LockedProfitState};
+
+    use crate::common::VaultFixture;
+
+    #[tokio::test]
+    async fn test_display_summary() {
+        let fixture = VaultFixture {
+            idle_balance: 400_000_000_000,
+            issuance_fee: 10,
+            redemption_fee: 25,
+            ..Default::default()
+        };
+        let venue = fixture.venue().await;
+
+        let summary = venue.summary();
+        assert_eq!(summary.vault_key, fixture.vault_key);
+        assert_eq!(summary.idle_balance, 400_000_000_000);
+        assert_eq!((summary.issuance_fee_bps, summary.redemption_fee_bps), (10, 25));
+
+        let line = venue.to_string();
+        assert_eq!(line, summary.to_string());
+        assert!(line.starts_with(&format!("Voltr vault {}", fixture.vault_key)));
+        for field in [
+            format!("asset={}", fixture.asset_mint),
+            "tvl=1000000000000".to_string(),
+            "idle=400000000000".to_string(),
+            format!("lp_supply={}", fixture.lp_supply),
+            "fees i/r=10/25".to_string(),
+        ] {
+            assert!(line.contains(&field), "{line} lacks {field}");
+        }
+        assert!(!line.contains('\n'));
+        assert!(line.len() <= 200, "{} chars", line.len());
+    }
+
+    #[test]
+    fn test_trimmed_debug() {
+        let hwm = HighWaterMark {
+            highest_asset_per_lp_decimal_bits: 1 << 48,
+            last_updated_ts: 1_700_000_000,
+        };
+        assert_eq!(
+            format!("{hwm:?}"),
+            "HighWaterMark { highest_asset_per_lp_decimal_bits: 0x1000000000000, \
+             last_updated_ts: 1700000000 (2023-11-14T22:13:20Z) }"
+        );
+
+        let locked = LockedProfitState::default();
+        assert_eq!(
+            format!("{locked:?}"),
+            "LockedProfitState { last_updated_locked_profit: 0, last_report: 0 (unset) }"
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_synthetic {
+    //! Venues built from explicit parameters:
+    //! - quote exactly like the same vault loaded from account bytes,
+    //! - build instructions and report tokens without any account fetch.
+
+    use solana_pubkey::Pubkey;
+    use titan_integration_template::trading_venue::{QuoteRequest, SwapType, TradingVenue};
+
+    use titan_voltr_integration::synthetic::SyntheticVaultParams;
+    use titan_voltr_integration::voltr_venue::VoltrVaultVenue;
+
+    use crate::common::VaultFixture;
+
+    #[tokio::test]
+    async fn test_synthetic_venue_matches_loaded_venue() {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let fixture = VaultFixture {
+            max_cap: 1_000_000_000_000 + 5_000_000,
+            idle_balance: 400_000_000_000,
+            accumulated_lp_fees: 1_000_000,
+            manager_management_fee: 200,
+            last_management_fee_update_ts: now - 86_400,
+            issuance_fee: 10,
+            redemption_fee: 25,
+            locked_profit_degradation_duration: 86_400,
+            last_updated_locked_profit: 10_000_000_000,
+            last_report: now - 3_600,
+            ..Default::default()
+        };
+        let loaded = fixture.venue().await;
+        let synthetic = fixture.synthetic_venue();
+
+        assert!(synthetic.initialized());
+        for (synthetic, loaded) in synthetic.get_token_info().iter().zip(loaded.get_token_info()) {
+            assert_eq!(synthetic.pubkey, loaded.pubkey);
+            assert_eq!(synthetic.get_token_program(), loaded.get_token_program());
+        }
+        for (input_mint, output_mint) in [
+            (fixture.asset_mint, fixture.lp_mint()),
+            (fixture.lp_mint(), fixture.asset_mint),
+        ] {
+            for amount in [0, 1, 1_000_000, 5_000_001, 500_000_000_000] {
+                let request = QuoteRequest {
+                    input_mint,
+                    output_mint,
+                    amount,
+                    swap_type: SwapType::ExactIn,
+                };
+                let expected = loaded.quote_at(&request, now).unwrap();
+                let actual = synthetic.quote_at(&request, now).unwrap();
+                assert_eq!(actual.expected_output, expected.expected_output);
+                assert_eq!(actual.not_enough_liquidity, expected.not_enough_liquidity);
+            }
+        }
+        assert_eq!(synthetic.bounds(0, 1).unwrap(), loaded.bounds(0, 1).unwrap());
+    }
+
+    #[test]
+    fn test_synthetic_venue_builds_instructions_offline() {
+        let vault_key = Pubkey::new_unique();
+        let asset_mint = Pubkey::new_unique();
+        let venue = VoltrVaultVenue::new_synthetic(
+            vault_key,
+            SyntheticVaultParams {
+                asset_mint,
+                asset_decimals: 6,
+                idle_ata: Pubkey::new_unique(),
+                // Enough idle for the redeem to be buildable.
+                total_value: 1_000_000,
+                idle_balance: 1_000_000,
+                lp_supply: 1_000_000,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let lp_mint = VoltrVaultVenue::derive_vault_lp_mint_pda(&vault_key);
+
+        assert_eq!(venue.get_token(0).unwrap().pubkey, asset_mint);
+        assert_eq!(venue.get_token(1).unwrap().pubkey, lp_mint);
+
+        let user = Pubkey::new_unique();
+        for (input_mint, output_mint) in [(asset_mint, lp_mint), (lp_mint, asset_mint)] {
+            let instruction = venue
+                .generate_swap_instruction(
+                    QuoteRequest {
+                        input_mint,
+                        output_mint,
+                        amount: 1_000,
+                        swap_type: SwapType::ExactIn,
+                    },
+                    user,
+                )
+                .unwrap();
+            assert!(instruction.accounts.iter().any(|meta| meta.pubkey == vault_key));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_warmup {
+    //! `warm_up()` against fixture caches:
+    //! - a lazily constructed venue loads in two batched calls, one already
+    //!   holding its vault in one, lookup table included,
+    //! - afterwards quotes, bounds, lookup table keys and instructions need
+    //!   no cache call,
+    //! - a missing asset mint or lookup table is reported, not fatal, unless
+    //!   the asset mint is required; a missing vault fails the warmup,
+    //! - a venue built from a fresh vault account warms up in one call
+    //!   without re-fetching it, and is refused for another vault's state,
+    //!   as in `update_state()`.
+
+    use solana_account::Account;
+    use solana_pubkey::Pubkey;
+    use titan_integration_template::trading_venue::{
+        AddressLookupTableTrait, FromAccount, QuoteRequest, SwapType, TradingVenue,
+    };
+
+    use titan_voltr_integration::config::{MintMetadata, VoltrVenueConfig};
+    use titan_voltr_integration::constants::{
+        ADDRESS_LOOKUP_TABLE_PROGRAM, LOOKUP_TABLE_META_SIZE, TOKEN_PROGRAM,
+    };
+    use titan_voltr_integration::voltr_venue::VoltrVaultVenue;
+    use titan_voltr_integration::warmup::{WarmupOptions, WarmupOutcome, WarmupPhase};
+
+    use crate::common::{CountingCache, VaultFixture};
+
+    fn lookup_table_account(addresses: &[Pubkey]) -> Account {
+        let mut data = vec![0u8; LOOKUP_TABLE_META_SIZE];
+        // `ProgramState::LookupTable`
+        data[0] = 1;
+        for address in addresses {
+            data.extend_from_slice(address.as_ref());
+        }
+        Account {
+            lamports: 1,
+            data,
+            owner: ADDRESS_LOOKUP_TABLE_PROGRAM,
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    fn requests(fixture: &VaultFixture) -> [QuoteRequest; 2] {
+        [
+            QuoteRequest {
+                input_mint: fixture.asset_mint,
+                output_mint: fixture.lp_mint(),
+                amount: 1_000_000,
+                swap_type: SwapType::ExactIn,
+            },
+            QuoteRequest {
+                input_mint: fixture.lp_mint(),
+                output_mint: fixture.asset_mint,
+                amount: 1_000_000,
+                swap_type: SwapType::ExactIn,
+            },
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_warm_up_leaves_venue_ready() {
+        let fixture = VaultFixture::default();
+        let table = Pubkey::new_unique();
+        let mut inner = fixture.cache();
+        inner.insert(table, lookup_table_account(&[fixture.vault_key]));
+        let cache = CountingCache::new(inner);
+
+        let mut venue =
+            VoltrVaultVenue::new_uninitialized(fixture.vault_key).with_config(VoltrVenueConfig {
+                lookup_table: Some(table),
+                ..Default::default()
+            });
+        let report = venue
+            .warm_up(&cache, WarmupOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(report.cache_calls, 2);
+        assert_eq!(cache.calls(), 2);
+        assert_eq!(
+            report
+                .phases
+                .iter()
+                .map(|phase| phase.phase)
+                .collect::<Vec<_>>(),
+            [
+                WarmupPhase::State,
+                WarmupPhase::Metadata,
+                WarmupPhase::Bounds,
+                WarmupPhase::LookupTable,
+                WarmupPhase::ComputeUnits,
+            ]
+        );
+        assert!(report
+            .phases
+            .iter()
+            .all(|phase| phase.outcome == WarmupOutcome::Loaded));
+        assert!(report.total_elapsed() >= report.phases[0].elapsed);
+        assert_eq!(
+            report.asset_metadata,
+            Some(MintMetadata {
+                decimals: fixture.asset_decimals,
+                token_program: TOKEN_PROGRAM,
+            })
+        );
+        assert_eq!(report.deposit_bounds, Some(venue.bounds(0, 1).unwrap()));
+        assert_eq!(report.redeem_bounds, Some(venue.bounds(1, 0).unwrap()));
+        assert_eq!(
+            report.lookup_table_keys,
+            venue.get_lookup_table_keys(None).await.unwrap()
+        );
+        assert_eq!(
+            report.lookup_table_missing,
+            Some(
+                report
+                    .lookup_table_keys
+                    .iter()
+                    .copied()
+                    .filter(|key| *key != fixture.vault_key)
+                    .collect()
+            )
+        );
+        assert!(report.deposit_compute_units > 0 && report.redeem_compute_units > 0);
+
+        // Everything after the warmup is served from the venue.
+        for request in requests(&fixture) {
+            assert!(venue.quote(request.clone()).unwrap().expected_output > 0);
+            venue
+                .generate_swap_instruction(request, Pubkey::new_unique())
+                .unwrap();
+        }
+        venue.get_lookup_table_keys(None).await.unwrap();
+        assert_eq!(cache.calls(), 2);
+
+        // A venue holding its vault warms up again in a single call.
+        let report = venue
+            .warm_up(&cache, WarmupOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(report.cache_calls, 1);
+        assert_eq!(cache.calls(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_warm_up_reports_non_fatal_failures() {
+        let fixture = VaultFixture::default();
+        let mut inner = fixture.cache();
+        inner.remove(&fixture.asset_mint);
+        let cache = CountingCache::new(inner);
+        let config = VoltrVenueConfig {
+            known_mint_metadata: Some(MintMetadata {
+                decimals: fixture.asset_decimals,
+                token_program: TOKEN_PROGRAM,
+            }),
+            lookup_table: Some(Pubkey::new_unique()),
+            ..Default::default()
+        };
+
+        let mut venue =
+            VoltrVaultVenue::new_uninitialized(fixture.vault_key).with_config(config.clone());
+        let report = venue
+            .warm_up(&cache, WarmupOptions::default())
+            .await
+            .unwrap();
+        let failed: Vec<WarmupPhase> = report.failed().map(|phase| phase.phase).collect();
+        assert_eq!(failed, [WarmupPhase::Metadata, WarmupPhase::LookupTable]);
+        assert_eq!(report.lookup_table_missing, None);
+        for request in requests(&fixture) {
+            assert!(venue.quote(request).unwrap().expected_output > 0);
+        }
+
+        // Skipping the lookup table leaves nothing to fail there.
+        let mut venue =
+            VoltrVaultVenue::new_uninitialized(fixture.vault_key).with_config(config.clone());
+        let report = venue
+            .warm_up(
+                &cache,
+                WarmupOptions {
+                    skip_lookup_table: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert!(matches!(
+            report.phase(WarmupPhase::LookupTable).unwrap().outcome,
+            WarmupOutcome::Skipped(_)
+        ));
+
+        let mut venue =
+            VoltrVaultVenue::new_uninitialized(fixture.vault_key).with_config(config.clone());
+        assert!(venue
+            .warm_up(
+                &cache,
+                WarmupOptions {
+                    require_asset_mint: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .is_err());
+
+        let mut venue = VoltrVaultVenue::new_uninitialized(Pubkey::new_unique());
+        assert!(venue
+            .warm_up(&cache, WarmupOptions::default())
+            .await
+            .is_err());
+        assert!(!venue.initialized());
+    }
+
+    #[tokio::test]
+    async fn test_warm_up_reuses_constructed_vault() {
+        let fixture = VaultFixture::default();
+        let mut inner = fixture.cache();
+        // Only the constructed state can supply the vault.
+        inner.remove(&fixture.vault_key);
+        let cache = CountingCache::new(inner);
+
+        let mut venue =
+            VoltrVaultVenue::from_account(&fixture.vault_key, &fixture.vault_account()).unwrap();
+        let report = venue
+            .warm_up(&cache, WarmupOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(report.cache_calls, 1);
+        assert!(venue.initialized());
+        for request in requests(&fixture) {
+            assert!(venue.quote(request).unwrap().expected_output > 0);
+        }
+
+        let other = VaultFixture::default();
+        let mut swapped =
+            VoltrVaultVenue::from_account(&fixture.vault_key, &other.vault_account()).unwrap();
+        let err = swapped
+            .warm_up(&cache, WarmupOptions::default())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Mismatch"), "{err}");
+        assert!(!swapped.initialized());
+    }
+}
@@ -0,0 +1,2415 @@
+mod common;
+
+#[cfg(test)]
+mod test_api_surface {
+    //! The prelude alone is enough to load, quote and trade through a venue;
+    //! only the synthetic accounts come from the shared fixtures.
+
+    use solana_pubkey::Pubkey;
+    use titan_voltr_integration::prelude::*;
+
+    use crate::common::VaultFixture;
+
+    #[tokio::test]
+    async fn test_prelude_end_to_end() {
+        let fixture = VaultFixture::default();
+        let cache: &dyn AccountsCache = &fixture.cache();
+        let user = Pubkey::new_unique();
+
+        let vault_account = cache.get_account(&fixture.vault_key).await.unwrap().unwrap();
+        let vault: Vault = Vault::load(&vault_account.data).unwrap();
+        assert_eq!(vault.asset.mint, fixture.asset_mint);
+
+        let mut venue = VoltrVaultVenue::from_account(&fixture.vault_key, &vault_account)
+            .unwrap()
+            .with_config(VoltrVenueConfig::default());
+        venue.update_state(cache).await.unwrap();
+        assert_eq!(venue.program_id(), VOLTR_VAULT_PROGRAM);
+        assert_eq!(venue.status(), VenueStatus::Ready);
+        assert!(venue.health_report().is_healthy());
+        let _: VenueSummary = venue.summary();
+
+        let (_, upper) = venue.bounds(0, 1).unwrap();
+        let request = QuoteRequest {
+            input_mint: venue.get_token(0).unwrap().pubkey,
+            output_mint: venue.get_token(1).unwrap().pubkey,
+            amount: upper.min(1_000_000),
+            swap_type: SwapType::ExactIn,
+        };
+
+        let quote: QuoteResult = venue.quote(request.clone()).unwrap();
+        let detailed: DetailedQuote = venue
+            .quote_detailed(&request, &user, Some(cache))
+            .await
+            .unwrap();
+        assert_eq!(detailed.direction, SwapDirection::Deposit);
+        assert_eq!(detailed.quote.expected_output, quote.expected_output);
+
+        let failures: Vec<PreconditionFailure> = venue
+            .check_preconditions(&user, &request, cache)
+            .await
+            .unwrap();
+        assert!(!failures.is_empty());
+
+        venue
+            .generate_swap_instruction_validated(request.clone(), user)
+            .unwrap();
+        let instructions = venue
+            .build_transaction_instructions(request, user, &TxOptions::default())
+            .unwrap();
+        assert_eq!(instructions.len(), 2);
+        assert!(!venue.get_lookup_table_keys(None).await.unwrap().is_empty());
+
+        let venues = [venue];
+        let (best, _) = best_deposit_venue(&venues, fixture.asset_mint, 1_000_000).unwrap();
+        assert_eq!(best.vault_key, fixture.vault_key);
+
+        let error: TradingVenueError = VoltrError::NotInitialized.into();
+        assert!(!error.to_string().is_empty());
+    }
+}
+
+#[cfg(test)]
+mod test_bounds {
+    //! Native quoting bounds on synthetic vaults:
+    //! - a capped deposit's upper bound lands exactly on the (inclusive) cap,
+    //! - redeems are bounded by the idle balance,
+    //! - both bounds sit on the edge between filled and unfilled quotes,
+    //! - a vault at capacity reports an empty range,
+    //! - pending fees and locked profit do not move the cap edge,
+    //! - the max amounts advertised in `TokenInfo` match the upper bounds,
+    //! - locked profit above total value blocks redeems but not deposits.
+
+    use titan_integration_template::trading_venue::{QuoteRequest, SwapType, TradingVenue};
+
+    use titan_voltr_integration::voltr_venue::{LiquidityRejection, SwapDirection, VoltrVaultVenue};
+
+    use crate::common::VaultFixture;
+
+    /// Quote an amount, returning `(filled, output)`; a failing quote counts
+    /// as unfilled.
+    fn quote(venue: &VoltrVaultVenue, in_idx: u8, out_idx: u8, amount: u64) -> (bool, u64) {
+        let request = QuoteRequest {
+            input_mint: venue.get_token(in_idx as usize).unwrap().pubkey,
+            output_mint: venue.get_token(out_idx as usize).unwrap().pubkey,
+            amount,
+            swap_type: SwapType::ExactIn,
+        };
+        match venue.quote(request) {
+            Ok(quote) => (
+                !quote.not_enough_liquidity && quote.expected_output > 0,
+                quote.expected_output,
+            ),
+            Err(_) => (false, 0),
+        }
+    }
+
+    /// Assert `(lower, upper)` are the exact edges of the filled range.
+    fn assert_edges(venue: &VoltrVaultVenue, in_idx: u8, out_idx: u8) -> (u64, u64) {
+        let (lower, upper) = venue.bounds(in_idx, out_idx).unwrap();
+        assert!(0 < lower && lower <= upper);
+
+        for amount in [lower, upper] {
+            assert!(quote(venue, in_idx, out_idx, amount).0, "amount {amount}");
+        }
+        assert!(!quote(venue, in_idx, out_idx, lower - 1).0);
+        if upper < u64::MAX {
+            assert!(!quote(venue, in_idx, out_idx, upper + 1).0);
+        }
+        (lower, upper)
+    }
+
+    #[test]
+    fn test_capped_deposit_upper_bound_is_inclusive() {
+        let fixture = VaultFixture {
+            max_cap: 1_000_000_000_000 + 5_000_000,
+            ..Default::default()
+        };
+        let venue = fixture.synthetic_venue();
+
+        let (lower, upper) = assert_edges(&venue, 0, 1);
+        assert_eq!(lower, 1);
+        assert_eq!(upper, 5_000_000);
+    }
+
+    #[test]
+    fn test_redeem_bounded_by_idle_balance() {
+        let fixture = VaultFixture {
+            idle_balance: 2_000_000,
+            redemption_fee: 10,
+            ..Default::default()
+        };
+        let venue = fixture.synthetic_venue();
+
+        let (_, upper) = assert_edges(&venue, 1, 0);
+        assert!(quote(&venue, 1, 0, upper).1 <= 2_000_000);
+    }
+
+    #[test]
+    fn test_uncapped_deposit_bounds() {
+        let venue = VaultFixture::default().synthetic_venue();
+        let (_, upper) = assert_edges(&venue, 0, 1);
+        assert!(upper > 1_000_000_000_000);
+    }
+
+    #[test]
+    fn test_vault_at_capacity_has_empty_range() {
+        let fixture = VaultFixture {
+            max_cap: 1_000_000_000_000,
+            ..Default::default()
+        };
+        let venue = fixture.synthetic_venue();
+
+        assert_eq!(venue.bounds(0, 1).unwrap(), (0, 0));
+        assert!(venue.bounds(0, 0).is_err());
+        assert!(venue.bounds(0, 2).is_err());
+    }
+
+    #[test]
+    fn test_cap_edge_ignores_pending_fees() {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let capped = VaultFixture {
+            max_cap: 1_000_000_000_000 + 5_000_000,
+            ..Default::default()
+        };
+        // Just under the cap with a year of 10% management fee unaccrued and
+        // a fresh locked profit report worth 10% of the vault. The program
+        // agrees in `test_program::test_differential`.
+        let pending = VaultFixture {
+            manager_management_fee: 1_000,
+            last_management_fee_update_ts: now - 365 * 24 * 60 * 60,
+            locked_profit_degradation_duration: 86_400,
+            last_updated_locked_profit: 100_000_000_000,
+            last_report: now,
+            ..capped.clone()
+        };
+
+        for fixture in [capped, pending] {
+            let venue = fixture.synthetic_venue();
+            assert_eq!(venue.bounds(0, 1).unwrap().1, 5_000_000);
+            assert!(quote(&venue, 0, 1, 5_000_000).0);
+            assert!(!quote(&venue, 0, 1, 5_000_001).0);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_token_info_max_amounts_are_tradable_edges() {
+        let fixture = VaultFixture {
+            max_cap: 1_000_000_000_000 + 5_000_000,
+            idle_balance: 2_000_000,
+            redemption_fee: 10,
+            ..Default::default()
+        };
+        let mut venue = fixture.venue().await;
+
+        for (direction, in_idx, out_idx) in
+            [(SwapDirection::Deposit, 0, 1), (SwapDirection::Redeem, 1, 0)]
+        {
+            let max = venue.max_input_amount(direction);
+            assert_eq!(max, venue.bounds(in_idx, out_idx).unwrap().1);
+            assert!(quote(&venue, in_idx, out_idx, max).0, "{direction:?} at {max}");
+            assert!(!quote(&venue, in_idx, out_idx, max + 1).0, "{direction:?} past {max}");
+        }
+        assert_eq!(venue.max_input_amount(SwapDirection::Deposit), 5_000_000);
+
+        // Limits follow each refresh.
+        let drained = VaultFixture {
+            idle_balance: 0,
+            ..fixture.clone()
+        };
+        venue.update_state(&drained.cache()).await.unwrap();
+        assert_eq!(venue.max_input_amount(SwapDirection::Redeem), 0);
+        assert_eq!(venue.max_input_amount(SwapDirection::Deposit), 5_000_000);
+    }
+
+    #[test]
+    fn test_locked_profit_above_total_value() {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        // A strategy loss cranked after a report worth more than the vault.
+        let fixture = VaultFixture {
+            locked_profit_degradation_duration: 86_400,
+            last_updated_locked_profit: 2_000_000_000_000,
+            last_report: now,
+            ..Default::default()
+        };
+        let venue = fixture.synthetic_venue();
+
+        let redeem = QuoteRequest {
+            input_mint: fixture.lp_mint(),
+            output_mint: fixture.asset_mint,
+            amount: 1_000_000,
+            swap_type: SwapType::ExactIn,
+        };
+        let (redeemed, rejection) = venue.quote_with_rejection(&redeem).unwrap();
+        assert!(redeemed.not_enough_liquidity);
+        assert_eq!(redeemed.expected_output, 0);
+        assert_eq!(rejection, Some(LiquidityRejection::UnlockedValueExceeded));
+        assert_eq!(venue.bounds(1, 0).unwrap(), (0, 0));
+        assert_eq!(venue.max_input_amount(SwapDirection::Redeem), 0);
+
+        assert!(quote(&venue, 0, 1, 1_000_000).0);
+        assert_eq!(venue.bounds(0, 1).unwrap().0, 1);
+
+        // Once the window has passed, redeems quote again.
+        assert!(venue.quote_at(&redeem, now + 86_401).unwrap().expected_output > 0);
+    }
+}
+
+#[cfg(test)]
+mod test_capacity {
+    //! How much of a capped vault a deposit would use:
+    //! - uncapped vaults report no utilization or remaining capacity,
+    //! - under the cap, utilization and the remaining capacity before and
+    //!   after follow the deposit, reaching 1.0 and 0 exactly on the cap,
+    //! - a vault already over its cap, as appreciation can leave it, reports
+    //!   more than 1.0 and no room left,
+    //! - huge amounts saturate instead of overflowing,
+    //! - utilization exceeds 1.0 exactly when the quote is rejected for the
+    //!   cap.
+
+    use solana_pubkey::Pubkey;
+    use titan_integration_template::trading_venue::{QuoteRequest, SwapType};
+
+    use titan_voltr_integration::voltr_venue::{LiquidityRejection, VoltrVaultVenue};
+
+    use crate::common::VaultFixture;
+
+    const TOTAL_VALUE: u64 = 1_000_000_000_000;
+
+    fn venue(max_cap: u64) -> (VaultFixture, VoltrVaultVenue) {
+        let fixture = VaultFixture {
+            max_cap,
+            ..Default::default()
+        };
+        let venue = fixture.synthetic_venue();
+        (fixture, venue)
+    }
+
+    fn deposit(fixture: &VaultFixture, amount: u64) -> QuoteRequest {
+        QuoteRequest {
+            input_mint: fixture.asset_mint,
+            output_mint: fixture.lp_mint(),
+            amount,
+            swap_type: SwapType::ExactIn,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_uncapped_vault() {
+        let (fixture, venue) = venue(0);
+        assert_eq!(venue.capacity_utilization_after(1_000), None);
+        assert_eq!(venue.capacity_utilization_after(u64::MAX), None);
+        let detailed = venue
+            .quote_detailed(&deposit(&fixture, 1_000), &Pubkey::new_unique(), None)
+            .await
+            .unwrap();
+        assert_eq!(detailed.remaining_capacity_before, None);
+        assert_eq!(detailed.remaining_capacity_after, None);
+    }
+
+    #[tokio::test]
+    async fn test_under_cap() {
+        let (fixture, venue) = venue(2 * TOTAL_VALUE);
+        assert_eq!(venue.capacity_utilization_after(0), Some(0.5));
+        assert_eq!(
+            venue.capacity_utilization_after(TOTAL_VALUE / 2),
+            Some(0.75)
+        );
+        assert_eq!(venue.capacity_utilization_after(TOTAL_VALUE), Some(1.0));
+
+        let user = Pubkey::new_unique();
+        let detailed = venue
+            .quote_detailed(&deposit(&fixture, TOTAL_VALUE / 2), &user, None)
+            .await
+            .unwrap();
+        assert_eq!(detailed.remaining_capacity_before, Some(TOTAL_VALUE));
+        assert_eq!(detailed.remaining_capacity_after, Some(TOTAL_VALUE / 2));
+        let detailed = venue
+            .quote_detailed(&deposit(&fixture, TOTAL_VALUE), &user, None)
+            .await
+            .unwrap();
+        assert_eq!(detailed.remaining_capacity_after, Some(0));
+        assert!(!detailed.quote.not_enough_liquidity);
+
+        // Redeems do not touch the cap.
+        let redeem = QuoteRequest {
+            input_mint: fixture.lp_mint(),
+            output_mint: fixture.asset_mint,
+            ..deposit(&fixture, 1_000)
+        };
+        let detailed = venue.quote_detailed(&redeem, &user, None).await.unwrap();
+        assert_eq!(detailed.remaining_capacity_before, None);
+        assert_eq!(detailed.remaining_capacity_after, None);
+    }
+
+    #[tokio::test]
+    async fn test_already_over_cap() {
+        // Appreciation took the vault past its cap.
+        let (fixture, venue) = venue(TOTAL_VALUE / 2);
+        assert_eq!(venue.capacity_utilization_after(0), Some(2.0));
+        assert!(venue.capacity_utilization_after(1).unwrap() > 2.0);
+
+        let detailed = venue
+            .quote_detailed(&deposit(&fixture, 1), &Pubkey::new_unique(), None)
+            .await
+            .unwrap();
+        assert_eq!(detailed.remaining_capacity_before, Some(0));
+        assert_eq!(detailed.remaining_capacity_after, Some(0));
+        assert_eq!(detailed.rejection, Some(LiquidityRejection::CapExceeded));
+    }
+
+    #[tokio::test]
+    async fn test_huge_amounts_saturate() {
+        let (fixture, venue) = venue(u64::MAX - 1);
+        let utilization = venue.capacity_utilization_after(u64::MAX).unwrap();
+        assert!(utilization > 1.0 && utilization.is_finite());
+        let detailed = venue
+            .quote_detailed(&deposit(&fixture, u64::MAX), &Pubkey::new_unique(), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            detailed.remaining_capacity_before,
+            Some(u64::MAX - 1 - TOTAL_VALUE)
+        );
+        assert_eq!(detailed.remaining_capacity_after, Some(0));
+        assert_eq!(detailed.rejection, Some(LiquidityRejection::CapExceeded));
+    }
+
+    #[test]
+    fn test_consistent_with_cap_rejection() {
+        for max_cap in [
+            TOTAL_VALUE / 2,
+            TOTAL_VALUE,
+            TOTAL_VALUE + 1,
+            TOTAL_VALUE + 999_999,
+            3 * TOTAL_VALUE,
+            // Large enough that adjacent totals round to the same f64.
+            u64::MAX - 1,
+        ] {
+            let (fixture, venue) = venue(max_cap);
+            let room = max_cap.saturating_sub(TOTAL_VALUE);
+            for amount in [
+                1,
+                room.saturating_sub(1),
+                room,
+                room.saturating_add(1),
+                room.saturating_mul(2),
+            ] {
+                // A zero deposit is never rejected.
+                if amount == 0 {
+                    continue;
+                }
+                let utilization = venue.capacity_utilization_after(amount).unwrap();
+                let (_, rejection) = venue
+                    .quote_with_rejection(&deposit(&fixture, amount))
+                    .unwrap();
+                assert_eq!(
+                    utilization > 1.0,
+                    rejection == Some(LiquidityRejection::CapExceeded),
+                    "cap {max_cap}, amount {amount}: utilization {utilization}"
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_detailed_quote {
+    //! Detailed quotes and the lamports a swap costs beyond its input:
+    //! - the output ATA flag flips once the ATA is created in LiteSVM,
+    //! - the extra lamports match the rent the ATA program actually charges,
+    //! - redeems owe nothing beyond the output ATA (no withdrawal receipt),
+    //! - redeems the vault is worth but cannot pay from idle report idle
+    //!   liquidity as the limit, with the largest fillable input,
+    //! - quote meta carries when and at which slot the state was loaded, and
+    //!   moves with each refresh.
+
+    use litesvm::LiteSVM;
+    use solana_account::Account;
+    use solana_program::native_token::LAMPORTS_PER_SOL;
+    use solana_pubkey::Pubkey;
+    use solana_sdk::signature::Keypair;
+    use solana_sdk::signer::Signer;
+    use solana_sdk::transaction::Transaction;
+    use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+    use titan_integration_template::trading_venue::{QuoteRequest, SwapType, TradingVenue};
+
+    use titan_voltr_integration::clock::QuoteClock;
+    use titan_voltr_integration::config::VoltrVenueConfig;
+    use titan_voltr_integration::constants::{
+        TOKEN_PROGRAM, VAULT_ASSET_IDLE_AUTH_SEED, VOLTR_VAULT_PROGRAM,
+    };
+    use titan_voltr_integration::quote::QuoteResultMeta;
+    use titan_voltr_integration::synthetic::token_account;
+    use titan_voltr_integration::voltr_venue::{
+        LiquidityRejection, SwapDirection, VoltrVaultVenue,
+    };
+
+    use crate::common::{MockCache, SlotMockCache, VaultFixture};
+
+    /// Snapshot the given LiteSVM accounts into a cache.
+    fn svm_cache(svm: &LiteSVM, pubkeys: &[Pubkey]) -> MockCache {
+        MockCache::from_pairs(
+            pubkeys
+                .iter()
+                .filter_map(|pubkey| Some((*pubkey, svm.get_account(pubkey)?))),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_output_account_flag_flips_after_ata_creation() {
+        let fixture = VaultFixture::default();
+        let venue = fixture.venue().await;
+
+        let mut svm = LiteSVM::new();
+        for (pubkey, account) in fixture.accounts() {
+            svm.set_account(pubkey, account).unwrap();
+        }
+        let user = Keypair::new();
+        svm.set_account(
+            user.pubkey(),
+            Account {
+                lamports: 10 * LAMPORTS_PER_SOL,
+                owner: solana_sdk::system_program::id(),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let request = QuoteRequest {
+            input_mint: fixture.asset_mint,
+            output_mint: fixture.lp_mint(),
+            amount: 1_000_000,
+            swap_type: SwapType::ExactIn,
+        };
+        let output_account = venue.output_account(&user.pubkey(), SwapDirection::Deposit);
+        let rent = venue.output_account_rent(SwapDirection::Deposit);
+
+        // Before: the LP ATA is missing and its rent is owed.
+        let cache = svm_cache(&svm, &[output_account]);
+        assert!(!venue
+            .output_account_exists(&user.pubkey(), SwapDirection::Deposit, &cache)
+            .await
+            .unwrap());
+        let detailed = venue
+            .quote_detailed(&request, &user.pubkey(), Some(&cache))
+            .await
+            .unwrap();
+        assert_eq!(detailed.output_account, output_account);
+        assert_eq!(detailed.output_account_exists, Some(false));
+        assert_eq!(detailed.extra_lamports_required, rent);
+        assert!(detailed.quote.expected_output > 0);
+
+        // Without a cache the estimate assumes the ATA is missing.
+        let uncached = venue
+            .quote_detailed(&request, &user.pubkey(), None)
+            .await
+            .unwrap();
+        assert_eq!(uncached.output_account_exists, None);
+        assert_eq!(uncached.extra_lamports_required, rent);
+
+        let create = create_associated_token_account_idempotent(
+            &user.pubkey(),
+            &user.pubkey(),
+            &fixture.lp_mint(),
+            &TOKEN_PROGRAM,
+        );
+        let tx = Transaction::new_signed_with_payer(
+            &[create],
+            Some(&user.pubkey()),
+            &[&user],
+            svm.latest_blockhash(),
+        );
+        svm.send_transaction(tx).unwrap();
+
+        // The ATA program funded the account with exactly the estimated rent.
+        assert_eq!(svm.get_account(&output_account).unwrap().lamports, rent);
+
+        // After: the flag flips and nothing extra is owed.
+        let cache = svm_cache(&svm, &[output_account]);
+        let detailed = venue
+            .quote_detailed(&request, &user.pubkey(), Some(&cache))
+            .await
+            .unwrap();
+        assert_eq!(detailed.output_account_exists, Some(true));
+        assert_eq!(detailed.extra_lamports_required, 0);
+        assert_eq!(
+            venue
+                .extra_lamports_required(&user.pubkey(), SwapDirection::Deposit, Some(&cache))
+                .await
+                .unwrap(),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_redeem_extra_lamports_are_output_ata_rent_only() {
+        let fixture = VaultFixture::default();
+        let venue = fixture.venue().await;
+        let user = Pubkey::new_unique();
+
+        let request = QuoteRequest {
+            input_mint: fixture.lp_mint(),
+            output_mint: fixture.asset_mint,
+            amount: 1_000_000,
+            swap_type: SwapType::ExactIn,
+        };
+        let output_account = venue.output_account(&user, SwapDirection::Redeem);
+
+        // The instruction writes to no user-side account other than the two
+        // ATAs, so no receipt is created and nothing is refunded.
+        let instruction = venue
+            .generate_swap_instruction(request.clone(), user)
+            .unwrap();
+        let lp_ata = venue.output_account(&user, SwapDirection::Deposit);
+        let (idle_auth, _) = Pubkey::find_program_address(
+            &[VAULT_ASSET_IDLE_AUTH_SEED, fixture.vault_key.as_ref()],
+            &VOLTR_VAULT_PROGRAM,
+        );
+        let vault_side = [
+            fixture.vault_key,
+            fixture.lp_mint(),
+            fixture.idle_ata,
+            idle_auth,
+        ];
+        let user_writable: Vec<Pubkey> = instruction
+            .accounts
+            .iter()
+            .filter(|meta| meta.is_writable && !vault_side.contains(&meta.pubkey))
+            .map(|meta| meta.pubkey)
+            .collect();
+        assert_eq!(user_writable, vec![lp_ata, output_account]);
+
+        let mut cache = fixture.cache();
+        let missing = venue
+            .quote_detailed(&request, &user, Some(&cache))
+            .await
+            .unwrap();
+        assert_eq!(
+            missing.extra_lamports_required,
+            venue.output_account_rent(SwapDirection::Redeem)
+        );
+
+        cache.insert(
+            output_account,
+            token_account(&fixture.asset_mint, &user, &TOKEN_PROGRAM, 0),
+        );
+        let existing = venue
+            .quote_detailed(&request, &user, Some(&cache))
+            .await
+            .unwrap();
+        assert_eq!(existing.extra_lamports_required, 0);
+    }
+
+    #[tokio::test]
+    async fn test_redeem_between_idle_and_unlocked_value() {
+        // 10% of the value idle, the rest deployed to strategies.
+        let fixture = VaultFixture {
+            idle_balance: 100_000_000_000,
+            ..Default::default()
+        };
+        let venue = fixture.synthetic_venue();
+        let user = Pubkey::new_unique();
+        let redeem = |amount| QuoteRequest {
+            input_mint: fixture.lp_mint(),
+            output_mint: fixture.asset_mint,
+            amount,
+            swap_type: SwapType::ExactIn,
+        };
+
+        // Half the supply: worth half the value, five times the idle balance.
+        let banded = venue
+            .quote_detailed(&redeem(fixture.lp_supply / 2), &user, None)
+            .await
+            .unwrap();
+        assert!(banded.quote.not_enough_liquidity);
+        assert_eq!(banded.quote.expected_output, 0);
+        assert_eq!(banded.rejection, Some(LiquidityRejection::IdleBalanceInsufficient));
+
+        let fillable = banded.max_fillable_input.unwrap();
+        assert_eq!(fillable, venue.max_input_amount(SwapDirection::Redeem));
+        let filled = venue.quote(redeem(fillable)).unwrap();
+        assert!(!filled.not_enough_liquidity);
+        assert!(filled.expected_output <= fixture.idle_balance);
+        assert!(venue.quote(redeem(fillable + 1)).unwrap().not_enough_liquidity);
+
+        // More LP than exists is limited by value, not liquidity.
+        let oversized = venue
+            .quote_detailed(&redeem(fixture.lp_supply * 2), &user, None)
+            .await
+            .unwrap();
+        assert_eq!(oversized.rejection, Some(LiquidityRejection::UnlockedValueExceeded));
+
+        let accepted = venue
+            .quote_detailed(&redeem(1_000_000), &user, None)
+            .await
+            .unwrap();
+        assert_eq!(accepted.rejection, None);
+        assert_eq!(accepted.max_fillable_input, None);
+    }
+
+    #[tokio::test]
+    async fn test_quote_meta_tracks_refreshes() {
+        const T0: u64 = 1_700_000_000;
+        let fixture = VaultFixture::default();
+        let user = Pubkey::new_unique();
+        let deposit = QuoteRequest {
+            input_mint: fixture.asset_mint,
+            output_mint: fixture.lp_mint(),
+            amount: 1_000_000,
+            swap_type: SwapType::ExactIn,
+        };
+
+        let mut venue =
+            VoltrVaultVenue::new_uninitialized(fixture.vault_key).with_config(VoltrVenueConfig {
+                clock: QuoteClock::Fixed(T0),
+                ..Default::default()
+            });
+        assert!(venue.quote_with_meta(&deposit).is_err());
+        venue.update_state(&fixture.cache()).await.unwrap();
+
+        let (quote, meta) = venue.quote_with_meta(&deposit).unwrap();
+        assert_eq!(
+            quote.expected_output,
+            venue.quote(deposit.clone()).unwrap().expected_output
+        );
+        assert_eq!(
+            meta,
+            QuoteResultMeta {
+                state_ts: T0,
+                state_slot: None,
+                computed_at: T0,
+                state_age_secs: 0,
+                degraded: false,
+            }
+        );
+
+        // Quoting later on the same state only moves `computed_at`.
+        venue.set_clock(QuoteClock::Fixed(T0 + 30));
+        let (_, meta) = venue.quote_with_meta(&deposit).unwrap();
+        assert_eq!((meta.state_ts, meta.computed_at), (T0, T0 + 30));
+
+        // A slot-aware refresh records its slot and time.
+        venue.set_clock(QuoteClock::Fixed(T0 + 60));
+        let cache = SlotMockCache::new(fixture.cache(), [vec![Some(250); 4]]);
+        venue.update_state_with_slots(&cache).await.unwrap();
+        let expected = QuoteResultMeta {
+            state_ts: T0 + 60,
+            state_slot: Some(250),
+            computed_at: T0 + 60,
+            state_age_secs: 0,
+            degraded: false,
+        };
+        assert_eq!(venue.quote_with_meta(&deposit).unwrap().1, expected);
+        let detailed = venue.quote_detailed(&deposit, &user, None).await.unwrap();
+        assert_eq!(detailed.meta, expected);
+
+        // A plain refresh reports no slot.
+        venue.set_clock(QuoteClock::Fixed(T0 + 90));
+        venue.update_state(&fixture.cache()).await.unwrap();
+        let (_, meta) = venue.quote_with_meta(&deposit).unwrap();
+        assert_eq!((meta.state_ts, meta.state_slot), (T0 + 90, None));
+    }
+}
+
+#[cfg(test)]
+mod test_direction {
+    //! `DirectionInfo` against what the venue does in each direction:
+    //! - mints, token programs and ATAs are those of the generated swap
+    //!   instruction and of `required_user_accounts()`,
+    //! - fees, input limit, compute units and account count follow the
+    //!   direction,
+    //! - only a capped vault's deposits have a capacity limit, saturating at
+    //!   0 over the cap,
+    //! - unrelated mint pairs are rejected,
+    //! - the write-locked accounts are exactly the writable non-user accounts
+    //!   of the swap instruction, so swaps conflict on the same vault only.
+
+    use solana_pubkey::Pubkey;
+    use titan_integration_template::trading_venue::{QuoteRequest, SwapType, TradingVenue};
+
+    use titan_voltr_integration::constants::{
+        DEPOSIT_ACCOUNTS_LEN, INSTANT_WITHDRAW_ACCOUNTS_LEN, TOKEN_PROGRAM,
+    };
+    use titan_voltr_integration::voltr_venue::SwapDirection;
+
+    use crate::common::VaultFixture;
+
+    fn request(input_mint: Pubkey, output_mint: Pubkey) -> QuoteRequest {
+        QuoteRequest {
+            input_mint,
+            output_mint,
+            amount: 1_000_000,
+            swap_type: SwapType::ExactIn,
+        }
+    }
+
+    #[test]
+    fn test_matches_swap_instruction() {
+        let fixture = VaultFixture {
+            issuance_fee: 10,
+            redemption_fee: 25,
+            ..Default::default()
+        };
+        let venue = fixture.synthetic_venue();
+        let user = Pubkey::new_unique();
+        for (input_mint, output_mint, direction, fee_bps, accounts_len) in [
+            (
+                fixture.asset_mint,
+                fixture.lp_mint(),
+                SwapDirection::Deposit,
+                10,
+                DEPOSIT_ACCOUNTS_LEN,
+            ),
+            (
+                fixture.lp_mint(),
+                fixture.asset_mint,
+                SwapDirection::Redeem,
+                25,
+                INSTANT_WITHDRAW_ACCOUNTS_LEN,
+            ),
+        ] {
+            let request = request(input_mint, output_mint);
+            let info = venue.direction_info_for(&request).unwrap();
+            assert_eq!(info, venue.direction_info(direction));
+            assert_eq!(info.direction, direction);
+            assert_eq!(
+                (info.input_mint, info.output_mint),
+                (input_mint, output_mint)
+            );
+            assert_eq!(info.input_token_program, TOKEN_PROGRAM);
+            assert_eq!(info.output_token_program, TOKEN_PROGRAM);
+            assert_eq!(info.fee_bps, fee_bps);
+            assert_eq!(info.max_input, venue.max_input_amount(direction));
+            assert_eq!(
+                info.compute_unit_limit,
+                direction.default_compute_unit_limit()
+            );
+            assert_eq!(info.capacity_limit, None);
+
+            let instruction = venue.generate_swap_instruction(request, user).unwrap();
+            assert_eq!(instruction.accounts.len(), info.accounts_len);
+            assert_eq!(info.accounts_len, accounts_len);
+            let accounts: Vec<Pubkey> = instruction.accounts.iter().map(|a| a.pubkey).collect();
+            assert!(accounts.contains(&info.input_account(&user)));
+            assert!(accounts.contains(&info.output_account(&user)));
+            assert_eq!(
+                info.output_account(&user),
+                venue.output_account(&user, direction)
+            );
+
+            let required = venue.required_user_accounts(&user, direction, 1_000_000);
+            assert_eq!(required[0].pubkey, info.input_account(&user));
+            assert_eq!(required[1].pubkey, info.output_account(&user));
+        }
+    }
+
+    #[test]
+    fn test_capacity_limit() {
+        let total_value = VaultFixture::default().total_value;
+        for (max_cap, limit) in [
+            (total_value + 5_000, Some(5_000)),
+            (total_value, Some(0)),
+            (total_value / 2, Some(0)),
+        ] {
+            let venue = VaultFixture {
+                max_cap,
+                ..Default::default()
+            }
+            .synthetic_venue();
+            assert_eq!(
+                venue.direction_info(SwapDirection::Deposit).capacity_limit,
+                limit
+            );
+            assert_eq!(
+                venue.direction_info(SwapDirection::Redeem).capacity_limit,
+                None
+            );
+        }
+    }
+
+    #[test]
+    fn test_unrelated_mints_rejected() {
+        let fixture = VaultFixture::default();
+        let venue = fixture.synthetic_venue();
+        let other = Pubkey::new_unique();
+        for (input_mint, output_mint) in [
+            (other, fixture.lp_mint()),
+            (fixture.asset_mint, other),
+            (fixture.asset_mint, fixture.asset_mint),
+        ] {
+            assert!(venue
+                .direction_info_for(&request(input_mint, output_mint))
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn test_write_locked_accounts_match_instructions() {
+        let fixture = VaultFixture::default();
+        let venue = fixture.synthetic_venue();
+        let user = Pubkey::new_unique();
+        for direction in [SwapDirection::Deposit, SwapDirection::Redeem] {
+            let info = venue.direction_info(direction);
+            let instruction = venue
+                .generate_swap_instruction(request(info.input_mint, info.output_mint), user)
+                .unwrap();
+            let user_accounts = [info.input_account(&user), info.output_account(&user)];
+            let writable: Vec<Pubkey> = instruction
+                .accounts
+                .iter()
+                .filter(|meta| meta.is_writable && !user_accounts.contains(&meta.pubkey))
+                .map(|meta| meta.pubkey)
+                .collect();
+            let locked = venue.write_locked_accounts(direction);
+            assert_eq!(locked, writable, "{direction:?}");
+            for key in [fixture.vault_key, fixture.lp_mint(), fixture.idle_ata] {
+                assert!(locked.contains(&key));
+            }
+        }
+    }
+
+    #[test]
+    fn test_conflicts_on_same_vault_only() {
+        let venue = VaultFixture::default().synthetic_venue();
+        let other = VaultFixture::default().synthetic_venue();
+        let directions = [SwapDirection::Deposit, SwapDirection::Redeem];
+        for direction in directions {
+            for other_direction in directions {
+                assert!(venue.conflicts_with(direction, &venue, other_direction));
+                assert!(!venue.conflicts_with(direction, &other, other_direction));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_execution {
+    //! Reconciling executed swaps from the user's balances:
+    //! - an execution paying exactly the quote has no slippage and an
+    //!   implied fee equal to the configured fee, in both directions,
+    //! - shortfalls and surpluses against the quote show as signed slippage,
+    //! - balances moving the wrong way are rejected,
+    //! - the report is measured against the quote acted on, not the state
+    //!   at the time of reconciling.
+
+    use titan_integration_template::trading_venue::{QuoteRequest, SwapType, TradingVenue};
+
+    use titan_voltr_integration::clock::QuoteClock;
+    use titan_voltr_integration::config::VoltrVenueConfig;
+    use titan_voltr_integration::core::{calc_deposit_lp_to_mint, calc_withdraw_asset_to_redeem};
+    use titan_voltr_integration::execution::SwapBalances;
+    use titan_voltr_integration::voltr_venue::SwapDirection;
+
+    use crate::common::VaultFixture;
+
+    const AMOUNT: u64 = 5_000_000_000;
+
+    fn fixture() -> VaultFixture {
+        VaultFixture {
+            issuance_fee: 100,
+            redemption_fee: 50,
+            ..Default::default()
+        }
+    }
+
+    fn request(fixture: &VaultFixture, direction: SwapDirection) -> QuoteRequest {
+        let (input_mint, output_mint) = match direction {
+            SwapDirection::Deposit => (fixture.asset_mint, fixture.lp_mint()),
+            SwapDirection::Redeem => (fixture.lp_mint(), fixture.asset_mint),
+        };
+        QuoteRequest {
+            input_mint,
+            output_mint,
+            amount: AMOUNT,
+            swap_type: SwapType::ExactIn,
+        }
+    }
+
+    #[test]
+    fn test_exact_execution() {
+        let fixture = fixture();
+        let venue = fixture.synthetic_venue();
+        let total_lp = fixture.lp_supply + fixture.dead_weight;
+
+        for (direction, fee_free_output) in [
+            (
+                SwapDirection::Deposit,
+                calc_deposit_lp_to_mint(AMOUNT, total_lp, fixture.total_value, 0).unwrap(),
+            ),
+            (
+                SwapDirection::Redeem,
+                calc_withdraw_asset_to_redeem(AMOUNT, total_lp, fixture.total_value, 0).unwrap(),
+            ),
+        ] {
+            let request = request(&fixture, direction);
+            let now = venue.effective_ts();
+            let quote = venue.quote_at(&request, now).unwrap();
+            let quoted = quote.expected_output;
+            let pre = SwapBalances {
+                input: AMOUNT * 3,
+                output: 7,
+            };
+            let post = SwapBalances {
+                input: AMOUNT * 2,
+                output: 7 + quoted,
+            };
+
+            let report = venue
+                .reconcile_execution(&request, &quote, now, pre, post)
+                .unwrap();
+            assert_eq!(report.direction, direction);
+            assert_eq!(report.input_spent, AMOUNT);
+            assert_eq!(report.output_received, quoted);
+            assert_eq!(report.quoted_output, quoted);
+            assert_eq!(report.slippage, 0);
+            assert_eq!(report.fee_free_output, fee_free_output);
+            assert_eq!(report.implied_fee, fee_free_output as i128 - quoted as i128);
+            assert!(report.implied_fee > 0);
+            assert_eq!(report.realized_price, quoted as f64 / AMOUNT as f64);
+        }
+    }
+
+    #[test]
+    fn test_signed_slippage() {
+        let fixture = fixture();
+        let venue = fixture.synthetic_venue();
+        let request = request(&fixture, SwapDirection::Redeem);
+        let now = venue.effective_ts();
+        let quote = venue.quote_at(&request, now).unwrap();
+        let quoted = quote.expected_output;
+        let pre = SwapBalances {
+            input: AMOUNT,
+            output: 0,
+        };
+
+        let short = SwapBalances {
+            input: 0,
+            output: quoted - 10,
+        };
+        assert_eq!(
+            venue
+                .reconcile_execution(&request, &quote, now, pre, short)
+                .unwrap()
+                .slippage,
+            10
+        );
+
+        let long = SwapBalances {
+            input: 0,
+            output: quoted + 10,
+        };
+        let report = venue
+            .reconcile_execution(&request, &quote, now, pre, long)
+            .unwrap();
+        assert_eq!(report.slippage, -10);
+        assert_eq!(
+            report.implied_fee,
+            report.fee_free_output as i128 - (quoted + 10) as i128
+        );
+    }
+
+    #[test]
+    fn test_inconsistent_balances() {
+        let fixture = fixture();
+        let venue = fixture.synthetic_venue();
+        let request = request(&fixture, SwapDirection::Deposit);
+        let now = venue.effective_ts();
+        let quote = venue.quote_at(&request, now).unwrap();
+        let pre = SwapBalances {
+            input: AMOUNT,
+            output: AMOUNT,
+        };
+
+        for post in [
+            SwapBalances {
+                input: AMOUNT + 1,
+                output: AMOUNT,
+            },
+            SwapBalances {
+                input: 0,
+                output: AMOUNT - 1,
+            },
+        ] {
+            assert!(venue
+                .reconcile_execution(&request, &quote, now, pre, post)
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn test_measured_against_the_quote_acted_on() {
+        const T0: u64 = 1_700_000_000;
+        // Management fee accruing and locked profit releasing over a day move
+        // both the quote and the fee-free output between quoting and
+        // reconciling.
+        let fixture = VaultFixture {
+            manager_management_fee: 200,
+            last_management_fee_update_ts: T0,
+            locked_profit_degradation_duration: 86_400,
+            last_updated_locked_profit: 100_000_000_000,
+            last_report: T0,
+            last_updated_ts: T0,
+            ..fixture()
+        };
+        let at = |ts| {
+            fixture.synthetic_venue().with_config(VoltrVenueConfig {
+                clock: QuoteClock::Fixed(ts),
+                ..Default::default()
+            })
+        };
+        let (quoted_venue, later_venue) = (at(T0), at(T0 + 43_200));
+
+        for direction in [SwapDirection::Deposit, SwapDirection::Redeem] {
+            let request = request(&fixture, direction);
+            let quote = quoted_venue.quote(request.clone()).unwrap();
+            assert_ne!(
+                later_venue.quote(request.clone()).unwrap().expected_output,
+                quote.expected_output
+            );
+            let pre = SwapBalances {
+                input: AMOUNT,
+                output: 0,
+            };
+            let post = SwapBalances {
+                input: 0,
+                output: quote.expected_output,
+            };
+
+            let expected = quoted_venue
+                .reconcile_execution(&request, &quote, T0, pre, post)
+                .unwrap();
+            let report = later_venue
+                .reconcile_execution(&request, &quote, T0, pre, post)
+                .unwrap();
+            assert_eq!(report, expected);
+            assert_eq!(report.quoted_output, quote.expected_output);
+            assert_eq!(report.slippage, 0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_min_output {
+    //! The `min_output_in_asset_units` economic filter:
+    //! - off by default, so dust swaps still quote,
+    //! - redeems are measured by their payout and deposits by their input net
+    //!   of the issuance fee, rejected exactly below the threshold,
+    //! - zero-amount quotes stay non-erroring and unrejected,
+    //! - the native lower bounds move up to the threshold.
+
+    use titan_integration_template::trading_venue::{QuoteRequest, SwapType, TradingVenue};
+
+    use titan_voltr_integration::config::VoltrVenueConfig;
+    use titan_voltr_integration::voltr_venue::{LiquidityRejection, VoltrVaultVenue};
+
+    use crate::common::VaultFixture;
+
+    const MIN_OUTPUT: u64 = 10_000;
+
+    fn fixture() -> VaultFixture {
+        VaultFixture {
+            issuance_fee: 10,
+            redemption_fee: 10,
+            ..Default::default()
+        }
+    }
+
+    fn venue(fixture: &VaultFixture, min_output_in_asset_units: u64) -> VoltrVaultVenue {
+        fixture.synthetic_venue().with_config(VoltrVenueConfig {
+            min_output_in_asset_units,
+            ..Default::default()
+        })
+    }
+
+    fn deposit(fixture: &VaultFixture, amount: u64) -> QuoteRequest {
+        QuoteRequest {
+            input_mint: fixture.asset_mint,
+            output_mint: fixture.lp_mint(),
+            amount,
+            swap_type: SwapType::ExactIn,
+        }
+    }
+
+    fn redeem(fixture: &VaultFixture, amount: u64) -> QuoteRequest {
+        QuoteRequest {
+            input_mint: fixture.lp_mint(),
+            output_mint: fixture.asset_mint,
+            amount,
+            swap_type: SwapType::ExactIn,
+        }
+    }
+
+    /// Whether `venue` fills `request`, checking that a refusal is for the
+    /// minimum output.
+    fn fills(venue: &VoltrVaultVenue, request: &QuoteRequest) -> bool {
+        let before = venue.rejection_count(LiquidityRejection::BelowMinimumOutput);
+        let quote = venue.quote(request.clone()).unwrap();
+        if quote.not_enough_liquidity {
+            assert_eq!(quote.expected_output, 0);
+            assert_eq!(
+                venue.rejection_count(LiquidityRejection::BelowMinimumOutput),
+                before + 1
+            );
+        }
+        !quote.not_enough_liquidity
+    }
+
+    #[test]
+    fn test_disabled_by_default() {
+        let fixture = fixture();
+        let venue = fixture.synthetic_venue();
+        assert_eq!(venue.config().min_output_in_asset_units, 0);
+        for request in [deposit(&fixture, 2), redeem(&fixture, 2)] {
+            assert!(!venue.quote(request).unwrap().not_enough_liquidity);
+        }
+    }
+
+    #[test]
+    fn test_threshold_boundary() {
+        let fixture = fixture();
+        let unfiltered = venue(&fixture, 0);
+
+        // A redeem is judged by what it pays out.
+        let (mut lo, mut hi) = (0, 10 * MIN_OUTPUT);
+        while hi - lo > 1 {
+            let mid = lo + (hi - lo) / 2;
+            let output = unfiltered
+                .quote(redeem(&fixture, mid))
+                .unwrap()
+                .expected_output;
+            if output < MIN_OUTPUT {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        let filtered = venue(&fixture, MIN_OUTPUT);
+        assert!(fills(&filtered, &redeem(&fixture, hi)));
+        assert!(!fills(&filtered, &redeem(&fixture, lo)));
+        assert!(!fills(&filtered, &redeem(&fixture, 1)));
+
+        // A deposit by its input after the 10 bps issuance fee.
+        let smallest_deposit = (MIN_OUTPUT * 10_000).div_ceil(9_990);
+        assert!(fills(&filtered, &deposit(&fixture, smallest_deposit)));
+        assert!(!fills(&filtered, &deposit(&fixture, smallest_deposit - 1)));
+        assert!(!fills(&filtered, &deposit(&fixture, MIN_OUTPUT)));
+
+        // Accepted quotes are unchanged by the filter.
+        for request in [deposit(&fixture, 1_000_000), redeem(&fixture, 1_000_000)] {
+            assert_eq!(
+                filtered.quote(request.clone()).unwrap().expected_output,
+                unfiltered.quote(request).unwrap().expected_output
+            );
+        }
+
+        // The native bounds start where the filter lets swaps through.
+        assert_eq!(filtered.bounds(0, 1).unwrap().0, smallest_deposit);
+        assert_eq!(filtered.bounds(1, 0).unwrap().0, hi);
+    }
+
+    #[test]
+    fn test_zero_amount_still_quotes() {
+        let fixture = fixture();
+        let venue = venue(&fixture, MIN_OUTPUT);
+        for request in [deposit(&fixture, 0), redeem(&fixture, 0)] {
+            let quote = venue.quote(request).unwrap();
+            assert_eq!(quote.expected_output, 0);
+            assert!(!quote.not_enough_liquidity);
+        }
+        assert_eq!(
+            venue.rejection_count(LiquidityRejection::BelowMinimumOutput),
+            0
+        );
+    }
+}
+
+#[cfg(all(test, feature = "test-utils"))]
+mod test_mock {
+    //! `MockableVoltrVenue` scripts a venue for router tests:
+    //! - an injected update failure drives a router's retry path,
+    //! - quote overrides replace the vault's pricing but not its instructions,
+    //! - latency delays every refresh.
+
+    use std::time::{Duration, Instant};
+
+    use solana_pubkey::Pubkey;
+    use titan_integration_template::account_caching::AccountsCache;
+    use titan_integration_template::trading_venue::{
+        error::TradingVenueError, QuoteRequest, QuoteResult, SwapType, TradingVenue,
+    };
+
+    use titan_voltr_integration::mock::MockableVoltrVenue;
+    use titan_voltr_integration::voltr_venue::VoltrVaultVenue;
+    use titan_voltr_integration::VOLTR_VAULT_PROGRAM;
+
+    use crate::common::VaultFixture;
+
+    /// A router's refresh step: retry a failing venue up to `attempts` times,
+    /// returning how many attempts it took.
+    async fn refresh_with_retry(
+        venue: &mut dyn TradingVenue,
+        cache: &dyn AccountsCache,
+        attempts: usize,
+    ) -> Result<usize, TradingVenueError> {
+        let mut last_error = None;
+        for attempt in 1..=attempts {
+            match venue.update_state(cache).await {
+                Ok(()) => return Ok(attempt),
+                Err(e) => last_error = Some(e),
+            }
+        }
+        Err(last_error.unwrap())
+    }
+
+    fn deposit(fixture: &VaultFixture) -> QuoteRequest {
+        QuoteRequest {
+            input_mint: fixture.asset_mint,
+            output_mint: fixture.lp_mint(),
+            amount: 1_000_000,
+            swap_type: SwapType::ExactIn,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_router_retries_injected_update_failure() {
+        let fixture = VaultFixture::default();
+        let cache = fixture.cache();
+        let mut venue =
+            MockableVoltrVenue::new(VoltrVaultVenue::new_uninitialized(fixture.vault_key));
+
+        venue.fail_next_update(TradingVenueError::AmmMethodError("rpc timeout".into()));
+        assert_eq!(refresh_with_retry(&mut venue, &cache, 3).await.unwrap(), 2);
+        assert!(venue.initialized());
+        assert!(venue.quote(deposit(&fixture)).unwrap().expected_output > 0);
+
+        venue.fail_next_update(TradingVenueError::AmmMethodError("rpc timeout".into()));
+        assert!(refresh_with_retry(&mut venue, &cache, 1).await.is_err());
+        // The failed refresh left the loaded state usable.
+        assert!(venue.initialized());
+    }
+
+    #[tokio::test]
+    async fn test_quote_override_keeps_real_instructions() {
+        let fixture = VaultFixture::default();
+        let mut venue = MockableVoltrVenue::new(fixture.venue().await);
+        let request = deposit(&fixture);
+        let real = venue.quote(request.clone()).unwrap();
+
+        venue.set_quote_override(|request| QuoteResult {
+            input_mint: request.input_mint,
+            output_mint: request.output_mint,
+            amount: request.amount,
+            expected_output: request.amount * 2,
+            not_enough_liquidity: false,
+        });
+        assert_eq!(
+            venue.quote(request.clone()).unwrap().expected_output,
+            2_000_000
+        );
+
+        let user = Pubkey::new_unique();
+        let instruction = venue
+            .generate_swap_instruction(request.clone(), user)
+            .unwrap();
+        assert_eq!(instruction.program_id, VOLTR_VAULT_PROGRAM);
+        assert_eq!(
+            instruction.data,
+            venue
+                .inner()
+                .generate_swap_instruction(request.clone(), user)
+                .unwrap()
+                .data
+        );
+
+        venue.clear_quote_override();
+        assert_eq!(
+            venue.quote(request).unwrap().expected_output,
+            real.expected_output
+        );
+    }
+
+    #[tokio::test]
+    async fn test_latency_delays_update() {
+        let fixture = VaultFixture::default();
+        let cache = fixture.cache();
+        let mut venue = MockableVoltrVenue::new(fixture.venue().await);
+        venue.set_latency(Duration::from_millis(50));
+
+        let start = Instant::now();
+        venue.update_state(&cache).await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+}
+
+#[cfg(test)]
+mod test_overrides {
+    //! What-if quotes with `quote_with_overrides()`:
+    //! - without overrides the result is exactly that of `quote()`,
+    //! - each override moves the output the way the vault math says it
+    //!   should,
+    //! - the venue itself is left untouched,
+    //! - a pending management fee harvest quotes as the inline estimate,
+    //! - a pending profit report locks value away from instant redeems.
+
+    use titan_integration_template::trading_venue::{
+        QuoteRequest, QuoteResult, SwapType, TradingVenue,
+    };
+
+    use titan_voltr_integration::clock::QuoteClock;
+    use titan_voltr_integration::config::VoltrVenueConfig;
+    use titan_voltr_integration::core::{
+        calc_withdraw_asset_to_redeem, quote_snapshot, SwapDirection,
+    };
+    use titan_voltr_integration::overrides::{PendingCrank, SnapshotOverrides};
+    use titan_voltr_integration::voltr_venue::VoltrVaultVenue;
+
+    use crate::common::VaultFixture;
+
+    const T0: u64 = 1_700_000_000;
+
+    fn fixture() -> VaultFixture {
+        VaultFixture {
+            idle_balance: 100_000_000_000,
+            manager_management_fee: 200,
+            issuance_fee: 10,
+            redemption_fee: 10,
+            last_management_fee_update_ts: T0,
+            locked_profit_degradation_duration: 86_400,
+            last_updated_locked_profit: 10_000_000_000,
+            last_report: T0,
+            last_updated_ts: T0,
+            ..Default::default()
+        }
+    }
+
+    fn venue(fixture: &VaultFixture) -> VoltrVaultVenue {
+        fixture.synthetic_venue().with_config(VoltrVenueConfig {
+            clock: QuoteClock::Fixed(T0 + 3_600),
+            ..Default::default()
+        })
+    }
+
+    fn deposit(fixture: &VaultFixture, amount: u64) -> QuoteRequest {
+        QuoteRequest {
+            input_mint: fixture.asset_mint,
+            output_mint: fixture.lp_mint(),
+            amount,
+            swap_type: SwapType::ExactIn,
+        }
+    }
+
+    fn redeem(fixture: &VaultFixture, amount: u64) -> QuoteRequest {
+        QuoteRequest {
+            input_mint: fixture.lp_mint(),
+            output_mint: fixture.asset_mint,
+            amount,
+            swap_type: SwapType::ExactIn,
+        }
+    }
+
+    fn fields(quote: &QuoteResult) -> (u64, u64, bool) {
+        (
+            quote.amount,
+            quote.expected_output,
+            quote.not_enough_liquidity,
+        )
+    }
+
+    fn output(
+        venue: &VoltrVaultVenue,
+        request: &QuoteRequest,
+        overrides: SnapshotOverrides,
+    ) -> u64 {
+        venue
+            .quote_with_overrides(request, &overrides)
+            .unwrap()
+            .expected_output
+    }
+
+    #[test]
+    fn test_no_overrides_matches_quote() {
+        let fixture = fixture();
+        let venue = venue(&fixture);
+        for request in [
+            deposit(&fixture, 0),
+            deposit(&fixture, 1_000_000_000),
+            redeem(&fixture, 1_000_000_000),
+            // More than idle can pay.
+            redeem(&fixture, 500_000_000_000),
+        ] {
+            assert_eq!(
+                fields(
+                    &venue
+                        .quote_with_overrides(&request, &SnapshotOverrides::default())
+                        .unwrap()
+                ),
+                fields(&venue.quote(request).unwrap())
+            );
+        }
+    }
+
+    #[test]
+    fn test_single_field_overrides() {
+        let fixture = fixture();
+        let venue = venue(&fixture);
+        let large_redeem = redeem(&fixture, 150_000_000_000);
+        let (deposit, redeem) = (
+            deposit(&fixture, 1_000_000_000),
+            redeem(&fixture, 1_000_000_000),
+        );
+        let base = |request| output(&venue, request, SnapshotOverrides::default());
+
+        // Twice the idle balance fills a redeem that idle could not pay.
+        assert!(
+            venue
+                .quote(large_redeem.clone())
+                .unwrap()
+                .not_enough_liquidity
+        );
+        let doubled_idle = SnapshotOverrides {
+            idle_balance: Some(2 * fixture.idle_balance),
+            ..Default::default()
+        };
+        let quote = venue
+            .quote_with_overrides(&large_redeem, &doubled_idle)
+            .unwrap();
+        assert!(!quote.not_enough_liquidity);
+        assert!(quote.expected_output > fixture.idle_balance);
+
+        // A higher issuance fee mints less LP.
+        let higher_issuance = SnapshotOverrides {
+            issuance_fee_bps: Some(100),
+            ..Default::default()
+        };
+        assert!(output(&venue, &deposit, higher_issuance) < base(&deposit));
+
+        // A higher redemption fee pays out less.
+        let higher_redemption = SnapshotOverrides {
+            redemption_fee_bps: Some(100),
+            ..Default::default()
+        };
+        assert!(output(&venue, &redeem, higher_redemption) < base(&redeem));
+
+        // More value per LP: fewer LP per deposit, more asset per redeem.
+        let more_value = SnapshotOverrides {
+            total_value: Some(2 * fixture.total_value),
+            ..Default::default()
+        };
+        assert!(output(&venue, &deposit, more_value) < base(&deposit));
+        assert!(output(&venue, &redeem, more_value) > base(&redeem));
+
+        // More LP for the same value: the opposite.
+        let more_lp = SnapshotOverrides {
+            lp_supply: Some(2 * fixture.lp_supply),
+            ..Default::default()
+        };
+        assert!(output(&venue, &deposit, more_lp) > base(&deposit));
+        assert!(output(&venue, &redeem, more_lp) < base(&redeem));
+
+        // No management fee: no dilution since the last fee update.
+        let no_management_fee = SnapshotOverrides {
+            management_fee_bps: Some(0),
+            ..Default::default()
+        };
+        assert!(output(&venue, &redeem, no_management_fee) > base(&redeem));
+
+        // Quoting a day later unlocks the reported profit.
+        let later = SnapshotOverrides {
+            current_ts: Some(T0 + 86_400),
+            management_fee_bps: Some(0),
+            ..Default::default()
+        };
+        assert!(output(&venue, &redeem, later) > output(&venue, &redeem, no_management_fee));
+        assert_eq!(
+            output(&venue, &redeem, later),
+            output(
+                &venue,
+                &redeem,
+                SnapshotOverrides {
+                    last_report: Some(T0 + 3_600 - 86_400),
+                    management_fee_bps: Some(0),
+                    ..Default::default()
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn test_overrides_leave_venue_untouched() {
+        let fixture = fixture();
+        let venue = venue(&fixture);
+        let request = redeem(&fixture, 1_000_000_000);
+        let before = (
+            venue.vault_snapshot().unwrap(),
+            fields(&venue.quote(request.clone()).unwrap()),
+        );
+
+        venue
+            .quote_with_overrides(
+                &request,
+                &SnapshotOverrides {
+                    idle_balance: Some(0),
+                    total_value: Some(1),
+                    lp_supply: Some(1),
+                    current_ts: Some(0),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            (
+                venue.vault_snapshot().unwrap(),
+                fields(&venue.quote(request).unwrap())
+            ),
+            before
+        );
+    }
+
+    #[test]
+    fn test_management_fee_harvest_matches_inline_estimate() {
+        let fixture = fixture();
+        let venue = venue(&fixture);
+        let ts = T0 + 3_600;
+        let before = venue.vault_snapshot().unwrap();
+        let harvested = venue
+            .apply_pending_crank(PendingCrank::ManagementFeeHarvest { ts })
+            .unwrap();
+        assert!(before.pending_management_fee_lp(ts).unwrap() > 0);
+        assert_eq!(
+            harvested.accumulated_lp_fees,
+            before.accumulated_lp_fees + before.pending_management_fee_lp(ts).unwrap()
+        );
+        assert_eq!(harvested.last_management_fee_update_ts, ts);
+        assert_eq!(harvested.pending_management_fee_lp(ts).unwrap(), 0);
+
+        for (direction, request) in [
+            (SwapDirection::Deposit, deposit(&fixture, 1_000_000_000)),
+            (SwapDirection::Redeem, redeem(&fixture, 1_000_000_000)),
+        ] {
+            let (output, rejection) =
+                quote_snapshot(&harvested, direction, request.amount, ts).unwrap();
+            assert_eq!(rejection, None);
+            assert_eq!(output, venue.quote(request).unwrap().expected_output);
+        }
+    }
+
+    #[test]
+    fn test_profit_report_locks_redeem_payout() {
+        let fixture = VaultFixture {
+            locked_profit_degradation_duration: 86_400,
+            ..Default::default()
+        };
+        let venue = venue(&fixture);
+        let ts = T0 + 3_600;
+        let amount = 1_000_000_000;
+        let locked_profit = fixture.total_value / 10;
+        let reported = venue
+            .apply_pending_crank(PendingCrank::ProfitReport { locked_profit, ts })
+            .unwrap();
+        let supply = reported.total_lp_supply_incl_fees().unwrap();
+        let payout = |at| {
+            let (output, rejection) =
+                quote_snapshot(&reported, SwapDirection::Redeem, amount, at).unwrap();
+            assert_eq!(rejection, None);
+            output
+        };
+
+        // All of the reported profit is locked at first, half of it half
+        // the degradation window later, none at its end.
+        for (at, locked) in [
+            (ts, locked_profit),
+            (ts + 43_200, locked_profit / 2),
+            (ts + 86_400, 0),
+        ] {
+            assert_eq!(
+                payout(at),
+                calc_withdraw_asset_to_redeem(amount, supply, fixture.total_value - locked, 0)
+                    .unwrap()
+            );
+        }
+        let unreported = venue
+            .quote(redeem(&fixture, amount))
+            .unwrap()
+            .expected_output;
+        assert!(payout(ts) < unreported);
+        assert_eq!(payout(ts + 86_400), unreported);
+    }
+}
+
+#[cfg(test)]
+mod test_pair_direction {
+    //! Mint pair membership without quoting:
+    //! - `pair_direction()` resolves both directions and nothing else, and
+    //!   agrees with which requests `quote()` accepts, save self-pairs of a
+    //!   vault mint, quoted as swapping for nothing,
+    //! - a venue whose vault is not loaded serves no mint,
+    //! - a check costs well under a microsecond.
+
+    use std::time::Instant;
+
+    use solana_pubkey::Pubkey;
+    use titan_integration_template::trading_venue::{QuoteRequest, SwapType, TradingVenue};
+
+    use titan_voltr_integration::voltr_venue::{SwapDirection, VoltrVaultVenue};
+
+    use crate::common::VaultFixture;
+
+    #[test]
+    fn test_pair_direction_matches_quote() {
+        let fixture = VaultFixture::default();
+        let venue = fixture.synthetic_venue();
+        let (asset, lp, other) = (fixture.asset_mint, fixture.lp_mint(), Pubkey::new_unique());
+
+        for (input, output, direction) in [
+            (asset, lp, Some(SwapDirection::Deposit)),
+            (lp, asset, Some(SwapDirection::Redeem)),
+            (asset, asset, None),
+            (lp, lp, None),
+            (asset, other, None),
+            (other, lp, None),
+            (other, other, None),
+        ] {
+            assert_eq!(venue.pair_direction(&input, &output), direction);
+            assert_eq!(venue.serves_pair(&input, &output), direction.is_some());
+            let quoted = venue.quote(QuoteRequest {
+                input_mint: input,
+                output_mint: output,
+                amount: 1_000_000,
+                swap_type: SwapType::ExactIn,
+            });
+            let self_pair = input == output && input != other;
+            assert_eq!(quoted.is_ok(), direction.is_some() || self_pair);
+            if self_pair {
+                let quoted = quoted.unwrap();
+                assert_eq!(quoted.expected_output, 0);
+                assert!(!quoted.not_enough_liquidity);
+            }
+        }
+        assert!(venue.serves_mint(&asset));
+        assert!(venue.serves_mint(&lp));
+        assert!(!venue.serves_mint(&other));
+    }
+
+    #[test]
+    fn test_unloaded_venue_serves_nothing() {
+        let venue = VoltrVaultVenue::new_uninitialized(Pubkey::new_unique());
+        let unset = Pubkey::default();
+        assert!(!venue.serves_mint(&unset));
+        assert_eq!(venue.pair_direction(&unset, &unset), None);
+    }
+
+    #[test]
+    fn test_membership_speed() {
+        let fixture = VaultFixture::default();
+        let venue = fixture.synthetic_venue();
+        let mints: Vec<Pubkey> = (0..400)
+            .map(|i| match i % 4 {
+                0 => fixture.asset_mint,
+                1 => fixture.lp_mint(),
+                _ => Pubkey::new_unique(),
+            })
+            .collect();
+
+        let start = Instant::now();
+        let mut served = 0;
+        for input in &mints {
+            for output in &mints {
+                served += venue.serves_pair(input, output) as usize;
+            }
+        }
+        let avg_time = start.elapsed().as_secs_f64() / (mints.len() * mints.len()) as f64;
+        log::info!("Average pair check: {}", avg_time);
+
+        // Each of the 100 asset inputs pairs with the 100 LP outputs, and
+        // the other way around.
+        assert_eq!(served, 2 * 100 * 100);
+        assert!(avg_time < 0.000_001, "pair check took {avg_time}s");
+    }
+}
+
+#[cfg(test)]
+mod test_policy {
+    //! `PolicyVenue` consults its policy before quoting or building a swap:
+    //! - allowed requests quote and trade as the wrapped venue does,
+    //! - a vault whose asset is not allowlisted is denied,
+    //! - fees above the cap deny only the direction that pays them,
+    //! - denials are `PolicyVenueError::Denied`, distinguishable from
+    //!   liquidity rejections and the wrapped venue's errors.
+
+    use std::collections::HashSet;
+
+    use solana_pubkey::Pubkey;
+    use titan_integration_template::trading_venue::{QuoteRequest, SwapType, TradingVenue};
+
+    use titan_voltr_integration::policy::{
+        MaxFeeBps, MintAllowlist, PolicyDecision, PolicyVenue, PolicyVenueError, QuotePolicy,
+    };
+
+    use crate::common::VaultFixture;
+
+    fn requests(fixture: &VaultFixture) -> [QuoteRequest; 2] {
+        [
+            QuoteRequest {
+                input_mint: fixture.asset_mint,
+                output_mint: fixture.lp_mint(),
+                amount: 1_000_000,
+                swap_type: SwapType::ExactIn,
+            },
+            QuoteRequest {
+                input_mint: fixture.lp_mint(),
+                output_mint: fixture.asset_mint,
+                amount: 1_000_000,
+                swap_type: SwapType::ExactIn,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_allowlisted_mint_quotes_like_the_venue() {
+        let fixture = VaultFixture::default();
+        let venue = PolicyVenue::new(
+            fixture.synthetic_venue(),
+            MintAllowlist(HashSet::from([fixture.asset_mint])),
+        );
+        for request in requests(&fixture) {
+            assert_eq!(
+                venue.quote(request.clone()).unwrap().expected_output,
+                venue
+                    .inner()
+                    .quote(request.clone())
+                    .unwrap()
+                    .expected_output
+            );
+            assert!(
+                venue
+                    .generate_swap_instruction(request, Pubkey::new_unique())
+                    .is_ok()
+            );
+        }
+    }
+
+    #[test]
+    fn test_mint_not_allowlisted_is_denied() {
+        let fixture = VaultFixture::default();
+        let venue = PolicyVenue::new(
+            fixture.synthetic_venue(),
+            MintAllowlist(HashSet::from([Pubkey::new_unique()])),
+        );
+        for request in requests(&fixture) {
+            let err = venue.try_quote(request.clone()).unwrap_err();
+            assert!(err.is_denial(), "{err}");
+            assert!(err.to_string().contains(&fixture.asset_mint.to_string()));
+            let err = venue
+                .try_generate_swap_instruction(request.clone(), Pubkey::new_unique())
+                .unwrap_err();
+            assert!(err.is_denial());
+            // Through `TradingVenue` the reason is kept in the message.
+            let err = venue.quote(request).unwrap_err();
+            assert!(err.to_string().contains(&fixture.asset_mint.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_fee_cap_denies_the_paying_direction() {
+        let fixture = VaultFixture {
+            issuance_fee: 50,
+            redemption_fee: 10,
+            ..Default::default()
+        };
+        let policy = MaxFeeBps {
+            issuance: 20,
+            redemption: 10,
+        };
+        let [deposit, redeem] = requests(&fixture);
+        assert!(matches!(
+            policy.allow(&fixture.synthetic_venue(), &deposit),
+            PolicyDecision::Deny(reason) if reason.contains("issuance fee 50 bps")
+        ));
+
+        let venue = PolicyVenue::new(fixture.synthetic_venue(), policy);
+        assert!(venue.try_quote(deposit).unwrap_err().is_denial());
+        assert!(venue.quote(redeem).unwrap().expected_output > 0);
+    }
+
+    #[test]
+    fn test_liquidity_rejection_is_not_a_denial() {
+        let fixture = VaultFixture {
+            idle_balance: 0,
+            ..Default::default()
+        };
+        let venue = PolicyVenue::new(
+            fixture.synthetic_venue(),
+            MaxFeeBps {
+                issuance: 0,
+                redemption: 0,
+            },
+        );
+        let [deposit, redeem] = requests(&fixture);
+        assert!(venue.quote(redeem).unwrap().not_enough_liquidity);
+
+        // Nor is a venue error, whatever its message.
+        let unserved = QuoteRequest {
+            input_mint: Pubkey::new_unique(),
+            ..deposit
+        };
+        let err = venue.try_quote(unserved).unwrap_err();
+        assert!(matches!(err, PolicyVenueError::Venue(_)), "{err}");
+        assert!(!err.is_denial());
+    }
+}
+
+#[cfg(test)]
+mod test_preconditions {
+    //! Precondition checks for the user side of a swap:
+    //! - the required ATAs are reported for both directions,
+    //! - missing accounts and short balances are flagged before funding,
+    //! - a funded user passes the check.
+
+    use solana_pubkey::Pubkey;
+    use titan_integration_template::trading_venue::{QuoteRequest, SwapType};
+
+    use titan_voltr_integration::constants::TOKEN_PROGRAM;
+    use titan_voltr_integration::preconditions::{PreconditionFailure, UserAccountRole};
+    use titan_voltr_integration::synthetic::token_account;
+    use titan_voltr_integration::voltr_venue::SwapDirection;
+
+    use crate::common::VaultFixture;
+
+    #[tokio::test]
+    async fn test_required_user_accounts() {
+        let fixture = VaultFixture::default();
+        let venue = fixture.venue().await;
+        let user = Pubkey::new_unique();
+
+        let deposit = venue.required_user_accounts(&user, SwapDirection::Deposit, 500);
+        assert_eq!(deposit.len(), 2);
+        assert_eq!(deposit[0].role, UserAccountRole::SourceAssetAta);
+        assert_eq!(deposit[0].mint, fixture.asset_mint);
+        assert_eq!(deposit[0].min_balance, 500);
+        assert_eq!(deposit[1].role, UserAccountRole::DestinationLpAta);
+        assert_eq!(deposit[1].mint, fixture.lp_mint());
+        assert_eq!(deposit[1].min_balance, 0);
+
+        let redeem = venue.required_user_accounts(&user, SwapDirection::Redeem, 500);
+        assert_eq!(redeem[0].role, UserAccountRole::SourceLpAta);
+        assert_eq!(redeem[0].pubkey, deposit[1].pubkey);
+        assert_eq!(redeem[1].role, UserAccountRole::DestinationAssetAta);
+        assert_eq!(redeem[1].pubkey, deposit[0].pubkey);
+
+        assert!(deposit.iter().chain(&redeem).all(|a| !a.created_if_missing));
+    }
+
+    #[tokio::test]
+    async fn test_check_preconditions_before_and_after_funding() {
+        let fixture = VaultFixture::default();
+        let venue = fixture.venue().await;
+        let user = Pubkey::new_unique();
+
+        let request = QuoteRequest {
+            input_mint: fixture.asset_mint,
+            output_mint: fixture.lp_mint(),
+            amount: 1_000_000,
+            swap_type: SwapType::ExactIn,
+        };
+        let required = venue.required_user_accounts(&user, SwapDirection::Deposit, request.amount);
+        let (source, destination) = (required[0].pubkey, required[1].pubkey);
+
+        // Nothing exists yet.
+        let mut cache = fixture.cache();
+        let failures = venue
+            .check_preconditions(&user, &request, &cache)
+            .await
+            .unwrap();
+        assert_eq!(
+            failures,
+            vec![
+                PreconditionFailure::MissingAccount {
+                    pubkey: source,
+                    role: UserAccountRole::SourceAssetAta,
+                },
+                PreconditionFailure::MissingAccount {
+                    pubkey: destination,
+                    role: UserAccountRole::DestinationLpAta,
+                },
+            ]
+        );
+
+        // ATAs exist but the source is underfunded.
+        cache.insert(
+            source,
+            token_account(&fixture.asset_mint, &user, &TOKEN_PROGRAM, 999_999),
+        );
+        cache.insert(
+            destination,
+            token_account(&fixture.lp_mint(), &user, &TOKEN_PROGRAM, 0),
+        );
+        let failures = venue
+            .check_preconditions(&user, &request, &cache)
+            .await
+            .unwrap();
+        assert_eq!(
+            failures,
+            vec![PreconditionFailure::InsufficientBalance {
+                pubkey: source,
+                role: UserAccountRole::SourceAssetAta,
+                required: 1_000_000,
+                available: 999_999,
+            }]
+        );
+
+        // Funded user passes.
+        cache.insert(
+            source,
+            token_account(&fixture.asset_mint, &user, &TOKEN_PROGRAM, 1_000_000),
+        );
+        let failures = venue
+            .check_preconditions(&user, &request, &cache)
+            .await
+            .unwrap();
+        assert!(failures.is_empty());
+
+        // An account for the wrong mint is rejected.
+        cache.insert(
+            destination,
+            token_account(&fixture.asset_mint, &user, &TOKEN_PROGRAM, 0),
+        );
+        let failures = venue
+            .check_preconditions(&user, &request, &cache)
+            .await
+            .unwrap();
+        assert_eq!(
+            failures,
+            vec![PreconditionFailure::InvalidAccount {
+                pubkey: destination,
+                role: UserAccountRole::DestinationLpAta,
+            }]
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_preflight {
+    //! `preflight()` over loaded venues:
+    //! - a venue loaded from a captured fixture passes every check,
+    //! - an idle account that is not the idle authority's ATA fails the
+    //!   PDA check,
+    //! - a vault full to its cap only warns, on its bounds,
+    //! - a venue never loaded is refused.
+
+    use titan_voltr_integration::clock::QuoteClock;
+    use titan_voltr_integration::config::VoltrVenueConfig;
+    use titan_voltr_integration::preflight::{PreflightCheck, PreflightOutcome};
+    use titan_voltr_integration::voltr_venue::VoltrVaultVenue;
+
+    use crate::common::VaultFixture;
+
+    #[cfg(feature = "replay")]
+    #[tokio::test]
+    async fn test_captured_fixture_passes() {
+        use titan_voltr_integration::fixture::FixtureCache;
+        use titan_voltr_integration::replay::ReplaySnapshot;
+
+        let fixture = VaultFixture {
+            manager_management_fee: 200,
+            issuance_fee: 10,
+            redemption_fee: 10,
+            ..Default::default()
+        }
+        .for_program();
+        let cache = FixtureCache::new(
+            fixture.vault_key,
+            ReplaySnapshot {
+                slot: 300_000_000,
+                timestamp: fixture.last_updated_ts + 3_600,
+                accounts: fixture.accounts().into_iter().collect(),
+            },
+        );
+        let venue = cache.venue().await.unwrap();
+
+        let report = venue.preflight(&cache).await.unwrap();
+        let checks: Vec<PreflightCheck> = report.checks.iter().map(|c| c.check).collect();
+        assert_eq!(
+            checks,
+            [
+                PreflightCheck::Health,
+                PreflightCheck::Pdas,
+                PreflightCheck::Decimals,
+                PreflightCheck::Fees,
+                PreflightCheck::Bounds,
+                PreflightCheck::QuoteAtBounds,
+                PreflightCheck::Instructions,
+                PreflightCheck::LookupTable,
+            ]
+        );
+        for check in &report.checks {
+            assert_eq!(check.outcome, PreflightOutcome::Pass, "{check:?}");
+        }
+        assert!(report.passed());
+        assert!(report
+            .checks
+            .iter()
+            .all(|c| c.elapsed <= report.total_elapsed()));
+    }
+
+    #[tokio::test]
+    async fn test_foreign_idle_account_fails() {
+        let fixture = VaultFixture::default();
+        let venue = fixture.venue().await;
+
+        let report = venue.preflight(&fixture.cache()).await.unwrap();
+        assert!(!report.passed());
+        let pdas = report.check(PreflightCheck::Pdas).unwrap();
+        let PreflightOutcome::Fail(message) = &pdas.outcome else {
+            panic!("expected the PDA check to fail, got {pdas:?}");
+        };
+        assert!(message.contains(&fixture.idle_ata.to_string()));
+        assert!(report.failures().any(|c| c.check == PreflightCheck::Pdas));
+    }
+
+    #[tokio::test]
+    async fn test_full_vault_warns() {
+        let mut fixture = VaultFixture::default().for_program();
+        fixture.max_cap = fixture.total_value;
+        let venue = fixture.venue().await.with_config(VoltrVenueConfig {
+            clock: QuoteClock::Fixed(fixture.last_updated_ts),
+            ..Default::default()
+        });
+
+        let report = venue.preflight(&fixture.cache()).await.unwrap();
+        assert!(report.passed(), "{report:?}");
+        let warnings: Vec<PreflightCheck> = report.warnings().map(|c| c.check).collect();
+        assert_eq!(warnings, [PreflightCheck::Bounds]);
+    }
+
+    #[tokio::test]
+    async fn test_unloaded_venue_refused() {
+        let fixture = VaultFixture::default();
+        let venue = VoltrVaultVenue::new_uninitialized(fixture.vault_key);
+        assert!(venue.preflight(&fixture.cache()).await.is_err());
+    }
+}
+
+#[cfg(test)]
+mod test_rejections {
+    //! Quotes refused for liquidity are all counted but warned about at most
+    //! once per reason per interval, without changing the quotes themselves.
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use log::{Level, Log, Metadata, Record};
+    use titan_integration_template::trading_venue::{QuoteRequest, SwapType, TradingVenue};
+
+    use titan_voltr_integration::clock::QuoteClock;
+    use titan_voltr_integration::config::VoltrVenueConfig;
+    use titan_voltr_integration::voltr_venue::LiquidityRejection;
+
+    use crate::common::VaultFixture;
+
+    const T0: u64 = 1_700_000_000;
+    const INTERVAL: u64 = 60;
+    const REJECTIONS: u64 = 10_000;
+
+    /// Counts the warnings the venue logs.
+    struct WarningCounter(AtomicUsize);
+
+    impl Log for WarningCounter {
+        fn enabled(&self, metadata: &Metadata) -> bool {
+            metadata.level() <= Level::Warn
+        }
+
+        fn log(&self, record: &Record) {
+            if record.level() == Level::Warn
+                && record.args().to_string().contains("is refusing quotes")
+            {
+                self.0.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    static WARNINGS: WarningCounter = WarningCounter(AtomicUsize::new(0));
+
+    fn warnings() -> usize {
+        WARNINGS.0.load(Ordering::Relaxed)
+    }
+
+    #[test]
+    fn test_rejection_warnings_are_rate_limited() {
+        log::set_logger(&WARNINGS).unwrap();
+        log::set_max_level(log::LevelFilter::Warn);
+
+        // Capped at its current value, with a tenth of it idle.
+        let fixture = VaultFixture {
+            max_cap: 1_000_000_000_000,
+            idle_balance: 100_000_000_000,
+            ..Default::default()
+        };
+        let mut venue = fixture.synthetic_venue().with_config(VoltrVenueConfig {
+            rejection_warning_interval_secs: Some(INTERVAL),
+            clock: QuoteClock::Fixed(T0),
+            ..Default::default()
+        });
+        let deposit = QuoteRequest {
+            input_mint: fixture.asset_mint,
+            output_mint: fixture.lp_mint(),
+            amount: 1_000_000,
+            swap_type: SwapType::ExactIn,
+        };
+        let redeem = QuoteRequest {
+            input_mint: fixture.lp_mint(),
+            output_mint: fixture.asset_mint,
+            amount: fixture.lp_supply / 2,
+            swap_type: SwapType::ExactIn,
+        };
+
+        let first = venue.quote(deposit.clone()).unwrap();
+        assert!(first.not_enough_liquidity);
+        for _ in 1..REJECTIONS {
+            let quote = venue.quote(deposit.clone()).unwrap();
+            assert!(quote.not_enough_liquidity);
+            assert_eq!(quote.expected_output, first.expected_output);
+        }
+        assert_eq!(
+            venue.rejection_count(LiquidityRejection::CapExceeded),
+            REJECTIONS
+        );
+        assert_eq!(warnings(), 1);
+
+        // Another reason warns on its own.
+        for _ in 0..REJECTIONS {
+            assert!(venue.quote(redeem.clone()).unwrap().not_enough_liquidity);
+        }
+        assert_eq!(
+            venue.rejection_count(LiquidityRejection::IdleBalanceInsufficient),
+            REJECTIONS
+        );
+        assert_eq!(warnings(), 2);
+
+        // Just before the interval ends nothing more is logged; once it has,
+        // one more warning per reason.
+        venue.set_clock(QuoteClock::Fixed(T0 + INTERVAL - 1));
+        venue.quote(deposit.clone()).unwrap();
+        assert_eq!(warnings(), 2);
+        venue.set_clock(QuoteClock::Fixed(T0 + INTERVAL));
+        for _ in 0..REJECTIONS {
+            venue.quote(deposit.clone()).unwrap();
+            venue.quote(redeem.clone()).unwrap();
+        }
+        assert_eq!(warnings(), 4);
+        assert_eq!(
+            venue.rejection_count(LiquidityRejection::CapExceeded),
+            2 * REJECTIONS + 1
+        );
+
+        // Without an interval refusals are only counted.
+        let silent = fixture.synthetic_venue();
+        for _ in 0..REJECTIONS {
+            silent.quote(deposit.clone()).unwrap();
+        }
+        assert_eq!(
+            silent.rejection_count(LiquidityRejection::CapExceeded),
+            REJECTIONS
+        );
+        assert_eq!(warnings(), 4);
+    }
+}
+
+#[cfg(test)]
+mod test_router {
+    //! Choosing between several vaults for the same asset:
+    //! - the cheapest vault with capacity wins a deposit,
+    //! - vaults at capacity, for other assets or failing to quote are skipped,
+    //! - redeems compare asset payouts for per-vault LP amounts,
+    //! - ties go to the lowest vault pubkey.
+
+    use solana_pubkey::Pubkey;
+
+    use titan_voltr_integration::router::{best_deposit_venue, best_redeem_venue};
+    use titan_voltr_integration::voltr_venue::VoltrVaultVenue;
+
+    use crate::common::VaultFixture;
+
+    fn fixture(asset_mint: Pubkey) -> VaultFixture {
+        VaultFixture {
+            asset_mint,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_best_deposit_venue() {
+        let usdc = Pubkey::new_unique();
+        let expensive = VaultFixture {
+            issuance_fee: 50,
+            ..fixture(usdc)
+        };
+        let cheap = VaultFixture {
+            issuance_fee: 10,
+            ..fixture(usdc)
+        };
+        let at_capacity = VaultFixture {
+            max_cap: 1_000_000_000_000,
+            ..fixture(usdc)
+        };
+        let other_asset = VaultFixture::default();
+
+        let venues = vec![
+            expensive.synthetic_venue(),
+            at_capacity.synthetic_venue(),
+            other_asset.synthetic_venue(),
+            cheap.synthetic_venue(),
+            // Never refreshed: quoting fails and the venue is skipped.
+            VoltrVaultVenue::new_uninitialized(Pubkey::new_unique()),
+        ];
+
+        let (venue, quote) = best_deposit_venue(&venues, usdc, 1_000_000).unwrap();
+        assert_eq!(venue.vault_key, cheap.vault_key);
+        assert!(!quote.not_enough_liquidity);
+        assert!(quote.expected_output > 0);
+
+        // With the cheap vault gone the fee-free one is still at capacity.
+        let (venue, _) = best_deposit_venue(&venues[..3], usdc, 1_000_000).unwrap();
+        assert_eq!(venue.vault_key, expensive.vault_key);
+
+        assert!(best_deposit_venue(&venues[1..3], usdc, 1_000_000).is_none());
+        assert!(best_deposit_venue(&venues, Pubkey::new_unique(), 1_000_000).is_none());
+    }
+
+    #[test]
+    fn test_best_redeem_venue() {
+        let usdc = Pubkey::new_unique();
+        let low_fee = VaultFixture {
+            redemption_fee: 5,
+            ..fixture(usdc)
+        };
+        let high_fee = VaultFixture {
+            redemption_fee: 30,
+            ..fixture(usdc)
+        };
+        let illiquid = VaultFixture {
+            idle_balance: 100,
+            ..fixture(usdc)
+        };
+
+        let venues = vec![
+            high_fee.synthetic_venue(),
+            illiquid.synthetic_venue(),
+            low_fee.synthetic_venue(),
+        ];
+
+        let (venue, quote) =
+            best_redeem_venue(&venues, usdc, &[1_000_000, 1_000_000, 1_000_000]).unwrap();
+        assert_eq!(venue.vault_key, low_fee.vault_key);
+        assert!(!quote.not_enough_liquidity);
+
+        // A larger position in the high-fee vault pays out more.
+        let (venue, _) =
+            best_redeem_venue(&venues, usdc, &[2_000_000, 1_000_000, 1_000_000]).unwrap();
+        assert_eq!(venue.vault_key, high_fee.vault_key);
+
+        // Venues without an LP amount are ignored.
+        let (venue, _) = best_redeem_venue(&venues, usdc, &[1_000_000]).unwrap();
+        assert_eq!(venue.vault_key, high_fee.vault_key);
+    }
+
+    #[test]
+    fn test_ties_break_by_lowest_vault_pubkey() {
+        let usdc = Pubkey::new_unique();
+        let (a, b) = (fixture(usdc), fixture(usdc));
+        let lowest = a.vault_key.min(b.vault_key);
+
+        let venues = vec![a.synthetic_venue(), b.synthetic_venue()];
+        let reversed = vec![b.synthetic_venue(), a.synthetic_venue()];
+
+        for venues in [&venues, &reversed] {
+            let (venue, _) = best_deposit_venue(venues, usdc, 1_000_000).unwrap();
+            assert_eq!(venue.vault_key, lowest);
+            let (venue, _) = best_redeem_venue(venues, usdc, &[1_000_000; 2]).unwrap();
+            assert_eq!(venue.vault_key, lowest);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_swap_type {
+    //! Every `SwapType` is handled explicitly:
+    //! - `ExactIn` quotes and builds swaps,
+    //! - any other variant fails with `UnsupportedSwapType` in quoting and
+    //!   instruction generation, never falling back to `ExactIn`.
+
+    use rstest::rstest;
+    use solana_pubkey::Pubkey;
+    use titan_integration_template::trading_venue::{QuoteRequest, SwapType, TradingVenue};
+
+    use titan_voltr_integration::transaction::TxOptions;
+    use titan_voltr_integration::VoltrError;
+
+    use crate::common::VaultFixture;
+
+    fn request(fixture: &VaultFixture, deposit: bool, swap_type: SwapType) -> QuoteRequest {
+        let (input_mint, output_mint) = if deposit {
+            (fixture.asset_mint, fixture.lp_mint())
+        } else {
+            (fixture.lp_mint(), fixture.asset_mint)
+        };
+        QuoteRequest {
+            input_mint,
+            output_mint,
+            amount: 1_000_000,
+            swap_type,
+        }
+    }
+
+    #[rstest]
+    #[case(true)]
+    #[case(false)]
+    fn test_exact_in_is_supported(#[case] deposit: bool) {
+        let fixture = VaultFixture::default();
+        let venue = fixture.synthetic_venue();
+        let request = request(&fixture, deposit, SwapType::ExactIn);
+
+        assert!(venue.quote(request.clone()).unwrap().expected_output > 0);
+        assert!(
+            venue
+                .generate_swap_instruction(request, Pubkey::new_unique())
+                .is_ok()
+        );
+    }
+
+    #[rstest]
+    #[case(true)]
+    #[case(false)]
+    fn test_exact_out_is_rejected(#[case] deposit: bool) {
+        let fixture = VaultFixture::default();
+        let venue = fixture.synthetic_venue();
+        let request = request(&fixture, deposit, SwapType::ExactOut);
+        let unsupported = VoltrError::UnsupportedSwapType.to_string();
+        let user = Pubkey::new_unique();
+
+        let errors = [
+            venue.quote(request.clone()).unwrap_err(),
+            venue.quote_at(&request, 0).unwrap_err(),
+            venue
+                .generate_swap_instruction(request.clone(), user)
+                .unwrap_err(),
+            venue
+                .build_transaction_instructions(request.clone(), user, &TxOptions::default())
+                .unwrap_err(),
+            venue
+                .generate_swap_instructions_batch(&[(request, user)])
+                .err()
+                .unwrap(),
+        ];
+        for err in errors {
+            assert!(err.to_string().contains(&unsupported), "{err}");
+        }
+    }
+}
@@ -0,0 +1,68 @@
+mod mainnet;
+
+#[cfg(test)]
+mod test_verification {
+    //! RPC-simulation quote verification against a live vault.
+    //!
+    //! Needs `SOLANA_RPC_URL`. Set `VOLTR_CANARY_USER` to a wallet holding
+    //! the vault asset to also check the quote matches the simulated deposit
+    //! exactly.
+
+    use std::{env, str::FromStr};
+
+    use rstest::rstest;
+    use solana_client::nonblocking::rpc_client::RpcClient;
+    use solana_pubkey::Pubkey;
+    use titan_integration_template::account_caching::rpc_cache::RpcClientCache;
+    use titan_integration_template::trading_venue::{
+        FromAccount, QuoteRequest, SwapType, TradingVenue,
+    };
+
+    use titan_voltr_integration::verification::verify_quote_via_rpc;
+    use titan_voltr_integration::voltr_venue::VoltrVaultVenue;
+
+    use crate::mainnet;
+
+    #[rstest]
+    #[tokio::test]
+    #[case("GqoypwVGG35JSR1AwCm2jeqJPUPvA4cWE45rSbfxHgdK")]
+    async fn test_verify_quote_via_rpc(#[case] vault_key: Pubkey) {
+        let rpc_url =
+            env::var("SOLANA_RPC_URL").expect("SOLANA_RPC_URL must be set for integration tests");
+
+        let rpc = RpcClient::new(rpc_url.clone());
+        let Some(vault_account) = mainnet::fetch_vault(&rpc, &vault_key).await else {
+            return;
+        };
+        let mut venue = VoltrVaultVenue::from_account(&vault_key, &vault_account).unwrap();
+        venue
+            .update_state(&RpcClientCache::new(RpcClient::new(rpc_url)))
+            .await
+            .unwrap();
+
+        let (lower, _) = venue.bounds(0, 1).unwrap();
+        let request = QuoteRequest {
+            input_mint: venue.get_token(0).unwrap().pubkey,
+            output_mint: venue.get_token(1).unwrap().pubkey,
+            amount: lower,
+            swap_type: SwapType::ExactIn,
+        };
+
+        // An unfunded user cannot deposit; the failure lands in the report.
+        let report = verify_quote_via_rpc(&venue, request.clone(), Pubkey::new_unique(), &rpc)
+            .await
+            .expect("Verification should report simulation failures");
+        assert!(report.error.is_some());
+        assert!(report.simulated_output.is_none());
+        assert!(!report.is_exact());
+
+        if let Ok(user) = env::var("VOLTR_CANARY_USER") {
+            let user = Pubkey::from_str(&user).expect("Invalid VOLTR_CANARY_USER");
+            let report = verify_quote_via_rpc(&venue, request, user, &rpc)
+                .await
+                .unwrap();
+            log::info!("Verification report: {:#?}", report);
+            assert!(report.is_exact(), "Quote diverged from simulation: {:#?}", report);
+        }
+    }
+}